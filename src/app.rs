@@ -1,29 +1,77 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::mpsc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use ignore::gitignore::Gitignore;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Line;
 use regex::Regex;
-use syntect::highlighting::{FontStyle, Theme as SyntectTheme, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{
+    FontStyle, HighlightState, Highlighter, Theme as SyntectTheme, ThemeSet,
+};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
 
+use crate::collab::CollabSession;
+use crate::hooks::Invocation;
+use crate::model::activity::Activities;
+use crate::model::bibtex::{self, Bibliography};
 use crate::model::buffer::Buffer;
+use crate::model::code_langs::CodeLangIndex;
 use crate::model::config::AppConfig;
-use crate::model::file_tree::FileTree;
+use crate::model::embed::EmbedCache;
+use crate::model::file_tree::{FileTree, Grouping};
+use crate::model::graph::{GraphRow, LinkGraph};
+use crate::model::journal;
+use crate::model::kanban::Column;
+use crate::model::link;
+use crate::model::link_index::LinkIndex;
+use crate::model::metadata::MetadataIndex;
+use crate::model::minimap::Minimap;
 use crate::model::mode::Mode;
+use crate::model::note_index::NoteIndex;
+use crate::model::outline::Heading;
+use crate::model::pane::Split;
+use crate::model::picker::Completion;
+use crate::model::pins;
+use crate::model::quickfix::QuickfixList;
+use crate::model::recent::{self, RecentFiles};
+use crate::model::scratch;
+use crate::model::search_index::SearchIndex;
+use crate::model::spell::SpellChecker;
+use crate::model::yank_history::YankHistory;
 use crate::msg::Msg;
 use crate::plugin::PluginManager;
+use crate::plugin::installer::HeldUpdate;
+use crate::plugin::manifest::PluginId;
+use crate::plugin::registry::RegistryEntry;
+use crate::plugin::runtime::{CompletionItem, Decoration};
+use crate::profile::StartupProfile;
+use crate::sync::{SyncBackend, SyncStatus};
+use crate::update::note_ops::RenamePlan;
+use crate::watcher::{self, FileWatcher};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum FinderMode {
     Files,
     Content,
+    /// Lines of one section of the active buffer (`Space s`).
+    Section,
+    /// Notes without links in or out (`:orphans`).
+    Orphans,
+    /// Notes opened lately, newest first (`:recent`).
+    Recent,
+    /// Links that resolve to nothing, one result per link (`:links check`).
+    BrokenLinks,
+    /// Notes left unchanged for a while, oldest first (`:stale`).
+    Stale,
+    /// Notes an ambiguous wikilink could mean, to pick one to follow.
+    LinkChoices,
 }
 
 #[derive(Debug, Clone)]
@@ -38,14 +86,240 @@ pub(crate) struct BacklinkEntry {
     pub(crate) path: PathBuf,
     pub(crate) line: usize,
     pub(crate) preview: String,
+    /// The `#Heading` or `^block` the link points at, if any.
+    pub(crate) anchor: Option<String>,
+}
+
+/// A change to notes on disk waiting for `y` in [`Mode::Confirm`].
+pub(crate) struct PendingConfirm {
+    pub(crate) prompt: String,
+    pub(crate) action: ConfirmAction,
+}
+
+pub(crate) enum ConfirmAction {
+    Rename(RenamePlan),
+    /// Append `from` to `to`, rewrite links to it and trash it.
+    Merge(RenamePlan),
+    Delete(PathBuf),
+    /// Move the `:bulk` targets into this vault folder.
+    MoveNotes(String),
+    /// Add the plugin cloned from this repo to the config and load it;
+    /// `n` deletes the clone.
+    InstallPlugin(String),
+    /// Fast-forward the plugin cloned from this repo onto an update that
+    /// asks for more access.
+    UpdatePlugin(HeldUpdate),
+}
+
+/// `K` popup: the start of a linked note, or the heading/block it anchors.
+#[derive(Debug, Clone)]
+pub(crate) struct LinkPreview {
+    pub(crate) title: String,
+    pub(crate) path: PathBuf,
+    /// Row in the target note where `lines` start, for opening it there.
+    pub(crate) line: usize,
+    pub(crate) lines: Vec<String>,
+    pub(crate) scroll: usize,
+}
+
+/// `:graph` popup: notes within `depth` links of `centre`.
+#[derive(Debug)]
+pub(crate) struct GraphView {
+    pub(crate) links: LinkGraph,
+    pub(crate) centre: PathBuf,
+    pub(crate) depth: usize,
+    pub(crate) rows: Vec<GraphRow>,
+    pub(crate) selected: usize,
+}
+
+/// `:stats` popup: labelled figures about the active note.
+#[derive(Debug, Clone)]
+pub(crate) struct StatsView {
+    pub(crate) title: String,
+    pub(crate) rows: Vec<(String, String)>,
+}
+
+/// `:plugins.logs` popup: a plugin's log as it was when opened.
+#[derive(Debug, Clone)]
+pub(crate) struct PluginLogsView {
+    pub(crate) plugin: String,
+    pub(crate) lines: Vec<String>,
+    /// Lines scrolled up from the newest.
+    pub(crate) back: usize,
+}
+
+/// A plugin command waiting on the command line for the answer to a
+/// `prompt_input` question.
+#[derive(Debug, Clone)]
+pub(crate) struct PluginPrompt {
+    pub(crate) command: String,
+    pub(crate) args: String,
+    /// Answers to the command's earlier questions.
+    pub(crate) answers: Vec<String>,
+    pub(crate) question: String,
+}
+
+/// `:plugins.browse` picker: the registry's plugins narrowed by what is
+/// typed.
+#[derive(Debug, Clone)]
+pub(crate) struct PluginBrowser {
+    pub(crate) entries: Vec<RegistryEntry>,
+    pub(crate) query: String,
+    /// `entries` matching `query`, best first.
+    pub(crate) candidates: Vec<RegistryEntry>,
+    pub(crate) selected: usize,
+}
+
+/// `:tasks` popup: open checkboxes gathered from the vault. Items toggled
+/// here stay listed so a slip can be undone.
+#[derive(Debug, Clone)]
+pub(crate) struct TasksView {
+    /// The `#tag` or folder the list was narrowed to, if any.
+    pub(crate) filter: String,
+    pub(crate) items: Vec<TaskItem>,
+    pub(crate) selected: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TaskItem {
+    pub(crate) path: PathBuf,
+    /// 1-based line in the note.
+    pub(crate) line: usize,
+    pub(crate) text: String,
+    pub(crate) done: bool,
+}
+
+/// `:kanban` board of the active note; rebuilt from the text after every
+/// move.
+#[derive(Debug, Clone)]
+pub(crate) struct KanbanView {
+    pub(crate) columns: Vec<Column>,
+    pub(crate) column: usize,
+    pub(crate) card: usize,
+}
+
+/// `:props` editor over the active note's frontmatter, which it reads
+/// afresh on every draw.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PropertiesView {
+    pub(crate) selected: usize,
+    /// Text being typed, with the field it replaces (`None` for a new
+    /// `key: value`).
+    pub(crate) input: Option<(Option<String>, String)>,
+}
+
+/// Insert-mode completion of the `@key` being typed.
+#[derive(Debug, Clone)]
+pub(crate) struct CitationCompletion {
+    /// `(key, summary)` of the matching entries, best first.
+    pub(crate) candidates: Vec<(String, String)>,
+    pub(crate) selected: usize,
+}
+
+/// Insert-mode completion offered by plugins after one of their triggers.
+#[derive(Debug, Clone)]
+pub(crate) struct PluginCompletion {
+    pub(crate) trigger: String,
+    /// Row and byte column just after the trigger.
+    pub(crate) start: (usize, usize),
+    /// Everything the plugins offered when the trigger was typed.
+    pub(crate) items: Vec<CompletionItem>,
+    /// `items` matching the text typed since, best first.
+    pub(crate) candidates: Vec<CompletionItem>,
+    pub(crate) selected: usize,
+}
+
+/// Insert-mode completion of the `[[note` or `[[note#heading` being typed.
+#[derive(Debug, Clone)]
+pub(crate) struct LinkPicker {
+    /// `(text to insert, detail)`, best first.
+    pub(crate) candidates: Vec<(String, String)>,
+    pub(crate) selected: usize,
+}
+
+/// A note's headings as `(title, #…)`, read when it was last modified at
+/// `modified`.
+#[derive(Debug, Clone)]
+pub(crate) struct LinkHeadings {
+    pub(crate) path: PathBuf,
+    pub(crate) modified: Option<std::time::SystemTime>,
+    pub(crate) headings: Vec<(String, String)>,
+}
+
+/// `z=` popup: replacements for the misspelled word at `row`, `range`.
+#[derive(Debug, Clone)]
+pub(crate) struct SpellPopup {
+    pub(crate) row: usize,
+    pub(crate) range: std::ops::Range<usize>,
+    pub(crate) word: String,
+    pub(crate) suggestions: Vec<String>,
+    pub(crate) selected: usize,
 }
 
 #[derive(Default)]
 pub(crate) struct RenderCache {
     pub(crate) top: usize,
     pub(crate) bottom: usize,
+    pub(crate) cursor_row: usize,
+    /// Screen row of the cursor line once embed previews are laid out.
+    pub(crate) cursor_screen_row: usize,
+    /// Rows scrolled off the top so embeds never push the cursor out of view.
+    pub(crate) scroll: u16,
+    /// Blank rows above the first line so typewriter mode can centre the
+    /// cursor near the top of a note.
+    pub(crate) top_pad: u16,
     pub(crate) lines: Vec<Line<'static>>,
     pub(crate) dirty: bool,
+    pub(crate) highlight: HighlightCache,
+}
+
+/// Heading path shown in the status bar. Headings are re-parsed only after
+/// an edit or a buffer switch, and the path only when the cursor changes
+/// line.
+#[derive(Default)]
+pub(crate) struct Breadcrumb {
+    pub(crate) buffer_id: Option<u64>,
+    pub(crate) row: usize,
+    pub(crate) headings: Vec<Heading>,
+    pub(crate) path: String,
+}
+
+/// The minimap as last drawn, kept for click hit-testing and rebuilt only
+/// when the note or the column height changes.
+pub(crate) struct MinimapCache {
+    pub(crate) buffer_id: u64,
+    pub(crate) area: Rect,
+    pub(crate) map: Minimap,
+}
+
+/// Code block state at the start of every line seen so far, so rendering a
+/// viewport resumes highlighting instead of re-scanning from the top.
+///
+/// Entries past `Buffer::changed_from` are dropped on the next render.
+#[derive(Default)]
+pub(crate) struct HighlightCache {
+    pub(crate) buffer_id: u64,
+    pub(crate) line_states: Vec<Option<CodeBlockState>>,
+}
+
+/// Syntax highlighter state inside a fenced code block.
+#[derive(Clone)]
+pub(crate) struct CodeBlockState {
+    pub(crate) parse: ParseState,
+    pub(crate) highlight: HighlightState,
+}
+
+impl CodeBlockState {
+    pub(crate) fn new(language: &str) -> Self {
+        let syntax = SYNTAX_SET
+            .find_syntax_by_token(language)
+            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+        let highlighter = Highlighter::new(&SYNTECT_THEME);
+        Self {
+            parse: ParseState::new(syntax),
+            highlight: HighlightState::new(&highlighter, ScopeStack::new()),
+        }
+    }
 }
 
 pub(crate) static WIKILINK_RE: LazyLock<Regex> =
@@ -58,6 +332,10 @@ pub(crate) static BOLD_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\*\*[^*]+\*\*").expect("valid bold regex"));
 pub(crate) static ITALIC_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\*[^*\s][^*]*\*").expect("valid italic regex"));
+pub(crate) static STRIKETHROUGH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"~~[^~]+~~").expect("valid strikethrough regex"));
+pub(crate) static HIGHLIGHT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"==[^=]+==").expect("valid highlight regex"));
 pub(crate) static SYNTAX_SET: LazyLock<SyntaxSet> =
     LazyLock::new(SyntaxSet::load_defaults_newlines);
 pub(crate) static SYNTECT_THEME: LazyLock<SyntectTheme> = LazyLock::new(|| {
@@ -70,18 +348,70 @@ pub(crate) static SYNTECT_THEME: LazyLock<SyntectTheme> = LazyLock::new(|| {
         .expect("at least one syntect theme")
 });
 
+/// How long the latest notification stays visible in the status bar.
+pub(crate) const NOTIFICATION_TTL: Duration = Duration::from_secs(4);
+
 pub struct App {
     pub mode: Mode,
     pub buffer: Buffer, // Phase 1: single buffer. Phase 2: BufferManager with SlotMap.
     pub(crate) inactive_buffers: HashMap<PathBuf, Buffer>,
     pub(crate) open_tabs: Vec<PathBuf>,
     pub file_tree: FileTree,
+    /// Grouping the sidebar's `v` switches back to from the folder tree.
+    pub(crate) last_grouping: Grouping,
     pub sidebar_visible: bool,
     pub(crate) finder_mode: FinderMode,
     pub(crate) finder_query: String,
     pub(crate) finder_results: Vec<FinderResult>,
     pub(crate) finder_selected: usize,
+    /// The finder was opened from Insert mode and goes back to it.
+    pub(crate) finder_from_insert: bool,
+    /// Heading and rows a `FinderMode::Section` search covers.
+    pub(crate) finder_section: Option<(Option<String>, std::ops::Range<usize>)>,
+    /// Files the last content search could not read.
+    pub(crate) finder_unreadable: usize,
+    /// Why the content query could not be parsed, e.g. a bad `re:` pattern.
+    pub(crate) finder_query_error: Option<String>,
+    /// The vault's files as of opening the finder or the last watcher
+    /// event, so typing does not walk the vault.
+    pub(crate) finder_files: Vec<PathBuf>,
+    /// Notes an `FinderMode::Orphans` finder picks from.
+    pub(crate) finder_orphans: Vec<PathBuf>,
+    /// Notes an `FinderMode::LinkChoices` finder picks from.
+    pub(crate) finder_link_choices: Vec<PathBuf>,
+    /// The `#Heading` the link being resolved points at.
+    pub(crate) finder_link_anchor: Option<String>,
+    /// Every hit a `FinderMode::BrokenLinks` finder narrows down.
+    pub(crate) finder_broken: Vec<FinderResult>,
+    /// Every note a `FinderMode::Stale` finder narrows down, oldest first.
+    pub(crate) finder_stale: Vec<FinderResult>,
+    /// The age in days those notes are past.
+    pub(crate) finder_stale_days: u64,
+    /// Finder results marked with Tab, handed to `:bulk` on Enter.
+    pub(crate) finder_marked: Vec<PathBuf>,
+    /// Notes the `:bulk` actions apply to.
+    pub(crate) bulk_targets: Vec<PathBuf>,
+    pub(crate) code_langs: CodeLangIndex,
+    pub(crate) embeds: EmbedCache,
+    /// Vault files, for styling wikilinks that point nowhere.
+    pub(crate) note_index: NoteIndex,
+    /// Frontmatter of every note, for the aliases in `note_index`.
+    pub(crate) metadata: MetadataIndex,
+    pub(crate) quickfix: QuickfixList,
     pub(crate) command_input: String,
+    /// The command line was opened from Visual mode (linewise or not) and
+    /// the selection stays for the command to use.
+    pub(crate) visual_command: Option<bool>,
+    /// Tab completion cycling on the command line.
+    pub(crate) command_completion: Option<Completion>,
+    /// Last yanked text, used when the system clipboard is unavailable.
+    pub(crate) register: String,
+    /// Recent yanks and deletes for `:yanks`, newest first.
+    pub(crate) yank_history: YankHistory,
+    pub(crate) yank_selected: usize,
+    /// Partner of an emphasis marker Backspace just removed: row, byte
+    /// range and marker text, dropped by a following Tab.
+    pub(crate) pending_pair: Option<(usize, std::ops::Range<usize>, String)>,
     pub config: AppConfig,
     #[allow(dead_code)]
     // Phase 3 scaffolding: runtime command/event dispatch will read this manager.
@@ -89,30 +419,107 @@ pub struct App {
     pub should_quit: bool,
     #[allow(dead_code)] // Phase 2: plugin system event bus
     pub event_tx: mpsc::Sender<Msg>,
-    #[allow(dead_code)] // Phase 2: status bar notifications
     pub notifications: VecDeque<String>,
+    pub(crate) notification_until: Option<Instant>,
     pub(crate) render_cache: RenderCache,
+    pub(crate) breadcrumb: Breadcrumb,
     pub(crate) last_saved_file: Option<(PathBuf, Instant)>,
     pub(crate) quit_confirm_armed: bool,
     pub(crate) quit_confirm_until: Option<Instant>,
     pub(crate) pending_key: Option<char>,
     pub(crate) pending_key_since: Option<Instant>,
     pub(crate) pending_create_path: Option<PathBuf>,
+    pub(crate) pending_confirm: Option<PendingConfirm>,
+    /// Background work shown as a spinner in the status bar.
+    pub(crate) activities: Activities,
+    /// Notes from `general.autosave_exclude`, saved only with Ctrl+S.
+    pub(crate) autosave_exclude: Gitignore,
     pub(crate) backlinks_visible: bool,
     pub(crate) backlinks: Vec<BacklinkEntry>,
     pub(crate) backlinks_selected: usize,
+    /// Backlinks to the active note, shown as `↩ N` on its tab. Dropped
+    /// when another note changes on disk and recounted on the next update.
+    pub(crate) backlink_count: Option<(PathBuf, usize)>,
+    /// Where the `↩ N` badge was last drawn, for clicks.
+    pub(crate) backlink_badge: Option<Rect>,
+    pub(crate) outline_visible: bool,
+    /// Distraction-free layout: centred column, no bars, typewriter scrolling.
+    pub(crate) zen: bool,
+    /// Overview column on the right of the editor, clickable with the mouse.
+    pub(crate) minimap_visible: bool,
+    pub(crate) minimap: Option<MinimapCache>,
+    /// `:set list`: tabs, non-breaking and trailing spaces drawn as glyphs.
+    pub(crate) show_invisibles: bool,
+    /// Second pane on the active note (`:split`).
+    pub(crate) split: Option<Split>,
+    /// ASCII glyphs and basic colours, from `general.compat`.
+    pub(crate) compat: bool,
+    /// Rendered `:diagram` shown in the preview popup.
+    pub(crate) diagram_preview: Option<PathBuf>,
+    /// Whether the preview image has been drawn at the popup's position.
+    pub(crate) diagram_placed: bool,
+    /// Raw terminal output written after the next frame.
+    pub(crate) terminal_escape: String,
+    pub(crate) outline_selected: usize,
+    pub(crate) collab: Option<CollabSession>,
+    pub(crate) spell: Option<SpellChecker>,
+    pub(crate) spell_popup: Option<SpellPopup>,
+    /// Entries of `citations.bibliography`, when configured and readable.
+    pub(crate) bibliography: Option<Bibliography>,
+    /// The bibliography that last failed to load and its modification
+    /// time, so the error is reported once until the file changes.
+    pub(crate) bibliography_failed: Option<(PathBuf, Option<std::time::SystemTime>)>,
+    pub(crate) citation_completion: Option<CitationCompletion>,
+    pub(crate) link_picker: Option<LinkPicker>,
+    /// Headings of the last note the link picker read from disk, so typing
+    /// after `#` reads it once.
+    pub(crate) link_headings: Option<LinkHeadings>,
+    pub(crate) plugin_completion: Option<PluginCompletion>,
+    /// Each plugin's last `set_decorations`, with the note it was made for.
+    pub(crate) plugin_decorations: HashMap<PluginId, (Option<PathBuf>, Vec<Decoration>)>,
+    pub(crate) link_preview: Option<LinkPreview>,
+    pub(crate) graph_view: Option<GraphView>,
+    pub(crate) stats_view: Option<StatsView>,
+    pub(crate) plugin_logs: Option<PluginLogsView>,
+    pub(crate) plugin_browser: Option<PluginBrowser>,
+    pub(crate) plugin_prompt: Option<PluginPrompt>,
+    pub(crate) tasks_view: Option<TasksView>,
+    pub(crate) kanban_view: Option<KanbanView>,
+    pub(crate) properties_view: Option<PropertiesView>,
+    /// Narrows content search to notes that can match; `None` until the
+    /// background build finishes.
+    pub(crate) search_index: Option<SearchIndex>,
+    /// Links, aliases and tags of every note for backlinks, the graph and
+    /// link resolution; `None` until the background build finishes.
+    pub(crate) link_index: Option<LinkIndex>,
+    /// Watches the vault; replaced when `:vault` switches.
+    pub(crate) file_watcher: Option<FileWatcher>,
+    /// Watches plugin directories to reload a plugin when its files change;
+    /// replaced whenever the plugin set is.
+    pub(crate) plugin_watcher: Option<FileWatcher>,
+    /// Notes opened lately, saved on quit.
+    pub(crate) recent: RecentFiles,
+    pub(crate) sync_backend: Option<Arc<dyn SyncBackend>>,
+    pub(crate) sync_status: SyncStatus,
+    pub(crate) last_sync: Option<Instant>,
+    pub(crate) next_sync_at: Option<Instant>,
     #[allow(dead_code)] // Phase 2: animation tick tracking
     pub(crate) last_tick: Instant,
 }
 
 impl App {
-    pub fn new(config: AppConfig, event_tx: mpsc::Sender<Msg>) -> Result<Self> {
+    pub fn new(
+        config: AppConfig,
+        event_tx: mpsc::Sender<Msg>,
+        profile: &mut StartupProfile,
+    ) -> Result<Self> {
         std::fs::create_dir_all(config.vault_path())?;
 
         let scratch_path = config.scratch_path();
+        let rotated = rotate_scratch(&config);
 
         let buffer = if scratch_path.exists() {
-            Buffer::from_file(scratch_path)?
+            profile.time("scratch load", || Buffer::from_file(scratch_path))?
         } else {
             // Ensure vault directory exists
             if let Some(parent) = scratch_path.parent() {
@@ -123,52 +530,197 @@ impl App {
             buf
         };
 
-        let file_tree = FileTree::new(config.vault_path(), config.search.ignore_patterns.clone())?;
-        let plugin_manager = PluginManager::new(&config);
-        let notifications = VecDeque::from(plugin_manager.startup_notifications());
+        let file_tree = profile.time("file tree", || {
+            let mut tree =
+                FileTree::new(config.vault_path(), config.search.ignore_patterns.clone())?;
+            tree.pinned = pins::load(&pins::default_path());
+            tree.refresh()?;
+            anyhow::Ok(tree)
+        })?;
+        let vault_files = file_tree.all_file_paths();
+        profile.note(format!(
+            "vault: {} files in {}",
+            vault_files.len(),
+            config.vault_path().display()
+        ));
+        let mut metadata = MetadataIndex::default();
+        profile.time("note aliases", || metadata.refresh(&vault_files));
+        let note_index = NoteIndex::new(&config.vault_path(), &vault_files)
+            .with_aliases(metadata.group_by("aliases").0);
+        let compat = crate::view::compat::enabled(&config.general.compat);
+        let plugin_manager = profile.time("plugin discovery", || PluginManager::new(&config));
+        let mut notifications = VecDeque::from(plugin_manager.startup_notifications());
+        if config.safe_mode {
+            notifications.push_front("safe mode: plugins and hooks disabled".to_string());
+        }
+        notifications.extend(rotated);
+        let (sync_backend, sync_status) = match crate::sync::backend_from_config(&config) {
+            Ok(backend) => (backend, SyncStatus::Idle),
+            Err(err) => {
+                notifications.push_back(err.clone());
+                (None, SyncStatus::Error(err))
+            }
+        };
+        let spell = if config.spell.enabled {
+            profile
+                .time("spell dictionary", || {
+                    SpellChecker::load(&config.spell, &config.vault_path())
+                })
+                .map_err(|err| notifications.push_back(err))
+                .ok()
+        } else {
+            None
+        };
+        let mut bibliography_failed = None;
+        let bibliography = config.bibliography_path().and_then(|path| {
+            profile
+                .time("bibliography", || Bibliography::load(&path))
+                .map_err(|err| {
+                    notifications.push_back(err);
+                    bibliography_failed = Some((path.clone(), bibtex::modified(&path)));
+                })
+                .ok()
+        });
+        let autosave_exclude = config.autosave_exclusions().unwrap_or_else(|err| {
+            notifications.push_back(err);
+            Gitignore::empty()
+        });
+        let next_sync_at = sync_backend
+            .as_ref()
+            .and_then(|backend| backend.interval())
+            .map(|interval| Instant::now() + interval);
 
-        Ok(Self {
+        let mut app = Self {
             mode: Mode::Normal,
             buffer,
             inactive_buffers: HashMap::new(),
             open_tabs: Vec::new(),
             file_tree,
+            last_grouping: Grouping::Tags,
             sidebar_visible: false,
             finder_mode: FinderMode::Files,
             finder_query: String::new(),
             finder_results: Vec::new(),
             finder_selected: 0,
+            finder_from_insert: false,
+            finder_section: None,
+            finder_unreadable: 0,
+            finder_query_error: None,
+            finder_orphans: Vec::new(),
+            finder_link_choices: Vec::new(),
+            finder_link_anchor: None,
+            finder_broken: Vec::new(),
+            finder_stale: Vec::new(),
+            finder_stale_days: 0,
+            finder_marked: Vec::new(),
+            finder_files: Vec::new(),
+            bulk_targets: Vec::new(),
+            code_langs: CodeLangIndex::default(),
+            embeds: EmbedCache::default(),
+            note_index,
+            metadata,
+            quickfix: QuickfixList::default(),
             command_input: String::new(),
+            visual_command: None,
+            command_completion: None,
+            register: String::new(),
+            yank_history: YankHistory::new(config.editor.yank_history),
+            yank_selected: 0,
+            pending_pair: None,
             plugin_manager,
             config,
             should_quit: false,
             event_tx,
             notifications,
+            notification_until: Some(Instant::now() + NOTIFICATION_TTL),
             render_cache: RenderCache {
                 dirty: true,
                 ..Default::default()
             },
+            breadcrumb: Breadcrumb::default(),
             last_saved_file: None,
             quit_confirm_armed: false,
             quit_confirm_until: None,
             pending_key: None,
             pending_key_since: None,
             pending_create_path: None,
+            pending_confirm: None,
+            activities: Activities::default(),
+            autosave_exclude,
             backlinks_visible: false,
             backlinks: Vec::new(),
             backlinks_selected: 0,
+            backlink_count: None,
+            backlink_badge: None,
+            outline_visible: false,
+            zen: false,
+            minimap_visible: false,
+            minimap: None,
+            show_invisibles: false,
+            split: None,
+            compat,
+            diagram_preview: None,
+            diagram_placed: false,
+            terminal_escape: String::new(),
+            outline_selected: 0,
+            collab: None,
+            spell,
+            spell_popup: None,
+            bibliography,
+            bibliography_failed,
+            citation_completion: None,
+            link_picker: None,
+            link_headings: None,
+            plugin_completion: None,
+            plugin_decorations: HashMap::new(),
+            link_preview: None,
+            graph_view: None,
+            stats_view: None,
+            plugin_logs: None,
+            plugin_browser: None,
+            plugin_prompt: None,
+            tasks_view: None,
+            kanban_view: None,
+            properties_view: None,
+            search_index: None,
+            link_index: None,
+            file_watcher: None,
+            plugin_watcher: None,
+            recent: RecentFiles::load(&recent::default_path()),
+            sync_backend,
+            sync_status,
+            last_sync: None,
+            next_sync_at,
             last_tick: Instant::now(),
         }
-        .with_initial_tab())
+        .with_initial_tab();
+        if app.config.editor.minimap {
+            app.set_minimap(true);
+        }
+        app.start_search_index();
+        app.start_link_index();
+        app.file_watcher = Some(watcher::spawn(
+            app.config.vault_path(),
+            app.event_tx.clone(),
+        ));
+        app.watch_plugins();
+        Ok(app)
     }
 
     fn with_initial_tab(mut self) -> Self {
         if let Some(path) = self.buffer.path.clone() {
+            self.recent.touch(&path);
             self.open_tabs.push(path);
         }
+        self.pin_tabs();
         self
     }
 
+    /// Line numbers are hidden in zen mode regardless of the config.
+    pub(crate) fn show_line_numbers(&self) -> bool {
+        self.config.editor.line_numbers && !self.zen
+    }
+
     pub(crate) fn pending_write_count(&self) -> usize {
         let mut count = 0;
 
@@ -193,6 +745,26 @@ impl App {
     // ── MVU: View ────────────────────────────────────────────────
 }
 
+/// Apply `general.scratch_rotate` before the scratch note is loaded,
+/// returning a notification when it was moved aside or could not be.
+fn rotate_scratch(config: &AppConfig) -> Option<String> {
+    let general = &config.general;
+    let rotation =
+        match scratch::Rotation::from_config(&general.scratch_rotate, general.scratch_max_kb) {
+            Ok(rotation) => rotation,
+            Err(err) => return Some(err),
+        };
+    let index = config.vault_path().join(&general.scratch_index);
+    match scratch::rotate_if_due(&config.scratch_path(), &index, rotation) {
+        Ok(Some(target)) => Some(format!(
+            "Scratch rotated to {}",
+            target.file_name().unwrap_or_default().to_string_lossy()
+        )),
+        Ok(None) => None,
+        Err(err) => Some(format!("scratch rotation failed: {err}")),
+    }
+}
+
 pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -213,45 +785,48 @@ pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-pub(crate) fn parse_plugin_command_input(raw: &str) -> String {
-    let input = raw.trim();
-    if input.len() < 2 {
-        return input.to_string();
-    }
-
-    let mut chars = input.chars();
-    let Some(first) = chars.next() else {
-        return String::new();
-    };
+/// Split `:plugin <command> [args]` input into the command name and the
+/// rest of the line. Quotes keep a name with spaces together, as in
+/// `plugin "word count" --words`.
+pub(crate) fn parse_plugin_command_input(raw: &str) -> (String, &str) {
+    next_command_word(raw).unwrap_or_default()
+}
 
-    if first != '"' && first != '\'' {
-        return input.to_string();
+/// Arguments for a plugin command: words split on whitespace, with single
+/// or double quotes grouping and `\` escaping the next character.
+pub(crate) fn split_command_args(raw: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut rest = raw;
+    while let Some((word, after)) = next_command_word(rest) {
+        words.push(word);
+        rest = after;
     }
+    words
+}
 
-    if !input.ends_with(first) {
-        return input.to_string();
+/// The first word of `input` and what follows it, or `None` when only
+/// whitespace is left.
+fn next_command_word(input: &str) -> Option<(String, &str)> {
+    let input = input.trim_start();
+    if input.is_empty() {
+        return None;
     }
-
-    let inner = &input[first.len_utf8()..input.len() - first.len_utf8()];
-    let mut out = String::with_capacity(inner.len());
-    let mut escaped = false;
-
-    for ch in inner.chars() {
-        if escaped {
-            out.push(ch);
-            escaped = false;
-        } else if ch == '\\' {
-            escaped = true;
-        } else {
-            out.push(ch);
+    let mut word = String::new();
+    let mut quote = None;
+    let mut chars = input.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        match (quote, ch) {
+            (_, '\\') => match chars.next() {
+                Some((_, escaped)) => word.push(escaped),
+                None => word.push('\\'),
+            },
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, _) if ch.is_whitespace() => return Some((word, &input[idx..])),
+            _ => word.push(ch),
         }
     }
-
-    if escaped {
-        out.push('\\');
-    }
-
-    out.trim().to_string()
+    Some((word, ""))
 }
 
 pub(crate) fn same_file_path(a: &PathBuf, b: &PathBuf) -> bool {
@@ -264,27 +839,51 @@ pub(crate) fn same_file_path(a: &PathBuf, b: &PathBuf) -> bool {
     matches!((a_canon, b_canon), (Ok(ca), Ok(cb)) if ca == cb)
 }
 
-pub(crate) fn spawn_buffer_save(path: PathBuf, rope: ropey::Rope) {
+/// Write `rope` to `path` on a background thread, then run the save hook.
+pub(crate) fn spawn_buffer_save(path: PathBuf, rope: ropey::Rope, hook: Option<Invocation>) {
     std::thread::spawn(move || {
-        use std::io::Write;
-        let result = (|| -> Result<()> {
-            let tmp = path.with_extension("tmp");
-            let file = std::fs::File::create(&tmp)?;
-            let mut writer = std::io::BufWriter::new(file);
-            for chunk in rope.chunks() {
-                writer.write_all(chunk.as_bytes())?;
-            }
-            writer.flush()?;
-            std::fs::rename(&tmp, &path)?;
-            Ok(())
-        })();
-
-        if let Err(e) = result {
+        if let Err(e) = write_buffer(&path, &rope) {
             tracing::error!("save failed: {e}");
+            if let Some(hook) = hook {
+                hook.skip(&format!("save failed: {e}"));
+            }
+            return;
+        }
+        if let Some(hook) = hook {
+            hook.run();
         }
     });
 }
 
+/// Write `rope` to `path` through a temporary file and record the change
+/// in the journal.
+pub(crate) fn write_buffer(path: &Path, rope: &ropey::Rope) -> Result<()> {
+    use std::io::Write;
+    let previous = std::fs::read_to_string(path).unwrap_or_default();
+    let tmp = path.with_extension("tmp");
+    let file = std::fs::File::create(&tmp)?;
+    let mut writer = std::io::BufWriter::new(file);
+    for chunk in rope.chunks() {
+        writer.write_all(chunk.as_bytes())?;
+    }
+    writer.flush()?;
+    std::fs::rename(&tmp, path)?;
+
+    let (added, removed) = journal::line_delta(&previous, &rope.to_string());
+    if added + removed > 0 {
+        let entry = journal::JournalEntry {
+            timestamp: journal::now_secs(),
+            path: path.to_path_buf(),
+            added,
+            removed,
+        };
+        if let Err(e) = journal::append(&journal::default_path(), &entry) {
+            tracing::warn!("journal write failed: {e}");
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum TokenKind {
     WikiLink,
@@ -292,6 +891,10 @@ pub(crate) enum TokenKind {
     InlineCode,
     Bold,
     Italic,
+    Strikethrough,
+    Highlight,
+    /// Pandoc-style `@key` citation.
+    Citation,
 }
 
 pub(crate) fn next_markdown_token(
@@ -329,6 +932,23 @@ pub(crate) fn next_markdown_token(
                 .map(|m| (m.start(), m.end(), TokenKind::Italic)),
             4,
         ),
+        (
+            STRIKETHROUGH_RE
+                .find_at(text, start_at)
+                .map(|m| (m.start(), m.end(), TokenKind::Strikethrough)),
+            5,
+        ),
+        (
+            HIGHLIGHT_RE
+                .find_at(text, start_at)
+                .map(|m| (m.start(), m.end(), TokenKind::Highlight)),
+            6,
+        ),
+        (
+            bibtex::next_citation(text, start_at)
+                .map(|range| (range.start, range.end, TokenKind::Citation)),
+            7,
+        ),
     ];
 
     candidates
@@ -338,6 +958,50 @@ pub(crate) fn next_markdown_token(
         .map(|(h, _)| h)
 }
 
+/// The visible part of a markdown token when syntax is concealed: delimiters
+/// and link targets are dropped, inline code is left untouched.
+pub(crate) fn conceal_token(token: &str, kind: TokenKind) -> String {
+    match kind {
+        TokenKind::WikiLink => {
+            let inner = token
+                .strip_prefix("[[")
+                .and_then(|t| t.strip_suffix("]]"))
+                .unwrap_or(token);
+            inner
+                .split_once('|')
+                .map_or(inner, |(_, alias)| alias)
+                .trim()
+                .to_string()
+        }
+        TokenKind::Link => token
+            .strip_prefix('[')
+            .and_then(|t| t.split_once("]("))
+            .map_or(token, |(label, _)| label)
+            .to_string(),
+        TokenKind::Bold => token
+            .strip_prefix("**")
+            .and_then(|t| t.strip_suffix("**"))
+            .unwrap_or(token)
+            .to_string(),
+        TokenKind::Italic => token
+            .strip_prefix('*')
+            .and_then(|t| t.strip_suffix('*'))
+            .unwrap_or(token)
+            .to_string(),
+        TokenKind::Strikethrough => token
+            .strip_prefix("~~")
+            .and_then(|t| t.strip_suffix("~~"))
+            .unwrap_or(token)
+            .to_string(),
+        TokenKind::Highlight => token
+            .strip_prefix("==")
+            .and_then(|t| t.strip_suffix("=="))
+            .unwrap_or(token)
+            .to_string(),
+        TokenKind::InlineCode | TokenKind::Citation => token.to_string(),
+    }
+}
+
 pub(crate) fn parse_code_fence_language(line: &str) -> Option<String> {
     let trimmed = line.trim_start();
     if !trimmed.starts_with("```") {
@@ -366,8 +1030,13 @@ pub(crate) fn parse_wikilink_target(wikilink: &str) -> Option<String> {
     }
 }
 
+/// The `Heading` or `^block` after `#` in `[[Note#Heading|alias]]`.
+pub(crate) fn wikilink_anchor(wikilink: &str) -> Option<String> {
+    link::wikilink_anchor(wikilink).map(str::to_string)
+}
+
 pub(crate) fn sanitize_link_name(raw: &str) -> String {
-    raw.split(['|', '#'])
+    raw.split(['|', '#', '^'])
         .next()
         .map(str::trim)
         .unwrap_or_default()
@@ -399,3 +1068,26 @@ pub(crate) fn syntect_to_ratatui(style: syntect::highlighting::Style) -> Style {
 
     rat_style
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conceal_token() {
+        let cases = [
+            ("[[Note]]", TokenKind::WikiLink, "Note"),
+            ("[[folder/Note#Part| shown ]]", TokenKind::WikiLink, "shown"),
+            ("[label](https://example.com)", TokenKind::Link, "label"),
+            ("**bold**", TokenKind::Bold, "bold"),
+            ("*slanted*", TokenKind::Italic, "slanted"),
+            ("~~gone~~", TokenKind::Strikethrough, "gone"),
+            ("==marked==", TokenKind::Highlight, "marked"),
+            ("`**kept**`", TokenKind::InlineCode, "`**kept**`"),
+            ("@knuth1984", TokenKind::Citation, "@knuth1984"),
+        ];
+        for (token, kind, shown) in cases {
+            assert_eq!(conceal_token(token, kind), shown, "{token}");
+        }
+    }
+}
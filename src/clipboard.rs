@@ -0,0 +1,109 @@
+//! System clipboard access by shelling out to the platform tool.
+//!
+//! Nothing here links against a windowing system: on X11, Wayland or macOS
+//! the usual command-line helper is used, and callers fall back to the
+//! in-app register when none is installed.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Candidate `(read, write)` commands for the current platform, best first.
+fn tools() -> Vec<(&'static [&'static str], &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![(&["pbpaste"], &["pbcopy"])];
+    }
+    if cfg!(windows) {
+        return vec![(
+            &["powershell", "-NoProfile", "-Command", "Get-Clipboard"],
+            &["clip"],
+        )];
+    }
+
+    let mut out: Vec<(&'static [&'static str], &'static [&'static str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        out.push((&["wl-paste", "--no-newline"], &["wl-copy"]));
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        out.push((
+            &["xclip", "-selection", "clipboard", "-o"],
+            &["xclip", "-selection", "clipboard"],
+        ));
+        out.push((
+            &["xsel", "--clipboard", "--output"],
+            &["xsel", "--clipboard", "--input"],
+        ));
+    }
+    out
+}
+
+/// Commands that print the clipboard as PNG, best first. They fail when the
+/// clipboard holds no image.
+fn image_tools() -> Vec<&'static [&'static str]> {
+    if cfg!(target_os = "macos") {
+        return vec![&["pngpaste", "-"]];
+    }
+    if cfg!(windows) {
+        return Vec::new();
+    }
+
+    let mut out: Vec<&'static [&'static str]> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        out.push(&["wl-paste", "--no-newline", "--type", "image/png"]);
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        out.push(&["xclip", "-selection", "clipboard", "-t", "image/png", "-o"]);
+    }
+    out
+}
+
+/// PNG bytes of an image on the clipboard, or `None` when there is none or
+/// no tool can fetch it.
+pub fn read_image() -> Option<Vec<u8>> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    image_tools().into_iter().find_map(|read| {
+        let output = Command::new(read[0])
+            .args(&read[1..])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        (output.status.success() && output.stdout.starts_with(PNG_SIGNATURE))
+            .then_some(output.stdout)
+    })
+}
+
+/// Current clipboard contents, or `None` when no tool is available.
+pub fn read() -> Option<String> {
+    tools().into_iter().find_map(|(read, _)| {
+        let output = Command::new(read[0])
+            .args(&read[1..])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+/// Replace the clipboard contents; returns the tool used.
+pub fn write(text: &str) -> Result<&'static str, String> {
+    for (_, write) in tools() {
+        let Ok(mut child) = Command::new(write[0])
+            .args(&write[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        if child.wait().is_ok_and(|status| status.success()) {
+            return Ok(write[0]);
+        }
+    }
+    Err("no clipboard tool found (install wl-clipboard, xclip or xsel)".to_string())
+}
@@ -0,0 +1,4 @@
+pub mod ot;
+pub mod session;
+
+pub use session::CollabSession;
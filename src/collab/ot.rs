@@ -0,0 +1,359 @@
+//! Minimal operational transform over character offsets.
+//!
+//! Documents are edited with sequences of primitive inserts and deletes. Two
+//! sequences produced concurrently against the same revision are reconciled
+//! with [`transform`], which guarantees both peers converge on the same text.
+//! When a remote insert lands strictly inside a concurrently deleted range the
+//! delete wins and the insert is dropped — acceptable for an experimental
+//! pairing mode and it keeps every transform one-to-one.
+
+use ropey::Rope;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Insert { pos: usize, text: String },
+    Delete { pos: usize, len: usize },
+    Noop,
+}
+
+impl Op {
+    fn insert_len(&self) -> usize {
+        match self {
+            Op::Insert { text, .. } => text.chars().count(),
+            _ => 0,
+        }
+    }
+}
+
+/// Transform `a` and `b` (both based on the same document) so that
+/// `apply(apply(doc, b), a') == apply(apply(doc, a), b')`.
+///
+/// `a_wins_ties` decides which insert goes first when both target the same
+/// offset; peers must agree on it (the host always wins).
+pub fn transform_op(a: &Op, b: &Op, a_wins_ties: bool) -> (Op, Op) {
+    match (a, b) {
+        (Op::Noop, _) | (_, Op::Noop) => (a.clone(), b.clone()),
+        (Op::Insert { pos: pa, text: ta }, Op::Insert { pos: pb, text: tb }) => {
+            if pa < pb || (pa == pb && a_wins_ties) {
+                (
+                    a.clone(),
+                    Op::Insert {
+                        pos: pb + a.insert_len(),
+                        text: tb.clone(),
+                    },
+                )
+            } else {
+                (
+                    Op::Insert {
+                        pos: pa + b.insert_len(),
+                        text: ta.clone(),
+                    },
+                    b.clone(),
+                )
+            }
+        }
+        (Op::Insert { .. }, Op::Delete { .. }) => {
+            let (b2, a2) = transform_delete_insert(b, a);
+            (a2, b2)
+        }
+        (Op::Delete { .. }, Op::Insert { .. }) => transform_delete_insert(a, b),
+        (Op::Delete { pos: pa, len: la }, Op::Delete { pos: pb, len: lb }) => {
+            let (a_end, b_end) = (pa + la, pb + lb);
+            if a_end <= *pb {
+                (a.clone(), delete(pb - la, *lb))
+            } else if b_end <= *pa {
+                (delete(pa - lb, *la), b.clone())
+            } else {
+                let overlap = a_end.min(b_end) - pa.max(pb);
+                let start = *pa.min(pb);
+                (delete(start, la - overlap), delete(start, lb - overlap))
+            }
+        }
+    }
+}
+
+/// Transform a delete `d` against a concurrent insert `i`.
+fn transform_delete_insert(d: &Op, i: &Op) -> (Op, Op) {
+    let (Op::Delete { pos: dp, len: dl }, Op::Insert { pos: ip, text }) = (d, i) else {
+        return (d.clone(), i.clone());
+    };
+    let il = i.insert_len();
+
+    if *ip <= *dp {
+        (delete(dp + il, *dl), i.clone())
+    } else if *ip >= dp + dl {
+        (
+            d.clone(),
+            Op::Insert {
+                pos: ip - dl,
+                text: text.clone(),
+            },
+        )
+    } else {
+        (delete(*dp, dl + il), Op::Noop)
+    }
+}
+
+fn delete(pos: usize, len: usize) -> Op {
+    if len == 0 {
+        Op::Noop
+    } else {
+        Op::Delete { pos, len }
+    }
+}
+
+/// Transform two op sequences based on the same revision.
+pub fn transform(a: &[Op], b: &[Op], a_wins_ties: bool) -> (Vec<Op>, Vec<Op>) {
+    let mut a_out: Vec<Op> = a.to_vec();
+    let mut b_out = Vec::with_capacity(b.len());
+
+    for b_op in b {
+        let mut b_cur = b_op.clone();
+        let mut next_a = Vec::with_capacity(a_out.len());
+        for a_op in &a_out {
+            let (a2, b2) = transform_op(a_op, &b_cur, a_wins_ties);
+            next_a.push(a2);
+            b_cur = b2;
+        }
+        a_out = next_a;
+        b_out.push(b_cur);
+    }
+
+    (a_out, b_out)
+}
+
+/// Apply `ops` to `rope`, clamping out-of-range offsets instead of panicking.
+pub fn apply(rope: &mut Rope, ops: &[Op]) {
+    for op in ops {
+        match op {
+            Op::Insert { pos, text } => {
+                let pos = (*pos).min(rope.len_chars());
+                rope.insert(pos, text);
+            }
+            Op::Delete { pos, len } => {
+                let start = (*pos).min(rope.len_chars());
+                let end = (pos + len).min(rope.len_chars());
+                rope.remove(start..end);
+            }
+            Op::Noop => {}
+        }
+    }
+}
+
+/// Map a character offset through `ops` so cursors follow remote edits.
+pub fn transform_offset(mut offset: usize, ops: &[Op]) -> usize {
+    for op in ops {
+        match op {
+            Op::Insert { pos, text } if *pos <= offset => offset += text.chars().count(),
+            Op::Delete { pos, len } if *pos < offset => {
+                offset -= (*len).min(offset - pos);
+            }
+            _ => {}
+        }
+    }
+    offset
+}
+
+/// Describe the change from `old` to `new` as a delete followed by an insert
+/// of the differing middle section. Returns an empty list when unchanged.
+pub fn diff(old: &str, new: &str) -> Vec<Op> {
+    if old == new {
+        return Vec::new();
+    }
+
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+
+    let prefix = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+    let suffix = old_chars
+        .iter()
+        .rev()
+        .zip(new_chars.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let removed = old_chars.len() - prefix - suffix;
+    let inserted: String = new_chars[prefix..new_chars.len() - suffix].iter().collect();
+
+    let mut ops = Vec::new();
+    if removed > 0 {
+        ops.push(Op::Delete {
+            pos: prefix,
+            len: removed,
+        });
+    }
+    if !inserted.is_empty() {
+        ops.push(Op::Insert {
+            pos: prefix,
+            text: inserted,
+        });
+    }
+    ops
+}
+
+// ── Wire format ──────────────────────────────────────────────────
+//
+// One message per line, tab separated. Text payloads escape `\`, tab and
+// newline so a message never spans lines.
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub fn encode_ops(ops: &[Op]) -> String {
+    ops.iter()
+        .filter_map(|op| match op {
+            Op::Insert { pos, text } => Some(format!("I:{pos}:{}", escape(text))),
+            Op::Delete { pos, len } => Some(format!("D:{pos}:{len}")),
+            Op::Noop => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+pub fn decode_ops(raw: &str) -> Option<Vec<Op>> {
+    raw.split('\t')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut fields = part.splitn(3, ':');
+            let kind = fields.next()?;
+            let pos = fields.next()?.parse().ok()?;
+            let payload = fields.next()?;
+            match kind {
+                "I" => Some(Op::Insert {
+                    pos,
+                    text: unescape(payload),
+                }),
+                "D" => Some(Op::Delete {
+                    pos,
+                    len: payload.parse().ok()?,
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn converge(base: &str, a: Vec<Op>, b: Vec<Op>) -> (String, String) {
+        let (a2, b2) = transform(&a, &b, true);
+
+        let mut left = Rope::from_str(base);
+        apply(&mut left, &a);
+        apply(&mut left, &b2);
+
+        let mut right = Rope::from_str(base);
+        apply(&mut right, &b);
+        apply(&mut right, &a2);
+
+        (left.to_string(), right.to_string())
+    }
+
+    #[test]
+    fn test_concurrent_inserts_converge() {
+        let (l, r) = converge(
+            "hello",
+            diff("hello", "hello world"),
+            diff("hello", "oh hello"),
+        );
+        assert_eq!(l, r);
+        assert_eq!(l, "oh hello world");
+    }
+
+    #[test]
+    fn test_same_position_inserts_respect_priority() {
+        let (l, r) = converge("ac", diff("ac", "abc"), diff("ac", "axc"));
+        assert_eq!(l, r);
+        assert_eq!(l, "abxc");
+    }
+
+    #[test]
+    fn test_overlapping_deletes_converge() {
+        let (l, r) = converge(
+            "abcdefgh",
+            diff("abcdefgh", "abgh"),
+            diff("abcdefgh", "abcdh"),
+        );
+        assert_eq!(l, r);
+        assert_eq!(l, "abh");
+    }
+
+    #[test]
+    fn test_insert_inside_deleted_range_is_dropped() {
+        let (l, r) = converge("abcdef", diff("abcdef", "af"), diff("abcdef", "abcXdef"));
+        assert_eq!(l, r);
+        assert_eq!(l, "af");
+    }
+
+    #[test]
+    fn test_diff_replaces_middle() {
+        assert_eq!(
+            diff("the cat sat", "the dog sat"),
+            vec![
+                Op::Delete { pos: 4, len: 3 },
+                Op::Insert {
+                    pos: 4,
+                    text: "dog".to_string()
+                }
+            ]
+        );
+        assert!(diff("same", "same").is_empty());
+    }
+
+    #[test]
+    fn test_transform_offset_follows_edits() {
+        let ops = diff("hello world", "hello big world");
+        assert_eq!(transform_offset(8, &ops), 12);
+        assert_eq!(transform_offset(2, &ops), 2);
+    }
+
+    #[test]
+    fn test_wire_roundtrip() {
+        let ops = vec![
+            Op::Delete { pos: 3, len: 2 },
+            Op::Insert {
+                pos: 3,
+                text: "a\tb\nc\\d:e".to_string(),
+            },
+        ];
+        assert_eq!(decode_ops(&encode_ops(&ops)), Some(ops));
+    }
+}
@@ -0,0 +1,491 @@
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ropey::Rope;
+
+use super::ot::{self, Op};
+use crate::msg::Msg;
+
+/// How long a connecting peer has to send its pairing line.
+const PAIR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest pairing line read before giving up on the peer.
+const MAX_PAIR_LINE: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Client,
+}
+
+/// Events emitted by the collab network threads.
+#[derive(Debug)]
+pub enum CollabEvent {
+    Connected {
+        peer: String,
+        stream: TcpStream,
+        writer: mpsc::Sender<String>,
+    },
+    Line(String),
+    Disconnected(String),
+    Failed(String),
+}
+
+/// What the App has to do to its buffer after a remote message.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemoteUpdate {
+    None,
+    /// Apply these ops to the shared buffer.
+    Apply(Vec<Op>),
+    /// Replace the shared buffer wholesale (initial snapshot from the host).
+    Snapshot {
+        name: String,
+        text: String,
+    },
+}
+
+/// A single-buffer pairing session using central-server OT: the host owns
+/// the canonical revision history and the client keeps at most one op in
+/// flight, buffering further local edits until it is acknowledged.
+pub struct CollabSession {
+    pub role: Role,
+    pub path: Option<PathBuf>,
+    pub peer: Option<String>,
+    pub peer_cursor: Option<usize>,
+    /// What a peer has to join with; set when hosting.
+    pub token: Option<String>,
+    revision: usize,
+    shadow: String,
+    history: Vec<Vec<Op>>,
+    outstanding: Option<Vec<Op>>,
+    buffered: Option<Vec<Op>>,
+    last_sent_cursor: Option<usize>,
+    writer: Option<mpsc::Sender<String>>,
+    stream: Option<TcpStream>,
+    stop: Arc<AtomicBool>,
+}
+
+impl CollabSession {
+    fn new(role: Role, path: Option<PathBuf>, shadow: String) -> Self {
+        Self {
+            role,
+            path,
+            peer: None,
+            peer_cursor: None,
+            token: None,
+            revision: 0,
+            shadow,
+            history: Vec::new(),
+            outstanding: None,
+            buffered: None,
+            last_sent_cursor: None,
+            writer: None,
+            stream: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Listen on `addr` and share `path` with the first peer that connects
+    /// with the session's pairing token; others are turned away.
+    pub fn host(
+        addr: &str,
+        path: PathBuf,
+        text: String,
+        tx: mpsc::Sender<Msg>,
+    ) -> std::io::Result<(Self, String)> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local = listener.local_addr()?.to_string();
+
+        let mut session = Self::new(Role::Host, Some(path), text);
+        let token = pairing_token();
+        session.token = Some(token.clone());
+        let stop = Arc::clone(&session.stop);
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((mut stream, peer)) => {
+                        let _ = stream.set_nonblocking(false);
+                        if read_pairing(&mut stream).as_deref() != Some(token.as_str()) {
+                            tracing::warn!("collab: {peer} did not pair, closing");
+                            let _ = stream.shutdown(std::net::Shutdown::Both);
+                            continue;
+                        }
+                        run_connection(stream, peer.to_string(), tx);
+                        return;
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                    Err(err) => {
+                        let _ = tx.send(Msg::Collab(CollabEvent::Failed(err.to_string())));
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok((session, local))
+    }
+
+    /// Connect to a hosting instance with its pairing `token`; the shared
+    /// buffer arrives as a snapshot.
+    pub fn join(addr: &str, token: &str, tx: mpsc::Sender<Msg>) -> Self {
+        let addr = addr.to_string();
+        let pair = format!("PAIR\t{token}");
+        thread::spawn(move || match TcpStream::connect(&addr) {
+            Ok(mut stream) => {
+                if let Err(err) = writeln!(stream, "{pair}") {
+                    let _ = tx.send(Msg::Collab(CollabEvent::Failed(format!("{addr}: {err}"))));
+                    return;
+                }
+                run_connection(stream, addr, tx)
+            }
+            Err(err) => {
+                let _ = tx.send(Msg::Collab(CollabEvent::Failed(format!("{addr}: {err}"))));
+            }
+        });
+
+        Self::new(Role::Client, None, String::new())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        self.writer = None;
+    }
+
+    pub fn attach(&mut self, peer: String, stream: TcpStream, writer: mpsc::Sender<String>) {
+        self.peer = Some(peer);
+        self.stream = Some(stream);
+        self.writer = Some(writer);
+
+        if self.role == Role::Host {
+            let name = self
+                .path
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "shared.md".to_string());
+            let hello = format!(
+                "HELLO\t{}\t{}\t{}",
+                self.revision,
+                ot::escape(&name),
+                ot::escape(&self.shadow)
+            );
+            self.send(hello);
+        }
+    }
+
+    pub fn detach(&mut self) {
+        self.peer = None;
+        self.peer_cursor = None;
+        self.writer = None;
+        self.stream = None;
+    }
+
+    /// Bind a client session to the buffer created from the host snapshot.
+    pub fn bind_path(&mut self, path: PathBuf) {
+        self.path = Some(path);
+    }
+
+    /// Reconcile local edits: diff the buffer against the shadow copy and
+    /// ship the resulting ops to the peer.
+    pub fn local_change(&mut self, current: &str) {
+        let ops = ot::diff(&self.shadow, current);
+        if ops.is_empty() {
+            return;
+        }
+        self.shadow = current.to_string();
+
+        match self.role {
+            Role::Host => {
+                self.history.push(ops.clone());
+                self.revision += 1;
+                let line = format!("OP\t{}\t{}", self.revision, ot::encode_ops(&ops));
+                self.send(line);
+            }
+            Role::Client => {
+                if self.outstanding.is_none() {
+                    let line = format!("OP\t{}\t{}", self.revision, ot::encode_ops(&ops));
+                    self.send(line);
+                    self.outstanding = Some(ops);
+                } else {
+                    self.buffered.get_or_insert_with(Vec::new).extend(ops);
+                }
+            }
+        }
+    }
+
+    pub fn local_cursor(&mut self, offset: usize) {
+        if self.last_sent_cursor == Some(offset) || !self.is_connected() {
+            return;
+        }
+        self.last_sent_cursor = Some(offset);
+        self.send(format!("CURSOR\t{offset}"));
+    }
+
+    /// Handle one protocol line. Local edits must be flushed with
+    /// [`local_change`](Self::local_change) first so the shadow matches the buffer.
+    pub fn remote_line(&mut self, line: &str) -> RemoteUpdate {
+        let mut fields = line.splitn(2, '\t');
+        let kind = fields.next().unwrap_or_default();
+        let rest = fields.next().unwrap_or_default();
+
+        match (kind, self.role) {
+            ("CURSOR", _) => {
+                self.peer_cursor = rest.trim().parse().ok();
+                RemoteUpdate::None
+            }
+            ("HELLO", Role::Client) => {
+                let mut parts = rest.splitn(3, '\t');
+                let revision = parts.next().and_then(|r| r.parse().ok());
+                let name = parts.next().map(ot::unescape);
+                let text = parts.next().map(ot::unescape);
+                let (Some(revision), Some(name), Some(text)) = (revision, name, text) else {
+                    return RemoteUpdate::None;
+                };
+                self.revision = revision;
+                self.shadow = text.clone();
+                self.outstanding = None;
+                self.buffered = None;
+                RemoteUpdate::Snapshot { name, text }
+            }
+            ("OP", role) => {
+                let (revision, raw_ops) = rest.split_once('\t').unwrap_or((rest, ""));
+                let (Ok(revision), Some(ops)) =
+                    (revision.parse::<usize>(), ot::decode_ops(raw_ops))
+                else {
+                    return RemoteUpdate::None;
+                };
+
+                let incoming = match role {
+                    Role::Host => {
+                        let base = revision.min(self.history.len());
+                        let concurrent: Vec<Op> = self.history[base..].concat();
+                        let (ops, _) = ot::transform(&ops, &concurrent, false);
+                        self.history.push(ops.clone());
+                        self.revision += 1;
+                        let ack = format!("ACK\t{}", self.revision);
+                        self.send(ack);
+                        ops
+                    }
+                    Role::Client => {
+                        let mut incoming = ops;
+                        if let Some(outstanding) = self.outstanding.take() {
+                            let (outstanding, transformed) =
+                                ot::transform(&outstanding, &incoming, false);
+                            self.outstanding = Some(outstanding);
+                            incoming = transformed;
+                        }
+                        if let Some(buffered) = self.buffered.take() {
+                            let (buffered, transformed) =
+                                ot::transform(&buffered, &incoming, false);
+                            self.buffered = Some(buffered);
+                            incoming = transformed;
+                        }
+                        self.revision = revision;
+                        incoming
+                    }
+                };
+
+                let mut rope = Rope::from_str(&self.shadow);
+                ot::apply(&mut rope, &incoming);
+                self.shadow = rope.to_string();
+                if let Some(cursor) = self.peer_cursor {
+                    self.peer_cursor = Some(ot::transform_offset(cursor, &incoming));
+                }
+                RemoteUpdate::Apply(incoming)
+            }
+            ("ACK", Role::Client) => {
+                if let Ok(revision) = rest.trim().parse() {
+                    self.revision = revision;
+                }
+                self.outstanding = None;
+                if let Some(buffered) = self.buffered.take() {
+                    let line = format!("OP\t{}\t{}", self.revision, ot::encode_ops(&buffered));
+                    self.send(line);
+                    self.outstanding = Some(buffered);
+                }
+                RemoteUpdate::None
+            }
+            _ => RemoteUpdate::None,
+        }
+    }
+
+    fn send(&mut self, line: String) {
+        let Some(writer) = self.writer.as_ref() else {
+            return;
+        };
+        if writer.send(line).is_err() {
+            self.writer = None;
+        }
+    }
+}
+
+/// 12 hex digits from the standard library's randomly keyed hasher.
+fn pairing_token() -> String {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or(0);
+    hasher.write_u128(nanos);
+    format!("{:012x}", hasher.finish() & 0xffff_ffff_ffff)
+}
+
+/// The token in a joining peer's `PAIR` line. Read a byte at a time so
+/// nothing after the line is taken from the connection.
+fn read_pairing(stream: &mut TcpStream) -> Option<String> {
+    stream.set_read_timeout(Some(PAIR_TIMEOUT)).ok()?;
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        match stream.read(&mut byte) {
+            Ok(1) if byte[0] == b'\n' => break,
+            Ok(1) if line.len() < MAX_PAIR_LINE => line.push(byte[0]),
+            _ => return None,
+        }
+    }
+    stream.set_read_timeout(None).ok()?;
+    let line = String::from_utf8(line).ok()?;
+    line.trim_end().strip_prefix("PAIR\t").map(str::to_string)
+}
+
+fn run_connection(stream: TcpStream, peer: String, tx: mpsc::Sender<Msg>) {
+    let (writer_tx, writer_rx) = mpsc::channel::<String>();
+    let (Ok(mut write_half), Ok(shutdown_handle)) = (stream.try_clone(), stream.try_clone()) else {
+        let _ = tx.send(Msg::Collab(CollabEvent::Failed(format!(
+            "{peer}: failed to clone socket"
+        ))));
+        return;
+    };
+
+    thread::spawn(move || {
+        for line in writer_rx {
+            if writeln!(write_half, "{line}").is_err() {
+                break;
+            }
+        }
+    });
+
+    if tx
+        .send(Msg::Collab(CollabEvent::Connected {
+            peer: peer.clone(),
+            stream: shutdown_handle,
+            writer: writer_tx,
+        }))
+        .is_err()
+    {
+        return;
+    }
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if tx.send(Msg::Collab(CollabEvent::Line(line))).is_err() {
+            return;
+        }
+    }
+
+    let _ = tx.send(Msg::Collab(CollabEvent::Disconnected(peer)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wired(role: Role, text: &str) -> (CollabSession, mpsc::Receiver<String>) {
+        let (tx, rx) = mpsc::channel();
+        let mut session = CollabSession::new(role, None, text.to_string());
+        session.writer = Some(tx);
+        (session, rx)
+    }
+
+    fn apply(text: &str, update: RemoteUpdate) -> String {
+        let RemoteUpdate::Apply(ops) = update else {
+            return text.to_string();
+        };
+        let mut rope = Rope::from_str(text);
+        ot::apply(&mut rope, &ops);
+        rope.to_string()
+    }
+
+    #[test]
+    fn test_concurrent_edits_converge_through_host() {
+        let (mut host, host_out) = wired(Role::Host, "shared note");
+        let (mut client, client_out) = wired(Role::Client, "shared note");
+
+        host.local_change("my shared note");
+        client.local_change("shared note!");
+
+        let host_op = host_out.try_recv().unwrap();
+        let client_op = client_out.try_recv().unwrap();
+
+        let host_text = apply("my shared note", host.remote_line(&client_op));
+        let ack = host_out.try_recv().unwrap();
+        let client_text = apply("shared note!", client.remote_line(&host_op));
+        assert_eq!(client.remote_line(&ack), RemoteUpdate::None);
+
+        assert_eq!(host_text, "my shared note!");
+        assert_eq!(client_text, host_text);
+    }
+
+    #[test]
+    fn test_host_only_pairs_with_its_token() {
+        let (host_tx, host_rx) = mpsc::channel();
+        let (mut host, local) = CollabSession::host(
+            "127.0.0.1:0",
+            PathBuf::from("shared.md"),
+            "shared note".to_string(),
+            host_tx,
+        )
+        .unwrap();
+        let token = host.token.clone().unwrap();
+        assert_eq!(token.len(), 12);
+
+        let (client_tx, _client_rx) = mpsc::channel();
+        CollabSession::join(&local, "wrong", client_tx.clone());
+        assert!(
+            host_rx.recv_timeout(Duration::from_millis(500)).is_err(),
+            "a wrong token must not connect"
+        );
+
+        CollabSession::join(&local, &token, client_tx);
+        match host_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(Msg::Collab(CollabEvent::Connected { peer, .. })) => {
+                assert!(peer.starts_with("127.0.0.1:"), "{peer}")
+            }
+            other => panic!("expected the peer to connect, got {other:?}"),
+        }
+        host.stop();
+    }
+
+    #[test]
+    fn test_client_buffers_edits_until_ack() {
+        let (mut client, client_out) = wired(Role::Client, "a");
+        client.local_change("ab");
+        client.local_change("abc");
+
+        assert!(client_out.try_recv().unwrap().starts_with("OP\t0\t"));
+        assert!(client_out.try_recv().is_err(), "second edit waits for ack");
+
+        client.remote_line("ACK\t1");
+        let flushed = client_out.try_recv().unwrap();
+        assert!(flushed.starts_with("OP\t1\t"), "got {flushed}");
+    }
+}
@@ -0,0 +1,184 @@
+//! Mermaid diagram previews.
+//!
+//! The block is rendered to a PNG by mermaid-cli (`mmdc`) on a background
+//! thread. Terminals that speak the kitty graphics protocol show it in a
+//! popup; everywhere else it opens in the desktop image viewer.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use ratatui::layout::Rect;
+
+use crate::msg::Msg;
+
+/// A diagram waiting to be rendered.
+pub struct Render {
+    /// Renderer program, `editor.mermaid_command`.
+    pub command: String,
+    pub source: String,
+    pub tx: mpsc::Sender<Msg>,
+}
+
+impl Render {
+    pub fn spawn(self) {
+        std::thread::spawn(move || {
+            let result = self.render();
+            if let Err(err) = &result {
+                tracing::warn!("diagram: {err}");
+            }
+            let _ = self.tx.send(Msg::DiagramRendered(result));
+        });
+    }
+
+    fn render(&self) -> Result<PathBuf, String> {
+        let program = find_program(&self.command).ok_or_else(|| {
+            format!(
+                "diagram: renderer `{}` not found (install @mermaid-js/mermaid-cli)",
+                self.command
+            )
+        })?;
+
+        let dir = cache_dir().ok_or_else(|| "diagram: no cache directory".to_string())?;
+        std::fs::create_dir_all(&dir).map_err(|err| format!("diagram: {err}"))?;
+        let mut hasher = DefaultHasher::new();
+        self.source.hash(&mut hasher);
+        let stem = format!("{:016x}", hasher.finish());
+        let output = dir.join(format!("{stem}.png"));
+        // Unchanged diagrams are not re-rendered.
+        if output.is_file() {
+            return Ok(output);
+        }
+
+        let input = dir.join(format!("{stem}.mmd"));
+        std::fs::write(&input, &self.source).map_err(|err| format!("diagram: {err}"))?;
+        let result = Command::new(&program)
+            .arg("-i")
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .args(["-b", "transparent"])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|err| format!("diagram: cannot run {}: {err}", program.display()))?;
+        if !result.status.success() || !output.is_file() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            let reason = stderr
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map_or_else(|| result.status.to_string(), str::to_string);
+            return Err(format!("diagram: {reason}"));
+        }
+        Ok(output)
+    }
+}
+
+/// Rendered diagrams, kept in the user's own cache directory so other local
+/// users cannot plant or swap them.
+fn cache_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "blackbox")
+        .map(|project_dirs| project_dirs.cache_dir().join("diagrams"))
+}
+
+/// `command` as given when it contains a path separator, else the first
+/// match on `$PATH`.
+fn find_program(command: &str) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{command}.cmd"), format!("{command}.exe")]
+    } else {
+        vec![command.to_string()]
+    };
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Whether the terminal can draw images with the kitty graphics protocol.
+pub fn kitty_graphics() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+}
+
+/// Escape sequence drawing the PNG at `path` scaled into `area`, leaving the
+/// cursor where it was.
+pub fn kitty_place(path: &Path, area: Rect) -> String {
+    format!(
+        "\x1b7\x1b[{};{}H\x1b_Gf=100,t=f,a=T,q=2,c={},r={};{}\x1b\\\x1b8",
+        area.y + 1,
+        area.x + 1,
+        area.width,
+        area.height,
+        base64(path.to_string_lossy().as_bytes())
+    )
+}
+
+/// Escape sequence removing every image placed by [`kitty_place`].
+pub const KITTY_CLEAR: &str = "\x1b_Ga=d,q=2\x1b\\";
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_pads_to_whole_quads() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"/tmp/a.png"), "L3RtcC9hLnBuZw==");
+    }
+
+    #[test]
+    fn test_kitty_place_is_one_based_and_scaled() {
+        let area = Rect::new(2, 3, 40, 10);
+        assert_eq!(
+            kitty_place(Path::new("/tmp/a.png"), area),
+            "\x1b7\x1b[4;3H\x1b_Gf=100,t=f,a=T,q=2,c=40,r=10;L3RtcC9hLnBuZw==\x1b\\\x1b8"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program_takes_paths_as_given() {
+        let dir = crate::test_util::temp_dir();
+        let program = dir.path().join("mmdc");
+        std::fs::write(&program, "").unwrap();
+        let given = program.to_string_lossy();
+        assert_eq!(find_program(&given), Some(program.clone()));
+        assert_eq!(find_program(&format!("{given}-missing")), None);
+        assert!(find_program("sh").is_some_and(|sh| sh.is_file()));
+        assert_eq!(find_program("blackbox-no-such-renderer"), None);
+    }
+}
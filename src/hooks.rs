@@ -0,0 +1,219 @@
+//! User shell hooks run on note lifecycle events.
+//!
+//! A hook is a shell command from `[hooks]` in the config. It runs in the
+//! background from the vault directory with the note path as `$1` and in
+//! `BLACKBOX_NOTE`; output goes to the log and, briefly, to a notification.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use crate::msg::Msg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Create,
+    Save,
+    Rename,
+    Delete,
+}
+
+impl HookEvent {
+    /// Label for the status bar while the hook runs.
+    pub fn activity(self) -> String {
+        format!("hook {}", self.name())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Create => "on_create",
+            HookEvent::Save => "on_save",
+            HookEvent::Rename => "on_rename",
+            HookEvent::Delete => "on_delete",
+        }
+    }
+}
+
+/// A hook ready to run for one note.
+pub struct Invocation {
+    pub command: String,
+    pub event: HookEvent,
+    pub path: PathBuf,
+    /// Previous path, for renames.
+    pub old_path: Option<PathBuf>,
+    pub vault: PathBuf,
+    pub tx: mpsc::Sender<Msg>,
+}
+
+impl Invocation {
+    /// Run on a background thread.
+    pub fn spawn(self) {
+        std::thread::spawn(move || self.run());
+    }
+
+    /// Run to completion on the current thread, reporting failures and any
+    /// output as a [`Msg::HookFinished`] (sent even when quiet, so the
+    /// status bar knows the hook is done).
+    pub fn run(self) {
+        let event = self.event.name();
+        let result = self.execute();
+        match &result {
+            Ok(output) => tracing::info!("hook {event} {}: ok {output}", self.path.display()),
+            Err(err) => tracing::warn!("hook {event} {}: {err}", self.path.display()),
+        }
+        // Quiet hooks stay quiet; anything printed is worth a glance.
+        let note = match result {
+            Ok(output) if output.is_empty() => Ok(output),
+            Ok(output) => Ok(format!("{event}: {output}")),
+            Err(err) => Err(err),
+        };
+        let _ = self.tx.send(Msg::HookFinished(self.event, note));
+    }
+
+    /// Report the hook as not run, so the status bar stops showing it.
+    pub fn skip(self, reason: &str) {
+        let event = self.event.name();
+        let _ = self.tx.send(Msg::HookFinished(
+            self.event,
+            Err(format!("{event}: not run, {reason}")),
+        ));
+    }
+
+    fn execute(&self) -> Result<String, String> {
+        let mut cmd = shell(&self.command);
+        cmd.arg(&self.path)
+            .current_dir(&self.vault)
+            .env("BLACKBOX_EVENT", self.event.name())
+            .env("BLACKBOX_NOTE", &self.path)
+            .env("BLACKBOX_VAULT", &self.vault)
+            .stdin(Stdio::null());
+        if let Some(old) = &self.old_path {
+            cmd.arg(old).env("BLACKBOX_OLD_PATH", old);
+        }
+
+        let output = cmd
+            .output()
+            .map_err(|err| format!("cannot run hook: {err}"))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() {
+            Ok(summary(&stdout).unwrap_or_default())
+        } else {
+            let reason = summary(&stderr)
+                .or_else(|| summary(&stdout))
+                .unwrap_or_else(|| output.status.to_string());
+            Err(format!("{}: {reason}", self.event.name()))
+        }
+    }
+}
+
+/// `sh -c <command> blackbox <args…>` so the script sees the note as `$1`.
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command, "blackbox"]);
+        cmd
+    }
+}
+
+/// Last non-empty line of hook output.
+fn summary(output: &str) -> Option<String> {
+    output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn invocation(command: &str, old_path: Option<&str>, vault: PathBuf) -> Invocation {
+        Invocation {
+            command: command.to_string(),
+            event: if old_path.is_some() {
+                HookEvent::Rename
+            } else {
+                HookEvent::Save
+            },
+            path: PathBuf::from("new.md"),
+            old_path: old_path.map(PathBuf::from),
+            vault,
+            tx: mpsc::channel().0,
+        }
+    }
+
+    #[test]
+    fn test_hook_sees_note_args_and_env() {
+        let vault = crate::test_util::temp_dir();
+        let vault = vault.path().to_path_buf();
+        let script = r#"echo "$1|$2|$BLACKBOX_EVENT|$BLACKBOX_NOTE|$BLACKBOX_OLD_PATH"; pwd"#;
+
+        let output = invocation(script, None, vault.clone()).execute().unwrap();
+        // Only the last line is kept: the vault as the working directory.
+        assert_eq!(
+            std::fs::canonicalize(&output).unwrap(),
+            std::fs::canonicalize(&vault).unwrap()
+        );
+
+        let script = r#"echo "$1|$2|$BLACKBOX_EVENT|$BLACKBOX_NOTE|$BLACKBOX_OLD_PATH""#;
+        assert_eq!(
+            invocation(script, None, vault.clone()).execute(),
+            Ok("new.md||on_save|new.md|".to_string())
+        );
+        assert_eq!(
+            invocation(script, Some("old.md"), vault.clone()).execute(),
+            Ok("new.md|old.md|on_rename|new.md|old.md".to_string())
+        );
+        assert_eq!(
+            invocation(
+                &format!("[ \"$BLACKBOX_VAULT\" = '{}' ]", vault.display()),
+                None,
+                vault.clone()
+            )
+            .execute(),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_reports_its_stderr() {
+        let vault = crate::test_util::temp_dir();
+        let vault = vault.path().to_path_buf();
+        assert_eq!(
+            invocation("echo out; echo oops >&2; exit 3", None, vault.clone()).execute(),
+            Err("on_save: oops".to_string())
+        );
+        assert_eq!(
+            invocation("echo only stdout; exit 1", None, vault.clone()).execute(),
+            Err("on_save: only stdout".to_string())
+        );
+        assert_eq!(
+            invocation("exit 2", None, vault).execute(),
+            Err("on_save: exit status: 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_skipped_hook_still_finishes() {
+        let (tx, rx) = mpsc::channel();
+        let hook = Invocation {
+            tx,
+            ..invocation("true", None, PathBuf::from("."))
+        };
+        hook.skip("save failed: disk full");
+        let Ok(Msg::HookFinished(HookEvent::Save, result)) = rx.try_recv() else {
+            panic!("expected a finished hook");
+        };
+        assert_eq!(
+            result,
+            Err("on_save: not run, save failed: disk full".to_string())
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Local control socket so `blackbox --uri …` can hand a link to the
+//! instance that is already running instead of starting a second one.
+//!
+//! The protocol is one request per connection, `open <uri>\n` or
+//! `capture <text>\n`, answered with `ok\n`. Only Unix domain sockets are
+//! supported; elsewhere every `--uri` invocation starts a new instance and
+//! `--capture` writes to the inbox note itself.
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use crate::msg::Msg;
+
+pub fn socket_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        let dir = project_dirs
+            .runtime_dir()
+            .unwrap_or_else(|| project_dirs.data_dir());
+        return dir.join("blackbox.sock");
+    }
+
+    std::env::temp_dir().join("blackbox.sock")
+}
+
+/// Removes the socket file when the server owning it shuts down.
+pub struct ServerGuard {
+    path: PathBuf,
+}
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{ServerGuard, socket_path};
+    use crate::msg::Msg;
+
+    /// Send one request line to a running instance. Fails when none is
+    /// listening.
+    pub fn send(request: &str) -> std::io::Result<()> {
+        let mut stream = UnixStream::connect(socket_path())?;
+        stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+        writeln!(stream, "{request}")?;
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!(
+                "unexpected reply: {}",
+                reply.trim()
+            )))
+        }
+    }
+
+    pub fn serve(tx: mpsc::Sender<Msg>) -> Option<ServerGuard> {
+        let path = socket_path();
+        if UnixStream::connect(&path).is_ok() {
+            tracing::info!("ipc: another instance owns {}", path.display());
+            return None;
+        }
+        let _ = std::fs::remove_file(&path);
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::warn!("ipc: cannot bind {}: {err}", path.display());
+                return None;
+            }
+        };
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A client that connects and never writes must not stall
+                // every later request.
+                if stream
+                    .set_read_timeout(Some(Duration::from_secs(2)))
+                    .is_err()
+                {
+                    continue;
+                }
+                let mut line = String::new();
+                let mut reader = BufReader::new(&stream);
+                if reader.read_line(&mut line).is_err() {
+                    continue;
+                }
+                let line = line.trim_end();
+                let msg = if let Some(uri) = line.strip_prefix("open ") {
+                    Msg::OpenUri(uri.to_string())
+                } else if let Some(text) = line.strip_prefix("capture ") {
+                    Msg::Capture(text.to_string())
+                } else {
+                    let _ = writeln!(&stream, "error: unknown request");
+                    continue;
+                };
+                if tx.send(msg).is_err() {
+                    return;
+                }
+                let _ = writeln!(&stream, "ok");
+            }
+        });
+
+        Some(ServerGuard { path })
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::sync::mpsc;
+
+    use super::ServerGuard;
+    use crate::msg::Msg;
+
+    pub fn send(_request: &str) -> std::io::Result<()> {
+        Err(std::io::Error::other("ipc is only supported on Unix"))
+    }
+
+    pub fn serve(_tx: mpsc::Sender<Msg>) -> Option<ServerGuard> {
+        None
+    }
+}
+
+/// Ask a running instance to open `uri`. Fails when none is listening.
+pub fn send_open(uri: &str) -> std::io::Result<()> {
+    imp::send(&format!("open {uri}"))
+}
+
+/// Hand `text` to a running instance's `:capture`, folded onto one line.
+/// Fails when none is listening.
+pub fn send_capture(text: &str) -> std::io::Result<()> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    imp::send(&format!("capture {text}"))
+}
+
+/// Listen for requests from later invocations, forwarding them as
+/// [`Msg::OpenUri`] and [`Msg::Capture`]. Returns `None` if another instance
+/// already listens.
+pub fn serve(tx: mpsc::Sender<Msg>) -> Option<ServerGuard> {
+    imp::serve(tx)
+}
@@ -1,31 +1,45 @@
 mod app;
+mod clipboard;
+mod collab;
+mod diagram;
+mod hooks;
+mod ipc;
 mod model;
 mod msg;
+mod opener;
 mod plugin;
+mod profile;
+mod sync;
+#[cfg(test)]
+mod test_util;
 mod update;
 mod view;
+mod watcher;
 
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
 use anyhow::Result;
-use crossterm::event::{self, Event};
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, Event,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
-use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 
 use app::App;
 use model::config::AppConfig;
 use msg::Msg;
+use profile::StartupProfile;
 
 fn main() -> Result<()> {
+    let mut profile = StartupProfile::default();
+
     // Handle --version / -V before any terminal setup
     let args: Vec<String> = std::env::args().collect();
     if args.iter().any(|a| a == "--version" || a == "-V") {
@@ -33,6 +47,17 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(text) = flag_arg(&args, "--capture") {
+        return capture(&text);
+    }
+
+    let uri = flag_arg(&args, "--uri");
+    if let Some(uri) = uri.as_deref()
+        && ipc::send_open(uri).is_ok()
+    {
+        return Ok(());
+    }
+
     // Initialize logging to file (never stdout)
     let log_dir = directories::ProjectDirs::from("", "", "blackbox")
         .map(|d| d.data_dir().to_path_buf())
@@ -48,22 +73,38 @@ fn main() -> Result<()> {
 
     tracing::info!("blackbox starting");
 
-    let config = AppConfig::load()?;
+    let mut config = profile.time("config load", AppConfig::load)?;
+    if args.iter().any(|a| a == "--safe") {
+        tracing::info!("safe mode: plugins and hooks disabled");
+        config.enter_safe_mode();
+    }
+
+    let profiling = args.iter().any(|a| a == "--startup-profile");
 
     // Terminal setup
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run(&mut terminal, config);
+    let result = run(&mut terminal, config, uri, &mut profile, profiling);
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
+    if profiling {
+        for line in profile.summary() {
+            eprintln!("{line}");
+        }
+    }
     if let Err(e) = result {
         eprintln!("blackbox error: {e:?}");
     }
@@ -71,10 +112,48 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, config: AppConfig) -> Result<()> {
+/// `<flag> <value>` or `<flag>=<value>`.
+fn flag_arg(args: &[String], flag: &str) -> Option<String> {
+    args.iter().enumerate().find_map(|(idx, arg)| {
+        if arg == flag {
+            args.get(idx + 1).cloned()
+        } else {
+            arg.strip_prefix(flag)?
+                .strip_prefix('=')
+                .map(str::to_string)
+        }
+    })
+}
+
+/// `blackbox --capture <text>`: hand the text to a running instance, or
+/// append it to the inbox note directly when none is running.
+fn capture(text: &str) -> Result<()> {
+    if text.trim().is_empty() {
+        anyhow::bail!("usage: blackbox --capture <text>");
+    }
+    if ipc::send_capture(text).is_ok() {
+        return Ok(());
+    }
+    let config = AppConfig::load()?;
+    let bullet = model::capture::bullet(text, model::journal::now_secs());
+    model::capture::append(&config.inbox_path(), &bullet)?;
+    println!("captured to {}", config.general.inbox);
+    Ok(())
+}
+
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    config: AppConfig,
+    uri: Option<String>,
+    profile: &mut StartupProfile,
+    profiling: bool,
+) -> Result<()> {
     let (tx, rx) = mpsc::channel::<Msg>();
-    let vault_path = config.vault_path();
-    let mut app = App::new(config, tx.clone())?;
+    let mut app = App::new(config, tx.clone(), profile)?;
+    let _ipc_server = ipc::serve(tx.clone());
+    if let Some(uri) = uri {
+        let _ = tx.send(Msg::OpenUri(uri));
+    }
 
     // Input thread — reads terminal events and forwards as Msg
     let tx_input = tx.clone();
@@ -85,6 +164,7 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, config: AppConfig)
                     Event::Key(k) => Msg::Key(k),
                     Event::Mouse(m) => Msg::Mouse(m),
                     Event::Resize(w, h) => Msg::Resize(w, h),
+                    Event::Paste(text) => Msg::Paste(text),
                     _ => continue,
                 };
                 if tx_input.send(msg).is_err() {
@@ -105,8 +185,17 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, config: AppConfig)
         }
     });
 
-    // File watcher thread — emits FileChanged for create/modify/remove events.
-    spawn_file_watcher(vault_path, tx.clone());
+    profile.time("first render", || terminal.draw(|f| app.view(f)))?;
+    profile.finish();
+    if profiling {
+        for line in profile.summary() {
+            tracing::info!("{line}");
+        }
+        app.push_notification(format!(
+            "startup took {:.0}ms, details in the log",
+            profile.total().as_secs_f64() * 1000.0
+        ));
+    }
 
     // ── Main event loop ──
     loop {
@@ -121,50 +210,20 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, config: AppConfig)
         if app.should_quit {
             // Final save before exit
             app.update(Msg::SaveAllBuffers)?;
+            app.save_search_index();
+            app.save_link_index();
+            app.save_recent_files();
             break;
         }
 
         terminal.draw(|f| app.view(f))?;
+        // Inline images and other sequences ratatui does not model.
+        if !app.terminal_escape.is_empty() {
+            let backend = terminal.backend_mut();
+            backend.write_all(std::mem::take(&mut app.terminal_escape).as_bytes())?;
+            backend.flush()?;
+        }
     }
 
     Ok(())
 }
-
-fn spawn_file_watcher(vault_path: PathBuf, tx: mpsc::Sender<Msg>) {
-    thread::spawn(move || {
-        let tx_watch = tx.clone();
-        let mut watcher: RecommendedWatcher =
-            match notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
-                Ok(event) => {
-                    if matches!(
-                        event.kind,
-                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
-                    ) {
-                        for path in event.paths {
-                            if tx_watch.send(Msg::FileChanged(path)).is_err() {
-                                return;
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("file watcher error: {err}");
-                }
-            }) {
-                Ok(w) => w,
-                Err(err) => {
-                    tracing::warn!("failed to initialize file watcher: {err}");
-                    return;
-                }
-            };
-
-        if let Err(err) = watcher.watch(&vault_path, RecursiveMode::Recursive) {
-            tracing::warn!("failed to watch vault path {}: {err}", vault_path.display());
-            return;
-        }
-
-        loop {
-            thread::park();
-        }
-    });
-}
@@ -0,0 +1,76 @@
+//! Background work in flight (sync passes, hooks, diagram renders), named
+//! for the status bar spinner so disk and CPU use have a visible cause.
+
+use std::time::{Duration, Instant};
+
+const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const FRAME_TIME: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+pub struct Activities {
+    /// Labels of running tasks and when each started, oldest first.
+    running: Vec<(String, Instant)>,
+}
+
+impl Activities {
+    pub fn start(&mut self, label: impl Into<String>) {
+        self.running.push((label.into(), Instant::now()));
+    }
+
+    /// Mark the oldest task with `label` as done.
+    pub fn finish(&mut self, label: &str) {
+        if let Some(idx) = self
+            .running
+            .iter()
+            .position(|(running, _)| running == label)
+        {
+            self.running.remove(idx);
+        }
+    }
+
+    pub fn is_running(&self, label: &str) -> bool {
+        self.running.iter().any(|(running, _)| running == label)
+    }
+
+    /// Spinner and the newest task, e.g. `⠹ sync: restic +1`.
+    pub fn indicator(&self, now: Instant) -> Option<String> {
+        let (label, _) = self.running.last()?;
+        let (_, oldest) = self.running.first()?;
+        let ticks = now.saturating_duration_since(*oldest).as_millis() / FRAME_TIME.as_millis();
+        let frame = FRAMES[ticks as usize % FRAMES.len()];
+        let more = match self.running.len() {
+            1 => String::new(),
+            n => format!(" +{}", n - 1),
+        };
+        Some(format!("{frame} {label}{more}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicator_names_newest_task() {
+        let mut activities = Activities::default();
+        assert_eq!(activities.indicator(Instant::now()), None);
+
+        activities.start("sync: restic");
+        activities.start("hook on_save");
+        activities.start("hook on_save");
+        let started = activities.running[0].1;
+        assert_eq!(
+            activities.indicator(started + Duration::from_millis(250)),
+            Some("⠹ hook on_save +2".to_string())
+        );
+
+        activities.finish("hook on_save");
+        activities.finish("sync: restic");
+        assert_eq!(
+            activities.indicator(started).as_deref(),
+            Some("⠋ hook on_save")
+        );
+        activities.finish("hook on_save");
+        assert!(activities.running.is_empty());
+    }
+}
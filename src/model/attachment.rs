@@ -0,0 +1,157 @@
+//! Files in the vault that are not notes: images, PDFs and other binaries
+//! that belong to the desktop's viewer rather than the editor.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::journal;
+use super::link;
+
+/// Extensions embedded with `![]()` rather than linked.
+const IMAGE_EXTS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+/// Extensions opened outside the editor regardless of their content.
+const BINARY_EXTS: &[&str] = &[
+    "pdf", "epub", "zip", "gz", "tar", "7z", "mp3", "wav", "ogg", "flac", "m4a", "mp4", "mkv",
+    "mov", "webm", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp",
+];
+
+/// Bytes read when sniffing a file of unknown type.
+const SNIFF_LEN: usize = 8192;
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+pub fn is_image(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| IMAGE_EXTS.contains(&ext.as_str()))
+}
+
+/// Whether `path` should go to the system handler instead of a buffer: a
+/// known image or binary extension, or a NUL byte near the start of a file
+/// that is not a note.
+pub fn is_attachment(path: &Path) -> bool {
+    match extension(path).as_deref() {
+        Some("md") => false,
+        Some(ext) if IMAGE_EXTS.contains(&ext) || BINARY_EXTS.contains(&ext) => true,
+        _ => looks_binary(path),
+    }
+}
+
+fn looks_binary(path: &Path) -> bool {
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    if file.take(SNIFF_LEN as u64).read_to_end(&mut head).is_err() {
+        return false;
+    }
+    head.contains(&0)
+}
+
+/// Where a file named `name` goes in `dir`, with `-2`, `-3`… before the
+/// extension when the name is taken.
+pub fn target(dir: &Path, name: &str, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    let first = dir.join(name);
+    if !exists(&first) {
+        return first;
+    }
+    let stem = first
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = first
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| first.with_file_name(format!("{stem}-{n}{ext}")))
+        .find(|candidate| !exists(candidate))
+        .expect("some suffix is free")
+}
+
+/// `pasted-image-YYYYMMDDHHMMSS.png` (UTC) for an image pasted at
+/// `timestamp`.
+pub fn pasted_image_name(timestamp: u64) -> String {
+    let secs = timestamp % 86_400;
+    format!(
+        "pasted-image-{}{:02}{:02}{:02}.png",
+        journal::format_day(timestamp).replace('-', ""),
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Markdown for `file` in a note in `note_dir`: an image embed or a link
+/// labelled with the file name.
+pub fn link_text(note_dir: &Path, file: &Path) -> String {
+    let url = link::relative_path(note_dir, file);
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if is_image(file) {
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("!{}", link::markdown_link(&stem, &url))
+    } else {
+        link::markdown_link(&name, &url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_attachments() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(&dir).unwrap();
+        let blob = dir.join("data.bin");
+        std::fs::write(&blob, b"ab\0cd").unwrap();
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "plain text").unwrap();
+
+        assert!(is_attachment(Path::new("paper.PDF")));
+        assert!(is_attachment(Path::new("shot.png")));
+        assert!(!is_attachment(Path::new("note.md")));
+        assert!(is_attachment(&blob));
+        assert!(!is_attachment(&text));
+    }
+
+    #[test]
+    fn test_target_and_link() {
+        let dir = Path::new("/vault/attachments");
+        let taken = |path: &Path| path.ends_with("shot.png") || path.ends_with("shot-2.png");
+        assert_eq!(
+            target(dir, "shot.png", taken),
+            PathBuf::from("/vault/attachments/shot-3.png")
+        );
+        assert_eq!(
+            target(dir, "paper.pdf", taken),
+            PathBuf::from("/vault/attachments/paper.pdf")
+        );
+
+        let note_dir = Path::new("/vault/projects");
+        assert_eq!(
+            link_text(note_dir, Path::new("/vault/attachments/shot 1.png")),
+            "![shot 1](../attachments/shot%201.png)"
+        );
+        assert_eq!(
+            link_text(
+                Path::new("/vault"),
+                Path::new("/vault/attachments/paper.pdf")
+            ),
+            "[paper.pdf](attachments/paper.pdf)"
+        );
+        assert_eq!(
+            pasted_image_name(1_760_450_400 + 3_723),
+            "pasted-image-20251014150203.png"
+        );
+    }
+}
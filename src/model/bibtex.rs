@@ -0,0 +1,332 @@
+//! Citations against a BibTeX bibliography: reading `.bib` entries and
+//! finding pandoc-style `@key` / `[@key, p. 4]` citations in note text.
+//!
+//! Columns are byte offsets into the line, like [`Cursor`](super::cursor).
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibEntry {
+    /// Entry type, lowercased (`article`, `book`…).
+    pub kind: String,
+    pub key: String,
+    /// 0-indexed line of the `@type{` opener in the file.
+    pub line: usize,
+    /// Fields in file order, names lowercased and values without braces.
+    pub fields: Vec<(String, String)>,
+}
+
+impl BibEntry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `Doe and Roe (2020) Title`, for completion lists.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(author) = self.field("author").or_else(|| self.field("editor")) {
+            parts.push(author.to_string());
+        }
+        if let Some(year) = self.field("year") {
+            parts.push(format!("({year})"));
+        }
+        if let Some(title) = self.field("title") {
+            parts.push(title.to_string());
+        }
+        parts.join(" ")
+    }
+
+    /// The formatted reference, then every field, for the `:cite` popup.
+    pub fn reference(&self) -> Vec<String> {
+        let mut head = String::new();
+        if let Some(author) = self.field("author").or_else(|| self.field("editor")) {
+            head.push_str(&format!("**{author}**"));
+        }
+        if let Some(year) = self.field("year") {
+            head.push_str(&format!(" ({year})"));
+        }
+        if let Some(title) = self.field("title") {
+            head.push_str(&format!(". *{title}*"));
+        }
+        if let Some(venue) = ["journal", "booktitle", "publisher"]
+            .iter()
+            .find_map(|name| self.field(name))
+        {
+            head.push_str(&format!(". {venue}"));
+        }
+        let mut lines = vec![
+            head.trim_start_matches(". ").to_string() + ".",
+            String::new(),
+        ];
+        lines.push(format!("`@{}{{{}}}`", self.kind, self.key));
+        lines.extend(
+            self.fields
+                .iter()
+                .map(|(name, value)| format!("- {name}: {value}")),
+        );
+        lines
+    }
+}
+
+/// Entries of a `.bib` file, reloaded when the file changes.
+#[derive(Debug, Clone)]
+pub struct Bibliography {
+    pub path: PathBuf,
+    pub entries: Vec<BibEntry>,
+    modified: Option<SystemTime>,
+}
+
+impl Bibliography {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("bibliography {}: {err}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: parse(&text),
+            modified: modified(path),
+        })
+    }
+
+    /// Whether the file on disk is newer than the entries.
+    pub fn is_stale(&self) -> bool {
+        modified(&self.path) != self.modified
+    }
+
+    /// The entry cited as `key`: exact first, then ignoring case as BibTeX
+    /// itself does.
+    pub fn get(&self, key: &str) -> Option<&BibEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .find(|entry| entry.key.eq_ignore_ascii_case(key))
+            })
+    }
+}
+
+/// Modification time of `path`, `None` when it cannot be read.
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Every entry in `text`, skipping `@comment`, `@preamble` and `@string`.
+pub fn parse(text: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while let Some(offset) = text[at..].find('@') {
+        let start = at + offset;
+        at = start + 1;
+        let rest = &text[at..];
+        let kind_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let kind = rest[..kind_len].to_lowercase();
+        let Some(open) = rest[kind_len..].trim_start().chars().next() else {
+            break;
+        };
+        if kind.is_empty() || !matches!(open, '{' | '(') {
+            continue;
+        }
+        let body_start = at + kind_len + rest[kind_len..].find(open).unwrap_or(0) + 1;
+        let close = if open == '{' { '}' } else { ')' };
+        let Some(body_len) = balanced_len(&text[body_start..], open, close) else {
+            break;
+        };
+        let body = &text[body_start..body_start + body_len];
+        at = body_start + body_len + 1;
+        if matches!(kind.as_str(), "comment" | "preamble" | "string") {
+            continue;
+        }
+        let Some((key, fields)) = body.split_once(',') else {
+            continue;
+        };
+        entries.push(BibEntry {
+            kind,
+            key: key.trim().to_string(),
+            line: text[..start].matches('\n').count(),
+            fields: parse_fields(fields),
+        });
+    }
+    entries
+}
+
+/// Length of `text` up to the `close` matching an already consumed `open`.
+fn balanced_len(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, ch) in text.char_indices() {
+        if ch == open {
+            depth += 1;
+        } else if ch == close {
+            if depth == 0 {
+                return Some(idx);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+fn parse_fields(body: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut rest = body;
+    while let Some((name, after)) = rest.split_once('=') {
+        let name = name.trim().trim_start_matches(',').trim().to_lowercase();
+        let after = after.trim_start();
+        let (value, len) = match after.chars().next() {
+            Some('{') => match balanced_len(&after[1..], '{', '}') {
+                Some(len) => (&after[1..1 + len], len + 2),
+                None => (&after[1..], after.len()),
+            },
+            Some('"') => match after[1..].find('"') {
+                Some(len) => (&after[1..1 + len], len + 2),
+                None => (&after[1..], after.len()),
+            },
+            _ => {
+                let len = after.find(',').unwrap_or(after.len());
+                (after[..len].trim(), len)
+            }
+        };
+        if !name.is_empty() {
+            fields.push((name, clean(value)));
+        }
+        rest = &after[len..];
+    }
+    fields
+}
+
+/// A field value without grouping braces and with whitespace collapsed.
+fn clean(value: &str) -> String {
+    value
+        .replace(['{', '}'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Characters allowed inside a citation key; it must start and end with a
+/// word character, so `@doe2020.` cites `doe2020`.
+fn key_char(ch: char) -> bool {
+    ch.is_alphanumeric() || "_:.#$%&-+?<>~/".contains(ch)
+}
+
+/// Whether an `@` at `at` opens a citation rather than an e-mail address
+/// or a handle glued to a word.
+fn opens_citation(line: &str, at: usize) -> bool {
+    line[..at]
+        .chars()
+        .next_back()
+        .is_none_or(|prev| prev.is_whitespace() || "[;(-".contains(prev))
+}
+
+/// Byte range of the key (without the `@`) of an `@` that opens a
+/// citation.
+fn key_range(line: &str, at: usize) -> Option<Range<usize>> {
+    if !opens_citation(line, at) {
+        return None;
+    }
+    let start = at + 1;
+    let len = line[start..]
+        .find(|c: char| !key_char(c))
+        .unwrap_or(line.len() - start);
+    let key = line[start..start + len].trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_');
+    key.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        .then(|| start..start + key.len())
+}
+
+/// The next citation at or after `from`: the `@key` range, `@` included.
+pub fn next_citation(line: &str, from: usize) -> Option<Range<usize>> {
+    line[from..].match_indices('@').find_map(|(offset, _)| {
+        let at = from + offset;
+        key_range(line, at).map(|key| at..key.end)
+    })
+}
+
+/// Key of the citation under `col`, or just before it so a cursor left
+/// at the end of the key still counts.
+pub fn citation_at(line: &str, col: usize) -> Option<&str> {
+    let mut from = 0;
+    while let Some(range) = next_citation(line, from) {
+        if (range.start..=range.end).contains(&col) {
+            return Some(&line[range.start + 1..range.end]);
+        }
+        from = range.end;
+    }
+    None
+}
+
+/// The key typed so far when `col` follows `@` and key characters, e.g. `doe`
+/// in `see [@doe|`; empty straight after the `@`.
+pub fn partial_before(line: &str, col: usize) -> Option<&str> {
+    let before = &line[..col];
+    let at = before.rfind('@')?;
+    let partial = &before[at + 1..];
+    (partial.chars().all(key_char) && opens_citation(line, at)).then_some(partial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"@comment{ignored, x = 1}
+@Article{doe2020,
+  author = {Doe, Jane and Roe, Rick},
+  title  = {On {Notes} and
+            Links},
+  journal = "Journal of Things",
+  year = 2020,
+}
+
+@book(roe:2019, title = {A Book}, publisher = {Press})
+"#;
+
+    #[test]
+    fn test_parse_entries_and_fields() {
+        let entries = parse(BIB);
+        assert_eq!(entries.len(), 2);
+        let doe = &entries[0];
+        assert_eq!(
+            (doe.kind.as_str(), doe.key.as_str(), doe.line),
+            ("article", "doe2020", 1)
+        );
+        assert_eq!(doe.field("title"), Some("On Notes and Links"));
+        assert_eq!(doe.field("journal"), Some("Journal of Things"));
+        assert_eq!(doe.field("year"), Some("2020"));
+        assert_eq!(
+            doe.summary(),
+            "Doe, Jane and Roe, Rick (2020) On Notes and Links"
+        );
+        assert_eq!(
+            doe.reference()[0],
+            "**Doe, Jane and Roe, Rick** (2020). *On Notes and Links*. Journal of Things."
+        );
+        assert_eq!(entries[1].key, "roe:2019");
+        assert_eq!(entries[1].line, 9);
+        assert_eq!(entries[1].reference()[0], "*A Book*. Press.");
+    }
+
+    #[test]
+    fn test_citations_in_text() {
+        let line = "As [@doe2020, p. 4; -@roe:2019] and @doe2020. say; mail me@x.org";
+        assert_eq!(next_citation(line, 0), Some(4..12));
+        assert_eq!(citation_at(line, 8), Some("doe2020"));
+        assert_eq!(citation_at(line, 22), Some("roe:2019"));
+        assert_eq!(citation_at(line, 40), Some("doe2020"));
+        assert_eq!(citation_at(line, 44), Some("doe2020"));
+        assert_eq!(citation_at(line, 62), None);
+        assert_eq!(next_citation(line, 46), None);
+
+        assert_eq!(partial_before("see [@do", 8), Some("do"));
+        assert_eq!(partial_before("see @", 5), Some(""));
+        assert_eq!(partial_before("me@x", 4), None);
+        assert_eq!(partial_before("[@doe, p", 8), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! `^block-id` markers: a ` ^id` at the end of a paragraph names it for
+//! `[[Note^id]]` links and `![[Note^id]]` embeds.
+
+use std::ops::Range;
+
+/// Length of ids made by [`new_id`].
+const ID_LEN: usize = 6;
+
+fn id_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-'
+}
+
+/// The id of the ` ^id` marker ending `line`.
+pub fn marker(line: &str) -> Option<&str> {
+    let (before, id) = line.trim_end().rsplit_once('^')?;
+    (!id.is_empty()
+        && id.chars().all(id_char)
+        && (before.is_empty() || before.ends_with(char::is_whitespace)))
+    .then_some(id)
+}
+
+/// `line` without its ` ^id` marker, for showing an embedded block.
+pub fn strip_marker(line: &str) -> &str {
+    match marker(line) {
+        Some(id) => line.trim_end()[..line.trim_end().len() - id.len() - 1].trim_end(),
+        None => line,
+    }
+}
+
+/// Headings and list items are blocks of their own.
+fn stands_alone(line: &str) -> bool {
+    let line = line.trim_start();
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    let numbered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    (1..=6).contains(&hashes) && line[hashes..].starts_with(' ')
+        || ["- ", "* ", "+ "]
+            .iter()
+            .any(|bullet| line.starts_with(bullet))
+        || numbered.len() < line.len() && numbered.starts_with(". ")
+}
+
+/// Rows of the block around `row`: its paragraph, or the heading or list
+/// item on it. `None` on a blank line.
+pub fn paragraph_at(lines: &[String], row: usize) -> Option<Range<usize>> {
+    let line = lines.get(row)?;
+    if line.trim().is_empty() {
+        return None;
+    }
+    if stands_alone(line) {
+        return Some(row..row + 1);
+    }
+    let joins = |line: &String| !line.trim().is_empty() && !stands_alone(line);
+    let start = lines[..row]
+        .iter()
+        .rposition(|line| !joins(line))
+        .map_or(0, |before| before + 1);
+    let end = lines[row..]
+        .iter()
+        .position(|line| !joins(line))
+        .map_or(lines.len(), |after| row + after);
+    Some(start..end)
+}
+
+/// Rows of the block marked `^id`, ending at the marker.
+pub fn find(lines: &[String], id: &str) -> Option<Range<usize>> {
+    let end = lines.iter().position(|line| marker(line) == Some(id))?;
+    let block = paragraph_at(lines, end)?;
+    Some(block.start..end + 1)
+}
+
+/// A short id from `seed` that `taken` does not already know.
+pub fn new_id(seed: u64, taken: impl Fn(&str) -> bool) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut state = seed;
+    loop {
+        // SplitMix64 steps keep consecutive seeds apart.
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut bits = state;
+        bits = (bits ^ (bits >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        bits ^= bits >> 31;
+        let id: String = (0..ID_LEN)
+            .map(|i| char::from(DIGITS[(bits >> (i * 6)) as usize % DIGITS.len()]))
+            .collect();
+        if !taken(&id) {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_markers() {
+        assert_eq!(marker("a quote ^quote-1"), Some("quote-1"));
+        assert_eq!(marker("^solo  "), Some("solo"));
+        assert_eq!(marker("2^10"), None);
+        assert_eq!(marker("a ^bad!"), None);
+        assert_eq!(strip_marker("a quote ^quote-1"), "a quote");
+        assert_eq!(strip_marker("x^2"), "x^2");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let note = lines("# Title\nfirst line\nsecond ^quote\n\n- item ^it\n- next\n1. one\ntext");
+        assert_eq!(paragraph_at(&note, 1), Some(1..3));
+        assert_eq!(paragraph_at(&note, 0), Some(0..1));
+        assert_eq!(paragraph_at(&note, 3), None);
+        assert_eq!(paragraph_at(&note, 5), Some(5..6));
+        assert_eq!(paragraph_at(&note, 6), Some(6..7));
+        assert_eq!(paragraph_at(&note, 7), Some(7..8));
+        assert_eq!(find(&note, "quote"), Some(1..3));
+        assert_eq!(find(&note, "it"), Some(4..5));
+        assert_eq!(find(&note, "missing"), None);
+
+        let id = new_id(7, |_| false);
+        assert_eq!(id.len(), ID_LEN);
+        assert!(id.chars().all(id_char));
+        assert_ne!(new_id(7, |taken| taken == id), id);
+    }
+}
@@ -1,8 +1,10 @@
 use ropey::Rope;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
-use super::cursor::CursorState;
+use super::cursor::{CursorState, Position};
+use super::frontmatter::Frontmatter;
 
 #[derive(Debug, Clone)]
 pub struct UndoEntry {
@@ -33,6 +35,11 @@ pub struct Viewport {
     pub top_line: usize,
     pub height: u16,
     pub scroll_off: u16,
+    /// Keep the cursor line in the middle of the viewport (zen mode).
+    pub typewriter: bool,
+    /// Frontmatter unfolded with `za` while `editor.conceal_frontmatter`
+    /// is on.
+    pub frontmatter_open: bool,
 }
 
 impl Default for Viewport {
@@ -41,12 +48,40 @@ impl Default for Viewport {
             top_line: 0,
             height: 24,
             scroll_off: 5,
+            typewriter: false,
+            frontmatter_open: false,
         }
     }
 }
 
+/// Word, character and line totals for a span of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextCounts {
+    pub words: usize,
+    pub chars: usize,
+    pub lines: usize,
+}
+
+impl TextCounts {
+    pub fn of(text: &str) -> Self {
+        Self {
+            words: text.split_whitespace().count(),
+            chars: text.chars().filter(|&c| c != '\n' && c != '\r').count(),
+            lines: text.lines().count(),
+        }
+    }
+}
+
+static NEXT_BUFFER_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_buffer_id() -> u64 {
+    NEXT_BUFFER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 /// A single text buffer backed by a Rope.
 pub struct Buffer {
+    /// Process-unique identity, so view caches can tell buffers apart.
+    pub id: u64,
     pub rope: Rope,
     pub path: Option<PathBuf>,
     pub dirty: bool,
@@ -54,12 +89,17 @@ pub struct Buffer {
     pub viewport: Viewport,
     pub save_debounce: Option<Instant>,
     pub undo_tree: UndoTree,
+    /// Display name for buffers that are not backed by a file (reports).
+    pub title: Option<String>,
+    /// Earliest line edited since the view last consumed it.
+    pub changed_from: Option<usize>,
 }
 
 impl Buffer {
     /// Create a new empty buffer.
     pub fn new() -> Self {
         Self {
+            id: next_buffer_id(),
             rope: Rope::new(),
             path: None,
             dirty: false,
@@ -67,13 +107,24 @@ impl Buffer {
             viewport: Viewport::default(),
             save_debounce: None,
             undo_tree: UndoTree::default(),
+            title: None,
+            changed_from: None,
         }
     }
 
+    /// Create an unsaved, file-less buffer holding generated report text.
+    pub fn report(title: &str, text: &str) -> Self {
+        let mut buffer = Self::new();
+        buffer.rope = Rope::from_str(text);
+        buffer.title = Some(title.to_string());
+        buffer
+    }
+
     /// Create a buffer from file contents.
     pub fn from_file(path: PathBuf) -> anyhow::Result<Self> {
         let text = std::fs::read_to_string(&path)?;
         Ok(Self {
+            id: next_buffer_id(),
             rope: Rope::from_str(&text),
             path: Some(path),
             dirty: false,
@@ -81,6 +132,8 @@ impl Buffer {
             viewport: Viewport::default(),
             save_debounce: None,
             undo_tree: UndoTree::default(),
+            title: None,
+            changed_from: None,
         })
     }
 
@@ -100,6 +153,22 @@ impl Buffer {
         self.undo_tree.last_edit_time = now;
     }
 
+    /// Record an undo snapshot unconditionally, so a discrete command is
+    /// never merged into the preceding burst of typing.
+    pub fn push_snapshot_now(&mut self) {
+        self.undo_tree.history.push(UndoEntry {
+            rope: self.rope.clone(),
+            cursor: self.cursor.clone(),
+        });
+        self.undo_tree.redo_stack.clear();
+        self.undo_tree.last_edit_time = Instant::now();
+    }
+
+    /// Record that lines from `row` onwards may have changed.
+    pub fn mark_changed(&mut self, row: usize) {
+        self.changed_from = Some(self.changed_from.map_or(row, |r| r.min(row)));
+    }
+
     pub fn undo(&mut self) -> bool {
         if let Some(entry) = self.undo_tree.history.pop() {
             self.undo_tree.redo_stack.push(UndoEntry {
@@ -109,6 +178,7 @@ impl Buffer {
             self.rope = entry.rope;
             self.cursor = entry.cursor;
             self.dirty = true;
+            self.mark_changed(0);
             return true;
         }
         false
@@ -123,11 +193,18 @@ impl Buffer {
             self.rope = entry.rope;
             self.cursor = entry.cursor;
             self.dirty = true;
+            self.mark_changed(0);
             return true;
         }
         false
     }
 
+    /// Unsaved text with no file to save it to, lost if the buffer is
+    /// replaced. Reports do not count: they are regenerated on demand.
+    pub fn has_unkept_text(&self) -> bool {
+        self.dirty && self.path.is_none() && self.title.is_none()
+    }
+
     /// Total number of lines in the buffer.
     pub fn line_count(&self) -> usize {
         self.rope.len_lines()
@@ -152,17 +229,19 @@ impl Buffer {
     /// Insert a character at the cursor position.
     pub fn insert_char(&mut self, ch: char) {
         self.push_snapshot();
-        let byte_idx = self.cursor_byte_offset();
-        self.rope.insert_char(byte_idx, ch);
+        let at = self.cursor_char_offset();
+        self.rope.insert_char(at, ch);
         self.cursor.col += ch.len_utf8();
         self.dirty = true;
+        self.mark_changed(self.cursor.row);
     }
 
     /// Insert a newline at the cursor position.
     pub fn insert_newline(&mut self) {
         self.push_snapshot();
-        let byte_idx = self.cursor_byte_offset();
-        self.rope.insert_char(byte_idx, '\n');
+        let at = self.cursor_char_offset();
+        self.rope.insert_char(at, '\n');
+        self.mark_changed(self.cursor.row);
         self.cursor.row += 1;
         self.cursor.col = 0;
         self.cursor.desired_col = 0;
@@ -176,32 +255,24 @@ impl Buffer {
             return;
         }
 
+        let at = self.cursor_char_offset();
         if self.cursor.col == 0 {
             // Join with previous line
             let prev_line_len = self
                 .line_text(self.cursor.row - 1)
                 .map(|l| l.len())
                 .unwrap_or(0);
-            let byte_idx = self.cursor_byte_offset();
             // delete the newline at end of previous line
-            self.rope.remove(byte_idx - 1..byte_idx);
+            self.rope.remove(at - 1..at);
             self.cursor.row -= 1;
             self.cursor.col = prev_line_len;
         } else {
-            let byte_idx = self.cursor_byte_offset();
-            // Find the previous character boundary
-            let prev_char_len = self
-                .rope
-                .byte_slice(..byte_idx)
-                .chunks()
-                .last()
-                .and_then(|s| s.chars().next_back())
-                .map(|c| c.len_utf8())
-                .unwrap_or(1);
-            self.rope.remove(byte_idx - prev_char_len..byte_idx);
+            let prev_char_len = self.rope.char(at - 1).len_utf8();
+            self.rope.remove(at - 1..at);
             self.cursor.col -= prev_char_len;
         }
 
+        self.mark_changed(self.cursor.row);
         self.dirty = true;
     }
 
@@ -215,43 +286,34 @@ impl Buffer {
             .map(|l| l.len())
             .unwrap_or(0);
 
-        if self.cursor.col >= line_len {
-            if self.cursor.row < self.line_count().saturating_sub(1) {
-                // delete the newline at end of current line
-                let byte_idx = self.cursor_byte_offset();
-                self.rope.remove(byte_idx..byte_idx + 1);
-            }
-        } else {
-            let byte_idx = self.cursor_byte_offset();
-            // Find the next character boundary
-            let next_char_len = self
-                .rope
-                .byte_slice(byte_idx..)
-                .chars()
-                .next()
-                .map(|c| c.len_utf8())
-                .unwrap_or(1);
-            self.rope.remove(byte_idx..byte_idx + next_char_len);
+        let at = self.cursor_char_offset();
+        if self.cursor.col < line_len || self.cursor.row < self.line_count().saturating_sub(1) {
+            // Past the end of the line this deletes its newline.
+            self.rope.remove(at..at + 1);
         }
 
+        self.mark_changed(self.cursor.row);
         self.dirty = true;
     }
 
-    pub fn delete_line(&mut self, row: usize) {
+    /// Delete line `row`, returning its text with the newline.
+    pub fn delete_line(&mut self, row: usize) -> String {
         self.push_snapshot();
         if row >= self.line_count() {
-            return;
+            return String::new();
         }
-        let start_idx = self.rope.line_to_byte(row);
+        let start_idx = self.rope.line_to_char(row);
         let end_idx = if row + 1 < self.line_count() {
-            self.rope.line_to_byte(row + 1)
+            self.rope.line_to_char(row + 1)
         } else {
-            self.rope.len_bytes()
+            self.rope.len_chars()
         };
 
-        if start_idx < self.rope.len_bytes() {
+        let removed = self.rope.slice(start_idx..end_idx).to_string();
+        if start_idx < self.rope.len_chars() {
             self.rope.remove(start_idx..end_idx);
         }
+        self.mark_changed(row);
 
         // Let cursor be clamped automatically or explicitly later
         if self.cursor.row > 0 && self.cursor.row >= self.line_count() {
@@ -260,6 +322,114 @@ impl Buffer {
         self.cursor.col = 0;
         self.cursor.desired_col = 0;
         self.dirty = true;
+        removed
+    }
+
+    /// Cursor position as a character offset into the whole buffer.
+    pub fn cursor_char_offset(&self) -> usize {
+        let byte = self.cursor_byte_offset().min(self.rope.len_bytes());
+        self.rope.byte_to_char(byte)
+    }
+
+    /// Move the cursor to a character offset, clamped to the buffer.
+    pub fn set_cursor_char_offset(&mut self, offset: usize) {
+        let offset = offset.min(self.rope.len_chars());
+        let row = self.rope.char_to_line(offset);
+        let col = self.rope.char_to_byte(offset) - self.rope.line_to_byte(row);
+        self.cursor.row = row;
+        self.cursor.col = col;
+        self.cursor.desired_col = col;
+        self.clamp_cursor();
+    }
+
+    /// Character offset of a `(row, byte col)` position, clamped to the buffer.
+    pub fn position_to_char(&self, pos: Position) -> usize {
+        let row = pos.row.min(self.rope.len_lines().saturating_sub(1));
+        let line_len = self.line_text(row).map_or(0, |l| l.len());
+        let byte = self.rope.line_to_byte(row) + pos.col.min(line_len);
+        self.rope.byte_to_char(byte.min(self.rope.len_bytes()))
+    }
+
+    /// Start a selection anchored at the cursor.
+    pub fn begin_selection(&mut self) {
+        let pos = self.cursor.position();
+        self.cursor.selection = Some((pos, pos));
+    }
+
+    /// Move the selection head to the cursor after a motion.
+    pub fn extend_selection(&mut self) {
+        let head = self.cursor.position();
+        if let Some((_, end)) = self.cursor.selection.as_mut() {
+            *end = head;
+        }
+    }
+
+    /// Character range covered by the selection. Charwise selections include
+    /// the character under the head; linewise ones cover whole lines.
+    pub fn selection_char_range(&self, linewise: bool) -> Option<std::ops::Range<usize>> {
+        let (anchor, head) = self.cursor.selection?;
+        let (first, last) = if (anchor.row, anchor.col) <= (head.row, head.col) {
+            (anchor, head)
+        } else {
+            (head, anchor)
+        };
+
+        if linewise {
+            let start = self.rope.line_to_char(first.row.min(self.rope.len_lines()));
+            let end = self
+                .rope
+                .line_to_char((last.row + 1).min(self.rope.len_lines()));
+            return Some(start..end);
+        }
+
+        let start = self.position_to_char(first);
+        let last_char = self.position_to_char(last);
+        let end = if last_char < self.rope.len_chars() && self.rope.char(last_char) != '\n' {
+            last_char + 1
+        } else {
+            last_char
+        };
+        Some(start..end)
+    }
+
+    pub fn selected_text(&self, linewise: bool) -> Option<String> {
+        let range = self.selection_char_range(linewise)?;
+        Some(self.rope.slice(range).to_string())
+    }
+
+    /// Byte range of line `row` covered by the selection, for highlighting.
+    pub fn selection_on_line(&self, row: usize, linewise: bool) -> Option<(usize, usize)> {
+        let range = self.selection_char_range(linewise)?;
+        let line_start = self.rope.line_to_char(row);
+        let line_len = self.line_text(row)?.chars().count();
+        let start = range.start.max(line_start);
+        let end = range.end.min(line_start + line_len);
+        if start > end || (start == end && !(linewise && range.contains(&line_start))) {
+            return None;
+        }
+
+        let line = self.rope.line(row);
+        Some((
+            line.char_to_byte(start - line_start),
+            line.char_to_byte(end - line_start),
+        ))
+    }
+
+    /// Remove the selected text as one undoable edit and clear the selection.
+    pub fn delete_selection(&mut self, linewise: bool) -> Option<String> {
+        let range = self.selection_char_range(linewise)?;
+        self.cursor.clear_selection();
+        if range.is_empty() {
+            return None;
+        }
+
+        self.push_snapshot_now();
+        let removed = self.rope.slice(range.clone()).to_string();
+        self.rope.remove(range.clone());
+        self.mark_changed(self.rope.char_to_line(range.start));
+        self.set_cursor_char_offset(range.start);
+        self.dirty = true;
+        Some(removed)
     }
 
     /// Compute the byte offset in the rope for the current cursor position.
@@ -268,6 +438,109 @@ impl Buffer {
         line_start + self.cursor.col
     }
 
+    /// Insert `text` at the cursor as one undo step, leaving the cursor after it.
+    pub fn insert_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.push_snapshot_now();
+        let at = self.cursor_char_offset();
+        self.rope.insert(at, text);
+        self.mark_changed(self.cursor.row);
+        self.set_cursor_char_offset(at + text.chars().count());
+        self.dirty = true;
+    }
+
+    /// Replace the selection with `text` as one undo step. Returns the
+    /// replaced text, or `None` without a selection.
+    pub fn replace_selection(&mut self, linewise: bool, text: &str) -> Option<String> {
+        let range = self.selection_char_range(linewise)?;
+        self.cursor.clear_selection();
+        Some(self.replace_chars(range, text))
+    }
+
+    /// Replace a character range with `text` as one undo step, leaving the
+    /// cursor after the inserted text. Returns the replaced text.
+    pub fn replace_chars(&mut self, range: std::ops::Range<usize>, text: &str) -> String {
+        self.push_snapshot_now();
+        let removed = self.rope.slice(range.clone()).to_string();
+        self.rope.remove(range.clone());
+        self.rope.insert(range.start, text);
+        self.mark_changed(self.rope.char_to_line(range.start));
+        self.set_cursor_char_offset(range.start + text.chars().count());
+        self.dirty = true;
+        removed
+    }
+
+    /// Replace several character ranges, all given against the current
+    /// text, as one undo step. The cursor keeps its place in the text around
+    /// it. Overlapping ranges are refused and leave the buffer untouched.
+    pub fn replace_many(
+        &mut self,
+        edits: &[(std::ops::Range<usize>, String)],
+    ) -> Result<(), String> {
+        let len = self.rope.len_chars();
+        let mut sorted: Vec<_> = edits
+            .iter()
+            .map(|(range, text)| (range.start.min(len)..range.end.min(len), text))
+            .collect();
+        sorted.sort_by_key(|(range, _)| (range.start, range.end));
+        if sorted
+            .windows(2)
+            .any(|pair| pair[0].0.end > pair[1].0.start)
+        {
+            return Err("edits overlap".to_string());
+        }
+        if sorted.is_empty() {
+            return Ok(());
+        }
+
+        self.push_snapshot_now();
+        let cursor = self.cursor_char_offset();
+        let mut shifted = cursor as isize;
+        for (range, text) in sorted.iter().rev() {
+            self.rope.remove(range.clone());
+            self.rope.insert(range.start, text);
+            if range.end <= cursor {
+                shifted += text.chars().count() as isize - range.len() as isize;
+            } else if range.start < cursor {
+                shifted = (range.start + text.chars().count()) as isize;
+            }
+        }
+        self.mark_changed(self.rope.char_to_line(sorted[0].0.start));
+        self.set_cursor_char_offset(shifted.max(0) as usize);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Append `text` at the end of the buffer as one undo step, leaving the
+    /// cursor where it is.
+    pub fn append_text(&mut self, text: &str) {
+        self.push_snapshot_now();
+        let end = self.rope.len_chars();
+        self.rope.insert(end, text);
+        self.mark_changed(self.rope.char_to_line(end));
+        self.dirty = true;
+    }
+
+    /// Replace whole lines `rows` with `lines` as one undo step, leaving the
+    /// cursor at the start of the first one.
+    pub fn replace_lines(&mut self, rows: std::ops::Range<usize>, lines: &[String]) {
+        let total = self.rope.len_lines();
+        let start = self.rope.line_to_char(rows.start.min(total));
+        let end = if rows.end >= total {
+            self.rope.len_chars()
+        } else {
+            self.rope.line_to_char(rows.end)
+        };
+        let mut text = lines.join("\n");
+        if end > start && self.rope.char(end - 1) == '\n' {
+            text.push('\n');
+        }
+        self.replace_chars(start..end, &text);
+        self.set_cursor_char_offset(start);
+    }
+
     /// Ensure the cursor stays within valid bounds.
     pub fn clamp_cursor(&mut self) {
         let max_row = self.rope.len_lines().saturating_sub(1);
@@ -285,6 +558,11 @@ impl Buffer {
         let off = self.viewport.scroll_off as usize;
         let height = self.viewport.height as usize;
 
+        if self.viewport.typewriter {
+            self.viewport.top_line = self.cursor.row.saturating_sub(height / 2);
+            return;
+        }
+
         if self.cursor.row < self.viewport.top_line + off {
             self.viewport.top_line = self.cursor.row.saturating_sub(off);
         }
@@ -293,6 +571,74 @@ impl Buffer {
         }
     }
 
+    /// Parse the YAML frontmatter block at the top of the buffer, if any.
+    pub fn frontmatter(&self) -> Option<Frontmatter> {
+        if self.line_text(0)?.trim_end() != "---" {
+            return None;
+        }
+
+        let mut text = String::new();
+        for idx in 0..self.line_count() {
+            let line = self.line_text(idx)?;
+            let closing = idx > 0 && line.trim_end() == "---";
+            text.push_str(&line);
+            text.push('\n');
+            if closing {
+                break;
+            }
+        }
+
+        Frontmatter::parse(&text)
+    }
+
+    /// Replace the frontmatter block (or insert one at the top) as a single
+    /// undoable edit, shifting the cursor to stay on the same body line. A
+    /// block left without fields is removed.
+    pub fn set_frontmatter(&mut self, frontmatter: &Frontmatter) {
+        self.push_snapshot_now();
+
+        let old_lines = self.frontmatter().map(|fm| fm.line_count).unwrap_or(0);
+        let end = self.rope.line_to_char(old_lines.min(self.line_count()));
+        let block = if frontmatter.fields.is_empty() {
+            String::new()
+        } else {
+            frontmatter.to_block()
+        };
+        let new_lines = block.lines().count();
+
+        self.rope.remove(0..end);
+        self.rope.insert(0, &block);
+        self.mark_changed(0);
+
+        if self.cursor.row >= old_lines {
+            self.cursor.row = self.cursor.row + new_lines - old_lines;
+        } else {
+            self.cursor.row = 0;
+            self.cursor.col = 0;
+            self.cursor.desired_col = 0;
+        }
+        self.clamp_cursor();
+        self.dirty = true;
+    }
+
+    /// `(done, total)` checkbox tasks in the buffer.
+    pub fn task_progress(&self) -> (usize, usize) {
+        let lines: Vec<String> = (0..self.line_count())
+            .filter_map(|i| self.line_text(i))
+            .collect();
+        super::task::task_progress(lines.iter().map(String::as_str))
+    }
+
+    /// Replace the whole buffer as one undoable edit, keeping the cursor on
+    /// the same row where possible.
+    pub fn replace_text(&mut self, text: &str) {
+        self.push_snapshot_now();
+        self.rope = Rope::from_str(text);
+        self.mark_changed(0);
+        self.clamp_cursor();
+        self.dirty = true;
+    }
+
     /// Count the total number of words in the buffer.
     pub fn word_count(&self) -> usize {
         let mut count = 0;
@@ -409,6 +755,230 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_typewriter_keeps_cursor_centered() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str(&"line\n".repeat(40));
+        buf.viewport.height = 10;
+        buf.viewport.typewriter = true;
+
+        buf.cursor.row = 20;
+        buf.scroll_to_cursor();
+        assert_eq!(buf.viewport.top_line, 15);
+
+        buf.cursor.row = 19;
+        buf.scroll_to_cursor();
+        assert_eq!(buf.viewport.top_line, 14);
+
+        buf.cursor.row = 2;
+        buf.scroll_to_cursor();
+        assert_eq!(buf.viewport.top_line, 0);
+    }
+
+    #[test]
+    fn test_set_frontmatter_inserts_and_replaces() {
+        use crate::model::frontmatter::FrontmatterValue;
+
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("# Note\nbody\n");
+        buf.cursor.row = 1;
+
+        let mut fm = Frontmatter::default();
+        fm.set("status", FrontmatterValue::Scalar("draft".to_string()));
+        buf.set_frontmatter(&fm);
+        assert_eq!(
+            buf.rope.to_string(),
+            "---\nstatus: draft\n---\n# Note\nbody\n"
+        );
+        assert_eq!(buf.cursor.row, 4);
+
+        let mut fm = buf.frontmatter().unwrap();
+        fm.set("status", FrontmatterValue::Scalar("final".to_string()));
+        buf.set_frontmatter(&fm);
+        assert_eq!(
+            buf.rope.to_string(),
+            "---\nstatus: final\n---\n# Note\nbody\n"
+        );
+        assert_eq!(buf.cursor.row, 4);
+
+        buf.set_frontmatter(&Frontmatter::default());
+        assert_eq!(buf.rope.to_string(), "# Note\nbody\n");
+        assert_eq!(buf.cursor.row, 1);
+
+        assert!(buf.undo());
+        assert!(buf.undo());
+        assert_eq!(
+            buf.frontmatter().unwrap().get_scalar("status"),
+            Some("draft")
+        );
+    }
+
+    #[test]
+    fn test_set_frontmatter_counts_chars_not_bytes() {
+        use crate::model::frontmatter::FrontmatterValue;
+
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("---\ntitle: Café über naïve\n---\nbody\n");
+        let mut fm = buf.frontmatter().unwrap();
+        fm.set("status", FrontmatterValue::Scalar("draft".to_string()));
+        buf.set_frontmatter(&fm);
+        assert_eq!(
+            buf.rope.to_string(),
+            "---\ntitle: Café über naïve\nstatus: draft\n---\nbody\n"
+        );
+    }
+
+    #[test]
+    fn test_cursor_char_offset_roundtrip() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("héllo\nwörld\n");
+        buf.cursor.row = 1;
+        buf.cursor.col = 3; // after "wö"
+        assert_eq!(buf.cursor_char_offset(), 8);
+
+        buf.set_cursor_char_offset(2);
+        assert_eq!((buf.cursor.row, buf.cursor.col), (0, 3));
+    }
+
+    #[test]
+    fn test_typing_and_deleting_around_multibyte_chars() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("“é”\n");
+        buf.cursor.move_to(0, "“é”".len());
+        buf.insert_char('!');
+        buf.insert_char('—');
+        assert_eq!(buf.line_text(0).unwrap(), "“é”!—");
+        buf.delete_char_before();
+        buf.cursor.move_to(0, "“".len());
+        buf.delete_char_forward();
+        assert_eq!(buf.line_text(0).unwrap(), "“”!");
+        assert_eq!(buf.cursor.col, "“".len());
+    }
+
+    #[test]
+    fn test_charwise_selection_is_inclusive() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("one two\nthree four\n");
+        buf.cursor.move_to(0, 4);
+        buf.begin_selection();
+        buf.cursor.move_to(1, 4);
+        buf.extend_selection();
+
+        assert_eq!(buf.selected_text(false).as_deref(), Some("two\nthree"));
+        assert_eq!(buf.selection_on_line(0, false), Some((4, 7)));
+        assert_eq!(buf.selection_on_line(1, false), Some((0, 5)));
+        assert_eq!(
+            buf.selected_text(true).as_deref(),
+            Some("one two\nthree four\n")
+        );
+    }
+
+    #[test]
+    fn test_delete_selection_is_undoable() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("abcdef\n");
+        buf.cursor.move_to(0, 4);
+        buf.begin_selection();
+        buf.cursor.move_to(0, 1);
+        buf.extend_selection();
+
+        assert_eq!(buf.delete_selection(false).as_deref(), Some("bcde"));
+        assert_eq!(buf.rope.to_string(), "af\n");
+        assert_eq!(buf.cursor.col, 1);
+        assert!(buf.cursor.selection.is_none());
+        assert!(buf.undo());
+        assert_eq!(buf.rope.to_string(), "abcdef\n");
+    }
+
+    #[test]
+    fn test_replace_selection_and_insert_text() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("see docs here\n");
+        buf.cursor.move_to(0, 4);
+        buf.begin_selection();
+        buf.cursor.move_to(0, 7);
+        buf.extend_selection();
+
+        let link = "[docs](https://é.io)";
+        assert_eq!(buf.replace_selection(false, link).as_deref(), Some("docs"));
+        assert_eq!(buf.rope.to_string(), "see [docs](https://é.io) here\n");
+        assert_eq!(buf.cursor_char_offset(), 4 + link.chars().count());
+        assert!(buf.undo());
+        assert_eq!(buf.rope.to_string(), "see docs here\n");
+
+        buf.cursor.move_to(0, 0);
+        buf.insert_text("a\nb");
+        assert_eq!(buf.rope.to_string(), "a\nbsee docs here\n");
+        assert_eq!((buf.cursor.row, buf.cursor.col), (1, 1));
+    }
+
+    #[test]
+    fn test_replace_many() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("one two three\n");
+        buf.cursor.move_to(0, 8);
+        let edits = vec![
+            (8..13, "3".to_string()),
+            (0..3, "uno".to_string()),
+            (4..7, "2".to_string()),
+        ];
+        buf.replace_many(&edits).unwrap();
+        assert_eq!(buf.rope.to_string(), "uno 2 3\n");
+        assert_eq!(buf.cursor_char_offset(), 6);
+        assert!(buf.dirty);
+
+        assert_eq!(
+            buf.replace_many(&[(0..3, "a".to_string()), (2..4, "b".to_string())]),
+            Err("edits overlap".to_string())
+        );
+        assert_eq!(buf.rope.to_string(), "uno 2 3\n");
+        assert!(buf.undo());
+        assert_eq!(buf.rope.to_string(), "one two three\n");
+    }
+
+    #[test]
+    fn test_replace_lines() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("a\nb c\nd");
+        buf.replace_lines(1..2, &["b".to_string(), "c".to_string()]);
+        assert_eq!(buf.rope.to_string(), "a\nb\nc\nd");
+        assert_eq!((buf.cursor.row, buf.cursor.col), (1, 0));
+
+        buf.replace_lines(2..4, &["c d".to_string()]);
+        assert_eq!(buf.rope.to_string(), "a\nb\nc d");
+        assert!(buf.undo());
+        assert_eq!(buf.rope.to_string(), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn test_edits_track_earliest_changed_line() {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str("a\nb\nc\n");
+        buf.cursor.move_to(2, 0);
+        buf.insert_char('x');
+        buf.cursor.move_to(1, 1);
+        buf.delete_char_before();
+        assert_eq!(buf.changed_from.take(), Some(1));
+
+        buf.cursor.move_to(1, 0);
+        buf.delete_char_before();
+        assert_eq!(buf.changed_from, Some(0));
+        assert_ne!(Buffer::new().id, buf.id);
+    }
+
+    #[test]
+    fn test_text_counts() {
+        let counts = TextCounts::of("two words\nand three more\n");
+        assert_eq!(
+            counts,
+            TextCounts {
+                words: 5,
+                chars: 23,
+                lines: 2
+            }
+        );
+    }
+
     #[test]
     fn test_from_file_roundtrip() {
         let content = "# Hello\n\nThis is a test note.\n";
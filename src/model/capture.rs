@@ -0,0 +1,63 @@
+//! Quick capture: timestamped bullets appended to the inbox note by
+//! `:capture`, `blackbox --capture` or a later invocation over IPC.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::model::journal;
+
+/// `- YYYY-MM-DD HH:MM text` (UTC), newline-terminated, with any line breaks
+/// in `text` folded into spaces so the bullet stays on one line.
+pub fn bullet(text: &str, timestamp: u64) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let minutes = timestamp / 60;
+    format!(
+        "- {} {:02}:{:02} {text}\n",
+        journal::format_day(timestamp),
+        minutes / 60 % 24,
+        minutes % 60
+    )
+}
+
+/// What to add after `existing` so `bullet` starts on its own line.
+pub fn appended(existing: &str, bullet: &str) -> String {
+    if existing.is_empty() || existing.ends_with('\n') {
+        bullet.to_string()
+    } else {
+        format!("\n{bullet}")
+    }
+}
+
+/// Append `bullet` to the note at `path` on disk, creating the note and its
+/// folder when they do not exist yet.
+pub fn append(path: &Path, bullet: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(appended(&existing, bullet).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bullet_and_append() {
+        // 2026-10-14 09:05:30 UTC
+        let bullet = bullet("call the\n  dentist ", 1_791_968_730);
+        assert_eq!(bullet, "- 2026-10-14 09:05 call the dentist\n");
+        assert_eq!(appended("", &bullet), bullet);
+        assert_eq!(appended("# Inbox\n", &bullet), bullet);
+        assert_eq!(appended("# Inbox", &bullet), format!("\n{bullet}"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sub/inbox.md");
+        append(&path, "- one\n").unwrap();
+        std::fs::write(&path, "- one").unwrap();
+        append(&path, "- two\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- one\n- two\n");
+    }
+}
@@ -0,0 +1,305 @@
+//! Index of fenced code block languages across the vault, refreshed lazily
+//! by modification time so only edited notes are re-read.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A fenced code block: its normalised language and the lines it spans,
+/// fences included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub lang: String,
+    pub lines: Range<usize>,
+    /// Whether a closing fence ends it; an unclosed block runs to the end
+    /// of the note.
+    pub closed: bool,
+}
+
+impl CodeBlock {
+    /// The lines between the fences in `lines`, with up to the opening
+    /// fence's indentation of spaces and tabs taken off each.
+    pub fn body<'a>(&self, lines: &'a [String]) -> Vec<&'a str> {
+        let fence = &lines[self.lines.start];
+        let indent = fence.len() - fence.trim_start_matches([' ', '\t']).len();
+        let end = if self.closed {
+            self.lines.end - 1
+        } else {
+            self.lines.end.min(lines.len())
+        };
+        lines[self.lines.start + 1..end]
+            .iter()
+            .map(|line| {
+                let strip = line
+                    .bytes()
+                    .take(indent)
+                    .take_while(|byte| *byte == b' ' || *byte == b'\t')
+                    .count();
+                &line[strip..]
+            })
+            .collect()
+    }
+}
+
+/// Canonical name for common language aliases (`py` → `python`).
+pub fn normalize_lang(raw: &str) -> String {
+    let lang = raw
+        .trim()
+        .trim_start_matches('{')
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '}')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('.')
+        .to_lowercase();
+    match lang.as_str() {
+        "py" | "python3" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "rs" => "rust",
+        "rb" => "ruby",
+        "sh" | "bash" | "zsh" | "shell" | "console" => "shell",
+        "yml" => "yaml",
+        "md" => "markdown",
+        "c++" | "cc" | "hpp" => "cpp",
+        "golang" => "go",
+        "" => "text",
+        other => other,
+    }
+    .to_string()
+}
+
+/// All fenced code blocks in `text`. A block closes on a line of nothing
+/// but its fence character, at least as many as opened it; an unclosed
+/// block runs to the end.
+pub fn code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, usize, char, usize)> = None;
+    let mut line_count = 0;
+
+    for (idx, line) in text.lines().enumerate() {
+        line_count = idx + 1;
+        let trimmed = line.trim_start();
+        let Some(fence) = trimmed.chars().next().filter(|ch| matches!(ch, '`' | '~')) else {
+            continue;
+        };
+        let run = trimmed.chars().take_while(|ch| *ch == fence).count();
+        if run < 3 {
+            continue;
+        }
+        let info = &trimmed[run..];
+
+        match &open {
+            Some((lang, start, opened_with, len))
+                if *opened_with == fence && run >= *len && info.trim().is_empty() =>
+            {
+                blocks.push(CodeBlock {
+                    lang: lang.clone(),
+                    lines: *start..idx + 1,
+                    closed: true,
+                });
+                open = None;
+            }
+            Some(_) => {}
+            None => open = Some((normalize_lang(info), idx, fence, run)),
+        }
+    }
+
+    if let Some((lang, start, _, _)) = open {
+        blocks.push(CodeBlock {
+            lang,
+            lines: start..line_count,
+            closed: false,
+        });
+    }
+    blocks
+}
+
+struct IndexedNote {
+    modified: Option<SystemTime>,
+    blocks: Vec<CodeBlock>,
+}
+
+/// Per-language totals for a stats report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LangUsage {
+    pub lang: String,
+    pub blocks: usize,
+    pub notes: usize,
+    pub lines: usize,
+}
+
+#[derive(Default)]
+pub struct CodeLangIndex {
+    notes: HashMap<PathBuf, IndexedNote>,
+}
+
+impl CodeLangIndex {
+    /// Bring the index in line with `paths`: re-read notes whose mtime
+    /// changed and forget the ones that disappeared.
+    pub fn refresh(&mut self, paths: &[PathBuf]) {
+        let present: HashSet<&PathBuf> = paths.iter().collect();
+        self.notes.retain(|path, _| present.contains(path));
+
+        for path in paths {
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            if self
+                .notes
+                .get(path)
+                .is_some_and(|note| note.modified.is_some() && note.modified == modified)
+            {
+                continue;
+            }
+            let blocks = std::fs::read_to_string(path)
+                .map(|text| code_blocks(&text))
+                .unwrap_or_default();
+            self.notes
+                .insert(path.clone(), IndexedNote { modified, blocks });
+        }
+    }
+
+    /// Blocks of `lang` (normalised) in `path`.
+    pub fn blocks_in(&self, path: &Path, lang: &str) -> Vec<&CodeBlock> {
+        let lang = normalize_lang(lang);
+        self.notes
+            .get(path)
+            .map(|note| note.blocks.iter().filter(|b| b.lang == lang).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn has_lang(&self, path: &Path, lang: &str) -> bool {
+        !self.blocks_in(path, lang).is_empty()
+    }
+
+    /// Languages by number of blocks, most used first.
+    pub fn usage(&self) -> Vec<LangUsage> {
+        let mut by_lang: HashMap<&str, LangUsage> = HashMap::new();
+        for note in self.notes.values() {
+            let mut seen: Vec<&str> = Vec::new();
+            for block in &note.blocks {
+                let entry = by_lang
+                    .entry(block.lang.as_str())
+                    .or_insert_with(|| LangUsage {
+                        lang: block.lang.clone(),
+                        blocks: 0,
+                        notes: 0,
+                        lines: 0,
+                    });
+                entry.blocks += 1;
+                entry.lines += block.lines.len().saturating_sub(2);
+                if !seen.contains(&block.lang.as_str()) {
+                    seen.push(&block.lang);
+                    entry.notes += 1;
+                }
+            }
+        }
+
+        let mut usage: Vec<LangUsage> = by_lang.into_values().collect();
+        usage.sort_by(|a, b| b.blocks.cmp(&a.blocks).then_with(|| a.lang.cmp(&b.lang)));
+        usage
+    }
+}
+
+/// Split a `lang:<name>` filter out of a finder query.
+pub fn split_lang_filter(query: &str) -> (Option<String>, String) {
+    let mut lang = None;
+    let mut rest = Vec::new();
+    for word in query.split_whitespace() {
+        match word.strip_prefix("lang:") {
+            Some(name) if !name.is_empty() => lang = Some(normalize_lang(name)),
+            Some(_) => {}
+            None => rest.push(word),
+        }
+    }
+    (lang, rest.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "# Notes\n```py\nprint(1)\n```\ntext\n~~~ {.rust}\nfn main() {}\n```\nstill rust\n~~~\n```\nplain\n";
+
+    #[test]
+    fn test_code_blocks_normalise_and_match_fences() {
+        let blocks = code_blocks(NOTE);
+        assert_eq!(
+            blocks,
+            vec![
+                CodeBlock {
+                    lang: "python".to_string(),
+                    lines: 1..4,
+                    closed: true,
+                },
+                CodeBlock {
+                    lang: "rust".to_string(),
+                    lines: 5..10,
+                    closed: true,
+                },
+                CodeBlock {
+                    lang: "text".to_string(),
+                    lines: 10..12,
+                    closed: false,
+                },
+            ]
+        );
+
+        // Shorter fences and fences with an info string stay inside.
+        let nested = "````md\n```sh\nls\n```\n```` not yet\n````\n";
+        assert_eq!(
+            code_blocks(nested),
+            vec![CodeBlock {
+                lang: "markdown".to_string(),
+                lines: 0..6,
+                closed: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_body_strips_the_fence_indent() {
+        let lines: Vec<String> = "  ```sh\n    ls\n \tcd\n\u{3000}x\n\u{a0} y\n  ```\n```\nopen"
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let blocks = code_blocks(&lines.join("\n"));
+        assert_eq!(
+            blocks[0].body(&lines),
+            ["  ls", "cd", "\u{3000}x", "\u{a0} y"]
+        );
+        assert_eq!(blocks[1].body(&lines), ["open"]);
+    }
+
+    #[test]
+    fn test_split_lang_filter() {
+        assert_eq!(
+            split_lang_filter("lang:PY async  retry"),
+            (Some("python".to_string()), "async retry".to_string())
+        );
+        assert_eq!(split_lang_filter("plain"), (None, "plain".to_string()));
+    }
+
+    #[test]
+    fn test_index_usage_and_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        std::fs::write(&a, "```python\nx\ny\n```\n```python\nz\n```\n").unwrap();
+        std::fs::write(&b, "```rust\nfn f() {}\n```\n").unwrap();
+
+        let mut index = CodeLangIndex::default();
+        let paths = vec![a.clone(), b.clone()];
+        index.refresh(&paths);
+        assert!(index.has_lang(&a, "py"));
+        assert!(!index.has_lang(&a, "rust"));
+        let usage = index.usage();
+        assert_eq!(usage[0].lang, "python");
+        assert_eq!((usage[0].blocks, usage[0].notes, usage[0].lines), (2, 1, 3));
+
+        index.refresh(&paths[..1]);
+        assert_eq!(index.usage().len(), 1);
+    }
+}
@@ -1,21 +1,86 @@
 use anyhow::{Result, anyhow};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct AppConfig {
     pub general: GeneralConfig,
     pub editor: EditorConfig,
     pub search: SearchConfig,
-    #[allow(dead_code)] // Phase 3: git sync feature
     pub sync: SyncConfig,
+    pub workflow: WorkflowConfig,
+    pub spell: SpellConfig,
+    pub citations: CitationsConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
     #[serde(default)]
     pub plugins: Vec<PluginConfig>,
+    #[serde(default)]
+    pub plugin_limits: PluginLimitsConfig,
+    #[serde(default)]
+    pub plugin_registry: PluginRegistryConfig,
+    /// Named vaults that `:vault <name>` switches between (`~` expands).
+    #[serde(default)]
+    pub vaults: BTreeMap<String, String>,
+    /// Set by `--safe`: third-party extensions stay off for this session.
+    #[serde(skip)]
+    pub safe_mode: bool,
+}
+
+/// Shell commands run in the background on note lifecycle events. Each gets
+/// the note path as `$1` (renames also pass the old path as `$2`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub on_create: Option<String>,
+    #[serde(default)]
+    pub on_save: Option<String>,
+    #[serde(default)]
+    pub on_rename: Option<String>,
+    #[serde(default)]
+    pub on_delete: Option<String>,
+}
+
+/// Bounds on every plugin call, so a runaway plugin cannot freeze the UI.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PluginLimitsConfig {
+    /// Wall-clock time per call before it is cancelled.
+    pub timeout_ms: u64,
+    /// Most linear memory a plugin may grow to.
+    pub memory_mb: u32,
+    /// Wasmtime fuel (roughly instructions) per call; 0 for no limit.
+    pub fuel: u64,
+    /// Failed calls within `failure_window_secs` that disable a plugin for
+    /// the session; 0 to keep calling it.
+    pub max_failures: u32,
+    pub failure_window_secs: u64,
+}
+
+impl Default for PluginLimitsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 2_000,
+            memory_mb: 64,
+            fuel: 0,
+            max_failures: 3,
+            failure_window_secs: 60,
+        }
+    }
+}
+
+/// Where `:plugins.browse` finds plugins to offer.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginRegistryConfig {
+    /// HTTPS URL of the JSON index; empty until one is chosen.
+    pub url: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct PluginConfig {
     #[serde(default)]
     #[allow(dead_code)] // Phase 3: plugin git source
@@ -32,16 +97,31 @@ pub struct PluginConfig {
     pub config: HashMap<String, toml::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct GeneralConfig {
     pub vault_path: String,
     pub scratch_file: String,
+    /// "off", "daily" or "size"; see `model::scratch`.
+    pub scratch_rotate: String,
+    pub scratch_max_kb: u64,
+    /// Vault-relative note listing rotated scratch notes.
+    pub scratch_index: String,
+    /// Vault-relative note that `:capture` appends to.
+    pub inbox: String,
+    /// Vault folder that `:bulk archive` moves notes into.
+    pub archive: String,
+    /// Vault folder that `:attach` copies files into.
+    pub attachments: String,
     pub auto_save_debounce_ms: u64,
+    /// Gitignore-style patterns for notes that are only saved explicitly.
+    pub autosave_exclude: Vec<String>,
     #[allow(dead_code)] // Phase 3: theme selection
     pub theme: String,
+    /// "auto", "on" or "off"; see `view::compat`.
+    pub compat: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct EditorConfig {
     #[allow(dead_code)] // Phase 3: tab expansion in editor widget
     pub tab_width: u16,
@@ -52,31 +132,109 @@ pub struct EditorConfig {
     #[allow(dead_code)]
     pub relative_line_numbers: bool,
     pub scroll_off: u16,
+    /// Hide markdown delimiters on lines the cursor is not on.
+    pub conceal: bool,
+    /// Fold the frontmatter block into one summary line (`za` unfolds it).
+    pub conceal_frontmatter: bool,
+    /// Lines of `![[embed]]` previews shown under the embed; 0 disables them.
+    pub embed_max_lines: usize,
+    /// Column width of the centred editor in zen mode.
+    pub zen_width: usize,
+    /// Column that `gq` and auto-wrap reflow prose to.
+    pub text_width: usize,
+    /// Hard-wrap prose at `text_width` while typing.
+    pub auto_wrap: bool,
+    /// Curly quotes and en/em dashes for `"`, `'`, `--` and `---` in prose.
+    pub smart_punctuation: bool,
+    /// Close `[`, `(` and backticks as they are typed, and delete pairs and
+    /// `**`-style markers together.
+    pub auto_pair: bool,
+    /// Show the minimap column at startup (`:minimap` toggles it).
+    pub minimap: bool,
+    /// Yanks and deletes kept for `:yanks`.
+    pub yank_history: usize,
+    /// Mermaid renderer for `:diagram` (mermaid-cli).
+    pub mermaid_command: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SearchConfig {
     pub max_results: usize,
     #[allow(dead_code)] // Phase 3: pass to WalkBuilder for content search
     pub ignore_patterns: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowConfig {
+    /// Frontmatter key holding the note's workflow state.
+    pub field: String,
+    /// Ordered states that `workflow.next` / `workflow.prev` step through.
+    pub states: Vec<String>,
+    /// States in which the note opens read-only.
+    pub readonly_states: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpellConfig {
+    /// Check spelling when blackbox starts (`:spell` toggles it at runtime).
+    pub enabled: bool,
+    /// Hunspell dictionary name, e.g. `en_US` for `en_US.aff` + `en_US.dic`.
+    pub language: String,
+    /// Searched before the blackbox config dir and the system hunspell dirs.
+    pub dictionary_dirs: Vec<String>,
+    /// Vault-relative word list that `zg` appends to.
+    pub custom_dictionary: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CitationsConfig {
+    /// BibTeX file for `@key` completion and `:cite`, vault-relative or
+    /// absolute (`~` expands). Empty turns citations off.
+    pub bibliography: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct SyncConfig {
-    #[allow(dead_code)] // Phase 3: sync backend selection
     pub backend: String,
-    #[allow(dead_code)] // Phase 3: git sync configuration
     pub git: Option<GitSyncConfig>,
+    #[serde(default)]
+    pub restic: Option<ResticSyncConfig>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResticSyncConfig {
+    /// restic repository string, e.g. `s3:s3.amazonaws.com/bucket/vault`,
+    /// `b2:bucket:vault` or `rclone:webdav:vault`.
+    pub repository: String,
+    /// Command printing the repository password (restic `--password-command`).
+    /// When unset restic falls back to `RESTIC_PASSWORD` / `RESTIC_PASSWORD_FILE`.
+    #[serde(default)]
+    pub password_command: Option<String>,
+    /// Minutes between background snapshots; 0 snapshots only on `:sync`.
+    #[serde(default = "default_restic_interval")]
+    pub interval_mins: u64,
+    /// Extra `--exclude` patterns on top of `.git` and `.trash`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default = "default_restic_binary")]
+    pub binary: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct GitSyncConfig {
-    #[allow(dead_code)] // Phase 3: auto-commit on save
+    /// Commit every change in the vault on each pass.
     pub auto_commit: bool,
-    #[allow(dead_code)] // Phase 3: auto-push to remote
+    /// Push local commits to the upstream branch on each pass.
     pub auto_push: bool,
-    #[allow(dead_code)] // Phase 3: commit message template
+    /// `{timestamp}` is replaced with the UTC time of the commit.
     pub commit_message_format: String,
+    /// Minutes between background pulls; 0 pulls only on `:sync` and
+    /// after saves.
+    #[serde(default)]
+    pub pull_interval_mins: u64,
+    /// Seconds after a save before the pass that commits and pushes it.
+    #[serde(default = "default_commit_delay")]
+    pub commit_delay_secs: u64,
 }
 
 impl AppConfig {
@@ -88,13 +246,12 @@ impl AppConfig {
         let defaults_str = include_str!("../../config/default.toml");
         let mut merged: toml::Table = toml::from_str(defaults_str)?;
 
-        if let Some(proj_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
-            let config_path = proj_dirs.config_dir().join("config.toml");
-            if config_path.exists() {
-                let user_str = fs::read_to_string(&config_path)?;
-                let user_table: toml::Table = toml::from_str(&user_str)?;
-                merge_tables(&mut merged, user_table);
-            }
+        if let Some(config_path) = user_config_path()
+            && config_path.exists()
+        {
+            let user_str = fs::read_to_string(&config_path)?;
+            let user_table: toml::Table = toml::from_str(&user_str)?;
+            merge_tables(&mut merged, user_table);
         }
 
         let mut config: AppConfig = toml::Value::Table(merged).try_into()?;
@@ -108,17 +265,69 @@ impl AppConfig {
                     .vault_path
                     .replacen('~', &home.to_string_lossy(), 1);
         }
+        for path in config.vaults.values_mut() {
+            if path.starts_with('~') {
+                let home = dirs_home().ok_or_else(|| anyhow!("cannot determine home directory"))?;
+                *path = path.replacen('~', &home.to_string_lossy(), 1);
+            }
+        }
 
         Ok(config)
     }
 
+    /// `--safe`: drop plugins and hooks so a crash or hang can be pinned on
+    /// (or cleared of) user extensions. The rest of the config still applies.
+    pub fn enter_safe_mode(&mut self) {
+        self.safe_mode = true;
+        self.plugins.clear();
+        self.hooks = HooksConfig::default();
+    }
+
     pub fn vault_path(&self) -> PathBuf {
         PathBuf::from(&self.general.vault_path)
     }
 
+    /// Name of the `[vaults]` entry for the open vault, if it has one.
+    pub fn vault_name(&self) -> Option<&str> {
+        let current = self.vault_path();
+        self.vaults
+            .iter()
+            .find(|(_, path)| current == Path::new(path.trim_end_matches('/')))
+            .map(|(name, _)| name.as_str())
+    }
+
     pub fn scratch_path(&self) -> PathBuf {
         self.vault_path().join(&self.general.scratch_file)
     }
+
+    pub fn inbox_path(&self) -> PathBuf {
+        self.vault_path().join(&self.general.inbox)
+    }
+
+    /// The configured `.bib` file, if any.
+    pub fn bibliography_path(&self) -> Option<PathBuf> {
+        let path = self.citations.bibliography.trim();
+        if path.is_empty() {
+            return None;
+        }
+        if let Some(rest) = path.strip_prefix("~/") {
+            return dirs_home().map(|home| home.join(rest));
+        }
+        Some(self.vault_path().join(path))
+    }
+
+    /// Matcher for `general.autosave_exclude`, rooted at the vault.
+    pub fn autosave_exclusions(&self) -> Result<Gitignore, String> {
+        let mut builder = GitignoreBuilder::new(self.vault_path());
+        for pattern in &self.general.autosave_exclude {
+            builder
+                .add_line(None, pattern)
+                .map_err(|err| format!("autosave_exclude: {err}"))?;
+        }
+        builder
+            .build()
+            .map_err(|err| format!("autosave_exclude: {err}"))
+    }
 }
 
 /// Recursively merge `src` into `dst`. Values in `src` override `dst`.
@@ -135,7 +344,88 @@ fn merge_tables(dst: &mut toml::Table, src: toml::Table) {
     }
 }
 
-fn dirs_home() -> Option<PathBuf> {
+/// `~/.config/blackbox/config.toml` or the platform's equivalent.
+pub fn user_config_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "blackbox")
+        .map(|proj_dirs| proj_dirs.config_dir().join("config.toml"))
+}
+
+/// `text` with a `[[plugins]]` entry for `key = value` appended, leaving
+/// the rest of the file as written.
+pub fn add_plugin_entry(text: &str, key: &str, value: &str) -> String {
+    let mut out = text.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let value = toml::Value::String(value.to_string());
+    out.push_str(&format!("[[plugins]]\n{key} = {value}\n"));
+    out
+}
+
+/// `text` without the `[[plugins]]` entries whose `key` is `value`, or
+/// `None` when there is none. Comments after an entry are kept, since they
+/// usually introduce the next one.
+pub fn remove_plugin_entry(text: &str, key: &str, value: &str) -> Option<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let headers: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with('['))
+        .map(|(idx, _)| idx)
+        .chain([lines.len()])
+        .collect();
+
+    let mut removed = Vec::new();
+    for pair in headers.windows(2) {
+        let (start, next) = (pair[0], pair[1]);
+        if lines[start].trim() != "[[plugins]]" {
+            continue;
+        }
+        let body = &lines[start + 1..next];
+        let matches = body.iter().any(|line| {
+            toml::from_str::<toml::Table>(line)
+                .ok()
+                .and_then(|table| table.get(key).and_then(|v| v.as_str().map(str::to_string)))
+                .is_some_and(|found| found == value)
+        });
+        if !matches {
+            continue;
+        }
+        let last = body
+            .iter()
+            .rposition(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map_or(start, |idx| start + 1 + idx);
+        let mut end = last + 1;
+        while end < next && lines[end].trim().is_empty() {
+            end += 1;
+        }
+        removed.push(start..end);
+    }
+    if removed.is_empty() {
+        return None;
+    }
+    let kept: String = lines
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !removed.iter().any(|range| range.contains(idx)))
+        .map(|(_, line)| *line)
+        .collect();
+    // Without the blank line that separated a last entry from the rest.
+    let trimmed = kept.trim_end();
+    Some(if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{trimmed}\n")
+    })
+}
+
+pub(crate) fn dirs_home() -> Option<PathBuf> {
     directories::BaseDirs::new().map(|d| d.home_dir().to_path_buf())
 }
 
@@ -143,6 +433,18 @@ fn default_plugin_enabled() -> bool {
     true
 }
 
+fn default_restic_interval() -> u64 {
+    60
+}
+
+fn default_commit_delay() -> u64 {
+    30
+}
+
+fn default_restic_binary() -> String {
+    "restic".to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,8 +457,49 @@ mod tests {
         assert!(config.is_ok(), "default config should parse: {config:?}");
         let cfg = config.unwrap();
         assert_eq!(cfg.general.scratch_file, ".scratch.md");
+        assert_eq!(cfg.general.inbox, "inbox.md");
+        assert_eq!(cfg.general.archive, "archive");
+        assert_eq!(cfg.general.attachments, "attachments");
+        assert_eq!(cfg.general.scratch_rotate, "off");
+        assert_eq!(cfg.general.scratch_max_kb, 512);
+        assert!(cfg.general.autosave_exclude.is_empty());
         assert_eq!(cfg.editor.scroll_off, 5);
+        assert!(!cfg.editor.conceal);
+        assert!(!cfg.editor.conceal_frontmatter);
+        assert_eq!(cfg.editor.embed_max_lines, 12);
+        assert_eq!(cfg.editor.zen_width, 80);
+        assert_eq!(cfg.editor.text_width, 80);
+        assert!(!cfg.editor.auto_wrap);
+        assert!(!cfg.editor.smart_punctuation);
+        assert!(!cfg.editor.auto_pair);
+        assert!(!cfg.editor.minimap);
+        assert_eq!(cfg.editor.yank_history, 20);
         assert_eq!(cfg.search.max_results, 50);
+        assert_eq!(cfg.workflow.field, "status");
+        assert_eq!(cfg.workflow.states, vec!["draft", "review", "final"]);
+        assert!(!cfg.spell.enabled);
+        assert_eq!(cfg.spell.language, "en_US");
+        assert_eq!(cfg.bibliography_path(), None);
+        assert!(cfg.hooks.on_save.is_none());
+    }
+
+    #[test]
+    fn test_safe_mode_drops_extensions() {
+        let mut config: AppConfig =
+            toml::from_str(include_str!("../../config/default.toml")).unwrap();
+        config.hooks.on_save = Some("echo saved".to_string());
+        config.plugins.push(PluginConfig {
+            repo: None,
+            branch: None,
+            path: Some(PathBuf::from("~/plugin")),
+            enabled: true,
+            config: HashMap::new(),
+        });
+
+        config.enter_safe_mode();
+        assert!(config.safe_mode);
+        assert!(config.plugins.is_empty());
+        assert!(config.hooks.on_save.is_none());
     }
 
     #[test]
@@ -185,6 +528,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_restic_section_fills_defaults() {
+        let defaults_str = include_str!("../../config/default.toml");
+        let mut merged: toml::Table = toml::from_str(defaults_str).unwrap();
+        let user_str =
+            "[sync]\nbackend = \"restic\"\n[sync.restic]\nrepository = \"b2:bucket:vault\"\n";
+        merge_tables(&mut merged, toml::from_str(user_str).unwrap());
+
+        let config: AppConfig = toml::Value::Table(merged).try_into().unwrap();
+        let restic = config.sync.restic.expect("restic section");
+        assert_eq!(config.sync.backend, "restic");
+        assert_eq!(restic.repository, "b2:bucket:vault");
+        assert_eq!(restic.interval_mins, 60);
+        assert_eq!(restic.binary, "restic");
+        assert!(
+            config.sync.git.is_some(),
+            "git defaults should be preserved"
+        );
+    }
+
+    #[test]
+    fn test_named_vaults() {
+        let defaults_str = include_str!("../../config/default.toml");
+        let mut merged: toml::Table = toml::from_str(defaults_str).unwrap();
+        let user_str = "[general]\nvault_path = \"/home/me/notes\"\n[vaults]\nwork = \"/srv/work\"\nnotes = \"/home/me/notes/\"\n";
+        merge_tables(&mut merged, toml::from_str(user_str).unwrap());
+
+        let mut config: AppConfig = toml::Value::Table(merged).try_into().unwrap();
+        assert_eq!(config.vaults.len(), 2);
+        assert_eq!(config.vault_name(), Some("notes"));
+        config.general.vault_path = "/elsewhere".to_string();
+        assert_eq!(config.vault_name(), None);
+    }
+
     #[test]
     fn test_deep_merge_partial_user_config() {
         let defaults_str = include_str!("../../config/default.toml");
@@ -230,6 +607,27 @@ mod tests {
         assert_eq!(config.editor.tab_width, 4);
     }
 
+    #[test]
+    fn test_autosave_exclusions_match_under_vault() {
+        let mut config: AppConfig =
+            toml::from_str(include_str!("../../config/default.toml")).unwrap();
+        config.general.vault_path = "/vault".to_string();
+        config.general.autosave_exclude = vec!["generated/".to_string(), "*.log.md".to_string()];
+        let matcher = config.autosave_exclusions().unwrap();
+        let excluded = |path: &str| matcher.matched_path_or_any_parents(path, false).is_ignore();
+        assert!(excluded("/vault/generated/report.md"));
+        assert!(excluded("/vault/daily/build.log.md"));
+        assert!(!excluded("/vault/notes/plan.md"));
+
+        config.general.autosave_exclude = vec!["{draft".to_string()];
+        assert!(
+            config
+                .autosave_exclusions()
+                .unwrap_err()
+                .starts_with("autosave_exclude:")
+        );
+    }
+
     #[test]
     fn test_merge_tables_recursive() {
         let mut dst: toml::Table = toml::from_str("[a]\nx = 1\ny = 2\n").unwrap();
@@ -239,4 +637,39 @@ mod tests {
         assert_eq!(a["x"].as_integer().unwrap(), 99, "src should override x");
         assert_eq!(a["y"].as_integer().unwrap(), 2, "y should be preserved");
     }
+
+    #[test]
+    fn test_plugin_entries_in_user_config() {
+        let text = "vault_path = \"~/notes\"\n\n[[plugins]]\nrepo = \"https://example.com/a\"\nenabled = true\n\n# my own\n[[plugins]]\npath = \"~/code/b\"\n";
+        let added = add_plugin_entry(text, "repo", "https://example.com/c");
+        assert!(added.starts_with(text));
+        assert!(added.ends_with("\n\n[[plugins]]\nrepo = \"https://example.com/c\"\n"));
+        let config: AppConfig = toml::from_str(&format!(
+            "{}\n{added}",
+            include_str!("../../config/default.toml")
+        ))
+        .unwrap();
+        assert_eq!(config.plugins.len(), 3);
+
+        assert_eq!(
+            remove_plugin_entry(&added, "repo", "https://example.com/a").as_deref(),
+            Some(
+                "vault_path = \"~/notes\"\n\n# my own\n[[plugins]]\npath = \"~/code/b\"\n\n[[plugins]]\nrepo = \"https://example.com/c\"\n"
+            )
+        );
+        assert_eq!(
+            remove_plugin_entry(&added, "path", "~/code/b").as_deref(),
+            Some(
+                "vault_path = \"~/notes\"\n\n[[plugins]]\nrepo = \"https://example.com/a\"\nenabled = true\n\n# my own\n[[plugins]]\nrepo = \"https://example.com/c\"\n"
+            )
+        );
+        assert_eq!(
+            remove_plugin_entry(text, "repo", "https://example.com/z"),
+            None
+        );
+        assert_eq!(
+            add_plugin_entry("", "repo", "r"),
+            "[[plugins]]\nrepo = \"r\"\n"
+        );
+    }
 }
@@ -7,20 +7,17 @@ pub struct CursorState {
     pub col: usize,
     /// Desired column for vertical movement ("sticky" column).
     pub desired_col: usize,
-    /// Active selection range, if any.
-    #[allow(dead_code)] // Phase 2: visual selection mode
+    /// Active selection as `(anchor, head)`, if any.
     pub selection: Option<(Position, Position)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)]
 pub struct Position {
     pub row: usize,
     pub col: usize,
 }
 
 impl CursorState {
-    #[allow(dead_code)] // Phase 2: used for selections
     pub fn position(&self) -> Position {
         Position {
             row: self.row,
@@ -35,7 +32,6 @@ impl CursorState {
         self.desired_col = col;
     }
 
-    #[allow(dead_code)] // Phase 2: visual selection mode
     pub fn clear_selection(&mut self) {
         self.selection = None;
     }
@@ -73,6 +69,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_clear_selection() {
         let mut c = CursorState::default();
         c.selection = Some((Position { row: 0, col: 0 }, Position { row: 1, col: 5 }));
@@ -0,0 +1,182 @@
+//! `![[Note]]` / `![[Note#Heading]]` / `![[Note^block]]` transclusions: parsing, picking the
+//! embedded excerpt, and a small mtime-checked cache of source notes.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+use regex::Regex;
+
+use crate::model::block;
+use crate::model::frontmatter::Frontmatter;
+use crate::model::outline;
+
+static EMBED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!\[\[([^\]|#^]+)(?:#([^\]|]+)|(\^[^\]|]+))?(?:\|[^\]]*)?\]\]")
+        .expect("valid embed regex")
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embed {
+    pub target: String,
+    pub heading: Option<String>,
+}
+
+/// Every embed on `line`, in order.
+pub fn embeds_in_line(line: &str) -> Vec<Embed> {
+    EMBED_RE
+        .captures_iter(line)
+        .map(|caps| Embed {
+            target: caps[1].trim().to_string(),
+            heading: caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|h| !h.is_empty()),
+        })
+        .collect()
+}
+
+/// Lines to show for an embed of `lines`: the section under `heading`
+/// (matched case-insensitively, heading line included), or the whole note
+/// without its frontmatter, `^id` markers hidden. `None` when the heading
+/// does not exist.
+pub fn excerpt(lines: &[String], heading: Option<&str>) -> Option<Vec<String>> {
+    excerpt_range(lines, heading).map(|range| {
+        lines[range]
+            .iter()
+            .map(|line| block::strip_marker(line).to_string())
+            .collect()
+    })
+}
+
+/// Line range behind [`excerpt`]. A `^id` anchor picks the paragraph that
+/// ends with that block id instead of a section.
+pub fn excerpt_range(lines: &[String], heading: Option<&str>) -> Option<Range<usize>> {
+    let Some(heading) = heading else {
+        let body = Frontmatter::parse(&lines.join("\n")).map_or(0, |fm| fm.line_count);
+        return Some(body.min(lines.len())..lines.len());
+    };
+
+    if let Some(id) = heading.strip_prefix('^') {
+        return block::find(lines, id);
+    }
+
+    let headings = outline::headings(lines.iter().map(String::as_str));
+    let found = headings
+        .iter()
+        .find(|h| h.title.eq_ignore_ascii_case(heading))?;
+    let (_, range) = outline::section_at(&headings, found.line, lines.len());
+    Some(range)
+}
+
+struct CachedNote {
+    modified: Option<SystemTime>,
+    lines: Vec<String>,
+}
+
+/// Contents of embedded notes, re-read when their mtime changes.
+#[derive(Default)]
+pub struct EmbedCache {
+    notes: HashMap<PathBuf, CachedNote>,
+}
+
+impl EmbedCache {
+    /// Lines of `path`, or `None` if it cannot be read.
+    pub fn lines(&mut self, path: &Path) -> Option<&[String]> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let stale = self
+            .notes
+            .get(path)
+            .is_none_or(|note| note.modified.is_none() || note.modified != modified);
+        if stale {
+            let Ok(text) = std::fs::read_to_string(path) else {
+                self.notes.remove(path);
+                return None;
+            };
+            let lines = text.lines().map(str::to_string).collect();
+            self.notes
+                .insert(path.to_path_buf(), CachedNote { modified, lines });
+        }
+        self.notes.get(path).map(|note| note.lines.as_slice())
+    }
+
+    /// Whether `path` is shown as an embed somewhere.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.notes.contains_key(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_embeds_in_line() {
+        assert_eq!(
+            embeds_in_line("See ![[Other Note#Setup|setup]] and ![[b]], not [[c]] ![[d^q]]"),
+            vec![
+                Embed {
+                    target: "Other Note".to_string(),
+                    heading: Some("Setup".to_string()),
+                },
+                Embed {
+                    target: "b".to_string(),
+                    heading: None,
+                },
+                Embed {
+                    target: "d".to_string(),
+                    heading: Some("^q".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_excerpt_section_and_whole_note() {
+        let note = lines(
+            "---\ntags: [x]\n---\n# Title\nintro\n## Setup\nstep\n### Detail\nmore\n## Usage\nuse\n",
+        );
+        assert_eq!(
+            excerpt(&note, Some("setup")).unwrap(),
+            lines("## Setup\nstep\n### Detail\nmore")
+        );
+        assert_eq!(excerpt(&note, None).unwrap()[0], "# Title");
+        assert!(excerpt(&note, Some("Missing")).is_none());
+
+        let blocks = lines("para\n\nfirst line\nsecond ^quote\n\nafter");
+        assert_eq!(excerpt_range(&blocks, Some("^quote")), Some(2..4));
+        assert_eq!(
+            excerpt(&blocks, Some("^quote")).unwrap(),
+            lines("first line\nsecond")
+        );
+        assert!(excerpt(&blocks, Some("^missing")).is_none());
+    }
+
+    #[test]
+    fn test_cache_reloads_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.md");
+        std::fs::write(&path, "one\n").unwrap();
+
+        let mut cache = EmbedCache::default();
+        assert_eq!(cache.lines(&path).unwrap(), ["one"]);
+        assert!(cache.contains(&path));
+
+        std::fs::write(&path, "two\nthree\n").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(cache.lines(&path).unwrap(), ["two", "three"]);
+    }
+}
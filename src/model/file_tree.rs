@@ -3,12 +3,38 @@ use ignore::WalkBuilder;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+use crate::model::metadata::MetadataIndex;
+use crate::model::trash;
+
+/// How the sidebar lays out the vault: the folder tree, or virtual groups of
+/// notes sharing a tag or a frontmatter value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Grouping {
+    #[default]
+    Folders,
+    Tags,
+    Field(String),
+}
+
+impl Grouping {
+    /// Frontmatter key the groups come from; `None` for the folder tree.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Grouping::Folders => None,
+            Grouping::Tags => Some("tags"),
+            Grouping::Field(key) => Some(key),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileNode {
     pub path: PathBuf,
     pub name: String,
     pub depth: usize,
     pub is_dir: bool,
+    /// Why the entry cannot be read, e.g. "permission denied".
+    pub problem: Option<String>,
 }
 
 pub struct FileTree {
@@ -18,12 +44,23 @@ pub struct FileTree {
     pub ignore_patterns: Vec<String>,
     expanded: HashSet<PathBuf>,
     pub create_input: String,
+    pub grouping: Grouping,
+    /// Notes listed in a section above the tree, in pin order.
+    pub pinned: Vec<PathBuf>,
+    /// Rows the pinned section takes at the top of `nodes`.
+    pinned_rows: usize,
+    metadata: MetadataIndex,
 }
 
 impl FileTree {
-    pub fn new(root: PathBuf, ignore_patterns: Vec<String>) -> Result<Self> {
+    pub fn new(root: PathBuf, mut ignore_patterns: Vec<String>) -> Result<Self> {
+        // Deleted notes stay out of the tree and searches whatever the config says.
+        if !ignore_patterns.iter().any(|pattern| pattern == trash::DIR) {
+            ignore_patterns.push(trash::DIR.to_string());
+        }
         let mut expanded = HashSet::new();
         expanded.insert(root.clone());
+        expanded.insert(pinned_header());
 
         let mut tree = Self {
             root,
@@ -32,6 +69,10 @@ impl FileTree {
             ignore_patterns,
             expanded,
             create_input: String::new(),
+            grouping: Grouping::Folders,
+            pinned: Vec::new(),
+            pinned_rows: 0,
+            metadata: MetadataIndex::default(),
         };
 
         tree.refresh()?;
@@ -40,8 +81,13 @@ impl FileTree {
 
     pub fn refresh(&mut self) -> Result<()> {
         self.nodes.clear();
+        self.push_pinned();
+        self.pinned_rows = self.nodes.len();
 
-        self.push_children(self.root.clone(), 0)?;
+        match self.grouping.key().map(str::to_string) {
+            None => self.push_children(self.root.clone(), 0)?,
+            Some(key) => self.push_groups(&key),
+        }
 
         if self.nodes.is_empty() {
             self.selected = 0;
@@ -88,6 +134,39 @@ impl FileTree {
             .collect()
     }
 
+    /// Every vault path that cannot be listed or opened, with the reason.
+    /// The rest of the vault stays usable; this only reports what was
+    /// skipped.
+    pub fn problems(&self) -> Vec<(PathBuf, String)> {
+        let ignores = self.ignore_patterns.clone();
+        let mut problems = Vec::new();
+        let walk = WalkBuilder::new(&self.root)
+            .hidden(false)
+            .filter_entry(move |entry| {
+                let s = entry.path().to_string_lossy();
+                !ignores.iter().any(|p| s.contains(p))
+            })
+            .build();
+        for entry in walk {
+            match entry {
+                Ok(entry) => {
+                    let path = entry.path();
+                    if let Some(problem) = access_problem(path, path.is_dir()) {
+                        problems.push((path.to_path_buf(), problem));
+                    }
+                }
+                Err(err) => {
+                    let path = walk_error_path(&err).unwrap_or_else(|| self.root.clone());
+                    if !problems.iter().any(|(seen, _)| *seen == path) {
+                        problems.push((path, walk_error_reason(&err)));
+                    }
+                }
+            }
+        }
+        problems.sort();
+        problems
+    }
+
     pub fn is_expanded(&self, path: &Path) -> bool {
         self.expanded.contains(path)
     }
@@ -122,6 +201,18 @@ impl FileTree {
             return self.refresh();
         }
 
+        // A note's group header is the nearest shallower node above it; a
+        // note can sit in several groups, so its path does not say which.
+        if self.grouping != Grouping::Folders || self.selected < self.pinned_rows {
+            if let Some(idx) = self.nodes[..self.selected]
+                .iter()
+                .rposition(|n| n.depth < node.depth)
+            {
+                self.selected = idx;
+            }
+            return Ok(());
+        }
+
         let Some(parent) = node.path.parent() else {
             return Ok(());
         };
@@ -144,7 +235,8 @@ impl FileTree {
 
     pub fn create_target_base_dir(&self) -> PathBuf {
         match self.selected_node() {
-            Some(node) if node.is_dir => node.path.clone(),
+            Some(node) if node.is_dir && node.path.starts_with(&self.root) => node.path.clone(),
+            Some(node) if node.is_dir => self.root.clone(),
             Some(node) => node
                 .path
                 .parent()
@@ -153,7 +245,9 @@ impl FileTree {
         }
     }
 
-    pub fn commit_create(&mut self) -> Result<Option<PathBuf>> {
+    /// Create the file or folder typed into `create_input`. Returns the note
+    /// to open and whether it was newly created; folders return `None`.
+    pub fn commit_create(&mut self) -> Result<Option<(PathBuf, bool)>> {
         let input = self.create_input.trim();
         if input.is_empty() {
             return Ok(None);
@@ -178,7 +272,8 @@ impl FileTree {
             std::fs::create_dir_all(parent)?;
         }
 
-        if !target.exists() {
+        let created = !target.exists();
+        if created {
             let title = target
                 .file_stem()
                 .and_then(|s| s.to_str())
@@ -188,7 +283,127 @@ impl FileTree {
 
         self.create_input.clear();
         self.refresh()?;
-        Ok(Some(target))
+        Ok(Some((target, created)))
+    }
+
+    /// Expand the folders holding `path` (or, when grouped, the first group
+    /// it is in) and select it. Returns whether the note is in the tree.
+    pub fn reveal(&mut self, path: &Path) -> Result<bool> {
+        match self.grouping.key().map(str::to_string) {
+            None => {
+                for dir in path.ancestors().skip(1) {
+                    if !dir.starts_with(&self.root) {
+                        break;
+                    }
+                    self.expanded.insert(dir.to_path_buf());
+                }
+            }
+            Some(key) => {
+                let files = self.all_file_paths();
+                self.metadata.refresh(&files);
+                let (groups, _) = self.metadata.group_by(&key);
+                let value = groups
+                    .into_iter()
+                    .find(|(_, notes)| notes.iter().any(|note| note == path))
+                    .map_or_else(|| format!("(no {key})"), |(value, _)| value);
+                self.expanded.insert(group_header(&key, &value));
+            }
+        }
+        self.refresh()?;
+        let Some(idx) = self.nodes[self.pinned_rows..]
+            .iter()
+            .position(|node| node.path == path)
+        else {
+            return Ok(false);
+        };
+        self.selected = self.pinned_rows + idx;
+        Ok(true)
+    }
+
+    /// Switch layout, starting at the top of the new one.
+    pub fn set_grouping(&mut self, grouping: Grouping) -> Result<()> {
+        self.grouping = grouping;
+        self.selected = 0;
+        self.refresh()
+    }
+
+    /// A collapsible `Pinned` header and the pinned notes that still exist.
+    fn push_pinned(&mut self) {
+        let notes: Vec<PathBuf> = self
+            .pinned
+            .iter()
+            .filter(|path| path.starts_with(&self.root) && path.is_file())
+            .cloned()
+            .collect();
+        if notes.is_empty() {
+            return;
+        }
+        let header = pinned_header();
+        let expanded = self.expanded.contains(&header);
+        self.nodes.push(FileNode {
+            path: header,
+            name: format!("Pinned ({})", notes.len()),
+            depth: 0,
+            is_dir: true,
+            problem: None,
+        });
+        if !expanded {
+            return;
+        }
+        for path in notes {
+            let name = path
+                .strip_prefix(&self.root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .into_owned();
+            self.nodes.push(FileNode {
+                path,
+                name,
+                depth: 1,
+                is_dir: false,
+                problem: None,
+            });
+        }
+    }
+
+    /// One collapsible header per value of `key`, holding the notes that
+    /// have it, then one for the notes that do not.
+    fn push_groups(&mut self, key: &str) {
+        let files = self.all_file_paths();
+        self.metadata.refresh(&files);
+        let (groups, ungrouped) = self.metadata.group_by(key);
+        let missing = (format!("(no {key})"), ungrouped);
+        for (value, notes) in groups.into_iter().chain([missing]) {
+            if notes.is_empty() {
+                continue;
+            }
+            let header = group_header(key, &value);
+            let expanded = self.expanded.contains(&header);
+            self.nodes.push(FileNode {
+                path: header,
+                name: format!("{value} ({})", notes.len()),
+                depth: 0,
+                is_dir: true,
+                problem: None,
+            });
+            if !expanded {
+                continue;
+            }
+            for path in notes {
+                let name = path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned();
+                self.nodes.push(FileNode {
+                    path,
+                    name,
+                    depth: 1,
+                    is_dir: false,
+                    problem: None,
+                });
+            }
+        }
     }
 
     fn push_children(&mut self, dir: PathBuf, depth: usize) -> Result<()> {
@@ -223,11 +438,13 @@ impl FileTree {
             }
         });
         for (path, is_dir, name) in entries {
+            let problem = access_problem(&path, is_dir);
             self.nodes.push(FileNode {
                 path: path.clone(),
                 name,
                 depth,
                 is_dir,
+                problem,
             });
 
             if is_dir && self.expanded.contains(&path) {
@@ -239,6 +456,58 @@ impl FileTree {
     }
 }
 
+/// Virtual path of a group's header. Headers live outside the vault so
+/// they never match a real path.
+fn group_header(key: &str, value: &str) -> PathBuf {
+    PathBuf::from(format!("<{key}>")).join(value)
+}
+
+/// Virtual path of the pinned section's header, outside the vault like the
+/// group headers.
+fn pinned_header() -> PathBuf {
+    PathBuf::from("<pinned>")
+}
+
+/// Whether a directory can be listed or a file opened.
+fn access_problem(path: &Path, is_dir: bool) -> Option<String> {
+    let result = if is_dir {
+        std::fs::read_dir(path).map(drop)
+    } else {
+        std::fs::File::open(path).map(drop)
+    };
+    result.err().map(|err| io_reason(&err))
+}
+
+fn io_reason(err: &std::io::Error) -> String {
+    match err.kind() {
+        std::io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        std::io::ErrorKind::NotFound => "missing (broken symlink?)".to_string(),
+        _ => err.to_string(),
+    }
+}
+
+fn walk_error_path(err: &ignore::Error) -> Option<PathBuf> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path.clone()),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            walk_error_path(err)
+        }
+        ignore::Error::Loop { child, .. } => Some(child.clone()),
+        _ => None,
+    }
+}
+
+fn walk_error_reason(err: &ignore::Error) -> String {
+    match err {
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => walk_error_reason(err),
+        ignore::Error::Io(io) => io_reason(io),
+        ignore::Error::Loop { .. } => "symlink loop".to_string(),
+        other => other.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,7 +542,8 @@ mod tests {
         tree.create_input = "mynote".to_string();
         let result = tree.commit_create().unwrap();
         assert!(result.is_some());
-        let path = result.unwrap();
+        let (path, created) = result.unwrap();
+        assert!(created);
         assert_eq!(path.extension().and_then(|e| e.to_str()), Some("md"));
         assert!(path.exists(), "file should have been created");
         let content = fs::read_to_string(&path).unwrap();
@@ -323,4 +593,103 @@ mod tests {
         let paths = tree.all_file_paths();
         assert_eq!(paths.len(), 2);
     }
+
+    #[test]
+    fn test_reveal_expands_ancestors() {
+        let tmp = make_temp_vault();
+        let note = tmp.path().join("a/b/deep.md");
+        fs::create_dir_all(note.parent().unwrap()).unwrap();
+        fs::write(&note, "---\ntags: [x]\n---\n").unwrap();
+        fs::write(tmp.path().join("top.md"), "").unwrap();
+        let mut tree = FileTree::new(tmp.path().to_path_buf(), vec![]).unwrap();
+        assert_eq!(tree.nodes.len(), 2, "folders start collapsed");
+
+        assert!(tree.reveal(&note).unwrap());
+        assert_eq!(tree.selected_node().unwrap().path, note);
+        assert_eq!(tree.nodes.len(), 4);
+
+        tree.set_grouping(Grouping::Tags).unwrap();
+        assert!(tree.reveal(&note).unwrap());
+        assert_eq!(tree.selected_node().unwrap().path, note);
+        assert!(!tree.reveal(&tmp.path().join("missing.md")).unwrap());
+    }
+
+    #[test]
+    fn test_group_by_tag() {
+        let tmp = make_temp_vault();
+        fs::write(tmp.path().join("a.md"), "---\ntags: [work]\n---\n").unwrap();
+        fs::create_dir(tmp.path().join("sub")).unwrap();
+        fs::write(
+            tmp.path().join("sub/b.md"),
+            "---\ntags: [work, home]\n---\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("c.md"), "").unwrap();
+        let mut tree = FileTree::new(tmp.path().to_path_buf(), vec![]).unwrap();
+        tree.set_grouping(Grouping::Tags).unwrap();
+
+        let names = |tree: &FileTree| -> Vec<String> {
+            tree.nodes.iter().map(|n| n.name.clone()).collect()
+        };
+        assert_eq!(names(&tree), ["home (1)", "work (2)", "(no tags) (1)"]);
+
+        tree.move_selection(1);
+        tree.toggle_selected_dir().unwrap();
+        assert_eq!(
+            names(&tree),
+            ["home (1)", "work (2)", "a.md", "sub/b.md", "(no tags) (1)"]
+        );
+        tree.move_selection(2);
+        tree.collapse_selected_or_parent().unwrap();
+        assert_eq!(tree.selected, 1);
+        assert_eq!(tree.create_target_base_dir(), tmp.path());
+    }
+
+    #[test]
+    fn test_pinned_section_above_tree() {
+        let tmp = make_temp_vault();
+        let note = tmp.path().join("sub/deep.md");
+        fs::create_dir_all(note.parent().unwrap()).unwrap();
+        fs::write(&note, "").unwrap();
+        fs::write(tmp.path().join("top.md"), "").unwrap();
+        let mut tree = FileTree::new(tmp.path().to_path_buf(), vec![]).unwrap();
+        tree.pinned = vec![note.clone(), tmp.path().join("gone.md")];
+        tree.refresh().unwrap();
+
+        let names: Vec<&str> = tree.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["Pinned (1)", "sub/deep.md", "sub", "top.md"]);
+        tree.move_selection(1);
+        tree.collapse_selected_or_parent().unwrap();
+        assert_eq!(tree.selected, 0);
+        assert_eq!(tree.create_target_base_dir(), tmp.path());
+
+        assert!(tree.reveal(&note).unwrap());
+        assert_eq!(tree.selected, 3);
+        tree.move_selection(-3);
+        tree.toggle_selected_dir().unwrap();
+        assert_eq!(tree.nodes.len(), 4);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_entries_are_reported_not_fatal() {
+        let tmp = make_temp_vault();
+        fs::write(tmp.path().join("ok.md"), "").unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("gone.md"), tmp.path().join("dangling.md"))
+            .unwrap();
+        let tree = FileTree::new(tmp.path().to_path_buf(), vec![]).unwrap();
+
+        assert_eq!(tree.nodes.len(), 2);
+        let dangling = tree.nodes.iter().find(|n| n.name == "dangling.md").unwrap();
+        assert!(dangling.problem.is_some());
+        assert!(
+            tree.nodes
+                .iter()
+                .any(|n| n.name == "ok.md" && n.problem.is_none())
+        );
+
+        let problems = tree.problems();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].0, tmp.path().join("dangling.md"));
+    }
 }
@@ -0,0 +1,389 @@
+/// A single frontmatter value.
+///
+/// Only the subset of YAML that notes actually use is understood: plain
+/// scalars, flow lists (`[a, b]`) and block lists (`- a`). Anything else
+/// (nested maps, multi-line strings) is kept verbatim so rewriting a note's
+/// frontmatter never drops data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontmatterValue {
+    Scalar(String),
+    List(Vec<String>),
+    Raw(Vec<String>),
+}
+
+impl FrontmatterValue {
+    /// Value typed into the property editor: comma-separated items (in
+    /// optional brackets) for a list field, otherwise the text as given,
+    /// where `[a, b]` still makes a list.
+    pub fn from_input(text: &str, list: bool) -> Self {
+        let text = text.trim();
+        if !list {
+            return parse_inline_value(text);
+        }
+        let inner = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .unwrap_or(text);
+        FrontmatterValue::List(
+            inner
+                .split(',')
+                .map(|item| unquote(item.trim()))
+                .filter(|item| !item.is_empty())
+                .collect(),
+        )
+    }
+
+    pub fn as_scalar(&self) -> Option<&str> {
+        match self {
+            FrontmatterValue::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed YAML frontmatter block at the top of a note.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    pub fields: Vec<(String, FrontmatterValue)>,
+    /// Number of lines the block occupies in the source, including both
+    /// `---` delimiters. Zero when the note has no frontmatter yet.
+    pub line_count: usize,
+}
+
+impl Frontmatter {
+    /// Parse the frontmatter block from the beginning of `text`.
+    ///
+    /// Returns `None` when the text does not open with a `---` line or the
+    /// block is never closed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()?.trim_end() != "---" {
+            return None;
+        }
+
+        let mut body = Vec::new();
+        let mut closed = false;
+        for line in lines {
+            if line.trim_end() == "---" {
+                closed = true;
+                break;
+            }
+            body.push(line);
+        }
+
+        if !closed {
+            return None;
+        }
+
+        Some(Self {
+            fields: parse_fields(&body),
+            line_count: body.len() + 2,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&FrontmatterValue> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_scalar(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(FrontmatterValue::as_scalar)
+    }
+
+    /// Insert or replace `key`, keeping the original field order.
+    pub fn set(&mut self, key: &str, value: FrontmatterValue) {
+        if let Some((_, existing)) = self.fields.iter_mut().find(|(name, _)| name == key) {
+            *existing = value;
+        } else {
+            self.fields.push((key.to_string(), value));
+        }
+    }
+
+    /// Add `item` to the `key` list (a scalar becomes a one-item list), or
+    /// remove it, dropping the field once it is empty. Items compare without
+    /// case. Returns whether anything changed.
+    pub fn edit_list(&mut self, key: &str, item: &str, add: bool) -> bool {
+        let mut items = match self.get(key) {
+            Some(FrontmatterValue::List(items)) => items.clone(),
+            Some(FrontmatterValue::Scalar(value)) if !value.is_empty() => vec![value.clone()],
+            Some(FrontmatterValue::Raw(_)) => return false,
+            _ => Vec::new(),
+        };
+        let present = items
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(item));
+        if add == present {
+            return false;
+        }
+        if add {
+            items.push(item.to_string());
+        } else {
+            items.retain(|existing| !existing.eq_ignore_ascii_case(item));
+        }
+        if items.is_empty() {
+            self.fields.retain(|(name, _)| name != key);
+        } else {
+            self.set(key, FrontmatterValue::List(items));
+        }
+        true
+    }
+
+    /// `text` with this block in place of its frontmatter, or added at the
+    /// top. A block left without fields is removed.
+    pub fn replace_in(&self, text: &str) -> String {
+        let old_lines = Frontmatter::parse(text).map_or(0, |fm| fm.line_count);
+        let body: String = text.split_inclusive('\n').skip(old_lines).collect();
+        if self.fields.is_empty() {
+            body
+        } else {
+            self.to_block() + &body
+        }
+    }
+
+    /// One-line digest of the fields, e.g. `title: Roadmap · tags: work, q3`,
+    /// for the folded block.
+    pub fn summary(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(key, value)| match value {
+                FrontmatterValue::Scalar(scalar) => format!("{key}: {scalar}"),
+                FrontmatterValue::List(items) => format!("{key}: {}", items.join(", ")),
+                FrontmatterValue::Raw(_) => format!("{key}: …"),
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+
+    /// Serialize back to a `---` delimited block, newline-terminated.
+    pub fn to_block(&self) -> String {
+        let mut out = String::from("---\n");
+        for (key, value) in &self.fields {
+            match value {
+                FrontmatterValue::Scalar(scalar) if scalar.is_empty() => {
+                    out.push_str(&format!("{key}:\n"));
+                }
+                FrontmatterValue::Scalar(scalar) => {
+                    out.push_str(&format!("{key}: {}\n", quote(scalar)));
+                }
+                FrontmatterValue::List(items) => {
+                    out.push_str(&format!("{key}:\n"));
+                    for item in items {
+                        out.push_str(&format!("  - {}\n", quote(item)));
+                    }
+                }
+                FrontmatterValue::Raw(lines) => {
+                    out.push_str(&format!("{key}:\n"));
+                    for line in lines {
+                        out.push_str(line);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        out.push_str("---\n");
+        out
+    }
+}
+
+fn parse_fields(body: &[&str]) -> Vec<(String, FrontmatterValue)> {
+    let mut fields = Vec::new();
+    let mut idx = 0;
+
+    while idx < body.len() {
+        let line = body[idx];
+        idx += 1;
+
+        if line.trim().is_empty() || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let rest = rest.trim();
+
+        if !rest.is_empty() {
+            fields.push((key, parse_inline_value(rest)));
+            continue;
+        }
+
+        let mut nested = Vec::new();
+        while idx < body.len()
+            && (body[idx].starts_with(char::is_whitespace) || body[idx].starts_with("- "))
+        {
+            nested.push(body[idx]);
+            idx += 1;
+        }
+
+        let value = if !nested.is_empty() && nested.iter().all(|l| l.trim_start().starts_with("- "))
+        {
+            FrontmatterValue::List(
+                nested
+                    .iter()
+                    .map(|l| unquote(l.trim_start().trim_start_matches("- ").trim()))
+                    .collect(),
+            )
+        } else if nested.is_empty() {
+            FrontmatterValue::Scalar(String::new())
+        } else {
+            FrontmatterValue::Raw(nested.iter().map(|l| l.to_string()).collect())
+        };
+        fields.push((key, value));
+    }
+
+    fields
+}
+
+fn parse_inline_value(raw: &str) -> FrontmatterValue {
+    if let Some(inner) = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        let items = inner
+            .split(',')
+            .map(|item| unquote(item.trim()))
+            .filter(|item| !item.is_empty())
+            .collect();
+        return FrontmatterValue::List(items);
+    }
+
+    FrontmatterValue::Scalar(unquote(raw))
+}
+
+fn unquote(raw: &str) -> String {
+    let quoted = |mark: char| raw.len() >= 2 && raw.starts_with(mark) && raw.ends_with(mark);
+    if quoted('"') {
+        raw[1..raw.len() - 1].to_string()
+    } else if quoted('\'') {
+        raw[1..raw.len() - 1].replace("''", "'")
+    } else {
+        raw.to_string()
+    }
+}
+
+/// `value` as a YAML scalar, quoted when it would otherwise be read as
+/// something else (a comment, a nested key, a flow list…).
+fn quote(value: &str) -> String {
+    let special = value != value.trim()
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.starts_with("- ")
+        || value.starts_with("? ")
+        || value == "-"
+        || value.starts_with([
+            '[', ']', '{', '}', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`', '#', ',',
+        ]);
+    if !special {
+        value.to_string()
+    } else if !value.contains(['"', '\\']) {
+        format!("\"{value}\"")
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scalars_and_lists() {
+        let text = "---\ntitle: \"Roadmap\"\ntags: [work, q3]\naliases:\n  - plan\n  - road\n---\n# Body\n";
+        let fm = Frontmatter::parse(text).unwrap();
+        assert_eq!(fm.get_scalar("title"), Some("Roadmap"));
+        assert_eq!(
+            fm.get("tags"),
+            Some(&FrontmatterValue::List(vec![
+                "work".to_string(),
+                "q3".to_string()
+            ]))
+        );
+        assert_eq!(
+            fm.get("aliases"),
+            Some(&FrontmatterValue::List(vec![
+                "plan".to_string(),
+                "road".to_string()
+            ]))
+        );
+        assert_eq!(fm.line_count, 7);
+        assert_eq!(
+            fm.summary(),
+            "title: Roadmap · tags: work, q3 · aliases: plan, road"
+        );
+    }
+
+    #[test]
+    fn test_parse_requires_opening_and_closing_delimiters() {
+        assert!(Frontmatter::parse("# Title\n---\n").is_none());
+        assert!(Frontmatter::parse("---\ntitle: x\n").is_none());
+    }
+
+    #[test]
+    fn test_set_preserves_order_and_unknown_blocks() {
+        let text = "---\nstatus: draft\nmeta:\n  owner: me\n---\n";
+        let mut fm = Frontmatter::parse(text).unwrap();
+        fm.set("status", FrontmatterValue::Scalar("review".to_string()));
+        assert_eq!(
+            fm.to_block(),
+            "---\nstatus: review\nmeta:\n  owner: me\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_list_adds_and_removes_items() {
+        let text = "---\ntitle: Plan\ntags: work\n---\n# Plan\n";
+        let mut fm = Frontmatter::parse(text).unwrap();
+        assert!(fm.edit_list("tags", "q3", true));
+        assert!(!fm.edit_list("tags", "Work", true));
+        assert_eq!(
+            fm.replace_in(text),
+            "---\ntitle: Plan\ntags:\n  - work\n  - q3\n---\n# Plan\n"
+        );
+
+        assert!(fm.edit_list("tags", "work", false));
+        assert!(fm.edit_list("tags", "q3", false));
+        assert!(!fm.edit_list("tags", "q3", false));
+        assert_eq!(fm.get("tags"), None);
+
+        let mut fresh = Frontmatter::default();
+        assert!(fresh.edit_list("tags", "idea", true));
+        assert_eq!(
+            fresh.replace_in("body\n"),
+            "---\ntags:\n  - idea\n---\nbody\n"
+        );
+        assert_eq!(Frontmatter::default().replace_in(text), "# Plan\n");
+    }
+
+    #[test]
+    fn test_edited_values_round_trip_as_yaml() {
+        let mut fm = Frontmatter::default();
+        fm.set(
+            "title",
+            FrontmatterValue::from_input(" Plan: phase #2 ", false),
+        );
+        fm.set("tags", FrontmatterValue::from_input("[work, , q3]", true));
+        fm.set("aliases", FrontmatterValue::from_input("#hash, it's", true));
+        fm.set("related", FrontmatterValue::from_input("[a, b]", false));
+        let block = fm.to_block();
+        assert_eq!(
+            block,
+            "---\ntitle: \"Plan: phase #2\"\ntags:\n  - work\n  - q3\naliases:\n  - \"#hash\"\n  - it's\nrelated:\n  - a\n  - b\n---\n"
+        );
+        assert_eq!(
+            Frontmatter::parse(&block).map(|parsed| parsed.fields),
+            Some(fm.fields)
+        );
+
+        let awkward = "'a' \"b\": c";
+        assert_eq!(quote(awkward), "'''a'' \"b\": c'");
+        assert_eq!(unquote(&quote(awkward)), awkward);
+    }
+
+    #[test]
+    fn test_set_appends_new_key() {
+        let mut fm = Frontmatter::default();
+        fm.set("status", FrontmatterValue::Scalar("draft".to_string()));
+        assert_eq!(fm.to_block(), "---\nstatus: draft\n---\n");
+    }
+}
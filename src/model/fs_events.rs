@@ -0,0 +1,180 @@
+//! Turning file watcher events into vault changes, pairing the two halves
+//! of a rename so open tabs follow the file instead of closing.
+//!
+//! inotify reports a rename as `From`, `To` and then `Both` with one
+//! tracker; other backends send the halves alone (FSEvents only as `Any`,
+//! told apart by whether the path still exists).
+
+use std::path::{Path, PathBuf};
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Created, modified or removed.
+    Changed(PathBuf),
+    Renamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct RenamePairing {
+    /// Old path of a rename whose new path has not arrived yet.
+    pending: Option<(PathBuf, Option<usize>)>,
+    /// The pair just reported, so a trailing `Both` is not reported again.
+    last: Option<(PathBuf, PathBuf)>,
+}
+
+impl RenamePairing {
+    pub fn push(&mut self, event: &Event, exists: impl Fn(&Path) -> bool) -> Vec<Change> {
+        let tracker = event.tracker();
+        match (&event.kind, event.paths.as_slice()) {
+            (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) => {
+                if self.pending.as_ref().is_some_and(|(path, _)| path == from) {
+                    self.pending = None;
+                }
+                let mut changes = self.flush();
+                if self.last.take() != Some((from.clone(), to.clone())) {
+                    changes.push(Change::Renamed {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                }
+                changes
+            }
+            (EventKind::Modify(ModifyKind::Name(mode)), [path]) => {
+                let is_old = match mode {
+                    RenameMode::From => true,
+                    RenameMode::To => false,
+                    _ => !exists(path),
+                };
+                if is_old {
+                    let changes = self.flush();
+                    self.pending = Some((path.clone(), tracker));
+                    return changes;
+                }
+                match self.pending.take() {
+                    Some((from, pending)) if pending == tracker => {
+                        self.last = Some((from.clone(), path.clone()));
+                        vec![Change::Renamed {
+                            from,
+                            to: path.clone(),
+                        }]
+                    }
+                    unpaired => {
+                        self.pending = unpaired;
+                        let mut changes = self.flush();
+                        changes.push(Change::Changed(path.clone()));
+                        changes
+                    }
+                }
+            }
+            (EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_), paths) => {
+                let mut changes = self.flush();
+                changes.extend(paths.iter().cloned().map(Change::Changed));
+                changes
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Give up waiting for the new path: the file left the vault.
+    pub fn flush(&mut self) -> Vec<Change> {
+        self.pending
+            .take()
+            .map(|(path, _)| Change::Changed(path))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::CreateKind;
+
+    fn name(mode: RenameMode, paths: &[&str], tracker: Option<usize>) -> Event {
+        let mut event = Event::new(EventKind::Modify(ModifyKind::Name(mode)));
+        for path in paths {
+            event = event.add_path(PathBuf::from(path));
+        }
+        match tracker {
+            Some(tracker) => event.set_tracker(tracker),
+            None => event,
+        }
+    }
+
+    fn renamed(from: &str, to: &str) -> Change {
+        Change::Renamed {
+            from: PathBuf::from(from),
+            to: PathBuf::from(to),
+        }
+    }
+
+    #[test]
+    fn test_renames_pair_once_per_backend() {
+        let mut pairing = RenamePairing::default();
+        let never = |_: &Path| false;
+        assert!(
+            pairing
+                .push(&name(RenameMode::From, &["/v/a.md"], Some(7)), never)
+                .is_empty()
+        );
+        assert_eq!(
+            pairing.push(&name(RenameMode::To, &["/v/b.md"], Some(7)), never),
+            [renamed("/v/a.md", "/v/b.md")]
+        );
+        assert!(
+            pairing
+                .push(
+                    &name(RenameMode::Both, &["/v/a.md", "/v/b.md"], Some(7)),
+                    never
+                )
+                .is_empty()
+        );
+
+        let exists = |path: &Path| path.ends_with("new.md");
+        assert!(
+            pairing
+                .push(&name(RenameMode::Any, &["/v/old.md"], None), exists)
+                .is_empty()
+        );
+        assert_eq!(
+            pairing.push(&name(RenameMode::Any, &["/v/new.md"], None), exists),
+            [renamed("/v/old.md", "/v/new.md")]
+        );
+    }
+
+    #[test]
+    fn test_unpaired_halves_are_plain_changes() {
+        let mut pairing = RenamePairing::default();
+        let never = |_: &Path| false;
+        pairing.push(&name(RenameMode::From, &["/v/gone.md"], Some(1)), never);
+        assert_eq!(
+            pairing.push(&name(RenameMode::To, &["/v/new.md"], Some(2)), never),
+            [
+                Change::Changed(PathBuf::from("/v/gone.md")),
+                Change::Changed(PathBuf::from("/v/new.md"))
+            ]
+        );
+
+        pairing.push(&name(RenameMode::From, &["/v/out.md"], None), never);
+        let create = Event::new(EventKind::Create(CreateKind::File)).add_path("/v/c.md".into());
+        assert_eq!(
+            pairing.push(&create, never),
+            [
+                Change::Changed(PathBuf::from("/v/out.md")),
+                Change::Changed(PathBuf::from("/v/c.md"))
+            ]
+        );
+
+        pairing.push(&name(RenameMode::From, &["/v/left.md"], None), never);
+        assert_eq!(
+            pairing.flush(),
+            [Change::Changed(PathBuf::from("/v/left.md"))]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Wikilinks between vault notes, and the neighbourhood of one note laid
+//! out as a tree for the `:graph` view.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::model::link;
+use crate::model::note_index::note_aliases;
+
+/// Which way the link to a row's note runs, seen from its parent row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Out,
+    In,
+    Both,
+}
+
+impl Edge {
+    pub fn arrow(self) -> char {
+        match self {
+            Edge::Out => '→',
+            Edge::In => '←',
+            Edge::Both => '↔',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    pub path: PathBuf,
+    /// Hops from the centre note, which is row 0 at depth 0.
+    pub depth: usize,
+    pub edge: Option<Edge>,
+    /// Box-drawing branches and arrow drawn before the note name.
+    pub prefix: String,
+}
+
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+    outgoing: HashMap<PathBuf, BTreeSet<PathBuf>>,
+    incoming: HashMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl LinkGraph {
+    /// Resolve the wikilinks of every `(path, text)` note under `root` the
+    /// way following them does: a vault path first, then a file name in any
+    /// folder, ignoring case, then an alias. Dead links and self-links are
+    /// left out.
+    pub fn build(root: &Path, notes: &[(PathBuf, String)]) -> Self {
+        let by_name: HashMap<String, &PathBuf> = notes
+            .iter()
+            .filter_map(|(path, _)| {
+                Some((path.file_name()?.to_string_lossy().to_lowercase(), path))
+            })
+            .collect();
+        let paths: HashSet<&PathBuf> = notes.iter().map(|(path, _)| path).collect();
+        let mut by_alias: HashMap<String, &PathBuf> = HashMap::new();
+        for (path, text) in notes {
+            for alias in note_aliases(text) {
+                by_alias.entry(alias.to_lowercase()).or_insert(path);
+            }
+        }
+
+        let mut graph = Self::default();
+        for (from, text) in notes {
+            for target in link::wikilink_targets(text) {
+                let file = format!("{target}.md");
+                let exact = root.join(&file);
+                let to = match paths.get(&exact) {
+                    Some(path) => *path,
+                    None => match by_name
+                        .get(&file.to_lowercase())
+                        .or_else(|| by_alias.get(&target.to_lowercase()))
+                    {
+                        Some(path) => *path,
+                        None => continue,
+                    },
+                };
+                graph.add_link(from, to);
+            }
+        }
+        graph
+    }
+
+    /// Record a link from `from` to `to`, leaving out self-links.
+    pub fn add_link(&mut self, from: &Path, to: &Path) {
+        if from == to {
+            return;
+        }
+        self.outgoing
+            .entry(from.to_path_buf())
+            .or_default()
+            .insert(to.to_path_buf());
+        self.incoming
+            .entry(to.to_path_buf())
+            .or_default()
+            .insert(from.to_path_buf());
+    }
+
+    fn edge(&self, from: &Path, to: &Path) -> Option<Edge> {
+        let links = |map: &HashMap<PathBuf, BTreeSet<PathBuf>>| {
+            map.get(from).is_some_and(|set| set.contains(to))
+        };
+        match (links(&self.outgoing), links(&self.incoming)) {
+            (true, true) => Some(Edge::Both),
+            (true, false) => Some(Edge::Out),
+            (false, true) => Some(Edge::In),
+            (false, false) => None,
+        }
+    }
+
+    fn neighbours(&self, path: &Path) -> BTreeSet<&PathBuf> {
+        self.outgoing
+            .get(path)
+            .into_iter()
+            .chain(self.incoming.get(path))
+            .flatten()
+            .collect()
+    }
+
+    /// Whether `path` neither links to nor is linked from another note.
+    pub fn is_orphan(&self, path: &Path) -> bool {
+        self.neighbours(path).is_empty()
+    }
+
+    /// Notes within `depth` links of `centre` in either direction, each
+    /// listed once under the note that first reached it.
+    pub fn neighbourhood(&self, centre: &Path, depth: usize) -> Vec<GraphRow> {
+        let mut children: HashMap<&Path, Vec<&PathBuf>> = HashMap::new();
+        let mut seen: HashSet<&Path> = HashSet::from([centre]);
+        let mut queue = VecDeque::from([(centre, 0)]);
+        while let Some((node, hops)) = queue.pop_front() {
+            if hops == depth {
+                continue;
+            }
+            for next in self.neighbours(node) {
+                if seen.insert(next) {
+                    children.entry(node).or_default().push(next);
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+
+        let mut rows = vec![GraphRow {
+            path: centre.to_path_buf(),
+            depth: 0,
+            edge: None,
+            prefix: String::new(),
+        }];
+        self.push_children(&children, centre, "", 1, &mut rows);
+        rows
+    }
+
+    fn push_children(
+        &self,
+        children: &HashMap<&Path, Vec<&PathBuf>>,
+        parent: &Path,
+        indent: &str,
+        depth: usize,
+        rows: &mut Vec<GraphRow>,
+    ) {
+        let Some(kids) = children.get(parent) else {
+            return;
+        };
+        for (idx, child) in kids.iter().enumerate() {
+            let last = idx + 1 == kids.len();
+            let edge = self.edge(parent, child);
+            let arrow = edge.map_or(' ', Edge::arrow);
+            let branch = if last { "└─" } else { "├─" };
+            rows.push(GraphRow {
+                path: child.to_path_buf(),
+                depth,
+                edge,
+                prefix: format!("{indent}{branch}{arrow} "),
+            });
+            let indent = format!("{indent}{}", if last { "    " } else { "│   " });
+            self.push_children(children, child, &indent, depth + 1, rows);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbourhood_tree() {
+        let root = PathBuf::from("/v");
+        let note = |name: &str, text: &str| (root.join(name), text.to_string());
+        let graph = LinkGraph::build(
+            &root,
+            &[
+                note("plan.md", "[[roadmap]] and [[Team|the team]] and [[gone]]"),
+                note("team.md", "back to [[plan]]"),
+                note("roadmap.md", "see [[sub/q3]]"),
+                note("sub/q3.md", "[[q3]] itself"),
+                note("ideas.md", "feeds [[plan#Goals]]"),
+                note("lonely.md", "only [[lonely]] and [[nowhere]]"),
+            ],
+        );
+
+        let rows: Vec<(String, usize)> = graph
+            .neighbourhood(&root.join("plan.md"), 2)
+            .into_iter()
+            .map(|row| {
+                let name = row.path.strip_prefix(&root).unwrap().display().to_string();
+                (format!("{}{name}", row.prefix), row.depth)
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("plan.md".to_string(), 0),
+                ("├─← ideas.md".to_string(), 1),
+                ("├─→ roadmap.md".to_string(), 1),
+                ("│   └─→ sub/q3.md".to_string(), 2),
+                ("└─↔ team.md".to_string(), 1),
+            ]
+        );
+
+        assert_eq!(graph.neighbourhood(&root.join("plan.md"), 1).len(), 4);
+        assert_eq!(graph.neighbourhood(&root.join("ideas.md"), 0).len(), 1);
+        assert!(graph.is_orphan(&root.join("lonely.md")));
+        assert!(!graph.is_orphan(&root.join("sub/q3.md")));
+
+        let graph = LinkGraph::build(
+            &root,
+            &[
+                note("plan.md", "---\naliases: [The Plan]\n---\n"),
+                note("ideas.md", "feeds [[the plan|it]]"),
+            ],
+        );
+        assert_eq!(
+            graph.edge(&root.join("plan.md"), &root.join("ideas.md")),
+            Some(Edge::In)
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Local change journal: one line per save recording which note changed and
+//! by how many lines, independent of any sync backend.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub path: PathBuf,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl JournalEntry {
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\n",
+            self.timestamp,
+            self.added,
+            self.removed,
+            self.path.display()
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(4, '\t');
+        Some(Self {
+            timestamp: fields.next()?.parse().ok()?,
+            added: fields.next()?.parse().ok()?,
+            removed: fields.next()?.parse().ok()?,
+            path: PathBuf::from(fields.next()?),
+        })
+    }
+}
+
+/// Journal file shared by every vault; entries store absolute paths.
+pub fn default_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        return project_dirs.data_dir().join("journal.tsv");
+    }
+
+    PathBuf::from(".blackbox-journal.tsv")
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Lines added and removed between two versions of a note, ignoring order.
+///
+/// A moved line counts as unchanged; an edited line as one removed plus one
+/// added. Cheap enough to run on every save.
+pub fn line_delta(old: &str, new: &str) -> (usize, usize) {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in old.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+
+    counts.values().fold((0, 0), |(added, removed), &n| {
+        if n > 0 {
+            (added + n as usize, removed)
+        } else {
+            (added, removed + n.unsigned_abs())
+        }
+    })
+}
+
+pub fn append(journal: &Path, entry: &JournalEntry) -> std::io::Result<()> {
+    if let Some(parent) = journal.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(journal)?;
+    file.write_all(entry.to_line().as_bytes())
+}
+
+/// Entries for notes under `vault` recorded at or after `since` (Unix
+/// seconds), oldest first. The journal is shared by every vault.
+pub fn read_since(journal: &Path, vault: &Path, since: u64) -> Vec<JournalEntry> {
+    let Ok(text) = std::fs::read_to_string(journal) else {
+        return Vec::new();
+    };
+
+    text.lines()
+        .filter_map(JournalEntry::from_line)
+        .filter(|entry| entry.timestamp >= since && entry.path.starts_with(vault))
+        .collect()
+}
+
+/// Parse a relative span such as `7d`, `2w`, `12h` or `30m`.
+pub fn parse_span(raw: &str) -> Option<Duration> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = raw.split_at(split);
+    let count: u64 = count.parse().ok()?;
+    let secs = match unit {
+        "m" => 60,
+        "h" => 3_600,
+        "d" => SECS_PER_DAY,
+        "w" => 7 * SECS_PER_DAY,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count.checked_mul(secs)?))
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp.
+pub fn format_day(timestamp: u64) -> String {
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / SECS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+struct NoteActivity {
+    path: PathBuf,
+    added: usize,
+    removed: usize,
+    saves: usize,
+}
+
+/// Group entries by day (newest first) and note, summing line counts.
+pub fn report(entries: &[JournalEntry], vault: &Path) -> Vec<String> {
+    let mut days: Vec<(u64, Vec<NoteActivity>)> = Vec::new();
+
+    for entry in entries {
+        let day = entry.timestamp / SECS_PER_DAY;
+        let notes = match days.iter_mut().position(|(d, _)| *d == day) {
+            Some(idx) => &mut days[idx].1,
+            None => {
+                days.push((day, Vec::new()));
+                &mut days.last_mut().expect("just pushed").1
+            }
+        };
+        match notes.iter_mut().find(|note| note.path == entry.path) {
+            Some(note) => {
+                note.added += entry.added;
+                note.removed += entry.removed;
+                note.saves += 1;
+            }
+            None => notes.push(NoteActivity {
+                path: entry.path.clone(),
+                added: entry.added,
+                removed: entry.removed,
+                saves: 1,
+            }),
+        }
+    }
+
+    days.sort_by_key(|(day, _)| std::cmp::Reverse(*day));
+
+    let mut lines = Vec::new();
+    for (day, mut notes) in days {
+        notes.sort_by_key(|note| std::cmp::Reverse(note.added + note.removed));
+        lines.push(format!("## {}", format_day(day * SECS_PER_DAY)));
+        lines.push(String::new());
+        for note in notes {
+            let name = note.path.strip_prefix(vault).unwrap_or(&note.path);
+            let plural = if note.saves == 1 { "" } else { "s" };
+            lines.push(format!(
+                "- {}  +{} -{} ({} save{plural})",
+                name.display(),
+                note.added,
+                note.removed,
+                note.saves
+            ));
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64, path: &str, added: usize, removed: usize) -> JournalEntry {
+        JournalEntry {
+            timestamp,
+            path: PathBuf::from(path),
+            added,
+            removed,
+        }
+    }
+
+    #[test]
+    fn test_line_delta_counts_edits() {
+        assert_eq!(line_delta("a\nb\nc\n", "a\nB\nc\nd\n"), (2, 1));
+        assert_eq!(line_delta("a\nb\n", "b\na\n"), (0, 0));
+        assert_eq!(line_delta("", "x\n"), (1, 0));
+    }
+
+    #[test]
+    fn test_parse_span_units() {
+        assert_eq!(parse_span("7d"), Some(Duration::from_secs(7 * 86_400)));
+        assert_eq!(parse_span("2w"), Some(Duration::from_secs(14 * 86_400)));
+        assert_eq!(parse_span("90m"), Some(Duration::from_secs(5_400)));
+        assert_eq!(parse_span("7"), None);
+        assert_eq!(parse_span("d"), None);
+        assert_eq!(parse_span("99999999999999999d"), None);
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(951_782_400), "2000-02-29");
+        assert_eq!(format_day(1_791_936_000), "2026-10-14");
+    }
+
+    #[test]
+    fn test_journal_roundtrip_and_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("journal.tsv");
+        let day = 1_791_936_000;
+        for e in [
+            entry(day - 10, "/v/old.md", 1, 0),
+            entry(day + 10, "/v/a.md", 3, 1),
+            entry(day + 20, "/v/a.md", 2, 0),
+            entry(day + 30, "/v/b.md", 9, 9),
+            entry(day + 40, "/other/c.md", 4, 0),
+        ] {
+            append(&journal, &e).unwrap();
+        }
+
+        let entries = read_since(&journal, Path::new("/v"), day);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            report(&entries, Path::new("/v")),
+            vec![
+                "## 2026-10-14",
+                "",
+                "- b.md  +9 -9 (1 save)",
+                "- a.md  +5 -1 (2 saves)",
+                "",
+            ]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! A note read as a kanban board: each `##` section is a column and its
+//! top-level list items are the cards.
+//!
+//! A card runs from its bullet to the next unindented line, so nested items
+//! and wrapped text travel with it.
+
+use std::ops::Range;
+
+use super::outline;
+use super::task;
+use super::wrap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub title: String,
+    /// 0-indexed line of the `##` heading.
+    pub line: usize,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    /// Text after the bullet and task box of the first line.
+    pub title: String,
+    /// Lines of the card, nested content included.
+    pub lines: Range<usize>,
+    pub done: Option<bool>,
+}
+
+/// Columns of the board in note order; empty when the note has no `##`
+/// headings.
+pub fn columns(lines: &[String]) -> Vec<Column> {
+    let headings = outline::headings(lines.iter().map(String::as_str));
+    let mut columns = Vec::new();
+    for (idx, heading) in headings.iter().enumerate() {
+        if heading.level != 2 {
+            continue;
+        }
+        let end = headings[idx + 1..]
+            .iter()
+            .find(|next| next.level <= 2)
+            .map_or(lines.len(), |next| next.line);
+        // Cards stop at a `###` inside the column, which is left alone.
+        let body_end = headings[idx + 1..]
+            .iter()
+            .find(|next| next.line < end)
+            .map_or(end, |next| next.line);
+        columns.push(Column {
+            title: heading.title.clone(),
+            line: heading.line,
+            cards: cards(lines, heading.line + 1..body_end),
+        });
+    }
+    columns
+}
+
+fn cards(lines: &[String], range: Range<usize>) -> Vec<Card> {
+    let mut cards: Vec<Card> = Vec::new();
+    let mut in_fence = false;
+    // Whether the open fence is nested in the last card.
+    let mut fence_in_card = false;
+    for row in range {
+        let line = &lines[row];
+        let trimmed = line.trim_start();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        let indented = line.starts_with([' ', '\t']);
+        let extends = cards.last().is_some_and(|card| card.lines.end == row)
+            && (indented
+                || (in_fence && fence_in_card)
+                || (line.trim().is_empty() && continues(lines, row)));
+        if fence {
+            if !in_fence {
+                fence_in_card = extends;
+            }
+            in_fence = !in_fence;
+        }
+        if extends {
+            if let Some(card) = cards.last_mut() {
+                card.lines.end = row + 1;
+            }
+            continue;
+        }
+        if in_fence || fence || indented {
+            continue;
+        }
+        if let Some(prefix) = wrap::list_prefix(line) {
+            cards.push(Card {
+                title: line[prefix..].trim().to_string(),
+                lines: row..row + 1,
+                done: task::parse_checkbox(line),
+            });
+        }
+    }
+    cards
+}
+
+/// Whether the blank line at `row` sits inside a card: the next non-blank
+/// line is indented under it.
+fn continues(lines: &[String], row: usize) -> bool {
+    lines[row..]
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.starts_with([' ', '\t']))
+}
+
+/// Move card `card` of column `from` to the end of column `to`.
+///
+/// Returns the rewritten lines and the card's index in its new column, or
+/// `None` when either index is out of range or the columns are the same.
+pub fn move_card(
+    lines: &[String],
+    from: usize,
+    card: usize,
+    to: usize,
+) -> Option<(Vec<String>, usize)> {
+    let board = columns(lines);
+    let moved = board.get(from)?.cards.get(card)?.lines.clone();
+    let target = board.get(to).filter(|_| from != to)?;
+
+    let at = match target.cards.last() {
+        Some(last) => last.lines.end,
+        // Below the heading and the blank line after it, if there is one.
+        None => {
+            let below = target.line + 1;
+            if lines.get(below).is_some_and(|line| line.trim().is_empty())
+                && below + 1 < lines.len()
+            {
+                below + 1
+            } else {
+                below
+            }
+        }
+    };
+    let block: Vec<String> = lines[moved.clone()].to_vec();
+    let mut out: Vec<String> = lines.to_vec();
+    out.drain(moved.clone());
+    let at = if at > moved.start {
+        at - moved.len()
+    } else {
+        at
+    };
+    out.splice(at..at, block);
+    Some((out, target.cards.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_columns_and_cards() {
+        let note = lines(
+            "# Board\n\n## Todo\n\n- write\n  - outline\n- [ ] review\n\n## Done\n```\n- code\n```\n### Notes\n- not a card\n",
+        );
+        let board = columns(&note);
+        assert_eq!(board.len(), 2);
+        assert_eq!(board[0].title, "Todo");
+        assert_eq!(
+            board[0].cards,
+            [
+                Card {
+                    title: "write".to_string(),
+                    lines: 4..6,
+                    done: None,
+                },
+                Card {
+                    title: "review".to_string(),
+                    lines: 6..7,
+                    done: Some(false),
+                },
+            ]
+        );
+        assert!(board[1].cards.is_empty());
+    }
+
+    #[test]
+    fn test_move_card_rewrites_markdown() {
+        let note = lines("## Todo\n\n- write\n  - outline\n- review\n\n## Done\n\n");
+        let (moved, idx) = move_card(&note, 0, 0, 1).unwrap();
+        assert_eq!(
+            moved.join("\n"),
+            "## Todo\n\n- review\n\n## Done\n\n- write\n  - outline\n"
+        );
+        assert_eq!(idx, 0);
+
+        let (back, idx) = move_card(&moved, 1, 0, 0).unwrap();
+        assert_eq!(
+            back.join("\n"),
+            "## Todo\n\n- review\n- write\n  - outline\n\n## Done\n\n"
+        );
+        assert_eq!(idx, 1);
+        assert_eq!(move_card(&note, 0, 0, 0), None);
+        assert_eq!(move_card(&note, 1, 0, 0), None);
+    }
+}
@@ -0,0 +1,659 @@
+//! Building markdown and wiki links from plain text.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static MARKDOWN_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[[^\]]*\]\(\s*<?([^)\s>]+)>?(?:\s+[^)]*)?\)").expect("valid link regex")
+});
+/// `[[target#heading|label]]`, capturing the target and what follows it.
+static WIKILINK_PARTS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\]|#^]+)([^\]]*)\]\]").expect("valid wikilink regex"));
+static BARE_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:[a-zA-Z][a-zA-Z0-9+.-]*://|mailto:)[^\s<>`]+").expect("valid url regex")
+});
+
+/// Whether `text` is a single bare URL worth turning into a link target.
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return false;
+    }
+
+    if text.starts_with("mailto:") {
+        return text.len() > "mailto:".len();
+    }
+    text.split_once("://").is_some_and(|(scheme, rest)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            && !rest.is_empty()
+    })
+}
+
+/// Target of the markdown link or bare URL covering byte column `col`.
+///
+/// Markdown link targets are returned as written, so they may be relative
+/// paths rather than URLs.
+pub fn link_target_at(line: &str, col: usize) -> Option<String> {
+    let covers = |m: regex::Match| m.start() <= col && col < m.end();
+
+    if let Some(caps) = MARKDOWN_LINK_RE
+        .captures_iter(line)
+        .find(|caps| caps.get(0).is_some_and(covers))
+    {
+        return Some(caps[1].to_string());
+    }
+
+    let url = BARE_URL_RE.find_iter(line).find(|m| covers(*m))?;
+    // Sentence punctuation after a URL is rarely part of it; a closing paren
+    // only is when the URL opened one, as in Wikipedia links.
+    let mut url = url.as_str();
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(inner) if inner.matches('(').count() < trimmed.matches(')').count() => inner,
+            _ => trimmed,
+        };
+        if trimmed == url {
+            break;
+        }
+        url = trimmed;
+    }
+    Some(url.to_string())
+}
+
+/// `[label](url)`, escaping brackets in the label and spaces/parens in the URL.
+pub fn markdown_link(label: &str, url: &str) -> String {
+    let label = label.replace('[', "\\[").replace(']', "\\]");
+    let url = url
+        .trim()
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29");
+    format!("[{label}]({url})")
+}
+
+/// `[[target]]`, or `[[target|label]]` when the label differs from the target.
+pub fn wikilink(target: &str, label: &str) -> String {
+    let label = label.trim();
+    if label.is_empty() || label == target {
+        format!("[[{target}]]")
+    } else {
+        format!("[[{target}|{label}]]")
+    }
+}
+
+/// Wikilink target for a vault-relative path: the note name without `.md`
+/// (attachments keep their extension), or the whole path when `ambiguous`
+/// because another file in the vault has the same name.
+pub fn wikilink_target(rel_path: &str, ambiguous: bool) -> String {
+    let target = if ambiguous {
+        rel_path
+    } else {
+        rel_path.rsplit('/').next().unwrap_or(rel_path)
+    };
+    target.strip_suffix(".md").unwrap_or(target).to_string()
+}
+
+/// The two ways a wikilink can name a note: by file name and by vault path,
+/// both without `.md`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteName {
+    pub name: String,
+    pub path: String,
+}
+
+impl NoteName {
+    pub fn of(rel_path: &str) -> Self {
+        Self {
+            name: wikilink_target(rel_path, false),
+            path: wikilink_target(rel_path, true),
+        }
+    }
+}
+
+/// Targets of every wikilink in `text`, without `#heading` or `|label`.
+pub fn wikilink_targets(text: &str) -> impl Iterator<Item = &str> {
+    WIKILINK_PARTS_RE
+        .captures_iter(text)
+        .filter_map(|caps| caps.get(1))
+        .map(|target| target.as_str().trim())
+        .filter(|target| !target.is_empty())
+}
+
+/// The `Heading` or `^block` after `#` in `[[Note#Heading|alias]]`, or
+/// the `^block` of `[[Note^block]]`.
+pub fn wikilink_anchor(wikilink: &str) -> Option<&str> {
+    let inner = wikilink.strip_prefix("[[")?.strip_suffix("]]")?;
+    let target = inner.split('|').next()?;
+    let anchor = match target.split_once('#') {
+        Some((_, anchor)) => anchor,
+        None => &target[target.find('^')?..],
+    };
+    Some(anchor.trim()).filter(|anchor| !anchor.trim_start_matches('^').is_empty())
+}
+
+/// The unclosed `[[…` before byte column `col`: the note typed so far and,
+/// once a `#` is typed, the heading typed so far.
+pub fn wikilink_partial_before(line: &str, col: usize) -> Option<(&str, Option<&str>)> {
+    let before = &line[..col];
+    let open = before.rfind("[[")?;
+    let inner = &before[open + 2..];
+    if inner.contains(['[', ']', '|']) {
+        return None;
+    }
+    Some(match inner.split_once('#') {
+        Some((note, heading)) => (note, Some(heading)),
+        None => (inner, None),
+    })
+}
+
+/// Point every wikilink to `from` in `text` at `to`, matching by name or by
+/// path in any case and keeping `#heading` and `|label` parts. Links written
+/// as paths stay paths. Returns the new text and how many links changed.
+pub fn rename_wikilinks(text: &str, from: &NoteName, to: &NoteName) -> (String, usize) {
+    let mut count = 0;
+    let renamed = WIKILINK_PARTS_RE.replace_all(text, |caps: &regex::Captures| {
+        let target = caps[1].trim();
+        let replacement = if target.eq_ignore_ascii_case(&from.path) && target.contains('/') {
+            &to.path
+        } else if target.eq_ignore_ascii_case(&from.name) {
+            &to.name
+        } else {
+            return caps[0].to_string();
+        };
+        if caps[1] == *replacement {
+            return caps[0].to_string();
+        }
+        count += 1;
+        format!("[[{replacement}{}]]", &caps[2])
+    });
+    (renamed.into_owned(), count)
+}
+
+/// A link in a note that points at nothing in the vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// Zero-based line.
+    pub line: usize,
+    /// The link as written, e.g. `[[Missing]]` or `[doc](gone.md)`.
+    pub text: String,
+}
+
+/// A wikilink or relative markdown link found by [`note_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLink {
+    /// Zero-based line.
+    pub line: usize,
+    /// The link as written.
+    pub text: String,
+    pub target: LinkTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// A wikilink target, without `#heading` or `|label`.
+    Note(String),
+    /// A markdown link path relative to the note's folder, decoded and
+    /// without its `#anchor`.
+    File(String),
+}
+
+impl NoteLink {
+    /// Whether the link points at nothing: a wikilink that `resolves`
+    /// rejects, or a file missing from `exists` under `note_dir`.
+    pub fn is_broken(
+        &self,
+        note_dir: &Path,
+        resolves: impl Fn(&str) -> bool,
+        exists: impl Fn(&Path) -> bool,
+    ) -> bool {
+        match &self.target {
+            LinkTarget::Note(target) => !resolves(target),
+            LinkTarget::File(path) => !exists(&normalize(&note_dir.join(path))),
+        }
+    }
+}
+
+/// Wikilinks and relative markdown links in `text`, skipping fenced code
+/// blocks. URLs, anchors and absolute paths are left out.
+pub fn note_links(text: &str) -> Vec<NoteLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    for (line_idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        for caps in WIKILINK_PARTS_RE.captures_iter(line) {
+            let target = caps[1].trim();
+            if !target.is_empty() {
+                links.push(NoteLink {
+                    line: line_idx,
+                    text: caps[0].to_string(),
+                    target: LinkTarget::Note(target.to_string()),
+                });
+            }
+        }
+        for caps in MARKDOWN_LINK_RE.captures_iter(line) {
+            let target = &caps[1];
+            let has_scheme = target.split_once(':').is_some_and(|(scheme, _)| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+            });
+            if has_scheme || target.starts_with(['#', '/']) {
+                continue;
+            }
+            let path = target.split('#').next().unwrap_or(target);
+            links.push(NoteLink {
+                line: line_idx,
+                text: caps[0].to_string(),
+                target: LinkTarget::File(percent_decode(path)),
+            });
+        }
+    }
+    links
+}
+
+/// Wikilinks that `resolves` rejects and relative markdown links whose file
+/// is missing from `exists`, resolved against `note_dir`. Fenced code
+/// blocks are skipped; URLs, anchors and absolute paths are not checked.
+pub fn broken_links(
+    text: &str,
+    note_dir: &Path,
+    resolves: impl Fn(&str) -> bool,
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<BrokenLink> {
+    note_links(text)
+        .into_iter()
+        .filter(|link| link.is_broken(note_dir, &resolves, &exists))
+        .map(|link| BrokenLink {
+            line: link.line,
+            text: link.text,
+        })
+        .collect()
+}
+
+/// Rewrite relative markdown link targets in a note moving from `old_dir`
+/// to `new_dir` while the note at `from` moves to `to`: each target is
+/// resolved against the old folder and written relative to the new one.
+/// URLs, anchors and absolute paths are left alone. Returns the new text
+/// and how many links changed.
+pub fn rebase_markdown_links(
+    text: &str,
+    old_dir: &Path,
+    new_dir: &Path,
+    from: &Path,
+    to: &Path,
+) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut count = 0;
+    for caps in MARKDOWN_LINK_RE.captures_iter(text) {
+        let Some(target) = caps.get(1) else {
+            continue;
+        };
+        let Some(rebased) = rebase_target(target.as_str(), old_dir, new_dir, from, to) else {
+            continue;
+        };
+        out.push_str(&text[copied..target.start()]);
+        out.push_str(&rebased);
+        copied = target.end();
+        count += 1;
+    }
+    out.push_str(&text[copied..]);
+    (out, count)
+}
+
+fn rebase_target(
+    target: &str,
+    old_dir: &Path,
+    new_dir: &Path,
+    from: &Path,
+    to: &Path,
+) -> Option<String> {
+    let has_scheme = target.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+    });
+    if has_scheme || target.starts_with(['#', '/']) {
+        return None;
+    }
+    let (path, anchor) = match target.split_once('#') {
+        Some((path, anchor)) => (path, Some(anchor)),
+        None => (target, None),
+    };
+    let resolved = normalize(&old_dir.join(path.replace("%20", " ")));
+    let moved = if resolved == normalize(from) {
+        to.to_path_buf()
+    } else {
+        resolved.clone()
+    };
+    if old_dir == new_dir && moved == resolved {
+        return None;
+    }
+    let mut rebased = relative_path(new_dir, &moved).replace(' ', "%20");
+    if let Some(anchor) = anchor {
+        rebased.push('#');
+        rebased.push_str(anchor);
+    }
+    (rebased != target).then_some(rebased)
+}
+
+/// `path` with `.` and `..` resolved without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for part in path.components() {
+        match part {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `target` relative to the folder `base`, with `/` separators.
+pub fn relative_path(base: &Path, target: &Path) -> String {
+    let base = normalize(base);
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let shared = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let ups = std::iter::repeat_n("..".to_string(), base.len() - shared);
+    let downs = target[shared..]
+        .iter()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned());
+    ups.chain(downs).collect::<Vec<_>>().join("/")
+}
+
+/// Apply `wrap` to `text` without its surrounding whitespace, so wrapping a
+/// selected word (or whole line) keeps the spacing and newline outside.
+pub fn wrap_trimmed(text: &str, wrap: impl FnOnce(&str) -> String) -> String {
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len().max(start);
+    format!(
+        "{}{}{}",
+        &text[..start],
+        wrap(&text[start..end]),
+        &text[end..]
+    )
+}
+
+/// URI scheme handled by `blackbox --uri`.
+pub const URI_SCHEME: &str = "blackbox://";
+
+/// Percent-encode everything except unreserved characters and `/`.
+pub fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~' | b'/') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// GitHub-style heading anchor: lowercase, spaces to `-`, punctuation dropped.
+pub fn heading_slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `blackbox://open?path=<vault-relative path>[&heading=<title>]`.
+pub fn note_uri(rel_path: &str, heading: Option<&str>) -> String {
+    let mut uri = format!("{URI_SCHEME}open?path={}", percent_encode(rel_path));
+    if let Some(heading) = heading {
+        uri.push_str("&heading=");
+        uri.push_str(&percent_encode(heading));
+    }
+    uri
+}
+
+/// Decode `%XX` escapes (and `+` as space, as browsers send query strings).
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |b: u8| (b as char).to_digit(16);
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    out.push((hi * 16 + lo) as u8);
+                    i += 3;
+                    continue;
+                }
+                out.push(b'%');
+            }
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A parsed `blackbox://open?…` link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteUri {
+    /// Vault-relative note path.
+    pub path: String,
+    pub heading: Option<String>,
+}
+
+/// Parse a URI produced by [`note_uri`]; unknown query keys are ignored.
+pub fn parse_note_uri(uri: &str) -> Option<NoteUri> {
+    let rest = uri.trim().strip_prefix(URI_SCHEME)?;
+    let (action, query) = rest.split_once('?')?;
+    if action.trim_end_matches('/') != "open" {
+        return None;
+    }
+
+    let mut path = None;
+    let mut heading = None;
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "path" | "file" => path = Some(percent_decode(value)),
+            "heading" => heading = Some(percent_decode(value)).filter(|h| !h.is_empty()),
+            _ => {}
+        }
+    }
+    let path = path.filter(|p| !p.is_empty())?;
+    Some(NoteUri { path, heading })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broken_links() {
+        let text = "[[Inbox]] [[gone|label]] ![[lost.png]]\n```\n[[in code]]\n```\n[ok](sub/a%20b.md#x) [bad](../nope.md) [web](https://x.y) [top](#top)\n";
+        let broken = broken_links(
+            text,
+            Path::new("/v/notes"),
+            |target| target.eq_ignore_ascii_case("inbox"),
+            |path| path == Path::new("/v/notes/sub/a b.md"),
+        );
+        assert_eq!(
+            broken,
+            [
+                BrokenLink {
+                    line: 0,
+                    text: "[[gone|label]]".to_string()
+                },
+                BrokenLink {
+                    line: 0,
+                    text: "[[lost.png]]".to_string()
+                },
+                BrokenLink {
+                    line: 4,
+                    text: "[bad](../nope.md)".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://example.com/a?b=c"));
+        assert!(is_url("  obsidian://open?vault=x\n"));
+        assert!(is_url("mailto:me@example.com"));
+        assert!(!is_url("example.com"));
+        assert!(!is_url("see https://example.com"));
+        assert!(!is_url("://missing-scheme"));
+    }
+
+    #[test]
+    fn test_link_target_at() {
+        let line = "see [docs](https://x.io/a \"Title\") or (https://en.wikipedia.org/wiki/Rust_(language)).";
+        assert_eq!(link_target_at(line, 6).as_deref(), Some("https://x.io/a"));
+        assert_eq!(
+            link_target_at(line, 50).as_deref(),
+            Some("https://en.wikipedia.org/wiki/Rust_(language)")
+        );
+        assert_eq!(link_target_at(line, 0), None);
+        assert_eq!(
+            link_target_at("[spec](../specs/api.md)", 2).as_deref(),
+            Some("../specs/api.md")
+        );
+    }
+
+    #[test]
+    fn test_link_builders() {
+        assert_eq!(
+            markdown_link("the [docs]", "https://x.io/a (b)"),
+            "[the \\[docs\\]](https://x.io/a%20%28b%29)"
+        );
+        assert_eq!(wikilink("Ideas", "Ideas"), "[[Ideas]]");
+        assert_eq!(wikilink("Ideas", "my ideas"), "[[Ideas|my ideas]]");
+        assert_eq!(wikilink_target("projects/Roadmap.md", false), "Roadmap");
+        assert_eq!(
+            wikilink_target("projects/Roadmap.md", true),
+            "projects/Roadmap"
+        );
+        assert_eq!(wikilink_target("assets/logo.png", false), "logo.png");
+        assert_eq!(wrap_trimmed(" word \n", |w| wikilink(w, w)), " [[word]] \n");
+    }
+
+    #[test]
+    fn test_rename_wikilinks() {
+        let from = NoteName::of("projects/Roadmap.md");
+        let to = NoteName::of("archive/Plan.md");
+        let text = "See [[roadmap]], [[Projects/Roadmap#Q3|the plan]] and ![[Roadmap]].\n\
+                    Not [[Roadmapping]] or [[other/Roadmap]].";
+        let (renamed, count) = rename_wikilinks(text, &from, &to);
+        assert_eq!(count, 3);
+        assert_eq!(
+            renamed,
+            "See [[Plan]], [[archive/Plan#Q3|the plan]] and ![[Plan]].\n\
+             Not [[Roadmapping]] or [[other/Roadmap]]."
+        );
+        assert_eq!(
+            rename_wikilinks("none", &from, &to),
+            ("none".to_string(), 0)
+        );
+        let moved = NoteName::of("archive/Roadmap.md");
+        assert_eq!(rename_wikilinks("[[Roadmap]]", &from, &moved).1, 0);
+    }
+
+    #[test]
+    fn test_rebase_markdown_links() {
+        let v = Path::new("/v");
+        let (from, to) = (v.join("inbox/plan.md"), v.join("projects/q3/plan.md"));
+
+        // Another note linking to the moved one.
+        let text = "[p](../inbox/plan.md#goals) [x](other.md) [u](https://e.com/plan.md)";
+        let daily = v.join("daily");
+        let (out, count) = rebase_markdown_links(text, &daily, &daily, &from, &to);
+        assert_eq!(count, 1);
+        assert_eq!(
+            out,
+            "[p](../projects/q3/plan.md#goals) [x](other.md) [u](https://e.com/plan.md)"
+        );
+
+        // The moved note's own links follow it.
+        let text = "![](img/a%20b.png) [top](#top) [d](../daily/today.md)";
+        let (old_dir, new_dir) = (v.join("inbox"), v.join("projects/q3"));
+        let (out, count) = rebase_markdown_links(text, &old_dir, &new_dir, &from, &to);
+        assert_eq!(count, 2);
+        assert_eq!(
+            out,
+            "![](../../inbox/img/a%20b.png) [top](#top) [d](../../daily/today.md)"
+        );
+    }
+
+    #[test]
+    fn test_wikilink_anchor_and_partial() {
+        assert_eq!(wikilink_anchor("[[Plan#Goals|the goals]]"), Some("Goals"));
+        assert_eq!(wikilink_anchor("[[Plan#^a1b2]]"), Some("^a1b2"));
+        assert_eq!(wikilink_anchor("[[Plan|x#y]]"), None);
+        assert_eq!(wikilink_anchor("[[Plan#]]"), None);
+        assert_eq!(wikilink_anchor("[[Plan^a1b2|quote]]"), Some("^a1b2"));
+        assert_eq!(wikilink_anchor("[[Plan^]]"), None);
+
+        assert_eq!(wikilink_partial_before("see [[Pla", 9), Some(("Pla", None)));
+        assert_eq!(
+            wikilink_partial_before("[[Plan#Go]] ", 9),
+            Some(("Plan", Some("Go")))
+        );
+        assert_eq!(wikilink_partial_before("[[", 2), Some(("", None)));
+        assert_eq!(wikilink_partial_before("[[Plan]] x", 10), None);
+        assert_eq!(wikilink_partial_before("[[Plan|la", 9), None);
+        assert_eq!(wikilink_partial_before("[a] b", 5), None);
+    }
+
+    #[test]
+    fn test_note_uri_and_slug() {
+        assert_eq!(
+            note_uri("projects/Q3 plan.md", Some("Next steps & risks")),
+            "blackbox://open?path=projects/Q3%20plan.md&heading=Next%20steps%20%26%20risks"
+        );
+        assert_eq!(note_uri("a.md", None), "blackbox://open?path=a.md");
+        assert_eq!(heading_slug("Next steps & risks!"), "next-steps--risks");
+    }
+
+    #[test]
+    fn test_parse_note_uri_roundtrip() {
+        let uri = note_uri("projects/Q3 plan.md", Some("Next steps & risks"));
+        assert_eq!(
+            parse_note_uri(&uri),
+            Some(NoteUri {
+                path: "projects/Q3 plan.md".to_string(),
+                heading: Some("Next steps & risks".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_note_uri("blackbox://open/?path=a+b.md&x=1").map(|u| u.path),
+            Some("a b.md".to_string())
+        );
+        assert_eq!(parse_note_uri("blackbox://open?heading=x"), None);
+        assert_eq!(parse_note_uri("https://open?path=a.md"), None);
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
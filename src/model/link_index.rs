@@ -0,0 +1,529 @@
+//! Links, aliases and tags of every note, kept on disk between runs and
+//! updated from saves and watcher events, so backlinks, the graph and link
+//! checks work from memory instead of re-reading the vault.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::graph::LinkGraph;
+use super::link::{self, LinkTarget, NoteLink};
+use super::note_index::note_aliases;
+use super::query;
+use super::search_index::modified;
+
+const HEADER: &str = "blackbox-link-index 1";
+
+#[derive(Debug, Default)]
+struct Note {
+    /// Modification time in nanoseconds, to skip unchanged files.
+    modified: u128,
+    links: Vec<NoteLink>,
+    /// Trimmed text of each line holding a link, for backlink previews.
+    previews: BTreeMap<usize, String>,
+    tags: Vec<String>,
+    aliases: Vec<String>,
+}
+
+impl Note {
+    fn read(text: &str, modified: u128) -> Self {
+        let links = link::note_links(text);
+        let lines: Vec<&str> = text.lines().collect();
+        let previews = links
+            .iter()
+            .filter_map(|link| Some((link.line, field(lines.get(link.line)?.trim()))))
+            .collect();
+        Self {
+            modified,
+            links,
+            previews,
+            tags: query::note_tags(text),
+            aliases: note_aliases(text),
+        }
+    }
+}
+
+/// A line that links to a note, from [`LinkIndex::backlinks`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Backlink<'a> {
+    pub path: &'a PathBuf,
+    /// Zero-based line.
+    pub line: usize,
+    pub preview: &'a str,
+    /// The `#Heading` or `^block` the link points at.
+    pub anchor: Option<&'a str>,
+}
+
+#[derive(Debug, Default)]
+pub struct LinkIndex {
+    root: PathBuf,
+    notes: HashMap<PathBuf, Note>,
+    /// Lowercased file names and the notes that have them.
+    names: HashMap<String, BTreeSet<PathBuf>>,
+    /// Lowercased aliases and the notes that claim them.
+    aliases: HashMap<String, BTreeSet<PathBuf>>,
+}
+
+/// Where the index is kept between runs.
+pub fn default_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        return project_dirs.data_dir().join("link-index.tsv");
+    }
+
+    PathBuf::from(".blackbox-link-index.tsv")
+}
+
+/// `text` made safe for one tab-separated field.
+fn field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Drop `path` from the notes under `key`, and the key once it has none.
+fn forget(map: &mut HashMap<String, BTreeSet<PathBuf>>, key: &str, path: &Path) {
+    if let Some(notes) = map.get_mut(key) {
+        notes.remove(path);
+        if notes.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
+/// Notes among `notes` that the wikilink `target` names, ignoring case and
+/// sorted: the one at that vault path when the target has a folder, else
+/// every note whose path ends with the target, so `[[Note]]` and
+/// `[[folder/Note]]` both find `area/folder/Note.md`.
+pub fn path_candidates<'a>(
+    root: &Path,
+    notes: impl IntoIterator<Item = &'a PathBuf>,
+    target: &str,
+) -> Vec<&'a PathBuf> {
+    let want = format!("{}.md", target.trim_matches('/')).to_lowercase();
+    let suffix = format!("/{want}");
+    let mut found = Vec::new();
+    for path in notes {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/").to_lowercase();
+        if rel == want && want.contains('/') {
+            return vec![path];
+        }
+        if rel == want || rel.ends_with(&suffix) {
+            found.push(path);
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Move the candidate in `dir`, the linking note's folder, to the front.
+/// Returns whether there was one.
+pub fn prefer_folder<P: AsRef<Path>>(candidates: &mut [P], dir: Option<&Path>) -> bool {
+    let Some(pos) = candidates
+        .iter()
+        .position(|path| path.as_ref().parent() == dir)
+    else {
+        return false;
+    };
+    candidates[..=pos].rotate_right(1);
+    true
+}
+
+fn is_note(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+impl LinkIndex {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        }
+    }
+
+    /// The vault the index covers.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The index saved by [`save`](Self::save) for `root`, or an empty one
+    /// when the file is missing, unreadable or for another vault.
+    pub fn load(root: &Path, cache: &Path) -> Self {
+        let mut index = Self::new(root);
+        let Ok(text) = std::fs::read_to_string(cache) else {
+            return index;
+        };
+        let mut lines = text.lines();
+        if lines.next() != Some(&format!("{HEADER}\t{}", root.display())) {
+            return index;
+        }
+
+        let mut current: Option<(PathBuf, Note)> = None;
+        for line in lines {
+            let mut fields = line.split('\t');
+            let kind = fields.next().unwrap_or_default();
+            if kind == "N" {
+                if let Some((path, note)) = current.take() {
+                    index.insert(path, note);
+                }
+                let (Some(Ok(modified)), Some(rel)) =
+                    (fields.next().map(str::parse), fields.next())
+                else {
+                    continue;
+                };
+                current = Some((
+                    root.join(rel),
+                    Note {
+                        modified,
+                        ..Note::default()
+                    },
+                ));
+                continue;
+            }
+            let Some((_, note)) = current.as_mut() else {
+                continue;
+            };
+            match (kind, fields.next(), fields.next(), fields.next()) {
+                ("W" | "F", Some(row), Some(target), Some(text)) => {
+                    let Ok(row) = row.parse() else {
+                        continue;
+                    };
+                    let target = match kind {
+                        "W" => LinkTarget::Note(target.to_string()),
+                        _ => LinkTarget::File(target.to_string()),
+                    };
+                    note.links.push(NoteLink {
+                        line: row,
+                        text: text.to_string(),
+                        target,
+                    });
+                }
+                ("P", Some(row), Some(preview), None) => {
+                    if let Ok(row) = row.parse() {
+                        note.previews.insert(row, preview.to_string());
+                    }
+                }
+                ("T", Some(tag), None, None) => note.tags.push(tag.to_string()),
+                ("A", Some(alias), None, None) => note.aliases.push(alias.to_string()),
+                _ => {}
+            }
+        }
+        if let Some((path, note)) = current {
+            index.insert(path, note);
+        }
+        index
+    }
+
+    pub fn save(&self, cache: &Path) -> io::Result<()> {
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = io::BufWriter::new(std::fs::File::create(cache)?);
+        writeln!(out, "{HEADER}\t{}", self.root.display())?;
+        for (path, note) in &self.notes {
+            let Some(rel) = path.strip_prefix(&self.root).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            if rel.contains(['\t', '\n']) {
+                continue;
+            }
+            writeln!(out, "N\t{}\t{rel}", note.modified)?;
+            for link in &note.links {
+                let (kind, target) = match &link.target {
+                    LinkTarget::Note(target) => ("W", target),
+                    LinkTarget::File(target) => ("F", target),
+                };
+                writeln!(
+                    out,
+                    "{kind}\t{}\t{}\t{}",
+                    link.line,
+                    field(target),
+                    field(&link.text)
+                )?;
+            }
+            for (row, preview) in &note.previews {
+                writeln!(out, "P\t{row}\t{preview}")?;
+            }
+            for tag in &note.tags {
+                writeln!(out, "T\t{}", field(tag))?;
+            }
+            for alias in &note.aliases {
+                writeln!(out, "A\t{}", field(alias))?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Bring the index in line with the notes among `files`: read new and
+    /// modified ones, forget the rest. Returns how many notes were read.
+    pub fn refresh(&mut self, files: &[PathBuf]) -> usize {
+        let current: HashSet<&PathBuf> = files.iter().filter(|path| is_note(path)).collect();
+        let gone: Vec<PathBuf> = self
+            .notes
+            .keys()
+            .filter(|path| !current.contains(path))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.remove(&path);
+        }
+
+        let mut read = 0;
+        for path in current {
+            let stale = self
+                .notes
+                .get(path)
+                .is_none_or(|note| Some(note.modified) != modified(path));
+            if stale {
+                self.update(path);
+                read += 1;
+            }
+        }
+        read
+    }
+
+    /// Re-read one note, or drop it when it is gone.
+    pub fn update(&mut self, path: &Path) {
+        self.remove(path);
+        if !is_note(path) {
+            return;
+        }
+        let Some(modified) = modified(path) else {
+            return;
+        };
+        if let Ok(text) = std::fs::read_to_string(path) {
+            self.insert(path.to_path_buf(), Note::read(&text, modified));
+        }
+    }
+
+    /// Index `text` as the note at `path` before it reaches the disk. The
+    /// note is read again once the watcher reports the write.
+    pub fn update_text(&mut self, path: &Path, text: &str) {
+        self.remove(path);
+        if is_note(path) {
+            self.insert(path.to_path_buf(), Note::read(text, 0));
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, note: Note) {
+        if let Some(name) = path.file_name() {
+            self.names
+                .entry(name.to_string_lossy().to_lowercase())
+                .or_default()
+                .insert(path.clone());
+        }
+        for alias in &note.aliases {
+            self.aliases
+                .entry(alias.to_lowercase())
+                .or_default()
+                .insert(path.clone());
+        }
+        self.notes.insert(path, note);
+    }
+
+    fn remove(&mut self, path: &Path) {
+        let Some(note) = self.notes.remove(path) else {
+            return;
+        };
+        if let Some(name) = path.file_name() {
+            forget(
+                &mut self.names,
+                &name.to_string_lossy().to_lowercase(),
+                path,
+            );
+        }
+        for alias in &note.aliases {
+            forget(&mut self.aliases, &alias.to_lowercase(), path);
+        }
+    }
+
+    /// The notes a wikilink target could mean, sorted: see
+    /// [`path_candidates`], then notes with the target as an alias.
+    pub fn candidates(&self, target: &str) -> Vec<&PathBuf> {
+        let file = format!("{target}.md").to_lowercase();
+        let name = file.rsplit('/').next().unwrap_or(&file);
+        let found = self
+            .names
+            .get(name)
+            .map(|notes| path_candidates(&self.root, notes, target))
+            .unwrap_or_default();
+        if !found.is_empty() {
+            return found;
+        }
+        self.aliases
+            .get(&target.to_lowercase())
+            .map(|notes| notes.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// The note a wikilink target in the note at `from` means: the
+    /// candidate in the same folder, else the first.
+    pub fn resolve_from(&self, target: &str, from: &Path) -> Option<&PathBuf> {
+        let mut candidates = self.candidates(target);
+        prefer_folder(&mut candidates, from.parent());
+        candidates.into_iter().next()
+    }
+
+    /// Every indexed note, sorted.
+    pub fn notes(&self) -> Vec<&PathBuf> {
+        let mut notes: Vec<&PathBuf> = self.notes.keys().collect();
+        notes.sort();
+        notes
+    }
+
+    /// Every link in every note, by note.
+    pub fn links(&self) -> impl Iterator<Item = (&PathBuf, &NoteLink)> {
+        self.notes
+            .iter()
+            .flat_map(|(path, note)| note.links.iter().map(move |link| (path, link)))
+    }
+
+    pub fn has_tag(&self, path: &Path, tag: &str) -> bool {
+        self.notes
+            .get(path)
+            .is_some_and(|note| note.tags.iter().any(|t| t == tag))
+    }
+
+    /// Lines in other notes with a wikilink resolving to `target`, sorted.
+    pub fn backlinks(&self, target: &Path) -> Vec<Backlink<'_>> {
+        let mut backlinks = Vec::new();
+        for (path, note) in &self.notes {
+            if path == target {
+                continue;
+            }
+            // The first anchor on a line names the section it links to.
+            let mut rows: BTreeMap<usize, Option<&str>> = BTreeMap::new();
+            for link in &note.links {
+                let LinkTarget::Note(name) = &link.target else {
+                    continue;
+                };
+                if self.resolve_from(name, path).is_some_and(|to| to == target) {
+                    let anchor = rows.entry(link.line).or_default();
+                    *anchor = anchor.or_else(|| link::wikilink_anchor(&link.text));
+                }
+            }
+            backlinks.extend(rows.into_iter().map(|(line, anchor)| Backlink {
+                path,
+                line,
+                preview: note.previews.get(&line).map_or("", String::as_str),
+                anchor,
+            }));
+        }
+        backlinks.sort();
+        backlinks
+    }
+
+    /// The wikilinks between notes, dead links and self-links left out.
+    pub fn graph(&self) -> LinkGraph {
+        let mut graph = LinkGraph::default();
+        for (from, link) in self.links() {
+            if let LinkTarget::Note(name) = &link.target
+                && let Some(to) = self.resolve_from(name, from)
+            {
+                graph.add_link(from, to);
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backlinks_follow_note_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let plan = root.join("plan.md");
+        let team = root.join("sub/team.md");
+        let ideas = root.join("ideas.md");
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(&plan, "---\naliases: [The Plan]\n---\n#work\n").unwrap();
+        std::fs::write(&team, "see [[plan]] and [[Plan|it]]\n[doc](../gone.md)\n").unwrap();
+        std::fs::write(&ideas, "```\n[[plan]]\n```\nfeeds [[the plan#Goals]]\n").unwrap();
+        let files = [
+            plan.clone(),
+            team.clone(),
+            ideas.clone(),
+            root.join("a.png"),
+        ];
+
+        let mut index = LinkIndex::new(root);
+        assert_eq!(index.refresh(&files), 3);
+        assert_eq!(index.refresh(&files), 0);
+        assert_eq!(index.candidates("sub/team"), [&team]);
+        assert_eq!(index.candidates("TEAM"), [&team]);
+        assert_eq!(index.candidates("the plan"), [&plan]);
+        assert!(index.has_tag(&plan, "work"));
+        assert_eq!(
+            index.backlinks(&plan),
+            [
+                Backlink {
+                    path: &ideas,
+                    line: 3,
+                    preview: "feeds [[the plan#Goals]]",
+                    anchor: Some("Goals"),
+                },
+                Backlink {
+                    path: &team,
+                    line: 0,
+                    preview: "see [[plan]] and [[Plan|it]]",
+                    anchor: None,
+                },
+            ]
+        );
+        assert!(!index.graph().is_orphan(&team));
+        assert!(index.links().any(|(path, link)| path == &team
+            && link.target == LinkTarget::File("../gone.md".to_string())));
+
+        let cache = root.join("cache/links.tsv");
+        index.save(&cache).unwrap();
+        let mut loaded = LinkIndex::load(root, &cache);
+        assert_eq!(loaded.backlinks(&plan), index.backlinks(&plan));
+        assert_eq!(loaded.candidates("the plan"), [&plan]);
+        assert_eq!(loaded.refresh(&files), 0);
+
+        index.update_text(&ideas, "[[plan]] [[plan]]\n");
+        assert_eq!(index.backlinks(&plan)[0].preview, "[[plan]] [[plan]]");
+        assert_eq!(index.refresh(&files), 1);
+
+        std::fs::remove_file(&ideas).unwrap();
+        index.update(&ideas);
+        assert_eq!(index.backlinks(&plan).len(), 1);
+        assert_eq!(index.notes(), [&plan, &team]);
+        assert!(
+            LinkIndex::load(&root.join("other"), &cache)
+                .notes
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_same_name_in_different_folders() {
+        let root = PathBuf::from("/v");
+        let notes = [
+            root.join("Note.md"),
+            root.join("work/Note.md"),
+            root.join("home/work/note.md"),
+            root.join("home/Other.md"),
+        ];
+        assert_eq!(path_candidates(&root, &notes, "note").len(), 3);
+        assert_eq!(
+            path_candidates(&root, &notes, "work/Note"),
+            [&notes[1]],
+            "a vault path wins"
+        );
+        assert_eq!(
+            path_candidates(&root, &notes, "home/work/Note"),
+            [&notes[2]]
+        );
+        assert_eq!(path_candidates(&root, &notes, "/work/note"), [&notes[1]]);
+        assert!(path_candidates(&root, &notes, "other/note").is_empty());
+
+        let mut found = path_candidates(&root, &notes, "Note");
+        assert!(prefer_folder(&mut found, Some(&root.join("home/work"))));
+        assert_eq!(found[0], &notes[2]);
+        assert!(!prefer_folder(&mut found, Some(&root.join("home"))));
+    }
+}
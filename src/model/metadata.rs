@@ -0,0 +1,118 @@
+//! Frontmatter of every note in the vault, re-read only when a file's
+//! modification time changes, for views that group notes by metadata.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::model::frontmatter::{Frontmatter, FrontmatterValue};
+
+#[derive(Debug, Default)]
+pub struct MetadataIndex {
+    notes: HashMap<PathBuf, (Option<SystemTime>, Frontmatter)>,
+}
+
+impl MetadataIndex {
+    /// Bring the index up to date with the vault's `files`, forgetting notes
+    /// that are gone. Only `.md` files are read.
+    pub fn refresh(&mut self, files: &[PathBuf]) {
+        let notes: HashSet<&PathBuf> = files
+            .iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        self.notes.retain(|path, _| notes.contains(path));
+        for path in notes {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            if self
+                .notes
+                .get(path)
+                .is_some_and(|(seen, _)| modified.is_some() && *seen == modified)
+            {
+                continue;
+            }
+            let frontmatter = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|text| Frontmatter::parse(&text))
+                .unwrap_or_default();
+            self.notes.insert(path.clone(), (modified, frontmatter));
+        }
+    }
+
+    /// Notes under each value of `key`, sorted by value; a note with a list
+    /// (or comma-separated) value is filed under every item. Notes without
+    /// the key come back separately.
+    pub fn group_by(&self, key: &str) -> (BTreeMap<String, Vec<PathBuf>>, Vec<PathBuf>) {
+        let mut groups: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        let mut ungrouped = Vec::new();
+        for (path, (_, fm)) in &self.notes {
+            let values = fm.get(key).map(field_values).unwrap_or_default();
+            if values.is_empty() {
+                ungrouped.push(path.clone());
+            }
+            for value in values {
+                groups.entry(value).or_default().push(path.clone());
+            }
+        }
+        for notes in groups.values_mut() {
+            notes.sort();
+        }
+        ungrouped.sort();
+        (groups, ungrouped)
+    }
+}
+
+/// The individual values of a field: list items, or a scalar split on
+/// commas, without a leading `#` so `#work` and `work` group together.
+pub fn field_values(value: &FrontmatterValue) -> Vec<String> {
+    let items: Vec<&str> = match value {
+        FrontmatterValue::Scalar(scalar) => scalar.split(',').collect(),
+        FrontmatterValue::List(items) => items.iter().map(String::as_str).collect(),
+        FrontmatterValue::Raw(_) => Vec::new(),
+    };
+    let mut values: Vec<String> = items
+        .into_iter()
+        .map(|item| item.trim().trim_start_matches('#').to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+    values.dedup();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_tags_and_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, text: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        };
+        let a = write("a.md", "---\ntags: [work, '#q3']\nproject: blackbox\n---\n");
+        let b = write("b.md", "---\ntags: work\n---\n");
+        let c = write("c.md", "# no frontmatter\n");
+        let png = write("d.png", "");
+
+        let mut index = MetadataIndex::default();
+        index.refresh(&[a.clone(), b.clone(), c.clone(), png]);
+        let (tags, untagged) = index.group_by("tags");
+        assert_eq!(
+            tags.into_iter().collect::<Vec<_>>(),
+            [
+                ("q3".to_string(), vec![a.clone()]),
+                ("work".to_string(), vec![a.clone(), b.clone()]),
+            ]
+        );
+        assert_eq!(untagged, std::slice::from_ref(&c));
+
+        let (projects, none) = index.group_by("project");
+        assert_eq!(projects["blackbox"], std::slice::from_ref(&a));
+        assert_eq!(none, [b, c]);
+
+        index.refresh(std::slice::from_ref(&a));
+        assert_eq!(index.group_by("tags").0["work"], [a]);
+        assert_eq!(index.group_by("tags").1, Vec::<PathBuf>::new());
+    }
+}
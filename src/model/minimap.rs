@@ -0,0 +1,116 @@
+//! Compressed overview of a note for the minimap column: each row stands
+//! for a run of lines, drawn as a heading title or a bar as long as the
+//! text it covers.
+
+use std::ops::Range;
+
+use crate::model::outline;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Row {
+    /// The highest-level heading among the row's lines.
+    Heading { level: usize, title: String },
+    /// Plain text; `fill` is the bar length in cells, 0 for blank lines.
+    Text { fill: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    /// Buffer lines each row stands for.
+    pub lines_per_row: usize,
+    pub rows: Vec<Row>,
+}
+
+impl Minimap {
+    /// Lay `lines` out in at most `height` rows of `width` cells, with a
+    /// full bar at `text_width` characters.
+    pub fn build(lines: &[String], height: usize, width: usize, text_width: usize) -> Self {
+        let lines_per_row = lines.len().div_ceil(height.max(1)).max(1);
+        let headings = outline::headings(lines.iter().map(String::as_str));
+        let text_width = text_width.max(1);
+
+        let rows = lines
+            .chunks(lines_per_row)
+            .enumerate()
+            .map(|(idx, chunk)| {
+                let span = idx * lines_per_row..idx * lines_per_row + chunk.len();
+                let heading = headings
+                    .iter()
+                    .filter(|h| span.contains(&h.line))
+                    .min_by_key(|h| h.level);
+                if let Some(heading) = heading {
+                    return Row::Heading {
+                        level: heading.level,
+                        title: heading.title.clone(),
+                    };
+                }
+                let longest = chunk
+                    .iter()
+                    .map(|line| line.trim_end().chars().count())
+                    .max()
+                    .unwrap_or(0);
+                Row::Text {
+                    fill: (longest * width).div_ceil(text_width).min(width),
+                }
+            })
+            .collect();
+        Self {
+            lines_per_row,
+            rows,
+        }
+    }
+
+    /// First buffer line of minimap `row`, for jumping there on a click.
+    pub fn line_at(&self, row: usize) -> usize {
+        row.min(self.rows.len().saturating_sub(1)) * self.lines_per_row
+    }
+
+    /// Rows covering the visible lines `top..top + height`.
+    pub fn viewport_rows(&self, top: usize, height: usize) -> Range<usize> {
+        let start = top / self.lines_per_row;
+        let end = (top + height.max(1)).div_ceil(self.lines_per_row);
+        start..end.min(self.rows.len()).max(start + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_rows_compress_and_keep_headings() {
+        let note = lines("# Top\nshort\n\nsome longer text here\n## Sub\nx\n```\n# code\n```");
+        let map = Minimap::build(&note, 4, 10, 20);
+        assert_eq!(map.lines_per_row, 3);
+        assert_eq!(
+            map.rows,
+            [
+                Row::Heading {
+                    level: 1,
+                    title: "Top".to_string()
+                },
+                Row::Heading {
+                    level: 2,
+                    title: "Sub".to_string()
+                },
+                Row::Text { fill: 3 },
+            ]
+        );
+        assert_eq!(map.line_at(1), 3);
+        assert_eq!(map.line_at(99), 6);
+    }
+
+    #[test]
+    fn test_viewport_rows() {
+        let note = vec![String::new(); 100];
+        let map = Minimap::build(&note, 20, 8, 80);
+        assert_eq!(map.lines_per_row, 5);
+        assert_eq!(map.viewport_rows(0, 10), 0..2);
+        assert_eq!(map.viewport_rows(12, 10), 2..5);
+        assert_eq!(map.viewport_rows(99, 10), 19..20);
+    }
+}
@@ -1,5 +1,40 @@
+pub mod activity;
+pub mod attachment;
+pub mod bibtex;
+pub mod block;
 pub mod buffer;
+pub mod capture;
+pub mod code_langs;
 pub mod config;
 pub mod cursor;
+pub mod embed;
 pub mod file_tree;
+pub mod frontmatter;
+pub mod fs_events;
+pub mod graph;
+pub mod journal;
+pub mod kanban;
+pub mod link;
+pub mod link_index;
+pub mod metadata;
+pub mod minimap;
 pub mod mode;
+pub mod note_index;
+pub mod outline;
+pub mod pairs;
+pub mod pane;
+pub mod picker;
+pub mod pins;
+pub mod punctuation;
+pub mod query;
+pub mod quickfix;
+pub mod recent;
+pub mod scratch;
+pub mod search_index;
+pub mod spell;
+pub mod stats;
+pub mod task;
+pub mod toc;
+pub mod trash;
+pub mod wrap;
+pub mod yank_history;
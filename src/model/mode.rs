@@ -15,15 +15,47 @@ pub enum Mode {
     Command,
     /// Fuzzy file finder overlay.
     FinderOpen,
-    /// WikiLink autocomplete picker.
-    LinkPicker,
     /// Confirm creating a new note from WikiLink follow.
     ConfirmCreate,
+    /// Confirm a change to notes on disk, such as `:rename`.
+    Confirm,
     /// Backlinks panel navigation.
     Backlinks,
+    /// Outline panel navigation.
+    Outline,
+    /// Characterwise visual selection (`v`).
+    Visual,
+    /// Linewise visual selection (`V`).
+    VisualLine,
+    /// Spelling suggestions popup (`z=`).
+    SpellSuggest,
+    /// Linked note preview popup (`K`).
+    LinkPreview,
+    /// Local link graph popup (`:graph`).
+    Graph,
+    /// Active note statistics popup (`:stats`).
+    Stats,
+    /// Open tasks across the vault (`:tasks`).
+    Tasks,
+    /// The active note's `##` sections as a board (`:kanban`).
+    Kanban,
+    /// Frontmatter fields of the active note (`:props`).
+    Properties,
+    /// Rendered diagram popup (`:diagram`).
+    DiagramPreview,
+    /// Yank history picker (`:yanks`).
+    YankPicker,
+    /// A plugin's recent log lines (`:plugins.logs`).
+    PluginLogs,
+    /// Plugins offered by the registry (`:plugins.browse`).
+    PluginBrowser,
 }
 
 impl Mode {
+    pub fn is_visual(&self) -> bool {
+        matches!(self, Mode::Visual | Mode::VisualLine)
+    }
+
     pub fn label(&self) -> &'static str {
         match self {
             Mode::Normal => "NORMAL",
@@ -32,9 +64,22 @@ impl Mode {
             Mode::SidebarCreate => "CREATE",
             Mode::Command => "COMMAND",
             Mode::FinderOpen => "FINDER",
-            Mode::LinkPicker => "LINK",
-            Mode::ConfirmCreate => "CONFIRM",
+            Mode::ConfirmCreate | Mode::Confirm => "CONFIRM",
             Mode::Backlinks => "BACKLINKS",
+            Mode::Outline => "OUTLINE",
+            Mode::Visual => "VISUAL",
+            Mode::VisualLine => "V-LINE",
+            Mode::SpellSuggest => "SPELL",
+            Mode::LinkPreview => "PREVIEW",
+            Mode::Graph => "GRAPH",
+            Mode::Stats => "STATS",
+            Mode::Tasks => "TASKS",
+            Mode::Kanban => "KANBAN",
+            Mode::Properties => "PROPS",
+            Mode::DiagramPreview => "DIAGRAM",
+            Mode::YankPicker => "YANKS",
+            Mode::PluginLogs => "LOGS",
+            Mode::PluginBrowser => "BROWSE",
         }
     }
 }
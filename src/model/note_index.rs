@@ -0,0 +1,154 @@
+//! In-memory set of vault files, so the editor can tell resolved wikilinks
+//! from dead ones on every frame without touching the disk.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::model::frontmatter::Frontmatter;
+use crate::model::metadata::field_values;
+
+#[derive(Debug, Default)]
+pub struct NoteIndex {
+    root: PathBuf,
+    /// Vault-relative paths with `/` separators, e.g. `projects/roadmap.md`.
+    paths: HashSet<String>,
+    /// Lowercased file names, for links that name a note in any folder.
+    names: HashSet<String>,
+    /// Lowercased frontmatter `aliases` and the note each one names; the
+    /// first note in path order wins when two share an alias.
+    aliases: HashMap<String, PathBuf>,
+}
+
+/// The `aliases:` a note's frontmatter gives it.
+pub fn note_aliases(text: &str) -> Vec<String> {
+    Frontmatter::parse(text)
+        .and_then(|fm| fm.get("aliases").map(field_values))
+        .unwrap_or_default()
+}
+
+impl NoteIndex {
+    pub fn new(root: &Path, files: &[PathBuf]) -> Self {
+        let mut index = Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        };
+        for file in files {
+            let Ok(rel) = file.strip_prefix(root) else {
+                continue;
+            };
+            index.paths.insert(rel.to_string_lossy().replace('\\', "/"));
+            if let Some(name) = file.file_name() {
+                index.names.insert(name.to_string_lossy().to_lowercase());
+            }
+        }
+        index
+    }
+
+    /// Also resolve links by alias, from `aliases` values grouped by note
+    /// as [`MetadataIndex::group_by`](super::metadata::MetadataIndex::group_by)
+    /// returns them.
+    pub fn with_aliases(mut self, aliases: BTreeMap<String, Vec<PathBuf>>) -> Self {
+        for (alias, mut notes) in aliases {
+            notes.sort();
+            if let Some(note) = notes.into_iter().next() {
+                self.aliases.entry(alias.to_lowercase()).or_insert(note);
+            }
+        }
+        self
+    }
+
+    /// The note with `target` among its aliases, ignoring case.
+    pub fn alias_target(&self, target: &str) -> Option<&PathBuf> {
+        self.aliases.get(&target.to_lowercase())
+    }
+
+    /// Lowercased aliases that resolve to `path`, sorted.
+    pub fn aliases_of(&self, path: &Path) -> Vec<&str> {
+        let mut aliases: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, note)| note.as_path() == path)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    pub fn contains_path(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.root).is_ok_and(|rel| {
+            self.paths
+                .contains(&rel.to_string_lossy().replace('\\', "/"))
+        })
+    }
+
+    /// Whether a sanitized link target (`Note`, `folder/Note`, `diagram.png`)
+    /// names an existing note or attachment, using the same rules as
+    /// following the link: an exact vault path first, then a file name or
+    /// trailing path match anywhere in the vault, ignoring case, then an
+    /// alias.
+    pub fn resolves(&self, target: &str) -> bool {
+        let note = format!("{target}.md");
+        [note.as_str(), target].into_iter().any(|candidate| {
+            self.paths.contains(candidate)
+                || self.names.contains(&candidate.to_lowercase())
+                || (candidate.contains('/') && {
+                    let suffix = format!("/{}", candidate.to_lowercase());
+                    self.paths
+                        .iter()
+                        .any(|path| path.to_lowercase().ends_with(&suffix))
+                })
+        }) || self.alias_target(target).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_paths_names_and_attachments() {
+        let root = PathBuf::from("/vault");
+        let index = NoteIndex::new(
+            &root,
+            &[
+                root.join("work/projects/Roadmap.md"),
+                root.join("inbox.md"),
+                root.join("assets/diagram.png"),
+            ],
+        );
+        assert!(index.resolves("inbox"));
+        assert!(index.resolves("roadmap"));
+        assert!(index.resolves("work/projects/Roadmap"));
+        assert!(index.resolves("projects/Roadmap"));
+        assert!(index.resolves("diagram.png"));
+        assert!(!index.resolves("missing"));
+        assert!(!index.resolves("archive/inbox"));
+        assert!(index.resolves("Projects/roadmap"));
+        assert!(index.resolves("assets/diagram.png"));
+        assert!(index.contains_path(&root.join("inbox.md")));
+        assert!(!index.contains_path(&root.join("missing.md")));
+    }
+
+    #[test]
+    fn test_resolves_aliases() {
+        let root = PathBuf::from("/vault");
+        let roadmap = root.join("projects/Roadmap.md");
+        let inbox = root.join("inbox.md");
+        let aliases = BTreeMap::from([
+            ("Plan".to_string(), vec![roadmap.clone()]),
+            ("Q3 Goals".to_string(), vec![roadmap.clone(), inbox.clone()]),
+        ]);
+        let index = NoteIndex::new(&root, &[roadmap.clone(), inbox.clone()]).with_aliases(aliases);
+        assert!(index.resolves("plan"));
+        assert_eq!(index.alias_target("q3 goals"), Some(&inbox));
+        assert_eq!(index.aliases_of(&roadmap), ["plan"]);
+        assert_eq!(index.aliases_of(&inbox), ["q3 goals"]);
+        assert!(!index.resolves("roadmap2"));
+
+        assert_eq!(
+            note_aliases("---\naliases: [Plan, \"Q3 Goals\"]\n---\n# Roadmap"),
+            ["Plan", "Q3 Goals"]
+        );
+        assert!(note_aliases("# no frontmatter").is_empty());
+    }
+}
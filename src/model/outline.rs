@@ -0,0 +1,338 @@
+//! Heading structure of a note: ATX headings outside fenced code blocks.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 0-indexed line of the heading.
+    pub line: usize,
+    /// Number of leading `#` characters (1–6).
+    pub level: usize,
+    pub title: String,
+}
+
+/// Parse a single line as an ATX heading, returning `(level, title)`.
+pub fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_end();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((
+        level,
+        rest.trim().trim_end_matches('#').trim_end().to_string(),
+    ))
+}
+
+/// All headings in `lines`, skipping anything inside ``` or ~~~ fences.
+pub fn headings<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Heading> {
+    let mut in_fence = false;
+    let mut out = Vec::new();
+
+    for (idx, line) in lines.into_iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some((level, title)) = parse_heading(line) {
+            out.push(Heading {
+                line: idx,
+                level,
+                title,
+            });
+        }
+    }
+    out
+}
+
+/// Section containing `row`: its heading (if any) and the half-open line
+/// range it spans, ending at the next heading of the same or higher level.
+///
+/// Lines before the first heading form an untitled leading section.
+pub fn section_at(
+    headings: &[Heading],
+    row: usize,
+    line_count: usize,
+) -> (Option<&Heading>, std::ops::Range<usize>) {
+    let Some(idx) = headings.iter().rposition(|h| h.line <= row) else {
+        let end = headings.first().map_or(line_count, |h| h.line);
+        return (None, 0..end);
+    };
+
+    let heading = &headings[idx];
+    let end = headings[idx + 1..]
+        .iter()
+        .find(|h| h.level <= heading.level)
+        .map_or(line_count, |h| h.line);
+    (Some(heading), heading.line..end)
+}
+
+/// Headings enclosing `row`, outermost first: the heading of the section it
+/// sits in and each parent heading above that.
+pub fn heading_path(headings: &[Heading], row: usize) -> Vec<&Heading> {
+    let mut path: Vec<&Heading> = Vec::new();
+    for heading in headings.iter().take_while(|h| h.line <= row) {
+        while path.last().is_some_and(|last| last.level >= heading.level) {
+            path.pop();
+        }
+        path.push(heading);
+    }
+    path
+}
+
+/// Line ranges of every section titled `title` (ignoring case), each
+/// including its subsections.
+pub fn sections_titled(
+    headings: &[Heading],
+    title: &str,
+    line_count: usize,
+) -> Vec<std::ops::Range<usize>> {
+    headings
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| h.title.eq_ignore_ascii_case(title.trim()))
+        .map(|(idx, _)| subtree(headings, idx, line_count))
+        .collect()
+}
+
+/// Pull `section:Name` or `section:"Heading name"` out of a search query,
+/// returning the heading and the remaining query.
+pub fn split_section_filter(query: &str) -> (Option<String>, String) {
+    let Some(start) = query.find("section:") else {
+        return (None, query.to_string());
+    };
+    let value_start = start + "section:".len();
+    let rest = &query[value_start..];
+    let (heading, end) = match rest.strip_prefix('"') {
+        Some(quoted) => match quoted.find('"') {
+            Some(close) => (&quoted[..close], value_start + close + 2),
+            None => (quoted, query.len()),
+        },
+        None => {
+            let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (&rest[..len], value_start + len)
+        }
+    };
+    let remaining = format!("{} {}", &query[..start], &query[end..]);
+    let remaining = remaining.split_whitespace().collect::<Vec<_>>().join(" ");
+    let heading = heading.trim();
+    (
+        (!heading.is_empty()).then(|| heading.to_string()),
+        remaining,
+    )
+}
+
+/// Line range of heading `idx`'s section including all of its subsections.
+fn subtree(headings: &[Heading], idx: usize, line_count: usize) -> std::ops::Range<usize> {
+    let heading = &headings[idx];
+    let end = headings[idx + 1..]
+        .iter()
+        .find(|h| h.level <= heading.level)
+        .map_or(line_count, |h| h.line);
+    heading.line..end
+}
+
+/// Swap heading `idx` (with its subtree) with the previous (`delta < 0`) or
+/// next sibling at the same level.
+///
+/// Returns the rewritten lines and the moved heading's new line, or `None`
+/// when there is no sibling in that direction.
+pub fn move_section(lines: &[String], idx: usize, delta: isize) -> Option<(Vec<String>, usize)> {
+    let found = headings(lines.iter().map(String::as_str));
+    let heading = found.get(idx)?;
+    let own = subtree(&found, idx, lines.len());
+
+    let sibling = if delta < 0 {
+        let prev = found[..idx]
+            .iter()
+            .rposition(|h| h.level <= heading.level)?;
+        (found[prev].level == heading.level).then_some(prev)?
+    } else {
+        let next = idx
+            + 1
+            + found[idx + 1..]
+                .iter()
+                .position(|h| h.level <= heading.level)?;
+        (found[next].level == heading.level).then_some(next)?
+    };
+    let other = subtree(&found, sibling, lines.len());
+
+    let (first, second) = if delta < 0 {
+        (other, own)
+    } else {
+        (own, other)
+    };
+    let mut upper: Vec<String> = lines[second.clone()].to_vec();
+    let mut lower: Vec<String> = lines[first.clone()].to_vec();
+
+    // Keep the blank separator where it was: the block that ends up on top
+    // inherits it if the original top block had one.
+    let had_gap = lower.last().is_some_and(|l| l.trim().is_empty());
+    let has_gap = upper.last().is_some_and(|l| l.trim().is_empty());
+    if had_gap && !has_gap {
+        upper.push(String::new());
+        lower.pop();
+    }
+
+    let moved_line = if delta < 0 {
+        first.start
+    } else {
+        first.start + upper.len()
+    };
+
+    let mut out = lines[..first.start].to_vec();
+    out.append(&mut upper);
+    out.append(&mut lower);
+    out.extend_from_slice(&lines[second.end..]);
+    Some((out, moved_line))
+}
+
+/// Promote (`delta < 0`) or demote heading `idx` and its subheadings.
+///
+/// Returns `None` when any heading in the subtree would leave the 1–6 range.
+pub fn shift_section_level(lines: &[String], idx: usize, delta: isize) -> Option<Vec<String>> {
+    let found = headings(lines.iter().map(String::as_str));
+    found.get(idx)?;
+    let range = subtree(&found, idx, lines.len());
+
+    let affected: Vec<&Heading> = found.iter().filter(|h| range.contains(&h.line)).collect();
+    if affected
+        .iter()
+        .any(|h| !(1..=6).contains(&(h.level as isize + delta)))
+    {
+        return None;
+    }
+
+    let mut out = lines.to_vec();
+    for heading in affected {
+        let level = (heading.level as isize + delta) as usize;
+        let rest = out[heading.line].trim_start_matches('#').to_string();
+        out[heading.line] = format!("{}{rest}", "#".repeat(level));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "intro\n# One\ntext\n## Sub\nmore\n```\n# not a heading\n```\n# Two\ntail\n";
+
+    #[test]
+    fn test_headings_skip_code_fences() {
+        let found = headings(NOTE.lines());
+        let titles: Vec<_> = found
+            .iter()
+            .map(|h| (h.line, h.level, h.title.as_str()))
+            .collect();
+        assert_eq!(titles, vec![(1, 1, "One"), (3, 2, "Sub"), (8, 1, "Two")]);
+        assert_eq!(parse_heading("#tag"), None);
+        assert_eq!(
+            parse_heading("## Closed ##"),
+            Some((2, "Closed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_section_at_spans_subsections() {
+        let found = headings(NOTE.lines());
+        let count = NOTE.lines().count();
+
+        let (heading, range) = section_at(&found, 2, count);
+        assert_eq!(heading.map(|h| h.title.as_str()), Some("One"));
+        assert_eq!(range, 1..8);
+
+        let (heading, range) = section_at(&found, 4, count);
+        assert_eq!(heading.map(|h| h.title.as_str()), Some("Sub"));
+        assert_eq!(range, 3..8);
+
+        let (heading, range) = section_at(&found, 0, count);
+        assert!(heading.is_none());
+        assert_eq!(range, 0..1);
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_move_section_swaps_with_sibling_subtree() {
+        let doc = lines("# A\na\n## A1\n\n# B\nb\n");
+        let (out, line) = move_section(&doc, 2, -1).unwrap();
+        assert_eq!(out, lines("# B\nb\n\n# A\na\n## A1\n"));
+        assert_eq!(line, 0);
+
+        let (out, line) = move_section(&doc, 0, 1).unwrap();
+        assert_eq!(out, lines("# B\nb\n\n# A\na\n## A1\n"));
+        assert_eq!(line, 3);
+    }
+
+    #[test]
+    fn test_move_section_stops_at_parent_boundary() {
+        let doc = lines("# A\n## A1\n# B\n## B1\n");
+        assert!(move_section(&doc, 3, -1).is_none());
+        assert!(move_section(&doc, 1, 1).is_none());
+        assert!(move_section(&doc, 0, -1).is_none());
+    }
+
+    #[test]
+    fn test_shift_section_level_moves_subtree() {
+        let doc = lines("# A\n## A1\n### A1a\n# B\n");
+        assert_eq!(
+            shift_section_level(&doc, 1, 1).unwrap(),
+            lines("# A\n### A1\n#### A1a\n# B\n")
+        );
+        assert!(shift_section_level(&doc, 0, -1).is_none());
+    }
+
+    #[test]
+    fn test_heading_path_follows_nesting() {
+        let lines = [
+            "intro",
+            "# Projects",
+            "## Archive",
+            "old",
+            "## Blackbox",
+            "#### TODO",
+            "item",
+            "### Notes",
+        ];
+        let headings = headings(lines);
+        let titles = |row| {
+            heading_path(&headings, row)
+                .into_iter()
+                .map(|h| h.title.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert!(titles(0).is_empty());
+        assert_eq!(titles(3), ["Projects", "Archive"]);
+        assert_eq!(titles(6), ["Projects", "Blackbox", "TODO"]);
+        assert_eq!(titles(7), ["Projects", "Blackbox", "Notes"]);
+    }
+
+    #[test]
+    fn test_section_filter_and_ranges() {
+        assert_eq!(
+            split_section_filter(r#"retry section:"Q3 Goals" async"#),
+            (Some("Q3 Goals".to_string()), "retry async".to_string())
+        );
+        assert_eq!(
+            split_section_filter("section:Sub todo"),
+            (Some("Sub".to_string()), "todo".to_string())
+        );
+        assert_eq!(split_section_filter("plain"), (None, "plain".to_string()));
+
+        let headings = headings(NOTE.lines());
+        let count = NOTE.lines().count();
+        assert_eq!(sections_titled(&headings, "one", count), vec![1..8]);
+        assert_eq!(sections_titled(&headings, "sub", count), vec![3..8]);
+        assert!(sections_titled(&headings, "missing", count).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Auto-paired brackets and backticks, and deleting markup in pairs so a
+//! note is not left with orphaned delimiters.
+//!
+//! Columns are byte offsets into the line, like [`Cursor`](super::cursor).
+
+use std::ops::Range;
+
+/// Characters typed with their closing partner.
+const PAIRS: &[(char, char)] = &[('[', ']'), ('(', ')'), ('`', '`')];
+
+/// Two-character emphasis markers that come in pairs on a line.
+const MARKERS: &[&str] = &["**", "__", "~~", "=="];
+
+/// Closing partner to insert after `ch` typed at `col`, if it pairs there:
+/// only before whitespace, punctuation or the end of the line, and a
+/// backtick not straight after a word or another backtick (so typing a
+/// ```` ``` ```` fence is not disturbed).
+pub fn closer(line: &str, col: usize, ch: char) -> Option<char> {
+    let &(_, close) = PAIRS.iter().find(|(open, _)| *open == ch)?;
+    let next = line[col..].chars().next();
+    if next.is_some_and(|c| !c.is_whitespace() && !")]}".contains(c)) {
+        return None;
+    }
+    let prev = line[..col].chars().next_back();
+    if ch == '`' && prev.is_some_and(|c| c == '`' || c.is_alphanumeric()) {
+        return None;
+    }
+    Some(close)
+}
+
+/// Whether typing `ch` at `col` should step over an identical closer
+/// already there instead of inserting another.
+pub fn steps_over(line: &str, col: usize, ch: char) -> bool {
+    PAIRS.iter().any(|&(_, close)| close == ch) && line[col..].starts_with(ch)
+}
+
+/// Whether Backspace at `col` sits inside an empty pair like `(|)`, so
+/// both halves go together.
+pub fn empty_pair_at(line: &str, col: usize) -> bool {
+    let prev = line[..col].chars().next_back();
+    let next = line[col..].chars().next();
+    PAIRS
+        .iter()
+        .any(|&(open, close)| prev == Some(open) && next == Some(close))
+}
+
+/// The paired emphasis marker ending at `col`, and where its partner is:
+/// `(marker, partner)` byte ranges on the line.
+pub fn marker_before(line: &str, col: usize) -> Option<(Range<usize>, Range<usize>)> {
+    let marker = MARKERS.iter().find(|m| line[..col].ends_with(*m))?;
+    let starts: Vec<usize> = marker_starts(line, marker);
+    let idx = starts
+        .iter()
+        .position(|&start| start + marker.len() == col)?;
+    let partner = if idx % 2 == 0 { idx + 1 } else { idx - 1 };
+    let partner = *starts.get(partner)?;
+    Some((col - marker.len()..col, partner..partner + marker.len()))
+}
+
+/// Non-overlapping occurrences of `marker`, outside inline code.
+fn marker_starts(line: &str, marker: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut in_code = false;
+    let mut at = 0;
+    while at < line.len() {
+        if line[at..].starts_with('`') {
+            in_code = !in_code;
+            at += 1;
+        } else if !in_code && line[at..].starts_with(marker) {
+            starts.push(at);
+            at += marker.len();
+        } else {
+            at += line[at..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairs_open_close_and_delete_together() {
+        assert_eq!(closer("see ", 4, '('), Some(')'));
+        assert_eq!(closer("see word", 4, '['), None);
+        assert_eq!(closer("a ", 2, '`'), Some('`'));
+        assert_eq!(closer("``", 2, '`'), None);
+        assert_eq!(closer("it", 2, '`'), None);
+        assert_eq!(closer("x", 1, '*'), None);
+
+        assert!(steps_over("[]", 1, ']'));
+        assert!(!steps_over("[]", 1, ')'));
+        assert!(empty_pair_at("a ()", 3));
+        assert!(!empty_pair_at("a (b)", 3));
+    }
+
+    #[test]
+    fn test_marker_partner_on_line() {
+        let line = "a **bold** and **more**";
+        assert_eq!(marker_before(line, 4), Some((2..4, 8..10)));
+        assert_eq!(marker_before(line, 10), Some((8..10, 2..4)));
+        assert_eq!(marker_before(line, 23), Some((21..23, 15..17)));
+        assert_eq!(marker_before("**unclosed", 2), None);
+        assert_eq!(marker_before("`**` **x**", 7), Some((5..7, 8..10)));
+    }
+}
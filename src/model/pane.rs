@@ -0,0 +1,107 @@
+//! View state for a split: a second cursor and scroll position into the
+//! active buffer.
+//!
+//! The text, undo history and dirty flag stay on the one [`Buffer`]; only
+//! the cursor and viewport belong to a pane. The focused pane's view lives
+//! in the buffer itself, the other pane's is parked here and swapped in when
+//! focus moves.
+
+use std::path::PathBuf;
+
+use super::buffer::{Buffer, Viewport};
+use super::cursor::CursorState;
+
+#[derive(Debug, Clone)]
+pub struct PaneView {
+    pub cursor: CursorState,
+    pub viewport: Viewport,
+}
+
+impl PaneView {
+    pub fn capture(buffer: &Buffer) -> Self {
+        let mut cursor = buffer.cursor.clone();
+        cursor.selection = None;
+        Self {
+            cursor,
+            viewport: buffer.viewport.clone(),
+        }
+    }
+
+    /// Exchange this view with the buffer's, keeping the incoming cursor
+    /// inside text that may have shrunk while the pane was unfocused.
+    pub fn swap_with(&mut self, buffer: &mut Buffer) {
+        std::mem::swap(&mut self.cursor, &mut buffer.cursor);
+        std::mem::swap(&mut self.viewport, &mut buffer.viewport);
+        buffer.clamp_cursor();
+        let last = buffer.line_count().saturating_sub(1);
+        buffer.viewport.top_line = buffer.viewport.top_line.min(last);
+    }
+}
+
+/// Two stacked panes on the same note.
+#[derive(Debug, Clone)]
+pub struct Split {
+    /// The note both panes show; the split closes if another note is opened.
+    pub path: Option<PathBuf>,
+    /// The unfocused pane.
+    pub other: PaneView,
+    /// Whether the focused pane is the top one.
+    pub focus_top: bool,
+}
+
+impl Split {
+    /// Split `buffer`, focusing the bottom pane; both start at the same spot.
+    pub fn new(buffer: &Buffer) -> Self {
+        Self {
+            path: buffer.path.clone(),
+            other: PaneView::capture(buffer),
+            focus_top: false,
+        }
+    }
+
+    /// Move focus to the other pane.
+    pub fn toggle_focus(&mut self, buffer: &mut Buffer) {
+        self.other.swap_with(buffer);
+        self.focus_top = !self.focus_top;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ropey::Rope;
+
+    fn buffer(text: &str) -> Buffer {
+        let mut buf = Buffer::new();
+        buf.rope = Rope::from_str(text);
+        buf
+    }
+
+    #[test]
+    fn test_panes_keep_independent_cursors() {
+        let mut buf = buffer("one\ntwo\nthree\nfour\n");
+        buf.cursor.move_to(3, 2);
+        let mut split = Split::new(&buf);
+
+        buf.cursor.move_to(0, 1);
+        split.toggle_focus(&mut buf);
+        assert!(split.focus_top);
+        assert_eq!((buf.cursor.row, buf.cursor.col), (3, 2));
+
+        split.toggle_focus(&mut buf);
+        assert_eq!((buf.cursor.row, buf.cursor.col), (0, 1));
+    }
+
+    #[test]
+    fn test_parked_cursor_clamped_after_edits() {
+        let mut buf = buffer("one\ntwo\nthree long line\n");
+        buf.cursor.move_to(2, 10);
+        let mut split = Split::new(&buf);
+
+        buf.cursor.move_to(0, 0);
+        buf.replace_lines(1..3, &["2".to_string()]);
+        split.toggle_focus(&mut buf);
+        assert!(buf.cursor.row < buf.line_count());
+        assert!(buf.cursor.col <= buf.line_text(buf.cursor.row).unwrap().len());
+    }
+}
@@ -0,0 +1,194 @@
+//! Fuzzy ranking shared by the file finder and command-line completion.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+/// Commands whose argument is a note or folder, completed with Tab.
+pub const NOTE_ARGUMENT_COMMANDS: &[&str] = &["merge", "rename"];
+
+/// Commands whose argument is a folder.
+pub const FOLDER_ARGUMENT_COMMANDS: &[&str] = &["move"];
+
+/// The best `limit` items for `query`, best first. An empty query keeps the
+/// original order.
+pub fn rank<T>(items: Vec<T>, query: &str, key: impl Fn(&T) -> String, limit: usize) -> Vec<T> {
+    if query.is_empty() {
+        return items.into_iter().take(limit).collect();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, T)> = items
+        .into_iter()
+        .filter_map(|item| Some((matcher.fuzzy_match(&key(&item), query)?, item)))
+        .collect();
+    scored.sort_by_key(|item| std::cmp::Reverse(item.0));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Tab completion in progress on the command line.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    /// The command and the space before its argument, e.g. `merge `.
+    pub head: String,
+    pub candidates: Vec<String>,
+    /// Shown after each candidate, such as a plugin command's description;
+    /// empty when there is nothing to add.
+    pub details: Vec<String>,
+    pub selected: usize,
+}
+
+impl Completion {
+    /// The command line with the selected candidate filled in.
+    pub fn input(&self) -> String {
+        format!("{}{}", self.head, self.candidates[self.selected])
+    }
+
+    /// Step through the candidates, wrapping at either end.
+    pub fn cycle(&mut self, delta: isize) {
+        let len = self.candidates.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+    }
+}
+
+/// Split `input` into `(head, partial argument)` when it is a command that
+/// takes a note.
+pub fn note_argument(input: &str) -> Option<(&str, &str)> {
+    argument(input, NOTE_ARGUMENT_COMMANDS)
+}
+
+/// Like [`note_argument`], for commands that take a folder.
+pub fn folder_argument(input: &str) -> Option<(&str, &str)> {
+    argument(input, FOLDER_ARGUMENT_COMMANDS)
+}
+
+/// Split `input` into `(head, partial name)` while a command name is still
+/// being typed, on its own or after `plugin ` / `p `.
+pub fn command_name_argument(input: &str) -> Option<(&str, &str)> {
+    let Some((name, rest)) = input.split_once(' ') else {
+        return Some(("", input));
+    };
+    (matches!(name, "plugin" | "p") && !rest.contains(' ')).then(|| input.split_at(name.len() + 1))
+}
+
+/// What has been typed since `trigger` when it starts a word before `col`
+/// and no whitespace follows it, e.g. `doe` in `see @doe|` for `@`.
+pub fn trigger_partial<'a>(line: &'a str, col: usize, trigger: &str) -> Option<&'a str> {
+    let before = line.get(..col)?;
+    let at = before.rfind(trigger)?;
+    let partial = &before[at + trigger.len()..];
+    let starts_word = before[..at]
+        .chars()
+        .next_back()
+        .is_none_or(|prev| prev.is_whitespace() || "([{".contains(prev));
+    (starts_word && !partial.contains(char::is_whitespace)).then_some(partial)
+}
+
+fn argument<'a>(input: &'a str, commands: &[&str]) -> Option<(&'a str, &'a str)> {
+    let (name, _) = input.split_once(' ')?;
+    commands
+        .contains(&name)
+        .then(|| input.split_at(name.len() + 1))
+}
+
+/// Notes (vault-relative, without `.md`) and folders (with a trailing `/`)
+/// matching `partial`, folders first when nothing has been typed.
+pub fn note_candidates(root: &Path, files: &[PathBuf], partial: &str, limit: usize) -> Vec<String> {
+    let mut folders = BTreeSet::new();
+    let mut notes = Vec::new();
+    for file in files {
+        let Ok(rel) = file.strip_prefix(root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        let Some(note) = rel.strip_suffix(".md") else {
+            continue;
+        };
+        let mut dir = note;
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            folders.insert(format!("{parent}/"));
+            dir = parent;
+        }
+        notes.push(note.to_string());
+    }
+    notes.sort();
+    let items: Vec<String> = folders.into_iter().chain(notes).collect();
+    rank(items, partial.trim(), String::clone, limit)
+}
+
+/// Folders holding notes, with a trailing `/`, matching `partial`.
+pub fn folder_candidates(
+    root: &Path,
+    files: &[PathBuf],
+    partial: &str,
+    limit: usize,
+) -> Vec<String> {
+    let folders = note_candidates(root, files, "", usize::MAX)
+        .into_iter()
+        .filter(|candidate| candidate.ends_with('/'))
+        .collect();
+    rank(folders, partial.trim(), String::clone, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_argument_completion() {
+        assert_eq!(note_argument("merge proj"), Some(("merge ", "proj")));
+        assert_eq!(note_argument("merge"), None);
+        assert_eq!(note_argument("count x"), None);
+        assert_eq!(note_argument("archive x"), None);
+
+        let root = PathBuf::from("/vault");
+        let files = [
+            root.join("projects/blackbox/roadmap.md"),
+            root.join("inbox.md"),
+            root.join("assets/logo.png"),
+        ];
+        assert_eq!(
+            note_candidates(&root, &files, "", 10),
+            [
+                "projects/",
+                "projects/blackbox/",
+                "inbox",
+                "projects/blackbox/roadmap"
+            ]
+        );
+        assert_eq!(
+            note_candidates(&root, &files, "roadmp", 10),
+            ["projects/blackbox/roadmap"]
+        );
+        assert_eq!(folder_argument("move pro"), Some(("move ", "pro")));
+        assert_eq!(
+            folder_candidates(&root, &files, "", 10),
+            ["projects/", "projects/blackbox/"]
+        );
+
+        let mut completion = Completion {
+            head: "cat ".to_string(),
+            candidates: vec!["a".to_string(), "b".to_string()],
+            details: Vec::new(),
+            selected: 0,
+        };
+        completion.cycle(-1);
+        assert_eq!(completion.input(), "cat b");
+
+        assert_eq!(command_name_argument("wc"), Some(("", "wc")));
+        assert_eq!(command_name_argument("plugin wc"), Some(("plugin ", "wc")));
+        assert_eq!(command_name_argument("p "), Some(("p ", "")));
+        assert_eq!(command_name_argument("plugin wc now"), None);
+        assert_eq!(command_name_argument("merge x"), None);
+
+        assert_eq!(trigger_partial("see @do", 7, "@"), Some("do"));
+        assert_eq!(trigger_partial("(:: ", 3, "::"), Some(""));
+        assert_eq!(trigger_partial("mail me@x", 9, "@"), None);
+        assert_eq!(trigger_partial("@doe and", 8, "@"), None);
+    }
+}
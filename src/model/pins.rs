@@ -0,0 +1,36 @@
+//! Pinned notes, kept between runs: listed above the sidebar tree and open
+//! as the first tabs. One list serves every vault; each shows its own.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `pins.txt` in the data directory, one path per line.
+pub fn default_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        return project_dirs.data_dir().join("pins.txt");
+    }
+
+    PathBuf::from(".blackbox-pins.txt")
+}
+
+pub fn load(file: &Path) -> Vec<PathBuf> {
+    std::fs::read_to_string(file)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+pub fn save(file: &Path, pins: &[PathBuf]) -> io::Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let text: String = pins
+        .iter()
+        .map(|path| path.to_string_lossy())
+        .filter(|path| !path.contains('\n'))
+        .map(|path| format!("{path}\n"))
+        .collect();
+    std::fs::write(file, text)
+}
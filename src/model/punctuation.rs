@@ -0,0 +1,132 @@
+//! Smart punctuation while typing prose: `--` becomes an en dash, `---` an
+//! em dash, and straight quotes turn into curly ones.
+//!
+//! Each conversion runs after the character is inserted and is its own undo
+//! step, so `u` straight after brings back what was typed literally.
+
+use crate::model::frontmatter::Frontmatter;
+
+/// Replacement for the end of `before` (the line up to and including the
+/// character just typed): how many trailing characters to replace, and
+/// with what.
+pub fn substitution(before: &str) -> Option<(usize, &'static str)> {
+    let mut rev = before.chars().rev();
+    let typed = rev.next()?;
+    let prev = rev.next();
+    let head = &before[..before.len() - typed.len_utf8()];
+    if in_code_span(head) || in_wikilink(head) {
+        return None;
+    }
+
+    match (prev, typed) {
+        (Some('–'), '-') => Some((2, "—")),
+        (Some('-'), '-') if !is_markup_rule(before) && !in_html_comment(before) => Some((2, "–")),
+        (_, '"') => Some((1, if opens_quote(prev) { "“" } else { "”" })),
+        (_, '\'') => Some((1, if opens_quote(prev) { "‘" } else { "’" })),
+        _ => None,
+    }
+}
+
+/// Lines where punctuation stays literal: frontmatter and fenced code,
+/// fence lines included.
+pub fn code_mask(lines: &[String]) -> Vec<bool> {
+    let frontmatter = Frontmatter::parse(&lines.join("\n")).map_or(0, |fm| fm.line_count);
+    let mut fence: Option<&str> = None;
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            if idx < frontmatter {
+                return true;
+            }
+            let trimmed = line.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+            match (fence, marker) {
+                (Some(open), Some(m)) if open == m => fence = None,
+                (None, Some(m)) => fence = Some(m),
+                (Some(_), _) => {}
+                (None, None) => return false,
+            }
+            true
+        })
+        .collect()
+}
+
+/// A quote opens at the start of a line or after a space, bracket or dash;
+/// anywhere else it closes (or is an apostrophe).
+fn opens_quote(prev: Option<char>) -> bool {
+    prev.is_none_or(|c| c.is_whitespace() || "([{<–—-“‘".contains(c))
+}
+
+/// An odd number of backticks means the cursor is inside `code`.
+fn in_code_span(head: &str) -> bool {
+    head.matches('`').count() % 2 == 1
+}
+
+/// Inside an unclosed `[[`, where a curly apostrophe would break the link.
+fn in_wikilink(head: &str) -> bool {
+    head.rfind("[[")
+        .is_some_and(|open| !head[open..].contains("]]"))
+}
+
+fn in_html_comment(before: &str) -> bool {
+    before
+        .rfind("<!-")
+        .is_some_and(|open| !before[open..].contains("-->"))
+}
+
+/// Horizontal rules, frontmatter fences and table separators are made of
+/// dashes that must stay dashes.
+fn is_markup_rule(before: &str) -> bool {
+    before.chars().all(|c| matches!(c, '-' | ':' | '|' | ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Type `text` one character at a time, applying substitutions.
+    fn typed(text: &str) -> String {
+        let mut line = String::new();
+        for ch in text.chars() {
+            line.push(ch);
+            if let Some((count, replacement)) = substitution(&line) {
+                let keep = line.chars().count() - count;
+                line = line.chars().take(keep).collect::<String>() + replacement;
+            }
+        }
+        line
+    }
+
+    #[test]
+    fn test_dashes_and_quotes() {
+        assert_eq!(typed("pages 3--5"), "pages 3–5");
+        assert_eq!(typed("wait---what"), "wait—what");
+        assert_eq!(typed("\"it's (\"fine\")\""), "“it’s (“fine”)”");
+        assert_eq!(typed("'quoted'"), "‘quoted’");
+    }
+
+    #[test]
+    fn test_markup_left_alone() {
+        assert_eq!(typed("---"), "---");
+        assert_eq!(typed("| --- | :--: |"), "| --- | :--: |");
+        assert_eq!(typed("<!-- note -->"), "<!-- note -->");
+        assert_eq!(
+            typed("run `a --flag \"x\"` now"),
+            "run `a --flag \"x\"` now"
+        );
+        assert_eq!(typed("see [[Bob's Note]]"), "see [[Bob's Note]]");
+    }
+
+    #[test]
+    fn test_code_mask_covers_frontmatter_and_fences() {
+        let lines: Vec<String> = "---\ntitle: \"x\"\n---\ntext\n```\ncode\n```\nmore"
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            code_mask(&lines),
+            [true, true, true, false, true, true, true, false]
+        );
+    }
+}
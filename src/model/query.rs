@@ -0,0 +1,250 @@
+//! Content search queries: plain words, `"quoted phrases"`, `-exclusions`,
+//! a `re:` regex and note filters (`tag:#foo`, `path:journal/`,
+//! `title:roadmap`), parsed once before any file is read.
+//!
+//! `lang:` and `section:` are split off first by their own modules.
+
+use std::sync::LazyLock;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::model::code_langs;
+use crate::model::frontmatter::{Frontmatter, FrontmatterValue};
+
+static INLINE_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)#([\p{L}\p{N}_/-]+)").expect("valid inline tag regex"));
+
+#[derive(Debug, Default)]
+pub struct ContentQuery {
+    /// Words and phrases a line must all contain, lowercased.
+    pub terms: Vec<String>,
+    /// Notes containing any of these are left out.
+    pub excluded: Vec<String>,
+    /// Matched case-insensitively against each line.
+    pub regex: Option<Regex>,
+    /// Without `#`, lowercased; the note must have every one.
+    pub tags: Vec<String>,
+    /// Substrings of the vault-relative path, lowercased.
+    pub paths: Vec<String>,
+    /// Substrings of the note title, lowercased.
+    pub titles: Vec<String>,
+}
+
+/// Split on whitespace, keeping `"quoted phrases"` (also after a prefix
+/// such as `-` or `title:`) together without their quotes.
+fn tokens(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in query.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            ch if ch.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            ch => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+impl ContentQuery {
+    /// Parse `query`; only an invalid `re:` pattern is an error.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        for token in tokens(query) {
+            let lower = token.to_lowercase();
+            if let Some(pattern) = token.strip_prefix("re:") {
+                if pattern.is_empty() {
+                    continue;
+                }
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|err| format!("re: {err}"))?;
+                parsed.regex = Some(regex);
+            } else if let Some(tag) = lower.strip_prefix("tag:") {
+                let tag = tag.trim_start_matches('#');
+                if !tag.is_empty() {
+                    parsed.tags.push(tag.to_string());
+                }
+            } else if let Some(path) = lower.strip_prefix("path:") {
+                if !path.is_empty() {
+                    parsed.paths.push(path.to_string());
+                }
+            } else if let Some(title) = lower.strip_prefix("title:") {
+                if !title.is_empty() {
+                    parsed.titles.push(title.to_string());
+                }
+            } else if let Some(excluded) = lower.strip_prefix('-') {
+                if !excluded.is_empty() {
+                    parsed.excluded.push(excluded.to_string());
+                }
+            } else {
+                parsed.terms.push(lower);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Whether lines are matched at all, rather than only whole notes.
+    pub fn matches_lines(&self) -> bool {
+        !self.terms.is_empty() || self.regex.is_some()
+    }
+
+    /// Whether anything beyond `lang:`/`section:` narrows the search.
+    pub fn is_empty(&self) -> bool {
+        !self.matches_lines() && !self.filters_notes()
+    }
+
+    fn filters_notes(&self) -> bool {
+        !(self.excluded.is_empty()
+            && self.tags.is_empty()
+            && self.paths.is_empty()
+            && self.titles.is_empty())
+    }
+
+    /// The note filters and exclusions, for a note at `rel_path`.
+    pub fn matches_note(&self, rel_path: &str, text: &str) -> bool {
+        let path = rel_path.to_lowercase();
+        if !self.paths.iter().all(|part| path.contains(part.as_str())) {
+            return false;
+        }
+        if !self.excluded.is_empty() {
+            let lower = text.to_lowercase();
+            if self
+                .excluded
+                .iter()
+                .any(|part| lower.contains(part.as_str()))
+            {
+                return false;
+            }
+        }
+        if !self.titles.is_empty() {
+            let title = note_title(rel_path, text).to_lowercase();
+            if !self.titles.iter().all(|part| title.contains(part.as_str())) {
+                return false;
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = note_tags(text);
+            if !self.tags.iter().all(|tag| tags.contains(tag)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn matches_line(&self, line: &str) -> bool {
+        let lower = line.to_lowercase();
+        self.terms.iter().all(|term| lower.contains(term.as_str()))
+            && self.regex.as_ref().is_none_or(|regex| regex.is_match(line))
+    }
+}
+
+/// Frontmatter `title`, else the first `# ` heading, else the file name.
+pub fn note_title(rel_path: &str, text: &str) -> String {
+    if let Some(title) = Frontmatter::parse(text)
+        .as_ref()
+        .and_then(|fm| fm.get_scalar("title"))
+        .filter(|title| !title.is_empty())
+    {
+        return title.to_string();
+    }
+    if let Some(heading) = text
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(str::trim)
+    {
+        return heading.to_string();
+    }
+    let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    name.strip_suffix(".md").unwrap_or(name).to_string()
+}
+
+/// Frontmatter `tags` and inline `#tags` outside code blocks, lowercased.
+pub fn note_tags(text: &str) -> Vec<String> {
+    let frontmatter = Frontmatter::parse(text);
+    let mut tags: Vec<String> = match frontmatter.as_ref().and_then(|fm| fm.get("tags")) {
+        Some(FrontmatterValue::List(items)) => items.clone(),
+        Some(FrontmatterValue::Scalar(value)) => {
+            value.split(',').map(|tag| tag.trim().to_string()).collect()
+        }
+        _ => Vec::new(),
+    };
+    let body_start = frontmatter.map_or(0, |fm| fm.line_count);
+    let code = code_langs::code_blocks(text);
+    for (idx, line) in text.lines().enumerate().skip(body_start) {
+        if code.iter().any(|block| block.lines.contains(&idx)) {
+            continue;
+        }
+        tags.extend(
+            INLINE_TAG_RE
+                .captures_iter(line)
+                .map(|caps| caps[1].to_string()),
+        );
+    }
+    tags.iter()
+        .map(|tag| tag.trim_start_matches('#').to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_operators() {
+        let query = ContentQuery::parse(
+            r#"Retry "exact Phrase" -draft -"not this" tag:#Work path:Journal/ title:road re:fn\s+main"#,
+        )
+        .unwrap();
+        assert_eq!(query.terms, ["retry", "exact phrase"]);
+        assert_eq!(query.excluded, ["draft", "not this"]);
+        assert_eq!(query.tags, ["work"]);
+        assert_eq!(query.paths, ["journal/"]);
+        assert_eq!(query.titles, ["road"]);
+        assert!(query.regex.as_ref().unwrap().is_match("FN  main"));
+        assert!(ContentQuery::parse("re:(").unwrap_err().starts_with("re:"));
+        assert!(ContentQuery::parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_matches_notes_and_lines() {
+        let note = "---\ntitle: Road Map\ntags: [work]\n---\nretry the exact phrase here #q3\n```\n#notatag\n```\n";
+        let query = ContentQuery::parse(r#""exact phrase" tag:q3 tag:work title:map"#).unwrap();
+        assert!(query.matches_note("journal/plan.md", note));
+        assert!(query.matches_line("retry the Exact Phrase here"));
+        assert!(!query.matches_line("exact and phrase"));
+
+        assert!(
+            !ContentQuery::parse("tag:notatag")
+                .unwrap()
+                .matches_note("a.md", note)
+        );
+        assert!(
+            !ContentQuery::parse("-retry")
+                .unwrap()
+                .matches_note("a.md", note)
+        );
+        assert!(
+            !ContentQuery::parse("path:archive/")
+                .unwrap()
+                .matches_note("a.md", note)
+        );
+        assert_eq!(note_title("dir/plain.md", "no heading"), "plain");
+        assert_eq!(note_title("x.md", "intro\n# Heading\n"), "Heading");
+    }
+
+    #[test]
+    fn test_note_tags_skip_code_blocks() {
+        let note = "---\ntags: [Work]\n---\n#plan\n```sh\n#comment\n```\n~~~\n#tilde\n```\n#still\n~~~\n#done\n";
+        assert_eq!(note_tags(note), ["work", "plan", "done"]);
+    }
+}
@@ -0,0 +1,89 @@
+//! Quickfix list: the file locations of the last content search, `:links
+//! check` or plugin diagnostics that `]q` / `[q` step through without
+//! reopening the source.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickfixEntry {
+    pub path: PathBuf,
+    /// 1-indexed line.
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QuickfixList {
+    /// What produced the list, e.g. `search: todo`.
+    pub title: String,
+    pub entries: Vec<QuickfixEntry>,
+    /// Entry last jumped to.
+    pub current: Option<usize>,
+}
+
+impl QuickfixList {
+    /// Replace the list; `current` marks an entry already visited.
+    pub fn set(&mut self, title: String, entries: Vec<QuickfixEntry>, current: Option<usize>) {
+        self.title = title;
+        self.current = current.filter(|&idx| idx < entries.len());
+        self.entries = entries;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Move `delta` entries from the current one, wrapping at either end.
+    /// With nothing visited yet, forward starts at the first entry and
+    /// backward at the last.
+    pub fn step(&mut self, delta: isize) -> Option<&QuickfixEntry> {
+        let len = self.entries.len() as isize;
+        if len == 0 {
+            return None;
+        }
+        let next = match self.current {
+            Some(idx) => (idx as isize + delta).rem_euclid(len),
+            None if delta >= 0 => (delta - 1).clamp(0, len - 1),
+            None => (len + delta).clamp(0, len - 1),
+        } as usize;
+        self.current = Some(next);
+        self.entries.get(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: usize) -> QuickfixEntry {
+        QuickfixEntry {
+            path: PathBuf::from("a.md"),
+            line,
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_step_wraps_both_ways() {
+        let mut list = QuickfixList::default();
+        assert!(list.step(1).is_none());
+
+        list.set("t".to_string(), vec![entry(1), entry(2), entry(3)], None);
+        assert_eq!(list.step(1).map(|e| e.line), Some(1));
+        assert_eq!(list.step(1).map(|e| e.line), Some(2));
+        assert_eq!(list.step(-2).map(|e| e.line), Some(3));
+        assert_eq!(list.step(1).map(|e| e.line), Some(1));
+
+        list.set("t".to_string(), vec![entry(1), entry(2)], None);
+        assert_eq!(list.step(-1).map(|e| e.line), Some(2));
+    }
+
+    #[test]
+    fn test_set_keeps_visited_entry() {
+        let mut list = QuickfixList::default();
+        list.set("t".to_string(), vec![entry(1), entry(2), entry(3)], Some(1));
+        assert_eq!(list.step(1).map(|e| e.line), Some(3));
+        list.set("t".to_string(), vec![entry(1)], Some(4));
+        assert_eq!(list.current, None);
+    }
+}
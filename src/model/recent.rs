@@ -0,0 +1,125 @@
+//! Notes opened lately, newest first, kept between runs so the file finder
+//! can offer them before anything is typed and `:recent` can list them.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many notes are remembered.
+pub const LIMIT: usize = 50;
+
+#[derive(Debug, Default)]
+pub struct RecentFiles {
+    /// Newest first, no repeats.
+    paths: Vec<PathBuf>,
+}
+
+/// `recent.txt` in the data directory, one path per line.
+pub fn default_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        return project_dirs.data_dir().join("recent.txt");
+    }
+
+    PathBuf::from(".blackbox-recent.txt")
+}
+
+impl RecentFiles {
+    /// The list saved at `file`, empty when there is none yet.
+    pub fn load(file: &Path) -> Self {
+        let paths = std::fs::read_to_string(file)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .take(LIMIT)
+            .collect();
+        Self { paths }
+    }
+
+    pub fn save(&self, file: &Path) -> io::Result<()> {
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut text = String::new();
+        for path in &self.paths {
+            let path = path.to_string_lossy();
+            if !path.contains('\n') {
+                text.push_str(&path);
+                text.push('\n');
+            }
+        }
+        std::fs::write(file, text)
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Move `path` to the front.
+    pub fn touch(&mut self, path: &Path) {
+        self.paths.retain(|recent| recent != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(LIMIT);
+    }
+
+    /// Follow a note or folder renamed from `from` to `to`.
+    pub fn rename(&mut self, from: &Path, to: &Path) {
+        for path in &mut self.paths {
+            if let Ok(rest) = path.strip_prefix(from) {
+                *path = if rest.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(rest)
+                };
+            }
+        }
+        let mut seen = Vec::new();
+        self.paths.retain(|path| {
+            let first = !seen.contains(path);
+            seen.push(path.clone());
+            first
+        });
+    }
+
+    /// `files` with the recent ones first, newest first, then the rest in
+    /// their original order.
+    pub fn order(&self, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let (mut recent, rest): (Vec<PathBuf>, Vec<PathBuf>) = files
+            .into_iter()
+            .partition(|path| self.paths.contains(path));
+        recent.sort_by_key(|path| self.paths.iter().position(|recent| recent == path));
+        recent.extend(rest);
+        recent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_orders_newest_first_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state/recent.txt");
+        let mut recent = RecentFiles::load(&file);
+        for note in ["/v/a.md", "/v/b.md", "/v/c.md", "/v/a.md"] {
+            recent.touch(Path::new(note));
+        }
+        recent.rename(Path::new("/v/c.md"), Path::new("/v/d/c.md"));
+        recent.save(&file).unwrap();
+
+        let recent = RecentFiles::load(&file);
+        assert_eq!(
+            recent.paths(),
+            [
+                PathBuf::from("/v/a.md"),
+                PathBuf::from("/v/d/c.md"),
+                PathBuf::from("/v/b.md")
+            ]
+        );
+        let files = ["/v/b.md", "/v/x.md", "/v/a.md", "/v/y.md"].map(PathBuf::from);
+        assert_eq!(
+            recent.order(files.to_vec()),
+            ["/v/a.md", "/v/b.md", "/v/x.md", "/v/y.md"].map(PathBuf::from)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Scratch rotation: moving the scratch note aside as
+//! `scratch-YYYY-MM-DD.md` once a day or once it grows past a size, and
+//! linking each rotation from an index note, so the always-open scratch
+//! buffer stays small.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::model::{capture, journal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Off,
+    /// When the scratch note was last written on an earlier (UTC) day.
+    Daily,
+    /// When the scratch note is larger than this many bytes.
+    Size(u64),
+}
+
+impl Rotation {
+    /// `general.scratch_rotate` ("off", "daily" or "size") with the
+    /// `scratch_max_kb` limit.
+    pub fn from_config(mode: &str, max_kb: u64) -> Result<Self, String> {
+        match mode {
+            "off" | "" => Ok(Rotation::Off),
+            "daily" => Ok(Rotation::Daily),
+            "size" => Ok(Rotation::Size(max_kb.max(1) * 1024)),
+            other => Err(format!(
+                "scratch_rotate: unknown mode {other:?} (off, daily or size)"
+            )),
+        }
+    }
+
+    /// Whether a scratch note of `len` bytes last modified at `modified`
+    /// (Unix seconds) should be rotated at `now`; the day it is filed under
+    /// when it should.
+    pub fn due(self, len: u64, modified: u64, now: u64) -> Option<String> {
+        if len == 0 {
+            return None;
+        }
+        match self {
+            Rotation::Off => None,
+            Rotation::Daily => {
+                let day = journal::format_day(modified);
+                (day != journal::format_day(now)).then_some(day)
+            }
+            Rotation::Size(max) => (len > max).then(|| journal::format_day(now)),
+        }
+    }
+}
+
+/// `scratch-<day>.md` next to the scratch note, or `scratch-<day>-2.md` and
+/// so on when a rotation for that day already exists.
+pub fn rotated_path(scratch: &Path, day: &str, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    let dir = scratch.parent().unwrap_or(Path::new(""));
+    let stem = scratch
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim_start_matches('.').to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "scratch".to_string());
+    let mut candidate = dir.join(format!("{stem}-{day}.md"));
+    let mut n = 2;
+    while exists(&candidate) {
+        candidate = dir.join(format!("{stem}-{day}-{n}.md"));
+        n += 1;
+    }
+    candidate
+}
+
+/// Rotate the scratch note when `rotation` says it is due: move it aside
+/// and link the rotation from `index`. Returns where it went.
+pub fn rotate_if_due(
+    scratch: &Path,
+    index: &Path,
+    rotation: Rotation,
+) -> io::Result<Option<PathBuf>> {
+    let Ok(meta) = std::fs::metadata(scratch) else {
+        return Ok(None);
+    };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+    let Some(day) = rotation.due(meta.len(), modified, journal::now_secs()) else {
+        return Ok(None);
+    };
+
+    let target = rotated_path(scratch, &day, Path::exists);
+    std::fs::rename(scratch, &target)?;
+    let name = target
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    capture::append(index, &format!("- [[{name}]]\n"))?;
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_due_and_naming() {
+        let day = 86_400;
+        let daily = Rotation::Daily;
+        assert_eq!(daily.due(10, 3 * day + 5, 3 * day + 60), None);
+        assert_eq!(
+            daily.due(10, 3 * day, 4 * day),
+            Some("1970-01-04".to_string())
+        );
+        assert_eq!(daily.due(0, 3 * day, 4 * day), None);
+
+        let size = Rotation::from_config("size", 1).unwrap();
+        assert_eq!(size.due(1024, 0, day), None);
+        assert_eq!(size.due(1025, 0, day), Some("1970-01-02".to_string()));
+        assert!(Rotation::from_config("weekly", 1).is_err());
+
+        let scratch = Path::new("/vault/.scratch.md");
+        let taken = [PathBuf::from("/vault/scratch-2026-10-14.md")];
+        assert_eq!(
+            rotated_path(scratch, "2026-10-14", |p| taken.iter().any(|t| t == p)),
+            PathBuf::from("/vault/scratch-2026-10-14-2.md")
+        );
+    }
+
+    #[test]
+    fn test_rotate_moves_and_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = dir.path().join(".scratch.md");
+        let index = dir.path().join("scratch-index.md");
+        std::fs::write(&scratch, "lots of notes\n").unwrap();
+
+        let target = rotate_if_due(&scratch, &index, Rotation::Size(4))
+            .unwrap()
+            .unwrap();
+        assert!(!scratch.exists());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "lots of notes\n");
+        let name = target.file_stem().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("scratch-"));
+        assert_eq!(
+            std::fs::read_to_string(&index).unwrap(),
+            format!("- [[{name}]]\n")
+        );
+        assert_eq!(
+            rotate_if_due(&scratch, &index, Rotation::Size(4)).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Word index over the vault for content search, kept on disk between runs
+//! and updated from watcher events, so a query only reads the notes that
+//! can match instead of every file.
+//!
+//! Content search matches substrings, so the index narrows rather than
+//! answers: a note is a candidate when, for every word of the query, some
+//! word of the note contains it. The matching lines still come from the
+//! candidate files themselves.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const HEADER: &str = "blackbox-search-index 1";
+
+#[derive(Debug)]
+struct Doc {
+    /// Modification time in nanoseconds, to skip unchanged files.
+    modified: u128,
+    words: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    root: PathBuf,
+    docs: HashMap<PathBuf, Doc>,
+    postings: HashMap<String, HashSet<PathBuf>>,
+    /// Text files that could not be read; always searched, so the finder
+    /// can still count them.
+    unreadable: HashSet<PathBuf>,
+}
+
+/// Lowercased runs of letters and digits in `text`.
+pub fn words(text: &str) -> BTreeSet<String> {
+    text.to_lowercase()
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Where the index is kept between runs.
+pub fn default_path() -> PathBuf {
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        return project_dirs.cache_dir().join("search-index.tsv");
+    }
+
+    PathBuf::from(".blackbox-search-index.tsv")
+}
+
+/// Modification time of `path` in nanoseconds since the epoch.
+pub fn modified(path: &Path) -> Option<u128> {
+    let time = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+impl SearchIndex {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        }
+    }
+
+    /// The vault the index covers.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The index saved by [`save`](Self::save) for `root`, or an empty one
+    /// when the file is missing, unreadable or for another vault.
+    pub fn load(root: &Path, cache: &Path) -> Self {
+        let mut index = Self::new(root);
+        let Ok(text) = std::fs::read_to_string(cache) else {
+            return index;
+        };
+        let mut lines = text.lines();
+        if lines.next() != Some(&format!("{HEADER}\t{}", root.display())) {
+            return index;
+        }
+        for line in lines {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(modified), Some(rel), Some(words)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(modified) = modified.parse() else {
+                continue;
+            };
+            let words = words
+                .split(' ')
+                .filter(|w| !w.is_empty())
+                .map(str::to_string);
+            index.insert(root.join(rel), modified, words.collect());
+        }
+        index
+    }
+
+    pub fn save(&self, cache: &Path) -> io::Result<()> {
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = io::BufWriter::new(std::fs::File::create(cache)?);
+        writeln!(out, "{HEADER}\t{}", self.root.display())?;
+        for (path, doc) in &self.docs {
+            let Some(rel) = path.strip_prefix(&self.root).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            if rel.contains(['\t', '\n']) {
+                continue;
+            }
+            writeln!(out, "{}\t{rel}\t{}", doc.modified, doc.words.join(" "))?;
+        }
+        out.flush()
+    }
+
+    /// Bring the index in line with `files`: read new and modified ones,
+    /// forget the rest. Returns how many files were read.
+    pub fn refresh(&mut self, files: &[PathBuf]) -> usize {
+        let current: HashSet<&PathBuf> = files.iter().collect();
+        let gone: Vec<PathBuf> = self
+            .docs
+            .keys()
+            .filter(|path| !current.contains(path))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.remove(&path);
+        }
+        self.unreadable.retain(|path| current.contains(path));
+
+        let mut read = 0;
+        for path in files {
+            let stale = self
+                .docs
+                .get(path)
+                .is_none_or(|doc| Some(doc.modified) != modified(path));
+            if stale || self.unreadable.contains(path) {
+                self.update(path);
+                read += 1;
+            }
+        }
+        read
+    }
+
+    /// Re-read one file, or drop it when it is gone or not text.
+    pub fn update(&mut self, path: &Path) {
+        self.remove(path);
+        self.unreadable.remove(path);
+        let Some(modified) = modified(path) else {
+            return;
+        };
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.insert(
+                path.to_path_buf(),
+                modified,
+                words(&text).into_iter().collect(),
+            ),
+            // Binary attachments are not searched at all.
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {}
+            Err(_) => {
+                self.unreadable.insert(path.to_path_buf());
+            }
+        }
+    }
+
+    /// Files that may contain `needle`, or `None` when it has no word to
+    /// narrow by (such as a lone space) and every file must be read.
+    pub fn candidates(&self, needle: &str) -> Option<HashSet<PathBuf>> {
+        let mut result: Option<HashSet<PathBuf>> = None;
+        for part in words(needle) {
+            let mut docs: HashSet<PathBuf> = self
+                .postings
+                .iter()
+                .filter(|(word, _)| word.contains(&part))
+                .flat_map(|(_, docs)| docs.iter().cloned())
+                .collect();
+            if let Some(previous) = &result {
+                docs.retain(|path| previous.contains(path));
+            }
+            result = Some(docs);
+        }
+        let mut result = result?;
+        result.extend(self.unreadable.iter().cloned());
+        Some(result)
+    }
+
+    fn insert(&mut self, path: PathBuf, modified: u128, words: Vec<String>) {
+        for word in &words {
+            self.postings
+                .entry(word.clone())
+                .or_default()
+                .insert(path.clone());
+        }
+        self.docs.insert(path, Doc { modified, words });
+    }
+
+    fn remove(&mut self, path: &Path) {
+        let Some(doc) = self.docs.remove(path) else {
+            return;
+        };
+        for word in doc.words {
+            if let Some(docs) = self.postings.get_mut(&word) {
+                docs.remove(path);
+                if docs.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_are_lowercased_runs() {
+        let found: Vec<String> = words("Road-map: Q3, road ÉTÉ").into_iter().collect();
+        assert_eq!(found, ["map", "q3", "road", "été"]);
+    }
+
+    #[test]
+    fn test_candidates_follow_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let plan = root.join("plan.md");
+        let ideas = root.join("ideas.md");
+        std::fs::write(&plan, "The roadmap for Q3\n").unwrap();
+        std::fs::write(&ideas, "loose ideas\n").unwrap();
+
+        let mut index = SearchIndex::new(root);
+        assert_eq!(index.refresh(&[plan.clone(), ideas.clone()]), 2);
+        assert_eq!(index.refresh(&[plan.clone(), ideas.clone()]), 0);
+        assert_eq!(
+            index.candidates("oadm"),
+            Some(HashSet::from([plan.clone()]))
+        );
+        assert_eq!(
+            index.candidates("for q3"),
+            Some(HashSet::from([plan.clone()]))
+        );
+        assert_eq!(index.candidates("map ideas"), Some(HashSet::new()));
+        assert_eq!(index.candidates(" - "), None);
+
+        std::fs::write(&ideas, "a roadmap idea\n").unwrap();
+        index.update(&ideas);
+        assert_eq!(
+            index.candidates("roadmap"),
+            Some(HashSet::from([plan.clone(), ideas.clone()]))
+        );
+        index.refresh(std::slice::from_ref(&ideas));
+        assert_eq!(
+            index.candidates("roadmap"),
+            Some(HashSet::from([ideas.clone()]))
+        );
+
+        let cache = root.join("cache/index.tsv");
+        index.save(&cache).unwrap();
+        let mut loaded = SearchIndex::load(root, &cache);
+        assert_eq!(
+            loaded.candidates("idea"),
+            Some(HashSet::from([ideas.clone()]))
+        );
+        assert_eq!(loaded.refresh(std::slice::from_ref(&ideas)), 0);
+        assert!(
+            SearchIndex::load(&root.join("other"), &cache)
+                .docs
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Spell checking against hunspell `.aff`/`.dic` dictionaries, plus a
+//! per-vault word list for names and jargon.
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use spellbook::Dictionary;
+
+use crate::model::config::SpellConfig;
+
+const MAX_SUGGESTIONS: usize = 9;
+
+static CODE_SPAN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`[^`]*`").expect("valid code span regex"));
+static WIKILINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[[^\]]*\]\]").expect("valid wikilink regex"));
+static LINK_TARGET_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\]\([^)]*\)").expect("valid link target regex"));
+
+/// Where hunspell dictionaries usually live, after any configured directories.
+fn system_dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(project_dirs) = directories::ProjectDirs::from("", "", "blackbox") {
+        dirs.push(project_dirs.config_dir().join("dictionaries"));
+    }
+    for dir in [
+        "/usr/share/hunspell",
+        "/usr/share/myspell",
+        "/usr/share/myspell/dicts",
+        "/usr/local/share/hunspell",
+        "/Library/Spelling",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(base) = directories::BaseDirs::new() {
+        dirs.push(base.home_dir().join("Library/Spelling"));
+    }
+    dirs
+}
+
+pub struct SpellChecker {
+    dictionary: Dictionary,
+    custom_path: PathBuf,
+    custom: HashSet<String>,
+}
+
+impl SpellChecker {
+    /// Load `<language>.aff`/`.dic` from the first directory that has both,
+    /// then the vault's custom word list.
+    pub fn load(config: &SpellConfig, vault: &Path) -> Result<Self, String> {
+        let dirs = config
+            .dictionary_dirs
+            .iter()
+            .map(PathBuf::from)
+            .chain(system_dictionary_dirs());
+
+        let language = &config.language;
+        let (aff, dic) = dirs
+            .map(|dir| {
+                (
+                    dir.join(format!("{language}.aff")),
+                    dir.join(format!("{language}.dic")),
+                )
+            })
+            .find(|(aff, dic)| aff.is_file() && dic.is_file())
+            .ok_or_else(|| format!("spell: no hunspell dictionary found for {language}"))?;
+
+        let read = |path: &Path| {
+            std::fs::read_to_string(path).map_err(|err| format!("spell: {}: {err}", path.display()))
+        };
+        let dictionary = Dictionary::new(&read(&aff)?, &read(&dic)?)
+            .map_err(|err| format!("spell: {}: {err}", dic.display()))?;
+
+        let mut checker = Self::with_dictionary(dictionary, vault.join(&config.custom_dictionary));
+        if let Ok(words) = std::fs::read_to_string(&checker.custom_path) {
+            checker.custom.extend(
+                words
+                    .lines()
+                    .map(str::trim)
+                    .filter(|w| !w.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        Ok(checker)
+    }
+
+    fn with_dictionary(dictionary: Dictionary, custom_path: PathBuf) -> Self {
+        Self {
+            dictionary,
+            custom_path,
+            custom: HashSet::new(),
+        }
+    }
+
+    pub fn check(&self, word: &str) -> bool {
+        self.custom.contains(word) || self.dictionary.check(word)
+    }
+
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        self.dictionary.suggest(word, &mut out);
+        out.truncate(MAX_SUGGESTIONS);
+        out
+    }
+
+    /// Accept `word` from now on and append it to the vault word list.
+    pub fn add_word(&mut self, word: &str) -> std::io::Result<()> {
+        if !self.custom.insert(word.to_string()) {
+            return Ok(());
+        }
+        if let Some(parent) = self.custom_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.custom_path)?;
+        writeln!(file, "{word}")
+    }
+
+    /// Byte ranges of misspelled words in a line of prose.
+    pub fn misspelled(&self, line: &str) -> Vec<Range<usize>> {
+        prose_words(line)
+            .into_iter()
+            .filter(|range| !self.check(&line[range.clone()]))
+            .collect()
+    }
+}
+
+/// Byte ranges of the words worth checking in `line`.
+///
+/// Skips inline code, `[[wikilinks]]`, link targets, URLs, paths, `#tags`,
+/// emails, acronyms and anything containing digits or underscores.
+pub fn prose_words(line: &str) -> Vec<Range<usize>> {
+    let masked = mask_markup(line);
+    let mut out = Vec::new();
+
+    let mut offset = 0;
+    for chunk in masked.split_inclusive(char::is_whitespace) {
+        let start = offset;
+        offset += chunk.len();
+        let chunk = chunk.trim_end();
+        if chunk.contains("://")
+            || chunk.contains(['@', '/', '\\', '_'])
+            || chunk.starts_with('#')
+            || chunk.chars().any(|c| c.is_ascii_digit())
+        {
+            continue;
+        }
+
+        let mut word_start = None;
+        let mut chars = chunk.char_indices().peekable();
+        while let Some((idx, ch)) = chars.next() {
+            let inner_apostrophe = matches!(ch, '\'' | '’')
+                && word_start.is_some()
+                && chars.peek().is_some_and(|(_, next)| next.is_alphabetic());
+            if ch.is_alphabetic() || inner_apostrophe {
+                word_start.get_or_insert(idx);
+                continue;
+            }
+            if let Some(ws) = word_start.take() {
+                push_word(&mut out, line, start + ws..start + idx);
+            }
+        }
+        if let Some(ws) = word_start {
+            push_word(&mut out, line, start + ws..start + chunk.len());
+        }
+    }
+    out
+}
+
+fn push_word(out: &mut Vec<Range<usize>>, line: &str, range: Range<usize>) {
+    let word = &line[range.clone()];
+    let is_acronym = word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && word.chars().all(|c| !c.is_lowercase());
+    if word.chars().count() > 1 && !is_acronym {
+        out.push(range);
+    }
+}
+
+/// `line` with code spans, wikilinks and link targets blanked out, keeping
+/// byte offsets intact.
+fn mask_markup(line: &str) -> String {
+    let mut masked = line.to_string();
+    let mut blank = |range: Range<usize>| {
+        let spaces = " ".repeat(masked[range.clone()].len());
+        masked.replace_range(range, &spaces);
+    };
+
+    for re in [&*CODE_SPAN_RE, &*WIKILINK_RE, &*LINK_TARGET_RE] {
+        let ranges: Vec<Range<usize>> = re.find_iter(line).map(|m| m.range()).collect();
+        for range in ranges {
+            blank(range);
+        }
+    }
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<&str> {
+        prose_words(line)
+            .into_iter()
+            .map(|range| &line[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_prose_words_skip_markup() {
+        assert_eq!(
+            words("I don't `frobnicate` [[Some Note]] or [the docs](http://x.io/y)."),
+            vec!["don't", "or", "the", "docs"]
+        );
+        assert_eq!(
+            words("See https://x.io #tag user@mail.com NASA v2 snake_case **bold**"),
+            vec!["See", "bold"]
+        );
+    }
+
+    #[test]
+    fn test_checker_with_custom_words() {
+        let aff = "SET UTF-8\nTRY esianrtolcdugmphbyfvkwz\n";
+        let dic = "3\nhello\nworld\nworlds\n";
+        let dir = tempfile::tempdir().unwrap();
+        let custom = dir.path().join(".dictionary.txt");
+        let mut checker =
+            SpellChecker::with_dictionary(Dictionary::new(aff, dic).unwrap(), custom.clone());
+
+        let line = "hello wrold and Blackbox";
+        let bad: Vec<&str> = checker
+            .misspelled(line)
+            .into_iter()
+            .map(|r| &line[r])
+            .collect();
+        assert_eq!(bad, vec!["wrold", "and", "Blackbox"]);
+        assert!(checker.suggest("wrold").contains(&"world".to_string()));
+
+        checker.add_word("Blackbox").unwrap();
+        assert!(checker.check("Blackbox"));
+        assert_eq!(std::fs::read_to_string(custom).unwrap(), "Blackbox\n");
+    }
+}
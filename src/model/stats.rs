@@ -0,0 +1,65 @@
+//! Figures about one note for `:stats`: size, reading time, structure and
+//! task progress. Links in are counted by the caller, which knows the vault.
+
+use crate::model::buffer::TextCounts;
+use crate::model::link;
+use crate::model::outline;
+use crate::model::task;
+
+/// Silent reading pace used for the estimate.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteStats {
+    pub counts: TextCounts,
+    pub headings: usize,
+    /// Wikilink targets in the note, repeats included.
+    pub links_out: Vec<String>,
+    pub tasks_done: usize,
+    pub tasks_total: usize,
+}
+
+impl NoteStats {
+    pub fn of(text: &str) -> Self {
+        let (tasks_done, tasks_total) = task::task_progress(text.lines());
+        Self {
+            counts: TextCounts::of(text),
+            headings: outline::headings(text.lines()).len(),
+            links_out: link::wikilink_targets(text).map(str::to_string).collect(),
+            tasks_done,
+            tasks_total,
+        }
+    }
+
+    /// Whole minutes to read the note, rounded up; zero only when empty.
+    pub fn reading_minutes(&self) -> usize {
+        self.counts.words.div_ceil(WORDS_PER_MINUTE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_stats() {
+        let text = "# Plan\nsee [[roadmap]] and [[team|them]]\n## Tasks\n- [x] one\n- [ ] two\n```\n# not a heading\n- [ ] not a task\n```\n";
+        let stats = NoteStats::of(text);
+        assert_eq!(stats.headings, 2);
+        assert_eq!(stats.links_out, ["roadmap", "team"]);
+        assert_eq!((stats.tasks_done, stats.tasks_total), (1, 2));
+        assert_eq!(stats.reading_minutes(), 1);
+        assert_eq!(NoteStats::of("").reading_minutes(), 0);
+        assert_eq!(
+            NoteStats {
+                counts: TextCounts {
+                    words: 401,
+                    ..TextCounts::default()
+                },
+                ..NoteStats::default()
+            }
+            .reading_minutes(),
+            3
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Markdown checkbox tasks (`- [ ]` / `- [x]`).
+
+/// Parse a list item checkbox, returning whether it is checked.
+///
+/// Accepts `-`, `*` and `+` bullets as well as ordered items (`1.`, `2)`),
+/// at any indentation.
+pub fn parse_checkbox(line: &str) -> Option<bool> {
+    checkbox_mark(line).map(|(_, checked)| checked)
+}
+
+/// The line with its checkbox flipped, or `None` if it has none.
+pub fn toggle_checkbox(line: &str) -> Option<String> {
+    let (at, checked) = checkbox_mark(line)?;
+    let mark = if checked { " " } else { "x" };
+    let len = line[at..].chars().next().map_or(1, char::len_utf8);
+    Some(format!("{}{mark}{}", &line[..at], &line[at + len..]))
+}
+
+/// Byte offset of the checkbox mark (the character between the brackets)
+/// and whether it is checked.
+fn checkbox_mark(line: &str) -> Option<(usize, bool)> {
+    let trimmed = line.trim_start();
+    let rest = if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        trimmed[digits..]
+            .strip_prefix(". ")
+            .or_else(|| trimmed[digits..].strip_prefix(") "))?
+    };
+
+    let mark = rest.strip_prefix('[')?.chars().next()?;
+    if !rest[1 + mark.len_utf8()..].starts_with(']') {
+        return None;
+    }
+    let at = line.len() - rest.len() + 1;
+    match mark {
+        ' ' => Some((at, false)),
+        'x' | 'X' => Some((at, true)),
+        _ => None,
+    }
+}
+
+/// Unchecked tasks in `text` as `(row, line)`, ignoring fenced code blocks.
+pub fn open_tasks(text: &str) -> Vec<(usize, String)> {
+    let mut in_fence = false;
+    let mut tasks = Vec::new();
+    for (row, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence && parse_checkbox(line) == Some(false) {
+            tasks.push((row, line.to_string()));
+        }
+    }
+    tasks
+}
+
+/// `(done, total)` checkboxes in `lines`, ignoring fenced code blocks.
+pub fn task_progress<'a>(lines: impl IntoIterator<Item = &'a str>) -> (usize, usize) {
+    let mut in_fence = false;
+    let mut done = 0;
+    let mut total = 0;
+
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(checked) = parse_checkbox(line) {
+            total += 1;
+            done += usize::from(checked);
+        }
+    }
+    (done, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checkbox_variants() {
+        assert_eq!(parse_checkbox("- [ ] open"), Some(false));
+        assert_eq!(parse_checkbox("  * [x] done"), Some(true));
+        assert_eq!(parse_checkbox("3. [X] numbered"), Some(true));
+        assert_eq!(parse_checkbox("- [link](url)"), None);
+        assert_eq!(parse_checkbox("[ ] not a list item"), None);
+        assert_eq!(parse_checkbox("- plain"), None);
+    }
+
+    #[test]
+    fn test_task_progress_skips_code() {
+        let text = "- [x] a\n- [ ] b\n```\n- [ ] in code\n```\n+ [x] c\n";
+        assert_eq!(task_progress(text.lines()), (2, 3));
+        assert_eq!(open_tasks(text), [(1, "- [ ] b".to_string())]);
+    }
+
+    #[test]
+    fn test_toggle_checkbox_keeps_the_rest_of_the_line() {
+        assert_eq!(
+            toggle_checkbox("  - [ ] call [x] back").as_deref(),
+            Some("  - [x] call [x] back")
+        );
+        assert_eq!(
+            toggle_checkbox("2. [X] done").as_deref(),
+            Some("2. [ ] done")
+        );
+        assert_eq!(toggle_checkbox("- plain"), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! `:toc` table of contents: a list of heading links between marker
+//! comments, so running the command again replaces the old block.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::model::frontmatter::Frontmatter;
+use crate::model::link::heading_slug;
+use crate::model::outline;
+
+pub const START_MARKER: &str = "<!-- toc -->";
+pub const END_MARKER: &str = "<!-- /toc -->";
+
+/// Line range of an existing TOC block, markers included.
+pub fn find_block(lines: &[String]) -> Option<Range<usize>> {
+    let start = lines.iter().position(|l| l.trim() == START_MARKER)?;
+    let end = lines[start..].iter().position(|l| l.trim() == END_MARKER)?;
+    Some(start..start + end + 1)
+}
+
+/// The TOC block for `lines`, markers included. A leading `# Title` is the
+/// note's name rather than a section, so it is left out.
+pub fn build(lines: &[String]) -> Vec<String> {
+    let block = find_block(lines);
+    let title = title_line(lines);
+    let headings: Vec<_> = outline::headings(lines.iter().map(String::as_str))
+        .into_iter()
+        .filter(|h| Some(h.line) != title)
+        .filter(|h| !block.as_ref().is_some_and(|b| b.contains(&h.line)))
+        .collect();
+    let base = headings.iter().map(|h| h.level).min().unwrap_or(1);
+
+    // Repeated titles get `-1`, `-2`, … like GitHub's anchors.
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut out = vec![START_MARKER.to_string()];
+    for heading in &headings {
+        let slug = heading_slug(&heading.title);
+        let count = seen.entry(slug.clone()).or_default();
+        let anchor = if *count == 0 {
+            slug
+        } else {
+            format!("{slug}-{count}")
+        };
+        *count += 1;
+        let indent = "  ".repeat(heading.level - base);
+        out.push(format!("{indent}- [{}](#{anchor})", heading.title));
+    }
+    out.push(END_MARKER.to_string());
+    out
+}
+
+/// Insert or refresh the TOC. Returns the lines to replace and their
+/// replacement, or `None` when the note already has an up-to-date TOC.
+pub fn apply(lines: &[String]) -> Option<(Range<usize>, Vec<String>)> {
+    let toc = build(lines);
+    let mut updated = lines.to_vec();
+    match find_block(lines) {
+        Some(block) => {
+            updated.splice(block, toc);
+        }
+        None => {
+            let title = title_line(lines);
+            let at = title.map_or_else(|| body_start(lines), |title| title + 1);
+            let mut insert = toc;
+            if title.is_some() && !lines[at - 1].trim().is_empty() {
+                insert.insert(0, String::new());
+            }
+            if lines.get(at).is_some_and(|l| !l.trim().is_empty()) {
+                insert.push(String::new());
+            }
+            updated.splice(at..at, insert);
+        }
+    }
+    changed_range(lines, updated)
+}
+
+/// First line after the frontmatter.
+fn body_start(lines: &[String]) -> usize {
+    Frontmatter::parse(&lines.join("\n")).map_or(0, |fm| fm.line_count)
+}
+
+/// The note's leading level-one heading, skipping frontmatter and blanks.
+fn title_line(lines: &[String]) -> Option<usize> {
+    let body = body_start(lines);
+    let first = (body..lines.len()).find(|&i| !lines[i].trim().is_empty())?;
+    matches!(outline::parse_heading(&lines[first]), Some((1, _))).then_some(first)
+}
+
+/// Trim the common head and tail so the edit touches as few lines as
+/// possible, always covering at least one line so it can be applied with
+/// [`Buffer::replace_lines`](crate::model::buffer::Buffer::replace_lines).
+fn changed_range(old: &[String], new: Vec<String>) -> Option<(Range<usize>, Vec<String>)> {
+    if old == new.as_slice() {
+        return None;
+    }
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (mut start, mut end, mut new_end) = (prefix, old.len() - suffix, new.len() - suffix);
+    if start == end {
+        if end < old.len() {
+            end += 1;
+            new_end += 1;
+        } else {
+            start = start.saturating_sub(1);
+        }
+    }
+    Some((start..end, new[start..new_end].to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    fn applied(text: &str) -> Vec<String> {
+        let mut note = lines(text);
+        if let Some((range, replacement)) = apply(&note) {
+            note.splice(range, replacement);
+        }
+        note
+    }
+
+    #[test]
+    fn test_toc_inserted_below_title() {
+        let note = applied("# Project\nintro\n## Goals\n### Next steps & risks!\n## Goals");
+        assert_eq!(
+            note,
+            lines(
+                "# Project\n\n<!-- toc -->\n- [Goals](#goals)\n  - [Next steps & risks!](#next-steps--risks)\n- [Goals](#goals-1)\n<!-- /toc -->\n\nintro\n## Goals\n### Next steps & risks!\n## Goals"
+            )
+        );
+    }
+
+    #[test]
+    fn test_toc_regenerated_in_place() {
+        let once = applied("---\ntags: [a]\n---\n## One\n```\n# not a heading\n```");
+        assert_eq!(
+            once,
+            lines(
+                "---\ntags: [a]\n---\n<!-- toc -->\n- [One](#one)\n<!-- /toc -->\n\n## One\n```\n# not a heading\n```"
+            )
+        );
+        assert_eq!(apply(&once), None);
+
+        let mut edited = once.clone();
+        edited.push("## Two".to_string());
+        let again = applied(&edited.join("\n"));
+        assert_eq!(
+            again[3..7],
+            lines("<!-- toc -->\n- [One](#one)\n- [Two](#two)\n<!-- /toc -->")[..]
+        );
+        assert_eq!(again.len(), once.len() + 2);
+    }
+}
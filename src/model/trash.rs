@@ -0,0 +1,67 @@
+//! Deleted notes go to `.trash/` in the vault, keeping their folders, so a
+//! mistaken delete can be undone by moving the file back.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Vault folder for deleted notes, left out of the sidebar and searches.
+pub const DIR: &str = ".trash";
+
+/// Where `path` goes in the trash: the same vault-relative path, with `-2`,
+/// `-3`… before the extension when an earlier delete already used it.
+pub fn target(root: &Path, path: &Path, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    let first = root.join(DIR).join(rel);
+    if !exists(&first) {
+        return first;
+    }
+    let stem = first
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = first
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| first.with_file_name(format!("{stem}-{n}{ext}")))
+        .find(|candidate| !exists(candidate))
+        .expect("some suffix is free")
+}
+
+/// Move `path` into the vault trash, returning where it went.
+pub fn move_to_trash(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let target = target(root, path, Path::exists);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_keeps_folders_and_numbers_repeats() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let note = root.join("projects/plan.md");
+        std::fs::create_dir_all(note.parent().unwrap()).unwrap();
+
+        for expected in ["plan.md", "plan-2.md"] {
+            std::fs::write(&note, "x").unwrap();
+            let moved = move_to_trash(root, &note).unwrap();
+            assert_eq!(moved, root.join(".trash/projects").join(expected));
+            assert!(moved.exists());
+            assert!(!note.exists());
+        }
+
+        let taken = |path: &Path| !path.ends_with("plan-4.md");
+        assert_eq!(
+            target(root, &note, taken),
+            root.join(".trash/projects/plan-4.md")
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Hard wrapping of markdown prose at a text width.
+//!
+//! Only plain paragraphs and list items are touched, inside blockquotes
+//! too: fenced code, tables, headings, frontmatter and blank lines are left
+//! exactly as they are.
+
+use std::ops::Range;
+
+use crate::model::outline;
+
+/// Whether each line is wrappable prose.
+pub fn prose_mask(lines: &[String]) -> Vec<bool> {
+    let mut mask = Vec::with_capacity(lines.len());
+    let mut fence: Option<&str> = None;
+    let mut in_frontmatter = lines.first().is_some_and(|l| l.trim_end() == "---");
+    let mut indented_code = false;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let quote = quote_prefix(line);
+        let content = &line[quote..];
+        let trimmed = content.trim_start();
+        if in_frontmatter {
+            mask.push(false);
+            if idx > 0 && line.trim_end() == "---" {
+                in_frontmatter = false;
+            }
+            continue;
+        }
+
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (Some(open), Some(m)) if open == m => fence = None,
+            (None, Some(m)) => fence = Some(m),
+            _ => {}
+        }
+        if fence.is_some() || marker.is_some() {
+            mask.push(false);
+            continue;
+        }
+
+        // Indented code only starts after a blank line, so list
+        // continuations indented four spaces still count as prose.
+        let indented = quote == 0 && (line.starts_with("    ") || line.starts_with('\t'));
+        let after_blank = idx == 0 || lines[idx - 1].trim().is_empty();
+        indented_code = !trimmed.is_empty() && indented && (indented_code || after_blank);
+
+        let structural = trimmed.is_empty()
+            || indented_code
+            || outline::parse_heading(content).is_some()
+            || trimmed.starts_with('|')
+            || trimmed.starts_with("<!--")
+            || is_rule(trimmed);
+        mask.push(!structural);
+    }
+    mask
+}
+
+fn is_rule(trimmed: &str) -> bool {
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&rule| compact.chars().all(|ch| ch == rule))
+}
+
+/// Length in bytes of a blockquote prefix: every `>` with the spaces
+/// around it. 0 outside quotes.
+pub fn quote_prefix(line: &str) -> usize {
+    let mut rest = line;
+    while let Some(after) = rest.trim_start_matches(' ').strip_prefix('>') {
+        rest = after.strip_prefix(' ').unwrap_or(after);
+    }
+    line.len() - rest.len()
+}
+
+fn quote_depth(line: &str) -> usize {
+    line[..quote_prefix(line)].matches('>').count()
+}
+
+/// Where the text of a line starts, after its quote prefix and a list
+/// marker or indent.
+fn text_start(line: &str) -> usize {
+    let quote = quote_prefix(line);
+    let inner = &line[quote..];
+    quote + list_prefix(inner).unwrap_or_else(|| inner.len() - inner.trim_start().len())
+}
+
+/// Prefix for the lines a wrapped `line` continues on: the same quote
+/// markers, then whitespace lining up with its text (tabs kept).
+pub fn continuation_prefix(line: &str) -> String {
+    let quote = quote_prefix(line);
+    let hang = line[quote..text_start(line)]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' });
+    line[..quote].chars().chain(hang).collect()
+}
+
+/// Whether `line` opens a list item, inside a quote or not.
+fn starts_item(line: &str) -> bool {
+    list_prefix(&line[quote_prefix(line)..]).is_some()
+}
+
+/// Length in bytes of a list marker prefix (indent, bullet or number, an
+/// optional task box and the following space), if `line` starts a list item.
+pub fn list_prefix(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    let marker = if rest.starts_with("- ") || rest.starts_with("* ") || rest.starts_with("+ ") {
+        2
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let after = &rest[digits..];
+        if digits > 0 && (after.starts_with(". ") || after.starts_with(") ")) {
+            digits + 2
+        } else {
+            return None;
+        }
+    };
+
+    let task = ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .find(|b| rest[marker..].starts_with(**b))
+        .map_or(0, |b| b.len());
+    Some(indent + marker + task)
+}
+
+/// A line ending in two spaces or a backslash is a hard break.
+fn hard_break(line: &str) -> bool {
+    line.ends_with("  ") || line.ends_with('\\')
+}
+
+/// Lines of the paragraph or list item containing `row`, or `None` when the
+/// row is not prose.
+pub fn paragraph_at(lines: &[String], mask: &[bool], row: usize) -> Option<Range<usize>> {
+    if !mask.get(row).copied().unwrap_or(false) {
+        return None;
+    }
+
+    let depth = quote_depth(&lines[row]);
+    let mut start = row;
+    while start > 0
+        && mask[start - 1]
+        && quote_depth(&lines[start - 1]) == depth
+        && !starts_item(&lines[start])
+        && !hard_break(&lines[start - 1])
+    {
+        start -= 1;
+    }
+    let mut end = row + 1;
+    while end < lines.len()
+        && mask[end]
+        && quote_depth(&lines[end]) == depth
+        && !starts_item(&lines[end])
+        && !hard_break(&lines[end - 1])
+    {
+        end += 1;
+    }
+    Some(start..end)
+}
+
+/// Re-wrap one paragraph to `width` columns. List items keep their marker
+/// on the first line and hang the rest under the item's text; quoted
+/// paragraphs repeat their `>` markers on every line.
+pub fn reflow_paragraph(lines: &[String], width: usize) -> Vec<String> {
+    let Some(first) = lines.first() else {
+        return Vec::new();
+    };
+    let prefix_len = text_start(first);
+    let first_prefix = &first[..prefix_len];
+    let rest_prefix = continuation_prefix(first);
+    let trailing_break = lines
+        .last()
+        .filter(|line| hard_break(line))
+        .map(|line| if line.ends_with('\\') { "\\" } else { "  " });
+
+    let words: Vec<&str> = std::iter::once(&first[prefix_len..])
+        .chain(lines[1..].iter().map(|line| &line[quote_prefix(line)..]))
+        .flat_map(str::split_whitespace)
+        .collect();
+
+    let mut out = Vec::new();
+    let mut current = first_prefix.to_string();
+    let mut has_word = false;
+    for word in words {
+        let fits = current.chars().count() + 1 + word.chars().count() <= width;
+        if has_word && !fits {
+            out.push(std::mem::replace(&mut current, rest_prefix.clone()));
+            has_word = false;
+        }
+        if has_word {
+            current.push(' ');
+        }
+        current.push_str(word);
+        has_word = true;
+    }
+    if let Some(suffix) = trailing_break {
+        current.push_str(suffix);
+    }
+    out.push(current);
+    out
+}
+
+/// Reflow every paragraph that overlaps `range`, leaving other lines alone.
+/// Returns the replacement for the (possibly widened) range it covered.
+pub fn reflow_range(
+    lines: &[String],
+    range: Range<usize>,
+    width: usize,
+) -> (Range<usize>, Vec<String>) {
+    let mask = prose_mask(lines);
+    let mut covered = range.clone();
+    let mut out = Vec::new();
+
+    let mut row = range.start;
+    while row < range.end.min(lines.len()) {
+        match paragraph_at(lines, &mask, row) {
+            // The first paragraph may start above `range`; it is reflowed
+            // whole, widening the covered lines.
+            Some(para) => {
+                covered.start = covered.start.min(para.start);
+                covered.end = covered.end.max(para.end);
+                out.extend(reflow_paragraph(&lines[para.clone()], width));
+                row = para.end;
+            }
+            None => {
+                out.push(lines[row].clone());
+                row += 1;
+            }
+        }
+    }
+    (covered, out)
+}
+
+/// Where to break `line` so it fits `width`: the byte offset of the last
+/// space at or before the limit that leaves text on both sides.
+pub fn break_point(line: &str, width: usize) -> Option<usize> {
+    if line.chars().count() <= width {
+        return None;
+    }
+    let keep = text_start(line);
+    let limit = line
+        .char_indices()
+        .nth(width)
+        .map_or(line.len(), |(idx, _)| idx);
+    let at = if line[limit..].starts_with(' ') {
+        Some(limit)
+    } else {
+        line[..limit].rfind(' ')
+    };
+    at.filter(|&idx| idx > keep && !line[keep..idx].trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_reflow_paragraph_and_list_item() {
+        assert_eq!(
+            reflow_paragraph(&lines("one two three\nfour five six seven"), 14),
+            lines("one two three\nfour five six\nseven")
+        );
+        assert_eq!(
+            reflow_paragraph(&lines("  - [ ] alpha beta gamma delta"), 18),
+            lines("  - [ ] alpha beta\n        gamma\n        delta")
+        );
+        assert_eq!(
+            reflow_paragraph(&lines("short\nlines  "), 80),
+            lines("short lines  ")
+        );
+    }
+
+    #[test]
+    fn test_paragraph_bounds_skip_structure() {
+        let note = lines(
+            "# Title\nfirst para\ncontinues here\n\n- item one\n  wraps\n- item two\n```\ncode line\n```\n| a | b |",
+        );
+        let mask = prose_mask(&note);
+        assert_eq!(paragraph_at(&note, &mask, 2), Some(1..3));
+        assert_eq!(paragraph_at(&note, &mask, 5), Some(4..6));
+        assert_eq!(paragraph_at(&note, &mask, 6), Some(6..7));
+        assert_eq!(paragraph_at(&note, &mask, 0), None);
+        assert_eq!(paragraph_at(&note, &mask, 8), None);
+        assert_eq!(paragraph_at(&note, &mask, 10), None);
+    }
+
+    #[test]
+    fn test_reflow_range_leaves_code_alone() {
+        let note = lines("aa bb cc dd\n```\naa bb cc dd\n```\nee ff");
+        let (range, out) = reflow_range(&note, 0..5, 5);
+        assert_eq!(range, 0..5);
+        assert_eq!(out, lines("aa bb\ncc dd\n```\naa bb cc dd\n```\nee ff"));
+    }
+
+    #[test]
+    fn test_reflow_blockquotes() {
+        let note = lines("> one two three\n> four five\n>\n> > - nested item here\ntext");
+        let mask = prose_mask(&note);
+        assert_eq!(paragraph_at(&note, &mask, 1), Some(0..2));
+        assert_eq!(paragraph_at(&note, &mask, 2), None);
+        assert_eq!(paragraph_at(&note, &mask, 3), Some(3..4));
+        assert_eq!(paragraph_at(&note, &mask, 4), Some(4..5));
+
+        let (range, out) = reflow_range(&note, 0..4, 14);
+        assert_eq!(range, 0..4);
+        assert_eq!(
+            out,
+            lines("> one two\n> three four\n> five\n>\n> > - nested\n> >   item\n> >   here")
+        );
+        assert_eq!(continuation_prefix("> > - [ ] x"), "> >       ");
+        assert_eq!(break_point("> one two", 7), Some(5));
+    }
+
+    #[test]
+    fn test_break_point() {
+        assert_eq!(break_point("short", 10), None);
+        assert_eq!(break_point("hello world again", 12), Some(11));
+        assert_eq!(break_point("- averyveryverylongword x", 10), None);
+        assert_eq!(break_point("- one two three", 9), Some(9));
+        assert_eq!(break_point("- one two three", 8), Some(5));
+    }
+}
@@ -0,0 +1,92 @@
+//! Recent yanks and deletes, newest first, so `:yanks` can paste an older
+//! one without naming registers up front.
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Default)]
+pub struct YankHistory {
+    entries: VecDeque<String>,
+    capacity: usize,
+}
+
+impl YankHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record `text` as the newest entry. Repeating an older entry moves it
+    /// to the front instead of storing it twice.
+    pub fn push(&mut self, text: String) {
+        if text.trim().is_empty() || self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|entry| *entry != text);
+        self.entries.push_front(text);
+        self.entries.truncate(self.capacity);
+    }
+
+    pub fn entries(&self) -> &VecDeque<String> {
+        &self.entries
+    }
+
+    /// Take entry `idx` for pasting, making it the newest again.
+    pub fn promote(&mut self, idx: usize) -> Option<String> {
+        let text = self.entries.remove(idx)?;
+        self.entries.push_front(text.clone());
+        Some(text)
+    }
+}
+
+/// One-line summary of an entry: its first non-blank line and how many
+/// more follow.
+pub fn preview(text: &str, width: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let first = lines
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let more = lines.len().saturating_sub(1);
+    let suffix = if more > 0 {
+        format!("  (+{more} lines)")
+    } else {
+        String::new()
+    };
+    let room = width.saturating_sub(suffix.chars().count()).max(1);
+    let mut head: String = first.chars().take(room).collect();
+    if first.chars().count() > room && room > 1 {
+        head.pop();
+        head.push('…');
+    }
+    head + &suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_dedups_and_caps() {
+        let mut history = YankHistory::new(3);
+        for text in ["a", "b", "  ", "c", "a", "d"] {
+            history.push(text.to_string());
+        }
+        assert_eq!(history.entries(), &["d", "a", "c"]);
+
+        assert_eq!(history.promote(2).as_deref(), Some("c"));
+        assert_eq!(history.entries(), &["c", "d", "a"]);
+        assert_eq!(history.promote(7), None);
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(
+            preview("\n  first line\nsecond\n", 40),
+            "first line  (+2 lines)"
+        );
+        assert_eq!(preview("abcdefgh", 5), "abcd…");
+    }
+}
@@ -1,7 +1,15 @@
 use crossterm::event::{KeyEvent, MouseEvent};
 use std::path::PathBuf;
 
+use crate::collab::session::CollabEvent;
+use crate::hooks::HookEvent;
+use crate::model::link_index::LinkIndex;
+use crate::model::search_index::SearchIndex;
+use crate::plugin::installer::GitReport;
 use crate::plugin::manifest::PluginId;
+use crate::plugin::registry::RegistryEntry;
+use crate::plugin::runtime::{Decoration, PluginEdit, Prompt};
+use crate::sync::SyncResult;
 
 /// Direction for cursor movement.
 #[derive(Debug, Clone, Copy)]
@@ -33,6 +41,7 @@ pub enum Msg {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Resize(u16, u16),
+    Paste(String),
 
     // -- Buffer operations
     InsertChar(char),
@@ -47,12 +56,49 @@ pub enum Msg {
     SaveActiveBuffer,
     SaveAllBuffers,
     OpenFile(PathBuf),
+    /// `blackbox://` link from `--uri` or another invocation.
+    OpenUri(String),
+    /// `capture <text>` from another invocation.
+    Capture(String),
     FileChanged(PathBuf),
+    /// Old and new path of a note renamed outside the app.
+    FileRenamed(PathBuf, PathBuf),
     ScratchAutoSave,
 
     // -- Plugins
     PluginCommand(String),
     PluginEvent(PluginId, PluginAction),
+    /// A step of `:plugins.sync` or `:plugins.update`, then its per-plugin
+    /// report.
+    PluginSyncProgress(String),
+    PluginSyncFinished(GitReport),
+    /// `:plugin install` finished cloning a repository.
+    PluginInstalled(String, Result<String, String>),
+    /// `:plugins.browse` fetched the registry index.
+    PluginRegistryFetched(Result<Vec<RegistryEntry>, String>),
+    /// Files changed under these plugin directories.
+    PluginFilesChanged(Vec<PathBuf>),
+
+    // -- Collaboration
+    Collab(CollabEvent),
+
+    // -- Sync
+    SyncFinished(Result<SyncResult, String>),
+
+    // -- Hooks
+    /// A lifecycle hook finished: what it printed (empty when quiet), or
+    /// its failure.
+    HookFinished(HookEvent, Result<String, String>),
+
+    // -- Diagrams
+    /// `:diagram` finished rendering to an image, or failed.
+    DiagramRendered(Result<PathBuf, String>),
+
+    // -- Search
+    /// The content search index finished loading and catching up.
+    SearchIndexReady(Box<SearchIndex>),
+    /// The vault link index finished loading and catching up.
+    LinkIndexReady(Box<LinkIndex>),
 
     // -- System
     Tick,
@@ -60,8 +106,14 @@ pub enum Msg {
 }
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Phase 3 scaffolding: plugin actions are emitted once runtime callbacks are wired.
 pub enum PluginAction {
     Notify(String),
+    /// Replace ranges of the active buffer as one undo step.
+    Edit(Vec<PluginEdit>),
+    /// Replace the plugin's decorations on the active buffer.
+    Decorate(PluginId, Vec<Decoration>),
+    /// Ask the user, then run the command again with the answer.
+    Prompt(Prompt),
+    #[allow(dead_code)] // Emitted once plugins can draw to a pane.
     RequestRedraw,
 }
@@ -0,0 +1,34 @@
+//! Hand URLs and files to the desktop's default application.
+
+use std::process::{Command, Stdio};
+
+fn command(target: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("open");
+        cmd.arg(target);
+        cmd
+    } else if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]).arg(target);
+        cmd
+    } else {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(target);
+        cmd
+    }
+}
+
+/// Open `target` without waiting for the application to exit.
+pub fn open(target: &str) -> Result<(), String> {
+    let mut cmd = command(target);
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("cannot run {program}: {err}"))?;
+    // Reap the helper so it does not linger as a zombie.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -1,10 +1,495 @@
+//! Functions the host exposes to plugins, under `extism:host/user` for WASM
+//! and in the `blackbox` table for Lua. Each one but `log` and
+//! `prompt_input` checks the permission it needs in the plugin's manifest,
+//! so a plugin that calls a function it was not granted fails the call with
+//! a clear error.
+
+#[cfg(feature = "plugins")]
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+#[cfg(feature = "plugins")]
+use std::path::{Component, Path};
+#[cfg(feature = "plugins")]
+use std::time::Duration;
+
+#[cfg(feature = "plugins")]
+use serde::Deserialize;
+use serde::Serialize;
+
+#[cfg(feature = "plugins")]
+use crate::model::config::PluginLimitsConfig;
+
+#[cfg(feature = "plugins")]
+use crate::plugin::permission::Permission;
+use crate::plugin::runtime::CursorContext;
+#[cfg(feature = "plugins")]
+use crate::plugin::runtime::{Decoration, PluginEdit, Prompt};
+
+/// The active buffer as `read_buffer` returns it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BufferView {
+    pub text: String,
+    /// `None` for an unsaved buffer.
+    pub path: Option<PathBuf>,
+    pub cursor: CursorContext,
+    pub selection: Option<SelectionView>,
+}
+
+/// A selection from `start` up to, not including, `end`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelectionView {
+    pub start: CursorContext,
+    pub end: CursorContext,
+    pub text: String,
+}
+
+/// What `http_request` takes.
+#[cfg(feature = "plugins")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpRequest {
+    pub url: String,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// What `http_request` returns, whatever the status.
+#[cfg(feature = "plugins")]
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// Lines of `log` output kept per plugin for `:plugins.logs`.
+#[cfg(feature = "plugins")]
+pub const LOG_LINES: usize = 200;
+
+/// What host functions can see while a plugin call runs.
+#[cfg(feature = "plugins")]
 #[derive(Debug, Default)]
-#[allow(dead_code)] // Phase 3 scaffolding: host function registry is wired when extism runtime lands.
+pub struct HostState {
+    /// The plugin's name, for the tracing log.
+    pub plugin: String,
+    pub permissions: Vec<Permission>,
+    /// The vault `read_note`, `write_note` and `list_notes` are confined to.
+    pub vault: PathBuf,
+    /// From the manifest, for `http_request`.
+    pub allowed_hosts: Vec<String>,
+    pub limits: PluginLimitsConfig,
+    pub buffer: BufferView,
+    /// Edits from `propose_edit`, applied together once the call returns.
+    pub proposed: Vec<PluginEdit>,
+    /// The last list passed to `set_decorations` during the call.
+    pub decorations: Option<Vec<Decoration>>,
+    /// The newest [`LOG_LINES`] lines from `log` and failed calls, kept
+    /// across calls and reloads.
+    pub logs: VecDeque<String>,
+    /// Answers still to hand to `prompt_input` during a command call;
+    /// `None` in calls that cannot ask.
+    pub answers: Option<VecDeque<String>>,
+    /// The first question `prompt_input` had no answer for.
+    pub prompt: Option<Prompt>,
+    /// The first of `write_note` and `http_request` the call made; a
+    /// command may not ask anything new after it.
+    pub side_effect: Option<&'static str>,
+}
+
+#[cfg(feature = "plugins")]
+impl HostState {
+    fn require(&self, permission: Permission, function: &str) -> Result<(), String> {
+        if self.permissions.contains(&permission) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{function} needs the {} permission",
+                permission.as_str()
+            ))
+        }
+    }
+
+    /// `log`: record `message` at `level` (`debug`, `info`, `warn` or
+    /// `error`) in the plugin's log and the tracing log.
+    pub fn log(&mut self, level: &str, message: &str) -> Result<(), String> {
+        let plugin = &self.plugin;
+        match level {
+            "debug" => tracing::debug!("plugin {plugin}: {message}"),
+            "info" => tracing::info!("plugin {plugin}: {message}"),
+            "warn" => tracing::warn!("plugin {plugin}: {message}"),
+            "error" => tracing::error!("plugin {plugin}: {message}"),
+            _ => return Err(format!("log: unknown level {level}")),
+        }
+        self.push_log(level, message);
+        Ok(())
+    }
+
+    /// Let `function` reach outside the buffer. A command runs again from
+    /// the start for each answer, so this fails while answers from earlier
+    /// runs are still to be handed out.
+    fn side_effect(&mut self, function: &'static str) -> Result<(), String> {
+        if self
+            .answers
+            .as_ref()
+            .is_some_and(|answers| !answers.is_empty())
+        {
+            return Err(format!("{function}: ask every question before calling it"));
+        }
+        self.side_effect.get_or_insert(function);
+        Ok(())
+    }
+
+    /// `prompt_input`: the user's answer to `question`. Answers are not
+    /// known during the call, so the first unanswered question fails it,
+    /// and the command runs again from the start once the user replies.
+    /// Asking after `write_note` or `http_request` fails the command
+    /// instead, so neither happens twice.
+    pub fn prompt_input(&mut self, question: &str, default: &str) -> Result<String, String> {
+        let Some(answers) = self.answers.as_mut() else {
+            return Err("prompt_input: only commands can ask for input".to_string());
+        };
+        if let Some(answer) = answers.pop_front() {
+            return Ok(answer);
+        }
+        if let Some(function) = self.side_effect {
+            return Err(format!("prompt_input: ask before calling {function}"));
+        }
+        self.prompt.get_or_insert_with(|| Prompt {
+            question: question.to_string(),
+            default: default.to_string(),
+        });
+        Err("prompt_input: waiting for the user".to_string())
+    }
+
+    /// Add a line to the plugin's log, dropping the oldest past
+    /// [`LOG_LINES`].
+    pub fn push_log(&mut self, level: &str, message: &str) {
+        if self.logs.len() == LOG_LINES {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(format!("{level:<5} {message}"));
+    }
+
+    /// `read_buffer`: text, path, cursor and selection of the active buffer.
+    pub fn read_buffer(&self) -> Result<&BufferView, String> {
+        self.require(Permission::ReadBuffer, "read_buffer")?;
+        Ok(&self.buffer)
+    }
+
+    /// `propose_edit`: replacements against the buffer as the command found
+    /// it, applied once the call returns.
+    pub fn propose_edit(&mut self, edits: Vec<PluginEdit>) -> Result<(), String> {
+        self.require(Permission::ProposeEdit, "propose_edit")?;
+        self.proposed.extend(edits);
+        Ok(())
+    }
+
+    /// `list_notes`: every note in the vault as a path relative to it,
+    /// skipping hidden and ignored files.
+    pub fn list_notes(&self) -> Result<Vec<String>, String> {
+        self.require(Permission::ReadVault, "list_notes")?;
+        let mut notes: Vec<String> = ignore::WalkBuilder::new(&self.vault)
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "md"))
+            .filter_map(|entry| {
+                let rel = entry.path().strip_prefix(&self.vault).ok()?;
+                Some(rel.to_string_lossy().replace('\\', "/"))
+            })
+            .collect();
+        notes.sort();
+        Ok(notes)
+    }
+
+    /// `read_note`: the text of the note at `path` in the vault.
+    pub fn read_note(&self, path: &str) -> Result<String, String> {
+        self.require(Permission::ReadVault, "read_note")?;
+        let file = self.vault_file(path)?;
+        std::fs::read_to_string(&file).map_err(|err| format!("read_note: {path}: {err}"))
+    }
+
+    /// `write_note`: create or replace the note at `path` in the vault,
+    /// making its folders.
+    pub fn write_note(&mut self, path: &str, text: &str) -> Result<(), String> {
+        self.require(Permission::WriteVault, "write_note")?;
+        let file = self.vault_file(path)?;
+        self.side_effect("write_note")?;
+        file.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&file, text))
+            .map_err(|err| format!("write_note: {path}: {err}"))
+    }
+
+    /// `path` under the vault. It must be relative and stay inside the
+    /// vault even through symlinks, whether or not it exists yet; the note
+    /// itself may not be a symlink, dangling or not, and no folder on the
+    /// way may be a dangling one.
+    fn vault_file(&self, path: &str) -> Result<PathBuf, String> {
+        let rel = Path::new(path);
+        let inside = rel
+            .components()
+            .all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+        if path.trim().is_empty() || !inside {
+            return Err(format!("{path} is not a path inside the vault"));
+        }
+        let file = self.vault.join(rel);
+        let is_link = |path: &Path| {
+            path.symlink_metadata()
+                .is_ok_and(|meta| meta.file_type().is_symlink())
+        };
+        let dangling = file
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| *ancestor != self.vault)
+            .any(|ancestor| is_link(ancestor) && !ancestor.exists());
+        if is_link(&file) || dangling {
+            return Err(format!("{path} is a symlink"));
+        }
+        let vault = self
+            .vault
+            .canonicalize()
+            .map_err(|err| format!("vault: {err}"))?;
+        let existing = file
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+            .and_then(|ancestor| ancestor.canonicalize().ok());
+        if !existing.is_some_and(|real| real.starts_with(&vault)) {
+            return Err(format!("{path} is not a path inside the vault"));
+        }
+        Ok(file)
+    }
+
+    /// `http_request`: send `request` to one of the manifest's
+    /// `allowed_hosts` and return the response. Redirects are handed back
+    /// rather than followed, so they cannot leave the allowed hosts; the
+    /// request gets the call's time limit and the body `memory_mb`.
+    pub fn http_request(&mut self, request: HttpRequest) -> Result<HttpResponse, String> {
+        self.require(Permission::Network, "http_request")?;
+        self.side_effect("http_request")?;
+        let uri: ureq::http::Uri = request
+            .url
+            .parse()
+            .map_err(|err| format!("http_request: {}: {err}", request.url))?;
+        if !matches!(uri.scheme_str(), Some("http" | "https")) {
+            return Err(format!(
+                "http_request: {} is not an http(s) URL",
+                request.url
+            ));
+        }
+        let host = uri.host().unwrap_or_default();
+        if !self
+            .allowed_hosts
+            .iter()
+            .any(|pattern| host_allowed(pattern, host))
+        {
+            return Err(format!("http_request: {host} is not in allowed_hosts"));
+        }
+
+        let method = request.method.as_deref().unwrap_or("GET").to_uppercase();
+        let mut builder = ureq::http::Request::builder()
+            .method(method.as_str())
+            .uri(uri);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .max_redirects(0)
+            .timeout_global(Some(Duration::from_millis(self.limits.timeout_ms)))
+            .build()
+            .new_agent();
+        let failed = |err: ureq::Error| format!("http_request: {}: {err}", request.url);
+        let sent = match request.body.clone() {
+            Some(body) => builder.body(body).map(|req| agent.run(req)),
+            None => builder.body(()).map(|req| agent.run(req)),
+        };
+        let mut response = sent
+            .map_err(|err| format!("http_request: {err}"))?
+            .map_err(failed)?;
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .body_mut()
+            .with_config()
+            .limit(u64::from(self.limits.memory_mb) * 1024 * 1024)
+            .read_to_vec()
+            .map_err(failed)?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// `set_decorations`: replace the plugin's marks on the active buffer;
+    /// an empty list clears them.
+    pub fn set_decorations(&mut self, decorations: Vec<Decoration>) -> Result<(), String> {
+        self.require(Permission::Decorate, "set_decorations")?;
+        self.decorations = Some(decorations);
+        Ok(())
+    }
+}
+
+/// Whether `host` matches an `allowed_hosts` entry: the host itself,
+/// `*.example.com` for any subdomain or `*` for everything.
+#[cfg(feature = "plugins")]
+fn host_allowed(pattern: &str, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let pattern = pattern.trim().to_ascii_lowercase();
+    match pattern.strip_prefix('*') {
+        Some("") => true,
+        Some(suffix) if suffix.starts_with('.') => host.ends_with(suffix),
+        _ => host == pattern,
+    }
+}
+
+#[cfg(feature = "plugins")]
 pub struct HostFunctions;
 
+#[cfg(feature = "plugins")]
 impl HostFunctions {
-    #[allow(dead_code)] // Phase 3 scaffolding: called once plugin runtime initialization is connected.
-    pub fn register_all() {
-        // Extism host function registration will be implemented here.
+    /// Every host function, sharing `state` with the runtime.
+    pub fn register_all(state: &extism::UserData<HostState>) -> Vec<extism::Function> {
+        vec![
+            extism::Function::new("read_buffer", [], [extism::PTR], state.clone(), read_buffer),
+            extism::Function::new(
+                "propose_edit",
+                [extism::PTR],
+                [],
+                state.clone(),
+                propose_edit,
+            ),
+            extism::Function::new(
+                "http_request",
+                [extism::PTR],
+                [extism::PTR],
+                state.clone(),
+                http_request,
+            ),
+            extism::Function::new("log", [extism::PTR, extism::PTR], [], state.clone(), log),
+            extism::Function::new(
+                "prompt_input",
+                [extism::PTR, extism::PTR],
+                [extism::PTR],
+                state.clone(),
+                prompt_input,
+            ),
+            extism::Function::new("list_notes", [], [extism::PTR], state.clone(), list_notes),
+            extism::Function::new(
+                "read_note",
+                [extism::PTR],
+                [extism::PTR],
+                state.clone(),
+                read_note,
+            ),
+            extism::Function::new(
+                "write_note",
+                [extism::PTR, extism::PTR],
+                [],
+                state.clone(),
+                write_note,
+            ),
+            extism::Function::new(
+                "set_decorations",
+                [extism::PTR],
+                [],
+                state.clone(),
+                set_decorations,
+            ),
+        ]
     }
 }
+
+// `read_buffer() -> json`: text, path, cursor and selection of the active
+// buffer.
+#[cfg(feature = "plugins")]
+extism::host_fn!(read_buffer(state: HostState;) -> String {
+    let state = state.get()?;
+    let state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    let buffer = state.read_buffer().map_err(extism::Error::msg)?;
+    Ok(serde_json::to_string(buffer)?)
+});
+
+// `propose_edit(json)`: a list of `{ "start", "end", "text" }` replacements
+// against the buffer as the command found it.
+#[cfg(feature = "plugins")]
+extism::host_fn!(propose_edit(state: HostState; edits: String) {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    let edits: Vec<PluginEdit> = serde_json::from_str(&edits)
+        .map_err(|err| extism::Error::msg(format!("propose_edit: {err}")))?;
+    state.propose_edit(edits).map_err(extism::Error::msg)
+});
+
+// `set_decorations(json)`: a list of `{ "line", "start", "end", "kind",
+// "gutter", "text" }` marks for the active buffer.
+#[cfg(feature = "plugins")]
+extism::host_fn!(set_decorations(state: HostState; decorations: String) {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    let decorations: Vec<Decoration> = serde_json::from_str(&decorations)
+        .map_err(|err| extism::Error::msg(format!("set_decorations: {err}")))?;
+    state.set_decorations(decorations).map_err(extism::Error::msg)
+});
+
+// `list_notes() -> json`: vault-relative paths of every note.
+#[cfg(feature = "plugins")]
+extism::host_fn!(list_notes(state: HostState;) -> String {
+    let state = state.get()?;
+    let state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    let notes = state.list_notes().map_err(extism::Error::msg)?;
+    Ok(serde_json::to_string(&notes)?)
+});
+
+// `read_note(path) -> text`: a note given relative to the vault.
+#[cfg(feature = "plugins")]
+extism::host_fn!(read_note(state: HostState; path: String) -> String {
+    let state = state.get()?;
+    let state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    state.read_note(&path).map_err(extism::Error::msg)
+});
+
+// `write_note(path, text)`: create or replace a note given relative to the
+// vault.
+#[cfg(feature = "plugins")]
+extism::host_fn!(write_note(state: HostState; path: String, text: String) {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    state.write_note(&path, &text).map_err(extism::Error::msg)
+});
+
+// `http_request(json) -> json`: `{ "url", "method", "headers", "body" }` to
+// `{ "status", "headers", "body" }`, for the manifest's `allowed_hosts`.
+#[cfg(feature = "plugins")]
+extism::host_fn!(http_request(state: HostState; request: String) -> String {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    let request: HttpRequest = serde_json::from_str(&request)
+        .map_err(|err| extism::Error::msg(format!("http_request: {err}")))?;
+    let response = state.http_request(request).map_err(extism::Error::msg)?;
+    Ok(serde_json::to_string(&response)?)
+});
+
+// `prompt_input(question, default) -> answer`: ask the user during a
+// command; needs no permission.
+#[cfg(feature = "plugins")]
+extism::host_fn!(prompt_input(state: HostState; question: String, default: String) -> String {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    state.prompt_input(&question, &default).map_err(extism::Error::msg)
+});
+
+// `log(level, message)`: a line in the plugin's log; needs no permission.
+#[cfg(feature = "plugins")]
+extism::host_fn!(log(state: HostState; level: String, message: String) {
+    let state = state.get()?;
+    let mut state = state.lock().map_err(|_| extism::Error::msg("host state poisoned"))?;
+    state.log(&level, &message).map_err(extism::Error::msg)
+});
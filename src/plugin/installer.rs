@@ -1,12 +1,471 @@
+//! Git-hosted plugins: clone what `repo` entries name into the plugin base
+//! directory and keep existing clones current.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
 use crate::model::config::PluginConfig;
+use crate::plugin::PluginManager;
+use crate::plugin::manifest::PluginManifest;
 
 #[derive(Debug, Default)]
-#[allow(dead_code)] // Phase 3 scaffolding: installer is invoked by future :PluginSync command.
 pub struct PluginInstaller;
 
+/// What a sync or update pass did: one line per plugin, and the updates
+/// it left unapplied because they ask for more access.
+#[derive(Debug, Default)]
+pub struct GitReport {
+    pub lines: Vec<String>,
+    pub held: Vec<HeldUpdate>,
+}
+
+/// A fetched update whose `plugin.toml` asks for permissions or hosts the
+/// checked out one does not; it is applied only once the user agrees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldUpdate {
+    pub repo: String,
+    /// The added access, as [`PluginManifest::added_access`] words it.
+    pub access: String,
+}
+
+/// How one plugin's pass ended.
+enum Outcome {
+    Done(String),
+    Held { summary: String, access: String },
+}
+
 impl PluginInstaller {
-    #[allow(dead_code)] // Phase 3 scaffolding: repo sync flow is planned, signature is stabilized now.
-    pub fn sync(_plugins: &[PluginConfig]) {
-        // Implementation will clone/pull plugin repositories and validate manifests.
+    /// Clone or update every enabled `repo` plugin, calling `progress` as
+    /// each one starts. Returns one line per plugin.
+    pub fn sync(plugins: &[PluginConfig], progress: impl FnMut(String)) -> GitReport {
+        Self::each_repo(plugins, "syncing", progress, |repo, branch, dir| {
+            Self::sync_one(repo, branch, dir)
+        })
+    }
+
+    /// Fetch every cloned `repo` plugin and report new commits and tags;
+    /// with `apply`, fast-forward the ones that have them.
+    pub fn update(
+        plugins: &[PluginConfig],
+        apply: bool,
+        progress: impl FnMut(String),
+    ) -> GitReport {
+        let verb = if apply { "updating" } else { "checking" };
+        Self::each_repo(plugins, verb, progress, |_, branch, dir| {
+            Self::update_one(branch, dir, apply, None)
+        })
+    }
+
+    /// Fast-forward the plugin a held update is for, as long as upstream
+    /// still asks for no more than the user agreed to.
+    pub fn accept_update(
+        plugins: &[PluginConfig],
+        held: &HeldUpdate,
+        progress: impl FnMut(String),
+    ) -> GitReport {
+        let plugins: Vec<PluginConfig> = plugins
+            .iter()
+            .filter(|plugin| plugin.repo.as_deref() == Some(held.repo.as_str()))
+            .cloned()
+            .collect();
+        Self::each_repo(&plugins, "updating", progress, |_, branch, dir| {
+            Self::update_one(branch, dir, true, Some(&held.access))
+        })
+    }
+
+    fn each_repo(
+        plugins: &[PluginConfig],
+        verb: &str,
+        mut progress: impl FnMut(String),
+        mut run: impl FnMut(&str, Option<&str>, &Path) -> Result<Outcome, String>,
+    ) -> GitReport {
+        let mut report = GitReport::default();
+        for plugin in plugins.iter().filter(|plugin| plugin.enabled) {
+            let Some(repo) = plugin.repo.as_deref() else {
+                continue;
+            };
+            let Some(dir) = PluginManager::resolve_plugin_root(plugin) else {
+                continue;
+            };
+            progress(format!("plugins: {verb} {repo}"));
+            let line = match run(repo, plugin.branch.as_deref(), &dir) {
+                Ok(Outcome::Done(summary)) => format!("plugin {repo}: {summary}"),
+                Ok(Outcome::Held { summary, access }) => {
+                    let line =
+                        format!("plugin {repo}: {summary}, not applied: it asks for {access}");
+                    report.held.push(HeldUpdate {
+                        repo: repo.to_string(),
+                        access,
+                    });
+                    line
+                }
+                Err(err) => format!("plugin {repo}: {err}"),
+            };
+            report.lines.push(line);
+        }
+        if report.lines.is_empty() {
+            report
+                .lines
+                .push("plugins: no repo plugins configured".to_string());
+        }
+        report
+    }
+
+    /// Clone `repo` into the plugin base directory and check it is a plugin,
+    /// removing the clone again when it is not.
+    pub fn install(repo: &str) -> Result<String, String> {
+        let plugin = Self::repo_config(repo);
+        let dir = PluginManager::resolve_plugin_root(&plugin)
+            .ok_or_else(|| "no plugin directory".to_string())?;
+        if dir.exists() {
+            return Err(format!("{} already exists", dir.display()));
+        }
+        match Self::sync_one(repo, None, &dir) {
+            Ok(Outcome::Done(summary)) => Ok(summary),
+            Ok(Outcome::Held { summary, .. }) => Ok(summary),
+            Err(err) => {
+                let _ = std::fs::remove_dir_all(&dir);
+                Err(err)
+            }
+        }
+    }
+
+    /// Delete the clone of a `repo` plugin. Plugins loaded from a `path`
+    /// are the user's own checkout and stay on disk.
+    pub fn uninstall(plugin: &PluginConfig) -> Result<(), String> {
+        if plugin.path.is_some() || plugin.repo.is_none() {
+            return Ok(());
+        }
+        let Some(dir) = PluginManager::resolve_plugin_root(plugin) else {
+            return Ok(());
+        };
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!("cannot remove {}: {err}", dir.display())),
+        }
+    }
+
+    /// The config entry `:plugin install <repo>` adds.
+    pub fn repo_config(repo: &str) -> PluginConfig {
+        PluginConfig {
+            repo: Some(repo.to_string()),
+            path: None,
+            branch: None,
+            enabled: true,
+            config: HashMap::new(),
+        }
+    }
+
+    /// Clone `repo` into `dir`, or fetch and fast-forward an existing clone,
+    /// on `branch` when one is pinned; then check the manifest.
+    /// An update that adds access is held back, since a clone is loaded
+    /// with whatever its `plugin.toml` asks for.
+    fn sync_one(repo: &str, branch: Option<&str>, dir: &Path) -> Result<Outcome, String> {
+        let summary = if dir.join(".git").is_dir() {
+            let before = head(dir)?;
+            git(dir, &["fetch", "--quiet", "origin"])?;
+            if let Some(access) = added_access(dir, &upstream(branch))? {
+                return Ok(Outcome::Held {
+                    summary: format!("update available at {before}"),
+                    access,
+                });
+            }
+            fast_forward(dir, branch)?;
+            let after = head(dir)?;
+            if before == after {
+                format!("up to date at {after}")
+            } else {
+                format!("updated {before} -> {after}")
+            }
+        } else {
+            if let Some(parent) = dir.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|err| format!("cannot create {}: {err}", parent.display()))?;
+            }
+            let mut args = vec!["clone", "--quiet"];
+            if let Some(branch) = branch {
+                args.extend(["--branch", branch]);
+            }
+            let target = dir.to_string_lossy();
+            // `--` keeps a repo like `--upload-pack=...` from being an option.
+            args.extend(["--", repo, &target]);
+            git(Path::new("."), &args)?;
+            format!("cloned at {}", head(dir)?)
+        };
+
+        let manifest = PluginManifest::validate(dir)?;
+        Ok(Outcome::Done(format!(
+            "{summary} ({} {})",
+            manifest.name, manifest.version
+        )))
+    }
+
+    /// New commits and tags upstream of a clone, fast-forwarded onto with
+    /// `apply`, as `2 new commits, tag v0.4.0: 0.3.0 (abc1234) -> 0.4.0
+    /// (def5678)`. An update that adds access other than `accepted` is held
+    /// back.
+    fn update_one(
+        branch: Option<&str>,
+        dir: &Path,
+        apply: bool,
+        accepted: Option<&str>,
+    ) -> Result<Outcome, String> {
+        if !dir.join(".git").is_dir() {
+            return Err("not cloned yet; run :plugins.sync".to_string());
+        }
+        git(dir, &["fetch", "--quiet", "--tags", "origin"])?;
+        let upstream = upstream(branch);
+        let before = version_at(dir)?;
+        let behind: usize = git(dir, &["rev-list", "--count", &format!("HEAD..{upstream}")])?
+            .parse()
+            .unwrap_or(0);
+        if behind == 0 {
+            return Ok(Outcome::Done(format!("up to date at {before}")));
+        }
+
+        let mut news = format!("{behind} new commit{}", if behind == 1 { "" } else { "s" });
+        let tag = latest_tag(dir, &upstream);
+        if tag.is_some() && tag != latest_tag(dir, "HEAD") {
+            news.push_str(&format!(", tag {}", tag.unwrap_or_default()));
+        }
+        let added = added_access(dir, &upstream)?.filter(|access| accepted != Some(access));
+        match (apply, added) {
+            (false, None) => return Ok(Outcome::Done(format!("{news} available (at {before})"))),
+            (false, Some(access)) => {
+                return Ok(Outcome::Done(format!(
+                    "{news} available (at {before}), asking for {access}"
+                )));
+            }
+            (true, Some(access)) => {
+                return Ok(Outcome::Held {
+                    summary: format!("{news} (at {before})"),
+                    access,
+                });
+            }
+            (true, None) => {}
+        }
+        fast_forward(dir, branch)?;
+        PluginManifest::validate(dir)?;
+        Ok(Outcome::Done(format!(
+            "{news}: {before} -> {}",
+            version_at(dir)?
+        )))
+    }
+}
+
+/// What a clone tracks: `origin/<branch>` when one is pinned.
+fn upstream(branch: Option<&str>) -> String {
+    branch.map_or_else(
+        || "@{upstream}".to_string(),
+        |branch| format!("origin/{branch}"),
+    )
+}
+
+/// Access the upstream `plugin.toml` adds over the checked out one, read
+/// from git before anything is merged.
+fn added_access(dir: &Path, upstream: &str) -> Result<Option<String>, String> {
+    let current = PluginManifest::load(dir)?;
+    let raw = git(dir, &["show", &format!("{upstream}:./plugin.toml")])?;
+    let newer: PluginManifest =
+        toml::from_str(&raw).map_err(|err| format!("upstream plugin.toml: {err}"))?;
+    Ok(current.added_access(&newer))
+}
+
+/// Move a fetched clone onto its upstream, refusing anything but a
+/// fast-forward so local changes are never lost.
+fn fast_forward(dir: &Path, branch: Option<&str>) -> Result<(), String> {
+    if let Some(branch) = branch {
+        git(dir, &["checkout", "--quiet", branch])?;
+    }
+    git(dir, &["merge", "--ff-only", "--quiet", &upstream(branch)])?;
+    Ok(())
+}
+
+/// `0.3.0 (abc1234)`: the manifest version and the checked out commit.
+fn version_at(dir: &Path) -> Result<String, String> {
+    let version = PluginManifest::load(dir)
+        .map(|manifest| manifest.version)
+        .unwrap_or_else(|_| "?".to_string());
+    Ok(format!("{version} ({})", head(dir)?))
+}
+
+fn latest_tag(dir: &Path, rev: &str) -> Option<String> {
+    git(dir, &["describe", "--tags", "--abbrev=0", rev]).ok()
+}
+
+/// Run git in `dir` without ever prompting for credentials, which would
+/// hang behind the TUI.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|err| format!("cannot run git: {err}"))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("failed")
+        .trim();
+    Err(format!("git {}: {reason}", args[0]))
+}
+
+fn head(dir: &Path) -> Result<String, String> {
+    git(dir, &["rev-parse", "--short", "HEAD"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(dir: &Path, version: &str) {
+        commit_asking(dir, version, "");
+    }
+
+    /// Commit a `plugin.toml` with `access` lines such as `permissions`.
+    fn commit_asking(dir: &Path, version: &str, access: &str) {
+        std::fs::write(
+            dir.join("plugin.toml"),
+            format!("name = \"demo\"\nversion = \"{version}\"\nentry = \"demo.wasm\"\n{access}"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("demo.wasm"), b"\0asm").unwrap();
+        for args in [
+            &["add", "-A"][..],
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                version,
+            ],
+        ] {
+            git(dir, args).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sync_clones_then_updates() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let tmp = crate::test_util::temp_dir();
+        let base = tmp.path().to_path_buf();
+        let upstream = base.join("demo");
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        commit(&upstream, "0.1.0");
+
+        let clone = base.join("plugins/demo");
+        let plugin = PluginConfig {
+            repo: Some(upstream.to_string_lossy().into_owned()),
+            path: Some(clone.clone()),
+            branch: Some("main".to_string()),
+            enabled: true,
+            config: HashMap::new(),
+        };
+        let mut seen = Vec::new();
+        let report = PluginInstaller::sync(std::slice::from_ref(&plugin), |line| seen.push(line));
+        assert_eq!(seen.len(), 1);
+        assert!(report.lines[0].contains("cloned at"), "{report:?}");
+        assert!(report.lines[0].ends_with("(demo 0.1.0)"), "{report:?}");
+
+        let report = PluginInstaller::sync(std::slice::from_ref(&plugin), |_| {});
+        assert!(report.lines[0].contains("up to date"), "{report:?}");
+
+        commit(&upstream, "0.2.0");
+        let report = PluginInstaller::sync(std::slice::from_ref(&plugin), |_| {});
+        assert!(report.lines[0].contains("updated"), "{report:?}");
+        assert!(report.lines[0].ends_with("(demo 0.2.0)"), "{report:?}");
+
+        commit(&upstream, "0.3.0");
+        git(&upstream, &["tag", "v0.3.0"]).unwrap();
+        commit(&upstream, "0.3.1");
+        let plugins = std::slice::from_ref(&plugin);
+        let report = PluginInstaller::update(plugins, false, |_| {});
+        assert!(
+            report.lines[0].contains("2 new commits, tag v0.3.0 available (at 0.2.0 ("),
+            "{report:?}"
+        );
+        let report = PluginInstaller::update(plugins, true, |_| {});
+        assert!(
+            report.lines[0].contains("2 new commits, tag v0.3.0: 0.2.0 ("),
+            "{report:?}"
+        );
+        assert!(report.lines[0].contains(") -> 0.3.1 ("), "{report:?}");
+        let report = PluginInstaller::update(plugins, false, |_| {});
+        assert!(
+            report.lines[0].contains("up to date at 0.3.1"),
+            "{report:?}"
+        );
+    }
+
+    #[test]
+    fn test_update_asking_for_more_access_is_held() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let tmp = crate::test_util::temp_dir();
+        let upstream = tmp.path().join("demo");
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        commit_asking(&upstream, "0.1.0", "permissions = [\"read_buffer\"]\n");
+        let plugin = PluginConfig {
+            repo: Some(upstream.to_string_lossy().into_owned()),
+            path: Some(tmp.path().join("plugins/demo")),
+            branch: Some("main".to_string()),
+            enabled: true,
+            config: HashMap::new(),
+        };
+        let plugins = std::slice::from_ref(&plugin);
+        PluginInstaller::sync(plugins, |_| {});
+
+        let network =
+            "permissions = [\"read_buffer\", \"network\"]\nallowed_hosts = [\"api.example.com\"]\n";
+        commit_asking(&upstream, "0.2.0", network);
+        for report in [
+            PluginInstaller::sync(plugins, |_| {}),
+            PluginInstaller::update(plugins, true, |_| {}),
+        ] {
+            assert!(
+                report.lines[0].ends_with("not applied: it asks for network (api.example.com)"),
+                "{report:?}"
+            );
+            assert_eq!(report.held[0].access, "network (api.example.com)");
+        }
+        let report = PluginInstaller::update(plugins, false, |_| {});
+        assert!(report.held.is_empty(), "{report:?}");
+        assert!(
+            report.lines[0].ends_with("asking for network (api.example.com)"),
+            "{report:?}"
+        );
+
+        // Upstream asks for more again before the user agreed.
+        let held = PluginInstaller::update(plugins, true, |_| {})
+            .held
+            .remove(0);
+        let write = "permissions = [\"read_buffer\", \"network\", \"write_vault\"]\nallowed_hosts = [\"api.example.com\"]\n";
+        commit_asking(&upstream, "0.3.0", write);
+        let report = PluginInstaller::accept_update(plugins, &held, |_| {});
+        assert_eq!(
+            report.held[0].access,
+            "write_vault, network (api.example.com)"
+        );
+        assert!(report.lines[0].contains("at 0.1.0"), "{report:?}");
+
+        let report = PluginInstaller::accept_update(plugins, &report.held[0].clone(), |_| {});
+        assert!(report.held.is_empty(), "{report:?}");
+        assert!(report.lines[0].contains("-> 0.3.0"), "{report:?}");
     }
 }
@@ -0,0 +1,268 @@
+//! Lua script plugins: a manifest whose `entry` ends in `.lua` runs in an
+//! embedded Lua 5.4 instead of Extism. The script defines the same exports
+//! as a WASM module, as global functions taking the decoded JSON input, and
+//! reaches the host API through the `blackbox` table.
+//!
+//! Only the `string`, `table`, `math` and `utf8` libraries are loaded, so a
+//! script can touch nothing the host functions do not hand it.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use mlua::{HookTriggers, Lua, LuaOptions, LuaSerdeExt, StdLib, Value, VmState};
+
+use crate::model::config::PluginLimitsConfig;
+use crate::plugin::host_fns::{HostState, HttpRequest};
+use crate::plugin::runtime::{Decoration, PluginEdit};
+
+/// VM instructions between checks of the call's time and fuel.
+const HOOK_INTERVAL: u32 = 1_000;
+
+/// Globals of the base library that read files or load code from outside
+/// the script.
+const UNSAFE_GLOBALS: &[&str] = &["dofile", "loadfile", "load", "collectgarbage"];
+
+pub fn is_script(entry: &Path) -> bool {
+    entry.extension().is_some_and(|ext| ext == "lua")
+}
+
+#[derive(Debug)]
+pub struct LuaPlugin {
+    lua: Lua,
+}
+
+impl LuaPlugin {
+    /// Run the script at `path` once, so it can define its exports, with
+    /// the host API backed by `host`.
+    pub fn load(
+        path: &Path,
+        limits: &PluginLimitsConfig,
+        host: Arc<Mutex<HostState>>,
+    ) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let libs = StdLib::STRING | StdLib::TABLE | StdLib::MATH | StdLib::UTF8;
+        let lua = Lua::new_with(libs, LuaOptions::default()).map_err(|err| err.to_string())?;
+        lua.set_memory_limit(limits.memory_mb as usize * 1024 * 1024)
+            .map_err(|err| err.to_string())?;
+        register_host_api(&lua, host).map_err(|err| err.to_string())?;
+        let plugin = Self { lua };
+        plugin.guarded(limits, || {
+            plugin
+                .lua
+                .load(&source)
+                .set_name(format!("@{}", path.display()))
+                .exec()
+        })?;
+        Ok(plugin)
+    }
+
+    pub fn function_exists(&self, name: &str) -> bool {
+        matches!(
+            self.lua.globals().get::<Value>(name),
+            Ok(Value::Function(_))
+        )
+    }
+
+    /// Call the global function `export` with `input` decoded from JSON.
+    /// A string result is returned as is and a table as JSON, matching
+    /// what a WASM export would output.
+    pub fn call(
+        &self,
+        export: &str,
+        input: &str,
+        limits: &PluginLimitsConfig,
+    ) -> Result<String, String> {
+        let input: serde_json::Value =
+            serde_json::from_str(input).map_err(|err| err.to_string())?;
+        let output = self.guarded(limits, || {
+            let function: mlua::Function = self.lua.globals().get(export)?;
+            function.call::<Value>(to_lua(&self.lua, &input)?)
+        })?;
+        match output {
+            Value::Nil => Ok(String::new()),
+            Value::String(text) => Ok(text.to_string_lossy()),
+            value => {
+                let json: serde_json::Value =
+                    self.lua.from_value(value).map_err(|err| err.to_string())?;
+                Ok(json.to_string())
+            }
+        }
+    }
+
+    /// Run `f` under the call limits, reporting a breach with the same
+    /// causes Extism uses so the runtime treats both engines alike.
+    fn guarded<T>(
+        &self,
+        limits: &PluginLimitsConfig,
+        f: impl FnOnce() -> mlua::Result<T>,
+    ) -> Result<T, String> {
+        let deadline = Instant::now() + Duration::from_millis(limits.timeout_ms);
+        let fuel = limits.fuel;
+        let burned = AtomicU64::new(0);
+        let hook = self.lua.set_hook(
+            HookTriggers::new().every_nth_instruction(HOOK_INTERVAL),
+            move |_, _| {
+                if Instant::now() > deadline {
+                    return Err(mlua::Error::runtime("timeout"));
+                }
+                let used = burned.fetch_add(HOOK_INTERVAL.into(), Ordering::Relaxed);
+                if fuel > 0 && used >= fuel {
+                    return Err(mlua::Error::runtime("plugin ran out of fuel"));
+                }
+                Ok(VmState::Continue)
+            },
+        );
+        let result = hook.and_then(|()| f());
+        self.lua.remove_hook();
+        result.map_err(|err| root_cause(&err))
+    }
+}
+
+/// The `blackbox` table: each host function, with tables in place of JSON.
+fn register_host_api(lua: &Lua, host: Arc<Mutex<HostState>>) -> mlua::Result<()> {
+    let globals = lua.globals();
+    for name in UNSAFE_GLOBALS {
+        globals.set(*name, Value::Nil)?;
+    }
+    let api = lua.create_table()?;
+
+    let state = host.clone();
+    api.set(
+        "read_buffer",
+        lua.create_function(move |lua, ()| {
+            let state = lock(&state)?;
+            let buffer = state.read_buffer().map_err(mlua::Error::runtime)?;
+            let json = serde_json::to_value(buffer).map_err(mlua::Error::external)?;
+            to_lua(lua, &json)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "propose_edit",
+        lua.create_function(move |lua, edits: Value| {
+            // One edit or a list of them.
+            let edits: Vec<PluginEdit> = match lua.from_value::<PluginEdit>(edits.clone()) {
+                Ok(edit) => vec![edit],
+                Err(_) => lua
+                    .from_value(edits)
+                    .map_err(|err| mlua::Error::runtime(format!("propose_edit: {err}")))?,
+            };
+            lock(&state)?
+                .propose_edit(edits)
+                .map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "log",
+        // `log(message)` or `log(level, message)`.
+        lua.create_function(move |_, (first, second): (String, Option<String>)| {
+            let (level, message) = match second {
+                Some(message) => (first, message),
+                None => ("info".to_string(), first),
+            };
+            lock(&state)?
+                .log(&level, &message)
+                .map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "prompt_input",
+        // `prompt_input(question)` or `prompt_input(question, default)`.
+        lua.create_function(move |_, (question, default): (String, Option<String>)| {
+            lock(&state)?
+                .prompt_input(&question, &default.unwrap_or_default())
+                .map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "http_request",
+        lua.create_function(move |lua, request: Value| {
+            let request: HttpRequest = lua
+                .from_value(request)
+                .map_err(|err| mlua::Error::runtime(format!("http_request: {err}")))?;
+            let response = lock(&state)?
+                .http_request(request)
+                .map_err(mlua::Error::runtime)?;
+            let json = serde_json::to_value(response).map_err(mlua::Error::external)?;
+            to_lua(lua, &json)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "list_notes",
+        lua.create_function(move |lua, ()| {
+            let notes = lock(&state)?.list_notes().map_err(mlua::Error::runtime)?;
+            lua.create_sequence_from(notes)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "read_note",
+        lua.create_function(move |_, path: String| {
+            lock(&state)?.read_note(&path).map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    let state = host.clone();
+    api.set(
+        "write_note",
+        lua.create_function(move |_, (path, text): (String, String)| {
+            lock(&state)?
+                .write_note(&path, &text)
+                .map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    let state = host;
+    api.set(
+        "set_decorations",
+        lua.create_function(move |lua, decorations: Value| {
+            let decorations: Vec<Decoration> = lua
+                .from_value(decorations)
+                .map_err(|err| mlua::Error::runtime(format!("set_decorations: {err}")))?;
+            lock(&state)?
+                .set_decorations(decorations)
+                .map_err(mlua::Error::runtime)
+        })?,
+    )?;
+
+    globals.set("blackbox", api)
+}
+
+fn lock(state: &Mutex<HostState>) -> mlua::Result<std::sync::MutexGuard<'_, HostState>> {
+    state
+        .lock()
+        .map_err(|_| mlua::Error::runtime("host state poisoned"))
+}
+
+/// JSON as Lua values, with `null` as `nil` so scripts can test fields with
+/// `if`.
+fn to_lua(lua: &Lua, json: &serde_json::Value) -> mlua::Result<Value> {
+    let options = mlua::serde::SerializeOptions::new()
+        .serialize_none_to_null(false)
+        .serialize_unit_to_null(false);
+    lua.to_value_with(json, options)
+}
+
+/// The innermost message of a Lua error, without the traceback that
+/// callbacks wrap it in.
+fn root_cause(err: &mlua::Error) -> String {
+    match err {
+        mlua::Error::CallbackError { cause, .. } => root_cause(cause),
+        mlua::Error::RuntimeError(message) => {
+            message.trim_start_matches("runtime error: ").to_string()
+        }
+        err => err.to_string(),
+    }
+}
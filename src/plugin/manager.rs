@@ -4,9 +4,14 @@ use std::path::{Path, PathBuf};
 
 use crate::model::config::AppConfig;
 use crate::model::config::PluginConfig;
+use crate::msg::PluginAction;
+use crate::plugin::host_fns::BufferView;
 use crate::plugin::manifest::PluginId;
 use crate::plugin::runtime::PluginRuntime;
 use crate::plugin::runtime::PluginStatus;
+use crate::plugin::runtime::{
+    CommandContext, CommandOutput, CompletionContext, CompletionItem, DecorateContext,
+};
 
 #[derive(Debug, Default)]
 pub struct PluginManager {
@@ -16,6 +21,11 @@ pub struct PluginManager {
 impl PluginManager {
     pub fn new(config: &AppConfig) -> Self {
         let mut manager = Self::default();
+        // `--safe` runs no plugin code, whatever was added to the config
+        // since startup.
+        if config.safe_mode {
+            return manager;
+        }
 
         for plugin in &config.plugins {
             if !plugin.enabled {
@@ -30,7 +40,14 @@ impl PluginManager {
             manager
                 .runtimes
                 .entry(plugin_id.clone())
-                .or_insert_with(|| PluginRuntime::discover(plugin_id, root_dir));
+                .or_insert_with(|| {
+                    PluginRuntime::discover(
+                        plugin_id,
+                        root_dir,
+                        config.plugin_limits.clone(),
+                        config.vault_path(),
+                    )
+                });
         }
 
         manager
@@ -97,8 +114,13 @@ impl PluginManager {
                     PluginStatus::Error(err) => format!("error: {err}"),
                 };
 
+                let api = runtime
+                    .manifest
+                    .as_ref()
+                    .map(|manifest| format!(" api {}", manifest.api_version))
+                    .unwrap_or_default();
                 format!(
-                    "plugin {} [{status}] ({})",
+                    "plugin {}{api} [{status}] ({})",
                     runtime.display_name(),
                     runtime.root_dir.display()
                 )
@@ -109,12 +131,21 @@ impl PluginManager {
         rows
     }
 
+    /// A help line per plugin command: its usage and description.
     pub fn command_notifications(&self) -> Vec<String> {
         let mut commands: Vec<String> = self
             .runtimes
             .values()
             .filter_map(|runtime| runtime.manifest.as_ref())
-            .flat_map(|manifest| manifest.commands.iter().map(|command| command.name.clone()))
+            .flat_map(|manifest| {
+                manifest
+                    .commands
+                    .iter()
+                    .map(|command| match &command.description {
+                        Some(description) => format!("  {} ({description})", command.usage()),
+                        None => format!("  {}", command.usage()),
+                    })
+            })
             .collect::<HashSet<_>>()
             .into_iter()
             .collect();
@@ -124,33 +155,290 @@ impl PluginManager {
         }
 
         commands.sort();
-        vec![format!("plugin commands: {}", commands.join(", "))]
+        commands.insert(0, "plugin commands:".to_string());
+        commands
+    }
+
+    pub fn supports_command(&self, command: &str) -> bool {
+        self.runtimes
+            .values()
+            .any(|runtime| runtime.supports_command(command))
+            || self.qualified(command).is_some()
     }
 
-    pub fn execute_command(&mut self, command: &str) -> Vec<String> {
-        let mut matches = self
+    /// Every plugin command as the command line takes it, with its
+    /// description: `plugin.command` when more than one plugin declares
+    /// the name, so each stays reachable.
+    pub fn command_palette(&self) -> Vec<(String, Option<String>)> {
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for manifest in self.runtimes.values().filter_map(|r| r.manifest.as_ref()) {
+            for command in &manifest.commands {
+                *owners.entry(command.name.as_str()).or_default() += 1;
+            }
+        }
+        let mut entries: Vec<(String, Option<String>)> = self
             .runtimes
-            .iter_mut()
-            .filter(|(_, runtime)| runtime.supports_command(command));
+            .values()
+            .filter_map(|runtime| runtime.manifest.as_ref())
+            .flat_map(|manifest| {
+                let owners = &owners;
+                manifest.commands.iter().map(move |command| {
+                    let name = if owners[command.name.as_str()] > 1 {
+                        format!("{}.{}", manifest.name, command.name)
+                    } else {
+                        command.name.clone()
+                    };
+                    (name, command.description.clone())
+                })
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
 
-        let Some((_, runtime)) = matches.next() else {
-            return vec![format!("plugin command not found: {command}")];
-        };
+    /// Every completion trigger a plugin declares, longest first so `@@`
+    /// is tried before `@`.
+    pub fn completion_triggers(&self) -> Vec<String> {
+        let mut triggers: Vec<String> = self
+            .runtimes
+            .values()
+            .filter_map(|runtime| runtime.manifest.as_ref())
+            .flat_map(|manifest| manifest.completions.iter())
+            .map(|completion| completion.trigger.clone())
+            .filter(|trigger| !trigger.is_empty())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        triggers.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        triggers
+    }
 
-        if matches.next().is_some() {
-            return vec![format!(
-                "plugin command is ambiguous: {command} (multiple plugins)"
-            )];
+    /// Completions from every plugin registered for `context.trigger`, with
+    /// a line per plugin that failed.
+    pub fn complete(
+        &mut self,
+        context: &CompletionContext,
+        buffer: &BufferView,
+    ) -> (Vec<CompletionItem>, Vec<String>) {
+        let mut items = Vec::new();
+        let mut errors = Vec::new();
+        let mut runtimes: Vec<&mut PluginRuntime> = self
+            .runtimes
+            .values_mut()
+            .filter(|runtime| runtime.completes(&context.trigger))
+            .collect();
+        runtimes.sort_by_key(|runtime| runtime.display_name());
+        for runtime in runtimes {
+            match runtime.complete(context, buffer.clone()) {
+                Ok(found) => items.extend(found),
+                Err(err) => errors.push(format!("plugin {}: {err}", runtime.display_name())),
+            }
+        }
+        (items, errors)
+    }
+
+    /// Whether any plugin may decorate buffers.
+    pub fn decorates(&self) -> bool {
+        self.runtimes.values().any(PluginRuntime::decorates)
+    }
+
+    /// Let every plugin with the `decorate` permission mark up the active
+    /// buffer after `context.event`. Plugins that have failed are skipped,
+    /// so a broken one does not report again on every save.
+    pub fn decorate(
+        &mut self,
+        context: &DecorateContext,
+        buffer: &BufferView,
+    ) -> Vec<PluginAction> {
+        let mut actions = Vec::new();
+        for runtime in self
+            .runtimes
+            .values_mut()
+            .filter(|runtime| runtime.decorates())
+        {
+            match runtime.decorate(context, buffer.clone()) {
+                Ok(Some(decorations)) => {
+                    actions.push(PluginAction::Decorate(runtime.id.clone(), decorations));
+                }
+                Ok(None) => {}
+                Err(err) => actions.push(PluginAction::Notify(format!(
+                    "plugin {}: {err}",
+                    runtime.display_name()
+                ))),
+            }
+        }
+        actions
+    }
+
+    /// The plugin and command named by `plugin.command`.
+    fn qualified(&self, command: &str) -> Option<(&PluginId, String)> {
+        let (plugin, name) = command.split_once('.')?;
+        self.runtimes.iter().find_map(|(id, runtime)| {
+            (runtime.manifest.as_ref()?.name == plugin && runtime.supports_command(name))
+                .then(|| (id, name.to_string()))
+        })
+    }
+
+    /// The display name and recent log lines of the plugin called `name`
+    /// in its manifest or by its directory.
+    pub fn logs(&self, name: &str) -> Option<(String, Vec<String>)> {
+        let runtime = self.runtimes.values().find(|runtime| runtime.named(name))?;
+        Some((runtime.display_name(), runtime.logs()))
+    }
+
+    /// Turn the plugin called `name` back on after it was disabled for
+    /// failing, reloading it from disk. Returns a line on how that went.
+    pub fn enable(&mut self, name: &str) -> Result<String, String> {
+        let runtime = self
+            .runtimes
+            .values_mut()
+            .find(|runtime| runtime.named(name))
+            .ok_or_else(|| format!("no plugin named {name}"))?;
+        let name = runtime.display_name();
+        if !matches!(runtime.status(), PluginStatus::Error(_)) {
+            return Ok(format!("plugin {name} is already enabled"));
+        }
+        runtime.reload();
+        match runtime.status() {
+            PluginStatus::Error(err) => Err(format!("{name}: {err}")),
+            _ => Ok(format!("plugin {name}: enabled")),
         }
+    }
 
-        match runtime.execute_command(command) {
-            Ok(Some(message)) => vec![message],
-            Ok(None) => vec![format!("plugin command not found: {command}")],
-            Err(err) => vec![format!("plugin {}: {err}", runtime.display_name())],
+    /// `(plugin, command)` for every command a loaded plugin declares.
+    pub fn commands(&self) -> Vec<(String, String)> {
+        self.runtimes
+            .values()
+            .filter_map(|runtime| runtime.manifest.as_ref())
+            .flat_map(|manifest| {
+                manifest
+                    .commands
+                    .iter()
+                    .map(|command| (manifest.name.clone(), command.name.clone()))
+            })
+            .collect()
+    }
+
+    /// Every plugin's display name, sorted.
+    pub fn plugin_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .runtimes
+            .values()
+            .map(PluginRuntime::display_name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Every plugin directory, for the hot-reload watcher.
+    pub fn plugin_dirs(&self) -> Vec<PathBuf> {
+        self.runtimes
+            .values()
+            .map(|runtime| runtime.root_dir.clone())
+            .collect()
+    }
+
+    /// Reload the plugins in `roots` after their files changed, returning a
+    /// line per plugin on how the reload went.
+    pub fn reload_dirs(&mut self, roots: &[PathBuf]) -> Vec<String> {
+        let mut notices: Vec<String> = self
+            .runtimes
+            .values_mut()
+            .filter(|runtime| roots.contains(&runtime.root_dir))
+            .map(|runtime| {
+                runtime.reload();
+                match runtime.status() {
+                    PluginStatus::Error(err) => {
+                        format!("plugin {}: reload failed: {err}", runtime.display_name())
+                    }
+                    _ => format!("plugin {}: reloaded", runtime.display_name()),
+                }
+            })
+            .collect();
+        notices.sort();
+        notices
+    }
+
+    /// Run `context.command` in the one plugin that declares it and turn
+    /// what it returns into actions for the app.
+    pub fn execute_command(
+        &mut self,
+        context: &CommandContext,
+        buffer: BufferView,
+    ) -> Vec<PluginAction> {
+        let command = context.command.as_str();
+        let mut owners: Vec<&PluginRuntime> = self
+            .runtimes
+            .values()
+            .filter(|runtime| runtime.supports_command(command))
+            .collect();
+        let (id, context) = match owners.len() {
+            1 => (owners[0].id.clone(), context.clone()),
+            0 => match self.qualified(command) {
+                Some((id, name)) => (
+                    id.clone(),
+                    CommandContext {
+                        command: name,
+                        ..context.clone()
+                    },
+                ),
+                None => {
+                    return vec![PluginAction::Notify(format!(
+                        "plugin command not found: {command}"
+                    ))];
+                }
+            },
+            _ => {
+                owners.sort_by_key(|runtime| runtime.display_name());
+                let choices: Vec<String> = owners
+                    .iter()
+                    .map(|runtime| format!("{}.{command}", runtime.display_name()))
+                    .collect();
+                return vec![PluginAction::Notify(format!(
+                    "plugin command is ambiguous: {command} (use {})",
+                    choices.join(" or ")
+                ))];
+            }
+        };
+        let Some(runtime) = self.runtimes.get_mut(&id) else {
+            return Vec::new();
+        };
+
+        match runtime.execute_command(&context, buffer) {
+            Ok(Some(CommandOutput {
+                prompt: Some(prompt),
+                ..
+            })) => vec![PluginAction::Prompt(prompt)],
+            Ok(Some(CommandOutput {
+                notify,
+                edits,
+                decorations,
+                prompt: None,
+            })) => {
+                let mut actions = Vec::new();
+                if !edits.is_empty() {
+                    actions.push(PluginAction::Edit(edits));
+                }
+                actions.extend(decorations.map(|marks| PluginAction::Decorate(id.clone(), marks)));
+                actions.extend(notify.map(PluginAction::Notify));
+                actions
+            }
+            Ok(None) => vec![PluginAction::Notify(format!(
+                "plugin command not found: {command}"
+            ))],
+            Err(err) => vec![PluginAction::Notify(format!(
+                "plugin {}: {err}",
+                runtime.display_name()
+            ))],
         }
     }
 
-    fn resolve_plugin_root(plugin: &PluginConfig) -> Option<PathBuf> {
+    /// Where `repo` plugins are cloned and `:plugins.new` writes to.
+    pub(crate) fn base_dir() -> PathBuf {
+        default_plugin_base_dir()
+    }
+
+    pub(crate) fn resolve_plugin_root(plugin: &PluginConfig) -> Option<PathBuf> {
         if let Some(path) = plugin.path.as_ref() {
             return Some(expand_tilde(path));
         }
@@ -214,6 +502,8 @@ fn expand_tilde(path: &std::path::Path) -> PathBuf {
 mod tests {
     use super::*;
     use crate::model::config::AppConfig;
+    #[cfg(feature = "plugins")]
+    use crate::plugin::manifest::PluginManifest;
 
     fn config_with_no_plugins() -> AppConfig {
         let defaults = include_str!("../../config/default.toml");
@@ -234,15 +524,1021 @@ mod tests {
     fn test_execute_unknown_command_returns_not_found() {
         let config = config_with_no_plugins();
         let mut manager = PluginManager::new(&config);
-        let result = manager.execute_command("nonexistent:command");
+        let context = CommandContext {
+            command: "nonexistent:command".to_string(),
+            ..CommandContext::default()
+        };
+        let result = manager.execute_command(&context, BufferView::default());
         assert_eq!(result.len(), 1);
+        let PluginAction::Notify(message) = &result[0] else {
+            panic!("expected a notification, got {:?}", result[0]);
+        };
+        assert!(
+            message.contains("not found"),
+            "expected 'not found' in: {message}"
+        );
+    }
+
+    /// A plugin in `dir` with `manifest` as its `plugin.toml` and `wat` as
+    /// its entry; wasmtime accepts the text format directly.
+    #[cfg(feature = "plugins")]
+    fn wat_plugin(dir: &Path, manifest: &str, wat: &str) -> AppConfig {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("plugin.toml"),
+            format!("name = \"test\"\nversion = \"0.1.0\"\nentry = \"plugin.wat\"\n{manifest}"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("plugin.wat"), wat).unwrap();
+
+        let mut config = config_with_no_plugins();
+        config.plugins.push(PluginConfig {
+            repo: None,
+            path: Some(dir.to_path_buf()),
+            branch: None,
+            enabled: true,
+            config: HashMap::new(),
+        });
+        config
+    }
+
+    /// Like [`wat_plugin`], with `script` as an `init.lua` entry.
+    #[cfg(feature = "plugins")]
+    fn lua_plugin(dir: &Path, manifest: &str, script: &str) -> AppConfig {
+        let config = wat_plugin(dir, manifest, "");
+        let toml = std::fs::read_to_string(dir.join("plugin.toml")).unwrap();
+        std::fs::write(
+            dir.join("plugin.toml"),
+            toml.replace("plugin.wat", "init.lua"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("init.lua"), script).unwrap();
+        config
+    }
+
+    /// `on_command` copying `data` into Extism memory at `$offs` and then
+    /// running `sink`, which imports what it calls.
+    #[cfg(feature = "plugins")]
+    fn constant_data_wat(data: &str, import: &str, sink: &str) -> String {
+        let len = data.len();
+        let data = data.replace('"', "\\\"");
+        format!(
+            r#"(module
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  {import}
+  (memory 1)
+  (data (i32.const 0) "{data}")
+  (func (export "on_command") (result i32)
+    (local $offs i64) (local $i i32)
+    (local.set $offs (call $alloc (i64.const {len})))
+    (block $done
+      (loop $copy
+        (br_if $done (i32.ge_u (local.get $i) (i32.const {len})))
+        (call $store_u8
+          (i64.add (local.get $offs) (i64.extend_i32_u (local.get $i)))
+          (i32.load8_u (local.get $i)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $copy)))
+    {sink}
+    (i32.const 0)))"#
+        )
+    }
+
+    /// `on_command` returning `response` whatever it is sent.
+    #[cfg(feature = "plugins")]
+    fn fixed_response_wat(response: &str) -> String {
+        constant_data_wat(
+            response,
+            r#"(import "extism:host/env" "output_set" (func $output_set (param i64 i64)))"#,
+            &format!(
+                "(call $output_set (local.get $offs) (i64.const {}))",
+                response.len()
+            ),
+        )
+    }
+
+    /// `on_command` returning `#` and what `read_buffer` gave it, so the
+    /// app shows it as plain text.
+    #[cfg(feature = "plugins")]
+    const READ_BUFFER_WAT: &str = r#"(module
+  (import "extism:host/user" "read_buffer" (func $read_buffer (result i64)))
+  (import "extism:host/env" "length" (func $length (param i64) (result i64)))
+  (import "extism:host/env" "load_u8" (func $load_u8 (param i64) (result i32)))
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (func (export "on_command") (result i32)
+    (local $buf i64) (local $len i64) (local $out i64) (local $i i64)
+    (local.set $buf (call $read_buffer))
+    (local.set $len (call $length (local.get $buf)))
+    (local.set $out (call $alloc (i64.add (local.get $len) (i64.const 1))))
+    (call $store_u8 (local.get $out) (i32.const 35))
+    (block $done
+      (loop $copy
+        (br_if $done (i64.ge_u (local.get $i) (local.get $len)))
+        (call $store_u8
+          (i64.add (local.get $out) (i64.add (local.get $i) (i64.const 1)))
+          (call $load_u8 (i64.add (local.get $buf) (local.get $i))))
+        (local.set $i (i64.add (local.get $i) (i64.const 1)))
+        (br $copy)))
+    (call $output_set (local.get $out) (i64.add (local.get $len) (i64.const 1)))
+    (i32.const 0)))"#;
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_execute_command_runs_wasm_entry() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let response = r#"{"notify":"stamped","edit":{"start":{"line":1,"column":0},"end":{"line":1,"column":2},"text":"ok"}}"#;
+        let config = wat_plugin(
+            &dir,
+            "[[commands]]\nname = \"stamp\"\n",
+            &fixed_response_wat(response),
+        );
+        let mut manager = PluginManager::new(&config);
+        assert!(manager.supports_command("stamp"));
+
+        let context = CommandContext {
+            command: "stamp".to_string(),
+            args: "now".to_string(),
+            ..CommandContext::default()
+        };
+        let actions = manager.execute_command(&context, BufferView::default());
+        assert_eq!(actions.len(), 2, "{actions:?}");
+        let PluginAction::Edit(edits) = &actions[0] else {
+            panic!("expected an edit, got {:?}", actions[0]);
+        };
+        let [edit] = edits.as_slice() else {
+            panic!("expected one edit, got {edits:?}");
+        };
+        assert_eq!((edit.start.line, edit.end.column), (1, 2));
+        assert_eq!(edit.text, "ok");
+        assert!(matches!(&actions[1], PluginAction::Notify(message) if message == "stamped"));
+        assert!(manager.list_notifications()[0].contains("[loaded]"));
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_command_args_reach_plugin_and_help() {
+        // Sends back `#` and the JSON it was called with, so it is plain text.
+        let wat = r#"(module
+  (import "extism:host/env" "input_length" (func $input_length (result i64)))
+  (import "extism:host/env" "input_load_u8" (func $input_load_u8 (param i64) (result i32)))
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (func (export "on_command") (result i32)
+    (local $len i64) (local $out i64) (local $i i64)
+    (local.set $len (call $input_length))
+    (local.set $out (call $alloc (i64.add (local.get $len) (i64.const 1))))
+    (call $store_u8 (local.get $out) (i32.const 35))
+    (block $done
+      (loop $copy
+        (br_if $done (i64.ge_u (local.get $i) (local.get $len)))
+        (call $store_u8
+          (i64.add (local.get $out) (i64.add (local.get $i) (i64.const 1)))
+          (call $input_load_u8 (local.get $i)))
+        (local.set $i (i64.add (local.get $i) (i64.const 1)))
+        (br $copy)))
+    (call $output_set (local.get $out) (i64.add (local.get $len) (i64.const 1)))
+    (i32.const 0)))"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let config = wat_plugin(
+            &dir,
+            "[[commands]]\nname = \"echo\"\ndescription = \"say it back\"\nargs = [\"text\", \"[times]\"]\n",
+            wat,
+        );
+        let mut manager = PluginManager::new(&config);
+        assert_eq!(
+            manager.command_notifications(),
+            vec!["plugin commands:", "  echo <text> [times] (say it back)"]
+        );
+
+        let context = CommandContext {
+            command: "echo".to_string(),
+            args: "'a b' 2".to_string(),
+            argv: vec!["a b".to_string(), "2".to_string()],
+            ..CommandContext::default()
+        };
+        let actions = manager.execute_command(&context, BufferView::default());
+        let [PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected one notification, got {actions:?}");
+        };
+        let sent: serde_json::Value = serde_json::from_str(&message[1..]).unwrap();
+        assert_eq!(sent["args"], "'a b' 2");
+        assert_eq!(sent["argv"], serde_json::json!(["a b", "2"]));
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_colliding_commands_are_qualified() {
+        let tmp = crate::test_util::temp_dir();
+        let base = tmp.path().to_path_buf();
+        let mut config = config_with_no_plugins();
+        for (name, commands) in [
+            (
+                "alpha",
+                "[[commands]]\nname = \"count\"\n[[commands]]\nname = \"solo\"\ndescription = \"only here\"\n",
+            ),
+            ("beta", "[[commands]]\nname = \"count\"\n"),
+        ] {
+            let dir = base.join(name);
+            config
+                .plugins
+                .extend(wat_plugin(&dir, commands, &fixed_response_wat(name)).plugins);
+            let manifest = std::fs::read_to_string(dir.join("plugin.toml")).unwrap();
+            std::fs::write(
+                dir.join("plugin.toml"),
+                manifest.replace("name = \"test\"", &format!("name = \"{name}\"")),
+            )
+            .unwrap();
+        }
+        let mut manager = PluginManager::new(&config);
+        assert_eq!(
+            manager.command_palette(),
+            vec![
+                ("alpha.count".to_string(), None),
+                ("beta.count".to_string(), None),
+                ("solo".to_string(), Some("only here".to_string())),
+            ]
+        );
+        assert!(manager.supports_command("beta.count"));
+        assert!(!manager.supports_command("beta.solo"));
+
+        let run = |manager: &mut PluginManager, command: &str| {
+            let context = CommandContext {
+                command: command.to_string(),
+                ..CommandContext::default()
+            };
+            match manager
+                .execute_command(&context, BufferView::default())
+                .as_slice()
+            {
+                [PluginAction::Notify(message)] => message.clone(),
+                actions => panic!("expected one notification, got {actions:?}"),
+            }
+        };
+        assert_eq!(
+            run(&mut manager, "count"),
+            "plugin command is ambiguous: count (use alpha.count or beta.count)"
+        );
+        assert_eq!(run(&mut manager, "beta.count"), "beta");
+        assert_eq!(run(&mut manager, "alpha.solo"), "alpha");
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_lua_plugin_uses_host_api() {
+        let script = r#"
+function on_command(ctx)
+  local buffer = blackbox.read_buffer()
+  blackbox.propose_edit({
+    start = { line = 0, column = 0 },
+    ["end"] = { line = 0, column = 0 },
+    text = ctx.argv[1] .. " ",
+  })
+  return { notify = buffer.text .. " " .. tostring(buffer.selection) .. " " .. tostring(io) }
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest =
+            "permissions = [\"read_buffer\", \"propose_edit\"]\n[[commands]]\nname = \"tag\"\n";
+        let mut manager = PluginManager::new(&lua_plugin(&dir, manifest, script));
+        let context = CommandContext {
+            command: "tag".to_string(),
+            argv: vec!["#lua".to_string()],
+            ..CommandContext::default()
+        };
+        let buffer = BufferView {
+            text: "hello".to_string(),
+            ..BufferView::default()
+        };
+        let actions = manager.execute_command(&context, buffer);
+        let [PluginAction::Edit(edits), PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected an edit and a notification, got {actions:?}");
+        };
+        assert_eq!(edits[0].text, "#lua ");
+        assert_eq!(message, "hello nil nil");
+
+        std::fs::write(
+            dir.join("plugin.toml"),
+            "name = \"test\"\nversion = \"0.1.0\"\nentry = \"init.lua\"\n[[commands]]\nname = \"tag\"\n",
+        )
+        .unwrap();
+        manager.reload_dirs(std::slice::from_ref(&dir));
+        let actions = manager.execute_command(&context, BufferView::default());
+        assert!(
+            matches!(&actions[0], PluginAction::Notify(message) if message.ends_with("read_buffer needs the read_buffer permission")),
+            "{actions:?}"
+        );
+
+        let mut config = lua_plugin(
+            &dir,
+            "[[commands]]\nname = \"tag\"\n",
+            "function on_command() while true do end end",
+        );
+        config.plugin_limits.timeout_ms = 50;
+        let mut manager = PluginManager::new(&config);
+        let actions = manager.execute_command(&context, BufferView::default());
+        assert!(
+            matches!(&actions[0], PluginAction::Notify(message) if message.contains("on_command timed out after 50 ms")),
+            "{actions:?}"
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_completions_for_trigger() {
+        let script = r#"
+function on_complete(ctx)
+  return { items = {
+    { label = "doe2020", detail = "Doe (2020) " .. ctx.partial },
+    { label = "roe", insert = "roe:2019" },
+  } }
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest = "[[completions]]\ntrigger = \"@\"\n[[completions]]\ntrigger = \"@@\"\n";
+        let mut manager = PluginManager::new(&lua_plugin(&dir, manifest, script));
+        assert_eq!(manager.completion_triggers(), vec!["@@", "@"]);
+
+        let context = CompletionContext {
+            trigger: "@".to_string(),
+            partial: "do".to_string(),
+            ..CompletionContext::default()
+        };
+        let (items, errors) = manager.complete(&context, &BufferView::default());
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].detail.as_deref(), Some("Doe (2020) do"));
+        assert_eq!(items[1].insert.as_deref(), Some("roe:2019"));
+
+        let other = CompletionContext {
+            trigger: "#".to_string(),
+            ..CompletionContext::default()
+        };
+        assert!(
+            manager
+                .complete(&other, &BufferView::default())
+                .0
+                .is_empty()
+        );
+
+        std::fs::write(
+            dir.join("init.lua"),
+            "function on_complete() return 'nope' end",
+        )
+        .unwrap();
+        manager.reload_dirs(std::slice::from_ref(&dir));
+        let (items, errors) = manager.complete(&context, &BufferView::default());
+        assert!(items.is_empty());
+        assert!(errors[0].contains("invalid completions"), "{errors:?}");
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_decorations_replace_per_call() {
+        use crate::plugin::runtime::DecorationKind;
+
+        let script = r#"
+function on_decorate(ctx)
+  blackbox.set_decorations({
+    { line = 0, start = 2, ["end"] = 5, kind = "error", gutter = "!", text = ctx.event },
+    { line = 1, text = "ok" },
+  })
+end
+
+function on_command()
+  blackbox.set_decorations({})
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest = "permissions = [\"decorate\"]\n[[commands]]\nname = \"clear\"\n";
+        let mut manager = PluginManager::new(&lua_plugin(&dir, manifest, script));
+        assert!(manager.decorates());
+
+        let context = DecorateContext {
+            event: "save".to_string(),
+            ..DecorateContext::default()
+        };
+        let actions = manager.decorate(&context, &BufferView::default());
+        let [PluginAction::Decorate(_, marks)] = actions.as_slice() else {
+            panic!("expected decorations, got {actions:?}");
+        };
+        assert_eq!(marks.len(), 2);
+        assert_eq!(marks[0].kind, DecorationKind::Error);
+        assert_eq!((marks[0].start, marks[0].end), (Some(2), Some(5)));
+        assert_eq!(marks[0].text.as_deref(), Some("save"));
+        assert_eq!(marks[1].kind, DecorationKind::Info);
+        assert_eq!(marks[1].start, None);
+
+        let command = CommandContext {
+            command: "clear".to_string(),
+            ..CommandContext::default()
+        };
+        let actions = manager.execute_command(&command, BufferView::default());
+        assert!(
+            matches!(actions.as_slice(), [PluginAction::Decorate(_, marks)] if marks.is_empty()),
+            "{actions:?}"
+        );
+
+        std::fs::write(
+            dir.join("plugin.toml"),
+            "name = \"test\"\nversion = \"0.1.0\"\nentry = \"init.lua\"\n[[commands]]\nname = \"clear\"\n",
+        )
+        .unwrap();
+        manager.reload_dirs(std::slice::from_ref(&dir));
+        assert!(!manager.decorates());
+        let actions = manager.execute_command(&command, BufferView::default());
+        assert!(
+            matches!(&actions[0], PluginAction::Notify(message) if message.ends_with("set_decorations needs the decorate permission")),
+            "{actions:?}"
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_reload_dirs_picks_up_rebuilt_plugin() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let config = wat_plugin(
+            &dir,
+            "[[commands]]\nname = \"greet\"\n",
+            &fixed_response_wat("v1"),
+        );
+        let mut manager = PluginManager::new(&config);
+        let context = CommandContext {
+            command: "greet".to_string(),
+            ..CommandContext::default()
+        };
+        let notify = |actions: Vec<PluginAction>| match actions.as_slice() {
+            [PluginAction::Notify(message)] => message.clone(),
+            _ => panic!("expected one notification, got {actions:?}"),
+        };
+        assert_eq!(
+            notify(manager.execute_command(&context, BufferView::default())),
+            "v1"
+        );
+        assert_eq!(manager.plugin_dirs(), vec![dir.clone()]);
+
+        std::fs::write(dir.join("plugin.wat"), fixed_response_wat("v2")).unwrap();
+        assert!(manager.reload_dirs(&[dir.join("elsewhere")]).is_empty());
+        assert_eq!(
+            notify(manager.execute_command(&context, BufferView::default())),
+            "v1"
+        );
+        assert_eq!(
+            manager.reload_dirs(std::slice::from_ref(&dir)),
+            vec!["plugin test: reloaded"]
+        );
+        assert_eq!(
+            notify(manager.execute_command(&context, BufferView::default())),
+            "v2"
+        );
+
+        std::fs::write(dir.join("plugin.toml"), "name = ").unwrap();
+        assert!(manager.reload_dirs(std::slice::from_ref(&dir))[0].contains("reload failed"));
+        assert_eq!(manager.error_count(), 1);
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_read_buffer_needs_permission() {
+        let buffer = || BufferView {
+            text: "hello".to_string(),
+            path: Some(PathBuf::from("/vault/note.md")),
+            ..BufferView::default()
+        };
+        let context = CommandContext {
+            command: "peek".to_string(),
+            ..CommandContext::default()
+        };
+        let command = "[[commands]]\nname = \"peek\"\n";
+
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let granted = format!("permissions = [\"read_buffer\"]\n{command}");
+        let mut manager = PluginManager::new(&wat_plugin(&dir, &granted, READ_BUFFER_WAT));
+        let actions = manager.execute_command(&context, buffer());
+        let [PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected one notification, got {actions:?}");
+        };
+        assert!(message.starts_with("#{\"text\":\"hello\""), "{message}");
+        assert!(message.contains("\"path\":\"/vault/note.md\""), "{message}");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut manager = PluginManager::new(&wat_plugin(&dir, command, READ_BUFFER_WAT));
+        let actions = manager.execute_command(&context, buffer());
+        let [PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected one notification, got {actions:?}");
+        };
+        assert!(
+            message.contains("read_buffer needs the read_buffer permission"),
+            "{message}"
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_propose_edit_collects_edits() {
+        let edits = r#"[{"start":{"line":0,"column":0},"end":{"line":0,"column":3},"text":"uno"},{"start":{"line":1,"column":0},"end":{"line":1,"column":0},"text":"- "}]"#;
+        let wat = constant_data_wat(
+            edits,
+            r#"(import "extism:host/user" "propose_edit" (func $propose_edit (param i64)))"#,
+            "(call $propose_edit (local.get $offs))",
+        );
+        let context = CommandContext {
+            command: "fix".to_string(),
+            ..CommandContext::default()
+        };
+        let command = "[[commands]]\nname = \"fix\"\n";
+
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let granted = format!("permissions = [\"propose_edit\"]\n{command}");
+        let mut manager = PluginManager::new(&wat_plugin(&dir, &granted, &wat));
+        let actions = manager.execute_command(&context, BufferView::default());
+        let [PluginAction::Edit(proposed)] = actions.as_slice() else {
+            panic!("expected one edit action, got {actions:?}");
+        };
+        let texts: Vec<&str> = proposed.iter().map(|edit| edit.text.as_str()).collect();
+        assert_eq!(texts, ["uno", "- "]);
+        assert_eq!(proposed[0].end.column, 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut manager = PluginManager::new(&wat_plugin(&dir, command, &wat));
+        let actions = manager.execute_command(&context, BufferView::default());
+        let [PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected one notification, got {actions:?}");
+        };
+        assert!(
+            message.contains("propose_edit needs the propose_edit permission"),
+            "{message}"
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_runaway_call_is_cancelled_and_disabled() {
+        let wat = r#"(module
+  (func (export "on_command") (result i32)
+    (loop $spin (br $spin))
+    (i32.const 0)))"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let mut config = wat_plugin(&dir, "[[commands]]\nname = \"spin\"\n", wat);
+        config.plugin_limits.timeout_ms = 50;
+        let mut manager = PluginManager::new(&config);
+        let context = CommandContext {
+            command: "spin".to_string(),
+            ..CommandContext::default()
+        };
+
+        let actions = manager.execute_command(&context, BufferView::default());
+        let [PluginAction::Notify(message)] = actions.as_slice() else {
+            panic!("expected one notification, got {actions:?}");
+        };
+        assert!(
+            message.contains("on_command timed out after 50 ms"),
+            "{message}"
+        );
+        assert_eq!(manager.error_count(), 1);
+
+        // Refused without running again until the plugin is reloaded.
+        let started = std::time::Instant::now();
+        let actions = manager.execute_command(&context, BufferView::default());
+        assert!(started.elapsed() < std::time::Duration::from_millis(50));
+        assert!(matches!(&actions[0], PluginAction::Notify(again) if again == message));
+
+        config.plugin_limits.timeout_ms = 10_000;
+        config.plugin_limits.fuel = 100_000;
+        let mut manager = PluginManager::new(&config);
+        let actions = manager.execute_command(&context, BufferView::default());
         assert!(
-            result[0].contains("not found"),
-            "expected 'not found' in: {}",
-            result[0]
+            matches!(&actions[0], PluginAction::Notify(message) if message.contains("ran out of fuel (100000)")),
+            "{actions:?}"
         );
     }
 
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_repeated_failures_disable_until_enabled() {
+        let script = r#"
+function on_command(ctx)
+  if ctx.command == "boom" then error("boom") end
+  return "fine"
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest = "[[commands]]\nname = \"boom\"\n[[commands]]\nname = \"ok\"\n";
+        let mut config = lua_plugin(&dir, manifest, script);
+        config.plugin_limits.max_failures = 2;
+        let mut manager = PluginManager::new(&config);
+        let run = |manager: &mut PluginManager, command: &str| {
+            let context = CommandContext {
+                command: command.to_string(),
+                ..CommandContext::default()
+            };
+            match manager
+                .execute_command(&context, BufferView::default())
+                .as_slice()
+            {
+                [PluginAction::Notify(message)] => message.clone(),
+                actions => panic!("expected one notification, got {actions:?}"),
+            }
+        };
+
+        let first = run(&mut manager, "boom");
+        assert!(!first.contains("disabled"), "{first}");
+        assert_eq!(manager.error_count(), 0);
+        let second = run(&mut manager, "boom");
+        assert!(
+            second.ends_with("2 failures in 60s; disabled until :plugins.enable test"),
+            "{second}"
+        );
+        assert_eq!(manager.error_count(), 1);
+        // Other commands are refused too, without running the script.
+        assert_eq!(run(&mut manager, "ok"), second);
+        assert_eq!(manager.logs("test").unwrap().1.len(), 2);
+
+        assert_eq!(manager.enable("test").unwrap(), "plugin test: enabled");
+        assert_eq!(run(&mut manager, "ok"), "fine");
+        assert!(!run(&mut manager, "boom").contains("disabled"));
+        assert_eq!(
+            manager.enable("test").unwrap(),
+            "plugin test is already enabled"
+        );
+        assert_eq!(
+            manager.enable("missing").unwrap_err(),
+            "no plugin named missing"
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_safe_mode_loads_nothing() {
+        let dir = crate::test_util::temp_dir();
+        let script = "function on_command(ctx) return \"ran\" end";
+        let mut config = lua_plugin(dir.path(), "[[commands]]\nname = \"run\"\n", script);
+        assert_eq!(PluginManager::new(&config).plugin_dirs().len(), 1);
+
+        // A plugin added after `--safe` cleared the list stays unloaded.
+        config.safe_mode = true;
+        let mut manager = PluginManager::new(&config);
+        assert!(manager.plugin_dirs().is_empty());
+        let context = CommandContext {
+            command: "run".to_string(),
+            ..CommandContext::default()
+        };
+        match manager
+            .execute_command(&context, BufferView::default())
+            .as_slice()
+        {
+            [PluginAction::Notify(message)] => assert!(message.contains("not found"), "{message}"),
+            actions => panic!("expected one notification, got {actions:?}"),
+        }
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_prompt_input_asks_until_answered() {
+        let script = r#"
+function on_command(ctx)
+  local template = blackbox.prompt_input("Template?", "daily")
+  local title = blackbox.prompt_input("Title?")
+  return template .. ": " .. title
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let mut config = lua_plugin(&dir, "[[commands]]\nname = \"new\"\n", script);
+        // An unanswered question is not a failure.
+        config.plugin_limits.max_failures = 1;
+        let mut manager = PluginManager::new(&config);
+        let mut run = |answers: &[&str]| {
+            let context = CommandContext {
+                command: "new".to_string(),
+                answers: answers.iter().map(|answer| answer.to_string()).collect(),
+                ..CommandContext::default()
+            };
+            manager.execute_command(&context, BufferView::default())
+        };
+
+        let asked = |actions: Vec<PluginAction>| match actions.as_slice() {
+            [PluginAction::Prompt(prompt)] => prompt.clone(),
+            actions => panic!("expected a prompt, got {actions:?}"),
+        };
+        let first = asked(run(&[]));
+        assert_eq!(first.question, "Template?");
+        assert_eq!(first.default, "daily");
+        let second = asked(run(&["meeting"]));
+        assert_eq!(second.question, "Title?");
+        assert_eq!(second.default, "");
+        match run(&["meeting", "Standup"]).as_slice() {
+            [PluginAction::Notify(message)] => assert_eq!(message, "meeting: Standup"),
+            actions => panic!("expected one notification, got {actions:?}"),
+        }
+        assert_eq!(manager.error_count(), 0);
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_prompt_input_never_repeats_a_write() {
+        let script = r##"
+function on_command(ctx)
+  if ctx.command == "late" then
+    blackbox.write_note("late.md", "x")
+    blackbox.prompt_input("Title?")
+  elseif ctx.command == "flaky" then
+    runs = (runs or 0) + 1
+    if runs > 1 then blackbox.write_note("flaky.md", "x") end
+    blackbox.prompt_input("Title?")
+  end
+  local title = blackbox.prompt_input("Title?")
+  blackbox.write_note(title .. ".md", "# " .. title)
+  return "wrote " .. title
+end
+"##;
+        let tmp = crate::test_util::temp_dir();
+        let base = tmp.path().to_path_buf();
+        let (dir, vault) = (base.join("plugin"), base.join("vault"));
+        std::fs::create_dir_all(&vault).unwrap();
+        let manifest = "permissions = [\"write_vault\"]\n[[commands]]\nname = \"new\"\n[[commands]]\nname = \"late\"\n[[commands]]\nname = \"flaky\"\n";
+        let mut config = lua_plugin(&dir, manifest, script);
+        config.general.vault_path = vault.to_string_lossy().into_owned();
+        config.plugin_limits.max_failures = 0;
+        let mut manager = PluginManager::new(&config);
+        let mut run = |command: &str, answers: &[&str]| {
+            let context = CommandContext {
+                command: command.to_string(),
+                answers: answers.iter().map(|answer| answer.to_string()).collect(),
+                ..CommandContext::default()
+            };
+            manager.execute_command(&context, BufferView::default())
+        };
+
+        assert!(matches!(
+            run("new", &[]).as_slice(),
+            [PluginAction::Prompt(_)]
+        ));
+        match run("new", &["Standup"]).as_slice() {
+            [PluginAction::Notify(message)] => assert_eq!(message, "wrote Standup"),
+            actions => panic!("expected one notification, got {actions:?}"),
+        }
+        assert_eq!(
+            std::fs::read_to_string(vault.join("Standup.md")).unwrap(),
+            "# Standup"
+        );
+
+        // Asking after a write fails rather than running the write again.
+        match run("late", &[]).as_slice() {
+            [PluginAction::Notify(message)] => {
+                assert!(
+                    message.contains("prompt_input: ask before calling write_note"),
+                    "{message}"
+                )
+            }
+            actions => panic!("expected one notification, got {actions:?}"),
+        }
+        assert!(vault.join("late.md").exists());
+
+        // A run handing out earlier answers may not write before using them.
+        assert!(matches!(
+            run("flaky", &[]).as_slice(),
+            [PluginAction::Prompt(_)]
+        ));
+        match run("flaky", &["Standup"]).as_slice() {
+            [PluginAction::Notify(message)] => {
+                assert!(
+                    message.contains("write_note: ask every question before calling it"),
+                    "{message}"
+                )
+            }
+            actions => panic!("expected one notification, got {actions:?}"),
+        }
+        assert!(!vault.join("flaky.md").exists());
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_vault_functions_stay_in_the_vault() {
+        let script = r##"
+function on_command(ctx)
+  if ctx.command == "escape" then
+    blackbox.write_note(ctx.argv[1], "x")
+    return "wrote"
+  end
+  blackbox.write_note("daily/2026-10-14.md", "# " .. blackbox.read_note("inbox.md"))
+  return table.concat(blackbox.list_notes(), ",")
+end
+"##;
+        let tmp = crate::test_util::temp_dir();
+        let base = tmp.path().to_path_buf();
+        let (dir, vault) = (base.join("plugin"), base.join("vault"));
+        std::fs::create_dir_all(&vault).unwrap();
+        std::fs::write(vault.join("inbox.md"), "Inbox").unwrap();
+        let manifest = "permissions = [\"read_vault\", \"write_vault\"]\n[[commands]]\nname = \"daily\"\n[[commands]]\nname = \"escape\"\n";
+        let mut config = lua_plugin(&dir, manifest, script);
+        config.general.vault_path = vault.to_string_lossy().into_owned();
+        // Refused calls are expected here; keep the plugin enabled.
+        config.plugin_limits.max_failures = 0;
+        let mut manager = PluginManager::new(&config);
+
+        let run = |manager: &mut PluginManager, command: &str, arg: &str| {
+            let context = CommandContext {
+                command: command.to_string(),
+                argv: vec![arg.to_string()],
+                ..CommandContext::default()
+            };
+            match manager
+                .execute_command(&context, BufferView::default())
+                .as_slice()
+            {
+                [PluginAction::Notify(message)] => message.clone(),
+                other => panic!("expected a notification, got {other:?}"),
+            }
+        };
+        assert_eq!(
+            run(&mut manager, "daily", ""),
+            "daily/2026-10-14.md,inbox.md"
+        );
+        assert_eq!(
+            std::fs::read_to_string(vault.join("daily/2026-10-14.md")).unwrap(),
+            "# Inbox"
+        );
+        for escape in ["../plugin/init.lua", "/tmp/x.md", "daily/../../x.md", ""] {
+            let message = run(&mut manager, "escape", escape);
+            assert!(
+                message.ends_with("is not a path inside the vault"),
+                "{message}"
+            );
+        }
+        assert_eq!(run(&mut manager, "escape", "ok.md"), "wrote");
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, vault.join("link")).unwrap();
+            let message = run(&mut manager, "escape", "link/x.md");
+            assert!(
+                message.ends_with("is not a path inside the vault"),
+                "{message}"
+            );
+            // Dangling links would be followed out of the vault on write.
+            std::os::unix::fs::symlink(base.join("outside.md"), vault.join("dangling.md")).unwrap();
+            std::os::unix::fs::symlink(base.join("gone"), vault.join("gone")).unwrap();
+            for link in ["dangling.md", "gone/x.md"] {
+                let message = run(&mut manager, "escape", link);
+                assert!(message.ends_with("is a symlink"), "{message}");
+            }
+            assert!(!base.join("outside.md").exists());
+        }
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_http_request_reaches_allowed_hosts_only() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0; 1024];
+            while !request.ends_with(b"\r\n\r\nnote") {
+                let read = stream.read(&mut chunk).unwrap();
+                request.extend_from_slice(&chunk[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 5\r\nX-Test: yes\r\n\r\nhello")
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let script = r#"
+function on_command(ctx)
+  local response = blackbox.http_request({
+    url = ctx.args, method = "post", headers = { ["X-Plugin"] = "clip" }, body = "note",
+  })
+  return response.status .. " " .. response.headers["x-test"] .. " " .. response.body
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest = "permissions = [\"network\"]\nallowed_hosts = [\"127.0.0.1\", \"*.example.com\"]\n[[commands]]\nname = \"clip\"\n";
+        let mut config = lua_plugin(&dir, manifest, script);
+        // Refused calls are expected here; keep the plugin enabled.
+        config.plugin_limits.max_failures = 0;
+        let mut manager = PluginManager::new(&config);
+        let mut run = |url: String| {
+            let context = CommandContext {
+                command: "clip".to_string(),
+                args: url,
+                ..CommandContext::default()
+            };
+            match manager
+                .execute_command(&context, BufferView::default())
+                .as_slice()
+            {
+                [PluginAction::Notify(message)] => message.clone(),
+                other => panic!("expected a notification, got {other:?}"),
+            }
+        };
+
+        assert_eq!(
+            run(format!("http://127.0.0.1:{port}/clip")),
+            "201 yes hello"
+        );
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /clip HTTP/1.1"), "{request}");
+        assert!(
+            request.to_lowercase().contains("x-plugin: clip"),
+            "{request}"
+        );
+
+        for (url, error) in [
+            ("http://localhost/", "localhost is not in allowed_hosts"),
+            (
+                "https://example.com/",
+                "example.com is not in allowed_hosts",
+            ),
+            ("ftp://127.0.0.1/notes", "is not an http(s) URL"),
+        ] {
+            let message = run(url.to_string());
+            assert!(message.ends_with(error), "{message}");
+        }
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_logs_keep_the_newest_lines_across_reloads() {
+        use crate::plugin::host_fns::LOG_LINES;
+
+        let script = r#"
+function on_command(ctx)
+  if ctx.command == "spam" then
+    for i = 1, 205 do blackbox.log("line " .. i) end
+    return
+  end
+  blackbox.log("warn", "careful")
+  blackbox.log("loud", "boom")
+end
+"#;
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manifest = "[[commands]]\nname = \"spam\"\n[[commands]]\nname = \"fail\"\n";
+        let mut manager = PluginManager::new(&lua_plugin(&dir, manifest, script));
+        let run = |manager: &mut PluginManager, command: &str| {
+            let context = CommandContext {
+                command: command.to_string(),
+                ..CommandContext::default()
+            };
+            manager.execute_command(&context, BufferView::default());
+        };
+
+        run(&mut manager, "fail");
+        let (name, lines) = manager.logs("test").unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(lines[0], "warn  careful");
+        assert!(
+            lines[1].starts_with("error on_command failed:")
+                && lines[1].ends_with("log: unknown level loud"),
+            "{lines:?}"
+        );
+
+        manager.reload_dirs(std::slice::from_ref(&dir));
+        run(&mut manager, "spam");
+        let lines = manager.logs("test").unwrap().1;
+        assert_eq!(lines.len(), LOG_LINES);
+        assert_eq!(lines.last().unwrap(), "info  line 205");
+        assert_eq!(lines[0], "info  line 6");
+        assert!(manager.logs("missing").is_none());
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_incompatible_api_version_is_an_error() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        let manager = PluginManager::new(&lua_plugin(&dir, "api_version = 99\n", ""));
+        assert_eq!(manager.error_count(), 1);
+        let rows = manager.list_notifications();
+        assert!(
+            rows[0].starts_with("plugin test api 99 [error: needs plugin API 99"),
+            "{rows:?}"
+        );
+        assert!(PluginManifest::validate(&dir).is_err());
+
+        let manager = PluginManager::new(&lua_plugin(&dir, "", ""));
+        assert_eq!(manager.error_count(), 0);
+        assert!(manager.list_notifications()[0].starts_with("plugin test api 1 [discovered]"));
+    }
+
     #[test]
     fn test_list_notifications_no_plugins() {
         let config = config_with_no_plugins();
@@ -1,9 +1,17 @@
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::plugin::permission::Permission;
 
+/// The plugin API this build implements: the exports it calls, the JSON
+/// they exchange and the host functions. Raised when any of them changes
+/// in a way an older plugin would misread.
+pub const API_VERSION: u32 = 1;
+
+/// The oldest plugin API this build still runs.
+pub const MIN_API_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PluginId(pub String);
 
@@ -21,24 +29,147 @@ pub struct PluginManifest {
     pub version: String,
     #[allow(dead_code)]
     pub entry: String,
+    /// The plugin API the plugin was built for; 1 when left out, which is
+    /// the API plugins had before it was versioned.
+    #[serde(default = "default_api_version")]
+    pub api_version: u32,
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
     pub author: Option<String>,
     #[serde(default)]
     pub permissions: Vec<Permission>,
+    /// Hosts `http_request` may reach with the `network` permission; `*.`
+    /// in front of a domain allows its subdomains and `*` any host.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
     #[serde(default)]
     pub commands: Vec<CommandDef>,
     #[serde(default)]
     pub keybindings: Vec<KeybindingDef>,
+    #[serde(default)]
+    pub completions: Vec<CompletionDef>,
+}
+
+impl PluginManifest {
+    /// Read `plugin.toml` in `root_dir`.
+    pub fn load(root_dir: &Path) -> Result<Self, String> {
+        let manifest_path = root_dir.join("plugin.toml");
+        let raw = std::fs::read_to_string(&manifest_path)
+            .map_err(|err| format!("{}: {err}", manifest_path.display()))?;
+
+        toml::from_str::<PluginManifest>(&raw)
+            .map_err(|err| format!("{}: {err}", manifest_path.display()))
+    }
+
+    /// Check that this build speaks the API the plugin was built for.
+    pub fn negotiate(&self) -> Result<(), String> {
+        if self.api_version > API_VERSION {
+            Err(format!(
+                "needs plugin API {}, this build supports up to {API_VERSION}; update blackbox",
+                self.api_version
+            ))
+        } else if self.api_version < MIN_API_VERSION {
+            Err(format!(
+                "built for plugin API {}, this build supports {MIN_API_VERSION} to {API_VERSION}; update the plugin",
+                self.api_version
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Read `plugin.toml` in `root_dir` and check that its API version is
+    /// supported and its entry is there.
+    pub fn validate(root_dir: &Path) -> Result<Self, String> {
+        let manifest = Self::load(root_dir)?;
+        manifest.negotiate()?;
+        let entry_path = root_dir.join(&manifest.entry);
+        if !entry_path.is_file() {
+            return Err(format!("missing entry: {}", entry_path.display()));
+        }
+        Ok(manifest)
+    }
+
+    /// What the plugin may do, asked about before it is installed:
+    /// `read_buffer, network (api.example.com)` or `no permissions`.
+    pub fn access_summary(&self) -> String {
+        if self.permissions.is_empty() {
+            return "no permissions".to_string();
+        }
+        let granted: Vec<String> = self
+            .permissions
+            .iter()
+            .map(|permission| match permission {
+                Permission::Network if !self.allowed_hosts.is_empty() => {
+                    format!("network ({})", self.allowed_hosts.join(", "))
+                }
+                permission => permission.as_str().to_string(),
+            })
+            .collect();
+        format!("permissions: {}", granted.join(", "))
+    }
+
+    /// What `newer` may do that this manifest may not, as `write_vault,
+    /// network (api.example.com)`; `None` when it asks for nothing more.
+    pub fn added_access(&self, newer: &PluginManifest) -> Option<String> {
+        let mut added: Vec<String> = newer
+            .permissions
+            .iter()
+            .filter(|permission| {
+                **permission != Permission::Network && !self.permissions.contains(permission)
+            })
+            .map(|permission| permission.as_str().to_string())
+            .collect();
+        if newer.permissions.contains(&Permission::Network) {
+            let had_network = self.permissions.contains(&Permission::Network);
+            let hosts: Vec<&str> = newer
+                .allowed_hosts
+                .iter()
+                .filter(|host| !had_network || !self.allowed_hosts.contains(host))
+                .map(String::as_str)
+                .collect();
+            if !hosts.is_empty() {
+                added.push(format!("network ({})", hosts.join(", ")));
+            } else if !had_network {
+                added.push("network".to_string());
+            }
+        }
+        (!added.is_empty()).then(|| added.join(", "))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
-#[allow(dead_code)] // Phase 3 scaffolding: consumed by command palette registration.
 pub struct CommandDef {
     pub name: String,
     #[serde(default)]
     pub description: Option<String>,
+    /// Arguments the command expects, for help text; `[name]` marks an
+    /// optional one.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl CommandDef {
+    /// `stamp <format> [zone]`.
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for arg in &self.args {
+            if arg.starts_with('[') {
+                usage.push_str(&format!(" {arg}"));
+            } else {
+                usage.push_str(&format!(" <{arg}>"));
+            }
+        }
+        usage
+    }
+}
+
+/// Insert-mode completion offered after `trigger` (such as `@`) is typed
+/// at the start of a word.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionDef {
+    pub trigger: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -64,6 +195,10 @@ pub struct PluginInstallSpec {
     pub config: HashMap<String, toml::Value>,
 }
 
+fn default_api_version() -> u32 {
+    1
+}
+
 #[allow(dead_code)] // Phase 3 scaffolding: default deserialization helper for install specs.
 fn default_enabled() -> bool {
     true
@@ -1,8 +1,13 @@
 pub mod host_fns;
 pub mod installer;
+#[cfg(feature = "plugins")]
+pub mod lua;
 pub mod manager;
 pub mod manifest;
 pub mod permission;
+pub mod registry;
 pub mod runtime;
+pub mod scaffold;
+pub mod validate;
 
 pub use manager::PluginManager;
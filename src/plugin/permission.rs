@@ -9,4 +9,26 @@ pub enum Permission {
     RegisterCommand,
     ListenEvents,
     BindKeys,
+    Decorate,
+    ReadVault,
+    WriteVault,
+    Network,
+}
+
+impl Permission {
+    /// The name used in `plugin.toml`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::ReadBuffer => "read_buffer",
+            Permission::ProposeEdit => "propose_edit",
+            Permission::DrawPane => "draw_pane",
+            Permission::RegisterCommand => "register_command",
+            Permission::ListenEvents => "listen_events",
+            Permission::BindKeys => "bind_keys",
+            Permission::Decorate => "decorate",
+            Permission::ReadVault => "read_vault",
+            Permission::WriteVault => "write_vault",
+            Permission::Network => "network",
+        }
+    }
 }
@@ -0,0 +1,108 @@
+//! The plugin registry: a JSON index of community plugins, fetched over
+//! HTTPS for `:plugins.browse` to list and install from.
+
+use serde::Deserialize;
+
+/// Longest wait for the index before giving up.
+#[cfg(feature = "plugins")]
+const FETCH_TIMEOUT_MS: u64 = 10_000;
+
+/// Largest index accepted.
+#[cfg(feature = "plugins")]
+const MAX_INDEX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// One plugin the registry offers.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RegistryEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Git URL handed to the installer.
+    pub repo: String,
+}
+
+#[cfg(any(feature = "plugins", test))]
+#[derive(Deserialize)]
+struct RegistryIndex {
+    plugins: Vec<RegistryEntry>,
+}
+
+/// The entries of an index document, `{"plugins": [...]}`, sorted by name.
+/// Entries without a name, or whose repo is not an `https://` or `git@`
+/// URL, are dropped; the index is not trusted.
+#[cfg(any(feature = "plugins", test))]
+pub fn parse(json: &str) -> Result<Vec<RegistryEntry>, String> {
+    let index: RegistryIndex =
+        serde_json::from_str(json).map_err(|err| format!("invalid registry index: {err}"))?;
+    let mut entries: Vec<RegistryEntry> = index
+        .plugins
+        .into_iter()
+        .filter(|entry| !entry.name.trim().is_empty() && is_git_url(&entry.repo))
+        .collect();
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    Ok(entries)
+}
+
+#[cfg(any(feature = "plugins", test))]
+fn is_git_url(repo: &str) -> bool {
+    (repo.starts_with("https://") || repo.starts_with("git@"))
+        && !repo.chars().any(char::is_whitespace)
+}
+
+/// Download and parse the index at `url`, which must be HTTPS.
+#[cfg(feature = "plugins")]
+pub fn fetch(url: &str) -> Result<Vec<RegistryEntry>, String> {
+    if !url.starts_with("https://") {
+        return Err(format!("{url} is not an https URL"));
+    }
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_millis(FETCH_TIMEOUT_MS)))
+        .build()
+        .new_agent();
+    let body = agent
+        .get(url)
+        .call()
+        .map_err(|err| format!("cannot fetch {url}: {err}"))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_INDEX_BYTES)
+        .read_to_string()
+        .map_err(|err| format!("cannot read {url}: {err}"))?;
+    parse(&body)
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn fetch(_url: &str) -> Result<Vec<RegistryEntry>, String> {
+    Err("built without plugin support".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sorts_and_skips_incomplete_entries() {
+        let entries = parse(
+            r#"{"plugins": [
+                {"name": "word-count", "description": "Count words", "repo": "https://example.com/wc.git"},
+                {"name": "", "repo": "https://example.com/blank.git"},
+                {"name": "no-repo", "repo": " "},
+                {"name": "option", "repo": "--upload-pack=touch /tmp/pwned"},
+                {"name": "local", "repo": "/home/me/plugin"},
+                {"name": "ssh", "repo": "git@example.com:me/ssh.git"},
+                {"name": "Backlinks+", "repo": "https://example.com/bl.git", "stars": 4}
+            ]}"#,
+        )
+        .unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["Backlinks+", "ssh", "word-count"]);
+        assert_eq!(entries[0].description, "");
+        assert_eq!(entries[2].repo, "https://example.com/wc.git");
+
+        assert!(
+            parse("[]")
+                .unwrap_err()
+                .starts_with("invalid registry index")
+        );
+    }
+}
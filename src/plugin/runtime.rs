@@ -1,7 +1,31 @@
-use std::fs;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "plugins")]
+use std::collections::VecDeque;
+use std::path::PathBuf;
+#[cfg(feature = "plugins")]
+use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
+
+use crate::model::config::PluginLimitsConfig;
+use crate::plugin::host_fns::BufferView;
+#[cfg(feature = "plugins")]
+use crate::plugin::host_fns::{HostFunctions, HostState};
+#[cfg(feature = "plugins")]
+use crate::plugin::lua::{self, LuaPlugin};
 use crate::plugin::manifest::{PluginId, PluginManifest};
+use crate::plugin::permission::Permission;
+
+/// Export called with a [`CommandContext`] when one of the plugin's
+/// commands runs.
+pub const COMMAND_EXPORT: &str = "on_command";
+
+/// Export called with a [`CompletionContext`] when one of the plugin's
+/// completion triggers is typed.
+pub const COMPLETE_EXPORT: &str = "on_complete";
+
+/// Export called with a [`DecorateContext`] after the active note is opened
+/// or saved, for plugins with the `decorate` permission.
+pub const DECORATE_EXPORT: &str = "on_decorate";
 
 #[derive(Debug, Clone)]
 pub enum PluginStatus {
@@ -10,32 +34,290 @@ pub enum PluginStatus {
     Error(String),
 }
 
-#[derive(Debug, Clone)]
+/// JSON sent to [`COMMAND_EXPORT`]: the command, its arguments and where
+/// the user is.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommandContext {
+    pub command: String,
+    /// Everything after the command name, as typed.
+    pub args: String,
+    /// `args` split into words, with quotes grouping them.
+    pub argv: Vec<String>,
+    /// The active note, `None` for an unsaved buffer.
+    pub path: Option<PathBuf>,
+    pub cursor: CursorContext,
+    /// What the user answered to the command's `prompt_input` calls so
+    /// far, handed back to them in order.
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    pub answers: Vec<String>,
+}
+
+/// JSON sent to [`COMPLETE_EXPORT`]: the trigger, what follows it before
+/// the cursor and where the user is.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompletionContext {
+    pub trigger: String,
+    pub partial: String,
+    pub path: Option<PathBuf>,
+    pub cursor: CursorContext,
+}
+
+/// JSON sent to [`DECORATE_EXPORT`]: why it is called (`open` or `save`)
+/// and where the user is.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecorateContext {
+    pub event: String,
+    pub path: Option<PathBuf>,
+    pub cursor: CursorContext,
+}
+
+/// How a decoration is coloured, most severe first.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DecorationKind {
+    Error,
+    Warning,
+    #[default]
+    Info,
+    Hint,
+}
+
+/// A mark on one line of the active buffer: the byte range `start..end`
+/// underlined when either is set (`end` defaults to the end of the line),
+/// a `gutter` symbol and `text` shown after the line.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Decoration {
+    pub line: usize,
+    #[serde(default)]
+    pub start: Option<usize>,
+    #[serde(default)]
+    pub end: Option<usize>,
+    #[serde(default)]
+    pub kind: DecorationKind,
+    #[serde(default)]
+    pub gutter: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// A question from `prompt_input`. The command stops there and is run
+/// again once the user answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Prompt {
+    pub question: String,
+    /// Filled in for the user to accept or edit.
+    pub default: String,
+}
+
+/// One entry of a completion list; `insert` replaces the partial text and
+/// defaults to `label`.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    #[serde(default)]
+    pub insert: Option<String>,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// What [`COMPLETE_EXPORT`] returns.
+#[derive(Debug, Default, Deserialize)]
+struct CompletionResponse {
+    #[serde(default)]
+    items: Vec<CompletionItem>,
+}
+
+/// 0-indexed line and byte column, like the editor's cursor.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CursorContext {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Replace the text between `start` and `end` in the active buffer.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct PluginEdit {
+    pub start: CursorContext,
+    pub end: CursorContext,
+    pub text: String,
+}
+
+/// What a command handler may return; empty output means nothing to do.
+#[derive(Debug, Default, Deserialize)]
+struct CommandResponse {
+    #[serde(default)]
+    notify: Option<String>,
+    #[serde(default)]
+    edit: Option<PluginEdit>,
+    #[serde(default)]
+    edits: Vec<PluginEdit>,
+}
+
+/// The result of one command call.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub notify: Option<String>,
+    /// Returned or passed to `propose_edit`, all against the buffer as it
+    /// was when the command started.
+    pub edits: Vec<PluginEdit>,
+    /// Passed to `set_decorations`, replacing the plugin's previous ones.
+    pub decorations: Option<Vec<Decoration>>,
+    /// A question to answer before the command can finish; nothing else
+    /// is set when there is one.
+    pub prompt: Option<Prompt>,
+}
+
+/// What an export left behind through the host functions besides its
+/// output.
+#[derive(Debug, Default)]
+struct CallOutput {
+    output: String,
+    edits: Vec<PluginEdit>,
+    decorations: Option<Vec<Decoration>>,
+    prompt: Option<Prompt>,
+}
+
+impl CommandOutput {
+    /// A JSON [`CommandResponse`], or plain text shown as a notification.
+    fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Ok(Self::default());
+        }
+        if !raw.starts_with('{') {
+            return Ok(Self {
+                notify: Some(raw.to_string()),
+                ..Self::default()
+            });
+        }
+        let response: CommandResponse =
+            serde_json::from_str(raw).map_err(|err| format!("invalid response: {err}"))?;
+        Ok(Self {
+            notify: response.notify,
+            edits: response.edit.into_iter().chain(response.edits).collect(),
+            decorations: None,
+            prompt: None,
+        })
+    }
+}
+
+/// A loaded entry: a WASM module or a Lua script.
+#[cfg(feature = "plugins")]
+#[derive(Debug)]
+enum Instance {
+    Wasm(Box<extism::Plugin>),
+    Lua(LuaPlugin),
+}
+
+#[cfg(feature = "plugins")]
+impl Instance {
+    fn exports(&self, export: &str) -> bool {
+        match self {
+            Instance::Wasm(plugin) => plugin.function_exists(export),
+            Instance::Lua(script) => script.function_exists(export),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct PluginRuntime {
     pub id: PluginId,
     pub root_dir: PathBuf,
     pub manifest: Option<PluginManifest>,
     pub status: PluginStatus,
+    limits: PluginLimitsConfig,
+    /// Where the vault host functions may read and write.
+    vault: PathBuf,
+    /// The instantiated entry, created on first use.
+    #[cfg(feature = "plugins")]
+    instance: Option<Instance>,
+    /// Shared with the host functions for the length of a call.
+    #[cfg(feature = "plugins")]
+    host: extism::UserData<HostState>,
+    /// When recent calls failed, oldest first, for disabling a plugin that
+    /// keeps failing.
+    #[cfg(feature = "plugins")]
+    failures: VecDeque<Instant>,
 }
 
 impl PluginRuntime {
-    pub fn discover(id: PluginId, root_dir: PathBuf) -> Self {
-        match Self::read_manifest(&root_dir) {
+    /// Read the plugin's manifest and check its API version; the entry is
+    /// loaded on first use.
+    pub fn discover(
+        id: PluginId,
+        root_dir: PathBuf,
+        limits: PluginLimitsConfig,
+        vault: PathBuf,
+    ) -> Self {
+        match PluginManifest::load(&root_dir) {
             Ok(manifest) => Self {
                 id,
                 root_dir,
+                status: match manifest.negotiate() {
+                    Ok(()) => PluginStatus::Discovered,
+                    Err(err) => PluginStatus::Error(err),
+                },
                 manifest: Some(manifest),
-                status: PluginStatus::Discovered,
+                limits,
+                vault,
+                #[cfg(feature = "plugins")]
+                instance: None,
+                #[cfg(feature = "plugins")]
+                host: extism::UserData::default(),
+                #[cfg(feature = "plugins")]
+                failures: VecDeque::new(),
             },
             Err(err) => Self {
                 id,
                 root_dir,
                 manifest: None,
                 status: PluginStatus::Error(err),
+                limits,
+                vault,
+                #[cfg(feature = "plugins")]
+                instance: None,
+                #[cfg(feature = "plugins")]
+                host: extism::UserData::default(),
+                #[cfg(feature = "plugins")]
+                failures: VecDeque::new(),
             },
         }
     }
 
+    /// Read the manifest again and drop the loaded entry, so the next call
+    /// instantiates the WASM or script as it is on disk now.
+    pub fn reload(&mut self) {
+        let root_dir = std::mem::take(&mut self.root_dir);
+        let vault = std::mem::take(&mut self.vault);
+        #[cfg(feature = "plugins")]
+        let logs = self.with_host(|state| std::mem::take(&mut state.logs));
+        *self = Self::discover(self.id.clone(), root_dir, self.limits.clone(), vault);
+        #[cfg(feature = "plugins")]
+        if let Some(logs) = logs {
+            self.with_host(|state| state.logs = logs);
+        }
+    }
+
+    /// The newest lines of the plugin's log, oldest first.
+    #[cfg(feature = "plugins")]
+    pub fn logs(&self) -> Vec<String> {
+        self.with_host(|state| state.logs.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    pub fn logs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[cfg(feature = "plugins")]
+    fn with_host<T>(&self, f: impl FnOnce(&mut HostState) -> T) -> Option<T> {
+        let host = self.host.get().ok()?;
+        let mut state = host.lock().ok()?;
+        Some(f(&mut state))
+    }
+
     pub fn status(&self) -> &PluginStatus {
         &self.status
     }
@@ -47,18 +329,215 @@ impl PluginRuntime {
             .unwrap_or(false)
     }
 
-    pub fn execute_command(&mut self, command: &str) -> Result<Option<String>, String> {
-        if !self.supports_command(command) {
+    /// Run one of the plugin's commands, with `buffer` readable through the
+    /// host functions. `Ok(None)` when it has no such command.
+    pub fn execute_command(
+        &mut self,
+        context: &CommandContext,
+        buffer: BufferView,
+    ) -> Result<Option<CommandOutput>, String> {
+        if !self.supports_command(&context.command) {
             return Ok(None);
         }
 
         self.ensure_loaded()?;
+        let payload = serde_json::to_string(context).map_err(|err| err.to_string())?;
+        #[cfg(feature = "plugins")]
+        self.with_host(|state| state.answers = Some(context.answers.iter().cloned().collect()));
+        let call = self.call(COMMAND_EXPORT, &payload, buffer)?;
+        if call.prompt.is_some() {
+            return Ok(Some(CommandOutput {
+                prompt: call.prompt,
+                ..CommandOutput::default()
+            }));
+        }
+        let mut output = CommandOutput::parse(&call.output)?;
+        output.edits.extend(call.edits);
+        output.decorations = call.decorations;
+        Ok(Some(output))
+    }
+
+    pub fn completes(&self, trigger: &str) -> bool {
+        self.manifest.as_ref().is_some_and(|manifest| {
+            manifest
+                .completions
+                .iter()
+                .any(|completion| completion.trigger == trigger)
+        })
+    }
+
+    /// Ask the plugin for completions after `context.trigger`; edits and
+    /// decorations it sets are dropped.
+    pub fn complete(
+        &mut self,
+        context: &CompletionContext,
+        buffer: BufferView,
+    ) -> Result<Vec<CompletionItem>, String> {
+        self.ensure_loaded()?;
+        let payload = serde_json::to_string(context).map_err(|err| err.to_string())?;
+        let raw = self.call(COMPLETE_EXPORT, &payload, buffer)?.output;
+        if raw.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        serde_json::from_str::<CompletionResponse>(&raw)
+            .map(|response| response.items)
+            .map_err(|err| format!("invalid completions: {err}"))
+    }
+
+    /// Whether the plugin may decorate buffers and has not failed.
+    pub fn decorates(&self) -> bool {
+        !matches!(self.status, PluginStatus::Error(_))
+            && self
+                .manifest
+                .as_ref()
+                .is_some_and(|manifest| manifest.permissions.contains(&Permission::Decorate))
+    }
+
+    /// Let the plugin mark up the buffer after `context.event`. `Ok(None)`
+    /// when its entry has no [`DECORATE_EXPORT`] or it left its
+    /// decorations alone.
+    pub fn decorate(
+        &mut self,
+        context: &DecorateContext,
+        buffer: BufferView,
+    ) -> Result<Option<Vec<Decoration>>, String> {
+        self.ensure_loaded()?;
+        if !self.exports(DECORATE_EXPORT) {
+            return Ok(None);
+        }
+        let payload = serde_json::to_string(context).map_err(|err| err.to_string())?;
+        Ok(self.call(DECORATE_EXPORT, &payload, buffer)?.decorations)
+    }
+
+    #[cfg(feature = "plugins")]
+    fn exports(&self, export: &str) -> bool {
+        self.instance
+            .as_ref()
+            .is_some_and(|instance| instance.exports(export))
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn exports(&self, _export: &str) -> bool {
+        false
+    }
+
+    /// Call `export` and return its output with what it passed to the host
+    /// functions.
+    #[cfg(feature = "plugins")]
+    fn call(
+        &mut self,
+        export: &str,
+        input: &str,
+        buffer: BufferView,
+    ) -> Result<CallOutput, String> {
+        let instance = self
+            .instance
+            .as_mut()
+            .ok_or_else(|| "plugin is not loaded".to_string())?;
+        if !instance.exports(export) {
+            return Err(format!("entry does not export {export}"));
+        }
+        let host = self.host.get().map_err(|err| err.to_string())?;
+        if let Ok(mut state) = host.lock() {
+            state.buffer = buffer;
+        }
+        let result = match instance {
+            Instance::Wasm(plugin) => plugin
+                .call::<&str, &str>(export, input)
+                .map(str::to_string)
+                .map_err(|err| err.root_cause().to_string()),
+            Instance::Lua(script) => script.call(export, input, &self.limits),
+        };
+        // Only hold on to the note's text while the plugin can read it.
+        let (edits, decorations, prompt) = match host.lock() {
+            Ok(mut state) => {
+                state.buffer = BufferView::default();
+                state.answers = None;
+                state.side_effect = None;
+                (
+                    std::mem::take(&mut state.proposed),
+                    state.decorations.take(),
+                    state.prompt.take(),
+                )
+            }
+            Err(_) => (Vec::new(), None, None),
+        };
+        // The call stopped to ask before touching anything outside the
+        // buffer; it runs again once the user answers.
+        if prompt.is_some() {
+            return Ok(CallOutput {
+                prompt,
+                ..CallOutput::default()
+            });
+        }
+        match result {
+            Ok(output) => Ok(CallOutput {
+                output,
+                edits,
+                decorations,
+                prompt: None,
+            }),
+            Err(err) => {
+                let err = self.call_failed(export, &err);
+                self.with_host(|state| state.push_log("error", &err));
+                Err(err)
+            }
+        }
+    }
+
+    /// The message for a failed call. Running out of time or fuel, or
+    /// failing `max_failures` times within the window, leaves the plugin
+    /// errored, and it stays off until it is enabled or reloaded.
+    #[cfg(feature = "plugins")]
+    fn call_failed(&mut self, export: &str, cause: &str) -> String {
+        let reason = match cause {
+            "timeout" => format!("{export} timed out after {} ms", self.limits.timeout_ms),
+            "plugin ran out of fuel" => format!("{export} ran out of fuel ({})", self.limits.fuel),
+            _ if self.failing_repeatedly() => format!(
+                "{export} failed: {cause}; {} failures in {}s",
+                self.failures.len(),
+                self.limits.failure_window_secs
+            ),
+            _ => return format!("{export} failed: {cause}"),
+        };
+        let err = format!(
+            "{reason}; disabled until :plugins.enable {}",
+            self.display_name()
+        );
+        self.instance = None;
+        self.status = PluginStatus::Error(err.clone());
+        err
+    }
+
+    /// Count a failed call and say whether `max_failures` have now happened
+    /// within `failure_window_secs`.
+    #[cfg(feature = "plugins")]
+    fn failing_repeatedly(&mut self) -> bool {
+        let max = self.limits.max_failures as usize;
+        if max == 0 {
+            return false;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(self.limits.failure_window_secs);
+        self.failures
+            .retain(|failed| now.duration_since(*failed) < window);
+        self.failures.push_back(now);
+        self.failures.len() >= max
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn call(
+        &mut self,
+        _export: &str,
+        _input: &str,
+        _buffer: BufferView,
+    ) -> Result<CallOutput, String> {
+        Err("this build has no plugin support".to_string())
+    }
 
-        Ok(Some(format!(
-            "plugin {} handled command: {}",
-            self.display_name(),
-            command
-        )))
+    /// Whether `name` is the plugin's name in its manifest or its directory.
+    pub fn named(&self, name: &str) -> bool {
+        self.display_name() == name || self.root_dir.file_name().is_some_and(|dir| dir == name)
     }
 
     pub fn display_name(&self) -> String {
@@ -68,18 +547,11 @@ impl PluginRuntime {
             .unwrap_or_else(|| self.id.0.clone())
     }
 
-    fn read_manifest(root_dir: &Path) -> Result<PluginManifest, String> {
-        let manifest_path = root_dir.join("plugin.toml");
-        let raw = fs::read_to_string(&manifest_path)
-            .map_err(|err| format!("{}: {err}", manifest_path.display()))?;
-
-        toml::from_str::<PluginManifest>(&raw)
-            .map_err(|err| format!("{}: {err}", manifest_path.display()))
-    }
-
     fn ensure_loaded(&mut self) -> Result<(), String> {
-        if matches!(self.status, PluginStatus::Loaded) {
-            return Ok(());
+        match &self.status {
+            PluginStatus::Loaded => return Ok(()),
+            PluginStatus::Error(err) => return Err(err.clone()),
+            PluginStatus::Discovered => {}
         }
 
         let manifest = self
@@ -87,13 +559,57 @@ impl PluginRuntime {
             .as_ref()
             .ok_or_else(|| "missing plugin manifest".to_string())?;
 
-        let wasm_path = self.root_dir.join(&manifest.entry);
-        if !wasm_path.is_file() {
-            let err = format!("missing wasm entry: {}", wasm_path.display());
+        let entry_path = self.root_dir.join(&manifest.entry);
+        if !entry_path.is_file() {
+            let err = format!("missing entry: {}", entry_path.display());
             self.status = PluginStatus::Error(err.clone());
             return Err(err);
         }
 
+        #[cfg(feature = "plugins")]
+        {
+            if let Ok(host) = self.host.get()
+                && let Ok(mut state) = host.lock()
+            {
+                state.plugin = manifest.name.clone();
+                state.permissions = manifest.permissions.clone();
+                state.vault = self.vault.clone();
+                state.allowed_hosts = manifest.allowed_hosts.clone();
+                state.limits = self.limits.clone();
+            }
+            let loaded = if lua::is_script(&entry_path) {
+                self.host
+                    .get()
+                    .map_err(|err| err.to_string())
+                    .and_then(|host| LuaPlugin::load(&entry_path, &self.limits, host))
+                    .map(Instance::Lua)
+            } else {
+                let wasm = extism::Manifest::new([extism::Wasm::file(&entry_path)])
+                    .with_timeout(Duration::from_millis(self.limits.timeout_ms))
+                    // 64 KiB pages.
+                    .with_memory_max(self.limits.memory_mb.saturating_mul(16));
+                let mut builder = extism::PluginBuilder::new(wasm)
+                    .with_wasi(true)
+                    .with_functions(HostFunctions::register_all(&self.host));
+                if self.limits.fuel > 0 {
+                    builder = builder.with_fuel_limit(self.limits.fuel);
+                }
+                builder
+                    .build()
+                    .map(|plugin| Instance::Wasm(Box::new(plugin)))
+                    .map_err(|err| err.to_string())
+            };
+            match loaded {
+                Ok(instance) => self.instance = Some(instance),
+                Err(err) => {
+                    let err = format!("cannot load {}: {err}", entry_path.display());
+                    self.with_host(|state| state.push_log("error", &err));
+                    self.status = PluginStatus::Error(err.clone());
+                    return Err(err);
+                }
+            }
+        }
+
         self.status = PluginStatus::Loaded;
         Ok(())
     }
@@ -0,0 +1,175 @@
+//! `:plugins.new`: a starter plugin to build on, a Rust crate that compiles
+//! to the WASM entry its manifest names.
+
+use std::path::{Path, PathBuf};
+
+use crate::plugin::manifest::API_VERSION;
+
+const PLUGIN_TOML: &str = r#"name = "{{name}}"
+version = "0.1.0"
+description = "Counts the words in the active note"
+entry = "plugin.wasm"
+api_version = {{api}}
+permissions = ["read_buffer"]
+
+[[commands]]
+name = "{{name}}"
+description = "count the words in the note"
+"#;
+
+const CARGO_TOML: &str = r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+extism-pdk = "1"
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+
+[profile.release]
+opt-level = "s"
+lto = true
+"#;
+
+const LIB_RS: &str = r#"//! {{name}}: a blackbox plugin. `./build.sh` writes `plugin.wasm`, and
+//! blackbox reloads the plugin when it changes.
+
+use extism_pdk::*;
+use serde::Deserialize;
+
+/// What `on_command` is called with.
+#[derive(Deserialize)]
+struct Context {
+    command: String,
+    args: String,
+}
+
+/// What `read_buffer` returns; `path`, `cursor` and `selection` are there
+/// too.
+#[derive(Deserialize)]
+struct Buffer {
+    text: String,
+}
+
+#[host_fn]
+extern "ExtismHost" {
+    fn read_buffer() -> String;
+    fn log(level: String, message: String);
+}
+
+/// Runs the plugin's `[[commands]]`. Plain text comes back as a
+/// notification; `{"notify": ..., "edits": [...]}` also edits the note.
+#[plugin_fn]
+pub fn on_command(input: String) -> FnResult<String> {
+    let context: Context = serde_json::from_str(&input)?;
+    // Shown by `:plugins.logs {{name}}`.
+    unsafe { log("info".to_string(), format!("{} {}", context.command, context.args))? };
+
+    let buffer: Buffer = serde_json::from_str(&unsafe { read_buffer()? })?;
+    let words = buffer.text.split_whitespace().count();
+    Ok(format!("{}: {words} words", context.command))
+}
+"#;
+
+const BUILD_SH: &str = r#"#!/bin/sh
+# Build plugin.wasm. Needs `rustup target add wasm32-unknown-unknown` once.
+set -e
+cd "$(dirname "$0")"
+cargo build --release --target wasm32-unknown-unknown
+cp target/wasm32-unknown-unknown/release/{{crate}}.wasm plugin.wasm
+"#;
+
+const GITIGNORE: &str = "/target\n";
+
+/// Whether `name` works as a plugin, command and crate name: lowercase
+/// ASCII letters, digits, `-` and `_`, starting with a letter.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name:?} is not a plugin name; use lowercase letters, digits, - and _"
+        ))
+    }
+}
+
+/// Write a starter plugin called `name` into `dir`, which must not exist
+/// or be empty. Returns the files written.
+pub fn scaffold(dir: &Path, name: &str) -> Result<Vec<PathBuf>, String> {
+    check_name(name)?;
+    if std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!("{} already exists", dir.display()));
+    }
+    let fill = |template: &str| {
+        template
+            .replace("{{name}}", name)
+            .replace("{{crate}}", &name.replace('-', "_"))
+            .replace("{{api}}", &API_VERSION.to_string())
+    };
+    let files = [
+        ("plugin.toml", fill(PLUGIN_TOML)),
+        ("Cargo.toml", fill(CARGO_TOML)),
+        ("src/lib.rs", fill(LIB_RS)),
+        ("build.sh", fill(BUILD_SH)),
+        (".gitignore", GITIGNORE.to_string()),
+    ];
+    let mut written = Vec::new();
+    for (rel, text) in files {
+        let path = dir.join(rel);
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, text))
+            .map_err(|err| format!("cannot write {}: {err}", path.display()))?;
+        written.push(path);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("build.sh");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+            .map_err(|err| format!("cannot make {} executable: {err}", script.display()))?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::validate::{self, Severity};
+
+    #[test]
+    fn test_scaffold_is_a_valid_plugin_once_built() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().join("word-stats").to_path_buf();
+        let written = scaffold(&dir, "word-stats").unwrap();
+        assert_eq!(written.len(), 5);
+        let build = std::fs::read_to_string(dir.join("build.sh")).unwrap();
+        assert!(build.contains("release/word_stats.wasm plugin.wasm"));
+
+        // Only the entry is missing until build.sh has run.
+        let report = validate::validate(&dir, &[], &[]);
+        assert_eq!(report.plugin.as_deref(), Some("word-stats 0.1.0"));
+        assert_eq!(report.findings.len(), 1, "{:?}", report.findings);
+        assert_eq!(report.findings[0].severity, Severity::Error);
+        assert!(
+            report.findings[0]
+                .message
+                .ends_with("plugin.wasm does not exist")
+        );
+
+        assert!(
+            scaffold(&dir, "word-stats")
+                .unwrap_err()
+                .ends_with("already exists")
+        );
+        assert!(check_name("Word Stats").is_err());
+        assert!(check_name("9lives").is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! `:plugins.validate`: check a plugin's `plugin.toml` the way its author
+//! wants to hear about it, every problem at once rather than the first one
+//! discovery trips over.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::plugin::manifest::PluginManifest;
+use crate::plugin::permission::Permission;
+
+/// Modes a keybinding may name.
+const KEYBINDING_MODES: &[&str] = &["normal", "insert", "visual", "sidebar"];
+
+/// Keys written `<Name>` in a keybinding, besides single characters and
+/// function keys.
+const NAMED_KEYS: &[&str] = &[
+    "leader", "space", "tab", "cr", "enter", "esc", "bs", "del", "up", "down", "left", "right",
+    "home", "end", "pageup", "pagedown", "lt", "bar",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// What [`validate`] found in one plugin.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub manifest_path: PathBuf,
+    /// `name version` when the manifest parsed.
+    pub plugin: Option<String>,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+
+    /// `2 errors, 1 warning`, or `no problems`.
+    pub fn summary(&self) -> String {
+        let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+        match (self.count(Severity::Error), self.count(Severity::Warning)) {
+            (0, 0) => "no problems".to_string(),
+            (errors, warnings) => format!(
+                "{}, {}",
+                plural(errors, "error"),
+                plural(warnings, "warning")
+            ),
+        }
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message);
+    }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message);
+    }
+
+    fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        self.findings.push(Finding {
+            severity,
+            message: message.into(),
+        });
+    }
+}
+
+/// Check the plugin at `path`, a plugin directory or its `plugin.toml`.
+/// `reserved` are the command line's built-in commands and `others` the
+/// `(plugin, command)` pairs already loaded, for reporting collisions.
+pub fn validate(path: &Path, reserved: &[&str], others: &[(String, String)]) -> Report {
+    let (root, manifest_path) = if path.is_dir() {
+        (path.to_path_buf(), path.join("plugin.toml"))
+    } else {
+        (
+            path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            path.to_path_buf(),
+        )
+    };
+    let mut report = Report {
+        manifest_path: manifest_path.clone(),
+        plugin: None,
+        findings: Vec::new(),
+    };
+
+    let raw = match std::fs::read_to_string(&manifest_path) {
+        Ok(raw) => raw,
+        Err(err) => {
+            report.error(format!("cannot read {}: {err}", manifest_path.display()));
+            return report;
+        }
+    };
+    let mut table: toml::Table = match toml::from_str(&raw) {
+        Ok(table) => table,
+        Err(err) => {
+            report.error(format!("not valid TOML: {}", err.message()));
+            return report;
+        }
+    };
+
+    // Checked one by one so a typo does not hide the rest of the manifest.
+    let permissions = check_permissions(&mut report, table.remove("permissions"));
+    let mut manifest = match PluginManifest::deserialize(toml::Value::Table(table)) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            report.error(err.message().to_string());
+            return report;
+        }
+    };
+    manifest.permissions = permissions;
+    report.plugin = Some(format!("{} {}", manifest.name, manifest.version));
+
+    if manifest.name.trim().is_empty() {
+        report.error("name is empty");
+    }
+    if let Err(err) = manifest.negotiate() {
+        report.error(format!("api_version {}: {err}", manifest.api_version));
+    }
+    check_entry(&mut report, &root, &manifest.entry);
+    check_commands(&mut report, &manifest, reserved, others);
+    check_keybindings(&mut report, &manifest);
+    for (idx, completion) in manifest.completions.iter().enumerate() {
+        if completion.trigger.is_empty() {
+            report.error(format!("completion {}: trigger is empty", idx + 1));
+        }
+    }
+    let network = manifest.permissions.contains(&Permission::Network);
+    if network && manifest.allowed_hosts.is_empty() {
+        report.warn("the network permission has no allowed_hosts to reach");
+    } else if !network && !manifest.allowed_hosts.is_empty() {
+        report.warn("allowed_hosts is set without the network permission");
+    }
+    report
+}
+
+/// The known permissions in `value`, reporting the rest.
+fn check_permissions(report: &mut Report, value: Option<toml::Value>) -> Vec<Permission> {
+    let items = match value {
+        None => return Vec::new(),
+        Some(toml::Value::Array(items)) => items,
+        Some(_) => {
+            report.error("permissions must be a list of names");
+            return Vec::new();
+        }
+    };
+    let mut permissions = Vec::new();
+    for item in items {
+        let shown = item.to_string();
+        match Permission::deserialize(item) {
+            Ok(permission) if permissions.contains(&permission) => {
+                report.warn(format!("permission {shown} is listed twice"));
+            }
+            Ok(permission) => permissions.push(permission),
+            Err(_) => report.error(format!("unknown permission {shown}")),
+        }
+    }
+    permissions
+}
+
+fn check_entry(report: &mut Report, root: &Path, entry: &str) {
+    let entry_path = root.join(entry);
+    if !entry_path.is_file() {
+        report.error(format!("entry {} does not exist", entry_path.display()));
+        return;
+    }
+    let ext = entry_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    if !matches!(ext.as_deref(), Some("wasm" | "lua")) {
+        report.warn(format!(
+            "entry {entry} is neither .wasm nor .lua; it is loaded as WASM"
+        ));
+    }
+}
+
+fn check_commands(
+    report: &mut Report,
+    manifest: &PluginManifest,
+    reserved: &[&str],
+    others: &[(String, String)],
+) {
+    let mut seen = HashSet::new();
+    for command in &manifest.commands {
+        let name = command.name.as_str();
+        if name.trim().is_empty() {
+            report.error("a command has an empty name");
+            continue;
+        }
+        if !seen.insert(name) {
+            report.error(format!("command {name} is declared twice"));
+            continue;
+        }
+        if reserved.contains(&name) {
+            report.warn(format!(
+                "command {name} is shadowed by a built-in; it runs as {}.{name}",
+                manifest.name
+            ));
+        }
+        for (plugin, _) in others
+            .iter()
+            .filter(|(plugin, other)| other == name && *plugin != manifest.name)
+        {
+            report.warn(format!(
+                "command {name} is also declared by {plugin}; each runs as <plugin>.{name}"
+            ));
+        }
+    }
+}
+
+fn check_keybindings(report: &mut Report, manifest: &PluginManifest) {
+    if !manifest.keybindings.is_empty() && !manifest.permissions.contains(&Permission::BindKeys) {
+        report.error("keybindings need the bind_keys permission");
+    }
+    for (idx, binding) in manifest.keybindings.iter().enumerate() {
+        let subject = format!("keybinding {} ({:?})", idx + 1, binding.keys);
+        if !KEYBINDING_MODES.contains(&binding.mode.as_str()) {
+            report.error(format!(
+                "{subject}: mode {:?} is not one of {}",
+                binding.mode,
+                KEYBINDING_MODES.join(", ")
+            ));
+        }
+        if let Err(err) = check_keys(&binding.keys) {
+            report.error(format!("{subject}: {err}"));
+        }
+        if !manifest
+            .commands
+            .iter()
+            .any(|command| command.name == binding.action)
+        {
+            report.error(format!(
+                "{subject}: action {:?} is not one of the plugin's commands",
+                binding.action
+            ));
+        }
+    }
+}
+
+/// Keys are characters typed as they are, or `<Name>` for a named key,
+/// with `C-`, `A-` or `S-` in front for modifiers: `<leader>wc`, `<C-k>`.
+fn check_keys(keys: &str) -> Result<(), String> {
+    if keys.is_empty() {
+        return Err("keys are empty".to_string());
+    }
+    let mut rest = keys;
+    while let Some(open) = rest.find('<') {
+        let Some(len) = rest[open..].find('>') else {
+            return Err(format!("unclosed < in {keys:?}"));
+        };
+        let mut name = &rest[open + 1..open + len];
+        while let Some(key) = ["C-", "A-", "S-", "c-", "a-", "s-"]
+            .iter()
+            .find_map(|modifier| name.strip_prefix(modifier))
+        {
+            name = key;
+        }
+        let lower = name.to_lowercase();
+        let function_key = lower
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u8>().ok())
+            .is_some_and(|n| (1..=12).contains(&n));
+        if name.chars().count() != 1 && !function_key && !NAMED_KEYS.contains(&lower.as_str()) {
+            return Err(format!("unknown key <{}>", &rest[open + 1..open + len]));
+        }
+        rest = &rest[open + len + 1..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_every_problem() {
+        let tmp = crate::test_util::temp_dir();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("plugin.toml"),
+            r#"
+name = "tidy"
+version = "0.2.0"
+entry = "tidy.wasm"
+permissions = ["read_buffer", "netwrok", "read_buffer"]
+allowed_hosts = ["example.com"]
+
+[[commands]]
+name = "stats"
+[[commands]]
+name = "tidy"
+[[commands]]
+name = "tidy"
+[[commands]]
+name = "fmt"
+
+[[keybindings]]
+mode = "normal"
+keys = "<leader>t"
+action = "tidy"
+[[keybindings]]
+mode = "command"
+keys = "<C-Hyper>"
+action = "format"
+"#,
+        )
+        .unwrap();
+        let others = [("prettier".to_string(), "fmt".to_string())];
+        let report = validate(&dir, &["stats", "help"], &others);
+        let messages: Vec<(Severity, &str)> = report
+            .findings
+            .iter()
+            .map(|finding| (finding.severity, finding.message.as_str()))
+            .collect();
+        let entry = format!("entry {} does not exist", dir.join("tidy.wasm").display());
+        assert_eq!(
+            messages,
+            [
+                (Severity::Error, "unknown permission \"netwrok\""),
+                (
+                    Severity::Warning,
+                    "permission \"read_buffer\" is listed twice"
+                ),
+                (Severity::Error, entry.as_str()),
+                (
+                    Severity::Warning,
+                    "command stats is shadowed by a built-in; it runs as tidy.stats"
+                ),
+                (Severity::Error, "command tidy is declared twice"),
+                (
+                    Severity::Warning,
+                    "command fmt is also declared by prettier; each runs as <plugin>.fmt"
+                ),
+                (Severity::Error, "keybindings need the bind_keys permission"),
+                (
+                    Severity::Error,
+                    "keybinding 2 (\"<C-Hyper>\"): mode \"command\" is not one of normal, insert, visual, sidebar"
+                ),
+                (
+                    Severity::Error,
+                    "keybinding 2 (\"<C-Hyper>\"): unknown key <C-Hyper>"
+                ),
+                (
+                    Severity::Error,
+                    "keybinding 2 (\"<C-Hyper>\"): action \"format\" is not one of the plugin's commands"
+                ),
+                (
+                    Severity::Warning,
+                    "allowed_hosts is set without the network permission"
+                ),
+            ]
+        );
+        assert_eq!(report.plugin.as_deref(), Some("tidy 0.2.0"));
+        assert_eq!(report.summary(), "7 errors, 4 warnings");
+
+        std::fs::write(dir.join("plugin.toml"), "name = \"tidy\"\nentry = 3\n").unwrap();
+        let report = validate(&dir.join("plugin.toml"), &[], &[]);
+        assert_eq!(report.count(Severity::Error), 1);
+        assert!(report.plugin.is_none());
+    }
+
+    #[test]
+    fn test_key_syntax() {
+        assert!(check_keys("gq").is_ok());
+        assert!(check_keys("<leader>wc").is_ok());
+        assert!(check_keys("<C-S-k><F5>").is_ok());
+        assert_eq!(check_keys(""), Err("keys are empty".to_string()));
+        assert_eq!(
+            check_keys("<C-k"),
+            Err("unclosed < in \"<C-k\"".to_string())
+        );
+        assert_eq!(check_keys("<F13>"), Err("unknown key <F13>".to_string()));
+    }
+}
@@ -0,0 +1,129 @@
+//! `--startup-profile`: wall-clock time spent in each startup phase, so
+//! "blackbox is slow to start" reports come with numbers attached.
+
+use std::time::{Duration, Instant};
+
+pub struct StartupProfile {
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    notes: Vec<String>,
+    ready: Option<Duration>,
+}
+
+impl Default for StartupProfile {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            phases: Vec::new(),
+            notes: Vec::new(),
+            ready: None,
+        }
+    }
+}
+
+impl StartupProfile {
+    /// Run `f`, recording how long it took under `phase`.
+    pub fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.phases.push((phase, start.elapsed()));
+        value
+    }
+
+    /// Extra context for the report, e.g. how many files the vault holds.
+    pub fn note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
+    /// Mark startup as done, so the total excludes time spent editing.
+    pub fn finish(&mut self) {
+        self.ready = Some(self.started.elapsed());
+    }
+
+    pub fn total(&self) -> Duration {
+        self.ready.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// One line per phase, slowest marked, then the notes and the total.
+    pub fn summary(&self) -> Vec<String> {
+        let slowest = self.phases.iter().map(|(_, took)| *took).max();
+        let mut lines = vec!["startup profile:".to_string()];
+        for (phase, took) in &self.phases {
+            let marker = if Some(*took) == slowest {
+                "  <- slowest"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "  {phase:<18} {:>9}{marker}",
+                format_duration(*took)
+            ));
+        }
+        for note in &self.notes {
+            lines.push(format!("  {note}"));
+        }
+        lines.push(format!(
+            "  {:<18} {:>9}",
+            "total",
+            format_duration(self.total())
+        ));
+        lines
+    }
+}
+
+fn format_duration(took: Duration) -> String {
+    format!("{:.1}ms", took.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_are_recorded_in_order() {
+        let mut profile = StartupProfile::default();
+        assert_eq!(profile.time("config", || 7), 7);
+        profile.time("vault", || std::thread::sleep(Duration::from_millis(5)));
+        assert_eq!(
+            profile
+                .phases
+                .iter()
+                .map(|(phase, _)| *phase)
+                .collect::<Vec<_>>(),
+            ["config", "vault"]
+        );
+        assert!(profile.phases[1].1 >= Duration::from_millis(5));
+
+        profile.finish();
+        let total = profile.total();
+        let timed: Duration = profile.phases.iter().map(|(_, took)| *took).sum();
+        assert!(total >= timed);
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(profile.total(), total);
+    }
+
+    #[test]
+    fn test_summary_marks_the_slowest_phase() {
+        let mut profile = StartupProfile {
+            phases: vec![
+                ("config", Duration::from_micros(300)),
+                ("search index", Duration::from_millis(12)),
+                ("plugins", Duration::from_millis(4)),
+            ],
+            ready: Some(Duration::from_millis(20)),
+            ..StartupProfile::default()
+        };
+        profile.note("vault: 42 files".to_string());
+        assert_eq!(
+            profile.summary(),
+            [
+                "startup profile:",
+                "  config                 0.3ms",
+                "  search index          12.0ms  <- slowest",
+                "  plugins                4.0ms",
+                "  vault: 42 files",
+                "  total                 20.0ms",
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Sync through the vault's own git repository.
+//!
+//! A pass commits what changed (with `auto_commit`), merges what the
+//! upstream branch gained and pushes local commits back (with `auto_push`).
+//! A merge that conflicts is aborted, so the working tree never holds
+//! conflict markers.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+
+use super::{SyncBackend, SyncResult};
+use crate::model::config::GitSyncConfig;
+use crate::model::journal;
+
+pub struct GitSync {
+    vault: PathBuf,
+    config: GitSyncConfig,
+}
+
+impl GitSync {
+    pub fn new(vault: PathBuf, config: GitSyncConfig) -> Self {
+        Self { vault, config }
+    }
+
+    /// Whether `vault` is inside a git work tree, checked without running
+    /// git.
+    pub fn is_repository(vault: &Path) -> bool {
+        vault.ancestors().any(|dir| dir.join(".git").exists())
+    }
+
+    fn git(&self, args: &[&str]) -> Result<Output> {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.vault)
+            .output()
+            .map_err(|err| anyhow!("cannot run git: {err}"))
+    }
+
+    /// Run git and return its trimmed stdout, failing with its last line
+    /// of stderr.
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let output = self.git(args)?;
+        if !output.status.success() {
+            return Err(anyhow!("git {}: {}", args[0], stderr_summary(&output)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn count(&self, range: &str) -> Result<usize> {
+        let count = self.run(&["rev-list", "--count", range])?;
+        count
+            .parse()
+            .map_err(|_| anyhow!("git rev-list: unexpected output {count:?}"))
+    }
+
+    /// Commit every change under the vault. Returns the short hash, `None`
+    /// when there was nothing to commit.
+    fn commit(&self) -> Result<Option<String>> {
+        if self.run(&["status", "--porcelain", "--", "."])?.is_empty() {
+            return Ok(None);
+        }
+        self.run(&["add", "-A", "--", "."])?;
+        let message = commit_message(&self.config.commit_message_format, journal::now_secs());
+        self.run(&["commit", "--quiet", "--allow-empty-message", "-m", &message])?;
+        self.run(&["rev-parse", "--short", "HEAD"]).map(Some)
+    }
+
+    /// Merge what the upstream gained, aborting the merge on a conflict.
+    fn pull(&self) -> Result<Pull> {
+        self.run(&["fetch", "--quiet"])?;
+        if self.count("HEAD..@{upstream}")? == 0 {
+            return Ok(Pull::Merged(Vec::new()));
+        }
+        let before = self.run(&["rev-parse", "HEAD"])?;
+        let merge = self.git(&["merge", "--quiet", "--no-edit", "@{upstream}"])?;
+        if !merge.status.success() {
+            let conflicts = self.run(&["diff", "--name-only", "--relative", "--diff-filter=U"])?;
+            if conflicts.is_empty() {
+                return Err(anyhow!("git merge: {}", stderr_summary(&merge)));
+            }
+            self.run(&["merge", "--abort"])?;
+            return Ok(Pull::Conflict(paths(&conflicts)));
+        }
+        let changed = self.run(&["diff", "--name-only", "--relative", &before, "HEAD"])?;
+        Ok(Pull::Merged(paths(&changed)))
+    }
+}
+
+/// How a pull ended, with vault-relative paths.
+enum Pull {
+    /// The notes the merge changed, none when already up to date.
+    Merged(Vec<PathBuf>),
+    /// The notes both sides changed; the merge was aborted.
+    Conflict(Vec<PathBuf>),
+}
+
+impl SyncBackend for GitSync {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        (self.config.pull_interval_mins > 0)
+            .then(|| Duration::from_secs(self.config.pull_interval_mins * 60))
+    }
+
+    fn after_save(&self) -> Option<Duration> {
+        (self.config.auto_commit || self.config.auto_push)
+            .then(|| Duration::from_secs(self.config.commit_delay_secs))
+    }
+
+    fn sync(&self) -> Result<SyncResult> {
+        let committed = if self.config.auto_commit {
+            self.commit()?
+        } else {
+            None
+        };
+
+        // Without an upstream branch there is nothing to pull from or push to.
+        let has_upstream = self
+            .git(&["rev-parse", "--abbrev-ref", "@{upstream}"])?
+            .status
+            .success();
+        let mut pulled = Vec::new();
+        let mut pushed = false;
+        if has_upstream {
+            pulled = match self.pull()? {
+                Pull::Merged(pulled) => pulled,
+                Pull::Conflict(conflicts) => return Ok(SyncResult::Conflict(conflicts)),
+            };
+            if self.config.auto_push && self.count("@{upstream}..HEAD")? > 0 {
+                self.run(&["push", "--quiet"])?;
+                pushed = true;
+            }
+        }
+
+        if committed.is_none() && pulled.is_empty() && !pushed {
+            return Ok(SyncResult::NoChanges);
+        }
+        Ok(SyncResult::Git {
+            committed,
+            pulled,
+            pushed,
+        })
+    }
+}
+
+/// `format` with `{timestamp}` as `YYYY-MM-DD HH:MM:SS` (UTC).
+fn commit_message(format: &str, timestamp: u64) -> String {
+    let secs = timestamp % 86_400;
+    let stamp = format!(
+        "{} {:02}:{:02}:{:02}",
+        journal::format_day(timestamp),
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    );
+    format.replace("{timestamp}", &stamp)
+}
+
+fn paths(lines: &str) -> Vec<PathBuf> {
+    lines
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn stderr_summary(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("failed")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+    }
+
+    /// A clone of `remote` that can commit.
+    fn clone(remote: &Path, dir: &Path) {
+        git(
+            remote.parent().unwrap(),
+            &[
+                "clone",
+                "--quiet",
+                remote.to_str().unwrap(),
+                dir.to_str().unwrap(),
+            ],
+        );
+        git(dir, &["config", "user.name", "BlackBox"]);
+        git(dir, &["config", "user.email", "blackbox@example.com"]);
+        git(dir, &["config", "commit.gpgsign", "false"]);
+    }
+
+    fn sync(vault: &Path, auto_push: bool) -> SyncResult {
+        let config = GitSyncConfig {
+            auto_commit: true,
+            auto_push,
+            commit_message_format: "blackbox: auto-save {timestamp}".to_string(),
+            pull_interval_mins: 0,
+            commit_delay_secs: 30,
+        };
+        GitSync::new(vault.to_path_buf(), config).sync().unwrap()
+    }
+
+    #[test]
+    fn test_commit_message_timestamp() {
+        assert_eq!(
+            commit_message("auto-save {timestamp}", 1_700_000_000),
+            "auto-save 2023-11-14 22:13:20"
+        );
+    }
+
+    #[test]
+    fn test_push_pull_and_conflicts_between_clones() {
+        let tmp = crate::test_util::temp_dir();
+        let root = tmp.path().to_path_buf();
+        let remote = root.join("remote.git");
+        std::fs::create_dir_all(&remote).unwrap();
+        git(
+            &remote,
+            &["init", "--quiet", "--bare", "--initial-branch=main"],
+        );
+        let (laptop, desktop) = (root.join("laptop"), root.join("desktop"));
+        clone(&remote, &laptop);
+        clone(&remote, &desktop);
+
+        // The first push creates the branch both clones then track.
+        std::fs::write(laptop.join("idea.md"), "# Idea\n").unwrap();
+        git(&laptop, &["add", "idea.md"]);
+        git(&laptop, &["commit", "--quiet", "-m", "first"]);
+        git(&laptop, &["push", "--quiet", "-u", "origin", "main"]);
+        git(&desktop, &["pull", "--quiet", "origin", "main"]);
+        git(&desktop, &["branch", "--quiet", "-u", "origin/main"]);
+        assert_eq!(sync(&desktop, true), SyncResult::NoChanges);
+
+        std::fs::write(laptop.join("idea.md"), "# Idea\n\nMore.\n").unwrap();
+        std::fs::write(laptop.join("todo.md"), "- [ ] ship\n").unwrap();
+        let SyncResult::Git {
+            committed, pushed, ..
+        } = sync(&laptop, true)
+        else {
+            panic!("expected a commit");
+        };
+        assert!(committed.is_some());
+        assert!(pushed);
+
+        let SyncResult::Git { pulled, pushed, .. } = sync(&desktop, false) else {
+            panic!("expected a pull");
+        };
+        assert_eq!(pulled, [PathBuf::from("idea.md"), PathBuf::from("todo.md")]);
+        assert!(!pushed);
+        assert_eq!(
+            std::fs::read_to_string(desktop.join("todo.md")).unwrap(),
+            "- [ ] ship\n"
+        );
+
+        // Both change the same line: the merge is aborted and reported.
+        std::fs::write(laptop.join("todo.md"), "- [x] ship\n").unwrap();
+        sync(&laptop, true);
+        std::fs::write(desktop.join("todo.md"), "- [ ] ship it\n").unwrap();
+        assert_eq!(
+            sync(&desktop, true),
+            SyncResult::Conflict(vec![PathBuf::from("todo.md")])
+        );
+        assert_eq!(
+            std::fs::read_to_string(desktop.join("todo.md")).unwrap(),
+            "- [ ] ship it\n"
+        );
+    }
+}
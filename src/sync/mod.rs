@@ -0,0 +1,85 @@
+//! Vault sync backends.
+//!
+//! Backends shell out to external tools (git, restic) rather than linking
+//! them, and always run on a background thread; the main loop only sees the
+//! final [`SyncResult`] as a `Msg::SyncFinished`.
+
+pub mod git;
+pub mod restic;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::model::config::AppConfig;
+
+pub trait SyncBackend: Send + Sync {
+    /// Short name shown in the status bar and notifications.
+    fn name(&self) -> &'static str;
+    /// How often the backend wants to run in the background, if at all.
+    fn interval(&self) -> Option<Duration>;
+    /// How long after a save the backend wants a pass, if at all.
+    fn after_save(&self) -> Option<Duration> {
+        None
+    }
+    /// Run one sync pass. Blocking; never call this on the main thread.
+    fn sync(&self) -> Result<SyncResult>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncResult {
+    NoChanges,
+    /// A git pass: the commit it made, the vault-relative notes a pull
+    /// changed and whether it pushed.
+    Git {
+        committed: Option<String>,
+        pulled: Vec<PathBuf>,
+        pushed: bool,
+    },
+    /// Notes changed on both sides, vault-relative; nothing was merged.
+    Conflict(Vec<PathBuf>),
+    Snapshot {
+        id: String,
+        changed: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    Idle,
+    Syncing,
+    Error(String),
+}
+
+/// Build the backend selected by `sync.backend`.
+///
+/// Returns `Ok(None)` for backends that do not run through this layer yet,
+/// and for git when the vault is not in a repository.
+pub fn backend_from_config(
+    config: &AppConfig,
+) -> std::result::Result<Option<Arc<dyn SyncBackend>>, String> {
+    match config.sync.backend.as_str() {
+        "restic" => {
+            let Some(restic) = config.sync.restic.clone() else {
+                return Err("sync: backend = \"restic\" needs a [sync.restic] section".to_string());
+            };
+            Ok(Some(Arc::new(restic::ResticSync::new(
+                config.vault_path(),
+                restic,
+            ))))
+        }
+        "git" => {
+            let Some(git) = config.sync.git.clone() else {
+                return Err("sync: backend = \"git\" needs a [sync.git] section".to_string());
+            };
+            let vault = config.vault_path();
+            if !git::GitSync::is_repository(&vault) {
+                return Ok(None);
+            }
+            Ok(Some(Arc::new(git::GitSync::new(vault, git))))
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,181 @@
+//! Off-site backup through restic.
+//!
+//! restic encrypts and deduplicates every snapshot client side, and reaches
+//! S3, B2, SFTP and (through its rclone backend) WebDAV or anything else
+//! rclone speaks. The repository is initialised on the first run.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+
+use super::{SyncBackend, SyncResult};
+use crate::model::config::ResticSyncConfig;
+
+/// restic exits with 10 when the repository does not exist (0.17+); older
+/// releases only say so on stderr.
+const EXIT_NO_REPOSITORY: i32 = 10;
+
+pub struct ResticSync {
+    vault: PathBuf,
+    config: ResticSyncConfig,
+}
+
+impl ResticSync {
+    pub fn new(vault: PathBuf, config: ResticSyncConfig) -> Self {
+        Self { vault, config }
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.config.binary);
+        cmd.arg("--repo").arg(&self.config.repository);
+        if let Some(password_command) = &self.config.password_command {
+            cmd.arg("--password-command").arg(password_command);
+        }
+        cmd
+    }
+
+    fn backup(&self) -> Result<Output> {
+        let mut cmd = self.command();
+        cmd.args([
+            "backup",
+            "--json",
+            "--exclude",
+            ".git",
+            "--exclude",
+            ".trash",
+        ])
+        .arg(&self.vault);
+        for pattern in &self.config.exclude {
+            cmd.arg("--exclude").arg(pattern);
+        }
+        cmd.output()
+            .map_err(|err| anyhow!("cannot run {}: {err}", self.config.binary))
+    }
+
+    fn init(&self) -> Result<()> {
+        let output = self
+            .command()
+            .arg("init")
+            .output()
+            .map_err(|err| anyhow!("cannot run {}: {err}", self.config.binary))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!("restic init: {}", stderr_summary(&output)))
+        }
+    }
+}
+
+impl SyncBackend for ResticSync {
+    fn name(&self) -> &'static str {
+        "restic"
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        (self.config.interval_mins > 0).then(|| Duration::from_secs(self.config.interval_mins * 60))
+    }
+
+    fn sync(&self) -> Result<SyncResult> {
+        let mut output = self.backup()?;
+        if !output.status.success() && repository_missing(&output) {
+            self.init()?;
+            output = self.backup()?;
+        }
+
+        if !output.status.success() {
+            return Err(anyhow!("restic backup: {}", stderr_summary(&output)));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_backup_summary(&stdout).ok_or_else(|| anyhow!("restic backup: no summary in output"))
+    }
+}
+
+fn repository_missing(output: &Output) -> bool {
+    if output.status.code() == Some(EXIT_NO_REPOSITORY) {
+        return true;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr.contains("Is there a repository at the following location?")
+        || stderr.contains("repository does not exist")
+}
+
+fn stderr_summary(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("failed")
+        .trim()
+        .to_string()
+}
+
+/// Pull the snapshot id and changed file count from `restic backup --json`.
+fn parse_backup_summary(stdout: &str) -> Option<SyncResult> {
+    let summary = stdout
+        .lines()
+        .rev()
+        .find(|line| line.contains("\"message_type\":\"summary\""))?;
+
+    let changed = json_number_field(summary, "files_new").unwrap_or(0)
+        + json_number_field(summary, "files_changed").unwrap_or(0);
+    if changed == 0 {
+        return Some(SyncResult::NoChanges);
+    }
+
+    let id = json_string_field(summary, "snapshot_id")?;
+    Some(SyncResult::Snapshot {
+        id: id.chars().take(8).collect(),
+        changed,
+    })
+}
+
+// restic's summary line is flat JSON, so a full parser is not worth the
+// dependency.
+fn json_string_field(line: &str, key: &str) -> Option<String> {
+    let needle = format!("\"{key}\":\"");
+    let start = line.find(&needle)? + needle.len();
+    let end = line[start..].find('"')?;
+    Some(line[start..start + end].to_string())
+}
+
+fn json_number_field(line: &str, key: &str) -> Option<usize> {
+    let needle = format!("\"{key}\":");
+    let start = line.find(&needle)? + needle.len();
+    let digits: String = line[start..]
+        .chars()
+        .take_while(|ch| ch.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backup_summary() {
+        let stdout = concat!(
+            "{\"message_type\":\"status\",\"percent_done\":0.5}\n",
+            "{\"message_type\":\"summary\",\"files_new\":2,\"files_changed\":1,",
+            "\"files_unmodified\":40,\"snapshot_id\":\"4f2a9c1de0b7\"}\n"
+        );
+        assert_eq!(
+            parse_backup_summary(stdout),
+            Some(SyncResult::Snapshot {
+                id: "4f2a9c1d".to_string(),
+                changed: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_backup_summary_without_changes() {
+        let stdout = "{\"message_type\":\"summary\",\"files_new\":0,\"files_changed\":0,\"snapshot_id\":\"abc\"}\n";
+        assert_eq!(parse_backup_summary(stdout), Some(SyncResult::NoChanges));
+        assert_eq!(parse_backup_summary("not json"), None);
+    }
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by unit tests.
+
+use std::path::Path;
+
+use tempfile::TempDir;
+
+use crate::app::App;
+use crate::model::config::AppConfig;
+use crate::profile::StartupProfile;
+
+/// A fresh directory, removed with everything in it when the guard drops.
+pub fn temp_dir() -> TempDir {
+    tempfile::tempdir().expect("failed to create temp dir")
+}
+
+/// An app on the default config with `vault` open, in safe mode so no
+/// plugins or hooks from the machine running the tests load.
+pub fn app_in(vault: &Path) -> App {
+    let mut config: AppConfig =
+        toml::from_str(include_str!("../config/default.toml")).expect("defaults should parse");
+    config.general.vault_path = vault.to_string_lossy().into_owned();
+    config.safe_mode = true;
+    App::new(
+        config,
+        std::sync::mpsc::channel().0,
+        &mut StartupProfile::default(),
+    )
+    .expect("app should start")
+}
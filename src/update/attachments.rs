@@ -0,0 +1,160 @@
+use crate::app::App;
+use crate::clipboard;
+use crate::model::attachment;
+use crate::model::config::dirs_home;
+use crate::model::journal;
+use crate::update::note_ops::rel_display;
+use std::path::{Path, PathBuf};
+
+impl App {
+    /// Hand an image, PDF or other binary to the system viewer.
+    pub(crate) fn open_attachment(&mut self, path: &Path) {
+        let shown = rel_display(&self.config.vault_path(), path);
+        match crate::opener::open(&path.to_string_lossy()) {
+            Ok(()) => self.push_notification(format!("Opened {shown} externally")),
+            Err(err) => self.push_notification(format!("cannot open {shown}: {err}")),
+        }
+    }
+
+    /// `:attach <path>`: copy a file into `general.attachments` and link it
+    /// at the cursor. Files already in the vault are linked where they are.
+    pub(crate) fn attach_command(&mut self, args: &str) -> Vec<String> {
+        // Terminals quote dropped paths that contain spaces.
+        let arg = args.trim().trim_matches(|c| c == '\'' || c == '"');
+        if arg.is_empty() {
+            return vec!["usage: attach <path>".to_string()];
+        }
+        let Some(note) = self.buffer.path.clone() else {
+            return vec!["attach: save the note first".to_string()];
+        };
+        if self.reject_readonly_edit() {
+            return Vec::new();
+        }
+        let source = match (arg.strip_prefix("~/"), dirs_home()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(arg),
+        };
+        if !source.is_file() {
+            return vec![format!("attach: {arg} is not a file")];
+        }
+
+        let root = self.config.vault_path();
+        let source = source.canonicalize().unwrap_or(source);
+        let vault = root.canonicalize().unwrap_or_else(|_| root.clone());
+        let stored = if let Ok(rel) = source.strip_prefix(&vault) {
+            root.join(rel)
+        } else {
+            let dir = root.join(&self.config.general.attachments);
+            let name = source
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let target = attachment::target(&dir, &name, Path::exists);
+            if let Err(err) =
+                std::fs::create_dir_all(&dir).and_then(|()| std::fs::copy(&source, &target))
+            {
+                return vec![format!("attach: cannot copy {arg}: {err}")];
+            }
+            if let Err(err) = self.file_tree.refresh() {
+                self.push_notification(format!("attach: cannot refresh the sidebar: {err}"));
+            }
+            target
+        };
+
+        let note_dir = note.parent().unwrap_or(&root);
+        self.buffer
+            .insert_text(&attachment::link_text(note_dir, &stored));
+        self.schedule_auto_save();
+        self.mark_render_dirty();
+        vec![format!("attach: linked {}", rel_display(&root, &stored))]
+    }
+
+    /// Save an image on the clipboard into `general.attachments` under a
+    /// timestamped name and embed it at the cursor as `![[name.png]]`.
+    /// Returns whether the clipboard held an image.
+    pub(crate) fn paste_clipboard_image(&mut self) -> bool {
+        let Some(png) = clipboard::read_image() else {
+            return false;
+        };
+        self.paste_image(&png, journal::now_secs());
+        true
+    }
+
+    /// Save `png`, pasted at `timestamp`, as an attachment and embed it at
+    /// the cursor.
+    fn paste_image(&mut self, png: &[u8], timestamp: u64) {
+        let root = self.config.vault_path();
+        let dir = root.join(&self.config.general.attachments);
+        let name = attachment::pasted_image_name(timestamp);
+        let target = attachment::target(&dir, &name, Path::exists);
+        if let Err(err) = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&target, png))
+        {
+            self.push_notification(format!("paste: cannot save image: {err}"));
+            return;
+        }
+        if let Err(err) = self.file_tree.refresh() {
+            self.push_notification(format!("paste: cannot refresh the sidebar: {err}"));
+        }
+
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or(name);
+        self.buffer.insert_text(&format!("![[{name}]]"));
+        self.buffer.scroll_to_cursor();
+        self.schedule_auto_save();
+        self.mark_render_dirty();
+        self.push_notification(format!("pasted image as {}", rel_display(&root, &target)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{app_in, temp_dir};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\npixels";
+
+    #[test]
+    fn test_pasted_image_is_saved_and_embedded() {
+        let vault = temp_dir();
+        let note = vault.path().join("note.md");
+        std::fs::write(&note, "See \n").unwrap();
+        let mut app = app_in(vault.path());
+        app.open_file(note).unwrap();
+        app.buffer.cursor.move_to(0, 4);
+
+        let at = 1_760_450_400 + 3_723;
+        app.paste_image(PNG, at);
+        app.paste_image(PNG, at);
+        let dir = vault.path().join(&app.config.general.attachments);
+        for name in [
+            "pasted-image-20251014150203.png",
+            "pasted-image-20251014150203-2.png",
+        ] {
+            assert_eq!(std::fs::read(dir.join(name)).unwrap(), PNG, "{name}");
+        }
+        // The second paste in the same second gets its own name.
+        assert_eq!(
+            app.buffer.line_text(0).unwrap(),
+            "See ![[pasted-image-20251014150203.png]]![[pasted-image-20251014150203-2.png]]"
+        );
+        assert!(app.buffer.dirty);
+    }
+
+    #[test]
+    fn test_pasted_image_that_cannot_be_saved_is_not_embedded() {
+        let vault = temp_dir();
+        let mut app = app_in(vault.path());
+        // A file where the attachments folder should be.
+        std::fs::write(vault.path().join(&app.config.general.attachments), "").unwrap();
+        app.notifications.clear();
+
+        app.paste_image(PNG, 0);
+        assert_eq!(app.buffer.rope.len_chars(), 0);
+        assert!(
+            app.notifications[0].starts_with("paste: cannot save image: "),
+            "{:?}",
+            app.notifications
+        );
+    }
+}
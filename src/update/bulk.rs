@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use crate::app::{App, ConfirmAction, PendingConfirm};
+use crate::model::config::dirs_home;
+use crate::model::frontmatter::Frontmatter;
+use crate::model::mode::Mode;
+use crate::update::note_ops::{inside_vault, plural, rel_display};
+
+impl App {
+    /// Finder `Tab`: mark or unmark the selected result's note and move on.
+    pub(crate) fn toggle_finder_mark(&mut self) {
+        let Some(result) = self.finder_results.get(self.finder_selected) else {
+            return;
+        };
+        match self
+            .finder_marked
+            .iter()
+            .position(|path| *path == result.path)
+        {
+            Some(idx) => {
+                self.finder_marked.remove(idx);
+            }
+            None => self.finder_marked.push(result.path.clone()),
+        }
+        self.finder_selected =
+            (self.finder_selected + 1).min(self.finder_results.len().saturating_sub(1));
+        self.mark_render_dirty();
+    }
+
+    /// Finder `Enter` with marks: make them the `:bulk` targets and open the
+    /// command line for an action.
+    pub(crate) fn begin_bulk_from_finder(&mut self) {
+        self.bulk_targets = std::mem::take(&mut self.finder_marked);
+        self.finder_query.clear();
+        self.finder_results.clear();
+        self.finder_selected = 0;
+        self.command_input = "bulk ".to_string();
+        self.mode = Mode::Command;
+        self.mark_render_dirty();
+    }
+
+    /// `:bulk <action>` on the notes marked in the finder.
+    pub(crate) fn bulk_command(&mut self, args: &str) -> Vec<String> {
+        if self.bulk_targets.is_empty() {
+            return vec!["bulk: no notes marked (Tab marks finder results)".to_string()];
+        }
+        let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let rest = rest.trim();
+        match action {
+            "tag" if !rest.is_empty() => self.bulk_tag(rest, true),
+            "untag" if !rest.is_empty() => self.bulk_tag(rest, false),
+            "move" if !rest.is_empty() => self.propose_bulk_move(rest),
+            "archive" => self.propose_bulk_move(&self.config.general.archive.clone()),
+            "export" if !rest.is_empty() => self.bulk_export(rest),
+            "" => {
+                let root = self.config.vault_path();
+                let names: Vec<String> = self
+                    .bulk_targets
+                    .iter()
+                    .map(|path| rel_display(&root, path))
+                    .collect();
+                vec![format!(
+                    "bulk: {} marked: {}",
+                    names.len(),
+                    names.join(", ")
+                )]
+            }
+            _ => vec![
+                "usage: bulk tag <tag> | untag <tag> | move <folder> | archive | export <dir>"
+                    .to_string(),
+            ],
+        }
+    }
+
+    /// Add or remove a frontmatter tag on every target.
+    fn bulk_tag(&mut self, tag: &str, add: bool) -> Vec<String> {
+        let tag = tag.trim_start_matches('#');
+        let mut changed = 0;
+        let mut notes = Vec::new();
+        for path in self.bulk_targets.clone() {
+            let Some(text) = self.note_text(&path) else {
+                notes.push(format!("bulk: cannot read {}", path.display()));
+                continue;
+            };
+            let mut frontmatter = Frontmatter::parse(&text).unwrap_or_default();
+            if !frontmatter.edit_list("tags", tag, add) {
+                continue;
+            }
+            match self.replace_note_text(&path, &frontmatter.replace_in(&text)) {
+                Ok(()) => changed += 1,
+                Err(err) => notes.push(format!("bulk: cannot write {}: {err}", path.display())),
+            }
+        }
+        if let Err(err) = self.file_tree.refresh() {
+            notes.push(format!("bulk: cannot refresh the sidebar: {err}"));
+        }
+        self.mark_render_dirty();
+
+        let verb = if add { "tagged" } else { "untagged" };
+        notes.insert(
+            0,
+            format!("bulk: {verb} {changed} note{} #{tag}", plural(changed)),
+        );
+        notes
+    }
+
+    fn propose_bulk_move(&mut self, folder: &str) -> Vec<String> {
+        let folder = folder.trim_matches('/').to_string();
+        if !folder.is_empty() && !inside_vault(&folder) {
+            return vec![format!("bulk: {folder} must stay inside the vault")];
+        }
+        let count = self.bulk_targets.len();
+        let shown = if folder.is_empty() { "/" } else { &folder };
+        self.pending_confirm = Some(PendingConfirm {
+            prompt: format!(
+                "move {count} note{} to {shown}, fixing links to them?",
+                plural(count)
+            ),
+            action: ConfirmAction::MoveNotes(folder),
+        });
+        self.mode = Mode::Confirm;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn apply_bulk_move(&mut self, folder: &str) -> Vec<String> {
+        let targets = self.bulk_targets.clone();
+        let (moved, mut notes) = self.move_notes(&targets, folder);
+        for (from, to) in &moved {
+            for target in &mut self.bulk_targets {
+                if target == from {
+                    *target = to.clone();
+                }
+            }
+        }
+        let shown = if folder.is_empty() { "/" } else { folder };
+        notes.insert(
+            0,
+            format!(
+                "bulk: moved {} note{} to {shown}",
+                moved.len(),
+                plural(moved.len())
+            ),
+        );
+        notes
+    }
+
+    /// Copy every target, unsaved edits included, into `dir` under its
+    /// vault-relative path.
+    fn bulk_export(&mut self, dir: &str) -> Vec<String> {
+        let dir = match (dir.strip_prefix("~/"), dirs_home()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(dir),
+        };
+        let root = self.config.vault_path();
+        if dir.starts_with(&root) {
+            return vec!["bulk: export to a folder outside the vault".to_string()];
+        }
+
+        let mut exported = 0;
+        let mut notes = Vec::new();
+        for path in self.bulk_targets.clone() {
+            let Some(text) = self.note_text(&path) else {
+                notes.push(format!("bulk: cannot read {}", path.display()));
+                continue;
+            };
+            let target = dir.join(path.strip_prefix(&root).unwrap_or(&path));
+            let written = target
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&target, text));
+            match written {
+                Ok(()) => exported += 1,
+                Err(err) => notes.push(format!("bulk: cannot write {}: {err}", target.display())),
+            }
+        }
+        notes.insert(
+            0,
+            format!(
+                "bulk: exported {exported} note{} to {}",
+                plural(exported),
+                dir.display()
+            ),
+        );
+        notes
+    }
+}
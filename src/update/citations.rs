@@ -0,0 +1,182 @@
+use crate::app::{App, CitationCompletion, LinkPreview};
+use crate::model::bibtex::{self, Bibliography};
+use crate::model::mode::Mode;
+use crate::model::picker;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Candidates shown while typing a citation.
+const COMPLETION_LIMIT: usize = 8;
+
+impl App {
+    /// Load the configured bibliography, or reload it after it changed.
+    pub(crate) fn refresh_bibliography(&mut self) {
+        let Some(path) = self.config.bibliography_path() else {
+            self.bibliography = None;
+            return;
+        };
+        if self
+            .bibliography
+            .as_ref()
+            .is_some_and(|bib| bib.path == path && !bib.is_stale())
+        {
+            return;
+        }
+        let failed = Some((path.clone(), bibtex::modified(&path)));
+        if self.bibliography.is_none() && self.bibliography_failed == failed {
+            return;
+        }
+        match Bibliography::load(&path) {
+            Ok(bib) => {
+                self.bibliography = Some(bib);
+                self.bibliography_failed = None;
+            }
+            Err(err) => {
+                self.bibliography = None;
+                self.bibliography_failed = failed;
+                self.push_notification(err);
+            }
+        }
+        self.mark_render_dirty();
+    }
+
+    /// `:cite`: the full reference for the citation under the cursor.
+    pub(crate) fn cite_command(&mut self) -> Vec<String> {
+        if self.config.bibliography_path().is_none() {
+            return vec!["cite: set citations.bibliography in the config".to_string()];
+        }
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let Some(key) = bibtex::citation_at(&line, self.buffer.cursor.col) else {
+            return vec!["cite: no @key under the cursor".to_string()];
+        };
+        self.refresh_bibliography();
+        let Some(bib) = self.bibliography.as_ref() else {
+            return vec!["cite: the bibliography could not be loaded".to_string()];
+        };
+        let Some(entry) = bib.get(key) else {
+            let name = bib.path.file_name().unwrap_or_default().to_string_lossy();
+            return vec![format!("cite: @{key} is not in {name}")];
+        };
+
+        self.link_preview = Some(LinkPreview {
+            title: format!("@{}", entry.key),
+            path: bib.path.clone(),
+            line: entry.line,
+            lines: entry.reference(),
+            scroll: 0,
+        });
+        self.mode = Mode::LinkPreview;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    /// Offer keys for the `@partial` before the cursor, or close the list
+    /// when the cursor is not in one.
+    pub(crate) fn update_citation_completion(&mut self) {
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let Some(partial) = bibtex::partial_before(&line, self.buffer.cursor.col) else {
+            self.citation_completion = None;
+            return;
+        };
+        if self.config.bibliography_path().is_none() {
+            return;
+        }
+        self.refresh_bibliography();
+        let Some(bib) = self.bibliography.as_ref() else {
+            return;
+        };
+
+        let entries: Vec<(String, String)> = bib
+            .entries
+            .iter()
+            .map(|entry| (entry.key.clone(), entry.summary()))
+            .collect();
+        let candidates = picker::rank(
+            entries,
+            partial,
+            |(key, summary)| format!("{key} {summary}"),
+            COMPLETION_LIMIT,
+        );
+        self.citation_completion = (!candidates.is_empty()).then_some(CitationCompletion {
+            candidates,
+            selected: 0,
+        });
+    }
+
+    /// Keys that steer an open completion list. Returns whether the key was
+    /// used; anything else goes on to be typed.
+    pub(crate) fn handle_key_citation_completion(&mut self, key: KeyEvent) -> bool {
+        let Some(completion) = self.citation_completion.as_mut() else {
+            return false;
+        };
+        let len = completion.candidates.len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => completion.selected = (completion.selected + 1) % len,
+            KeyCode::Char('n') if ctrl => completion.selected = (completion.selected + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => {
+                completion.selected = (completion.selected + len - 1) % len;
+            }
+            KeyCode::Char('p') if ctrl => {
+                completion.selected = (completion.selected + len - 1) % len;
+            }
+            KeyCode::Enter => {
+                let key = completion.candidates[completion.selected].0.clone();
+                self.accept_citation(&key);
+            }
+            KeyCode::Esc => self.citation_completion = None,
+            _ => return false,
+        }
+        self.mark_render_dirty();
+        true
+    }
+
+    /// Replace the partial key before the cursor with `key`.
+    fn accept_citation(&mut self, key: &str) {
+        self.citation_completion = None;
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let Some(partial) = bibtex::partial_before(&line, self.buffer.cursor.col) else {
+            return;
+        };
+        let end = self.buffer.cursor_char_offset();
+        let start = end - partial.chars().count();
+        self.buffer.replace_chars(start..end, key);
+        self.schedule_auto_save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{app_in, temp_dir};
+
+    #[test]
+    fn test_missing_bibliography_is_reported_once() {
+        let vault = temp_dir();
+        let mut app = app_in(vault.path());
+        app.config.citations.bibliography = "refs.bib".to_string();
+        app.notifications.clear();
+
+        for _ in 0..3 {
+            app.refresh_bibliography();
+        }
+        assert_eq!(app.notifications.len(), 1, "{:?}", app.notifications);
+        assert!(app.bibliography.is_none());
+
+        std::fs::write(
+            vault.path().join("refs.bib"),
+            "@book{knuth,\n  title = {TAOCP},\n}\n",
+        )
+        .unwrap();
+        app.refresh_bibliography();
+        assert!(app.bibliography.as_ref().unwrap().get("knuth").is_some());
+        assert_eq!(app.notifications.len(), 1);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::app::App;
+use crate::clipboard;
+use crate::model::cursor::Position;
+use crate::model::link;
+use crate::model::mode::Mode;
+use crate::model::outline;
+use crate::model::{block, journal};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// Text for `p`: the system clipboard when reachable, else the register.
+    fn paste_source(&self) -> String {
+        clipboard::read()
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| self.register.clone())
+    }
+
+    pub(crate) fn yank_selection(&mut self) {
+        let linewise = self.mode == Mode::VisualLine;
+        let Some(text) = self.buffer.selected_text(linewise) else {
+            return;
+        };
+
+        let chars = text.chars().count();
+        if let Err(err) = clipboard::write(&text) {
+            tracing::debug!("clipboard: {err}");
+        }
+        self.set_register(text);
+        self.buffer.cursor.clear_selection();
+        self.mode = Mode::Normal;
+        self.push_notification(format!("yanked {chars} chars"));
+        self.mark_render_dirty();
+    }
+
+    /// Keep yanked text for `p` and `:yanks`.
+    fn set_register(&mut self, text: String) {
+        self.yank_history.push(text.clone());
+        self.register = text;
+    }
+
+    /// `p` in Normal mode: insert after the character under the cursor.
+    pub(crate) fn paste_after_cursor(&mut self) {
+        let text = self.paste_source();
+        if text.is_empty() {
+            self.push_notification("clipboard is empty".to_string());
+            return;
+        }
+        self.paste_text_after_cursor(&text);
+    }
+
+    /// Ctrl+V in Insert mode: an image on the clipboard becomes an
+    /// attachment, anything else is pasted at the cursor.
+    pub(crate) fn paste_in_insert(&mut self) {
+        if self.reject_readonly_edit() || self.paste_clipboard_image() {
+            return;
+        }
+        let text = self.paste_source();
+        if text.is_empty() {
+            self.push_notification("clipboard is empty".to_string());
+            return;
+        }
+        self.insert_pasted(&text);
+    }
+
+    fn paste_text_after_cursor(&mut self, text: &str) {
+        let at = self.buffer.cursor_char_offset();
+        if self.buffer.rope.get_char(at).is_some_and(|ch| ch != '\n') {
+            self.buffer.set_cursor_char_offset(at + 1);
+        }
+        self.insert_pasted(text);
+    }
+
+    /// `:yanks`: pick an earlier yank or delete to paste.
+    pub(crate) fn open_yank_picker(&mut self) -> Vec<String> {
+        if self.yank_history.entries().is_empty() {
+            return vec!["yanks: nothing yanked or deleted yet".to_string()];
+        }
+        self.yank_selected = 0;
+        self.mode = Mode::YankPicker;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_yanks(&mut self, key: KeyEvent) -> Result<()> {
+        let count = self.yank_history.entries().len();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.yank_selected = (self.yank_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.yank_selected = self.yank_selected.saturating_sub(1);
+            }
+            KeyCode::Enter => self.paste_yank(self.yank_selected),
+            KeyCode::Char(ch @ '1'..='9') => {
+                let choice = ch as usize - '1' as usize;
+                if choice < count {
+                    self.paste_yank(choice);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// Paste history entry `idx` after the cursor; it becomes the register
+    /// so a following `p` repeats it.
+    fn paste_yank(&mut self, idx: usize) {
+        self.mode = Mode::Normal;
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let Some(text) = self.yank_history.promote(idx) else {
+            return;
+        };
+        if let Err(err) = clipboard::write(&text) {
+            tracing::debug!("clipboard: {err}");
+        }
+        self.register = text.clone();
+        self.paste_text_after_cursor(&text);
+    }
+
+    /// Bracketed paste from the terminal, routed by mode.
+    pub(crate) fn handle_paste(&mut self, text: String) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        match self.mode {
+            Mode::Normal | Mode::Insert => {
+                if self.reject_readonly_edit() {
+                    return;
+                }
+                // Terminals paste an image-only clipboard as empty text.
+                if text.is_empty() && self.mode == Mode::Insert && self.paste_clipboard_image() {
+                    return;
+                }
+                self.insert_pasted(&text);
+            }
+            Mode::Visual | Mode::VisualLine => self.paste_over_selection(&text),
+            Mode::Command => {
+                self.command_input.push_str(&text.replace('\n', " "));
+                self.mark_render_dirty();
+            }
+            Mode::FinderOpen => {
+                self.finder_query.push_str(&text.replace('\n', " "));
+                if let Err(err) = self.refresh_finder_results() {
+                    self.push_notification(format!("finder: {err}"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn paste_from_clipboard_over_selection(&mut self) {
+        let text = self.paste_source();
+        self.paste_over_selection(&text);
+    }
+
+    /// Replace the selection with pasted text, except that a URL wraps it as
+    /// `[selection](url)` and a note name as `[[note|selection]]`.
+    pub(crate) fn paste_over_selection(&mut self, text: &str) {
+        let linewise = self.mode == Mode::VisualLine;
+        if self.reject_readonly_edit() {
+            self.exit_visual();
+            return;
+        }
+        let Some(selected) = self.buffer.selected_text(linewise) else {
+            return;
+        };
+
+        let pasted = text.trim();
+        let single_line = !selected.trim().contains('\n') && !pasted.contains('\n');
+        let replacement = if single_line && link::is_url(pasted) {
+            link::wrap_trimmed(&selected, |label| link::markdown_link(label, pasted))
+        } else if let Some(note) = single_line
+            .then(|| self.resolve_wikilink_target(pasted))
+            .flatten()
+            .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        {
+            link::wrap_trimmed(&selected, |label| link::wikilink(&note, label))
+        } else {
+            text.to_string()
+        };
+
+        self.buffer.replace_selection(linewise, &replacement);
+        self.finish_paste();
+    }
+
+    /// Visual `L`: turn the selection into `[[selection]]`.
+    pub(crate) fn wrap_selection_as_wikilink(&mut self) {
+        let linewise = self.mode == Mode::VisualLine;
+        if self.reject_readonly_edit() {
+            self.exit_visual();
+            return;
+        }
+        let Some(selected) = self.buffer.selected_text(linewise) else {
+            return;
+        };
+        if selected.trim().contains('\n') || selected.trim().is_empty() {
+            self.push_notification("link: select text on a single line".to_string());
+            return;
+        }
+
+        let replacement = link::wrap_trimmed(&selected, |label| link::wikilink(label, label));
+        self.buffer.replace_selection(linewise, &replacement);
+        self.finish_paste();
+    }
+
+    /// `copy.wikilink`, `copy.link` and `copy.uri`: a reference to the
+    /// active note, pointing at the cursor's section with `--heading` (or `#`).
+    pub(crate) fn copy_note_reference(&mut self, kind: &str, args: &str) -> Vec<String> {
+        let with_heading = match args {
+            "" => false,
+            "--heading" | "#" => true,
+            _ => return vec![format!("usage: copy.{kind} [--heading]")],
+        };
+        let Some(path) = self.buffer.path.clone() else {
+            return vec!["copy: buffer has no file".to_string()];
+        };
+
+        let vault = self.config.vault_path();
+        let rel = path
+            .strip_prefix(&vault)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let stem = path
+            .file_stem()
+            .map_or_else(|| rel.clone(), |s| s.to_string_lossy().into_owned());
+
+        let heading = if with_heading {
+            let lines = self.buffer_lines();
+            let headings = outline::headings(lines.iter().map(String::as_str));
+            let (heading, _) = outline::section_at(&headings, self.buffer.cursor.row, lines.len());
+            match heading {
+                Some(heading) => Some(heading.title.clone()),
+                None => return vec!["copy: cursor is not under a heading".to_string()],
+            }
+        } else {
+            None
+        };
+
+        let text = match kind {
+            "wikilink" => match &heading {
+                Some(heading) => format!("[[{stem}#{heading}]]"),
+                None => format!("[[{stem}]]"),
+            },
+            "link" => {
+                let mut target = link::percent_encode(&rel);
+                if let Some(heading) = &heading {
+                    target.push('#');
+                    target.push_str(&link::heading_slug(heading));
+                }
+                link::markdown_link(heading.as_deref().unwrap_or(&stem), &target)
+            }
+            _ => link::note_uri(&rel, heading.as_deref()),
+        };
+
+        let note = match clipboard::write(&text) {
+            Ok(_) => format!("copied {text}"),
+            Err(err) => format!("copied {text} to register only: {err}"),
+        };
+        self.set_register(text);
+        vec![note]
+    }
+
+    /// `:block`: give the paragraph under the cursor a `^id` (keeping one it
+    /// already has) and copy a `[[Note#^id]]` link to it.
+    pub(crate) fn block_command(&mut self) -> Vec<String> {
+        let Some(path) = self.buffer.path.clone() else {
+            return vec!["block: buffer has no file".to_string()];
+        };
+        let lines = self.buffer_lines();
+        let Some(rows) = block::paragraph_at(&lines, self.buffer.cursor.row) else {
+            return vec!["block: cursor is not in a paragraph".to_string()];
+        };
+        let last = rows.end - 1;
+        let id = match block::marker(&lines[last]) {
+            Some(id) => id.to_string(),
+            None => {
+                if self.reject_readonly_edit() {
+                    return Vec::new();
+                }
+                let taken = |id: &str| lines.iter().any(|line| block::marker(line) == Some(id));
+                let id = block::new_id(journal::now_secs() ^ last as u64, taken);
+                let cursor = self.buffer.cursor.position();
+                let end = self.buffer.position_to_char(Position {
+                    row: last,
+                    col: lines[last].trim_end().len(),
+                });
+                let end_of_line = self.buffer.position_to_char(Position {
+                    row: last,
+                    col: lines[last].len(),
+                });
+                self.buffer
+                    .replace_chars(end..end_of_line, &format!(" ^{id}"));
+                self.buffer.cursor.move_to(cursor.row, cursor.col);
+                self.schedule_auto_save();
+                self.mark_render_dirty();
+                id
+            }
+        };
+
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let text = format!("[[{stem}#^{id}]]");
+        let note = match clipboard::write(&text) {
+            Ok(_) => format!("block ^{id}: copied {text}"),
+            Err(err) => format!("block ^{id}: copied {text} to register only: {err}"),
+        };
+        self.set_register(text);
+        vec![note]
+    }
+
+    /// `Space y` / `:copy.code`: copy the fenced block under the cursor,
+    /// without its fences and with a list item's indent removed.
+    pub(crate) fn copy_code_block(&mut self) -> Vec<String> {
+        let Some(block) = self.code_block_at_cursor() else {
+            return vec!["copy: cursor is not in a code block".to_string()];
+        };
+        let lines = self.buffer_lines();
+        let body = block.body(&lines);
+
+        let mut text = body.join("\n");
+        if !body.is_empty() {
+            text.push('\n');
+        }
+        let lang = if block.lang == "text" {
+            "plain"
+        } else {
+            block.lang.as_str()
+        };
+        let count = body.len();
+        let noun = if count == 1 { "line" } else { "lines" };
+        let note = match clipboard::write(&text) {
+            Ok(_) => format!("copied {lang} block ({count} {noun})"),
+            Err(err) => format!("copied {lang} block ({count} {noun}) to register only: {err}"),
+        };
+        self.set_register(text);
+        vec![note]
+    }
+
+    fn insert_pasted(&mut self, text: &str) {
+        self.buffer.insert_text(text);
+        self.finish_paste();
+    }
+
+    fn finish_paste(&mut self) {
+        self.buffer.clamp_cursor();
+        self.buffer.scroll_to_cursor();
+        if self.mode.is_visual() {
+            self.mode = Mode::Normal;
+        }
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{app_in, temp_dir};
+
+    #[test]
+    fn test_block_marks_the_paragraph_and_copies_a_link() {
+        let vault = temp_dir();
+        let note = vault.path().join("note.md");
+        std::fs::write(
+            &note,
+            "# Note\n\nfirst line\nsecond line   \n\nother ^keep\n",
+        )
+        .unwrap();
+        let mut app = app_in(vault.path());
+        app.open_file(note).unwrap();
+
+        app.buffer.cursor.move_to(1, 0);
+        assert_eq!(app.block_command(), ["block: cursor is not in a paragraph"]);
+
+        app.buffer.cursor.move_to(2, 3);
+        let notes = app.block_command();
+        let line = app.buffer.line_text(3).unwrap();
+        let (text, id) = line.rsplit_once(" ^").unwrap();
+        assert_eq!(text, "second line");
+        assert!(!id.is_empty() && !id.contains(char::is_whitespace), "{id}");
+        let link = format!("[[note#^{id}]]");
+        assert!(notes[0].starts_with(&format!("block ^{id}: copied {link}")));
+        assert_eq!(app.register, link);
+        assert_eq!(app.yank_history.entries().front(), Some(&link));
+        assert_eq!((app.buffer.cursor.row, app.buffer.cursor.col), (2, 3));
+
+        // A paragraph that has an id keeps it, untouched.
+        app.buffer.cursor.move_to(5, 0);
+        assert!(app.block_command()[0].starts_with("block ^keep: copied [[note#^keep]]"));
+        assert_eq!(app.buffer.line_text(5).unwrap(), "other ^keep");
+        assert_eq!(app.register, "[[note#^keep]]");
+    }
+}
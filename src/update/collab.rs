@@ -0,0 +1,293 @@
+use crate::app::{App, same_file_path};
+use crate::collab::ot;
+use crate::collab::session::{CollabEvent, CollabSession, RemoteUpdate, Role};
+use crate::model::buffer::Buffer;
+use crate::update::workflow::workflow_locked;
+use ropey::Rope;
+
+/// Loopback only; hosting for the LAN takes an explicit address.
+const DEFAULT_COLLAB_ADDR: &str = "127.0.0.1:7878";
+
+impl App {
+    pub(crate) fn collab_command(&mut self, args: &str) -> Vec<String> {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => vec![self.collab_summary()],
+            (Some("host"), addr, None) => self.collab_host(addr.unwrap_or(DEFAULT_COLLAB_ADDR)),
+            (Some("join"), Some(addr), Some(token)) => self.collab_join(addr, token),
+            (Some("stop"), None, None) => self.collab_stop(),
+            _ => vec!["usage: collab [host [addr] | join <addr> <token> | stop]".to_string()],
+        }
+    }
+
+    fn collab_summary(&self) -> String {
+        let Some(session) = self.collab.as_ref() else {
+            return "collab: inactive".to_string();
+        };
+
+        let role = match session.role {
+            Role::Host => "hosting",
+            Role::Client => "joined",
+        };
+        let note = session
+            .path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "(waiting for snapshot)".to_string());
+        let peer = session.peer.as_deref().unwrap_or("no peer yet");
+        let token = session
+            .token
+            .as_deref()
+            .map(|token| format!(" (token {token})"))
+            .unwrap_or_default();
+        format!("collab: {role} {note} with {peer}{token}")
+    }
+
+    fn collab_host(&mut self, addr: &str) -> Vec<String> {
+        if self.collab.is_some() {
+            return vec!["collab: session already active (collab stop first)".to_string()];
+        }
+        let Some(path) = self.buffer.path.clone() else {
+            return vec!["collab: active buffer has no file to share".to_string()];
+        };
+
+        match CollabSession::host(
+            addr,
+            path,
+            self.buffer.rope.to_string(),
+            self.event_tx.clone(),
+        ) {
+            Ok((session, local)) => {
+                let token = session.token.clone().unwrap_or_default();
+                self.collab = Some(session);
+                vec![format!(
+                    "collab: hosting on {local}, waiting for a peer to run: collab join {local} {token}"
+                )]
+            }
+            Err(err) => vec![format!("collab: cannot listen on {addr}: {err}")],
+        }
+    }
+
+    fn collab_join(&mut self, addr: &str, token: &str) -> Vec<String> {
+        if self.collab.is_some() {
+            return vec!["collab: session already active (collab stop first)".to_string()];
+        }
+        if self.buffer.has_unkept_text() {
+            return vec!["collab: the current buffer has unsaved text and no file".to_string()];
+        }
+        self.collab = Some(CollabSession::join(addr, token, self.event_tx.clone()));
+        vec![format!("collab: connecting to {addr}")]
+    }
+
+    fn collab_stop(&mut self) -> Vec<String> {
+        match self.collab.take() {
+            Some(mut session) => {
+                session.stop();
+                self.mark_render_dirty();
+                vec!["collab: session ended".to_string()]
+            }
+            None => vec!["collab: inactive".to_string()],
+        }
+    }
+
+    pub(crate) fn handle_collab_event(&mut self, event: CollabEvent) {
+        if self.collab.is_none() {
+            return;
+        }
+
+        match event {
+            CollabEvent::Connected {
+                peer,
+                stream,
+                writer,
+            } => {
+                self.collab_flush_local();
+                if let Some(session) = self.collab.as_mut() {
+                    session.attach(peer.clone(), stream, writer);
+                }
+                self.push_notification(format!("collab: connected to {peer}"));
+            }
+            CollabEvent::Line(line) => {
+                self.collab_flush_local();
+                let Some(update) = self.collab.as_mut().map(|s| s.remote_line(&line)) else {
+                    return;
+                };
+                match update {
+                    RemoteUpdate::None => {}
+                    RemoteUpdate::Apply(ops) => self.collab_apply_remote(&ops),
+                    RemoteUpdate::Snapshot { name, text } => {
+                        self.collab_open_snapshot(&name, &text)
+                    }
+                }
+                self.mark_render_dirty();
+            }
+            CollabEvent::Disconnected(peer) => {
+                // A client turned away never receives the snapshot.
+                let refused = self
+                    .collab
+                    .as_ref()
+                    .is_some_and(|session| session.role == Role::Client && session.path.is_none());
+                if let Some(session) = self.collab.as_mut() {
+                    session.detach();
+                }
+                if refused {
+                    self.push_notification(format!(
+                        "collab: {peer} closed the connection; check the pairing token"
+                    ));
+                } else {
+                    self.push_notification(format!("collab: {peer} disconnected"));
+                }
+                self.mark_render_dirty();
+            }
+            CollabEvent::Failed(err) => {
+                self.collab = None;
+                self.push_notification(format!("collab: {err}"));
+            }
+        }
+    }
+
+    /// Push pending local edits and cursor moves to the peer.
+    pub(crate) fn collab_flush_local(&mut self) {
+        let Some(path) = self.collab.as_ref().and_then(|s| s.path.clone()) else {
+            return;
+        };
+
+        let is_active = self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|p| same_file_path(p, &path));
+        let Some(buffer) = self.collab_buffer(&path) else {
+            return;
+        };
+        let text = buffer.rope.to_string();
+        let cursor = buffer.cursor_char_offset();
+
+        if let Some(session) = self.collab.as_mut() {
+            session.local_change(&text);
+            if is_active {
+                session.local_cursor(cursor);
+            }
+        }
+    }
+
+    pub(crate) fn collab_peer_cursor(&self) -> Option<(usize, usize)> {
+        let session = self.collab.as_ref()?;
+        let path = session.path.as_ref()?;
+        let active = self.buffer.path.as_ref()?;
+        if !same_file_path(active, path) {
+            return None;
+        }
+
+        let offset = session.peer_cursor?.min(self.buffer.rope.len_chars());
+        let row = self.buffer.rope.char_to_line(offset);
+        let col = self.buffer.rope.char_to_byte(offset) - self.buffer.rope.line_to_byte(row);
+        Some((row, col))
+    }
+
+    fn collab_buffer(&self, path: &std::path::Path) -> Option<&Buffer> {
+        if self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|p| same_file_path(p, &path.to_path_buf()))
+        {
+            return Some(&self.buffer);
+        }
+        self.inactive_buffers.get(path)
+    }
+
+    fn collab_apply_remote(&mut self, ops: &[ot::Op]) {
+        let Some(path) = self.collab.as_ref().and_then(|s| s.path.clone()) else {
+            return;
+        };
+
+        let buffer = if self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|p| same_file_path(p, &path))
+        {
+            &mut self.buffer
+        } else if let Some(buffer) = self.inactive_buffers.get_mut(&path) {
+            buffer
+        } else {
+            return;
+        };
+
+        // A note locked by its workflow state keeps its text; the next
+        // flush sends the peer the ops that undo its edit.
+        if workflow_locked(buffer, &self.config.workflow) {
+            self.push_notification(
+                "collab: the shared note is read-only; the peer's edit was undone".to_string(),
+            );
+            self.collab_flush_local();
+            return;
+        }
+
+        let first_pos = ops
+            .iter()
+            .filter_map(|op| match op {
+                ot::Op::Insert { pos, .. } | ot::Op::Delete { pos, .. } => Some(*pos),
+                ot::Op::Noop => None,
+            })
+            .min();
+        if let Some(pos) = first_pos {
+            let row = buffer.rope.char_to_line(pos.min(buffer.rope.len_chars()));
+            buffer.mark_changed(row);
+        }
+
+        let cursor = ot::transform_offset(buffer.cursor_char_offset(), ops);
+        ot::apply(&mut buffer.rope, ops);
+        buffer.set_cursor_char_offset(cursor);
+        buffer.dirty = true;
+        self.schedule_auto_save_for(&path);
+    }
+
+    fn collab_open_snapshot(&mut self, name: &str, text: &str) {
+        let file_name = std::path::Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "shared.md".to_string());
+        let path = self.config.vault_path().join(".collab").join(file_name);
+        if let Some(parent) = path.parent()
+            && let Err(err) = std::fs::create_dir_all(parent)
+        {
+            self.push_notification(format!("collab: {}: {err}", parent.display()));
+            return;
+        }
+        // Typed into since joining; the shared note would replace it.
+        if self.buffer.has_unkept_text() {
+            self.collab_stop();
+            self.push_notification(
+                "collab: left the session, the current buffer has unsaved text and no file"
+                    .to_string(),
+            );
+            return;
+        }
+
+        let mut buffer = Buffer::new();
+        buffer.rope = Rope::from_str(text);
+        buffer.path = Some(path.clone());
+        buffer.dirty = true;
+        buffer.viewport = self.buffer.viewport.clone();
+        buffer.viewport.top_line = 0;
+
+        // Only a clean buffer or a report is dropped here.
+        let previous = std::mem::replace(&mut self.buffer, buffer);
+        if let Some(active_path) = previous.path.clone() {
+            self.inactive_buffers.insert(active_path, previous);
+        }
+        self.inactive_buffers.remove(&path);
+        if !self.open_tabs.iter().any(|p| same_file_path(p, &path)) {
+            self.open_tabs.push(path.clone());
+        }
+
+        if let Some(session) = self.collab.as_mut() {
+            session.bind_path(path);
+        }
+        self.schedule_auto_save();
+        self.push_notification(format!("collab: editing shared {name}"));
+    }
+}
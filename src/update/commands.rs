@@ -0,0 +1,387 @@
+use crate::app::{App, parse_plugin_command_input};
+use crate::model::picker::{self, Completion};
+
+/// Commands the command line handles itself, ahead of any plugin command
+/// of the same name. Kept in step with `handle_plugin_command`.
+pub(crate) const BUILTIN_COMMANDS: &[&str] = &[
+    "help",
+    "plugin",
+    "p",
+    "plugins",
+    "pl",
+    "plugins.update",
+    "pl.update",
+    "plugins.list",
+    "pl.list",
+    "plugins.errors",
+    "pl.errors",
+    "plugins.logs",
+    "pl.logs",
+    "plugins.enable",
+    "pl.enable",
+    "plugins.browse",
+    "pl.browse",
+    "plugins.validate",
+    "pl.validate",
+    "plugins.new",
+    "pl.new",
+    "plugins.sync",
+    "pl.sync",
+    "plugins.reload",
+    "pl.reload",
+    "workflow",
+    "wf",
+    "workflow.next",
+    "wf.next",
+    "workflow.prev",
+    "wf.prev",
+    "workflow.set",
+    "wf.set",
+    "collab",
+    "vault",
+    "sync",
+    "sync.status",
+    "changes",
+    "count",
+    "langs",
+    "problems",
+    "quickfix",
+    "qf",
+    "quickfix.next",
+    "qf.next",
+    "quickfix.prev",
+    "qf.prev",
+    "outline",
+    "toc",
+    "backlinks",
+    "split",
+    "sp",
+    "only",
+    "diagram",
+    "mermaid",
+    "zen",
+    "yanks",
+    "capture",
+    "rename",
+    "move",
+    "delete",
+    "reveal",
+    "graph",
+    "orphans",
+    "recent",
+    "stale",
+    "stats",
+    "tasks",
+    "kanban",
+    "props",
+    "cite",
+    "attach",
+    "block",
+    "extract",
+    "merge",
+    "links",
+    "pin",
+    "bulk",
+    "sidebar.group",
+    "set",
+    "minimap",
+    "spell",
+    "copy.wikilink",
+    "copy.link",
+    "copy.uri",
+    "copy.code",
+];
+
+impl App {
+    /// Tab / Shift+Tab on the command line: complete a plugin command name
+    /// or a note or folder argument, cycling through the matches on
+    /// repeated presses.
+    pub(crate) fn complete_command(&mut self, delta: isize) {
+        if let Some(completion) = self.command_completion.as_mut() {
+            completion.cycle(delta);
+            self.command_input = completion.input();
+            return;
+        }
+        let files = self.file_tree.all_file_paths();
+        let root = self.config.vault_path();
+        let limit = self.config.search.max_results;
+        let mut details = Vec::new();
+        let (head, candidates) = if let Some((head, partial)) =
+            picker::folder_argument(&self.command_input)
+        {
+            (
+                head,
+                picker::folder_candidates(&root, &files, partial, limit),
+            )
+        } else if let Some((head, partial)) = picker::note_argument(&self.command_input) {
+            (head, picker::note_candidates(&root, &files, partial, limit))
+        } else if let Some((head, partial)) = picker::command_name_argument(&self.command_input) {
+            let commands = self.plugin_manager.command_palette();
+            let (names, descriptions) =
+                picker::rank(commands, partial.trim(), |c| c.0.clone(), limit)
+                    .into_iter()
+                    .map(|(name, description)| (name, description.unwrap_or_default()))
+                    .unzip();
+            details = descriptions;
+            (head, names)
+        } else {
+            return;
+        };
+        let completion = Completion {
+            head: head.to_string(),
+            candidates,
+            details,
+            selected: 0,
+        };
+        match completion.candidates.len() {
+            0 => {}
+            1 => self.command_input = completion.input(),
+            _ => {
+                self.command_input = completion.input();
+                self.command_completion = Some(completion);
+            }
+        }
+    }
+
+    pub(crate) fn handle_plugin_command(&mut self, command: String) {
+        let command = command.trim();
+        if command.is_empty() {
+            return;
+        }
+
+        let (name, args) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, args)| (name, args.trim()))
+            .unwrap_or((command, ""));
+
+        let notifications = if let Some(raw_plugin_command) = command
+            .strip_prefix("plugin ")
+            .or_else(|| command.strip_prefix("p "))
+        {
+            let (plugin_command, rest) = parse_plugin_command_input(raw_plugin_command);
+            match plugin_command.as_str() {
+                "install" => self.plugin_install_command(rest),
+                "uninstall" => self.plugin_uninstall_command(rest),
+                "" => vec!["usage: plugin <command> [args] (alias: p <command>)".to_string()],
+                _ => self.run_plugin_command(&plugin_command, rest.trim()),
+            }
+        } else {
+            match name {
+                "help" => {
+                    let mut notes = vec!["built-ins:".to_string()];
+                    notes.push("  help".to_string());
+                    notes.push("  plugin <command> [args] (alias: p <command>)".to_string());
+                    notes.push(
+                        "    examples: plugin word_count --words | plugin \"word count\" 'a b'"
+                            .to_string(),
+                    );
+                    notes.push("  plugin install <git-url> | plugin uninstall <name>".to_string());
+                    notes.push("  plugins (alias: pl)".to_string());
+                    notes.push("  plugins.list (alias: pl.list)".to_string());
+                    notes.push("  plugins.errors (alias: pl.errors)".to_string());
+                    notes.push("  plugins.logs <name> (alias: pl.logs)".to_string());
+                    notes.push("  plugins.enable <name> (alias: pl.enable)".to_string());
+                    notes.push("  plugins.browse (alias: pl.browse)".to_string());
+                    notes.push("  plugins.validate <path> (alias: pl.validate)".to_string());
+                    notes.push("  plugins.new <name> [dir] (alias: pl.new)".to_string());
+                    notes.push("  plugins.reload (alias: pl.reload)".to_string());
+                    notes.push("  plugins.sync (alias: pl.sync)".to_string());
+                    notes.push("  plugins.update [apply] (alias: pl.update)".to_string());
+                    notes.push("  workflow (alias: wf)".to_string());
+                    notes.push("  workflow.next | workflow.prev".to_string());
+                    notes.push("  workflow.set <state>".to_string());
+                    notes.push("  collab [host [addr] | join <addr> <token> | stop]".to_string());
+                    notes.push("  vault [name] (list or switch vaults)".to_string());
+                    notes.push("  sync | sync.status".to_string());
+                    notes.push("  changes [--since 7d]".to_string());
+                    notes.push("  count".to_string());
+                    notes.push("  langs".to_string());
+                    notes.push("  problems".to_string());
+                    notes.push("  quickfix [clear | diagnostics] (alias: qf)".to_string());
+                    notes.push("  quickfix.next | quickfix.prev (]q / [q)".to_string());
+                    notes.push("  outline".to_string());
+                    notes.push("  toc".to_string());
+                    notes.push("  backlinks (the tab's ↩ N count; Ctrl+B toggles)".to_string());
+                    notes.push("  diagram (alias: mermaid)".to_string());
+                    notes.push("  zen".to_string());
+                    notes.push("  minimap".to_string());
+                    notes.push("  set list | nolist | list!".to_string());
+                    notes.push("  split (alias: sp) | only".to_string());
+                    notes.push("  spell [on|off]".to_string());
+                    notes.push("  copy.wikilink | copy.link | copy.uri [--heading]".to_string());
+                    notes.push("  copy.code (Space y)".to_string());
+                    notes.push("  yanks".to_string());
+                    notes.push("  capture <text>".to_string());
+                    notes.push("  rename <name> | <folder/name> (sidebar r)".to_string());
+                    notes.push("  move <folder> | /".to_string());
+                    notes.push("  delete (sidebar d)".to_string());
+                    notes.push("  reveal (Space r)".to_string());
+                    notes.push("  graph [1|2] (Space l)".to_string());
+                    notes.push("  orphans".to_string());
+                    notes.push("  recent".to_string());
+                    notes.push("  stale [days] (notes unchanged the longest)".to_string());
+                    notes.push("  stats".to_string());
+                    notes.push("  extract <name> [--heading] (from Visual mode)".to_string());
+                    notes.push("  merge <note>".to_string());
+                    notes.push("  links check".to_string());
+                    notes.push("  pin".to_string());
+                    notes.push("  tasks [#tag|folder/]".to_string());
+                    notes.push("  kanban".to_string());
+                    notes.push("  props".to_string());
+                    notes.push("  cite (reference for the @key under the cursor)".to_string());
+                    notes.push(
+                        "  attach <path> (copy a file into the vault and link it)".to_string(),
+                    );
+                    notes.push("  block (give the paragraph a ^id and copy a link)".to_string());
+                    notes.push(
+                        "  bulk tag|untag <tag> | move <folder> | archive | export <dir> (Tab marks in the finder)"
+                            .to_string(),
+                    );
+                    notes.push("  sidebar.group tags | <field> | folders (sidebar v)".to_string());
+                    notes.extend(self.plugin_manager.command_notifications());
+                    notes
+                }
+                "plugins" | "pl" if args.is_empty() => {
+                    vec![self.plugin_manager.summary_notification()]
+                }
+                // `:plugins update` is `:plugins.update`, and so on.
+                "plugins" | "pl" => {
+                    self.handle_plugin_command(format!("{name}.{args}"));
+                    Vec::new()
+                }
+                "plugins.update" | "pl.update" => match args {
+                    "" => self.plugin_update_command(false),
+                    "apply" => self.plugin_update_command(true),
+                    _ => vec!["usage: plugins.update [apply]".to_string()],
+                },
+                "plugins.list" | "pl.list" => self.plugin_manager.list_notifications(),
+                "plugins.errors" | "pl.errors" => {
+                    let errors = self.plugin_manager.error_notifications();
+                    if errors.is_empty() {
+                        vec!["plugins: no errors".to_string()]
+                    } else {
+                        errors
+                    }
+                }
+                "plugins.logs" | "pl.logs" => self.plugin_logs_command(args),
+                "plugins.enable" | "pl.enable" => self.plugin_enable_command(args),
+                "plugins.browse" | "pl.browse" => self.plugin_browse_command(),
+                "plugins.validate" | "pl.validate" => self.plugin_validate_command(args),
+                "plugins.new" | "pl.new" => self.plugin_new_command(args),
+                "plugins.sync" | "pl.sync" => self.plugin_sync_command(),
+                "plugins.reload" | "pl.reload" => {
+                    if let Some(refused) = self.refuse_in_safe_mode("plugins reload") {
+                        refused
+                    } else {
+                        self.reload_plugins();
+                        let mut notes = vec!["plugins: reloaded".to_string()];
+                        notes.push(self.plugin_manager.summary_notification());
+                        notes.extend(self.plugin_manager.error_notifications());
+                        notes
+                    }
+                }
+                "workflow" | "wf" => vec![self.workflow_summary()],
+                "workflow.next" | "wf.next" => self.workflow_step(1),
+                "workflow.prev" | "wf.prev" => self.workflow_step(-1),
+                "workflow.set" | "wf.set" => self.workflow_set(args),
+                "collab" => self.collab_command(args),
+                "vault" => self.vault_command(args),
+                "sync" => self.sync_command(),
+                "sync.status" => vec![self.sync_summary()],
+                "changes" => self.changes_report(args),
+                "count" => vec![self.count_summary()],
+                "langs" => self.langs_report(),
+                "problems" => self.problems_report(),
+                "quickfix" | "qf" => self.quickfix_command(args),
+                "quickfix.next" | "qf.next" | "quickfix.prev" | "qf.prev" => {
+                    let delta = if name.ends_with("next") { 1 } else { -1 };
+                    match self.quickfix_step(delta) {
+                        Ok(()) => Vec::new(),
+                        Err(err) => vec![format!("quickfix: {err}")],
+                    }
+                }
+                "outline" => {
+                    self.toggle_outline_panel();
+                    Vec::new()
+                }
+                "toc" => self.insert_toc(),
+                "split" | "sp" => self.split_pane(),
+                "only" => {
+                    self.close_split();
+                    Vec::new()
+                }
+                "diagram" | "mermaid" => self.render_diagram_at_cursor(),
+                "zen" => {
+                    self.toggle_zen();
+                    Vec::new()
+                }
+                "yanks" => self.open_yank_picker(),
+                "capture" => self.capture(args),
+                "rename" => self.rename_command(args),
+                "move" => self.move_command(args),
+                "delete" => self.delete_command(),
+                "reveal" => self.reveal_active_note(),
+                "graph" => self.graph_command(args),
+                "orphans" => self.orphans_command(),
+                "recent" => self.recent_command(),
+                "stale" => self.stale_command(args),
+                "stats" => self.stats_command(),
+                "tasks" => self.tasks_command(args),
+                "kanban" => self.kanban_command(),
+                "props" => self.properties_command(),
+                "cite" => self.cite_command(),
+                "attach" => self.attach_command(args),
+                "block" => self.block_command(),
+                "extract" => self.extract_command(args),
+                "merge" => self.merge_command(args),
+                "links" => self.links_command(args),
+                "pin" => self.pin_command(),
+                "bulk" => self.bulk_command(args),
+                "sidebar.group" => self.sidebar_group(args),
+                "set" => self.set_option(args),
+                "minimap" => {
+                    self.set_minimap(!self.minimap_visible);
+                    Vec::new()
+                }
+                "backlinks" => match self.open_backlinks_panel() {
+                    Ok(()) => Vec::new(),
+                    Err(err) => vec![format!("backlinks: {err}")],
+                },
+                "spell" => self.spell_command(args),
+                "copy.wikilink" => self.copy_note_reference("wikilink", args),
+                "copy.link" => self.copy_note_reference("link", args),
+                "copy.uri" => self.copy_note_reference("uri", args),
+                "copy.code" => self.copy_code_block(),
+                // Plugin commands may contain spaces; otherwise what follows
+                // the name is passed on as arguments.
+                _ if self.plugin_manager.supports_command(command) => {
+                    self.run_plugin_command(command, "")
+                }
+                _ => self.run_plugin_command(name, args),
+            }
+        };
+
+        // A selection kept for the command line ends with the command,
+        // unless the command is still asking questions.
+        if self.plugin_prompt.is_none() && self.visual_command.take().is_some() {
+            self.buffer.cursor.clear_selection();
+        }
+        for notification in notifications {
+            self.push_notification(notification);
+        }
+    }
+
+    /// `:set <option>`, vim style: `list`, `nolist`, `list!` / `invlist`.
+    fn set_option(&mut self, args: &str) -> Vec<String> {
+        let show = match args {
+            "list" => true,
+            "nolist" => false,
+            "list!" | "invlist" => !self.show_invisibles,
+            "" => return vec!["usage: set list | nolist | list!".to_string()],
+            other => return vec![format!("set: unknown option {other}")],
+        };
+        self.show_invisibles = show;
+        self.mark_render_dirty();
+        vec![if show { "list" } else { "nolist" }.to_string()]
+    }
+}
@@ -0,0 +1,61 @@
+use crate::app::App;
+use crate::diagram::{self, Render};
+use crate::model::mode::Mode;
+use anyhow::Result;
+use crossterm::event::KeyEvent;
+use std::path::PathBuf;
+
+impl App {
+    /// `:diagram`: render the ```mermaid block under the cursor.
+    pub(crate) fn render_diagram_at_cursor(&mut self) -> Vec<String> {
+        let Some(block) = self.code_block_at_cursor().filter(|b| b.lang == "mermaid") else {
+            return vec!["diagram: cursor is not in a ```mermaid block".to_string()];
+        };
+        let lines = self.buffer_lines();
+        let source = block.body(&lines).join("\n");
+        if source.trim().is_empty() {
+            return vec!["diagram: block is empty".to_string()];
+        }
+
+        self.activities.start("diagram");
+        Render {
+            command: self.config.editor.mermaid_command.clone(),
+            source,
+            tx: self.event_tx.clone(),
+        }
+        .spawn();
+        vec!["diagram: rendering…".to_string()]
+    }
+
+    pub(crate) fn handle_diagram_rendered(&mut self, result: Result<PathBuf, String>) {
+        let path = match result {
+            Ok(path) => path,
+            Err(err) => {
+                self.push_notification(err);
+                return;
+            }
+        };
+
+        if diagram::kitty_graphics() {
+            self.diagram_preview = Some(path);
+            self.diagram_placed = false;
+            self.mode = Mode::DiagramPreview;
+            self.mark_render_dirty();
+        } else {
+            let shown = path.display().to_string();
+            match crate::opener::open(&shown) {
+                Ok(()) => self.push_notification(format!("diagram: opened {shown}")),
+                Err(err) => self.push_notification(format!("diagram: saved {shown} ({err})")),
+            }
+        }
+    }
+
+    /// Any key closes the preview.
+    pub(crate) fn handle_key_diagram(&mut self, _key: KeyEvent) -> Result<()> {
+        self.diagram_preview = None;
+        self.terminal_escape.push_str(diagram::KITTY_CLEAR);
+        self.mode = Mode::Normal;
+        self.mark_render_dirty();
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 use crate::app::{App, same_file_path, spawn_buffer_save};
+use crate::hooks::{HookEvent, Invocation};
 use crate::model::buffer::Buffer;
+use crate::model::mode::Mode;
+use crate::model::note_index::{NoteIndex, note_aliases};
+use crate::model::{attachment, capture, journal};
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::time::Instant;
 
@@ -34,6 +38,9 @@ impl App {
             self.save_inactive_buffer(&path);
         }
 
+        self.collab_flush_local();
+        self.schedule_background_sync(now);
+
         Ok(())
     }
     pub(crate) fn save_buffer(&mut self) -> Result<()> {
@@ -51,7 +58,54 @@ impl App {
         self.last_saved_file = Some((path.clone(), Instant::now()));
 
         let rope = self.buffer.rope.clone();
-        spawn_buffer_save(path, rope);
+        self.index_saved_note(&path, &rope);
+        self.decorate_buffer("save");
+        let hook = self.hook(HookEvent::Save, &path, None);
+        spawn_buffer_save(path, rope, hook);
+        self.schedule_sync_after_save();
+    }
+    /// `:capture <text>`: append a timestamped bullet to the inbox note
+    /// without switching to it. An open inbox gets the bullet in its buffer
+    /// so unsaved edits there are kept.
+    pub(crate) fn capture(&mut self, text: &str) -> Vec<String> {
+        if text.trim().is_empty() {
+            return vec!["usage: capture <text>".to_string()];
+        }
+        let path = self.config.inbox_path();
+        let bullet = capture::bullet(text, journal::now_secs());
+        let shown = self.config.general.inbox.clone();
+
+        if self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, &path))
+        {
+            let existing = self.buffer.rope.to_string();
+            self.buffer
+                .append_text(&capture::appended(&existing, &bullet));
+            self.mark_render_dirty();
+            self.schedule_auto_save();
+            return vec![format!("Captured to {shown}")];
+        }
+        let open = self
+            .inactive_buffers
+            .keys()
+            .find(|tab| same_file_path(tab, &path))
+            .cloned();
+        if let Some(tab) = open {
+            if let Some(buffer) = self.inactive_buffers.get_mut(&tab) {
+                let existing = buffer.rope.to_string();
+                buffer.append_text(&capture::appended(&existing, &bullet));
+            }
+            self.save_inactive_buffer(&tab);
+            return vec![format!("Captured to {shown}")];
+        }
+
+        match capture::append(&path, &bullet) {
+            Ok(()) => vec![format!("Captured to {shown}")],
+            Err(err) => vec![format!("capture: cannot write {shown}: {err}")],
+        }
     }
     pub(crate) fn save_inactive_buffer(&mut self, path: &PathBuf) {
         let Some(buffer) = self.inactive_buffers.get_mut(path) else {
@@ -65,7 +119,10 @@ impl App {
         buffer.save_debounce = None;
         buffer.dirty = false;
         let rope = buffer.rope.clone();
-        spawn_buffer_save(path, rope);
+        self.index_saved_note(&path, &rope);
+        let hook = self.hook(HookEvent::Save, &path, None);
+        spawn_buffer_save(path, rope, hook);
+        self.schedule_sync_after_save();
     }
     pub(crate) fn save_all_buffers(&mut self) {
         if let Some(path) = self.buffer.path.clone()
@@ -91,7 +148,18 @@ impl App {
         }
     }
     pub(crate) fn handle_file_changed(&mut self, path: PathBuf) -> Result<()> {
+        // Left over from the watcher of a vault switched away from.
+        if !path.starts_with(self.config.vault_path()) {
+            return Ok(());
+        }
         self.file_tree.refresh()?;
+        if !self.is_ignored(&path) {
+            self.update_search_index(&path);
+            self.update_link_index(&path);
+        }
+        if self.mode == Mode::FinderOpen {
+            self.refresh_finder_files()?;
+        }
 
         if !path.exists() {
             self.open_tabs.retain(|tab| !same_file_path(tab, &path));
@@ -107,6 +175,39 @@ impl App {
             self.inactive_buffers.remove(&stale);
         }
 
+        // A note appearing, disappearing or changing its aliases can
+        // (un)break wikilinks.
+        if !self.is_ignored(&path)
+            && (path.is_file() != self.note_index.contains_path(&path)
+                || self.aliases_changed(&path))
+        {
+            self.refresh_note_index();
+            self.mark_render_dirty();
+        }
+
+        // Any other note may have gained or lost a link to this one.
+        if self
+            .buffer
+            .path
+            .as_ref()
+            .is_none_or(|active| !same_file_path(active, &path))
+        {
+            self.backlink_count = None;
+        }
+
+        if self
+            .config
+            .bibliography_path()
+            .is_some_and(|bib| same_file_path(&bib, &path))
+        {
+            self.refresh_bibliography();
+        }
+
+        // Embed previews re-read their source when it changes.
+        if self.embeds.contains(&path) {
+            self.mark_render_dirty();
+        }
+
         if !self.should_reload_active(&path) {
             return Ok(());
         }
@@ -126,6 +227,119 @@ impl App {
 
         Ok(())
     }
+    /// A note or folder renamed outside the app: open buffers and tabs
+    /// follow it to the new path. Moving it somewhere ignored, like the
+    /// trash, counts as removing it.
+    pub(crate) fn handle_file_renamed(&mut self, from: PathBuf, to: PathBuf) -> Result<()> {
+        if !to.starts_with(self.config.vault_path()) {
+            return Ok(());
+        }
+        if self.is_ignored(&to) {
+            self.handle_file_changed(from)?;
+            return self.handle_file_changed(to);
+        }
+
+        self.retarget_open_paths(&from, &to);
+        self.file_tree.refresh()?;
+        self.update_search_index(&from);
+        self.update_search_index(&to);
+        self.update_link_index(&from);
+        self.update_link_index(&to);
+        self.refresh_note_index();
+        self.backlink_count = None;
+        if self.mode == Mode::FinderOpen {
+            self.refresh_finder_files()?;
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+    /// Point buffers, tabs and the split at `from` (a note or a folder) to
+    /// its new place `to`.
+    pub(crate) fn retarget_open_paths(&mut self, from: &Path, to: &Path) {
+        let moved = |path: &Path| -> Option<PathBuf> {
+            let rest = path.strip_prefix(from).ok()?;
+            Some(if rest.as_os_str().is_empty() {
+                to.to_path_buf()
+            } else {
+                to.join(rest)
+            })
+        };
+        if let Some(path) = self.buffer.path.as_deref().and_then(moved) {
+            self.buffer.path = Some(path);
+        }
+        for tab in &mut self.open_tabs {
+            if let Some(path) = moved(tab) {
+                *tab = path;
+            }
+        }
+        let stale: Vec<PathBuf> = self
+            .inactive_buffers
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .cloned()
+            .collect();
+        for old in stale {
+            if let (Some(mut buffer), Some(path)) =
+                (self.inactive_buffers.remove(&old), moved(&old))
+            {
+                buffer.path = Some(path.clone());
+                self.inactive_buffers.insert(path, buffer);
+            }
+        }
+        if let Some(split) = self.split.as_mut()
+            && let Some(path) = split.path.as_deref().and_then(moved)
+        {
+            split.path = Some(path);
+        }
+        self.recent.rename(from, to);
+        let mut repinned = false;
+        for pin in &mut self.file_tree.pinned {
+            if let Some(path) = moved(pin) {
+                *pin = path;
+                repinned = true;
+            }
+        }
+        if repinned && let Err(err) = self.save_pins() {
+            self.push_notification(format!("pin: cannot save pins: {err}"));
+        }
+    }
+    fn is_ignored(&self, path: &Path) -> bool {
+        let shown = path.to_string_lossy();
+        self.file_tree
+            .ignore_patterns
+            .iter()
+            .any(|p| shown.contains(p.as_str()))
+    }
+    /// Whether `path` matches `general.autosave_exclude`. Such notes stay
+    /// dirty until Ctrl+S (or quitting) writes them.
+    fn autosave_excluded(&self, path: Option<&Path>) -> bool {
+        path.is_some_and(|path| {
+            !self.autosave_exclude.is_empty()
+                && path.starts_with(self.autosave_exclude.path())
+                && self
+                    .autosave_exclude
+                    .matched_path_or_any_parents(path, false)
+                    .is_ignore()
+        })
+    }
+    fn aliases_changed(&self, path: &Path) -> bool {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        let mut aliases: Vec<String> = note_aliases(&text)
+            .iter()
+            .map(|alias| alias.to_lowercase())
+            .collect();
+        aliases.sort();
+        aliases.dedup();
+        aliases != self.note_index.aliases_of(path)
+    }
+    pub(crate) fn refresh_note_index(&mut self) {
+        let files = self.file_tree.all_file_paths();
+        self.metadata.refresh(&files);
+        self.note_index = NoteIndex::new(&self.config.vault_path(), &files)
+            .with_aliases(self.metadata.group_by("aliases").0);
+    }
     pub(crate) fn should_reload_active(&self, path: &PathBuf) -> bool {
         let Some(active) = self.buffer.path.as_ref() else {
             return false;
@@ -159,7 +373,20 @@ impl App {
             return Ok(());
         }
 
-        if self.buffer.dirty {
+        if path.exists() && attachment::is_attachment(&path) {
+            self.open_attachment(&path);
+            return Ok(());
+        }
+
+        // An unreadable note is a problem to report, not a reason to quit.
+        if path.exists()
+            && let Err(err) = std::fs::File::open(&path)
+        {
+            self.push_notification(format!("cannot open {}: {err}", path.display()));
+            return Ok(());
+        }
+
+        if self.buffer.dirty && !self.autosave_excluded(self.buffer.path.as_deref()) {
             self.save_buffer()?;
         }
 
@@ -172,7 +399,67 @@ impl App {
         Ok(())
     }
     pub(crate) fn schedule_auto_save(&mut self) {
+        if self.autosave_excluded(self.buffer.path.as_deref()) {
+            return;
+        }
         let debounce_ms = self.config.general.auto_save_debounce_ms;
         self.buffer.save_debounce = Some(Instant::now() + Duration::from_millis(debounce_ms));
     }
+    /// Schedule a save for `path` whether it is the active or an inactive buffer.
+    pub(crate) fn schedule_auto_save_for(&mut self, path: &PathBuf) {
+        if self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, path))
+        {
+            self.schedule_auto_save();
+            return;
+        }
+
+        if self.autosave_excluded(Some(path)) {
+            return;
+        }
+        let debounce_ms = self.config.general.auto_save_debounce_ms;
+        if let Some(buffer) = self.inactive_buffers.get_mut(path) {
+            buffer.save_debounce = Some(Instant::now() + Duration::from_millis(debounce_ms));
+        }
+    }
+
+    /// The configured hook for `event`, ready to run for `path`. It shows
+    /// as running in the status bar until its [`Msg::HookFinished`].
+    pub(crate) fn hook(
+        &mut self,
+        event: HookEvent,
+        path: &Path,
+        old_path: Option<&Path>,
+    ) -> Option<Invocation> {
+        let hooks = &self.config.hooks;
+        let command = match event {
+            HookEvent::Create => &hooks.on_create,
+            HookEvent::Save => &hooks.on_save,
+            HookEvent::Rename => &hooks.on_rename,
+            HookEvent::Delete => &hooks.on_delete,
+        }
+        .as_deref()
+        .filter(|command| !command.trim().is_empty())?;
+
+        let command = command.to_string();
+        self.activities.start(event.activity());
+        Some(Invocation {
+            command,
+            event,
+            path: path.to_path_buf(),
+            old_path: old_path.map(Path::to_path_buf),
+            vault: self.config.vault_path(),
+            tx: self.event_tx.clone(),
+        })
+    }
+
+    /// Run the hook for `event` in the background, if one is configured.
+    pub(crate) fn run_hook(&mut self, event: HookEvent, path: &Path, old_path: Option<&Path>) {
+        if let Some(hook) = self.hook(event, path, old_path) {
+            hook.spawn();
+        }
+    }
 }
@@ -0,0 +1,176 @@
+use crate::app::{App, FinderMode, FinderResult, GraphView};
+use crate::model::graph::LinkGraph;
+use crate::model::link;
+use crate::model::mode::Mode;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::path::PathBuf;
+
+impl App {
+    /// `:graph [1|2]`: the current note and the notes within one or two
+    /// links of it, either direction.
+    pub(crate) fn graph_command(&mut self, args: &str) -> Vec<String> {
+        let depth = match args.trim() {
+            "" | "1" => 1,
+            "2" => 2,
+            other => return vec![format!("graph: depth must be 1 or 2, not \"{other}\"")],
+        };
+        let Some(centre) = self.buffer.path.clone() else {
+            return vec!["graph: the buffer is not a note".to_string()];
+        };
+
+        let links = self.link_graph();
+        let rows = links.neighbourhood(&centre, depth);
+
+        self.graph_view = Some(GraphView {
+            links,
+            centre,
+            depth,
+            rows,
+            selected: 0,
+        });
+        self.mode = Mode::Graph;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    /// `:orphans`: pick from the notes with no links in or out, to open
+    /// or Tab-mark them for `:bulk`.
+    pub(crate) fn orphans_command(&mut self) -> Vec<String> {
+        let (notes, links): (Vec<PathBuf>, LinkGraph) = match &self.link_index {
+            Some(index) => (index.notes().into_iter().cloned().collect(), index.graph()),
+            None => {
+                let (notes, links) = self.vault_links();
+                (notes.into_iter().map(|(path, _)| path).collect(), links)
+            }
+        };
+        let orphans: Vec<PathBuf> = notes
+            .into_iter()
+            .filter(|path| links.is_orphan(path))
+            .collect();
+        if orphans.is_empty() {
+            return vec!["orphans: every note links or is linked".to_string()];
+        }
+        self.finder_orphans = orphans;
+        if let Err(err) = self.open_finder(FinderMode::Orphans) {
+            return vec![format!("orphans: {err}")];
+        }
+        Vec::new()
+    }
+
+    /// `:links check`: every wikilink and relative markdown link in the
+    /// vault that resolves to nothing, by file and line, in the finder.
+    pub(crate) fn links_command(&mut self, args: &str) -> Vec<String> {
+        if args.trim() != "check" {
+            return vec!["usage: links check".to_string()];
+        }
+        let resolves = |target: &str| self.note_index.resolves(target);
+        let (checked, hits): (usize, Vec<(PathBuf, usize, String)>) = match &self.link_index {
+            Some(index) => {
+                let hits = index
+                    .links()
+                    .filter(|(path, link)| {
+                        link.is_broken(path.parent().unwrap_or(path), resolves, |file| {
+                            file.exists()
+                        })
+                    })
+                    .map(|(path, link)| (path.clone(), link.line, link.text.clone()))
+                    .collect();
+                (index.notes().len(), hits)
+            }
+            None => {
+                let (notes, _) = self.vault_links();
+                let hits = notes
+                    .iter()
+                    .flat_map(|(path, text)| {
+                        let dir = path.parent().unwrap_or(path);
+                        link::broken_links(text, dir, resolves, |file| file.exists())
+                            .into_iter()
+                            .map(|hit| (path.clone(), hit.line, hit.text))
+                    })
+                    .collect();
+                (notes.len(), hits)
+            }
+        };
+        let mut broken: Vec<FinderResult> = hits
+            .into_iter()
+            .map(|(path, line, text)| FinderResult {
+                preview: format!("{}:{}  {text}", path.to_string_lossy(), line + 1),
+                path,
+                line: Some(line + 1),
+            })
+            .collect();
+        if broken.is_empty() {
+            return vec![format!("links: all links in {checked} notes resolve")];
+        }
+        broken.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        self.finder_broken = broken;
+        if let Err(err) = self.open_finder(FinderMode::BrokenLinks) {
+            return vec![format!("links: {err}")];
+        }
+        Vec::new()
+    }
+
+    /// The links between notes, from the link index once it is ready.
+    fn link_graph(&self) -> LinkGraph {
+        match &self.link_index {
+            Some(index) => index.graph(),
+            None => self.vault_links().1,
+        }
+    }
+
+    /// Every note's text (open buffers first) and the links between them.
+    fn vault_links(&self) -> (Vec<(PathBuf, String)>, LinkGraph) {
+        let notes: Vec<(PathBuf, String)> = self
+            .file_tree
+            .all_file_paths()
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .filter_map(|path| Some((path.clone(), self.note_text(&path)?)))
+            .collect();
+        let links = LinkGraph::build(&self.config.vault_path(), &notes);
+        (notes, links)
+    }
+
+    pub(crate) fn handle_key_graph(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(view) = self.graph_view.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.selected = (view.selected + 1).min(view.rows.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char(ch @ ('1' | '2')) => {
+                view.depth = ch as usize - '0' as usize;
+                view.rows = view.links.neighbourhood(&view.centre, view.depth);
+                view.selected = 0;
+            }
+            // Walk the graph: the selected note becomes the centre.
+            KeyCode::Char('l') | KeyCode::Right => {
+                if let Some(row) = view.rows.get(view.selected) {
+                    view.centre = row.path.clone();
+                    view.rows = view.links.neighbourhood(&view.centre, view.depth);
+                    view.selected = 0;
+                }
+            }
+            KeyCode::Enter => {
+                let path = view.rows.get(view.selected).map(|row| row.path.clone());
+                self.graph_view = None;
+                self.mode = Mode::Normal;
+                if let Some(path) = path {
+                    self.open_file(path)?;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.graph_view = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+}
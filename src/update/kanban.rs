@@ -0,0 +1,96 @@
+use crate::app::{App, KanbanView};
+use crate::model::kanban;
+use crate::model::mode::Mode;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// `:kanban`: the active note's `##` sections as columns of cards.
+    pub(crate) fn kanban_command(&mut self) -> Vec<String> {
+        let columns = kanban::columns(&self.buffer_lines());
+        if columns.is_empty() {
+            return vec!["kanban: no ## sections in this note".to_string()];
+        }
+        self.kanban_view = Some(KanbanView {
+            columns,
+            column: 0,
+            card: 0,
+        });
+        self.mode = Mode::Kanban;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_kanban(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(view) = self.kanban_view.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+
+        let cards = |view: &KanbanView, column: usize| view.columns[column].cards.len();
+        match key.code {
+            KeyCode::Char('h') | KeyCode::Left => {
+                view.column = view.column.saturating_sub(1);
+                view.card = view.card.min(cards(view, view.column).saturating_sub(1));
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                view.column = (view.column + 1).min(view.columns.len() - 1);
+                view.card = view.card.min(cards(view, view.column).saturating_sub(1));
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.card = (view.card + 1).min(cards(view, view.column).saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.card = view.card.saturating_sub(1),
+            KeyCode::Char('H') => self.move_kanban_card(-1),
+            KeyCode::Char('L') => self.move_kanban_card(1),
+            KeyCode::Enter => {
+                let line = view.columns[view.column]
+                    .cards
+                    .get(view.card)
+                    .map_or(view.columns[view.column].line, |card| card.lines.start);
+                self.kanban_view = None;
+                self.mode = Mode::Normal;
+                self.buffer.cursor.move_to(line, 0);
+                self.buffer.scroll_to_cursor();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.kanban_view = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// Move the selected card to the end of the next column left or right,
+    /// as one undoable edit, and follow it there.
+    fn move_kanban_card(&mut self, delta: isize) {
+        let Some(view) = self.kanban_view.as_ref() else {
+            return;
+        };
+        let Some(to) = view
+            .column
+            .checked_add_signed(delta)
+            .filter(|&to| to < view.columns.len())
+        else {
+            return;
+        };
+        let (from, card) = (view.column, view.card);
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let Some((lines, moved)) = kanban::move_card(&self.buffer_lines(), from, card, to) else {
+            return;
+        };
+        let mut text = lines.join("\n");
+        text.push('\n');
+        self.buffer.replace_text(&text);
+        self.schedule_auto_save();
+        if let Some(view) = self.kanban_view.as_mut() {
+            view.columns = kanban::columns(&lines);
+            view.column = to;
+            view.card = moved;
+        }
+    }
+}
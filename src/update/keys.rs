@@ -1,4 +1,5 @@
-use crate::app::{App, FinderMode};
+use crate::app::{App, ConfirmAction, FinderMode};
+use crate::hooks::HookEvent;
 use crate::model::mode::Mode;
 use crate::msg::{Direction as MoveDir, Msg};
 use anyhow::Result;
@@ -9,16 +10,35 @@ impl App {
     pub(crate) fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
         match self.mode {
             Mode::Normal => self.handle_key_normal(key),
-            Mode::Insert => self.handle_key_insert(key),
+            Mode::Insert => {
+                if self.reject_readonly_edit() {
+                    return Ok(());
+                }
+                self.handle_key_insert(key)
+            }
             Mode::Command => self.handle_key_command(key),
             Mode::Sidebar => self.handle_key_sidebar(key),
             Mode::SidebarCreate => self.handle_key_sidebar_create(key),
             Mode::FinderOpen => self.handle_key_finder(key),
             Mode::ConfirmCreate => self.handle_key_confirm_create(key),
+            Mode::Confirm => self.handle_key_confirm(key),
             Mode::Backlinks => self.handle_key_backlinks(key),
-            _ => Ok(()),
+            Mode::Outline => self.handle_key_outline(key),
+            Mode::Visual | Mode::VisualLine => self.handle_key_visual(key),
+            Mode::SpellSuggest => self.handle_key_spell(key),
+            Mode::LinkPreview => self.handle_key_link_preview(key),
+            Mode::Stats => self.handle_key_stats(key),
+            Mode::Tasks => self.handle_key_tasks(key),
+            Mode::Kanban => self.handle_key_kanban(key),
+            Mode::Properties => self.handle_key_properties(key),
+            Mode::Graph => self.handle_key_graph(key),
+            Mode::YankPicker => self.handle_key_yanks(key),
+            Mode::DiagramPreview => self.handle_key_diagram(key),
+            Mode::PluginLogs => self.handle_key_plugin_logs(key),
+            Mode::PluginBrowser => self.handle_key_plugin_browser(key),
         }
     }
+    #[allow(clippy::collapsible_match)]
     pub(crate) fn handle_key_normal(&mut self, key: KeyEvent) -> Result<()> {
         if key.code != KeyCode::Char('q') {
             self.quit_confirm_armed = false;
@@ -33,6 +53,7 @@ impl App {
             match key.code {
                 KeyCode::Char('f') => self.open_finder(FinderMode::Files)?,
                 KeyCode::Char('g') => self.open_finder(FinderMode::Content)?,
+                KeyCode::Char('s') => self.open_section_search()?,
                 KeyCode::Char('e') => {
                     self.sidebar_visible = !self.sidebar_visible;
                     if self.sidebar_visible {
@@ -41,6 +62,23 @@ impl App {
                     }
                 }
                 KeyCode::Char('b') => self.toggle_backlinks_panel()?,
+                KeyCode::Char('r') => {
+                    for note in self.reveal_active_note() {
+                        self.push_notification(note);
+                    }
+                }
+                KeyCode::Char('l') => {
+                    for note in self.graph_command("") {
+                        self.push_notification(note);
+                    }
+                }
+                KeyCode::Char('o') => self.toggle_outline_panel(),
+                KeyCode::Char('z') => self.toggle_zen(),
+                KeyCode::Char('y') => {
+                    for note in self.copy_code_block() {
+                        self.push_notification(note);
+                    }
+                }
                 KeyCode::Char('n') => {
                     self.file_tree.begin_create();
                     self.sidebar_visible = true;
@@ -65,9 +103,15 @@ impl App {
             if key.code == KeyCode::Char('d') {
                 self.follow_wikilink_under_cursor()?;
                 return Ok(());
+            } else if key.code == KeyCode::Char('x') {
+                self.open_link_under_cursor()?;
+                return Ok(());
             } else if key.code == KeyCode::Char('g') {
                 self.move_cursor(MoveDir::Top);
                 return Ok(());
+            } else if key.code == KeyCode::Char('q') {
+                self.reflow_at_cursor();
+                return Ok(());
             } else if key.code == KeyCode::Char('t') {
                 self.switch_tab_relative(1)?;
                 return Ok(());
@@ -77,10 +121,33 @@ impl App {
             }
         }
 
+        if self.pending_key == Some('z') {
+            self.pending_key = None;
+            match key.code {
+                KeyCode::Char('=') => self.open_spell_suggestions(),
+                KeyCode::Char('g') => self.spell_add_word_at_cursor(),
+                KeyCode::Char('a') => self.toggle_frontmatter_fold(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if let Some(bracket @ (']' | '[')) = self.pending_key {
+            self.pending_key = None;
+            if key.code == KeyCode::Char('q') {
+                self.quickfix_step(if bracket == ']' { 1 } else { -1 })?;
+            }
+            return Ok(());
+        }
+
         if self.pending_key == Some('d') {
             self.pending_key = None;
             if key.code == KeyCode::Char('d') {
-                self.buffer.delete_line(self.buffer.cursor.row);
+                if self.reject_readonly_edit() {
+                    return Ok(());
+                }
+                let removed = self.buffer.delete_line(self.buffer.cursor.row);
+                self.yank_history.push(removed);
                 self.buffer.clamp_cursor();
                 self.mark_render_dirty();
                 self.schedule_auto_save();
@@ -102,6 +169,23 @@ impl App {
             return Ok(());
         }
 
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('w') {
+            self.cycle_pane();
+            return Ok(());
+        }
+
+        let is_edit_key = match key.code {
+            KeyCode::Char('i' | 'a' | 'A' | 'I' | 'o' | 'O' | 'x' | 'p') => {
+                !key.modifiers.contains(KeyModifiers::CONTROL)
+            }
+            KeyCode::Char('u') => key.modifiers.is_empty(),
+            KeyCode::Char('r') => key.modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        };
+        if is_edit_key && self.reject_readonly_edit() {
+            return Ok(());
+        }
+
         match key.code {
             KeyCode::Char(' ') if key.modifiers.is_empty() => {
                 self.pending_key = Some(' ');
@@ -113,6 +197,10 @@ impl App {
             KeyCode::Char('d') if key.modifiers.is_empty() => {
                 self.pending_key = Some('d');
             }
+            KeyCode::Char('z') if key.modifiers.is_empty() => {
+                self.pending_key = Some('z');
+            }
+            KeyCode::Char(bracket @ (']' | '[')) => self.pending_key = Some(bracket),
             KeyCode::Char('q') => {
                 let pending = self.pending_write_count();
                 if pending == 0 {
@@ -171,12 +259,16 @@ impl App {
                 self.command_input.clear();
                 self.mark_render_dirty();
             }
+            KeyCode::Char('v') if key.modifiers.is_empty() => self.enter_visual(Mode::Visual),
+            KeyCode::Char('V') => self.enter_visual(Mode::VisualLine),
+            KeyCode::Char('K') => self.open_link_preview(),
             KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.switch_tab_relative(1)?;
             }
             KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.switch_tab_relative(-1)?;
             }
+            KeyCode::Char('p') => self.paste_after_cursor(),
             KeyCode::Char('/') => self.open_finder(FinderMode::Files)?,
             KeyCode::Char('F')
                 if key.modifiers.contains(KeyModifiers::CONTROL)
@@ -229,11 +321,104 @@ impl App {
         }
         Ok(())
     }
+    pub(crate) fn enter_visual(&mut self, mode: Mode) {
+        self.buffer.begin_selection();
+        self.mode = mode;
+        self.mark_render_dirty();
+    }
+    pub(crate) fn exit_visual(&mut self) {
+        self.buffer.cursor.clear_selection();
+        self.mode = Mode::Normal;
+        self.mark_render_dirty();
+    }
+    pub(crate) fn handle_key_visual(&mut self, key: KeyEvent) -> Result<()> {
+        let linewise = self.mode == Mode::VisualLine;
+
+        if self.pending_key == Some('g') {
+            self.pending_key = None;
+            if key.code == KeyCode::Char('g') {
+                self.move_cursor(MoveDir::Top);
+                self.buffer.extend_selection();
+                self.mark_render_dirty();
+            } else if key.code == KeyCode::Char('q') {
+                self.reflow_selection();
+            }
+            return Ok(());
+        }
+
+        let motion = match key.code {
+            KeyCode::Char('h') | KeyCode::Left => Some(MoveDir::Left),
+            KeyCode::Char('j') | KeyCode::Down => Some(MoveDir::Down),
+            KeyCode::Char('k') | KeyCode::Up => Some(MoveDir::Up),
+            KeyCode::Char('l') | KeyCode::Right => Some(MoveDir::Right),
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(MoveDir::PageUp)
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(MoveDir::PageDown)
+            }
+            KeyCode::Char('w') => Some(MoveDir::WordForward),
+            KeyCode::Char('b') => Some(MoveDir::WordBackward),
+            KeyCode::Char('e') => Some(MoveDir::WordEnd),
+            KeyCode::Char('G') => Some(MoveDir::Bottom),
+            KeyCode::Char('0') => Some(MoveDir::LineStart),
+            KeyCode::Char('$') => Some(MoveDir::LineEnd),
+            KeyCode::Char('^') => Some(MoveDir::FirstNonWhitespace),
+            KeyCode::Char('{') => Some(MoveDir::ParagraphUp),
+            KeyCode::Char('}') => Some(MoveDir::ParagraphDown),
+            _ => None,
+        };
+        if let Some(dir) = motion {
+            self.move_cursor(dir);
+            self.buffer.extend_selection();
+            self.mark_render_dirty();
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Esc => self.exit_visual(),
+            KeyCode::Char('v') if linewise => self.mode = Mode::Visual,
+            KeyCode::Char('V') if !linewise => self.mode = Mode::VisualLine,
+            KeyCode::Char('v' | 'V') => self.exit_visual(),
+            KeyCode::Char('g') => self.pending_key = Some('g'),
+            KeyCode::Char('d' | 'x') => {
+                if self.reject_readonly_edit() {
+                    self.exit_visual();
+                    return Ok(());
+                }
+                if let Some(removed) = self.buffer.delete_selection(linewise) {
+                    self.yank_history.push(removed);
+                }
+                self.buffer.clamp_cursor();
+                self.buffer.scroll_to_cursor();
+                self.exit_visual();
+                self.schedule_auto_save();
+            }
+            KeyCode::Char('y') => self.yank_selection(),
+            KeyCode::Char('p') => self.paste_from_clipboard_over_selection(),
+            KeyCode::Char('L') => self.wrap_selection_as_wikilink(),
+            KeyCode::Char(':') => {
+                self.visual_command = Some(linewise);
+                self.command_input.clear();
+                self.mode = Mode::Command;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
     pub(crate) fn handle_key_command(&mut self, key: KeyEvent) -> Result<()> {
+        if !matches!(key.code, KeyCode::Tab | KeyCode::BackTab) {
+            self.command_completion = None;
+        }
         match key.code {
             KeyCode::Esc => {
                 self.mode = Mode::Normal;
                 self.command_input.clear();
+                self.plugin_prompt = None;
+                if self.visual_command.take().is_some() {
+                    self.buffer.cursor.clear_selection();
+                }
                 self.mark_render_dirty();
             }
             KeyCode::Enter => {
@@ -242,14 +427,32 @@ impl App {
                 self.command_input.clear();
                 self.mark_render_dirty();
 
-                if !command.is_empty() {
+                if let Some(prompt) = self.plugin_prompt.take() {
+                    let mut answers = prompt.answers;
+                    answers.push(command);
+                    for note in self.run_plugin_command_with(&prompt.command, &prompt.args, answers)
+                    {
+                        self.push_notification(note);
+                    }
+                    if self.plugin_prompt.is_none() && self.visual_command.take().is_some() {
+                        self.buffer.cursor.clear_selection();
+                    }
+                } else if !command.is_empty() {
                     let _ = self.event_tx.send(Msg::PluginCommand(command));
+                } else if self.visual_command.take().is_some() {
+                    self.buffer.cursor.clear_selection();
                 }
             }
             KeyCode::Backspace => {
                 self.command_input.pop();
                 self.mark_render_dirty();
             }
+            // Answers to a plugin's question are not commands.
+            KeyCode::Tab | KeyCode::BackTab if self.plugin_prompt.is_some() => {}
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.complete_command(if key.code == KeyCode::Tab { 1 } else { -1 });
+                self.mark_render_dirty();
+            }
             KeyCode::Char(ch)
                 if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
             {
@@ -262,6 +465,9 @@ impl App {
         Ok(())
     }
     pub(crate) fn handle_key_insert(&mut self, key: KeyEvent) -> Result<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('l') {
+            return self.open_finder(FinderMode::Files);
+        }
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('e') {
             self.sidebar_visible = !self.sidebar_visible;
             if self.sidebar_visible {
@@ -270,6 +476,23 @@ impl App {
             }
             return Ok(());
         }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('v') {
+            self.paste_in_insert();
+            return Ok(());
+        }
+
+        if self.handle_key_citation_completion(key)
+            || self.handle_key_link_picker(key)
+            || self.handle_key_plugin_completion(key)
+        {
+            return Ok(());
+        }
+        if key.code == KeyCode::Tab && self.remove_pending_pair() {
+            self.mark_render_dirty();
+            self.schedule_auto_save();
+            return Ok(());
+        }
+        self.pending_pair = None;
 
         match key.code {
             KeyCode::Esc => self.mode = Mode::Normal,
@@ -286,12 +509,18 @@ impl App {
                 self.schedule_auto_save();
             }
             KeyCode::Backspace => {
-                self.buffer.delete_char_before();
+                if !self.smart_backspace() {
+                    self.buffer.delete_char_before();
+                }
                 self.mark_render_dirty();
                 self.schedule_auto_save();
             }
             KeyCode::Char(ch) => {
-                self.buffer.insert_char(ch);
+                if !self.auto_pair_char(ch) {
+                    self.buffer.insert_char(ch);
+                }
+                self.smart_punctuate();
+                self.auto_wrap_line();
                 self.mark_render_dirty();
                 self.schedule_auto_save();
             }
@@ -301,6 +530,18 @@ impl App {
             KeyCode::Down => self.move_cursor(MoveDir::Down),
             _ => {}
         }
+        match key.code {
+            KeyCode::Char(_) | KeyCode::Backspace => {
+                self.update_citation_completion();
+                self.update_link_picker();
+                self.update_plugin_completion();
+            }
+            _ => {
+                self.citation_completion = None;
+                self.link_picker = None;
+                self.plugin_completion = None;
+            }
+        }
         Ok(())
     }
     pub(crate) fn handle_key_sidebar(&mut self, key: KeyEvent) -> Result<()> {
@@ -331,6 +572,10 @@ impl App {
                 self.file_tree.begin_create();
                 self.mode = Mode::SidebarCreate;
             }
+            KeyCode::Char('v') => self.toggle_sidebar_grouping()?,
+            KeyCode::Char('r') => self.begin_sidebar_rename()?,
+            KeyCode::Char('d') => self.begin_sidebar_delete(),
+            KeyCode::Char('p') => self.pin_sidebar_selection(),
             KeyCode::Enter => {
                 if let Some(node) = self.file_tree.selected_node() {
                     if node.is_dir {
@@ -359,6 +604,42 @@ impl App {
         }
         Ok(())
     }
+    pub(crate) fn handle_key_confirm(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.mode = Mode::Normal;
+                if let Some(pending) = self.pending_confirm.take() {
+                    let notes = match pending.action {
+                        ConfirmAction::Rename(plan) => self.apply_rename(plan),
+                        ConfirmAction::Merge(plan) => self.apply_merge(plan)?,
+                        ConfirmAction::Delete(path) => self.apply_delete(&path)?,
+                        ConfirmAction::MoveNotes(folder) => self.apply_bulk_move(&folder),
+                        ConfirmAction::InstallPlugin(repo) => self.apply_plugin_install(&repo),
+                        ConfirmAction::UpdatePlugin(held) => self.apply_plugin_update(held),
+                    };
+                    for note in notes {
+                        self.push_notification(note);
+                    }
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                match self.pending_confirm.take().map(|pending| pending.action) {
+                    Some(ConfirmAction::InstallPlugin(repo)) => {
+                        self.push_notification(self.cancel_plugin_install(&repo));
+                    }
+                    Some(ConfirmAction::UpdatePlugin(held)) => {
+                        let repo = held.repo;
+                        self.push_notification(format!("plugin {repo}: update not applied"));
+                    }
+                    _ => {}
+                }
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    #[allow(clippy::collapsible_match)]
     pub(crate) fn handle_key_backlinks(&mut self, key: KeyEvent) -> Result<()> {
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('b') {
             self.toggle_backlinks_panel()?;
@@ -394,6 +675,34 @@ impl App {
 
         Ok(())
     }
+    pub(crate) fn handle_key_outline(&mut self, key: KeyEvent) -> Result<()> {
+        let count = self.outline_headings().len();
+
+        match key.code {
+            KeyCode::Esc => {
+                self.outline_visible = false;
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down if count > 0 => {
+                self.outline_selected = (self.outline_selected + 1).min(count - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.outline_selected = self.outline_selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                self.outline_jump();
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Char('J') => self.outline_move(1),
+            KeyCode::Char('K') => self.outline_move(-1),
+            KeyCode::Char('H' | '<') => self.outline_shift_level(-1),
+            KeyCode::Char('L' | '>') => self.outline_shift_level(1),
+            _ => {}
+        }
+
+        self.mark_render_dirty();
+        Ok(())
+    }
     pub(crate) fn handle_key_sidebar_create(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Esc => {
@@ -404,7 +713,10 @@ impl App {
                 self.file_tree.create_input.pop();
             }
             KeyCode::Enter => {
-                if let Some(path) = self.file_tree.commit_create()? {
+                if let Some((path, created)) = self.file_tree.commit_create()? {
+                    if created {
+                        self.run_hook(HookEvent::Create, &path, None);
+                    }
                     self.open_file(path)?;
                     self.mode = Mode::Normal;
                 } else {
@@ -418,24 +730,60 @@ impl App {
         }
         Ok(())
     }
+    #[allow(clippy::collapsible_match)]
     pub(crate) fn handle_key_finder(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Esc => {
-                self.mode = Mode::Normal;
+                self.mode = if self.finder_from_insert {
+                    Mode::Insert
+                } else {
+                    Mode::Normal
+                };
                 self.finder_query.clear();
                 self.finder_results.clear();
                 self.finder_selected = 0;
             }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.mode = if self.finder_from_insert {
+                    Mode::Insert
+                } else {
+                    Mode::Normal
+                };
+                self.insert_finder_link();
+                self.finder_query.clear();
+                self.finder_results.clear();
+                self.finder_selected = 0;
+            }
+            KeyCode::Tab
+                if !matches!(
+                    self.finder_mode,
+                    FinderMode::Section | FinderMode::BrokenLinks
+                ) =>
+            {
+                self.toggle_finder_mark()
+            }
+            KeyCode::Enter if !self.finder_marked.is_empty() => self.begin_bulk_from_finder(),
             KeyCode::Enter => {
                 if let Some(result) = self.finder_results.get(self.finder_selected).cloned() {
-                    self.open_file(result.path)?;
-                    if let Some(line) = result.line {
-                        let target = line.saturating_sub(1);
-                        self.buffer.cursor.row =
-                            target.min(self.buffer.line_count().saturating_sub(1));
-                        self.buffer.cursor.col = 0;
-                        self.buffer.cursor.desired_col = 0;
-                        self.buffer.scroll_to_cursor();
+                    if matches!(
+                        self.finder_mode,
+                        FinderMode::Content | FinderMode::BrokenLinks
+                    ) {
+                        self.quickfix_from_finder();
+                    }
+                    match (self.finder_mode, result.line) {
+                        // Section hits are in the buffer already open.
+                        (FinderMode::Section, Some(line)) => {
+                            self.buffer.cursor.move_to(line - 1, 0);
+                            self.buffer.scroll_to_cursor();
+                            self.mark_render_dirty();
+                        }
+                        (FinderMode::LinkChoices, _) => {
+                            let anchor = self.finder_link_anchor.take();
+                            self.open_note_at_anchor(result.path, anchor.as_deref())?;
+                        }
+                        (_, Some(line)) => self.open_file_at_line(result.path, line)?,
+                        (_, None) => self.open_file(result.path)?,
                     }
                 }
                 self.mode = Mode::Normal;
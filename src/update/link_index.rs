@@ -0,0 +1,67 @@
+use crate::app::App;
+use crate::model::link_index::{self, LinkIndex};
+use crate::msg::Msg;
+use std::path::Path;
+
+impl App {
+    /// Load the saved link index and bring it up to date off the main
+    /// thread; backlinks and the graph read every note until it is ready.
+    pub(crate) fn start_link_index(&mut self) {
+        let root = self.config.vault_path();
+        let files = self.file_tree.all_file_paths();
+        let tx = self.event_tx.clone();
+        self.activities.start("link index");
+        std::thread::spawn(move || {
+            let cache = link_index::default_path();
+            let mut index = LinkIndex::load(&root, &cache);
+            if index.refresh(&files) > 0
+                && let Err(err) = index.save(&cache)
+            {
+                tracing::warn!("link index: cannot save {}: {err}", cache.display());
+            }
+            let _ = tx.send(Msg::LinkIndexReady(Box::new(index)));
+        });
+    }
+
+    pub(crate) fn handle_link_index_ready(&mut self, mut index: LinkIndex) {
+        // Built for a vault that has since been switched away from.
+        if index.root() != self.config.vault_path() {
+            return;
+        }
+        self.activities.finish("link index");
+        // Catch up on changes the watcher reported while it was building.
+        index.refresh(&self.file_tree.all_file_paths());
+        self.link_index = Some(index);
+        self.backlink_count = None;
+        self.mark_render_dirty();
+    }
+
+    /// Keep the link index current after the watcher saw `path` change.
+    pub(crate) fn update_link_index(&mut self, path: &Path) {
+        if let Some(index) = self.link_index.as_mut() {
+            if path.is_dir() {
+                index.refresh(&self.file_tree.all_file_paths());
+            } else {
+                index.update(path);
+            }
+        }
+    }
+
+    /// Index a note's text as it is saved, ahead of the watcher event.
+    pub(crate) fn index_saved_note(&mut self, path: &Path, rope: &ropey::Rope) {
+        if let Some(index) = self.link_index.as_mut() {
+            index.update_text(path, &rope.to_string());
+            self.backlink_count = None;
+        }
+    }
+
+    pub(crate) fn save_link_index(&self) {
+        let Some(index) = self.link_index.as_ref() else {
+            return;
+        };
+        let cache = link_index::default_path();
+        if let Err(err) = index.save(&cache) {
+            tracing::warn!("link index: cannot save {}: {err}", cache.display());
+        }
+    }
+}
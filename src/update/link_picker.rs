@@ -0,0 +1,254 @@
+use crate::app::{App, LinkHeadings, LinkPicker, same_file_path};
+use std::path::PathBuf;
+
+use crate::model::{bibtex, link, outline, picker};
+use crate::update::note_ops::rel_display;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Most notes or headings offered at once.
+const PICKER_LIMIT: usize = 8;
+
+impl App {
+    /// Offer notes for the `[[partial` before the cursor, or headings of
+    /// the note once a `#` is typed; close the list outside a wikilink.
+    pub(crate) fn update_link_picker(&mut self) {
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let Some((note, heading)) = link::wikilink_partial_before(&line, self.buffer.cursor.col)
+        else {
+            self.link_picker = None;
+            return;
+        };
+
+        let candidates = match heading {
+            Some(partial) => self.heading_candidates(note, partial),
+            None => self.note_link_candidates(note),
+        };
+        self.link_picker = (!candidates.is_empty()).then_some(LinkPicker {
+            candidates,
+            selected: 0,
+        });
+    }
+
+    /// `(target, folder)` of the notes matching `partial`, linked by name
+    /// unless another note shares it.
+    fn note_link_candidates(&self, partial: &str) -> Vec<(String, String)> {
+        let root = self.config.vault_path();
+        let notes: Vec<String> = self
+            .file_tree
+            .all_file_paths()
+            .iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .map(|path| rel_display(&root, path))
+            .collect();
+        let ranked = picker::rank(notes.clone(), partial, String::clone, PICKER_LIMIT);
+        ranked
+            .into_iter()
+            .map(|rel| {
+                let name = link::wikilink_target(&rel, false);
+                let ambiguous = notes
+                    .iter()
+                    .filter(|other| link::wikilink_target(other, false).eq_ignore_ascii_case(&name))
+                    .count()
+                    > 1;
+                let folder = rel.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+                (link::wikilink_target(&rel, ambiguous), folder)
+            })
+            .collect()
+    }
+
+    /// `(title, #…)` of the headings in `note` matching `partial`; an empty
+    /// `note` means the one being edited.
+    fn heading_candidates(&mut self, note: &str, partial: &str) -> Vec<(String, String)> {
+        let headings = if note.trim().is_empty() {
+            note_headings(self.buffer_lines().iter().map(String::as_str))
+        } else {
+            match self.resolve_wikilink_target(note) {
+                Some(path) => self.linked_note_headings(&path),
+                None => Vec::new(),
+            }
+        };
+        picker::rank(headings, partial, |(title, _)| title.clone(), PICKER_LIMIT)
+    }
+
+    /// Headings of the note at `path`: from its buffer when open, otherwise
+    /// from disk, reusing the last read while the file is unchanged.
+    fn linked_note_headings(&mut self, path: &PathBuf) -> Vec<(String, String)> {
+        let open = self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, path))
+            || self.inactive_buffers.contains_key(path);
+        if open {
+            return self
+                .note_text(path)
+                .map_or_else(Vec::new, |text| note_headings(text.lines()));
+        }
+        let modified = bibtex::modified(path);
+        if let Some(cached) = &self.link_headings
+            && cached.path == *path
+            && cached.modified == modified
+        {
+            return cached.headings.clone();
+        }
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        let headings = note_headings(text.lines());
+        self.link_headings = Some(LinkHeadings {
+            path: path.clone(),
+            modified,
+            headings: headings.clone(),
+        });
+        headings
+    }
+
+    /// Keys that steer an open link list. Returns whether the key was used;
+    /// anything else goes on to be typed.
+    pub(crate) fn handle_key_link_picker(&mut self, key: KeyEvent) -> bool {
+        let Some(picker) = self.link_picker.as_mut() else {
+            return false;
+        };
+        let len = picker.candidates.len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => picker.selected = (picker.selected + 1) % len,
+            KeyCode::Char('n') if ctrl => picker.selected = (picker.selected + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => picker.selected = (picker.selected + len - 1) % len,
+            KeyCode::Char('p') if ctrl => picker.selected = (picker.selected + len - 1) % len,
+            KeyCode::Enter => {
+                let insert = picker.candidates[picker.selected].0.clone();
+                self.accept_link_pick(&insert);
+            }
+            KeyCode::Esc => self.link_picker = None,
+            _ => return false,
+        }
+        self.mark_render_dirty();
+        true
+    }
+
+    /// Replace the note or heading typed so far with `insert` and close the
+    /// link, stepping over a `]]` that is already there.
+    fn accept_link_pick(&mut self, insert: &str) {
+        self.link_picker = None;
+        let row = self.buffer.cursor.row;
+        let line = self.buffer.line_text(row).unwrap_or_default();
+        let col = self.buffer.cursor.col;
+        let Some((note, heading)) = link::wikilink_partial_before(&line, col) else {
+            return;
+        };
+        let partial = heading.unwrap_or(note);
+        let end = self.buffer.cursor_char_offset();
+        let start = end - partial.chars().count();
+        let closed = line[col..].starts_with("]]");
+        let text = if closed {
+            insert.to_string()
+        } else {
+            format!("{insert}]]")
+        };
+        self.buffer.replace_chars(start..end, &text);
+        if closed {
+            let col = self.buffer.cursor.col;
+            self.buffer.cursor.move_to(row, col + 2);
+        }
+        self.schedule_auto_save();
+    }
+}
+
+/// `(title, #…)` of every heading in `lines`.
+fn note_headings<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    outline::headings(lines)
+        .into_iter()
+        .map(|h| (h.title, "#".repeat(h.level)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::model::buffer::Buffer;
+    use crate::test_util::{app_in, temp_dir};
+
+    fn type_into(app: &mut crate::app::App, line: &str, col: usize) {
+        app.buffer = Buffer::new();
+        app.buffer.replace_text(line);
+        app.buffer.cursor.move_to(0, col);
+        app.update_link_picker();
+    }
+
+    fn candidates(app: &crate::app::App) -> Vec<String> {
+        app.link_picker.as_ref().map_or_else(Vec::new, |picker| {
+            picker
+                .candidates
+                .iter()
+                .map(|(insert, _)| insert.clone())
+                .collect()
+        })
+    }
+
+    fn enter(app: &mut crate::app::App) {
+        let key = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.handle_key_link_picker(key));
+    }
+
+    #[test]
+    fn test_link_picker_offers_notes_and_closes_the_link() {
+        let vault = temp_dir();
+        for rel in ["alpha.md", "notes/beta.md", "archive/beta.md"] {
+            let path = vault.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let mut app = app_in(vault.path());
+
+        type_into(&mut app, "See [[be", 8);
+        let mut offered = candidates(&app);
+        offered.sort();
+        assert_eq!(offered, ["archive/beta", "notes/beta"]);
+
+        type_into(&mut app, "See [[alp", 9);
+        assert_eq!(candidates(&app), ["alpha"]);
+        enter(&mut app);
+        assert_eq!(app.buffer.line_text(0).unwrap(), "See [[alpha]]");
+        assert!(app.link_picker.is_none());
+
+        type_into(&mut app, "See alp", 7);
+        assert!(app.link_picker.is_none());
+    }
+
+    #[test]
+    fn test_link_picker_offers_headings_and_rereads_changed_notes() {
+        let vault = temp_dir();
+        let alpha = vault.path().join("alpha.md");
+        std::fs::write(&alpha, "# Alpha\n## Setup\n## Usage\n").unwrap();
+        let mut app = app_in(vault.path());
+
+        type_into(&mut app, "[[alpha#Us]] next", 10);
+        assert_eq!(candidates(&app), ["Usage"]);
+        assert!(app.link_headings.is_some());
+        enter(&mut app);
+        assert_eq!(app.buffer.line_text(0).unwrap(), "[[alpha#Usage]] next");
+        assert_eq!(app.buffer.cursor.col, 15);
+
+        std::fs::write(&alpha, "# Alpha\n## Uninstall\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&alpha)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        type_into(&mut app, "[[alpha#Un", 10);
+        assert_eq!(candidates(&app), ["Uninstall"]);
+
+        type_into(&mut app, "# Here\n[[#He", 0);
+        app.buffer.cursor.move_to(1, 5);
+        app.update_link_picker();
+        assert_eq!(candidates(&app), ["Here"]);
+    }
+}
@@ -1,29 +1,68 @@
+pub mod attachments;
 pub mod buffer_ops;
+pub mod bulk;
+pub mod citations;
+pub mod clipboard;
+pub mod collab;
+pub mod commands;
+pub mod diagram;
 pub mod file_io;
+pub mod graph;
+pub mod kanban;
 pub mod keys;
+pub mod link_index;
+pub mod link_picker;
 pub mod navigation;
+pub mod note_ops;
+pub mod outline;
+pub mod plugin_completion;
+pub mod plugins;
+pub mod preview;
+pub mod properties;
+pub mod quickfix;
+pub mod reports;
 pub mod search;
+pub mod spell;
+pub mod sync;
+pub mod tasks;
+pub mod typing;
+pub mod vault;
+pub mod workflow;
+pub mod wrap;
 
-use crate::app::{App, parse_plugin_command_input};
+use crate::app::{App, NOTIFICATION_TTL};
 use crate::msg::{Msg, PluginAction};
-use crate::plugin::PluginManager;
+use crate::view::split_heights;
 use anyhow::Result;
+use std::time::Instant;
 
 impl App {
     pub fn update(&mut self, msg: Msg) -> Result<()> {
+        let previous = (self.buffer.id, self.buffer.cursor.row);
         match msg {
             Msg::Key(key) => self.handle_key(key)?,
+            Msg::Paste(text) => self.handle_paste(text),
             Msg::InsertChar(ch) => {
+                if self.reject_readonly_edit() {
+                    return Ok(());
+                }
                 self.buffer.insert_char(ch);
+                self.auto_wrap_line();
                 self.mark_render_dirty();
                 self.schedule_auto_save();
             }
             Msg::DeleteChar => {
+                if self.reject_readonly_edit() {
+                    return Ok(());
+                }
                 self.buffer.delete_char_before();
                 self.mark_render_dirty();
                 self.schedule_auto_save();
             }
             Msg::NewLine => {
+                if self.reject_readonly_edit() {
+                    return Ok(());
+                }
                 self.buffer.insert_newline();
                 self.mark_render_dirty();
                 self.schedule_auto_save();
@@ -33,84 +72,84 @@ impl App {
             Msg::SaveActiveBuffer => self.save_buffer()?,
             Msg::SaveAllBuffers => self.save_all_buffers(),
             Msg::OpenFile(path) => self.open_file(path)?,
+            Msg::OpenUri(uri) => self.open_uri(&uri)?,
+            Msg::Capture(text) => {
+                for note in self.capture(&text) {
+                    self.push_notification(note);
+                }
+            }
             Msg::FileChanged(path) => self.handle_file_changed(path)?,
+            Msg::FileRenamed(from, to) => self.handle_file_renamed(from, to)?,
             Msg::PluginCommand(command) => self.handle_plugin_command(command),
             Msg::PluginEvent(_plugin_id, action) => self.handle_plugin_event(action),
+            Msg::PluginSyncProgress(line) => self.push_notification(line),
+            Msg::PluginSyncFinished(report) => self.handle_plugin_sync_finished(report),
+            Msg::PluginInstalled(repo, result) => self.handle_plugin_installed(repo, result),
+            Msg::PluginRegistryFetched(result) => self.handle_plugin_registry_fetched(result),
+            Msg::PluginFilesChanged(roots) => self.handle_plugin_files_changed(&roots),
+            Msg::Collab(event) => self.handle_collab_event(event),
+            Msg::SyncFinished(result) => {
+                self.handle_sync_finished(result);
+                self.mark_render_dirty();
+            }
+            Msg::HookFinished(event, result) => {
+                self.activities.finish(&event.activity());
+                match result {
+                    Ok(output) if output.is_empty() => {}
+                    Ok(output) => self.push_notification(output),
+                    Err(err) => self.push_notification(format!("hook failed: {err}")),
+                }
+            }
+            Msg::SearchIndexReady(index) => self.handle_search_index_ready(*index),
+            Msg::LinkIndexReady(index) => self.handle_link_index_ready(*index),
+            Msg::DiagramRendered(result) => {
+                self.activities.finish("diagram");
+                self.handle_diagram_rendered(result);
+            }
+            Msg::Mouse(event) => self.handle_mouse(event)?,
             Msg::Tick => self.handle_tick()?,
             Msg::Quit => self.should_quit = true,
             Msg::Resize(_w, h) => {
-                self.buffer.viewport.height = h.saturating_sub(3); // tab + status bar
+                // Zen mode drops the tab and status bars.
+                let chrome = if self.zen { 1 } else { 3 };
+                self.buffer.viewport.height = h.saturating_sub(chrome);
+                if let Some(split) = self.split.as_mut() {
+                    let (top, bottom) = split_heights(self.buffer.viewport.height);
+                    let (focused, other) = if split.focus_top {
+                        (top, bottom)
+                    } else {
+                        (bottom, top)
+                    };
+                    self.buffer.viewport.height = focused;
+                    split.other.viewport.height = other;
+                }
+                self.diagram_placed = false;
                 self.mark_render_dirty();
             }
             _ => {}
         }
+        self.sync_split();
+        self.refresh_breadcrumb();
+        self.refresh_backlink_count();
+        let previous_row = (previous.0 == self.buffer.id).then_some(previous.1);
+        self.keep_cursor_out_of_fold(previous_row);
         Ok(())
     }
-    pub(crate) fn handle_plugin_command(&mut self, command: String) {
-        let command = command.trim();
-        if command.is_empty() {
-            return;
-        }
-
-        let notifications = if let Some(raw_plugin_command) = command
-            .strip_prefix("plugin ")
-            .or_else(|| command.strip_prefix("p "))
-        {
-            let plugin_command = parse_plugin_command_input(raw_plugin_command);
-            if plugin_command.is_empty() {
-                vec!["usage: plugin <command> (alias: p <command>)".to_string()]
-            } else {
-                self.plugin_manager.execute_command(&plugin_command)
-            }
-        } else {
-            match command {
-                "help" => {
-                    let mut notes = vec!["built-ins:".to_string()];
-                    notes.push("  help".to_string());
-                    notes.push("  plugin <command> (alias: p <command>)".to_string());
-                    notes.push(
-                        "    examples: plugin word_count | plugin \"word count\"".to_string(),
-                    );
-                    notes.push("  plugins (alias: pl)".to_string());
-                    notes.push("  plugins.list (alias: pl.list)".to_string());
-                    notes.push("  plugins.errors (alias: pl.errors)".to_string());
-                    notes.push("  plugins.reload (alias: pl.reload)".to_string());
-                    notes.extend(self.plugin_manager.command_notifications());
-                    notes
-                }
-                "plugins" | "pl" => vec![self.plugin_manager.summary_notification()],
-                "plugins.list" | "pl.list" => self.plugin_manager.list_notifications(),
-                "plugins.errors" | "pl.errors" => {
-                    let errors = self.plugin_manager.error_notifications();
-                    if errors.is_empty() {
-                        vec!["plugins: no errors".to_string()]
-                    } else {
-                        errors
-                    }
-                }
-                "plugins.reload" | "pl.reload" => {
-                    self.plugin_manager = PluginManager::new(&self.config);
-                    let mut notes = vec!["plugins: reloaded".to_string()];
-                    notes.push(self.plugin_manager.summary_notification());
-                    notes.extend(self.plugin_manager.error_notifications());
-                    notes
-                }
-                _ => self.plugin_manager.execute_command(command),
-            }
-        };
-
-        for notification in notifications {
-            self.push_notification(notification);
-        }
-    }
     pub(crate) fn handle_plugin_event(&mut self, action: PluginAction) {
         match action {
             PluginAction::Notify(message) => self.push_notification(message),
+            PluginAction::Edit(edits) => self.apply_plugin_edits(edits),
+            PluginAction::Decorate(id, decorations) => {
+                self.set_plugin_decorations(id, decorations);
+            }
+            // Only commands ask, and `run_plugin_command` opens the prompt.
+            PluginAction::Prompt(_) => {}
             PluginAction::RequestRedraw => self.mark_render_dirty(),
         }
     }
     pub(crate) fn push_notification(&mut self, message: String) {
         self.notifications.push_back(message);
+        self.notification_until = Some(Instant::now() + NOTIFICATION_TTL);
         while self.notifications.len() > 8 {
             self.notifications.pop_front();
         }
@@ -1,10 +1,26 @@
 use crate::app::{
-    App, BacklinkEntry, WIKILINK_RE, parse_wikilink_target, same_file_path, sanitize_link_name,
+    App, BacklinkEntry, FinderMode, WIKILINK_RE, parse_wikilink_target, same_file_path,
+    sanitize_link_name,
 };
+use crate::hooks::HookEvent;
 use crate::model::buffer::Buffer;
+use crate::model::embed;
+use crate::model::file_tree::Grouping;
+use crate::model::frontmatter::Frontmatter;
+use crate::model::link;
+use crate::model::link_index;
 use crate::model::mode::Mode;
+use crate::model::pane::Split;
+use crate::model::pins;
+use crate::update::note_ops::rel_display;
+use crate::view::split_heights;
 use anyhow::Result;
-use std::path::PathBuf;
+use crossterm::Command;
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Position;
+use std::path::{Path, PathBuf};
 
 impl App {
     pub(crate) fn switch_tab_relative(&mut self, delta: isize) -> Result<()> {
@@ -21,6 +37,72 @@ impl App {
         let path = self.open_tabs[next].clone();
         self.activate_tab(path)
     }
+    /// Put the pinned notes of this vault first in the tab bar, opening
+    /// any that are not, and the other tabs after them in their order.
+    pub(crate) fn pin_tabs(&mut self) {
+        let root = self.config.vault_path();
+        let mut tabs: Vec<PathBuf> = self
+            .file_tree
+            .pinned
+            .iter()
+            .filter(|path| path.starts_with(&root) && path.is_file())
+            .cloned()
+            .collect();
+        for tab in self.open_tabs.drain(..) {
+            if !tabs.iter().any(|pinned| same_file_path(pinned, &tab)) {
+                tabs.push(tab);
+            }
+        }
+        self.open_tabs = tabs;
+    }
+
+    /// `:pin` / sidebar `p`: pin `path` above the sidebar tree and to the
+    /// front of the tabs, or unpin it.
+    pub(crate) fn toggle_pin(&mut self, path: PathBuf) -> String {
+        let root = self.config.vault_path();
+        let name = rel_display(&root, &path);
+        let pinned = &mut self.file_tree.pinned;
+        let message = match pinned.iter().position(|p| same_file_path(p, &path)) {
+            Some(idx) => {
+                pinned.remove(idx);
+                format!("Unpinned {name}")
+            }
+            None => {
+                pinned.push(path);
+                self.pin_tabs();
+                format!("Pinned {name}")
+            }
+        };
+        if let Err(err) = self.file_tree.refresh() {
+            return format!("pin: {err}");
+        }
+        self.mark_render_dirty();
+        match self.save_pins() {
+            Ok(()) => message,
+            Err(err) => format!("{message} (not saved: {err})"),
+        }
+    }
+
+    pub(crate) fn save_pins(&self) -> std::io::Result<()> {
+        pins::save(&pins::default_path(), &self.file_tree.pinned)
+    }
+
+    pub(crate) fn pin_command(&mut self) -> Vec<String> {
+        match self.buffer.path.clone() {
+            Some(path) if path.exists() => vec![self.toggle_pin(path)],
+            _ => vec!["pin: the note must be saved first".to_string()],
+        }
+    }
+
+    /// Sidebar `p`: pin or unpin the selected note.
+    pub(crate) fn pin_sidebar_selection(&mut self) {
+        let message = match self.file_tree.selected_node() {
+            Some(node) if !node.is_dir => self.toggle_pin(node.path.clone()),
+            _ => "pin: select a note".to_string(),
+        };
+        self.push_notification(message);
+    }
+
     pub(crate) fn active_tab_index(&self) -> Option<usize> {
         let active = self.buffer.path.as_ref()?;
         self.open_tabs
@@ -28,6 +110,7 @@ impl App {
             .position(|p| same_file_path(p, active))
     }
     pub(crate) fn activate_tab(&mut self, path: PathBuf) -> Result<()> {
+        let viewport_height = self.buffer.viewport.height;
         if let Some(active_path) = self.buffer.path.clone() {
             let current = std::mem::replace(&mut self.buffer, Buffer::new());
             self.inactive_buffers.insert(active_path, current);
@@ -40,8 +123,11 @@ impl App {
         };
 
         next.viewport.scroll_off = self.config.editor.scroll_off;
+        next.viewport.height = viewport_height;
+        next.viewport.typewriter = self.zen;
         self.buffer = next;
 
+        self.recent.touch(&path);
         if !self.open_tabs.iter().any(|p| same_file_path(p, &path)) {
             self.open_tabs.push(path);
         }
@@ -50,6 +136,7 @@ impl App {
             self.refresh_backlinks();
         }
 
+        self.decorate_buffer("open");
         self.mark_render_dirty();
         Ok(())
     }
@@ -67,27 +154,292 @@ impl App {
         self.file_tree.refresh()?;
         Ok(())
     }
+    /// `:backlinks`, or a click on the `↩ N` count: open the backlinks
+    /// panel, leaving it open if it already is.
+    pub(crate) fn open_backlinks_panel(&mut self) -> Result<()> {
+        if self.backlinks_visible {
+            return Ok(());
+        }
+        self.toggle_backlinks_panel()
+    }
+    /// `:split`: a second pane on the current note with its own cursor and
+    /// scroll position, stacked above the one being edited.
+    pub(crate) fn split_pane(&mut self) -> Vec<String> {
+        if self.zen {
+            return vec!["split: not available in zen mode".to_string()];
+        }
+        if self.split.is_some() {
+            return vec!["split: already split (Ctrl+W switches, :only closes)".to_string()];
+        }
+
+        let mut split = Split::new(&self.buffer);
+        let (top, bottom) = split_heights(self.buffer.viewport.height);
+        split.other.viewport.height = top;
+        self.buffer.viewport.height = bottom;
+        self.buffer.scroll_to_cursor();
+        self.split = Some(split);
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    /// Close the unfocused pane, giving its rows back.
+    pub(crate) fn close_split(&mut self) {
+        let Some(split) = self.split.take() else {
+            return;
+        };
+        self.buffer.viewport.height += split.other.viewport.height + 1;
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+    }
+
+    /// `Ctrl+W`: move focus to the other pane.
+    pub(crate) fn cycle_pane(&mut self) {
+        if let Some(split) = self.split.as_mut() {
+            split.toggle_focus(&mut self.buffer);
+            self.mark_render_dirty();
+        }
+    }
+
+    /// Both panes show one note; opening another closes the split.
+    pub(crate) fn sync_split(&mut self) {
+        if self
+            .split
+            .as_ref()
+            .is_some_and(|split| split.path != self.buffer.path)
+        {
+            self.close_split();
+        }
+    }
+
+    /// `:zen` / `Space z`: toggle the distraction-free, typewriter-scrolled
+    /// layout. Side panels stay hidden until zen mode is left.
+    pub(crate) fn toggle_zen(&mut self) {
+        self.close_split();
+        self.zen = !self.zen;
+        let viewport = &mut self.buffer.viewport;
+        viewport.typewriter = self.zen;
+        // The tab and status bars give their two rows to the editor.
+        viewport.height = if self.zen {
+            viewport.height + 2
+        } else {
+            viewport.height.saturating_sub(2)
+        };
+        if matches!(self.mode, Mode::Sidebar | Mode::Outline | Mode::Backlinks) {
+            self.mode = Mode::Normal;
+        }
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+    }
+    /// `:sidebar.group tags | <field> | folders`: lay the sidebar out by tag
+    /// or frontmatter value instead of by folder.
+    pub(crate) fn sidebar_group(&mut self, args: &str) -> Vec<String> {
+        let grouping = match args {
+            "" => return vec!["usage: sidebar.group tags | <field> | folders".to_string()],
+            "folders" => Grouping::Folders,
+            "tags" | "tag" => Grouping::Tags,
+            field => Grouping::Field(field.to_string()),
+        };
+        let shown = match grouping.key() {
+            Some(key) => format!("Sidebar grouped by {key}"),
+            None => "Sidebar shows folders".to_string(),
+        };
+        self.last_grouping = match &grouping {
+            Grouping::Folders => self.last_grouping.clone(),
+            other => other.clone(),
+        };
+        if let Err(err) = self.file_tree.set_grouping(grouping) {
+            return vec![format!("sidebar: {err}")];
+        }
+        self.sidebar_visible = true;
+        self.mode = Mode::Sidebar;
+        vec![shown]
+    }
+
+    /// `:reveal` / `Space r`: open the sidebar on the active note, expanding
+    /// the folders above it.
+    pub(crate) fn reveal_active_note(&mut self) -> Vec<String> {
+        let Some(path) = self.buffer.path.clone() else {
+            return vec!["reveal: no note open".to_string()];
+        };
+        match self.file_tree.reveal(&path) {
+            Ok(true) => {
+                self.sidebar_visible = true;
+                self.mode = Mode::Sidebar;
+                self.mark_render_dirty();
+                Vec::new()
+            }
+            Ok(false) => vec![format!("reveal: {} is not in the sidebar", path.display())],
+            Err(err) => vec![format!("reveal: {err}")],
+        }
+    }
+
+    /// Sidebar `v`: switch between the folder tree and the last grouping.
+    pub(crate) fn toggle_sidebar_grouping(&mut self) -> Result<()> {
+        let next = match self.file_tree.grouping {
+            Grouping::Folders => self.last_grouping.clone(),
+            _ => Grouping::Folders,
+        };
+        self.file_tree.set_grouping(next)
+    }
+
+    /// The frontmatter block while it is folded into its summary line.
+    pub(crate) fn folded_frontmatter(&self) -> Option<Frontmatter> {
+        if !self.config.editor.conceal_frontmatter || self.buffer.viewport.frontmatter_open {
+            return None;
+        }
+        self.buffer.frontmatter()
+    }
+
+    /// `za`: fold or unfold the frontmatter block.
+    pub(crate) fn toggle_frontmatter_fold(&mut self) {
+        if !self.config.editor.conceal_frontmatter || self.buffer.frontmatter().is_none() {
+            self.push_notification("No folded frontmatter here".to_string());
+            return;
+        }
+        let viewport = &mut self.buffer.viewport;
+        viewport.frontmatter_open = !viewport.frontmatter_open;
+        if !viewport.frontmatter_open {
+            self.keep_cursor_out_of_fold(None);
+        }
+        self.mark_render_dirty();
+    }
+
+    /// Cursor motions step over a folded frontmatter block: into it from
+    /// its summary line lands on the body, into it from below lands on the
+    /// summary line. Typing inside it unfolds it instead.
+    pub(crate) fn keep_cursor_out_of_fold(&mut self, previous_row: Option<usize>) {
+        let Some(fm) = self.folded_frontmatter() else {
+            return;
+        };
+        let row = self.buffer.cursor.row;
+        if row >= fm.line_count || (row == 0 && self.mode != Mode::Insert) {
+            return;
+        }
+        if self.mode == Mode::Insert {
+            self.buffer.viewport.frontmatter_open = true;
+        } else if previous_row.is_some_and(|prev| prev < row) {
+            let body = fm
+                .line_count
+                .min(self.buffer.line_count().saturating_sub(1));
+            self.buffer.cursor.move_to(body, 0);
+        } else {
+            self.buffer.cursor.move_to(0, 0);
+        }
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+    }
+
+    /// `:minimap`: show or hide the overview column. Clicking it needs mouse
+    /// reporting, which is only switched on while it is visible so the
+    /// terminal's own text selection keeps working otherwise.
+    pub(crate) fn set_minimap(&mut self, visible: bool) {
+        self.minimap_visible = visible;
+        self.minimap = None;
+        let mut escape = String::new();
+        let _ = if visible {
+            EnableMouseCapture.write_ansi(&mut escape)
+        } else {
+            DisableMouseCapture.write_ansi(&mut escape)
+        };
+        self.terminal_escape.push_str(&escape);
+        self.mark_render_dirty();
+    }
+
+    /// A left click on the minimap jumps to the lines under it.
+    pub(crate) fn handle_mouse(&mut self, event: MouseEvent) -> Result<()> {
+        if event.kind != MouseEventKind::Down(MouseButton::Left) || self.zen {
+            return Ok(());
+        }
+        let position = Position::new(event.column, event.row);
+        if self
+            .backlink_badge
+            .is_some_and(|badge| badge.contains(position))
+        {
+            return self.open_backlinks_panel();
+        }
+        let Some(minimap) = &self.minimap else {
+            return Ok(());
+        };
+        let area = minimap.area;
+        if !area.contains(position) {
+            return Ok(());
+        }
+        let line = minimap.map.line_at(usize::from(event.row - area.y));
+        let line = line.min(self.buffer.line_count().saturating_sub(1));
+        self.buffer.cursor.move_to(line, 0);
+        let half = usize::from(self.buffer.viewport.height / 2);
+        self.buffer.viewport.top_line = line.saturating_sub(half);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        Ok(())
+    }
+
     pub(crate) fn refresh_backlinks(&mut self) {
-        self.backlinks.clear();
+        self.backlinks = self.collect_backlinks();
         self.backlinks_selected = 0;
+        self.backlink_count = self
+            .buffer
+            .path
+            .clone()
+            .map(|path| (path, self.backlinks.len()));
+    }
 
-        let Some(active_path) = self.buffer.path.clone() else {
+    /// Recount the active note's backlinks after a tab switch or a change
+    /// to another note.
+    pub(crate) fn refresh_backlink_count(&mut self) {
+        let Some(path) = &self.buffer.path else {
+            self.backlink_count = None;
             return;
         };
+        if self
+            .backlink_count
+            .as_ref()
+            .is_some_and(|(counted, _)| same_file_path(counted, path))
+        {
+            return;
+        }
+        let count = self.collect_backlinks().len();
+        self.backlink_count = Some((path.clone(), count));
+    }
 
-        let Some(note_name) = active_path
+    /// Lines in other notes that link to the active one.
+    fn collect_backlinks(&self) -> Vec<BacklinkEntry> {
+        match &self.buffer.path {
+            Some(path) => self.backlinks_to(path),
+            None => Vec::new(),
+        }
+    }
+
+    /// Lines in other notes that link to `target`, by name or by alias.
+    pub(crate) fn backlinks_to(&self, target: &PathBuf) -> Vec<BacklinkEntry> {
+        if let Some(index) = &self.link_index {
+            return index
+                .backlinks(target)
+                .into_iter()
+                .map(|backlink| BacklinkEntry {
+                    path: backlink.path.clone(),
+                    line: backlink.line + 1,
+                    preview: backlink.preview.to_string(),
+                    anchor: backlink.anchor.map(str::to_string),
+                })
+                .collect();
+        }
+
+        let mut backlinks = Vec::new();
+        let Some(note_name) = target
             .file_stem()
             .map(|stem| stem.to_string_lossy().to_string())
         else {
-            return;
+            return backlinks;
         };
+        let aliases = self.note_index.aliases_of(target);
 
         let files = self.file_tree.all_file_paths();
         for path in files {
             if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
                 continue;
             }
-            if same_file_path(&path, &active_path) {
+            if same_file_path(&path, target) {
                 continue;
             }
 
@@ -96,38 +448,53 @@ impl App {
             };
 
             for (idx, line) in contents.lines().enumerate() {
-                let has_link = WIKILINK_RE.find_iter(line).any(|m| {
-                    parse_wikilink_target(&line[m.start()..m.end()])
-                        .is_some_and(|target| target.eq_ignore_ascii_case(&note_name))
+                let mut links = WIKILINK_RE.find_iter(line).filter(|m| {
+                    parse_wikilink_target(m.as_str()).is_some_and(|target| {
+                        target.eq_ignore_ascii_case(&note_name)
+                            || aliases.contains(&target.to_lowercase().as_str())
+                    })
                 });
 
-                if has_link {
-                    self.backlinks.push(BacklinkEntry {
+                if let Some(first) = links.next() {
+                    let anchor = std::iter::once(first)
+                        .chain(links)
+                        .find_map(|m| link::wikilink_anchor(m.as_str()));
+                    backlinks.push(BacklinkEntry {
                         path: path.clone(),
                         line: idx + 1,
                         preview: line.trim().to_string(),
+                        anchor: anchor.map(str::to_string),
                     });
                 }
             }
         }
 
-        self.backlinks.sort_by(|a, b| {
+        backlinks.sort_by(|a, b| {
             a.path
                 .cmp(&b.path)
                 .then(a.line.cmp(&b.line))
                 .then(a.preview.cmp(&b.preview))
         });
+        backlinks
     }
     pub(crate) fn follow_wikilink_under_cursor(&mut self) -> Result<()> {
         let Some(link_text) = self.wikilink_under_cursor() else {
-            self.notifications
-                .push_back("No WikiLink under cursor".to_string());
+            self.push_notification("No WikiLink under cursor".to_string());
             return Ok(());
         };
 
-        if let Some(target) = self.resolve_wikilink_target(&link_text) {
-            self.open_file(target)?;
-            return Ok(());
+        let anchor = self
+            .wikilink_text_under_cursor()
+            .and_then(|text| link::wikilink_anchor(&text).map(str::to_string));
+        let candidates = self.wikilink_candidates(&link_text);
+        let dir = self.buffer.path.as_deref().and_then(Path::parent);
+        if candidates.len() > 1 && candidates[0].parent() != dir {
+            self.finder_link_choices = candidates;
+            self.finder_link_anchor = anchor;
+            return self.open_finder(FinderMode::LinkChoices);
+        }
+        if let Some(target) = candidates.into_iter().next() {
+            return self.open_note_at_anchor(target, anchor.as_deref());
         }
 
         let path = self.config.vault_path().join(format!("{link_text}.md"));
@@ -136,6 +503,34 @@ impl App {
         self.mark_render_dirty();
         Ok(())
     }
+    /// Open a wikilink's note at its `#Heading` or `^block`, if it has one.
+    pub(crate) fn open_note_at_anchor(
+        &mut self,
+        path: PathBuf,
+        anchor: Option<&str>,
+    ) -> Result<()> {
+        self.open_file(path)?;
+        if let Some(anchor) = anchor
+            && !self.jump_to_anchor(anchor)
+        {
+            self.push_notification(match anchor.strip_prefix('^') {
+                Some(id) => format!("No block ^{id} in this note"),
+                None => format!("No heading \"{anchor}\" in this note"),
+            });
+        }
+        Ok(())
+    }
+    /// Move to the `#Heading` or `^block` an anchor names in the active
+    /// note; false when the note has no such anchor.
+    pub(crate) fn jump_to_anchor(&mut self, anchor: &str) -> bool {
+        let Some(range) = embed::excerpt_range(&self.buffer_lines(), Some(anchor)) else {
+            return false;
+        };
+        self.buffer.cursor.move_to(range.start, 0);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        true
+    }
     pub(crate) fn confirm_create_wikilink(&mut self) -> Result<()> {
         let Some(path) = self.pending_create_path.take() else {
             self.mode = Mode::Normal;
@@ -153,6 +548,7 @@ impl App {
 
         if !path.exists() {
             std::fs::write(&path, format!("# {title}\n\n"))?;
+            self.run_hook(HookEvent::Create, &path, None);
         }
 
         self.mode = Mode::Normal;
@@ -161,35 +557,134 @@ impl App {
         Ok(())
     }
     pub(crate) fn wikilink_under_cursor(&self) -> Option<String> {
+        parse_wikilink_target(&self.wikilink_text_under_cursor()?)
+    }
+    /// The whole `[[…]]` under the cursor, anchor and alias included.
+    pub(crate) fn wikilink_text_under_cursor(&self) -> Option<String> {
         let line = self.buffer.line_text(self.buffer.cursor.row)?;
         let col = self.buffer.cursor.col;
 
-        for m in WIKILINK_RE.find_iter(&line) {
-            if col >= m.start() && col < m.end() {
-                return parse_wikilink_target(&line[m.start()..m.end()]);
-            }
+        WIKILINK_RE
+            .find_iter(&line)
+            .find(|m| col >= m.start() && col < m.end())
+            .map(|m| m.as_str().to_string())
+    }
+    /// Notes a wikilink could mean, the one in the active note's folder
+    /// first: a vault path, then notes whose path ends with the target in
+    /// any case, then aliases.
+    pub(crate) fn wikilink_candidates(&self, link_text: &str) -> Vec<PathBuf> {
+        let clean = sanitize_link_name(link_text);
+        if clean.is_empty() {
+            return Vec::new();
         }
 
-        None
+        let mut found: Vec<PathBuf> = match &self.link_index {
+            Some(index) => index.candidates(&clean).into_iter().cloned().collect(),
+            None => {
+                let notes: Vec<PathBuf> = self
+                    .file_tree
+                    .all_file_paths()
+                    .into_iter()
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                    .collect();
+                let found: Vec<PathBuf> =
+                    link_index::path_candidates(&self.config.vault_path(), &notes, &clean)
+                        .into_iter()
+                        .cloned()
+                        .collect();
+                if found.is_empty() {
+                    self.note_index
+                        .alias_target(&clean)
+                        .filter(|path| path.exists())
+                        .cloned()
+                        .into_iter()
+                        .collect()
+                } else {
+                    found
+                }
+            }
+        };
+        let dir = self.buffer.path.as_deref().and_then(Path::parent);
+        link_index::prefer_folder(&mut found, dir);
+        found
     }
+
+    /// The note a wikilink means, picking the first candidate when the name
+    /// is ambiguous.
     pub(crate) fn resolve_wikilink_target(&self, link_text: &str) -> Option<PathBuf> {
-        let clean = sanitize_link_name(link_text);
-        if clean.is_empty() {
-            return None;
+        self.wikilink_candidates(link_text).into_iter().next()
+    }
+
+    /// Open a `blackbox://open?path=…[&heading=…]` link, jumping to the
+    /// heading when it names one in the note.
+    pub(crate) fn open_uri(&mut self, uri: &str) -> Result<()> {
+        let Some(target) = link::parse_note_uri(uri) else {
+            self.push_notification(format!("uri: cannot parse {uri}"));
+            return Ok(());
+        };
+
+        let rel = std::path::Path::new(&target.path);
+        if rel.is_absolute()
+            || rel
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            self.push_notification(format!("uri: {} is outside the vault", target.path));
+            return Ok(());
+        }
+        let path = self.config.vault_path().join(rel);
+        if !path.is_file() {
+            self.push_notification(format!("uri: no note at {}", target.path));
+            return Ok(());
         }
 
-        let vault = self.config.vault_path();
-        let exact = vault.join(format!("{clean}.md"));
-        if exact.exists() {
-            return Some(exact);
+        self.mode = Mode::Normal;
+        self.open_file(path)?;
+
+        if let Some(heading) = target.heading
+            && !self.jump_to_anchor(&heading)
+        {
+            self.push_notification(format!("uri: no heading \"{heading}\""));
         }
+        self.mark_render_dirty();
+        Ok(())
+    }
 
-        let expected = format!("{clean}.md").to_lowercase();
-        self.file_tree.all_file_paths().into_iter().find(|path| {
-            path.extension().and_then(|ext| ext.to_str()) == Some("md")
-                && path
-                    .file_name()
-                    .is_some_and(|name| name.to_string_lossy().to_lowercase() == expected)
-        })
+    /// `gx`: open the link or URL under the cursor. URLs go to the browser;
+    /// relative targets resolve against the note's folder, opening notes here
+    /// and other files in their default application.
+    pub(crate) fn open_link_under_cursor(&mut self) -> Result<()> {
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let Some(target) = link::link_target_at(&line, self.buffer.cursor.col) else {
+            self.push_notification("No link under cursor".to_string());
+            return Ok(());
+        };
+
+        if link::is_url(&target) {
+            if let Err(err) = crate::opener::open(&target) {
+                self.push_notification(format!("gx: {err}"));
+            }
+            return Ok(());
+        }
+
+        let file = link::percent_decode(target.split('#').next().unwrap_or_default());
+        let base = self
+            .buffer
+            .path
+            .as_ref()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| self.config.vault_path());
+        let path = base.join(&file);
+        if !path.exists() {
+            self.push_notification(format!("gx: {file} not found"));
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            self.open_file(path)?;
+        } else if let Err(err) = crate::opener::open(&path.to_string_lossy()) {
+            self.push_notification(format!("gx: {err}"));
+        }
+        Ok(())
     }
 }
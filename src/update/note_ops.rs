@@ -0,0 +1,766 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::app::{App, ConfirmAction, PendingConfirm, same_file_path};
+use crate::hooks::HookEvent;
+use crate::model::buffer::Buffer;
+use crate::model::frontmatter::Frontmatter;
+use crate::model::link::{self, NoteName};
+use crate::model::mode::Mode;
+use crate::model::outline;
+use crate::model::trash;
+
+/// A rename or move checked and counted, waiting for the user to confirm it.
+pub(crate) struct RenamePlan {
+    from: PathBuf,
+    to: PathBuf,
+    /// New text for every note with links to rewrite.
+    rewrites: Vec<(PathBuf, String)>,
+    links: usize,
+}
+
+impl App {
+    /// `:rename <name>`: rename the active note and point every wikilink to
+    /// it at the new name. A bare name stays in the note's folder; a name
+    /// with `/` is relative to the vault. Asks first, with the count of
+    /// links and notes it will touch.
+    pub(crate) fn rename_command(&mut self, args: &str) -> Vec<String> {
+        let Some(from) = self.buffer.path.clone() else {
+            return vec!["rename: no note open".to_string()];
+        };
+        let name = args.trim().trim_end_matches(".md");
+        if name.is_empty() {
+            return vec!["usage: rename <name> | rename <folder/name>".to_string()];
+        }
+        if !inside_vault(name) {
+            return vec![format!("rename: {name} must stay inside the vault")];
+        }
+
+        let to = if name.contains('/') {
+            self.config.vault_path().join(format!("{name}.md"))
+        } else {
+            from.with_file_name(format!("{name}.md"))
+        };
+        self.propose_rename("rename", from, to)
+    }
+
+    /// `:extract <name> [--heading]` from Visual mode: move the selection
+    /// into a new note and leave `[[name]]` in its place. The note is titled
+    /// `name`, or with `--heading` after the heading the selection sits
+    /// under. Names resolve like `:rename`.
+    pub(crate) fn extract_command(&mut self, args: &str) -> Vec<String> {
+        let usage = || vec!["usage: extract <name> [--heading] (from Visual mode)".to_string()];
+        let Some(linewise) = self.visual_command else {
+            return usage();
+        };
+        let (name, with_heading) = match args.trim().strip_suffix("--heading") {
+            Some(name) => (name.trim(), true),
+            None => (args.trim(), false),
+        };
+        let name = name.trim_end_matches(".md");
+        if name.is_empty() {
+            return usage();
+        }
+        if !inside_vault(name) {
+            return vec![format!("extract: {name} must stay inside the vault")];
+        }
+        if self.reject_readonly_edit() {
+            return Vec::new();
+        }
+        let Some(range) = self.buffer.selection_char_range(linewise) else {
+            return usage();
+        };
+        let selected = self.buffer.rope.slice(range.clone()).to_string();
+        if selected.trim().is_empty() {
+            return vec!["extract: the selection is empty".to_string()];
+        }
+
+        let root = self.config.vault_path();
+        let to = match &self.buffer.path {
+            Some(from) if !name.contains('/') => from.with_file_name(format!("{name}.md")),
+            _ => root.join(format!("{name}.md")),
+        };
+        if to.exists() {
+            return vec![format!(
+                "extract: {} already exists",
+                rel_display(&root, &to)
+            )];
+        }
+        let title = if with_heading {
+            let row = self.buffer.rope.char_to_line(range.start);
+            let lines: Vec<String> = (0..self.buffer.line_count())
+                .filter_map(|i| self.buffer.line_text(i))
+                .collect();
+            let headings = outline::headings(lines.iter().map(String::as_str));
+            match outline::section_at(&headings, row, lines.len()).0 {
+                Some(heading) => heading.title.clone(),
+                None => return vec!["extract: no heading above the selection".to_string()],
+            }
+        } else {
+            name.rsplit('/').next().unwrap_or(name).to_string()
+        };
+
+        let written = to
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&to, format!("# {title}\n\n{}\n", selected.trim_end())));
+        if let Err(err) = written {
+            return vec![format!("extract: cannot write {}: {err}", to.display())];
+        }
+        self.run_hook(HookEvent::Create, &to, None);
+
+        let link = if linewise {
+            format!("[[{name}]]\n")
+        } else {
+            format!("[[{name}]]")
+        };
+        self.buffer.cursor.clear_selection();
+        self.buffer.replace_chars(range, &link);
+        self.buffer.clamp_cursor();
+        self.buffer.scroll_to_cursor();
+        self.schedule_auto_save();
+        if let Err(err) = self.file_tree.refresh() {
+            return vec![format!("extract: {err}")];
+        }
+        self.refresh_note_index();
+        self.update_search_index(&to);
+        self.update_link_index(&to);
+        self.mark_render_dirty();
+
+        let lines = selected.trim_end().lines().count();
+        vec![format!(
+            "Extracted {lines} line{} to {}",
+            plural(lines),
+            rel_display(&root, &to)
+        )]
+    }
+
+    /// `:move <folder>`: move the active note into a vault folder, creating
+    /// it if needed, and fix path-based links to and from it.
+    pub(crate) fn move_command(&mut self, args: &str) -> Vec<String> {
+        let Some(from) = self.buffer.path.clone() else {
+            return vec!["move: no note open".to_string()];
+        };
+        if args.trim().is_empty() {
+            return vec!["usage: move <folder> (/ for the vault root)".to_string()];
+        }
+        let folder = args.trim().trim_matches('/');
+        if !inside_vault(folder) {
+            return vec![format!("move: {folder} must stay inside the vault")];
+        }
+        let Some(file_name) = from.file_name() else {
+            return vec!["move: the note has no file name".to_string()];
+        };
+        let to = self.config.vault_path().join(folder).join(file_name);
+        self.propose_rename("move", from, to)
+    }
+
+    /// Check `from` → `to`, count the links it rewrites and ask to go ahead.
+    fn propose_rename(&mut self, verb: &str, from: PathBuf, to: PathBuf) -> Vec<String> {
+        let root = self.config.vault_path();
+        if same_file_path(&from, &to) {
+            return vec![format!("{verb}: already {}", rel_display(&root, &to))];
+        }
+        if to.exists() && !is_case_change(&from, &to) {
+            return vec![format!(
+                "{verb}: {} already exists",
+                rel_display(&root, &to)
+            )];
+        }
+
+        let plan = self.plan_rename(from, to);
+        let (from_rel, to_rel) = (rel_display(&root, &plan.from), rel_display(&root, &plan.to));
+        let prompt = if plan.links == 0 {
+            format!("{verb} {from_rel} → {to_rel} (no links)?")
+        } else {
+            format!(
+                "{verb} {from_rel} → {to_rel}, rewriting {} link{} in {} note{}?",
+                plan.links,
+                plural(plan.links),
+                plan.rewrites.len(),
+                plural(plan.rewrites.len())
+            )
+        };
+        self.pending_confirm = Some(PendingConfirm {
+            prompt,
+            action: ConfirmAction::Rename(plan),
+        });
+        self.mode = Mode::Confirm;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    /// Find every note linking to `from` by name or path, and the moved
+    /// note's own relative links, reading open buffers rather than disk so
+    /// unsaved edits are rewritten too.
+    fn plan_rename(&self, from: PathBuf, to: PathBuf) -> RenamePlan {
+        let root = self.config.vault_path();
+        let rel = |path: &Path| rel_display(&root, path);
+        let files = self.file_tree.all_file_paths();
+        let old = NoteName::of(&rel(&from));
+        let new_rel = rel(&to);
+        // Keep links unique when another note already has the new name.
+        let clash = files.iter().any(|file| {
+            !same_file_path(file, &from)
+                && !same_file_path(file, &to)
+                && file.file_stem().is_some_and(|stem| {
+                    stem.eq_ignore_ascii_case(to.file_stem().unwrap_or_default())
+                })
+        });
+        let new = NoteName {
+            name: link::wikilink_target(&new_rel, clash),
+            path: link::wikilink_target(&new_rel, true),
+        };
+
+        let mut plan = RenamePlan {
+            from,
+            to,
+            rewrites: Vec::new(),
+            links: 0,
+        };
+        for file in files {
+            if file.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(text) = self.note_text(&file) else {
+                continue;
+            };
+            let (renamed, wikilinks) = link::rename_wikilinks(&text, &old, &new);
+            let old_dir = file.parent().unwrap_or(&root);
+            let new_dir = if same_file_path(&file, &plan.from) {
+                plan.to.parent().unwrap_or(&root)
+            } else {
+                old_dir
+            };
+            let (rebased, links) =
+                link::rebase_markdown_links(&renamed, old_dir, new_dir, &plan.from, &plan.to);
+            if wikilinks + links > 0 {
+                plan.links += wikilinks + links;
+                plan.rewrites.push((file, rebased));
+            }
+        }
+        plan
+    }
+
+    /// A note's current text: its buffer if open, otherwise the file.
+    pub(crate) fn note_text(&self, path: &PathBuf) -> Option<String> {
+        if self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, path))
+        {
+            return Some(self.buffer.rope.to_string());
+        }
+        if let Some(buffer) = self.inactive_buffers.get(path) {
+            return Some(buffer.rope.to_string());
+        }
+        std::fs::read_to_string(path).ok()
+    }
+
+    /// Give a note new text: in its buffer when open (saving it), otherwise
+    /// on disk.
+    pub(crate) fn replace_note_text(&mut self, path: &PathBuf, text: &str) -> std::io::Result<()> {
+        if let Some(active) = self.buffer.path.clone()
+            && same_file_path(&active, path)
+        {
+            let cursor = self.buffer.cursor_char_offset();
+            let len = self.buffer.rope.len_chars();
+            self.buffer.replace_chars(0..len, text);
+            self.buffer.set_cursor_char_offset(cursor);
+            self.save_active_buffer_at_path(active);
+            return Ok(());
+        }
+        if let Some(buffer) = self.inactive_buffers.get_mut(path) {
+            let len = buffer.rope.len_chars();
+            buffer.replace_chars(0..len, text);
+            buffer.clamp_cursor();
+            self.save_inactive_buffer(path);
+            return Ok(());
+        }
+        std::fs::write(path, text)
+    }
+
+    pub(crate) fn apply_rename(&mut self, plan: RenamePlan) -> Vec<String> {
+        let RenamePlan {
+            from,
+            to,
+            rewrites,
+            links,
+        } = plan;
+        let root = self.config.vault_path();
+        if let Some(parent) = to.parent()
+            && let Err(err) = std::fs::create_dir_all(parent)
+        {
+            return vec![format!("rename: cannot create {}: {err}", parent.display())];
+        }
+        if let Err(err) = std::fs::rename(&from, &to) {
+            return vec![format!("rename: {err}")];
+        }
+
+        self.retarget_open_paths(&from, &to);
+
+        let mut failed = Vec::new();
+        for (path, text) in rewrites {
+            let path = if same_file_path(&path, &from) {
+                to.clone()
+            } else {
+                path
+            };
+            if let Err(err) = self.replace_note_text(&path, &text) {
+                failed.push(format!("rename: cannot rewrite {}: {err}", path.display()));
+            }
+        }
+        if let Some(active) = self.buffer.path.clone()
+            && same_file_path(&active, &to)
+        {
+            self.save_active_buffer_at_path(active);
+        }
+
+        self.run_hook(HookEvent::Rename, &to, Some(&from));
+        if let Err(err) = self.file_tree.refresh() {
+            failed.push(format!("rename: cannot refresh the sidebar: {err}"));
+        }
+        self.refresh_note_index();
+        self.backlink_count = None;
+        if self.backlinks_visible {
+            self.refresh_backlinks();
+        }
+        self.mark_render_dirty();
+
+        let mut notes = vec![format!(
+            "{} → {} ({links} link{} updated)",
+            rel_display(&root, &from),
+            rel_display(&root, &to),
+            plural(links)
+        )];
+        notes.extend(failed);
+        notes
+    }
+
+    /// `:merge <note>`: append the active note to `note`, point links to it
+    /// at `note` instead and move it to the trash, after a prompt.
+    pub(crate) fn merge_command(&mut self, args: &str) -> Vec<String> {
+        let Some(from) = self.buffer.path.clone() else {
+            return vec!["merge: no note open".to_string()];
+        };
+        let name = args.trim().trim_end_matches(".md");
+        if name.is_empty() {
+            return vec!["usage: merge <note>".to_string()];
+        }
+        let root = self.config.vault_path();
+        let Some(to) = self.resolve_wikilink_target(name) else {
+            return vec![format!("merge: no note named {name}")];
+        };
+        if same_file_path(&from, &to) {
+            return vec!["merge: cannot merge a note into itself".to_string()];
+        }
+
+        let plan = self.plan_rename(from, to);
+        let elsewhere: Vec<&PathBuf> = plan
+            .rewrites
+            .iter()
+            .map(|(path, _)| path)
+            .filter(|path| !same_file_path(path, &plan.from))
+            .collect();
+        let links = if elsewhere.is_empty() {
+            "no links to rewrite".to_string()
+        } else {
+            format!(
+                "rewriting links in {} note{}",
+                elsewhere.len(),
+                plural(elsewhere.len())
+            )
+        };
+        let prompt = format!(
+            "merge {} into {}, {links}, and move it to {}?",
+            rel_display(&root, &plan.from),
+            rel_display(&root, &plan.to),
+            trash::DIR
+        );
+        self.pending_confirm = Some(PendingConfirm {
+            prompt,
+            action: ConfirmAction::Merge(plan),
+        });
+        self.mode = Mode::Confirm;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    /// Carry out a confirmed `:merge`: the old note's body (relative links
+    /// rebased, frontmatter dropped) goes below the target's text.
+    pub(crate) fn apply_merge(&mut self, plan: RenamePlan) -> anyhow::Result<Vec<String>> {
+        let RenamePlan {
+            from,
+            to,
+            rewrites,
+            links,
+        } = plan;
+        let root = self.config.vault_path();
+        let mut moved_text = None;
+        let mut failed = Vec::new();
+        for (path, text) in rewrites {
+            if same_file_path(&path, &from) {
+                moved_text = Some(text);
+            } else if let Err(err) = self.replace_note_text(&path, &text) {
+                failed.push(format!("merge: cannot rewrite {}: {err}", path.display()));
+            }
+        }
+        let Some(moved) = moved_text.or_else(|| self.note_text(&from)) else {
+            return Ok(vec![format!("merge: cannot read {}", from.display())]);
+        };
+        let body_start = Frontmatter::parse(&moved).map_or(0, |fm| fm.line_count);
+        let body: Vec<&str> = moved.lines().skip(body_start).collect();
+        let Some(target) = self.note_text(&to) else {
+            return Ok(vec![format!("merge: cannot read {}", to.display())]);
+        };
+        let merged = format!("{}\n\n{}\n", target.trim_end(), body.join("\n").trim());
+        if let Err(err) = self.replace_note_text(&to, &merged) {
+            return Ok(vec![format!("merge: cannot write {}: {err}", to.display())]);
+        }
+
+        let mut notes = vec![format!(
+            "Merged {} into {} ({links} link{} updated)",
+            rel_display(&root, &from),
+            rel_display(&root, &to),
+            plural(links)
+        )];
+        let is_active = self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, &from));
+        notes.extend(self.apply_delete(&from)?);
+        if is_active {
+            self.open_file(to)?;
+        }
+        notes.extend(failed);
+        Ok(notes)
+    }
+
+    /// Move each note into `folder` without asking, fixing links as
+    /// `:move` does. Returns where each went and any failures.
+    pub(crate) fn move_notes(
+        &mut self,
+        paths: &[PathBuf],
+        folder: &str,
+    ) -> (Vec<(PathBuf, PathBuf)>, Vec<String>) {
+        let root = self.config.vault_path();
+        let mut moved = Vec::new();
+        let mut failed = Vec::new();
+        for from in paths {
+            let Some(file_name) = from.file_name() else {
+                continue;
+            };
+            let to = root.join(folder).join(file_name);
+            if same_file_path(from, &to) {
+                continue;
+            }
+            if to.exists() {
+                failed.push(format!("move: {} already exists", rel_display(&root, &to)));
+                continue;
+            }
+            let plan = self.plan_rename(from.clone(), to.clone());
+            let notes = self.apply_rename(plan);
+            if to.exists() {
+                moved.push((from.clone(), to));
+                failed.extend(notes.into_iter().skip(1));
+            } else {
+                failed.extend(notes);
+            }
+        }
+        (moved, failed)
+    }
+
+    /// `:delete`: move the active note to the vault trash, after a prompt
+    /// that warns when other notes still link to it.
+    pub(crate) fn delete_command(&mut self) -> Vec<String> {
+        match self.buffer.path.clone() {
+            Some(path) => {
+                self.begin_delete(path);
+                Vec::new()
+            }
+            None => vec!["delete: no note open".to_string()],
+        }
+    }
+
+    /// Sidebar `d`: delete the selected file.
+    pub(crate) fn begin_sidebar_delete(&mut self) {
+        match self.file_tree.selected_node() {
+            Some(node) if !node.is_dir && node.path.exists() => {
+                let path = node.path.clone();
+                self.begin_delete(path);
+            }
+            _ => self.push_notification("delete: select a note".to_string()),
+        }
+    }
+
+    fn begin_delete(&mut self, path: PathBuf) {
+        let shown = rel_display(&self.config.vault_path(), &path);
+        let mut linking: Vec<PathBuf> = self
+            .backlinks_to(&path)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        linking.dedup();
+        let prompt = match linking.len() {
+            0 => format!("move {shown} to {}?", trash::DIR),
+            n => format!(
+                "move {shown} to {}? {n} note{} still link{} to it",
+                trash::DIR,
+                plural(n),
+                if n == 1 { "s" } else { "" }
+            ),
+        };
+        self.pending_confirm = Some(PendingConfirm {
+            prompt,
+            action: ConfirmAction::Delete(path),
+        });
+        self.mode = Mode::Confirm;
+        self.mark_render_dirty();
+    }
+
+    /// Move `path` to the trash and close its tab. Unsaved edits are
+    /// written first so the trashed copy is the latest one.
+    pub(crate) fn apply_delete(&mut self, path: &PathBuf) -> anyhow::Result<Vec<String>> {
+        let root = self.config.vault_path();
+        let is_active = self
+            .buffer
+            .path
+            .as_ref()
+            .is_some_and(|active| same_file_path(active, path));
+        let unsaved = if is_active {
+            self.buffer.dirty.then(|| self.buffer.rope.to_string())
+        } else {
+            self.inactive_buffers
+                .get(path)
+                .filter(|buffer| buffer.dirty)
+                .map(|buffer| buffer.rope.to_string())
+        };
+        if let Some(text) = unsaved
+            && let Err(err) = std::fs::write(path, text)
+        {
+            return Ok(vec![format!(
+                "delete: cannot save {}: {err}",
+                path.display()
+            )]);
+        }
+        let trashed = match trash::move_to_trash(&root, path) {
+            Ok(trashed) => trashed,
+            Err(err) => return Ok(vec![format!("delete: {err}")]),
+        };
+
+        let tab = self
+            .open_tabs
+            .iter()
+            .position(|tab| same_file_path(tab, path));
+        self.open_tabs.retain(|tab| !same_file_path(tab, path));
+        self.inactive_buffers.remove(path);
+        let pins = self.file_tree.pinned.len();
+        self.file_tree
+            .pinned
+            .retain(|pin| !same_file_path(pin, path));
+        if self.file_tree.pinned.len() != pins
+            && let Err(err) = self.save_pins()
+        {
+            self.push_notification(format!("pin: cannot save pins: {err}"));
+        }
+        if is_active {
+            self.buffer.path = None;
+            let next = tab
+                .and_then(|idx| {
+                    self.open_tabs
+                        .get(idx.min(self.open_tabs.len().saturating_sub(1)))
+                })
+                .cloned();
+            match next {
+                Some(next) => self.activate_tab(next)?,
+                None => self.open_scratch()?,
+            }
+        }
+
+        self.run_hook(HookEvent::Delete, path, None);
+        self.file_tree.refresh()?;
+        self.refresh_note_index();
+        self.backlink_count = None;
+        if self.backlinks_visible {
+            self.refresh_backlinks();
+        }
+        self.mark_render_dirty();
+        Ok(vec![format!(
+            "Moved {} to {}",
+            rel_display(&root, path),
+            rel_display(&root, &trashed)
+        )])
+    }
+
+    /// Make the scratch note active when no other tab is left open.
+    fn open_scratch(&mut self) -> anyhow::Result<()> {
+        let scratch = self.config.scratch_path();
+        if scratch.exists() {
+            return self.activate_tab(scratch);
+        }
+        let mut buffer = Buffer::new();
+        buffer.viewport.height = self.buffer.viewport.height;
+        buffer.viewport.scroll_off = self.config.editor.scroll_off;
+        buffer.viewport.typewriter = self.zen;
+        buffer.path = Some(scratch.clone());
+        self.buffer = buffer;
+        self.open_tabs.push(scratch);
+        Ok(())
+    }
+
+    /// Sidebar `r`: `:rename` prefilled with the selected note.
+    pub(crate) fn begin_sidebar_rename(&mut self) -> anyhow::Result<()> {
+        let Some(node) = self.file_tree.selected_node() else {
+            return Ok(());
+        };
+        if node.is_dir || node.path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            self.push_notification("rename: select a note".to_string());
+            return Ok(());
+        }
+        let path = node.path.clone();
+        self.open_file(path.clone())?;
+        let rel = rel_display(&self.config.vault_path(), &path);
+        self.command_input = format!("rename {}", link::wikilink_target(&rel, true));
+        self.mode = Mode::Command;
+        self.mark_render_dirty();
+        Ok(())
+    }
+}
+
+/// Whether a vault-relative `name` stays inside the vault.
+pub(crate) fn inside_vault(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|part| matches!(part, Component::Normal(_)))
+}
+
+pub(crate) fn rel_display(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Whether `to` only differs from `from` in case, which a case-insensitive
+/// file system reports as already existing.
+fn is_case_change(from: &Path, to: &Path) -> bool {
+    from.to_string_lossy()
+        .eq_ignore_ascii_case(&to.to_string_lossy())
+}
+
+pub(crate) fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::ConfirmAction;
+    use crate::test_util::{app_in, temp_dir};
+
+    #[test]
+    fn test_extract_moves_the_selection_to_a_new_note() {
+        let vault = temp_dir();
+        let plan = vault.path().join("projects/plan.md");
+        std::fs::create_dir_all(plan.parent().unwrap()).unwrap();
+        std::fs::write(&plan, "# Plan\n\n## Tasks\nbuy milk\ncall bob\n## Done\n").unwrap();
+        let mut app = app_in(vault.path());
+        app.open_file(plan).unwrap();
+
+        assert_eq!(
+            app.extract_command("list"),
+            ["usage: extract <name> [--heading] (from Visual mode)"]
+        );
+        app.visual_command = Some(true);
+        app.buffer.cursor.move_to(3, 0);
+        app.buffer.begin_selection();
+        app.buffer.cursor.move_to(4, 0);
+        app.buffer.extend_selection();
+        assert_eq!(
+            app.extract_command("../list"),
+            ["extract: ../list must stay inside the vault"]
+        );
+        assert_eq!(
+            app.extract_command("list --heading"),
+            ["Extracted 2 lines to projects/list.md"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(vault.path().join("projects/list.md")).unwrap(),
+            "# Tasks\n\nbuy milk\ncall bob\n"
+        );
+        assert_eq!(
+            app.buffer.rope.to_string(),
+            "# Plan\n\n## Tasks\n[[list]]\n## Done\n"
+        );
+
+        // Charwise, titled by the name, and never over an existing note.
+        app.visual_command = Some(false);
+        app.buffer.cursor.move_to(4, 3);
+        app.buffer.begin_selection();
+        app.buffer.cursor.move_to(4, 6);
+        app.buffer.extend_selection();
+        assert_eq!(
+            app.extract_command("list"),
+            ["extract: projects/list.md already exists"]
+        );
+        assert_eq!(
+            app.extract_command("archive/done"),
+            ["Extracted 1 line to archive/done.md"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(vault.path().join("archive/done.md")).unwrap(),
+            "# done\n\nDone\n"
+        );
+        assert_eq!(app.buffer.line_text(4).unwrap(), "## [[archive/done]]");
+    }
+
+    #[test]
+    fn test_merge_appends_the_note_and_repoints_links() {
+        let vault = temp_dir();
+        let root = vault.path();
+        std::fs::write(root.join("index.md"), "see [[old]] and [[old#Body|it]]\n").unwrap();
+        std::fs::write(
+            root.join("old.md"),
+            "---\ntags: [x]\n---\n# Body\nold text\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("target.md"), "# Target\n").unwrap();
+        let mut app = app_in(root);
+        app.open_file(root.join("old.md")).unwrap();
+
+        assert_eq!(
+            app.merge_command("missing"),
+            ["merge: no note named missing"]
+        );
+        assert_eq!(
+            app.merge_command("old"),
+            ["merge: cannot merge a note into itself"]
+        );
+        assert!(app.merge_command("target").is_empty());
+        let pending = app.pending_confirm.take().unwrap();
+        assert_eq!(
+            pending.prompt,
+            "merge old.md into target.md, rewriting links in 1 note, and move it to .trash?"
+        );
+        let ConfirmAction::Merge(plan) = pending.action else {
+            panic!("expected a merge");
+        };
+        let notes = app.apply_merge(plan).unwrap();
+        assert_eq!(notes[0], "Merged old.md into target.md (2 links updated)");
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("target.md")).unwrap(),
+            "# Target\n\n# Body\nold text\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("index.md")).unwrap(),
+            "see [[target]] and [[target#Body|it]]\n"
+        );
+        assert!(!root.join("old.md").exists());
+        assert!(root.join(".trash/old.md").exists());
+        assert_eq!(
+            app.buffer.path.as_deref(),
+            Some(root.join("target.md").as_path())
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::app::App;
+use crate::model::code_langs::{self, CodeBlock};
+use crate::model::mode::Mode;
+use crate::model::outline::{self, Heading};
+use crate::model::toc;
+
+impl App {
+    pub(crate) fn outline_headings(&self) -> Vec<Heading> {
+        let lines = self.buffer_lines();
+        outline::headings(lines.iter().map(String::as_str))
+    }
+
+    /// Recompute the status bar heading path if the buffer was edited or
+    /// the cursor moved to another line since the last message.
+    pub(crate) fn refresh_breadcrumb(&mut self) {
+        let crumb = &self.breadcrumb;
+        let stale = crumb.buffer_id != Some(self.buffer.id) || self.buffer.changed_from.is_some();
+        if !stale && crumb.row == self.buffer.cursor.row {
+            return;
+        }
+        if stale {
+            self.breadcrumb.headings = self.outline_headings();
+            self.breadcrumb.buffer_id = Some(self.buffer.id);
+        }
+        let row = self.buffer.cursor.row;
+        self.breadcrumb.row = row;
+        self.breadcrumb.path = outline::heading_path(&self.breadcrumb.headings, row)
+            .iter()
+            .map(|h| h.title.as_str())
+            .collect::<Vec<_>>()
+            .join(" > ");
+    }
+
+    /// Buffer contents as lines, without the phantom empty line after a
+    /// trailing newline.
+    pub(crate) fn buffer_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = (0..self.buffer.line_count())
+            .filter_map(|i| self.buffer.line_text(i))
+            .collect();
+        if lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines
+    }
+
+    /// The fenced code block the cursor is in, fences included.
+    pub(crate) fn code_block_at_cursor(&self) -> Option<CodeBlock> {
+        let row = self.buffer.cursor.row;
+        code_langs::code_blocks(&self.buffer_lines().join("\n"))
+            .into_iter()
+            .find(|block| block.lines.contains(&row))
+    }
+
+    pub(crate) fn toggle_outline_panel(&mut self) {
+        self.outline_visible = !self.outline_visible;
+
+        if self.outline_visible {
+            let row = self.buffer.cursor.row;
+            self.outline_selected = self
+                .outline_headings()
+                .iter()
+                .rposition(|h| h.line <= row)
+                .unwrap_or(0);
+            self.mode = Mode::Outline;
+        } else {
+            self.mode = Mode::Normal;
+        }
+        self.mark_render_dirty();
+    }
+
+    pub(crate) fn outline_jump(&mut self) {
+        let Some(heading) = self.outline_headings().get(self.outline_selected).cloned() else {
+            return;
+        };
+        self.buffer.cursor.move_to(heading.line, 0);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+    }
+
+    /// Move the selected section among its siblings.
+    pub(crate) fn outline_move(&mut self, delta: isize) {
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let lines = self.buffer_lines();
+        let Some((rewritten, line)) = outline::move_section(&lines, self.outline_selected, delta)
+        else {
+            self.push_notification("outline: no sibling section to swap with".to_string());
+            return;
+        };
+
+        self.apply_outline_edit(&rewritten);
+        self.outline_selected = self
+            .outline_headings()
+            .iter()
+            .position(|h| h.line == line)
+            .unwrap_or(self.outline_selected);
+        self.buffer.cursor.move_to(line, 0);
+        self.buffer.scroll_to_cursor();
+    }
+
+    /// Promote (`delta < 0`) or demote the selected section's headings.
+    pub(crate) fn outline_shift_level(&mut self, delta: isize) {
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let lines = self.buffer_lines();
+        let Some(rewritten) = outline::shift_section_level(&lines, self.outline_selected, delta)
+        else {
+            self.push_notification("outline: heading level must stay between 1 and 6".to_string());
+            return;
+        };
+        self.apply_outline_edit(&rewritten);
+    }
+
+    /// `:toc`: insert a table of contents below the title, or refresh the
+    /// one between the `<!-- toc -->` markers.
+    pub(crate) fn insert_toc(&mut self) -> Vec<String> {
+        if self.reject_readonly_edit() {
+            return Vec::new();
+        }
+        let lines = self.buffer_lines();
+        let Some((range, replacement)) = toc::apply(&lines) else {
+            return vec!["toc: already up to date".to_string()];
+        };
+        let entries = replacement
+            .iter()
+            .filter(|line| line.trim_start().starts_with("- ["))
+            .count();
+
+        // Keep the cursor on the same text when lines are added above it.
+        let (row, col) = (self.buffer.cursor.row, self.buffer.cursor.col);
+        let shift = replacement.len() as isize - range.len() as isize;
+        let past = row >= range.end;
+        self.buffer.replace_lines(range, &replacement);
+        if past {
+            self.buffer
+                .cursor
+                .move_to(row.saturating_add_signed(shift), col);
+        }
+        self.buffer.clamp_cursor();
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+        vec![format!("toc: {entries} headings")]
+    }
+
+    fn apply_outline_edit(&mut self, lines: &[String]) {
+        let mut text = lines.join("\n");
+        text.push('\n');
+        self.buffer.replace_text(&text);
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+    }
+}
@@ -0,0 +1,120 @@
+use crate::app::{App, PluginCompletion};
+use crate::model::picker;
+use crate::plugin::runtime::{CompletionContext, CursorContext};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Candidates shown while typing after a plugin's trigger.
+const COMPLETION_LIMIT: usize = 8;
+
+impl App {
+    /// Offer plugin completions for the trigger before the cursor, or close
+    /// the list when the cursor is not after one. Plugins are asked once,
+    /// when the trigger is typed; the list then narrows as typing goes on.
+    pub(crate) fn update_plugin_completion(&mut self) {
+        let row = self.buffer.cursor.row;
+        let col = self.buffer.cursor.col;
+        let line = self.buffer.line_text(row).unwrap_or_default();
+        // The trigger nearest the cursor; the longest of those that end in
+        // the same place.
+        let found = self
+            .plugin_manager
+            .completion_triggers()
+            .into_iter()
+            .filter_map(|trigger| {
+                let partial = picker::trigger_partial(&line, col, &trigger)?.to_string();
+                Some((trigger, partial))
+            })
+            .min_by_key(|(_, partial)| partial.len());
+        let Some((trigger, partial)) = found else {
+            self.plugin_completion = None;
+            return;
+        };
+        if self.citation_completion.is_some() || self.link_picker.is_some() {
+            self.plugin_completion = None;
+            return;
+        }
+
+        let start = (row, col - partial.len());
+        let items = match self.plugin_completion.take() {
+            Some(open) if open.trigger == trigger && open.start == start => open.items,
+            _ => {
+                let context = CompletionContext {
+                    trigger: trigger.clone(),
+                    partial: partial.clone(),
+                    path: self.buffer.path.clone(),
+                    cursor: CursorContext {
+                        line: row,
+                        column: col,
+                    },
+                };
+                let buffer = self.plugin_buffer_view();
+                let (items, errors) = self.plugin_manager.complete(&context, &buffer);
+                for err in errors {
+                    self.push_notification(err);
+                }
+                items
+            }
+        };
+        let candidates = picker::rank(
+            items.clone(),
+            &partial,
+            |item| item.label.clone(),
+            COMPLETION_LIMIT,
+        );
+        self.plugin_completion = Some(PluginCompletion {
+            trigger,
+            start,
+            items,
+            candidates,
+            selected: 0,
+        });
+    }
+
+    /// Keys that steer an open completion list. Returns whether the key was
+    /// used; anything else goes on to be typed.
+    pub(crate) fn handle_key_plugin_completion(&mut self, key: KeyEvent) -> bool {
+        let Some(completion) = self
+            .plugin_completion
+            .as_mut()
+            .filter(|completion| !completion.candidates.is_empty())
+        else {
+            return false;
+        };
+        let len = completion.candidates.len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => completion.selected = (completion.selected + 1) % len,
+            KeyCode::Char('n') if ctrl => completion.selected = (completion.selected + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => {
+                completion.selected = (completion.selected + len - 1) % len;
+            }
+            KeyCode::Char('p') if ctrl => {
+                completion.selected = (completion.selected + len - 1) % len;
+            }
+            KeyCode::Enter => self.accept_plugin_completion(),
+            KeyCode::Esc => self.plugin_completion = None,
+            _ => return false,
+        }
+        self.mark_render_dirty();
+        true
+    }
+
+    /// Replace what was typed after the trigger with the selected item.
+    fn accept_plugin_completion(&mut self) {
+        let Some(completion) = self.plugin_completion.take() else {
+            return;
+        };
+        let item = &completion.candidates[completion.selected];
+        let text = item.insert.as_deref().unwrap_or(&item.label);
+        let line = self
+            .buffer
+            .line_text(self.buffer.cursor.row)
+            .unwrap_or_default();
+        let typed = line[completion.start.1..self.buffer.cursor.col]
+            .chars()
+            .count();
+        let end = self.buffer.cursor_char_offset();
+        self.buffer.replace_chars(end - typed..end, text);
+        self.schedule_auto_save();
+    }
+}
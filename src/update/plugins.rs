@@ -0,0 +1,696 @@
+use crate::app::{
+    App, ConfirmAction, PendingConfirm, PluginBrowser, PluginLogsView, PluginPrompt,
+    split_command_args,
+};
+use crate::model::config::{self, PluginConfig, dirs_home};
+use crate::model::cursor::Position;
+use crate::model::mode::Mode;
+use crate::model::picker;
+use crate::msg::{Msg, PluginAction};
+use crate::plugin::PluginManager;
+use crate::plugin::host_fns::{BufferView, SelectionView};
+use crate::plugin::installer::{GitReport, HeldUpdate, PluginInstaller};
+use crate::plugin::manifest::{PluginId, PluginManifest};
+use crate::plugin::registry::{self, RegistryEntry};
+use crate::plugin::runtime::{
+    CommandContext, CursorContext, DecorateContext, Decoration, PluginEdit,
+};
+use crate::plugin::scaffold;
+use crate::plugin::validate::{self, Severity};
+use crate::update::commands::BUILTIN_COMMANDS;
+use crate::watcher;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
+
+const PLUGIN_SYNC: &str = "plugin sync";
+const PLUGIN_INSTALL: &str = "plugin install";
+const PLUGIN_REGISTRY: &str = "plugin registry";
+
+impl App {
+    /// Run a plugin command against the active note, applying any edit it
+    /// returns. Returns its notifications.
+    pub(crate) fn run_plugin_command(&mut self, command: &str, args: &str) -> Vec<String> {
+        self.run_plugin_command_with(command, args, Vec::new())
+    }
+
+    /// `run_plugin_command` with the answers to the `prompt_input`
+    /// questions asked so far. Another question opens the command line for
+    /// its answer.
+    pub(crate) fn run_plugin_command_with(
+        &mut self,
+        command: &str,
+        args: &str,
+        answers: Vec<String>,
+    ) -> Vec<String> {
+        let context = CommandContext {
+            command: command.to_string(),
+            args: args.to_string(),
+            argv: split_command_args(args),
+            path: self.buffer.path.clone(),
+            cursor: CursorContext {
+                line: self.buffer.cursor.row,
+                column: self.buffer.cursor.col,
+            },
+            answers: answers.clone(),
+        };
+        let buffer = self.plugin_buffer_view();
+        let mut notes = Vec::new();
+        for action in self.plugin_manager.execute_command(&context, buffer) {
+            match action {
+                PluginAction::Notify(message) => notes.push(message),
+                PluginAction::Prompt(prompt) => {
+                    self.plugin_prompt = Some(PluginPrompt {
+                        command: command.to_string(),
+                        args: args.to_string(),
+                        answers: answers.clone(),
+                        question: prompt.question,
+                    });
+                    self.command_input = prompt.default;
+                    self.mode = Mode::Command;
+                }
+                other => self.handle_plugin_event(other),
+            }
+        }
+        notes
+    }
+
+    /// The active buffer for `read_buffer`; the selection is the one a
+    /// command typed from Visual mode acts on.
+    pub(crate) fn plugin_buffer_view(&self) -> BufferView {
+        let rope = &self.buffer.rope;
+        let position = |offset: usize| {
+            let line = rope.char_to_line(offset);
+            CursorContext {
+                line,
+                column: rope.char_to_byte(offset) - rope.line_to_byte(line),
+            }
+        };
+        let selection = self
+            .visual_command
+            .and_then(|linewise| self.buffer.selection_char_range(linewise))
+            .map(|range| SelectionView {
+                start: position(range.start),
+                end: position(range.end),
+                text: rope.slice(range).to_string(),
+            });
+        BufferView {
+            text: rope.to_string(),
+            path: self.buffer.path.clone(),
+            cursor: CursorContext {
+                line: self.buffer.cursor.row,
+                column: self.buffer.cursor.col,
+            },
+            selection,
+        }
+    }
+
+    /// Apply the edits a plugin returned or proposed to the active buffer
+    /// as one undo step.
+    pub(crate) fn apply_plugin_edits(&mut self, edits: Vec<PluginEdit>) {
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let ranges: Vec<_> = edits
+            .into_iter()
+            .map(|edit| {
+                let start = self.buffer.position_to_char(Position {
+                    row: edit.start.line,
+                    col: edit.start.column,
+                });
+                let end = self.buffer.position_to_char(Position {
+                    row: edit.end.line,
+                    col: edit.end.column,
+                });
+                (start.min(end)..start.max(end), edit.text)
+            })
+            .collect();
+        if let Err(err) = self.buffer.replace_many(&ranges) {
+            self.push_notification(format!("plugin edit: {err}"));
+            return;
+        }
+        self.buffer.scroll_to_cursor();
+        self.schedule_auto_save();
+        self.mark_render_dirty();
+    }
+
+    /// Let decorating plugins mark up the active note after `event` (`open`
+    /// or `save`). Decorations made for other notes are dropped.
+    pub(crate) fn decorate_buffer(&mut self, event: &str) {
+        let active = &self.buffer.path;
+        self.plugin_decorations
+            .retain(|_, (path, _)| path == active);
+        if !self.plugin_manager.decorates() {
+            return;
+        }
+        let context = DecorateContext {
+            event: event.to_string(),
+            path: self.buffer.path.clone(),
+            cursor: CursorContext {
+                line: self.buffer.cursor.row,
+                column: self.buffer.cursor.col,
+            },
+        };
+        let buffer = self.plugin_buffer_view();
+        for action in self.plugin_manager.decorate(&context, &buffer) {
+            self.handle_plugin_event(action);
+        }
+    }
+
+    pub(crate) fn set_plugin_decorations(&mut self, id: PluginId, decorations: Vec<Decoration>) {
+        if decorations.is_empty() {
+            self.plugin_decorations.remove(&id);
+        } else {
+            self.plugin_decorations
+                .insert(id, (self.buffer.path.clone(), decorations));
+        }
+        self.mark_render_dirty();
+    }
+
+    /// `:plugins.logs <name>`: the plugin's recent `log` lines and failed
+    /// calls in a popup. The name may be left out when there is one plugin.
+    pub(crate) fn plugin_logs_command(&mut self, name: &str) -> Vec<String> {
+        let names = self.plugin_manager.plugin_names();
+        let name = match (name.trim(), names.as_slice()) {
+            ("", [only]) => only.clone(),
+            ("", _) => {
+                return vec![format!(
+                    "usage: plugins.logs <name> ({})",
+                    if names.is_empty() {
+                        "no plugins configured".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )];
+            }
+            (name, _) => name.to_string(),
+        };
+        let Some((plugin, lines)) = self.plugin_manager.logs(&name) else {
+            return vec![format!("plugins.logs: no plugin named {name}")];
+        };
+        self.plugin_logs = Some(PluginLogsView {
+            plugin,
+            lines,
+            back: 0,
+        });
+        self.mode = Mode::PluginLogs;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_plugin_logs(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(view) = self.plugin_logs.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Up => {
+                view.back = (view.back + 1).min(view.lines.len().saturating_sub(1));
+            }
+            KeyCode::Char('j') | KeyCode::Down => view.back = view.back.saturating_sub(1),
+            KeyCode::Char('g') => view.back = view.lines.len().saturating_sub(1),
+            KeyCode::Char('G') => view.back = 0,
+            KeyCode::Char('r') => {
+                if let Some((_, lines)) = self.plugin_manager.logs(&view.plugin) {
+                    view.lines = lines;
+                    view.back = 0;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                self.plugin_logs = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// `:plugins.enable <name>`: reload a plugin that was disabled for
+    /// failing, so it is called again.
+    pub(crate) fn plugin_enable_command(&mut self, name: &str) -> Vec<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return vec!["usage: plugins.enable <name>".to_string()];
+        }
+        match self.plugin_manager.enable(name) {
+            Ok(note) => {
+                self.decorate_buffer("open");
+                self.mark_render_dirty();
+                vec![note]
+            }
+            Err(err) => vec![format!("plugins.enable: {err}")],
+        }
+    }
+
+    /// `:plugins.validate <path>`: check a plugin directory or manifest and
+    /// open what was found as a report.
+    pub(crate) fn plugin_validate_command(&mut self, path: &str) -> Vec<String> {
+        let arg = path.trim().trim_matches(|c| c == '\'' || c == '"');
+        if arg.is_empty() {
+            return vec!["usage: plugins.validate <plugin directory or plugin.toml>".to_string()];
+        }
+        let path = match (arg.strip_prefix("~/"), dirs_home()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(arg),
+        };
+        let report = validate::validate(&path, BUILTIN_COMMANDS, &self.plugin_manager.commands());
+
+        let mut lines = vec!["# Plugin validation".to_string(), String::new()];
+        lines.push(match &report.plugin {
+            Some(plugin) => format!("`{}`: {plugin}", report.manifest_path.display()),
+            None => format!("`{}`", report.manifest_path.display()),
+        });
+        lines.push(String::new());
+        for finding in &report.findings {
+            let label = match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            lines.push(format!("- **{label}** {}", finding.message));
+        }
+        if !report.findings.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("Result: {}.", report.summary()));
+        self.open_report("plugins validate", lines);
+        vec![format!("plugins.validate: {}", report.summary())]
+    }
+
+    /// `:plugins.new <name> [dir]`: write a starter plugin, into the plugin
+    /// directory unless `dir` is given, and add it to `config.toml` so it
+    /// loads once built.
+    pub(crate) fn plugin_new_command(&mut self, args: &str) -> Vec<String> {
+        if let Some(refused) = self.refuse_in_safe_mode("plugins new") {
+            return refused;
+        }
+        let argv = split_command_args(args);
+        let (name, dir) = match argv.as_slice() {
+            [name] => (name, PluginManager::base_dir().join(name)),
+            [name, dir] => {
+                let dir = match (dir.strip_prefix("~/"), dirs_home()) {
+                    (Some(rest), Some(home)) => home.join(rest),
+                    _ => PathBuf::from(dir),
+                };
+                (name, dir)
+            }
+            _ => return vec!["usage: plugins.new <name> [dir]".to_string()],
+        };
+        if let Err(err) = scaffold::scaffold(&dir, name) {
+            return vec![format!("plugins.new: {err}")];
+        }
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let mut notes = vec![format!(
+            "plugins.new: wrote {}; run build.sh there to build plugin.wasm",
+            dir.display()
+        )];
+        let shown = dir.to_string_lossy().into_owned();
+        if let Err(err) =
+            edit_user_config(|text| Some(config::add_plugin_entry(text, "path", &shown)))
+        {
+            notes.push(format!("plugins.new: {err}; loaded for this session only"));
+        }
+        self.config.plugins.push(PluginConfig {
+            repo: None,
+            path: Some(dir),
+            branch: None,
+            enabled: true,
+            config: Default::default(),
+        });
+        self.reload_plugins();
+        notes
+    }
+
+    /// `:plugins.browse`: fetch the registry index off the main thread, then
+    /// list its plugins in a picker.
+    pub(crate) fn plugin_browse_command(&mut self) -> Vec<String> {
+        if let Some(refused) = self.refuse_in_safe_mode("plugins browse") {
+            return refused;
+        }
+        let url = self.config.plugin_registry.url.trim().to_string();
+        if url.is_empty() {
+            return vec!["plugins.browse: set plugin_registry.url in config.toml".to_string()];
+        }
+        let note = format!("plugins.browse: fetching {url}");
+        let tx = self.event_tx.clone();
+        self.activities.start(PLUGIN_REGISTRY);
+        std::thread::spawn(move || {
+            let _ = tx.send(Msg::PluginRegistryFetched(registry::fetch(&url)));
+        });
+        vec![note]
+    }
+
+    pub(crate) fn handle_plugin_registry_fetched(
+        &mut self,
+        result: Result<Vec<RegistryEntry>, String>,
+    ) {
+        self.activities.finish(PLUGIN_REGISTRY);
+        let entries = match result {
+            Ok(entries) if entries.is_empty() => {
+                self.push_notification("plugins.browse: the registry lists no plugins".to_string());
+                return;
+            }
+            Ok(entries) => entries,
+            Err(err) => {
+                self.push_notification(format!("plugins.browse: {err}"));
+                return;
+            }
+        };
+        // Don't pull the user out of whatever they moved on to.
+        if self.mode != Mode::Normal {
+            self.push_notification(format!(
+                "plugins.browse: {} plugins available; run it again to pick one",
+                entries.len()
+            ));
+            return;
+        }
+        self.plugin_browser = Some(PluginBrowser {
+            candidates: entries.clone(),
+            entries,
+            query: String::new(),
+            selected: 0,
+        });
+        self.mode = Mode::PluginBrowser;
+        self.mark_render_dirty();
+    }
+
+    pub(crate) fn handle_key_plugin_browser(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(browser) = self.plugin_browser.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+        let len = browser.candidates.len().max(1);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Down | KeyCode::Tab => browser.selected = (browser.selected + 1) % len,
+            KeyCode::Char('n') if ctrl => browser.selected = (browser.selected + 1) % len,
+            KeyCode::Up | KeyCode::BackTab => browser.selected = (browser.selected + len - 1) % len,
+            KeyCode::Char('p') if ctrl => browser.selected = (browser.selected + len - 1) % len,
+            KeyCode::Esc => {
+                self.plugin_browser = None;
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                let repo = browser
+                    .candidates
+                    .get(browser.selected)
+                    .map(|entry| entry.repo.clone());
+                self.plugin_browser = None;
+                self.mode = Mode::Normal;
+                if let Some(repo) = repo {
+                    for line in self.plugin_install_command(&repo) {
+                        self.push_notification(line);
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                browser.query.pop();
+                filter_plugin_browser(browser);
+            }
+            KeyCode::Char(ch) if !ctrl => {
+                browser.query.push(ch);
+                filter_plugin_browser(browser);
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// `:plugins.sync`: clone or update `repo` plugins off the main thread,
+    /// then rediscover every plugin.
+    pub(crate) fn plugin_sync_command(&mut self) -> Vec<String> {
+        self.spawn_plugin_git(|plugins, progress| PluginInstaller::sync(plugins, progress))
+    }
+
+    /// `:plugins.update [apply]`: report new commits and tags for every
+    /// cloned plugin, fast-forwarding them with `apply`.
+    pub(crate) fn plugin_update_command(&mut self, apply: bool) -> Vec<String> {
+        self.spawn_plugin_git(move |plugins, progress| {
+            PluginInstaller::update(plugins, apply, progress)
+        })
+    }
+
+    /// Run one git pass over the configured plugins on a background thread,
+    /// one at a time.
+    fn spawn_plugin_git(
+        &mut self,
+        job: impl FnOnce(&[PluginConfig], &mut dyn FnMut(String)) -> GitReport + Send + 'static,
+    ) -> Vec<String> {
+        if let Some(refused) = self.refuse_in_safe_mode("plugins sync") {
+            return refused;
+        }
+        if self.activities.is_running(PLUGIN_SYNC) {
+            return vec!["plugins: sync already running".to_string()];
+        }
+        let plugins = self.config.plugins.clone();
+        let tx = self.event_tx.clone();
+        self.activities.start(PLUGIN_SYNC);
+        std::thread::spawn(move || {
+            let report = job(&plugins, &mut |line| {
+                let _ = tx.send(Msg::PluginSyncProgress(line));
+            });
+            let _ = tx.send(Msg::PluginSyncFinished(report));
+        });
+        Vec::new()
+    }
+
+    pub(crate) fn handle_plugin_sync_finished(&mut self, report: GitReport) {
+        self.activities.finish(PLUGIN_SYNC);
+        self.reload_plugins();
+        for line in report.lines {
+            self.push_notification(line);
+        }
+        for line in self.plugin_manager.error_notifications() {
+            self.push_notification(line);
+        }
+        // One prompt at a time; the rest are asked about on the next pass.
+        if let Some(held) = report.held.into_iter().next()
+            && self.mode == Mode::Normal
+            && self.pending_confirm.is_none()
+            && !self.config.safe_mode
+        {
+            self.pending_confirm = Some(PendingConfirm {
+                prompt: format!("update {} with these permissions?", held.repo),
+                action: ConfirmAction::UpdatePlugin(held),
+            });
+            self.mode = Mode::Confirm;
+        }
+        self.mark_render_dirty();
+    }
+
+    /// `y` to an update prompt: fast-forward onto the held update, then
+    /// reload it as any sync does.
+    pub(crate) fn apply_plugin_update(&mut self, held: HeldUpdate) -> Vec<String> {
+        self.spawn_plugin_git(move |plugins, progress| {
+            PluginInstaller::accept_update(plugins, &held, progress)
+        })
+    }
+
+    /// `Some` refusal in a `--safe` session, checked by every path that
+    /// fetches, installs or loads plugins.
+    pub(crate) fn refuse_in_safe_mode(&self, what: &str) -> Option<Vec<String>> {
+        self.config.safe_mode.then(|| {
+            vec![format!(
+                "{what}: disabled in safe mode; restart without --safe"
+            )]
+        })
+    }
+
+    /// Discover every configured plugin again and watch their directories.
+    pub(crate) fn reload_plugins(&mut self) {
+        self.plugin_manager = PluginManager::new(&self.config);
+        self.plugin_decorations.clear();
+        self.watch_plugins();
+        self.decorate_buffer("open");
+    }
+
+    pub(crate) fn watch_plugins(&mut self) {
+        let dirs = self.plugin_manager.plugin_dirs();
+        self.plugin_watcher =
+            (!dirs.is_empty()).then(|| watcher::spawn_plugins(dirs, self.event_tx.clone()));
+    }
+
+    /// The watcher saw files change under `roots`; reload those plugins so
+    /// the next call runs the new manifest and module.
+    pub(crate) fn handle_plugin_files_changed(&mut self, roots: &[PathBuf]) {
+        for line in self.plugin_manager.reload_dirs(roots) {
+            self.push_notification(line);
+        }
+        self.decorate_buffer("open");
+        self.mark_render_dirty();
+    }
+
+    /// `:plugin install <git-url>`: clone off the main thread, then show
+    /// the plugin's permissions and only add it to `config.toml` and load it
+    /// once the user agrees.
+    pub(crate) fn plugin_install_command(&mut self, repo: &str) -> Vec<String> {
+        if let Some(refused) = self.refuse_in_safe_mode("plugin install") {
+            return refused;
+        }
+        let repo = repo.trim();
+        if repo.is_empty() {
+            return vec!["usage: plugin install <git-url>".to_string()];
+        }
+        if self
+            .config
+            .plugins
+            .iter()
+            .any(|plugin| plugin.repo.as_deref() == Some(repo))
+        {
+            return vec![format!("plugin install: {repo} is already configured")];
+        }
+        let note = format!("plugin install: cloning {repo}");
+        let repo = repo.to_string();
+        let tx = self.event_tx.clone();
+        self.activities.start(PLUGIN_INSTALL);
+        std::thread::spawn(move || {
+            let result = PluginInstaller::install(&repo);
+            let _ = tx.send(Msg::PluginInstalled(repo, result));
+        });
+        vec![note]
+    }
+
+    pub(crate) fn handle_plugin_installed(&mut self, repo: String, result: Result<String, String>) {
+        self.activities.finish(PLUGIN_INSTALL);
+        if self.config.safe_mode {
+            self.push_notification(self.cancel_plugin_install(&repo));
+            return;
+        }
+        if let Err(err) = result {
+            self.push_notification(format!("plugin install: {err}"));
+            return;
+        }
+        let manifest = PluginManager::resolve_plugin_root(&PluginInstaller::repo_config(&repo))
+            .ok_or_else(|| "no plugin directory".to_string())
+            .and_then(|root| PluginManifest::load(&root));
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                self.push_notification(format!("plugin install: {err}"));
+                self.push_notification(self.cancel_plugin_install(&repo));
+                return;
+            }
+        };
+        // Another prompt or popup is open; asking now would take it over.
+        if self.mode != Mode::Normal || self.pending_confirm.is_some() {
+            self.push_notification(format!(
+                "plugin install: {repo} was cloned while busy; run :plugin install {repo} again"
+            ));
+            self.push_notification(self.cancel_plugin_install(&repo));
+            return;
+        }
+        // The status bar is too narrow for a long list of hosts.
+        self.push_notification(format!(
+            "plugin {} {} from {repo} asks for {}",
+            manifest.name,
+            manifest.version,
+            manifest.access_summary()
+        ));
+        self.pending_confirm = Some(PendingConfirm {
+            prompt: format!("install {} with these permissions?", manifest.name),
+            action: ConfirmAction::InstallPlugin(repo),
+        });
+        self.mode = Mode::Confirm;
+        self.mark_render_dirty();
+    }
+
+    /// `y` to an install prompt: add the cloned plugin to `config.toml` and
+    /// load it.
+    pub(crate) fn apply_plugin_install(&mut self, repo: &str) -> Vec<String> {
+        if let Some(refused) = self.refuse_in_safe_mode("plugin install") {
+            return refused;
+        }
+        let mut notes = Vec::new();
+        if let Err(err) =
+            edit_user_config(|text| Some(config::add_plugin_entry(text, "repo", repo)))
+        {
+            notes.push(format!(
+                "plugin install: {err}; loaded for this session only"
+            ));
+        }
+        self.config.plugins.push(PluginInstaller::repo_config(repo));
+        self.reload_plugins();
+        notes.push(format!("plugin {repo}: installed"));
+        notes.extend(self.plugin_manager.error_notifications());
+        self.mark_render_dirty();
+        notes
+    }
+
+    /// Delete the clone of a plugin the user did not install.
+    pub(crate) fn cancel_plugin_install(&self, repo: &str) -> String {
+        match PluginInstaller::uninstall(&PluginInstaller::repo_config(repo)) {
+            Ok(()) => format!("plugin install: {repo} not installed"),
+            Err(err) => format!("plugin install: {err}"),
+        }
+    }
+
+    /// `:plugin uninstall <name>`: drop the plugin named in its manifest (or
+    /// by its directory or repo) from `config.toml`, delete its clone and
+    /// unload it.
+    pub(crate) fn plugin_uninstall_command(&mut self, name: &str) -> Vec<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return vec!["usage: plugin uninstall <name>".to_string()];
+        }
+        let Some(idx) = self.config.plugins.iter().position(|plugin| {
+            plugin.repo.as_deref() == Some(name)
+                || PluginManager::resolve_plugin_root(plugin).is_some_and(|root| {
+                    root.file_name().is_some_and(|dir| dir == name)
+                        || PluginManifest::load(&root).is_ok_and(|manifest| manifest.name == name)
+                })
+        }) else {
+            return vec![format!("plugin uninstall: no plugin named {name}")];
+        };
+
+        let plugin = self.config.plugins.remove(idx);
+        let mut notes = Vec::new();
+        let entry = match (&plugin.repo, &plugin.path) {
+            (_, Some(path)) => ("path", path.to_string_lossy().into_owned()),
+            (Some(repo), None) => ("repo", repo.clone()),
+            (None, None) => ("repo", String::new()),
+        };
+        if let Err(err) =
+            edit_user_config(|text| config::remove_plugin_entry(text, entry.0, &entry.1))
+        {
+            notes.push(format!("plugin uninstall: {err}"));
+        }
+        if let Err(err) = PluginInstaller::uninstall(&plugin) {
+            notes.push(format!("plugin uninstall: {err}"));
+        }
+        self.reload_plugins();
+        self.mark_render_dirty();
+        notes.push(format!("plugin uninstall: removed {name}"));
+        notes
+    }
+}
+
+/// Rewrite the user's `config.toml` through `edit`, which returns `None`
+/// to leave it alone.
+fn edit_user_config(edit: impl FnOnce(&str) -> Option<String>) -> Result<(), String> {
+    let path = config::user_config_path().ok_or_else(|| "no config directory".to_string())?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(format!("cannot read {}: {err}", path.display())),
+    };
+    let Some(updated) = edit(&text) else {
+        return Ok(());
+    };
+    path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, updated))
+        .map_err(|err| format!("cannot write {}: {err}", path.display()))
+}
+
+/// Re-rank the registry's plugins for the browser's query, by name and
+/// description.
+fn filter_plugin_browser(browser: &mut PluginBrowser) {
+    browser.candidates = picker::rank(
+        browser.entries.clone(),
+        &browser.query,
+        |entry| format!("{} {}", entry.name, entry.description),
+        usize::MAX,
+    );
+    browser.selected = 0;
+}
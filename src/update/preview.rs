@@ -0,0 +1,80 @@
+use crate::app::{App, LinkPreview, wikilink_anchor};
+use crate::model::embed;
+use crate::model::mode::Mode;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// `K`: preview the note behind the wikilink under the cursor, or just
+    /// its anchored heading or block, without leaving the buffer.
+    pub(crate) fn open_link_preview(&mut self) {
+        let Some(link) = self.wikilink_text_under_cursor() else {
+            self.push_notification("No WikiLink under cursor".to_string());
+            return;
+        };
+        let Some(target) = self.wikilink_under_cursor() else {
+            return;
+        };
+        let Some(path) = self.resolve_wikilink_target(&target) else {
+            self.push_notification(format!("preview: no note named \"{target}\""));
+            return;
+        };
+        let Some(text) = self.note_text(&path) else {
+            self.push_notification(format!("preview: cannot read {}", path.display()));
+            return;
+        };
+
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let anchor = wikilink_anchor(&link);
+        let Some(range) = embed::excerpt_range(&lines, anchor.as_deref()) else {
+            self.push_notification(format!(
+                "preview: no heading \"{}\" in {target}",
+                anchor.unwrap_or_default()
+            ));
+            return;
+        };
+        let title = match &anchor {
+            Some(anchor) => format!("{target}#{anchor}"),
+            None => target,
+        };
+
+        self.link_preview = Some(LinkPreview {
+            title,
+            path,
+            line: range.start,
+            lines: lines[range].to_vec(),
+            scroll: 0,
+        });
+        self.mode = Mode::LinkPreview;
+        self.mark_render_dirty();
+    }
+
+    pub(crate) fn handle_key_link_preview(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(preview) = self.link_preview.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                preview.scroll = (preview.scroll + 1).min(preview.lines.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => preview.scroll = preview.scroll.saturating_sub(1),
+            KeyCode::Enter => {
+                let preview = self.link_preview.take().expect("preview is open");
+                self.mode = Mode::Normal;
+                self.open_file(preview.path)?;
+                self.buffer.cursor.move_to(preview.line, 0);
+                self.buffer.clamp_cursor();
+                self.buffer.scroll_to_cursor();
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('K') => {
+                self.link_preview = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+}
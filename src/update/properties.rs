@@ -0,0 +1,139 @@
+use crate::app::{App, PropertiesView};
+use crate::model::frontmatter::FrontmatterValue;
+use crate::model::mode::Mode;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Fields edited as lists even while they hold a single scalar.
+const LIST_KEYS: &[&str] = &["tags", "aliases"];
+
+impl App {
+    /// `:props`: edit the active note's frontmatter field by field.
+    pub(crate) fn properties_command(&mut self) -> Vec<String> {
+        if self.buffer.path.is_none() {
+            return vec!["props: the buffer is not a note".to_string()];
+        }
+        self.properties_view = Some(PropertiesView::default());
+        self.mode = Mode::Properties;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_properties(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(view) = self.properties_view.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+        let fields = self.buffer.frontmatter().unwrap_or_default().fields;
+
+        if let Some((field, text)) = view.input.as_mut() {
+            match key.code {
+                KeyCode::Esc => view.input = None,
+                KeyCode::Enter => {
+                    let (field, text) = (field.clone(), text.clone());
+                    view.input = None;
+                    self.commit_property(field, &text);
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(ch)
+                    if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+                {
+                    text.push(ch);
+                }
+                _ => {}
+            }
+            self.mark_render_dirty();
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.selected = (view.selected + 1).min(fields.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Char('e') => match fields.get(view.selected) {
+                Some((name, FrontmatterValue::Scalar(value))) => {
+                    view.input = Some((Some(name.clone()), value.clone()));
+                }
+                Some((name, FrontmatterValue::List(items))) => {
+                    view.input = Some((Some(name.clone()), items.join(", ")));
+                }
+                Some((name, FrontmatterValue::Raw(_))) => {
+                    let name = name.clone();
+                    self.push_notification(format!(
+                        "props: {name} is nested YAML; edit it in the note"
+                    ));
+                }
+                None => view.input = Some((None, String::new())),
+            },
+            KeyCode::Char('a') => view.input = Some((None, String::new())),
+            KeyCode::Char('d') => {
+                if let Some((name, _)) = fields.get(view.selected) {
+                    let name = name.clone();
+                    self.remove_property(&name);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.properties_view = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// Write typed text to `field`, or add the `key: value` it spells out.
+    fn commit_property(&mut self, field: Option<String>, text: &str) {
+        let (name, value) = match field {
+            Some(name) => (name, text),
+            None => match text.split_once(':') {
+                Some((name, value)) if !name.trim().is_empty() => (name.trim().to_string(), value),
+                _ => {
+                    self.push_notification("props: type key: value".to_string());
+                    return;
+                }
+            },
+        };
+        if self.reject_readonly_edit() {
+            return;
+        }
+
+        let mut frontmatter = self.buffer.frontmatter().unwrap_or_default();
+        let list = matches!(frontmatter.get(&name), Some(FrontmatterValue::List(_)))
+            || LIST_KEYS.contains(&name.to_lowercase().as_str());
+        match FrontmatterValue::from_input(value, list) {
+            FrontmatterValue::List(items) if items.is_empty() => {
+                frontmatter.fields.retain(|(key, _)| *key != name);
+            }
+            value => frontmatter.set(&name, value),
+        }
+        self.apply_frontmatter(&frontmatter);
+        if let Some(view) = self.properties_view.as_mut() {
+            view.selected = frontmatter
+                .fields
+                .iter()
+                .position(|(key, _)| *key == name)
+                .unwrap_or(
+                    view.selected
+                        .min(frontmatter.fields.len().saturating_sub(1)),
+                );
+        }
+    }
+
+    fn remove_property(&mut self, name: &str) {
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let mut frontmatter = self.buffer.frontmatter().unwrap_or_default();
+        frontmatter.fields.retain(|(key, _)| key != name);
+        self.apply_frontmatter(&frontmatter);
+        if let Some(view) = self.properties_view.as_mut() {
+            view.selected = view
+                .selected
+                .min(frontmatter.fields.len().saturating_sub(1));
+        }
+    }
+}
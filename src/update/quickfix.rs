@@ -0,0 +1,202 @@
+use crate::app::App;
+use crate::model::quickfix::QuickfixEntry;
+use crate::plugin::runtime::DecorationKind;
+use crate::update::note_ops::plural;
+use anyhow::Result;
+use std::path::PathBuf;
+
+impl App {
+    /// Open `path` with the cursor at the start of 1-indexed `line`.
+    pub(crate) fn open_file_at_line(&mut self, path: PathBuf, line: usize) -> Result<()> {
+        self.open_file(path)?;
+        let target = line.saturating_sub(1);
+        self.buffer.cursor.row = target.min(self.buffer.line_count().saturating_sub(1));
+        self.buffer.cursor.col = 0;
+        self.buffer.cursor.desired_col = 0;
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// Replace the quickfix list: with content search hits or `:links
+    /// check` results from the finder, or plugin diagnostics.
+    pub(crate) fn set_quickfix(
+        &mut self,
+        title: String,
+        entries: Vec<QuickfixEntry>,
+        current: Option<usize>,
+    ) {
+        self.quickfix.set(title, entries, current);
+    }
+
+    /// `]q` / `[q`: jump to the next or previous quickfix entry.
+    pub(crate) fn quickfix_step(&mut self, delta: isize) -> Result<()> {
+        let len = self.quickfix.entries.len();
+        let Some(entry) = self.quickfix.step(delta).cloned() else {
+            self.push_notification("quickfix: list is empty".to_string());
+            return Ok(());
+        };
+        let idx = self.quickfix.current.unwrap_or_default();
+
+        self.open_file_at_line(entry.path.clone(), entry.line)?;
+        let rel = self.vault_relative(&entry.path);
+        self.push_notification(format!(
+            "({}/{len}) {rel}:{}  {}",
+            idx + 1,
+            entry.line,
+            entry.text
+        ));
+        Ok(())
+    }
+
+    /// `:quickfix`: list the entries, marking the current one.
+    pub(crate) fn quickfix_command(&mut self, args: &str) -> Vec<String> {
+        match args {
+            "" => {}
+            "clear" => {
+                self.quickfix = Default::default();
+                return vec!["quickfix: cleared".to_string()];
+            }
+            "diagnostics" | "diag" => {
+                let entries = self.plugin_diagnostics();
+                if entries.is_empty() {
+                    return vec!["quickfix: no plugin errors or warnings".to_string()];
+                }
+                let count = entries.len();
+                self.set_quickfix("diagnostics".to_string(), entries, None);
+                return vec![format!(
+                    "quickfix: {count} diagnostic{}, ]q to step through",
+                    plural(count)
+                )];
+            }
+            _ => return vec!["usage: quickfix [clear | diagnostics]".to_string()],
+        }
+        if self.quickfix.is_empty() {
+            return vec!["quickfix: list is empty".to_string()];
+        }
+
+        let mut lines = vec![
+            format!("# Quickfix — {}", self.quickfix.title),
+            String::new(),
+        ];
+        for (idx, entry) in self.quickfix.entries.iter().enumerate() {
+            let marker = if self.quickfix.current == Some(idx) {
+                ">"
+            } else {
+                " "
+            };
+            lines.push(format!(
+                "{marker} {:>3}. {}:{}  {}",
+                idx + 1,
+                self.vault_relative(&entry.path),
+                entry.line,
+                entry.text
+            ));
+        }
+        lines.push(String::new());
+        lines.push("Step through with ]q and [q.".to_string());
+        self.open_report("quickfix", lines);
+        Vec::new()
+    }
+
+    /// Error and warning decorations plugins have set, by note and line.
+    fn plugin_diagnostics(&self) -> Vec<QuickfixEntry> {
+        let mut entries: Vec<QuickfixEntry> = self
+            .plugin_decorations
+            .values()
+            .filter_map(|(path, decorations)| Some((path.as_ref()?, decorations)))
+            .flat_map(|(path, decorations)| {
+                decorations.iter().filter_map(move |decoration| {
+                    let kind = match decoration.kind {
+                        DecorationKind::Error => "error",
+                        DecorationKind::Warning => "warning",
+                        DecorationKind::Info | DecorationKind::Hint => return None,
+                    };
+                    let text = decoration.text.as_deref().unwrap_or_default();
+                    Some(QuickfixEntry {
+                        path: path.clone(),
+                        line: decoration.line + 1,
+                        text: format!("{kind}: {text}").trim_end().to_string(),
+                    })
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        entries
+    }
+
+    fn vault_relative(&self, path: &std::path::Path) -> String {
+        let vault = self.config.vault_path();
+        path.strip_prefix(&vault)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::plugin::manifest::PluginId;
+    use crate::plugin::runtime::{Decoration, DecorationKind};
+    use crate::test_util::{app_in, temp_dir};
+
+    fn decoration(line: usize, kind: DecorationKind, text: &str) -> Decoration {
+        Decoration {
+            line,
+            start: None,
+            end: None,
+            kind,
+            gutter: None,
+            text: Some(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_plugin_diagnostics_fill_the_list() {
+        let vault = temp_dir();
+        let mut app = app_in(vault.path());
+        assert_eq!(
+            app.quickfix_command("diagnostics"),
+            ["quickfix: no plugin errors or warnings"]
+        );
+
+        let (a, b) = (PathBuf::from("/v/a.md"), PathBuf::from("/v/b.md"));
+        app.plugin_decorations.insert(
+            PluginId::new("grammar"),
+            (
+                Some(b.clone()),
+                vec![
+                    decoration(4, DecorationKind::Warning, "passive voice"),
+                    decoration(1, DecorationKind::Hint, "consider a list"),
+                ],
+            ),
+        );
+        app.plugin_decorations.insert(
+            PluginId::new("lint"),
+            (
+                Some(a.clone()),
+                vec![decoration(0, DecorationKind::Error, "broken table")],
+            ),
+        );
+        assert_eq!(
+            app.quickfix_command("diag"),
+            ["quickfix: 2 diagnostics, ]q to step through"]
+        );
+        let entries: Vec<_> = app
+            .quickfix
+            .entries
+            .iter()
+            .map(|entry| (&entry.path, entry.line, entry.text.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (&a, 1, "error: broken table"),
+                (&b, 5, "warning: passive voice")
+            ]
+        );
+        assert_eq!(app.quickfix.title, "diagnostics");
+    }
+}
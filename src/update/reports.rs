@@ -0,0 +1,229 @@
+use crate::app::{App, StatsView};
+use crate::model::buffer::{Buffer, TextCounts};
+use crate::model::journal;
+use crate::model::mode::Mode;
+use crate::model::outline;
+use crate::model::stats::NoteStats;
+use crate::update::note_ops::plural;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::HashSet;
+
+const DEFAULT_CHANGES_SPAN: &str = "7d";
+
+impl App {
+    /// Show generated text in a file-less buffer, keeping the current note
+    /// open in its tab.
+    pub(crate) fn open_report(&mut self, title: &str, lines: Vec<String>) {
+        if self.buffer.has_unkept_text() {
+            self.push_notification(format!(
+                "{title}: the current buffer has unsaved text and no file"
+            ));
+            return;
+        }
+        let mut report = Buffer::report(title, &(lines.join("\n") + "\n"));
+        report.viewport = self.buffer.viewport.clone();
+        report.viewport.top_line = 0;
+
+        let previous = std::mem::replace(&mut self.buffer, report);
+        if let Some(path) = previous.path.clone() {
+            self.inactive_buffers.insert(path, previous);
+        }
+        self.mark_render_dirty();
+    }
+
+    pub(crate) fn changes_report(&mut self, args: &str) -> Vec<String> {
+        let span_arg = args
+            .strip_prefix("--since")
+            .map(str::trim)
+            .unwrap_or(args)
+            .trim();
+        let span_arg = if span_arg.is_empty() {
+            DEFAULT_CHANGES_SPAN
+        } else {
+            span_arg
+        };
+        let Some(span) = journal::parse_span(span_arg) else {
+            return vec!["usage: changes [--since <n>m|h|d|w]".to_string()];
+        };
+
+        let vault = self.config.vault_path();
+        let since = journal::now_secs().saturating_sub(span.as_secs());
+        let entries = journal::read_since(&journal::default_path(), &vault, since);
+        if entries.is_empty() {
+            return vec![format!("changes: nothing saved in the last {span_arg}")];
+        }
+
+        let notes = entries
+            .iter()
+            .map(|entry| &entry.path)
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        let added: usize = entries.iter().map(|entry| entry.added).sum();
+        let removed: usize = entries.iter().map(|entry| entry.removed).sum();
+
+        let mut lines = vec![
+            format!("# Changes in the last {span_arg}"),
+            String::new(),
+            format!("{notes} notes, +{added} -{removed} lines (days in UTC)"),
+            String::new(),
+        ];
+        lines.extend(journal::report(&entries, &vault));
+        self.open_report(&format!("changes {span_arg}"), lines);
+        Vec::new()
+    }
+
+    /// Word/char/line totals for the whole note and the cursor's section.
+    pub(crate) fn count_summary(&self) -> String {
+        let lines: Vec<String> = (0..self.buffer.line_count())
+            .filter_map(|i| self.buffer.line_text(i))
+            .collect();
+        let note = TextCounts::of(&self.buffer.rope.to_string());
+
+        let headings = outline::headings(lines.iter().map(String::as_str));
+        let (heading, range) = outline::section_at(&headings, self.buffer.cursor.row, lines.len());
+        let section = TextCounts::of(&lines[range].join("\n"));
+        let title = heading.map_or("(top)", |h| h.title.as_str());
+
+        format!(
+            "note: {} w, {} ch, {} ln | section \"{title}\": {} w, {} ch, {} ln",
+            note.words, note.chars, note.lines, section.words, section.chars, section.lines
+        )
+    }
+
+    /// `:stats`: size, reading time, headings, links and tasks of the
+    /// active note in a popup.
+    pub(crate) fn stats_command(&mut self) -> Vec<String> {
+        let stats = NoteStats::of(&self.buffer.rope.to_string());
+        let resolved: HashSet<_> = stats
+            .links_out
+            .iter()
+            .filter_map(|target| self.resolve_wikilink_target(target))
+            .collect();
+        let dead = stats
+            .links_out
+            .iter()
+            .filter(|target| !self.note_index.resolves(target))
+            .count();
+        let mut links_out = format!(
+            "{} to {} note{}",
+            stats.links_out.len(),
+            resolved.len(),
+            plural(resolved.len())
+        );
+        if dead > 0 {
+            links_out.push_str(&format!(", {dead} dead"));
+        }
+
+        let links_in = match &self.buffer.path {
+            Some(path) => {
+                let backlinks = self.backlinks_to(path);
+                let notes: HashSet<_> = backlinks.iter().map(|entry| &entry.path).collect();
+                format!(
+                    "{} from {} note{}",
+                    backlinks.len(),
+                    notes.len(),
+                    plural(notes.len())
+                )
+            }
+            None => "0 (note not saved)".to_string(),
+        };
+        let tasks = match stats.tasks_total {
+            0 => "none".to_string(),
+            total => format!(
+                "{}/{total} done ({}%)",
+                stats.tasks_done,
+                stats.tasks_done * 100 / total
+            ),
+        };
+        let reading = match stats.reading_minutes() {
+            0 => "-".to_string(),
+            minutes => format!("{minutes} min"),
+        };
+
+        let title = self
+            .buffer
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(
+                || "[note]".to_string(),
+                |name| name.to_string_lossy().to_string(),
+            );
+        self.stats_view = Some(StatsView {
+            title,
+            rows: vec![
+                ("Words".to_string(), stats.counts.words.to_string()),
+                ("Characters".to_string(), stats.counts.chars.to_string()),
+                ("Lines".to_string(), stats.counts.lines.to_string()),
+                ("Reading time".to_string(), reading),
+                ("Headings".to_string(), stats.headings.to_string()),
+                ("Links out".to_string(), links_out),
+                ("Links in".to_string(), links_in),
+                ("Tasks".to_string(), tasks),
+            ],
+        });
+        self.mode = Mode::Stats;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_stats(&mut self, key: KeyEvent) -> Result<()> {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter) {
+            self.stats_view = None;
+            self.mode = Mode::Normal;
+            self.mark_render_dirty();
+        }
+        Ok(())
+    }
+
+    /// `:langs`: code block languages used across the vault.
+    pub(crate) fn langs_report(&mut self) -> Vec<String> {
+        let files = self.file_tree.all_file_paths();
+        self.code_langs.refresh(&files);
+        let usage = self.code_langs.usage();
+        if usage.is_empty() {
+            return vec!["langs: no fenced code blocks in the vault".to_string()];
+        }
+
+        let mut lines = vec![
+            "# Code block languages".to_string(),
+            String::new(),
+            "| language | blocks | notes | lines |".to_string(),
+            "| --- | ---: | ---: | ---: |".to_string(),
+        ];
+        for lang in &usage {
+            lines.push(format!(
+                "| {} | {} | {} | {} |",
+                lang.lang, lang.blocks, lang.notes, lang.lines
+            ));
+        }
+        lines.push(String::new());
+        lines.push("Search one with `lang:<name>` in the finder.".to_string());
+        self.open_report("langs", lines);
+        Vec::new()
+    }
+
+    /// `:problems`: vault paths that cannot be listed or read, which the
+    /// sidebar, searches and the link index skip.
+    pub(crate) fn problems_report(&mut self) -> Vec<String> {
+        let problems = self.file_tree.problems();
+        if problems.is_empty() {
+            return vec!["problems: every vault path is readable".to_string()];
+        }
+
+        let vault = self.config.vault_path();
+        let mut lines = vec!["# Problems".to_string(), String::new()];
+        for (path, reason) in &problems {
+            let rel = path.strip_prefix(&vault).unwrap_or(path);
+            lines.push(format!("- `{}`: {reason}", rel.display()));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "{} paths skipped; the rest of the vault works as usual.",
+            problems.len()
+        ));
+        self.open_report("problems", lines);
+        Vec::new()
+    }
+}
@@ -1,84 +1,351 @@
 use crate::app::{App, FinderMode, FinderResult};
+use crate::model::code_langs;
+use crate::model::journal;
+use crate::model::link;
 use crate::model::mode::Mode;
+use crate::model::outline;
+use crate::model::picker;
+use crate::model::query::{self, ContentQuery};
+use crate::model::quickfix::QuickfixEntry;
+use crate::model::recent;
+use crate::model::search_index::{self, SearchIndex};
+use crate::msg::Msg;
+use crate::update::note_ops::rel_display;
 use anyhow::Result;
-use fuzzy_matcher::FuzzyMatcher;
-use fuzzy_matcher::skim::SkimMatcherV2;
+
+/// Age in days past which `:stale` lists a note by default.
+const STALE_DAYS: u64 = 90;
 
 impl App {
+    /// Load the saved search index and bring it up to date off the main
+    /// thread; content search reads every file until it is ready.
+    pub(crate) fn start_search_index(&mut self) {
+        let root = self.config.vault_path();
+        let files = self.file_tree.all_file_paths();
+        let tx = self.event_tx.clone();
+        self.activities.start("search index");
+        std::thread::spawn(move || {
+            let cache = search_index::default_path();
+            let mut index = SearchIndex::load(&root, &cache);
+            if index.refresh(&files) > 0
+                && let Err(err) = index.save(&cache)
+            {
+                tracing::warn!("search index: cannot save {}: {err}", cache.display());
+            }
+            let _ = tx.send(Msg::SearchIndexReady(Box::new(index)));
+        });
+    }
+
+    pub(crate) fn handle_search_index_ready(&mut self, mut index: SearchIndex) {
+        // Built for a vault that has since been switched away from.
+        if index.root() != self.config.vault_path() {
+            return;
+        }
+        self.activities.finish("search index");
+        // Catch up on changes the watcher reported while it was building.
+        index.refresh(&self.file_tree.all_file_paths());
+        self.search_index = Some(index);
+        self.mark_render_dirty();
+    }
+
+    /// Keep the search index current after the watcher saw `path` change.
+    pub(crate) fn update_search_index(&mut self, path: &std::path::Path) {
+        if let Some(index) = self.search_index.as_mut() {
+            if path.is_dir() {
+                index.refresh(&self.file_tree.all_file_paths());
+            } else {
+                index.update(path);
+            }
+        }
+    }
+
+    pub(crate) fn save_search_index(&self) {
+        let Some(index) = self.search_index.as_ref() else {
+            return;
+        };
+        let cache = search_index::default_path();
+        if let Err(err) = index.save(&cache) {
+            tracing::warn!("search index: cannot save {}: {err}", cache.display());
+        }
+    }
+
+    pub(crate) fn save_recent_files(&self) {
+        let file = recent::default_path();
+        if let Err(err) = self.recent.save(&file) {
+            tracing::warn!("recent files: cannot save {}: {err}", file.display());
+        }
+    }
+
+    /// `:recent`: the finder on notes opened lately, newest first.
+    pub(crate) fn recent_command(&mut self) -> Vec<String> {
+        if self.recent.paths().is_empty() {
+            return vec!["recent: no notes opened yet".to_string()];
+        }
+        if let Err(err) = self.open_finder(FinderMode::Recent) {
+            return vec![format!("recent: {err}")];
+        }
+        Vec::new()
+    }
+
+    /// `:stale [days]`: notes unchanged for `days` (90 by default), oldest
+    /// first, leaving out the archive and the scratch note.
+    pub(crate) fn stale_command(&mut self, args: &str) -> Vec<String> {
+        let days = match args.trim() {
+            "" => STALE_DAYS,
+            raw => match raw.parse::<u64>() {
+                Ok(days) => days,
+                Err(_) => return vec!["usage: stale [days]".to_string()],
+            },
+        };
+        let root = self.config.vault_path();
+        let archive = root.join(&self.config.general.archive);
+        let scratch = self.config.scratch_path();
+        let now = journal::now_secs();
+        let mut stale: Vec<(u64, std::path::PathBuf)> = self
+            .file_tree
+            .all_file_paths()
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .filter(|path| !path.starts_with(&archive) && *path != scratch)
+            .filter_map(|path| {
+                let secs = (search_index::modified(&path)? / 1_000_000_000) as u64;
+                (now.saturating_sub(secs) >= days.saturating_mul(86_400)).then_some((secs, path))
+            })
+            .collect();
+        if stale.is_empty() {
+            return vec![format!("stale: every note changed in the last {days} days")];
+        }
+        stale.sort();
+        self.finder_stale = stale
+            .into_iter()
+            .map(|(secs, path)| FinderResult {
+                preview: format!(
+                    "{}  {} ({}d)",
+                    rel_display(&root, &path),
+                    journal::format_day(secs),
+                    now.saturating_sub(secs) / 86_400
+                ),
+                path,
+                line: None,
+            })
+            .collect();
+        self.finder_stale_days = days;
+        if let Err(err) = self.open_finder(FinderMode::Stale) {
+            return vec![format!("stale: {err}")];
+        }
+        Vec::new()
+    }
+
     pub(crate) fn open_finder(&mut self, mode: FinderMode) -> Result<()> {
+        self.finder_from_insert = self.mode == Mode::Insert;
         self.mode = Mode::FinderOpen;
         self.finder_mode = mode;
         self.finder_query.clear();
         self.finder_selected = 0;
+        self.finder_marked.clear();
         self.file_tree.refresh()?;
+        self.refresh_finder_files()
+    }
+
+    /// Read the vault's files again after they changed, then rank them.
+    pub(crate) fn refresh_finder_files(&mut self) -> Result<()> {
+        self.finder_files = self.file_tree.all_file_paths();
         self.refresh_finder_results()
     }
-    pub(crate) fn refresh_finder_results(&mut self) -> Result<()> {
-        self.file_tree.refresh()?;
+    /// Finder `Ctrl+L`: insert a wikilink to the selected result at the
+    /// cursor instead of opening it.
+    pub(crate) fn insert_finder_link(&mut self) {
+        let Some(result) = self.finder_results.get(self.finder_selected) else {
+            return;
+        };
+        let vault = self.config.vault_path();
+        let rel = result
+            .path
+            .strip_prefix(&vault)
+            .unwrap_or(&result.path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let name = result.path.file_name().map(|n| n.to_ascii_lowercase());
+        let ambiguous = self
+            .file_tree
+            .all_file_paths()
+            .iter()
+            .filter(|path| path.file_name().map(|n| n.to_ascii_lowercase()) == name)
+            .count()
+            > 1;
+        let text = link::wikilink(&link::wikilink_target(&rel, ambiguous), "");
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let at = self.buffer.cursor_char_offset();
+        self.buffer.replace_chars(at..at, &text);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+    }
 
-        let files = self.file_tree.all_file_paths();
+    /// Rank the finder's results for the query typed so far among
+    /// `finder_files`.
+    pub(crate) fn refresh_finder_results(&mut self) -> Result<()> {
+        let mut files = self.finder_files.clone();
         let limit = self.config.search.max_results;
 
+        // `lang:python` narrows either mode to notes with such code blocks.
+        let (lang, query) = code_langs::split_lang_filter(&self.finder_query);
+        if let Some(lang) = &lang {
+            self.code_langs.refresh(&files);
+            files.retain(|path| self.code_langs.has_lang(path, lang));
+        }
+
         self.finder_results.clear();
 
-        if self.finder_mode == FinderMode::Files {
-            if self.finder_query.is_empty() {
-                self.finder_results = files
-                    .into_iter()
-                    .take(limit)
-                    .map(|path| FinderResult {
-                        preview: path.to_string_lossy().to_string(),
-                        path,
-                        line: None,
-                    })
-                    .collect();
-                self.finder_selected = 0;
-                return Ok(());
+        if matches!(
+            self.finder_mode,
+            FinderMode::Files | FinderMode::Orphans | FinderMode::Recent | FinderMode::LinkChoices
+        ) {
+            match self.finder_mode {
+                FinderMode::Orphans => files.retain(|path| self.finder_orphans.contains(path)),
+                FinderMode::LinkChoices => {
+                    files.retain(|path| self.finder_link_choices.contains(path));
+                }
+                // The active note is where you are, not where you go back to.
+                FinderMode::Recent => {
+                    files = self
+                        .recent
+                        .paths()
+                        .iter()
+                        .filter(|path| self.buffer.path.as_ref() != Some(*path))
+                        .filter(|path| files.contains(path))
+                        .cloned()
+                        .collect();
+                }
+                _ if query.is_empty() => files = self.recent.order(files),
+                _ => {}
             }
-
-            let matcher = SkimMatcherV2::default();
-            let mut scored: Vec<(i64, FinderResult)> = files
-                .into_iter()
-                .filter_map(|path| {
-                    let candidate = path.to_string_lossy().to_string();
-                    matcher
-                        .fuzzy_match(&candidate, &self.finder_query)
-                        .map(|score| {
-                            (
-                                score,
-                                FinderResult {
-                                    path,
-                                    line: None,
-                                    preview: candidate,
-                                },
-                            )
-                        })
-                })
-                .collect();
-
-            scored.sort_by(|a, b| b.0.cmp(&a.0));
-
-            self.finder_results = scored
-                .into_iter()
-                .take(limit)
-                .map(|(_, item)| item)
-                .collect();
+            self.finder_results = picker::rank(
+                files,
+                &query,
+                |path| path.to_string_lossy().to_string(),
+                limit,
+            )
+            .into_iter()
+            .map(|path| FinderResult {
+                preview: path.to_string_lossy().to_string(),
+                path,
+                line: None,
+            })
+            .collect();
+        } else if self.finder_mode == FinderMode::BrokenLinks {
+            self.finder_results = picker::rank(
+                self.finder_broken.clone(),
+                &query,
+                |hit| hit.preview.clone(),
+                limit,
+            );
+        } else if self.finder_mode == FinderMode::Stale {
+            self.finder_results = picker::rank(
+                self.finder_stale.clone(),
+                &query,
+                |hit| hit.preview.clone(),
+                limit,
+            );
+        } else if self.finder_mode == FinderMode::Section {
+            self.finder_results = self.section_search_hits(&query, limit);
         } else {
-            if self.finder_query.is_empty() {
+            // `section:"Heading"` keeps hits inside sections with that title.
+            let (section, query) = outline::split_section_filter(&query);
+            self.finder_query_error = None;
+            let parsed = match ContentQuery::parse(&query) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    self.finder_query_error = Some(err);
+                    self.finder_selected = 0;
+                    return Ok(());
+                }
+            };
+            if parsed.is_empty() && lang.is_none() && section.is_none() {
                 self.finder_selected = 0;
                 return Ok(());
             }
 
-            let needle = self.finder_query.to_lowercase();
             let mut hits = Vec::new();
+            self.finder_unreadable = 0;
+            if let Some(index) = &self.search_index {
+                for term in &parsed.terms {
+                    if let Some(candidates) = index.candidates(term) {
+                        files.retain(|path| candidates.contains(path));
+                    }
+                }
+            }
+            let root = self.file_tree.root.clone();
 
             for path in files {
-                let Ok(contents) = std::fs::read_to_string(&path) else {
-                    continue;
+                let contents = match std::fs::read_to_string(&path) {
+                    Ok(contents) => contents,
+                    // Binary attachments are expected; anything else is a
+                    // file the search could not look inside.
+                    Err(err) if err.kind() == std::io::ErrorKind::InvalidData => continue,
+                    Err(_) => {
+                        self.finder_unreadable += 1;
+                        continue;
+                    }
                 };
+                let rel = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy();
+                if !parsed.matches_note(&rel, &contents) {
+                    continue;
+                }
+                let blocks = lang
+                    .as_deref()
+                    .map(|lang| self.code_langs.blocks_in(&path, lang));
+                let sections = section.as_deref().map(|title| {
+                    let headings = outline::headings(contents.lines());
+                    outline::sections_titled(&headings, title, contents.lines().count())
+                });
+                if sections.as_ref().is_some_and(Vec::is_empty) {
+                    continue;
+                }
+
+                // With only note filters (`tag:`, `path:`…), list each note
+                // once by its title.
+                if !parsed.matches_lines() && blocks.is_none() && sections.is_none() {
+                    hits.push(FinderResult {
+                        preview: format!(
+                            "{}  {}",
+                            path.to_string_lossy(),
+                            query::note_title(&rel, &contents)
+                        ),
+                        path: path.clone(),
+                        line: Some(1),
+                    });
+                    if hits.len() >= limit {
+                        break;
+                    }
+                    continue;
+                }
 
                 for (idx, line) in contents.lines().enumerate() {
-                    if line.to_lowercase().contains(&needle) {
+                    // With a language filter, match only inside its blocks;
+                    // an empty query lists each block by its opening fence.
+                    let in_scope = blocks.as_ref().is_none_or(|blocks| {
+                        blocks.iter().any(|block| {
+                            if parsed.matches_lines() {
+                                block.lines.contains(&idx)
+                            } else {
+                                block.lines.start == idx
+                            }
+                        })
+                    });
+                    // With only a section filter, list the matching headings.
+                    let in_section = sections.as_ref().is_none_or(|ranges| {
+                        ranges.iter().any(|range| {
+                            if !parsed.matches_lines() && blocks.is_none() {
+                                range.start == idx
+                            } else {
+                                range.contains(&idx)
+                            }
+                        })
+                    });
+                    if in_scope && in_section && parsed.matches_line(line) {
                         hits.push(FinderResult {
                             preview: format!(
                                 "{}:{}  {}",
@@ -111,4 +378,115 @@ impl App {
 
         Ok(())
     }
+
+    /// `Space s`: search the section under the cursor, heading and
+    /// subsections included.
+    pub(crate) fn open_section_search(&mut self) -> Result<()> {
+        let lines = self.buffer_lines();
+        let headings = outline::headings(lines.iter().map(String::as_str));
+        let (heading, rows) = outline::section_at(&headings, self.buffer.cursor.row, lines.len());
+        self.finder_section = Some((heading.map(|h| h.title.clone()), rows));
+        self.open_finder(FinderMode::Section)
+    }
+
+    fn section_search_hits(&self, query: &str, limit: usize) -> Vec<FinderResult> {
+        let Some((_, rows)) = &self.finder_section else {
+            return Vec::new();
+        };
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let needle = query.to_lowercase();
+        let path = self.buffer.path.clone().unwrap_or_default();
+        rows.clone()
+            .filter_map(|row| Some((row, self.buffer.line_text(row)?)))
+            .filter(|(_, line)| line.to_lowercase().contains(&needle))
+            .take(limit)
+            .map(|(row, line)| FinderResult {
+                preview: format!("{}  {}", row + 1, line.trim()),
+                path: path.clone(),
+                line: Some(row + 1),
+            })
+            .collect()
+    }
+
+    /// Keep content search hits as the quickfix list so `]q` / `[q` can
+    /// walk them after the finder closes.
+    pub(crate) fn quickfix_from_finder(&mut self) {
+        let entries = self
+            .finder_results
+            .iter()
+            .filter_map(|result| {
+                let line = result.line?;
+                let prefix = format!("{}:{line}  ", result.path.to_string_lossy());
+                Some(QuickfixEntry {
+                    path: result.path.clone(),
+                    line,
+                    text: result
+                        .preview
+                        .strip_prefix(&prefix)
+                        .unwrap_or(&result.preview)
+                        .to_string(),
+                })
+            })
+            .collect();
+        let title = match self.finder_mode {
+            FinderMode::BrokenLinks => "links check".to_string(),
+            _ => format!("search: {}", self.finder_query.trim()),
+        };
+        self.set_quickfix(title, entries, Some(self.finder_selected));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::app::FinderMode;
+    use crate::test_util::{app_in, temp_dir};
+
+    #[test]
+    fn test_stale_lists_notes_untouched_for_days() {
+        let vault = temp_dir();
+        let old = vault.path().join("old.md");
+        std::fs::write(&old, "# Old\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(40 * 86_400))
+            .unwrap();
+        std::fs::write(vault.path().join("fresh.md"), "# Fresh\n").unwrap();
+        let mut app = app_in(vault.path());
+
+        assert!(app.stale_command("30").is_empty());
+        assert!(matches!(app.finder_mode, FinderMode::Stale));
+        let stale: Vec<_> = app.finder_stale.iter().map(|result| &result.path).collect();
+        assert_eq!(stale, [&old]);
+        assert!(app.finder_stale[0].preview.ends_with("(40d)"));
+
+        assert_eq!(
+            app.stale_command("999999999999999"),
+            ["stale: every note changed in the last 999999999999999 days"]
+        );
+        assert_eq!(app.stale_command("-3"), ["usage: stale [days]"]);
+    }
+
+    #[test]
+    fn test_finder_picks_up_notes_from_watcher_events() {
+        let vault = temp_dir();
+        std::fs::write(vault.path().join("alpha.md"), "").unwrap();
+        let mut app = app_in(vault.path());
+        app.open_finder(FinderMode::Files).unwrap();
+        let found = |app: &crate::app::App, path| {
+            app.finder_results.iter().any(|result| &result.path == path)
+        };
+
+        let beta = vault.path().join("beta.md");
+        std::fs::write(&beta, "").unwrap();
+        app.refresh_finder_results().unwrap();
+        assert!(!found(&app, &beta));
+        app.handle_file_changed(beta.clone()).unwrap();
+        assert!(found(&app, &beta));
+    }
 }
@@ -0,0 +1,162 @@
+use crate::app::{App, SpellPopup};
+use crate::model::mode::Mode;
+use crate::model::spell::{self, SpellChecker};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// `:spell [on|off]`, toggling without an argument.
+    pub(crate) fn spell_command(&mut self, args: &str) -> Vec<String> {
+        let enable = match args {
+            "" => self.spell.is_none(),
+            "on" => true,
+            "off" => false,
+            _ => return vec!["usage: spell [on|off]".to_string()],
+        };
+
+        self.mark_render_dirty();
+        if !enable {
+            self.spell = None;
+            return vec!["spell: off".to_string()];
+        }
+        if self.spell.is_some() {
+            return vec![format!("spell: on ({})", self.config.spell.language)];
+        }
+        match SpellChecker::load(&self.config.spell, &self.config.vault_path()) {
+            Ok(checker) => {
+                self.spell = Some(checker);
+                vec![format!("spell: on ({})", self.config.spell.language)]
+            }
+            Err(err) => vec![err],
+        }
+    }
+
+    /// Misspelled byte ranges in `line`, empty when spell checking is off.
+    pub(crate) fn misspelled_ranges(&self, line: &str) -> Vec<std::ops::Range<usize>> {
+        self.spell
+            .as_ref()
+            .map(|checker| checker.misspelled(line))
+            .unwrap_or_default()
+    }
+
+    fn spell_word_at_cursor(&mut self) -> Option<(std::ops::Range<usize>, String)> {
+        if self.spell.is_none() {
+            self.push_notification("spell: off (enable with :spell)".to_string());
+            return None;
+        }
+        let line = self.buffer.line_text(self.buffer.cursor.row)?;
+        let col = self.buffer.cursor.col;
+        let found = spell::prose_words(&line)
+            .into_iter()
+            .find(|range| range.start <= col && col < range.end);
+        if found.is_none() {
+            self.push_notification("spell: no word under cursor".to_string());
+        }
+        found.map(|range| {
+            let word = line[range.clone()].to_string();
+            (range, word)
+        })
+    }
+
+    /// `z=`: open the suggestion popup for the word under the cursor.
+    pub(crate) fn open_spell_suggestions(&mut self) {
+        let Some((range, word)) = self.spell_word_at_cursor() else {
+            return;
+        };
+        let Some(checker) = self.spell.as_ref() else {
+            return;
+        };
+        if checker.check(&word) {
+            self.push_notification(format!("spell: \"{word}\" is spelled correctly"));
+            return;
+        }
+
+        let suggestions = checker.suggest(&word);
+        if suggestions.is_empty() {
+            self.push_notification(format!("spell: no suggestions for \"{word}\""));
+            return;
+        }
+        self.spell_popup = Some(SpellPopup {
+            row: self.buffer.cursor.row,
+            range,
+            word,
+            suggestions,
+            selected: 0,
+        });
+        self.mode = Mode::SpellSuggest;
+        self.mark_render_dirty();
+    }
+
+    /// `zg`: add the word under the cursor to the vault dictionary.
+    pub(crate) fn spell_add_word_at_cursor(&mut self) {
+        let Some((_, word)) = self.spell_word_at_cursor() else {
+            return;
+        };
+        let Some(checker) = self.spell.as_mut() else {
+            return;
+        };
+        let note = match checker.add_word(&word) {
+            Ok(()) => format!(
+                "spell: added \"{word}\" to {}",
+                self.config.spell.custom_dictionary
+            ),
+            Err(err) => format!("spell: cannot save \"{word}\": {err}"),
+        };
+        self.push_notification(note);
+        self.mark_render_dirty();
+    }
+
+    pub(crate) fn handle_key_spell(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(popup) = self.spell_popup.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                popup.selected = (popup.selected + 1).min(popup.suggestions.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => popup.selected = popup.selected.saturating_sub(1),
+            KeyCode::Enter => {
+                let choice = popup.selected;
+                self.apply_spell_suggestion(choice);
+            }
+            KeyCode::Char(ch @ '1'..='9') => {
+                let choice = ch as usize - '1' as usize;
+                if choice < popup.suggestions.len() {
+                    self.apply_spell_suggestion(choice);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.spell_popup = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    fn apply_spell_suggestion(&mut self, choice: usize) {
+        let Some(popup) = self.spell_popup.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        if self.reject_readonly_edit() {
+            return;
+        }
+
+        // A collab peer may have edited the line while the popup was open.
+        let line = self.buffer.line_text(popup.row).unwrap_or_default();
+        if line.get(popup.range.clone()) != Some(popup.word.as_str()) {
+            return;
+        }
+        let line_start = self.buffer.rope.line_to_char(popup.row);
+        let start = line_start + line[..popup.range.start].chars().count();
+        let end = start + popup.word.chars().count();
+        self.buffer
+            .replace_chars(start..end, &popup.suggestions[choice]);
+        self.buffer.set_cursor_char_offset(start);
+        self.schedule_auto_save();
+    }
+}
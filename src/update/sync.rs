@@ -0,0 +1,178 @@
+use crate::app::App;
+use crate::msg::Msg;
+use crate::sync::{SyncResult, SyncStatus};
+use std::time::Instant;
+
+impl App {
+    pub(crate) fn sync_command(&mut self) -> Vec<String> {
+        match self.start_sync() {
+            Ok(name) => vec![format!("sync: {name} started")],
+            Err(err) => vec![err],
+        }
+    }
+
+    pub(crate) fn sync_summary(&self) -> String {
+        let Some(backend) = self.sync_backend.as_ref() else {
+            return match &self.sync_status {
+                SyncStatus::Error(err) => err.clone(),
+                _ if self.config.sync.backend == "git" => {
+                    "sync: the vault is not in a git repository".to_string()
+                }
+                _ => format!(
+                    "sync: backend \"{}\" does not run in the background",
+                    self.config.sync.backend
+                ),
+            };
+        };
+
+        let state = match &self.sync_status {
+            SyncStatus::Idle => "idle".to_string(),
+            SyncStatus::Syncing => "running".to_string(),
+            SyncStatus::Error(err) => format!("failed ({err})"),
+        };
+        let last = self
+            .last_sync
+            .map(|at| format!(", last run {} ago", format_elapsed(at)))
+            .unwrap_or_default();
+        let next = self
+            .next_sync_at
+            .map(|at| {
+                let mins = at.saturating_duration_since(Instant::now()).as_secs() / 60;
+                format!(", next in {mins}m")
+            })
+            .unwrap_or_default();
+        format!("sync: {} {state}{last}{next}", backend.name())
+    }
+
+    /// Spawn a background sync pass unless one is already running.
+    fn start_sync(&mut self) -> Result<&'static str, String> {
+        let Some(backend) = self.sync_backend.clone() else {
+            return Err(self.sync_summary());
+        };
+        if self.sync_status == SyncStatus::Syncing {
+            return Err(format!("sync: {} already running", backend.name()));
+        }
+
+        self.sync_status = SyncStatus::Syncing;
+        self.next_sync_at = None;
+        self.activities.start(format!("sync: {}", backend.name()));
+        let tx = self.event_tx.clone();
+        let name = backend.name();
+        std::thread::spawn(move || {
+            let result = backend.sync().map_err(|err| err.to_string());
+            let _ = tx.send(Msg::SyncFinished(result));
+        });
+        Ok(name)
+    }
+
+    /// Bring the next pass forward to the backend's delay after a save.
+    pub(crate) fn schedule_sync_after_save(&mut self) {
+        let Some(delay) = self
+            .sync_backend
+            .as_ref()
+            .and_then(|backend| backend.after_save())
+        else {
+            return;
+        };
+        let at = Instant::now() + delay;
+        self.next_sync_at = Some(self.next_sync_at.map_or(at, |next| next.min(at)));
+    }
+
+    pub(crate) fn schedule_background_sync(&mut self, now: Instant) {
+        if self.next_sync_at.is_some_and(|at| now >= at) {
+            let _ = self.start_sync();
+        }
+    }
+
+    pub(crate) fn handle_sync_finished(&mut self, result: Result<SyncResult, String>) {
+        if let Some(backend) = self.sync_backend.as_ref() {
+            self.activities.finish(&format!("sync: {}", backend.name()));
+        }
+        let now = Instant::now();
+        self.last_sync = Some(now);
+        // Keep a pass a save asked for while this one ran.
+        let next = self
+            .sync_backend
+            .as_ref()
+            .and_then(|backend| backend.interval())
+            .map(|interval| now + interval);
+        self.next_sync_at = match (self.next_sync_at, next) {
+            (Some(saved), Some(next)) => Some(saved.min(next)),
+            (saved, next) => saved.or(next),
+        };
+
+        match result {
+            Ok(result) => {
+                self.sync_status = SyncStatus::Idle;
+                let message = match result {
+                    SyncResult::NoChanges => "sync: up to date".to_string(),
+                    SyncResult::Snapshot { id, changed } => {
+                        format!("sync: snapshot {id} ({changed} files changed)")
+                    }
+                    SyncResult::Git {
+                        committed,
+                        pulled,
+                        pushed,
+                    } => {
+                        // Pulled notes reload like any other change on disk.
+                        let vault = self.config.vault_path();
+                        for path in &pulled {
+                            let _ = self.event_tx.send(Msg::FileChanged(vault.join(path)));
+                        }
+                        let mut done = Vec::new();
+                        if let Some(hash) = committed {
+                            done.push(format!("committed {hash}"));
+                        }
+                        if !pulled.is_empty() {
+                            done.push(format!("pulled {} files", pulled.len()));
+                        }
+                        if pushed {
+                            done.push("pushed".to_string());
+                        }
+                        format!("sync: {}", done.join(", "))
+                    }
+                    SyncResult::Conflict(paths) => {
+                        let names: Vec<String> = paths
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect();
+                        format!(
+                            "sync: not merged, {} changed on both sides: {}",
+                            paths.len(),
+                            names.join(", ")
+                        )
+                    }
+                };
+                self.push_notification(message);
+            }
+            Err(err) => {
+                self.push_notification(format!("sync: {err}"));
+                self.sync_status = SyncStatus::Error(err);
+            }
+        }
+    }
+
+    /// Compact indicator for the status bar, `None` when no backend runs
+    /// here or a pass is running (the activity spinner shows that).
+    pub(crate) fn sync_indicator(&self) -> Option<String> {
+        self.sync_backend.as_ref()?;
+        let label = match &self.sync_status {
+            SyncStatus::Syncing => return None,
+            SyncStatus::Error(_) => "sync failed".to_string(),
+            SyncStatus::Idle => match self.last_sync {
+                Some(at) => format!("synced {}", format_elapsed(at)),
+                None => "sync pending".to_string(),
+            },
+        };
+        Some(label)
+    }
+}
+
+fn format_elapsed(at: Instant) -> String {
+    let secs = at.elapsed().as_secs();
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
@@ -0,0 +1,175 @@
+use crate::app::{App, TaskItem, TasksView};
+use crate::model::mode::Mode;
+use crate::model::query;
+use crate::model::task;
+use crate::update::note_ops::{plural, rel_display};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// `:tasks [#tag|folder/]`: unchecked tasks across the vault, narrowed
+    /// to notes with the tag or under the folder.
+    pub(crate) fn tasks_command(&mut self, args: &str) -> Vec<String> {
+        let filter = args.trim();
+        let tag = filter.strip_prefix('#').map(str::to_lowercase);
+        let folder = match filter.trim_matches('/') {
+            "" => None,
+            _ if tag.is_some() => None,
+            folder => Some(format!("{folder}/")),
+        };
+
+        let root = self.config.vault_path();
+        let mut items = Vec::new();
+        for path in self.file_tree.all_file_paths() {
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let rel = rel_display(&root, &path);
+            if folder
+                .as_ref()
+                .is_some_and(|folder| !rel.starts_with(folder))
+            {
+                continue;
+            }
+            // The link index knows each note's tags without reading it.
+            if let (Some(tag), Some(index)) = (&tag, &self.link_index)
+                && !index.has_tag(&path, tag)
+            {
+                continue;
+            }
+            let Some(text) = self.note_text(&path) else {
+                continue;
+            };
+            if tag
+                .as_ref()
+                .is_some_and(|tag| !query::note_tags(&text).contains(tag))
+            {
+                continue;
+            }
+            items.extend(
+                task::open_tasks(&text)
+                    .into_iter()
+                    .map(|(row, line)| TaskItem {
+                        path: path.clone(),
+                        line: row + 1,
+                        text: line,
+                        done: false,
+                    }),
+            );
+        }
+        if items.is_empty() {
+            return vec![match filter {
+                "" => "tasks: no open tasks in the vault".to_string(),
+                filter => format!("tasks: no open tasks in {filter}"),
+            }];
+        }
+        items.sort_by(|a, b| {
+            rel_display(&root, &a.path)
+                .cmp(&rel_display(&root, &b.path))
+                .then(a.line.cmp(&b.line))
+        });
+
+        self.tasks_view = Some(TasksView {
+            filter: filter.to_string(),
+            items,
+            selected: 0,
+        });
+        self.mode = Mode::Tasks;
+        self.mark_render_dirty();
+        Vec::new()
+    }
+
+    pub(crate) fn handle_key_tasks(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(view) = self.tasks_view.as_mut() else {
+            self.mode = Mode::Normal;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.selected = (view.selected + 1).min(view.items.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char(' ' | 'x') => self.toggle_selected_task(),
+            KeyCode::Enter => {
+                let item = view.items.get(view.selected).cloned();
+                self.tasks_view = None;
+                self.mode = Mode::Normal;
+                if let Some(item) = item {
+                    self.open_file_at_line(item.path, item.line)?;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.tasks_view = None;
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+        self.mark_render_dirty();
+        Ok(())
+    }
+
+    /// Flip the selected task's checkbox in its note, refusing if the line
+    /// no longer holds the task the list shows.
+    fn toggle_selected_task(&mut self) {
+        let Some(item) = self
+            .tasks_view
+            .as_ref()
+            .and_then(|view| view.items.get(view.selected))
+            .cloned()
+        else {
+            return;
+        };
+        let rel = rel_display(&self.config.vault_path(), &item.path);
+        let Some(text) = self.note_text(&item.path) else {
+            self.push_notification(format!("tasks: cannot read {rel}"));
+            return;
+        };
+
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        let current = lines
+            .get(item.line - 1)
+            .map(|line| line.trim_end_matches('\r'));
+        let Some(toggled) = current
+            .filter(|line| task::parse_checkbox(line) == Some(item.done))
+            .and_then(task::toggle_checkbox)
+        else {
+            self.push_notification(format!(
+                "tasks: {rel}:{} changed since the list was made; run :tasks again",
+                item.line
+            ));
+            return;
+        };
+        let ending = if lines[item.line - 1].ends_with('\r') {
+            "\r"
+        } else {
+            ""
+        };
+        let replaced = format!("{toggled}{ending}");
+        lines[item.line - 1] = &replaced;
+
+        if let Err(err) = self.replace_note_text(&item.path, &lines.join("\n")) {
+            self.push_notification(format!("tasks: cannot write {rel}: {err}"));
+            return;
+        }
+        if let Some(view) = self.tasks_view.as_mut()
+            && let Some(shown) = view.items.get_mut(view.selected)
+        {
+            shown.done = !shown.done;
+            shown.text = toggled;
+        }
+    }
+
+    /// `3/7 done` for the popup footer.
+    pub(crate) fn tasks_progress(&self) -> String {
+        let Some(view) = self.tasks_view.as_ref() else {
+            return String::new();
+        };
+        let done = view.items.iter().filter(|item| item.done).count();
+        format!(
+            "{done}/{} task{} done",
+            view.items.len(),
+            plural(view.items.len())
+        )
+    }
+}
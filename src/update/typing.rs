@@ -0,0 +1,115 @@
+use crate::app::App;
+use crate::model::{pairs, punctuation};
+
+impl App {
+    /// With `editor.smart_punctuation`, turn the dash or quote just typed
+    /// into its typographic form, except in code and frontmatter.
+    pub(crate) fn smart_punctuate(&mut self) {
+        if !self.config.editor.smart_punctuation {
+            return;
+        }
+        let row = self.buffer.cursor.row;
+        let Some(line) = self.buffer.line_text(row) else {
+            return;
+        };
+        let col = self.buffer.cursor.col.min(line.len());
+        let Some((count, replacement)) = punctuation::substitution(&line[..col]) else {
+            return;
+        };
+        let lines = self.buffer_lines();
+        if punctuation::code_mask(&lines)
+            .get(row)
+            .copied()
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        let end = self.buffer.rope.line_to_char(row) + line[..col].chars().count();
+        self.buffer.replace_chars(end - count..end, replacement);
+    }
+
+    /// With `editor.auto_pair`, type `ch` together with its closer, or step
+    /// over the closer already under the cursor. Returns whether `ch` was
+    /// handled.
+    pub(crate) fn auto_pair_char(&mut self, ch: char) -> bool {
+        if !self.config.editor.auto_pair {
+            return false;
+        }
+        let row = self.buffer.cursor.row;
+        let Some(line) = self.buffer.line_text(row) else {
+            return false;
+        };
+        let col = self.buffer.cursor.col.min(line.len());
+        if pairs::steps_over(&line, col, ch) {
+            self.buffer.cursor.move_to(row, col + ch.len_utf8());
+            return true;
+        }
+        let Some(close) = pairs::closer(&line, col, ch) else {
+            return false;
+        };
+        self.buffer.insert_char(ch);
+        self.buffer.insert_char(close);
+        self.buffer.cursor.move_to(row, col + ch.len_utf8());
+        true
+    }
+
+    /// With `editor.auto_pair`, Backspace in an empty pair like `(|)`
+    /// deletes both halves, and after a paired `**`/`__`/`~~`/`==` marker
+    /// deletes the whole marker and offers to drop its partner with Tab.
+    /// Returns whether the key was handled.
+    pub(crate) fn smart_backspace(&mut self) -> bool {
+        if !self.config.editor.auto_pair {
+            return false;
+        }
+        let row = self.buffer.cursor.row;
+        let Some(line) = self.buffer.line_text(row) else {
+            return false;
+        };
+        let col = self.buffer.cursor.col.min(line.len());
+        let line_start = self.buffer.rope.line_to_char(row);
+        let char_at = |byte: usize| line_start + line[..byte].chars().count();
+
+        if pairs::empty_pair_at(&line, col) {
+            let at = char_at(col);
+            self.buffer.replace_chars(at - 1..at + 1, "");
+            return true;
+        }
+        let Some((marker, partner)) = pairs::marker_before(&line, col) else {
+            return false;
+        };
+        let start = char_at(marker.start);
+        self.buffer.replace_chars(start..start + marker.len(), "");
+        let partner = if partner.start > marker.start {
+            partner.start - marker.len()..partner.end - marker.len()
+        } else {
+            partner
+        };
+        let text = line[marker].to_string();
+        self.push_notification(format!("{text} left unpaired, Tab removes its partner"));
+        self.pending_pair = Some((row, partner, text));
+        true
+    }
+
+    /// Tab right after [`smart_backspace`](Self::smart_backspace) removed a
+    /// marker: delete its partner too, if the line still has it.
+    pub(crate) fn remove_pending_pair(&mut self) -> bool {
+        let Some((row, range, marker)) = self.pending_pair.take() else {
+            return false;
+        };
+        let Some(line) = self.buffer.line_text(row) else {
+            return false;
+        };
+        if line.get(range.clone()) != Some(marker.as_str()) {
+            return false;
+        }
+        let (cursor_row, mut cursor_col) = (self.buffer.cursor.row, self.buffer.cursor.col);
+        if cursor_row == row && range.end <= cursor_col {
+            cursor_col -= range.len();
+        }
+        let start = self.buffer.rope.line_to_char(row) + line[..range.start].chars().count();
+        self.buffer.replace_chars(start..start + range.len(), "");
+        self.buffer.cursor.move_to(cursor_row, cursor_col);
+        true
+    }
+}
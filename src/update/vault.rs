@@ -0,0 +1,153 @@
+use crate::app::{App, write_buffer};
+use crate::hooks::HookEvent;
+use crate::profile::StartupProfile;
+use std::path::PathBuf;
+
+impl App {
+    /// `:vault [name]`: list the `[vaults]` entries, or switch to one.
+    pub(crate) fn vault_command(&mut self, args: &str) -> Vec<String> {
+        let name = args.trim();
+        if name.is_empty() {
+            return self.vault_list();
+        }
+        let Some(path) = self.config.vaults.get(name).cloned() else {
+            let mut notes = vec![format!("vault: no vault named \"{name}\"")];
+            notes.extend(self.vault_list());
+            return notes;
+        };
+        if self.config.vault_name() == Some(name) {
+            return vec![format!("vault: {name} is already open")];
+        }
+        match self.switch_vault(PathBuf::from(path)) {
+            Ok(()) => vec![format!(
+                "vault: switched to {name} ({})",
+                self.config.vault_path().display()
+            )],
+            Err(err) => vec![format!("vault: {err}")],
+        }
+    }
+
+    fn vault_list(&self) -> Vec<String> {
+        if self.config.vaults.is_empty() {
+            return vec!["vault: no [vaults] in the config".to_string()];
+        }
+        let current = self.config.vault_name();
+        let mut notes = vec!["vaults:".to_string()];
+        notes.extend(self.config.vaults.iter().map(|(name, path)| {
+            let marker = if current == Some(name.as_str()) {
+                "*"
+            } else {
+                " "
+            };
+            format!(" {marker}{name}  {path}")
+        }));
+        notes
+    }
+
+    /// Save every note, then start over against `root`: a new file tree,
+    /// watcher and indexes, with the register and yank history kept. Nothing
+    /// changes when a note cannot be saved.
+    pub(crate) fn switch_vault(&mut self, root: PathBuf) -> Result<(), String> {
+        if self.collab.is_some() {
+            return Err("stop the collab session first (:collab stop)".to_string());
+        }
+        self.save_before_switch()?;
+        self.save_search_index();
+        self.save_link_index();
+        self.save_recent_files();
+
+        let mut config = self.config.clone();
+        config.general.vault_path = root.to_string_lossy().into_owned();
+        let mut app = App::new(
+            config,
+            self.event_tx.clone(),
+            &mut StartupProfile::default(),
+        )
+        .map_err(|err| format!("cannot open {}: {err}", root.display()))?;
+        app.register = std::mem::take(&mut self.register);
+        std::mem::swap(&mut app.yank_history, &mut self.yank_history);
+        app.sidebar_visible = self.sidebar_visible;
+        if app.minimap_visible != self.minimap_visible {
+            app.set_minimap(self.minimap_visible);
+        }
+        *self = app;
+        Ok(())
+    }
+
+    /// Write every unsaved note now rather than on a background thread, so
+    /// a failed write is seen before the buffers holding it are dropped.
+    fn save_before_switch(&mut self) -> Result<(), String> {
+        let vault = self.config.vault_path();
+        let mut saved = Vec::new();
+        let mut result = Ok(());
+        let buffers = std::iter::once(&mut self.buffer).chain(self.inactive_buffers.values_mut());
+        for buffer in buffers {
+            if !buffer.dirty && buffer.save_debounce.is_none() {
+                continue;
+            }
+            if buffer.has_unkept_text() {
+                result = Err("the current buffer has unsaved text and no file".to_string());
+                break;
+            }
+            let Some(path) = buffer.path.clone() else {
+                continue;
+            };
+            if let Err(err) = write_buffer(&path, &buffer.rope) {
+                let name = path.strip_prefix(&vault).unwrap_or(&path);
+                result = Err(format!("cannot save {}: {err}", name.display()));
+                break;
+            }
+            buffer.dirty = false;
+            buffer.save_debounce = None;
+            saved.push((path, buffer.rope.clone()));
+        }
+
+        for (path, rope) in saved {
+            self.index_saved_note(&path, &rope);
+            if let Some(hook) = self.hook(HookEvent::Save, &path, None) {
+                hook.spawn();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::buffer::Buffer;
+    use crate::test_util::app_in;
+
+    #[test]
+    fn test_switch_vault_stays_when_a_note_cannot_be_written() {
+        let tmp = crate::test_util::temp_dir();
+        let (home, work) = (tmp.path().join("home"), tmp.path().join("work"));
+        let mut app = app_in(&home);
+
+        // A plain file where a folder should be makes the second write fail.
+        std::fs::write(home.join("blocker"), "").unwrap();
+        let mut blocked = Buffer::new();
+        blocked.path = Some(home.join("blocker/note.md"));
+        blocked.dirty = true;
+        app.inactive_buffers
+            .insert(home.join("blocker/note.md"), blocked);
+        let mut saved = Buffer::new();
+        saved.path = Some(home.join("a.md"));
+        saved.dirty = true;
+        app.buffer = saved;
+
+        let err = app.switch_vault(work.clone()).unwrap_err();
+        assert!(err.starts_with("cannot save blocker/note.md: "), "{err}");
+        assert_eq!(app.config.vault_path(), home);
+        assert!(home.join("a.md").exists());
+        assert!(!app.buffer.dirty);
+        assert!(app.inactive_buffers[&home.join("blocker/note.md")].dirty);
+
+        // Text with no file to go to is kept as well.
+        app.inactive_buffers.clear();
+        app.buffer = Buffer::new();
+        app.buffer.dirty = true;
+        assert!(app.switch_vault(work.clone()).is_err());
+        assert_eq!(app.config.vault_path(), home);
+        assert!(!work.exists());
+    }
+}
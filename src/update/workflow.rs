@@ -0,0 +1,126 @@
+use crate::app::App;
+use crate::model::buffer::Buffer;
+use crate::model::config::WorkflowConfig;
+use crate::model::frontmatter::{Frontmatter, FrontmatterValue};
+use crate::model::mode::Mode;
+
+/// Read a buffer's workflow state from its frontmatter.
+pub(crate) fn buffer_workflow_state(buffer: &Buffer, config: &WorkflowConfig) -> Option<String> {
+    buffer
+        .frontmatter()?
+        .get_scalar(&config.field)
+        .map(str::trim)
+        .filter(|state| !state.is_empty())
+        .map(str::to_string)
+}
+
+/// Whether a buffer's workflow state is one of the read-only ones.
+pub(crate) fn workflow_locked(buffer: &Buffer, config: &WorkflowConfig) -> bool {
+    buffer_workflow_state(buffer, config).is_some_and(|state| {
+        config
+            .readonly_states
+            .iter()
+            .any(|locked| locked.eq_ignore_ascii_case(&state))
+    })
+}
+
+impl App {
+    pub(crate) fn active_workflow_state(&self) -> Option<String> {
+        buffer_workflow_state(&self.buffer, &self.config.workflow)
+    }
+
+    pub(crate) fn buffer_is_readonly(&self) -> bool {
+        workflow_locked(&self.buffer, &self.config.workflow)
+    }
+
+    /// Returns `true` (and explains why) when the active note is locked by
+    /// its workflow state and the requested edit must be dropped.
+    pub(crate) fn reject_readonly_edit(&mut self) -> bool {
+        if !self.buffer_is_readonly() {
+            return false;
+        }
+
+        if self.mode == Mode::Insert {
+            self.mode = Mode::Normal;
+        }
+
+        let state = self.active_workflow_state().unwrap_or_default();
+        self.push_notification(format!(
+            "note is {state} (read-only); use workflow.prev to reopen it"
+        ));
+        true
+    }
+
+    pub(crate) fn workflow_summary(&self) -> String {
+        let states = self.config.workflow.states.join(" → ");
+        match self.active_workflow_state() {
+            Some(state) => format!("workflow: {state} ({states})"),
+            None => format!("workflow: no {} set ({states})", self.config.workflow.field),
+        }
+    }
+
+    pub(crate) fn workflow_step(&mut self, delta: isize) -> Vec<String> {
+        let states = &self.config.workflow.states;
+        if states.is_empty() {
+            return vec!["workflow: no states configured".to_string()];
+        }
+
+        let current = self.active_workflow_state().and_then(|state| {
+            states
+                .iter()
+                .position(|candidate| candidate.eq_ignore_ascii_case(&state))
+        });
+
+        let next = match current {
+            None => 0,
+            Some(idx) => {
+                let max = states.len() as isize - 1;
+                let next = (idx as isize + delta).clamp(0, max) as usize;
+                if next == idx {
+                    return vec![format!("workflow: already {}", states[idx])];
+                }
+                next
+            }
+        };
+
+        let state = states[next].clone();
+        self.workflow_set(&state)
+    }
+
+    pub(crate) fn workflow_set(&mut self, state: &str) -> Vec<String> {
+        let state = state.trim();
+        let Some(state) = self
+            .config
+            .workflow
+            .states
+            .iter()
+            .find(|candidate| candidate.eq_ignore_ascii_case(state))
+            .cloned()
+        else {
+            return vec![format!(
+                "usage: workflow.set <{}>",
+                self.config.workflow.states.join("|")
+            )];
+        };
+
+        let mut frontmatter = self.buffer.frontmatter().unwrap_or_default();
+        frontmatter.set(
+            &self.config.workflow.field,
+            FrontmatterValue::Scalar(state.clone()),
+        );
+        self.apply_frontmatter(&frontmatter);
+
+        let mut notes = vec![format!("workflow: {state}")];
+        if self.buffer_is_readonly() {
+            notes.push("workflow: note is now read-only".to_string());
+        }
+        notes
+    }
+
+    pub(crate) fn apply_frontmatter(&mut self, frontmatter: &Frontmatter) {
+        self.buffer.set_frontmatter(frontmatter);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+    }
+}
@@ -0,0 +1,81 @@
+use crate::app::App;
+use crate::model::mode::Mode;
+use crate::model::wrap;
+use std::ops::Range;
+
+impl App {
+    /// `gq`: reflow the paragraph or list item under the cursor.
+    pub(crate) fn reflow_at_cursor(&mut self) {
+        let row = self.buffer.cursor.row;
+        self.reflow_rows(row..row + 1);
+    }
+
+    /// Visual `gq`: reflow every paragraph the selection touches.
+    pub(crate) fn reflow_selection(&mut self) {
+        let linewise = self.mode == Mode::VisualLine;
+        let rows = self.buffer.selection_char_range(linewise).map(|range| {
+            let rope = &self.buffer.rope;
+            let last = range.end.saturating_sub(1).max(range.start);
+            rope.char_to_line(range.start)..rope.char_to_line(last) + 1
+        });
+        self.exit_visual();
+        if let Some(rows) = rows {
+            self.reflow_rows(rows);
+        }
+    }
+
+    fn reflow_rows(&mut self, rows: Range<usize>) {
+        if self.reject_readonly_edit() {
+            return;
+        }
+        let lines = self.buffer_lines();
+        let width = self.config.editor.text_width;
+        let (covered, reflowed) = wrap::reflow_range(&lines, rows, width);
+        if covered.start >= lines.len() || lines[covered.clone()] == reflowed[..] {
+            self.push_notification("gq: nothing to reflow".to_string());
+            return;
+        }
+
+        self.buffer.replace_lines(covered, &reflowed);
+        self.buffer.scroll_to_cursor();
+        self.mark_render_dirty();
+        self.schedule_auto_save();
+    }
+
+    /// With `editor.auto_wrap`, break the line being typed once it passes
+    /// `editor.text_width`, carrying the overflow to a new line indented
+    /// like the paragraph or list item, under the same quote markers.
+    pub(crate) fn auto_wrap_line(&mut self) {
+        if !self.config.editor.auto_wrap {
+            return;
+        }
+        let width = self.config.editor.text_width;
+        let row = self.buffer.cursor.row;
+        let Some(line) = self.buffer.line_text(row) else {
+            return;
+        };
+        let Some(at) = wrap::break_point(&line, width) else {
+            return;
+        };
+        // Only wrap while typing past the margin, not when editing earlier.
+        if self.buffer.cursor.col <= at {
+            return;
+        }
+        let lines = self.buffer_lines();
+        if !wrap::prose_mask(&lines).get(row).copied().unwrap_or(false) {
+            return;
+        }
+
+        let indent = wrap::continuation_prefix(&line);
+        let cursor_tail = self.buffer.cursor.col - (at + 1);
+
+        let line_start = self.buffer.rope.line_to_char(row);
+        let space = line_start + line[..at].chars().count();
+        self.buffer
+            .replace_chars(space..space + 1, &format!("\n{indent}"));
+        self.buffer
+            .cursor
+            .move_to(row + 1, indent.len() + cursor_tail);
+        self.buffer.scroll_to_cursor();
+    }
+}
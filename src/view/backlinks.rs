@@ -29,7 +29,10 @@ impl App {
                         preview.push('…');
                     }
 
-                    let label = format!("{file}:{}  {preview}", entry.line);
+                    let label = match &entry.anchor {
+                        Some(anchor) => format!("{file}:{} #{anchor}  {preview}", entry.line),
+                        None => format!("{file}:{}  {preview}", entry.line),
+                    };
                     if idx == self.backlinks_selected {
                         Line::from(Span::styled(
                             label,
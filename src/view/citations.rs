@@ -0,0 +1,24 @@
+use crate::app::App;
+use crate::model::mode::Mode;
+use crate::view::render_completion_popup;
+use ratatui::{Frame, layout::Position, style::Color};
+
+impl App {
+    /// `@key` candidates in a box under the cursor.
+    pub(crate) fn render_citation_completion(&self, frame: &mut Frame, cursor: Option<Position>) {
+        let (Some(completion), Some(cursor)) = (self.citation_completion.as_ref(), cursor) else {
+            return;
+        };
+        if self.mode != Mode::Insert {
+            return;
+        }
+        render_completion_popup(
+            frame,
+            cursor,
+            " Citations ",
+            &completion.candidates,
+            completion.selected,
+            Color::Rgb(190, 150, 255),
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::app::{App, centered_rect};
 use ratatui::{
     Frame,
+    layout::Rect,
     style::{Color, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
@@ -11,15 +12,62 @@ impl App {
         let area = centered_rect(70, 20, frame.area());
         frame.render_widget(Clear, area);
 
-        let prompt = Paragraph::new(format!(":{}", self.command_input)).block(
+        // A plugin's `prompt_input` question takes the place of the colon.
+        let (title, line) = match &self.plugin_prompt {
+            Some(prompt) => (
+                format!(" {} ", prompt.command),
+                format!("{} {}", prompt.question, self.command_input),
+            ),
+            None => (" Command ".to_string(), format!(":{}", self.command_input)),
+        };
+        let prompt = Paragraph::new(line.clone()).block(
             Block::default()
-                .title(" Command ")
+                .title(title)
                 .borders(Borders::ALL)
                 .style(Style::default().bg(Color::Rgb(15, 15, 24))),
         );
         frame.render_widget(prompt, area);
 
-        let cursor_x = area.x + 2 + self.command_input.len() as u16;
+        // Completion matches fill the rows under the prompt.
+        if let Some(completion) = &self.command_completion {
+            let rows = area.height.saturating_sub(3) as usize;
+            let first = completion.selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = completion
+                .candidates
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(idx, candidate)| {
+                    let style = if idx == completion.selected {
+                        Style::default().fg(Color::Black).bg(Color::Cyan)
+                    } else {
+                        Style::default().fg(Color::Rgb(150, 150, 170))
+                    };
+                    let mut spans = vec![Span::styled(format!(" {candidate} "), style)];
+                    if let Some(detail) = completion
+                        .details
+                        .get(idx)
+                        .filter(|detail| !detail.is_empty())
+                    {
+                        spans.push(Span::styled(
+                            format!(" {detail}"),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    Line::from(spans)
+                })
+                .collect();
+            let list_area = Rect {
+                x: area.x + 2,
+                y: area.y + 2,
+                width: area.width.saturating_sub(4),
+                height: rows as u16,
+            };
+            frame.render_widget(Paragraph::new(lines), list_area);
+        }
+
+        let cursor_x = area.x + 1 + line.chars().count() as u16;
         let cursor_y = area.y + 1;
         frame.set_cursor_position((cursor_x, cursor_y));
     }
@@ -36,6 +84,10 @@ impl App {
                 "  g  Grep content",
                 Style::default().fg(Color::Cyan),
             )),
+            Line::from(Span::styled(
+                "  s  Search section",
+                Style::default().fg(Color::Cyan),
+            )),
             Line::from(Span::styled(
                 "  e  Explorer / Sidebar",
                 Style::default().fg(Color::Cyan),
@@ -44,6 +96,26 @@ impl App {
                 "  b  Backlinks",
                 Style::default().fg(Color::Cyan),
             )),
+            Line::from(Span::styled(
+                "  r  Reveal in sidebar",
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(Span::styled(
+                "  l  Local graph",
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(Span::styled(
+                "  o  Outline",
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(Span::styled(
+                "  z  Zen mode",
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(Span::styled(
+                "  y  Copy code block",
+                Style::default().fg(Color::Cyan),
+            )),
             Line::from(Span::styled(
                 "  n  New note",
                 Style::default().fg(Color::Cyan),
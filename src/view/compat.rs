@@ -0,0 +1,164 @@
+//! Compatibility mode for terminals and fonts that mangle unicode glyphs or
+//! 24-bit colour (the Linux console, old xterms, serial lines).
+//!
+//! Rather than teaching every widget a second set of glyphs, the finished
+//! frame is rewritten: box drawing and symbols become ASCII, RGB and 256-colour
+//! styles snap to the nearest of the 16 basic colours.
+
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+/// Resolve `general.compat` ("auto", "on" or "off") against the environment.
+pub fn enabled(setting: &str) -> bool {
+    match setting {
+        "on" | "true" | "ascii" => true,
+        "off" | "false" => false,
+        _ => detect(),
+    }
+}
+
+/// Basic terminals by `$TERM`, or a locale that is not UTF-8.
+fn detect() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    if matches!(term.as_str(), "linux" | "dumb" | "ansi" | "cons25") || term.starts_with("vt") {
+        return true;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        })
+}
+
+/// Rewrite every cell of a rendered frame in place.
+pub fn downgrade(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        if let Some(ascii) = ascii_glyph(cell.symbol()) {
+            cell.set_symbol(ascii);
+        }
+        cell.fg = basic_color(cell.fg);
+        cell.bg = basic_color(cell.bg);
+    }
+}
+
+fn ascii_glyph(symbol: &str) -> Option<&'static str> {
+    let mut chars = symbol.chars();
+    let ch = chars.next()?;
+    if ch.is_ascii() || chars.next().is_some() {
+        return None;
+    }
+    Some(match ch {
+        '─' | '━' | '═' | '—' | '–' => "-",
+        '│' | '┃' | '║' | '▏' | '▕' => "|",
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╰' | '╯' | '╔' | '╗'
+        | '╚' | '╝' => "+",
+        '▾' | '▼' => "v",
+        '▸' | '▶' | '→' => ">",
+        '◂' | '◀' | '←' | '↩' => "<",
+        '↔' => "=",
+        '▴' | '▲' => "^",
+        '●' | '•' | '◆' | '★' => "*",
+        '○' | '◇' | '·' => "o",
+        '…' => ".",
+        '⠋' | '⠼' | '⠇' => "|",
+        '⠙' | '⠴' | '⠏' => "/",
+        '⠹' | '⠦' => "-",
+        '⠸' | '⠧' => "\\",
+        '␣' => "_",
+        '⚠' => "!",
+        '✓' | '✔' => "+",
+        '✗' | '✘' => "x",
+        '█' | '▓' | '▒' | '░' => "#",
+        '“' | '”' => "\"",
+        '‘' | '’' => "'",
+        _ => return None,
+    })
+}
+
+/// Nearest of the 16 ANSI colours for RGB and 256-colour values.
+fn basic_color(color: Color) -> Color {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(idx) if idx >= 16 => indexed_rgb(idx),
+        other => return other,
+    };
+    const PALETTE: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (170, 0, 0)),
+        (Color::Green, (0, 170, 0)),
+        (Color::Yellow, (170, 85, 0)),
+        (Color::Blue, (0, 0, 170)),
+        (Color::Magenta, (170, 0, 170)),
+        (Color::Cyan, (0, 170, 170)),
+        (Color::Gray, (170, 170, 170)),
+        (Color::DarkGray, (85, 85, 85)),
+        (Color::LightRed, (255, 85, 85)),
+        (Color::LightGreen, (85, 255, 85)),
+        (Color::LightYellow, (255, 255, 85)),
+        (Color::LightBlue, (85, 85, 255)),
+        (Color::LightMagenta, (255, 85, 255)),
+        (Color::LightCyan, (85, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    let distance = |(pr, pg, pb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        d(r, pr) + d(g, pg) + d(b, pb)
+    };
+    PALETTE
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// RGB of an xterm 256-colour index (16–255).
+fn indexed_rgb(idx: u8) -> (u8, u8, u8) {
+    if idx >= 232 {
+        let level = 8 + (idx - 232) * 10;
+        return (level, level, level);
+    }
+    let cube = idx - 16;
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    (level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_glyph() {
+        assert_eq!(ascii_glyph("─"), Some("-"));
+        assert_eq!(ascii_glyph("╭"), Some("+"));
+        assert_eq!(ascii_glyph("▸"), Some(">"));
+        assert_eq!(ascii_glyph("”"), Some("\""));
+        // ASCII, unknown symbols and grapheme clusters are left alone.
+        assert_eq!(ascii_glyph("a"), None);
+        assert_eq!(ascii_glyph("é"), None);
+        assert_eq!(ascii_glyph("e\u{301}"), None);
+        assert_eq!(ascii_glyph(""), None);
+    }
+
+    #[test]
+    fn test_basic_color() {
+        assert_eq!(basic_color(Color::Rgb(250, 80, 90)), Color::LightRed);
+        assert_eq!(basic_color(Color::Rgb(10, 10, 10)), Color::Black);
+        assert_eq!(basic_color(Color::Indexed(21)), Color::Blue);
+        // Already basic colours pass through.
+        assert_eq!(basic_color(Color::Indexed(3)), Color::Indexed(3));
+        assert_eq!(basic_color(Color::Cyan), Color::Cyan);
+        assert_eq!(basic_color(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn test_indexed_rgb() {
+        assert_eq!(indexed_rgb(16), (0, 0, 0));
+        assert_eq!(indexed_rgb(196), (255, 0, 0));
+        assert_eq!(indexed_rgb(110), (135, 175, 215));
+        assert_eq!(indexed_rgb(231), (255, 255, 255));
+        assert_eq!(indexed_rgb(232), (8, 8, 8));
+        assert_eq!(indexed_rgb(255), (238, 238, 238));
+    }
+}
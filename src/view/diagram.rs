@@ -0,0 +1,31 @@
+use crate::app::{App, centered_rect};
+use crate::diagram;
+use ratatui::{
+    Frame,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear},
+};
+
+impl App {
+    /// Frame for a kitty-graphics diagram preview; the image itself is
+    /// written straight to the terminal once the frame is on screen.
+    pub(crate) fn render_diagram_popup(&mut self, frame: &mut Frame) {
+        let Some(path) = self.diagram_preview.clone() else {
+            return;
+        };
+        let area = centered_rect(80, 80, frame.area());
+        let block = Block::default()
+            .title(" mermaid (any key closes) ")
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::Rgb(15, 15, 24)).fg(Color::Gray));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        if !self.diagram_placed && inner.width > 0 && inner.height > 0 {
+            self.terminal_escape
+                .push_str(&diagram::kitty_place(&path, inner));
+            self.diagram_placed = true;
+        }
+    }
+}
@@ -1,44 +1,143 @@
 use crate::app::App;
 use crate::app::{
-    SYNTAX_SET, SYNTECT_THEME, TokenKind, next_markdown_token, parse_code_fence_language,
-    syntect_to_ratatui,
+    CodeBlockState, SYNTAX_SET, SYNTECT_THEME, TokenKind, conceal_token, next_markdown_token,
+    parse_code_fence_language, parse_wikilink_target, syntect_to_ratatui,
 };
+use crate::model::embed;
 use crate::model::mode::Mode;
+use crate::plugin::runtime::{Decoration, DecorationKind};
 use ratatui::{
     Frame,
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Paragraph, Wrap},
 };
+use std::collections::HashMap;
+use std::path::Path;
 use syntect::easy::HighlightLines;
 
 impl App {
     pub(crate) fn render_editor(&mut self, frame: &mut Frame, area: ratatui::layout::Rect) {
         let top = self.buffer.viewport.top_line;
-        let bottom = (top + area.height as usize).min(self.buffer.line_count());
+        // A folded frontmatter block draws as its first line; the rows it
+        // frees go to the lines below.
+        let folded = self.folded_frontmatter();
+        let hidden = folded.as_ref().map_or(0..0, |fm| 1..fm.line_count);
+        let hidden_shown = hidden.end.saturating_sub(top.max(hidden.start));
+        let bottom = (top + area.height as usize + hidden_shown).min(self.buffer.line_count());
 
         let needs_rebuild = self.render_cache.dirty
             || self.render_cache.top != top
-            || self.render_cache.bottom != bottom;
+            || self.render_cache.bottom != bottom
+            || self.render_cache.cursor_row != self.buffer.cursor.row;
 
         if needs_rebuild {
-            let mut code_block_lang = self.code_block_lang_before_line(top);
+            let mut code_block = self.code_block_state_at(top);
+            let known_states = self.render_cache.highlight.line_states.len();
+            let mut new_states = Vec::new();
 
             let highlight_cursor = self.mode == Mode::Normal
                 || self.mode == Mode::Sidebar
                 || self.mode == Mode::Command
                 || self.mode == Mode::Backlinks
                 || self.mode == Mode::FinderOpen;
-            let show_line_nums = self.config.editor.line_numbers;
+            let show_line_nums = self.show_line_numbers();
             let rel_line_nums = self.config.editor.relative_line_numbers;
+            let list_mode = self.show_invisibles;
             let cursor_row = self.buffer.cursor.row;
+            let peer_cursor = self.collab_peer_cursor();
+            let linewise = self.mode == Mode::VisualLine;
+            let show_selection = self.mode.is_visual();
             let gutter_width = self.buffer.line_count().to_string().len().max(3);
+            let sign_column = self.decoration_sign_column();
+            let mut gutter_pad = if show_line_nums { gutter_width + 1 } else { 0 };
+            if sign_column {
+                gutter_pad += SIGN_WIDTH;
+            }
+            let mut decorations = self.line_decorations();
+            let mut previews: HashMap<usize, Vec<Line<'static>>> = (top..bottom)
+                .map(|i| (i, self.embed_preview_lines(i, gutter_pad)))
+                .filter(|(_, lines)| !lines.is_empty())
+                .collect();
+            let mut cursor_screen_row = 0;
+            let summary = folded.map(|fm| {
+                let style = Style::default().fg(Color::Rgb(120, 120, 150));
+                vec![
+                    Span::styled("▸ ", style),
+                    Span::styled(fm.summary(), style.add_modifier(Modifier::ITALIC)),
+                    Span::styled(format!("  ({} lines)", fm.line_count), style),
+                ]
+            });
 
             self.render_cache.lines = (top..bottom)
-                .map(|i| {
+                .flat_map(|i| {
                     let text = self.buffer.line_text(i).unwrap_or_default();
-                    let mut spans = self.render_markdown_line(&text, &mut code_block_lang);
                     let is_cursor_line = i == cursor_row;
+                    let selection = show_selection
+                        .then(|| self.buffer.selection_on_line(i, linewise))
+                        .flatten();
+                    let marks = decorations.remove(&i).unwrap_or_default();
+                    // Ranges are byte columns of the raw line, so a line they
+                    // underline is drawn unconcealed.
+                    let conceal = self.config.editor.conceal
+                        && !is_cursor_line
+                        && selection.is_none()
+                        && peer_cursor.is_none_or(|(peer_row, _)| peer_row != i)
+                        && !marks.iter().any(highlights_range);
+                    let in_code = code_block.is_some();
+                    let mut spans = self.render_markdown_line(&text, &mut code_block, conceal);
+                    spans = underline_decorations(spans, &text, &marks);
+                    let preview = if in_code || code_block.is_some() {
+                        Vec::new()
+                    } else {
+                        previews.remove(&i).unwrap_or_default()
+                    };
+                    if i + 1 >= known_states {
+                        new_states.push((i + 1, code_block.clone()));
+                    }
+                    if hidden.contains(&i) {
+                        return Vec::new();
+                    }
+                    if i == 0
+                        && let Some(summary) = &summary
+                    {
+                        spans = summary.clone();
+                    }
+
+                    if let Some((start, end)) = selection {
+                        let selection_style = Style::default().bg(Color::Rgb(60, 60, 110));
+                        spans = overlay_style(spans, start, end, selection_style);
+                        if start == end {
+                            spans.push(Span::styled(" ", selection_style));
+                        }
+                    }
+
+                    if let Some((peer_row, peer_col)) = peer_cursor
+                        && peer_row == i
+                    {
+                        let end = text[peer_col.min(text.len())..]
+                            .chars()
+                            .next()
+                            .map_or(peer_col, |ch| peer_col + ch.len_utf8());
+                        let peer_style = Style::default().fg(Color::Black).bg(Color::Magenta);
+                        spans = overlay_style(spans, peer_col, end, peer_style);
+                        if end == peer_col {
+                            spans.push(Span::styled(" ", peer_style));
+                        }
+                    }
+
+                    if list_mode {
+                        spans = show_invisibles(spans);
+                    }
+
+                    for mark in &marks {
+                        if let Some(note) = &mark.text {
+                            let style = Style::default()
+                                .fg(decoration_color(mark.kind))
+                                .add_modifier(Modifier::ITALIC);
+                            spans.push(Span::styled(format!("  {note}"), style));
+                        }
+                    }
 
                     if show_line_nums {
                         let mut num = i + 1;
@@ -58,55 +157,198 @@ impl App {
                         spans.insert(0, Span::styled(gutter_text, gutter_style));
                     }
 
+                    if sign_column {
+                        let sign = marks.iter().find_map(|mark| {
+                            let symbol = mark.gutter.as_deref()?.chars().next()?;
+                            Some(Span::styled(
+                                format!("{symbol} "),
+                                Style::default().fg(decoration_color(mark.kind)),
+                            ))
+                        });
+                        spans.insert(0, sign.unwrap_or_else(|| Span::raw(" ".repeat(SIGN_WIDTH))));
+                    }
+
                     if is_cursor_line && highlight_cursor {
                         for span in spans.iter_mut() {
                             span.style = span.style.bg(Color::Rgb(30, 30, 45));
                         }
                     }
 
-                    Line::from(spans)
+                    if i == cursor_row {
+                        cursor_screen_row += i - top - hidden_shown.min(i - top);
+                    } else if i < cursor_row {
+                        cursor_screen_row += preview.len();
+                    }
+                    let mut rows = vec![Line::from(spans)];
+                    rows.extend(preview);
+                    rows
                 })
                 .collect();
+            let line_states = &mut self.render_cache.highlight.line_states;
+            for (line, state) in new_states {
+                if line_states.len() == line {
+                    line_states.push(state);
+                }
+            }
             self.render_cache.top = top;
             self.render_cache.bottom = bottom;
+            self.render_cache.cursor_row = cursor_row;
+            self.render_cache.cursor_screen_row = cursor_screen_row;
             self.render_cache.dirty = false;
         }
 
-        let mut editor = Paragraph::new(self.render_cache.lines.clone());
+        // Previews above the cursor can push it below the editor; scroll the
+        // rendered rows just enough to keep it on screen.
+        let typewriter = self.buffer.viewport.typewriter;
+        let cache = &mut self.render_cache;
+        cache.scroll = (cache.cursor_screen_row + 1).saturating_sub(area.height as usize) as u16;
+        cache.top_pad = if typewriter {
+            (area.height as usize / 2).saturating_sub(cache.cursor_screen_row) as u16
+        } else {
+            0
+        };
+        let mut lines = vec![Line::default(); cache.top_pad as usize];
+        lines.extend(cache.lines.iter().cloned());
+        let mut editor = Paragraph::new(lines).scroll((cache.scroll, 0));
         if self.config.editor.soft_wrap {
             editor = editor.wrap(Wrap { trim: false });
         }
         frame.render_widget(editor, area);
     }
-    pub(crate) fn code_block_lang_before_line(&self, line_index: usize) -> Option<String> {
-        if line_index == 0 {
-            return None;
+    /// Plugin decorations on the active buffer by line, most severe first.
+    fn line_decorations(&self) -> HashMap<usize, Vec<Decoration>> {
+        let mut owners: Vec<_> = self
+            .plugin_decorations
+            .iter()
+            .filter(|(_, (path, _))| *path == self.buffer.path)
+            .collect();
+        owners.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+        let mut lines: HashMap<usize, Vec<Decoration>> = HashMap::new();
+        for (_, (_, marks)) in owners {
+            for mark in marks {
+                lines.entry(mark.line).or_default().push(mark.clone());
+            }
+        }
+        for marks in lines.values_mut() {
+            marks.sort_by_key(|mark| mark.kind);
+        }
+        lines
+    }
+
+    /// Whether a plugin put a symbol in the gutter of the active buffer,
+    /// which then gets a sign column left of the line numbers.
+    pub(crate) fn decoration_sign_column(&self) -> bool {
+        self.plugin_decorations
+            .values()
+            .filter(|(path, _)| *path == self.buffer.path)
+            .flat_map(|(_, marks)| marks)
+            .any(|mark| mark.gutter.is_some())
+    }
+
+    /// Read-only preview rows for the `![[embeds]]` on buffer line `row`,
+    /// indented under the line and dimmed.
+    fn embed_preview_lines(&mut self, row: usize, gutter_pad: usize) -> Vec<Line<'static>> {
+        let max_lines = self.config.editor.embed_max_lines;
+        if max_lines == 0 {
+            return Vec::new();
+        }
+        let Some(text) = self.buffer.line_text(row) else {
+            return Vec::new();
+        };
+
+        let bar_style = Style::default().fg(Color::DarkGray);
+        let bar = |spans: Vec<Span<'static>>| {
+            let mut line = vec![
+                Span::raw(" ".repeat(gutter_pad)),
+                Span::styled("  ▏ ", bar_style),
+            ];
+            line.extend(spans.into_iter().map(|span| {
+                let style = span.style.add_modifier(Modifier::DIM);
+                span.style(style)
+            }));
+            Line::from(line)
+        };
+        let note = |msg: String| {
+            bar(vec![Span::styled(
+                msg,
+                bar_style.add_modifier(Modifier::ITALIC),
+            )])
+        };
+
+        let mut out = Vec::new();
+        for embed in embed::embeds_in_line(&text) {
+            // `![[diagram.png]]` and friends are attachments, not notes.
+            if Path::new(&embed.target)
+                .extension()
+                .is_some_and(|ext| ext != "md")
+            {
+                continue;
+            }
+            let Some(path) = self.resolve_wikilink_target(&embed.target) else {
+                out.push(note(format!("embed: no note named \"{}\"", embed.target)));
+                continue;
+            };
+            let excerpt = self
+                .embeds
+                .lines(&path)
+                .and_then(|lines| embed::excerpt(lines, embed.heading.as_deref()));
+            let Some(excerpt) = excerpt else {
+                let anchor = embed.heading.unwrap_or_default();
+                let missing = match anchor.strip_prefix('^') {
+                    Some(id) => format!("block ^{id}"),
+                    None => format!("heading \"{anchor}\""),
+                };
+                out.push(note(format!("embed: no {missing} in {}", embed.target)));
+                continue;
+            };
+
+            let mut code_block = None;
+            for line in excerpt.iter().take(max_lines) {
+                out.push(bar(self.render_markdown_line(line, &mut code_block, true)));
+            }
+            if excerpt.len() > max_lines {
+                out.push(note(format!("… {} more lines", excerpt.len() - max_lines)));
+            }
+        }
+        out
+    }
+    /// Code block state at the start of `line`, extending the cache from the
+    /// last valid entry instead of re-scanning the buffer.
+    fn code_block_state_at(&mut self, line: usize) -> Option<CodeBlockState> {
+        let cache = &mut self.render_cache.highlight;
+        if cache.buffer_id != self.buffer.id {
+            cache.buffer_id = self.buffer.id;
+            cache.line_states.clear();
+            self.buffer.changed_from = None;
+        }
+        if let Some(row) = self.buffer.changed_from.take() {
+            cache.line_states.truncate(row + 1);
+        }
+        if cache.line_states.is_empty() {
+            cache.line_states.push(None);
         }
 
-        let mut code_block_lang = None;
-        for i in 0..line_index {
-            let text = self.buffer.line_text(i).unwrap_or_default();
+        while cache.line_states.len() <= line {
+            let idx = cache.line_states.len() - 1;
+            let mut state = cache.line_states[idx].clone();
+            let text = self.buffer.line_text(idx).unwrap_or_default();
             if let Some(lang) = parse_code_fence_language(&text) {
-                if code_block_lang.is_some() {
-                    code_block_lang = None;
-                } else {
-                    code_block_lang = Some(lang);
-                }
+                state = toggle_code_block(state, &lang);
+            } else if let Some(block) = state.as_mut() {
+                highlight_code_line(block, &text);
             }
+            cache.line_states.push(state);
         }
-        code_block_lang
+        cache.line_states[line].clone()
     }
     pub(crate) fn render_markdown_line(
         &self,
         text: &str,
-        code_block_lang: &mut Option<String>,
+        code_block: &mut Option<CodeBlockState>,
+        conceal: bool,
     ) -> Vec<Span<'static>> {
         if let Some(lang) = parse_code_fence_language(text) {
-            if code_block_lang.is_some() {
-                *code_block_lang = None;
-            } else {
-                *code_block_lang = Some(lang);
-            }
+            *code_block = toggle_code_block(code_block.take(), &lang);
             return vec![Span::styled(
                 text.to_string(),
                 Style::default()
@@ -116,32 +358,22 @@ impl App {
             )];
         }
 
-        if let Some(lang) = code_block_lang.as_deref() {
-            return self.render_code_block_line(text, lang);
+        if let Some(block) = code_block.as_mut() {
+            return self.render_code_block_line(text, block);
         }
 
         let base_style = self.base_markdown_style(text);
-        self.render_inline_markdown(text, base_style)
+        self.render_inline_markdown(text, base_style, conceal)
     }
-    pub(crate) fn render_code_block_line(&self, text: &str, language: &str) -> Vec<Span<'static>> {
-        let syntax = SYNTAX_SET
-            .find_syntax_by_token(language)
-            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-        let mut highlighter = HighlightLines::new(syntax, &SYNTECT_THEME);
-
-        let highlighted = highlighter.highlight_line(text, &SYNTAX_SET);
-        let Ok(tokens) = highlighted else {
-            return vec![Span::styled(
-                text.to_string(),
-                Style::default()
-                    .fg(Color::Rgb(200, 200, 200))
-                    .bg(Color::Rgb(18, 18, 28)),
-            )];
-        };
-
-        let spans: Vec<Span<'static>> = tokens
+    pub(crate) fn render_code_block_line(
+        &self,
+        text: &str,
+        block: &mut CodeBlockState,
+    ) -> Vec<Span<'static>> {
+        let spans: Vec<Span<'static>> = highlight_code_line(block, text)
+            .unwrap_or_default()
             .into_iter()
-            .map(|(style, segment)| Span::styled(segment.to_string(), syntect_to_ratatui(style)))
+            .map(|(style, segment)| Span::styled(segment, syntect_to_ratatui(style)))
             .collect();
 
         if spans.is_empty() {
@@ -188,25 +420,36 @@ impl App {
         &self,
         text: &str,
         base_style: Style,
+        conceal: bool,
     ) -> Vec<Span<'static>> {
         let mut spans: Vec<Span<'static>> = Vec::new();
         let mut cursor = 0;
+        let misspelled = self.misspelled_ranges(text);
 
         while cursor < text.len() {
             let next = next_markdown_token(text, cursor);
 
             let Some((start, end, kind)) = next else {
                 if cursor < text.len() {
-                    spans.push(Span::styled(text[cursor..].to_string(), base_style));
+                    spans.extend(spell_spans(
+                        text,
+                        cursor..text.len(),
+                        base_style,
+                        &misspelled,
+                    ));
                 }
                 break;
             };
 
             if start > cursor {
-                spans.push(Span::styled(text[cursor..start].to_string(), base_style));
+                spans.extend(spell_spans(text, cursor..start, base_style, &misspelled));
             }
 
+            let token = &text[start..end];
             let token_style = match kind {
+                TokenKind::WikiLink if !self.wikilink_resolves(token) => base_style
+                    .fg(Color::Rgb(170, 70, 70))
+                    .add_modifier(Modifier::UNDERLINED | Modifier::DIM),
                 TokenKind::WikiLink => base_style
                     .fg(Color::Rgb(0, 255, 136))
                     .add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
@@ -218,9 +461,23 @@ impl App {
                     .bg(Color::Rgb(32, 32, 48)),
                 TokenKind::Bold => base_style.add_modifier(Modifier::BOLD),
                 TokenKind::Italic => base_style.add_modifier(Modifier::ITALIC),
+                TokenKind::Strikethrough => base_style
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                TokenKind::Highlight => base_style.fg(Color::Black).bg(Color::Rgb(255, 214, 0)),
+                TokenKind::Citation => self.citation_style(token, base_style),
             };
 
-            spans.push(Span::styled(text[start..end].to_string(), token_style));
+            let shown = if conceal {
+                conceal_token(token, kind)
+            } else {
+                token.to_string()
+            };
+            if shown == token {
+                spans.extend(spell_spans(text, start..end, token_style, &misspelled));
+            } else {
+                spans.push(Span::styled(shown, token_style));
+            }
             cursor = end;
         }
 
@@ -230,4 +487,245 @@ impl App {
             spans
         }
     }
+
+    /// Citations stand out only with a bibliography configured; keys it
+    /// lacks are dimmed like dead wikilinks.
+    fn citation_style(&self, token: &str, base_style: Style) -> Style {
+        if self.config.bibliography_path().is_none() {
+            return base_style;
+        }
+        let key = token.trim_start_matches('@');
+        if self
+            .bibliography
+            .as_ref()
+            .is_some_and(|bib| bib.get(key).is_none())
+        {
+            return base_style
+                .fg(Color::Rgb(170, 70, 70))
+                .add_modifier(Modifier::DIM);
+        }
+        base_style
+            .fg(Color::Rgb(190, 150, 255))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// `[[#Heading]]` points into the current note, so only named targets
+    /// can be dead.
+    fn wikilink_resolves(&self, token: &str) -> bool {
+        parse_wikilink_target(token).is_none_or(|target| self.note_index.resolves(&target))
+    }
+}
+
+/// Spans for `text[range]`, with words in `misspelled` underlined in red.
+fn spell_spans(
+    text: &str,
+    range: std::ops::Range<usize>,
+    style: Style,
+    misspelled: &[std::ops::Range<usize>],
+) -> Vec<Span<'static>> {
+    let misspelled_style = style
+        .add_modifier(Modifier::UNDERLINED)
+        .underline_color(Color::Red);
+    let mut spans = Vec::new();
+    let mut at = range.start;
+
+    for word in misspelled
+        .iter()
+        .filter(|word| word.start >= range.start && word.end <= range.end)
+    {
+        if word.start > at {
+            spans.push(Span::styled(text[at..word.start].to_string(), style));
+        }
+        spans.push(Span::styled(
+            text[word.clone()].to_string(),
+            misspelled_style,
+        ));
+        at = word.end;
+    }
+    if at < range.end {
+        spans.push(Span::styled(text[at..range.end].to_string(), style));
+    }
+    spans
+}
+
+/// A fence line opens a block in `language`, or closes the open one.
+fn toggle_code_block(state: Option<CodeBlockState>, language: &str) -> Option<CodeBlockState> {
+    match state {
+        Some(_) => None,
+        None => Some(CodeBlockState::new(language)),
+    }
+}
+
+/// Highlight one line inside a code block, advancing `block` past it.
+fn highlight_code_line(
+    block: &mut CodeBlockState,
+    text: &str,
+) -> Option<Vec<(syntect::highlighting::Style, String)>> {
+    let mut highlighter =
+        HighlightLines::from_state(&SYNTECT_THEME, block.highlight.clone(), block.parse.clone());
+    // The newline-aware syntax set needs the terminator to close line-scoped
+    // contexts such as `//` comments before the next line starts.
+    let line = format!("{text}\n");
+    let tokens = highlighter
+        .highlight_line(&line, &SYNTAX_SET)
+        .ok()
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .map(|(style, segment)| (style, segment.trim_end_matches('\n').to_string()))
+                .filter(|(_, segment)| !segment.is_empty())
+                .collect()
+        });
+
+    let (highlight, parse) = highlighter.state();
+    block.highlight = highlight;
+    block.parse = parse;
+    tokens
+}
+
+/// Columns of the sign column: a symbol and a space.
+pub(crate) const SIGN_WIDTH: usize = 2;
+
+fn decoration_color(kind: DecorationKind) -> Color {
+    match kind {
+        DecorationKind::Error => Color::Red,
+        DecorationKind::Warning => Color::Yellow,
+        DecorationKind::Info => Color::Cyan,
+        DecorationKind::Hint => Color::DarkGray,
+    }
+}
+
+fn highlights_range(mark: &Decoration) -> bool {
+    mark.start.is_some() || mark.end.is_some()
+}
+
+/// Underline the ranges `marks` give on a line of `text`, clamped to it,
+/// in each mark's colour.
+fn underline_decorations(
+    mut spans: Vec<Span<'static>>,
+    text: &str,
+    marks: &[Decoration],
+) -> Vec<Span<'static>> {
+    for mark in marks.iter().rev().filter(|mark| highlights_range(mark)) {
+        let end = mark.end.unwrap_or(text.len()).min(text.len());
+        let start = mark.start.unwrap_or(0).min(end);
+        let style = Style::default()
+            .add_modifier(Modifier::UNDERLINED)
+            .underline_color(decoration_color(mark.kind));
+        spans = overlay_style(spans, start, end, style);
+    }
+    spans
+}
+
+/// Patch `style` onto the byte range `start..end` of a rendered line, splitting
+/// spans at the range boundaries. Offsets refer to the concatenated span text.
+pub(crate) fn overlay_style(
+    spans: Vec<Span<'static>>,
+    start: usize,
+    end: usize,
+    style: Style,
+) -> Vec<Span<'static>> {
+    if start >= end {
+        return spans;
+    }
+
+    let mut out = Vec::with_capacity(spans.len() + 2);
+    let mut offset = 0;
+
+    for span in spans {
+        let text = span.content.to_string();
+        let span_start = offset;
+        let span_end = offset + text.len();
+        offset = span_end;
+
+        if span_end <= start || span_start >= end {
+            out.push(span);
+            continue;
+        }
+
+        let cut_a = start.saturating_sub(span_start).min(text.len());
+        let cut_b = (end - span_start).min(text.len());
+        if !text.is_char_boundary(cut_a) || !text.is_char_boundary(cut_b) {
+            out.push(span);
+            continue;
+        }
+
+        if cut_a > 0 {
+            out.push(Span::styled(text[..cut_a].to_string(), span.style));
+        }
+        out.push(Span::styled(
+            text[cut_a..cut_b].to_string(),
+            span.style.patch(style),
+        ));
+        if cut_b < text.len() {
+            out.push(Span::styled(text[cut_b..].to_string(), span.style));
+        }
+    }
+
+    out
+}
+
+/// `:set list`: draw tabs, non-breaking spaces and trailing whitespace as
+/// dim glyphs. Runs after byte-offset overlays, since glyphs are wider in
+/// bytes than what they replace.
+fn show_invisibles(spans: Vec<Span<'static>>) -> Vec<Span<'static>> {
+    let content: String = spans.iter().map(|span| span.content.as_ref()).collect();
+    let trailing = content.trim_end().len();
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::DIM);
+
+    let mut out = Vec::with_capacity(spans.len());
+    let mut offset = 0;
+    for span in spans {
+        let mut plain = String::new();
+        for (idx, ch) in span.content.char_indices() {
+            let glyph = match ch {
+                '\t' => Some('→'),
+                '\u{a0}' => Some('␣'),
+                ' ' if offset + idx >= trailing => Some('·'),
+                _ => None,
+            };
+            match glyph {
+                Some(glyph) => {
+                    if !plain.is_empty() {
+                        out.push(Span::styled(std::mem::take(&mut plain), span.style));
+                    }
+                    out.push(Span::styled(glyph.to_string(), span.style.patch(dim)));
+                }
+                None => plain.push(ch),
+            }
+        }
+        offset += span.content.len();
+        if !plain.is_empty() {
+            out.push(Span::styled(plain, span.style));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::test_util::{app_in, temp_dir};
+
+    #[test]
+    fn test_strikethrough_and_highlight_render() {
+        let vault = temp_dir();
+        let app = app_in(vault.path());
+        let text = "a ~~old~~ b ==new== c ~~ =";
+
+        let spans = app.render_inline_markdown(text, Style::default(), true);
+        let shown: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(shown, ["a ", "old", " b ", "new", " c ~~ ="]);
+        assert_eq!(spans[1].style.fg, Some(Color::DarkGray));
+        assert!(spans[1].style.add_modifier.contains(Modifier::CROSSED_OUT));
+        assert_eq!(spans[3].style.bg, Some(Color::Rgb(255, 214, 0)));
+        assert_eq!(spans[4].style, Style::default());
+
+        let spans = app.render_inline_markdown(text, Style::default(), false);
+        let shown: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(shown, ["a ", "~~old~~", " b ", "==new==", " c ~~ ="]);
+    }
 }
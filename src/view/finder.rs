@@ -23,10 +23,29 @@ impl App {
 
         let input = Paragraph::new(self.finder_query.clone()).block(
             Block::default()
-                .title(if self.finder_mode == FinderMode::Files {
-                    " Finder (Files) "
-                } else {
-                    " Search (Content) "
+                .title(match self.finder_mode {
+                    FinderMode::Files => " Finder (Files) ".to_string(),
+                    FinderMode::Content => " Search (Content) ".to_string(),
+                    FinderMode::Orphans => {
+                        format!(" Orphans ({} unlinked notes) ", self.finder_orphans.len())
+                    }
+                    FinderMode::Recent => " Recent notes ".to_string(),
+                    FinderMode::LinkChoices => format!(
+                        " Which note? ({} share the name) ",
+                        self.finder_link_choices.len()
+                    ),
+                    FinderMode::BrokenLinks => {
+                        format!(" Broken links ({}) ", self.finder_broken.len())
+                    }
+                    FinderMode::Stale => format!(
+                        " Stale notes ({} unchanged for {}+ days) ",
+                        self.finder_stale.len(),
+                        self.finder_stale_days
+                    ),
+                    FinderMode::Section => match &self.finder_section {
+                        Some((Some(title), _)) => format!(" Search (Section: {title}) "),
+                        _ => " Search (Section) ".to_string(),
+                    },
                 })
                 .borders(Borders::ALL)
                 .style(Style::default().bg(Color::Rgb(15, 15, 24))),
@@ -43,7 +62,11 @@ impl App {
                 .iter()
                 .enumerate()
                 .map(|(idx, item)| {
-                    let label = item.preview.clone();
+                    let label = if self.finder_marked.contains(&item.path) {
+                        format!("● {}", item.preview)
+                    } else {
+                        item.preview.clone()
+                    };
                     if idx == self.finder_selected {
                         Line::from(Span::styled(
                             format!("> {label}"),
@@ -66,7 +89,27 @@ impl App {
         );
         frame.render_widget(result_block, chunks[1]);
 
-        let footer = Paragraph::new(" Enter: open  Esc: close  j/k: move ").block(
+        let mut hint = if self.finder_marked.is_empty() {
+            " Enter: open  Ctrl+L: insert link  Tab: mark  Esc: close  j/k: move ".to_string()
+        } else {
+            format!(
+                " {} marked  Tab: mark  Enter: bulk action  Esc: close ",
+                self.finder_marked.len()
+            )
+        };
+        if let Some(err) = self
+            .finder_query_error
+            .as_ref()
+            .filter(|_| self.finder_mode == FinderMode::Content)
+        {
+            hint = format!(" {err} ");
+        } else if self.finder_mode == FinderMode::Content && self.finder_unreadable > 0 {
+            hint.push_str(&format!(
+                " {} unreadable files skipped, see :problems ",
+                self.finder_unreadable
+            ));
+        }
+        let footer = Paragraph::new(hint).block(
             Block::default().borders(Borders::ALL).style(
                 Style::default()
                     .bg(Color::Rgb(15, 15, 24))
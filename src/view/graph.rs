@@ -0,0 +1,74 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_graph(&self, frame: &mut Frame) {
+        let Some(view) = self.graph_view.as_ref() else {
+            return;
+        };
+        let area = centered_rect(60, 60, frame.area());
+        let rows = usize::from(area.height.saturating_sub(4)).max(1);
+        let first = view.selected.saturating_sub(rows - 1);
+        let vault = self.config.vault_path();
+
+        let mut lines: Vec<Line> = view
+            .rows
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows)
+            .map(|(idx, row)| {
+                let rel = row.path.strip_prefix(&vault).unwrap_or(&row.path);
+                let name = rel.with_extension("").to_string_lossy().to_string();
+                let style = if idx == view.selected {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else if row.depth == 0 {
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("  {}", row.prefix),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(name, style),
+                ])
+            })
+            .collect();
+        if view.rows.len() == 1 {
+            lines.push(Line::from(Span::styled(
+                "  no links to or from this note",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            "  Enter open, l recentre, 1/2 hops, Esc close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    " Graph: {} notes within {} ",
+                    view.rows.len() - 1,
+                    if view.depth == 1 { "1 link" } else { "2 links" }
+                ))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_kanban(&self, frame: &mut Frame) {
+        let Some(view) = self.kanban_view.as_ref() else {
+            return;
+        };
+        let area = centered_rect(90, 80, frame.area());
+        frame.render_widget(Clear, area);
+        let name = self
+            .buffer
+            .path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map_or_else(
+                || "note".to_string(),
+                |stem| stem.to_string_lossy().to_string(),
+            );
+        let board = Block::default()
+            .title(format!(" Kanban: {name} "))
+            .title_bottom(" h/l column, j/k card, H/L move card, Enter jump, Esc close ")
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::Rgb(15, 15, 24)));
+        let inner = board.inner(area);
+        frame.render_widget(board, area);
+
+        let count = u32::try_from(view.columns.len()).unwrap_or(u32::MAX);
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(view.columns.iter().map(|_| Constraint::Ratio(1, count)))
+            .split(inner);
+
+        for (idx, (column, chunk)) in view.columns.iter().zip(chunks.iter()).enumerate() {
+            let active = idx == view.column;
+            let rows = usize::from(chunk.height.saturating_sub(2)).max(1);
+            let first = if active {
+                view.card.saturating_sub(rows - 1)
+            } else {
+                0
+            };
+            let lines: Vec<Line> = column
+                .cards
+                .iter()
+                .enumerate()
+                .skip(first)
+                .map(|(card_idx, card)| {
+                    let mut style = if card.done == Some(true) {
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::CROSSED_OUT)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    if active && card_idx == view.card {
+                        style = style
+                            .fg(Color::Black)
+                            .bg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD);
+                    }
+                    Line::from(Span::styled(format!(" {}", card.title), style))
+                })
+                .collect();
+            let border = if active {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let widget = Paragraph::new(lines).block(
+                Block::default()
+                    .title(format!(" {} ({}) ", column.title, column.cards.len()))
+                    .borders(Borders::ALL)
+                    .border_style(border),
+            );
+            frame.render_widget(widget, *chunk);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::app::App;
+use crate::model::mode::Mode;
+use crate::view::render_completion_popup;
+use ratatui::{Frame, layout::Position, style::Color};
+
+impl App {
+    /// Notes, or headings after `#`, for the `[[link` being typed.
+    pub(crate) fn render_link_picker(&self, frame: &mut Frame, cursor: Option<Position>) {
+        let (Some(picker), Some(cursor)) = (self.link_picker.as_ref(), cursor) else {
+            return;
+        };
+        if self.mode != Mode::Insert {
+            return;
+        }
+        render_completion_popup(
+            frame,
+            cursor,
+            " Link ",
+            &picker.candidates,
+            picker.selected,
+            Color::Cyan,
+        );
+    }
+}
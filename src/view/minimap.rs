@@ -0,0 +1,96 @@
+use crate::app::{App, MinimapCache};
+use crate::model::minimap::{Minimap, Row};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+/// Columns taken by the minimap, its border included.
+const MINIMAP_WIDTH: u16 = 14;
+
+impl App {
+    /// Draw the minimap at the right edge of `area` when it is on and there
+    /// is room, returning what is left for the editor.
+    pub(crate) fn render_minimap(&mut self, frame: &mut Frame, area: Rect) -> Rect {
+        if !self.minimap_visible || area.width < MINIMAP_WIDTH * 3 {
+            self.minimap = None;
+            return area;
+        }
+        let editor = Rect {
+            width: area.width - MINIMAP_WIDTH,
+            ..area
+        };
+        let column = Rect {
+            x: editor.x + editor.width,
+            width: MINIMAP_WIDTH,
+            ..area
+        };
+        let block = Block::default()
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(Color::Rgb(50, 50, 70)));
+        let inner = block.inner(column);
+        frame.render_widget(block, column);
+
+        // Rebuilt before the editor consumes `changed_from` this frame.
+        let stale = self.minimap.as_ref().is_none_or(|cache| {
+            cache.buffer_id != self.buffer.id
+                || cache.area != inner
+                || self.buffer.changed_from.is_some()
+        });
+        if stale {
+            let lines = self.buffer_lines();
+            self.minimap = Some(MinimapCache {
+                buffer_id: self.buffer.id,
+                area: inner,
+                map: Minimap::build(
+                    &lines,
+                    usize::from(inner.height),
+                    usize::from(inner.width),
+                    self.config.editor.text_width,
+                ),
+            });
+        }
+        let Some(cache) = &self.minimap else {
+            return editor;
+        };
+
+        let viewport = &self.buffer.viewport;
+        let visible = cache
+            .map
+            .viewport_rows(viewport.top_line, usize::from(viewport.height));
+        let width = usize::from(inner.width);
+        let lines: Vec<Line> = cache
+            .map
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                let bg = if visible.contains(&idx) {
+                    Color::Rgb(40, 40, 62)
+                } else {
+                    Color::Reset
+                };
+                let span = match row {
+                    Row::Heading { level, title } => {
+                        let text: String = title.chars().take(width).collect();
+                        let mut style = Style::default().fg(Color::Magenta);
+                        if *level <= 2 {
+                            style = style.add_modifier(Modifier::BOLD);
+                        }
+                        Span::styled(format!("{text:<width$}"), style.bg(bg))
+                    }
+                    Row::Text { fill } => Span::styled(
+                        format!("{:<width$}", "─".repeat(*fill)),
+                        Style::default().fg(Color::Rgb(90, 90, 110)).bg(bg),
+                    ),
+                };
+                Line::from(span)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+        editor
+    }
+}
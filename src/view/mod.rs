@@ -1,19 +1,63 @@
 pub mod backlinks;
+pub mod citations;
 pub mod command;
+pub mod compat;
+pub mod diagram;
 pub mod editor;
 pub mod finder;
+pub mod graph;
+pub mod kanban;
+pub mod link_picker;
+pub mod minimap;
+pub mod outline;
+pub mod plugin_browser;
+pub mod plugin_completion;
+pub mod plugin_logs;
+pub mod preview;
+pub mod properties;
 pub mod sidebar;
+pub mod spell;
+pub mod stats;
 pub mod statusbar;
+pub mod tasks;
+pub mod yanks;
 
 use crate::app::App;
 use crate::model::mode::Mode;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 impl App {
     pub fn view(&mut self, frame: &mut Frame) {
+        self.render_frame(frame);
+        if self.compat {
+            compat::downgrade(frame.buffer_mut());
+        }
+    }
+
+    fn render_frame(&mut self, frame: &mut Frame) {
+        if self.zen {
+            let area = frame.area();
+            let width = (self.config.editor.zen_width as u16).clamp(1, area.width.max(1));
+            let column = Rect {
+                x: area.x + (area.width - width) / 2,
+                width,
+                ..area
+            };
+            self.render_editor(frame, column);
+            let cursor = self.place_editor_cursor(frame, column);
+            self.render_citation_completion(frame, cursor);
+            self.render_link_picker(frame, cursor);
+            self.render_plugin_completion(frame, cursor);
+            self.render_overlays(frame);
+            return;
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -25,7 +69,8 @@ impl App {
 
         self.render_tab_bar(frame, chunks[0]);
 
-        let editor_area = if self.sidebar_visible && self.backlinks_visible {
+        let right_panel = self.backlinks_visible || self.outline_visible;
+        let editor_area = if self.sidebar_visible && right_panel {
             let body = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([
@@ -36,9 +81,9 @@ impl App {
                 .split(chunks[1]);
 
             self.render_sidebar(frame, body[0]);
-            self.render_editor(frame, body[1]);
-            self.render_backlinks_panel(frame, body[2]);
-            body[1]
+            let focused = self.render_editor_panes(frame, body[1]);
+            self.render_right_panels(frame, body[2]);
+            focused
         } else if self.sidebar_visible {
             let body = Layout::default()
                 .direction(Direction::Horizontal)
@@ -46,40 +91,118 @@ impl App {
                 .split(chunks[1]);
 
             self.render_sidebar(frame, body[0]);
-            self.render_editor(frame, body[1]);
-            body[1]
-        } else if self.backlinks_visible {
+            self.render_editor_panes(frame, body[1])
+        } else if right_panel {
             let body = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Min(1), Constraint::Percentage(25)])
                 .split(chunks[1]);
 
-            self.render_editor(frame, body[0]);
-            self.render_backlinks_panel(frame, body[1]);
-            body[0]
+            let focused = self.render_editor_panes(frame, body[0]);
+            self.render_right_panels(frame, body[1]);
+            focused
         } else {
-            self.render_editor(frame, chunks[1]);
-            chunks[1]
+            self.render_editor_panes(frame, chunks[1])
         };
 
+        let cursor = self.place_editor_cursor(frame, editor_area);
+        self.render_status_bar(frame, chunks[2]);
+        self.render_citation_completion(frame, cursor);
+        self.render_link_picker(frame, cursor);
+        self.render_plugin_completion(frame, cursor);
+        self.render_overlays(frame);
+    }
+
+    /// The editor, or both panes of a split. Returns the focused pane.
+    fn render_editor_panes(&mut self, frame: &mut Frame, area: Rect) -> Rect {
+        let area = self.render_minimap(frame, area);
+        let Some(mut split) = self.split.take() else {
+            self.render_editor(frame, area);
+            return area;
+        };
+
+        let (top_height, _) = split_heights(area.height);
+        let panes = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(top_height),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ])
+            .split(area);
+        let (focused, unfocused) = if split.focus_top {
+            (panes[0], panes[2])
+        } else {
+            (panes[2], panes[0])
+        };
+
+        // Draw the parked view first so the render cache ends up holding
+        // the focused pane, which the cursor placement reads.
+        split.other.swap_with(&mut self.buffer);
+        self.render_editor(frame, unfocused);
+        split.other.swap_with(&mut self.buffer);
+        self.render_editor(frame, focused);
+
+        let rule = "─".repeat(panes[1].width as usize);
+        frame.render_widget(
+            Paragraph::new(rule).style(Style::default().fg(Color::DarkGray)),
+            panes[1],
+        );
+        self.split = Some(split);
+        focused
+    }
+
+    /// Put the terminal cursor on the buffer cursor, returning where that
+    /// is when it is on screen.
+    fn place_editor_cursor(&self, frame: &mut Frame, editor_area: Rect) -> Option<Position> {
         let mut gutter_offset = 0;
-        if self.config.editor.line_numbers {
+        if self.show_line_numbers() {
             gutter_offset = self.buffer.line_count().to_string().len().max(3) as u16 + 1;
         }
+        if self.decoration_sign_column() {
+            gutter_offset += editor::SIGN_WIDTH as u16;
+        }
 
+        let cache = &self.render_cache;
         let cursor_x = self.buffer.cursor.col as u16 + editor_area.x + gutter_offset;
-        let cursor_y =
-            (self.buffer.cursor.row - self.buffer.viewport.top_line) as u16 + editor_area.y;
-        if cursor_y < editor_area.y + editor_area.height {
-            frame.set_cursor_position((cursor_x, cursor_y));
+        let cursor_y = (cache.cursor_screen_row as u16 + cache.top_pad)
+            .saturating_sub(cache.scroll)
+            + editor_area.y;
+        if cursor_y >= editor_area.y + editor_area.height {
+            return None;
         }
+        frame.set_cursor_position((cursor_x, cursor_y));
+        Some(Position::new(cursor_x, cursor_y))
+    }
 
-        self.render_status_bar(frame, chunks[2]);
-
+    /// Popups drawn over whatever layout is active.
+    fn render_overlays(&mut self, frame: &mut Frame) {
         if self.mode == Mode::FinderOpen {
             self.render_finder_overlay(frame);
         } else if self.mode == Mode::Command {
             self.render_command_overlay(frame);
+        } else if self.mode == Mode::YankPicker {
+            self.render_yank_picker(frame);
+        } else if self.mode == Mode::SpellSuggest {
+            self.render_spell_popup(frame);
+        } else if self.mode == Mode::LinkPreview {
+            self.render_link_preview(frame);
+        } else if self.mode == Mode::Graph {
+            self.render_graph(frame);
+        } else if self.mode == Mode::Stats {
+            self.render_stats(frame);
+        } else if self.mode == Mode::Tasks {
+            self.render_tasks(frame);
+        } else if self.mode == Mode::Kanban {
+            self.render_kanban(frame);
+        } else if self.mode == Mode::Properties {
+            self.render_properties(frame);
+        } else if self.mode == Mode::DiagramPreview {
+            self.render_diagram_popup(frame);
+        } else if self.mode == Mode::PluginLogs {
+            self.render_plugin_logs(frame);
+        } else if self.mode == Mode::PluginBrowser {
+            self.render_plugin_browser(frame);
         }
 
         if let Some(ch) = self.pending_key
@@ -90,4 +213,88 @@ impl App {
             self.render_which_key(frame);
         }
     }
+
+    /// Backlinks and outline share the right column, stacked when both are open.
+    fn render_right_panels(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+        match (self.outline_visible, self.backlinks_visible) {
+            (true, true) => {
+                let halves = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                self.render_outline_panel(frame, halves[0]);
+                self.render_backlinks_panel(frame, halves[1]);
+            }
+            (true, false) => self.render_outline_panel(frame, area),
+            _ => self.render_backlinks_panel(frame, area),
+        }
+    }
+}
+
+/// Rows for the top and bottom panes of a split `height` rows tall, leaving
+/// one for the rule between them.
+pub(crate) fn split_heights(height: u16) -> (u16, u16) {
+    let top = height.saturating_sub(1) / 2;
+    (top, height.saturating_sub(1).saturating_sub(top))
+}
+
+/// Insert-mode completions in a box under the cursor (above it near the
+/// bottom of the screen): each `(text, detail)` with the text in `accent`.
+pub(crate) fn render_completion_popup(
+    frame: &mut Frame,
+    cursor: Position,
+    title: &str,
+    candidates: &[(String, String)],
+    selected: usize,
+    accent: Color,
+) {
+    let screen = frame.area();
+    let width = 60.min(screen.width);
+    let height = (candidates.len() as u16 + 2).min(screen.height);
+    let below = cursor.y + 1;
+    let y = if below + height <= screen.height {
+        below
+    } else {
+        cursor.y.saturating_sub(height)
+    };
+    let x = cursor.x.min(screen.width.saturating_sub(width));
+    let area = Rect::new(x, y, width, height);
+
+    let text_width = usize::from(width.saturating_sub(4));
+    let lines: Vec<Line> = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, (text, detail))| {
+            let (text_style, detail_style) = if idx == selected {
+                let style = Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD);
+                (style, style)
+            } else {
+                (
+                    Style::default().fg(accent),
+                    Style::default().fg(Color::DarkGray),
+                )
+            };
+            let detail: String = detail
+                .chars()
+                .take(text_width.saturating_sub(text.chars().count() + 2))
+                .collect();
+            Line::from(vec![
+                Span::styled(format!(" {text}"), text_style),
+                Span::styled(format!("  {detail}"), detail_style),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .title_bottom(" Tab next, Enter insert ")
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+    );
+    frame.render_widget(widget, area);
 }
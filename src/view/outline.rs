@@ -0,0 +1,57 @@
+use crate::app::App;
+use crate::model::mode::Mode;
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_outline_panel(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+        let headings = self.outline_headings();
+        let focused = self.mode == Mode::Outline;
+
+        let mut lines: Vec<Line> = if headings.is_empty() {
+            vec![Line::from(Span::styled(
+                "No headings",
+                Style::default().fg(Color::DarkGray),
+            ))]
+        } else {
+            headings
+                .iter()
+                .enumerate()
+                .map(|(idx, heading)| {
+                    let indent = "  ".repeat(heading.level.saturating_sub(1));
+                    let label = format!("{indent}{}", heading.title);
+                    let style = if focused && idx == self.outline_selected {
+                        Style::default().fg(Color::Black).bg(Color::Cyan)
+                    } else if heading.level == 1 {
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::Gray)
+                    };
+                    Line::from(Span::styled(label, style))
+                })
+                .collect()
+        };
+
+        if focused {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "J/K move  H/L level",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let panel = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Outline ")
+                .borders(Borders::LEFT)
+                .style(Style::default().bg(Color::Rgb(12, 12, 18))),
+        );
+        frame.render_widget(panel, area);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    /// The registry's plugins matching the query, one per line with its
+    /// description; ones already configured are marked.
+    pub(crate) fn render_plugin_browser(&self, frame: &mut Frame) {
+        let Some(browser) = self.plugin_browser.as_ref() else {
+            return;
+        };
+        let area = centered_rect(70, 60, frame.area());
+        let rows = usize::from(area.height.saturating_sub(5)).max(1);
+        let first = browser.selected.saturating_sub(rows - 1);
+        let name_width = browser
+            .candidates
+            .iter()
+            .map(|entry| entry.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("  > ", Style::default().fg(Color::Cyan)),
+                Span::raw(browser.query.clone()),
+            ]),
+            Line::from(Span::raw("")),
+        ];
+        for (idx, entry) in browser.candidates.iter().enumerate().skip(first).take(rows) {
+            let installed = self
+                .config
+                .plugins
+                .iter()
+                .any(|plugin| plugin.repo.as_deref() == Some(entry.repo.as_str()));
+            let selected = idx == browser.selected;
+            let name_style = if selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Cyan)
+            };
+            let mut spans = vec![
+                Span::raw("  "),
+                Span::styled(format!("{:<name_width$}", entry.name), name_style),
+                Span::raw("  "),
+                Span::raw(entry.description.clone()),
+            ];
+            if installed {
+                spans.push(Span::styled(
+                    "  (installed)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        if browser.candidates.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  no plugins match",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    " Plugins ({}/{}) ",
+                    browser.candidates.len(),
+                    browser.entries.len()
+                ))
+                .title_bottom(" type to filter, Up/Down select, Enter install, Esc close ")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::app::App;
+use crate::model::mode::Mode;
+use crate::view::render_completion_popup;
+use ratatui::{Frame, layout::Position, style::Color};
+
+impl App {
+    /// Items from plugins for the text after one of their triggers.
+    pub(crate) fn render_plugin_completion(&self, frame: &mut Frame, cursor: Option<Position>) {
+        let (Some(completion), Some(cursor)) = (self.plugin_completion.as_ref(), cursor) else {
+            return;
+        };
+        if self.mode != Mode::Insert || completion.candidates.is_empty() {
+            return;
+        }
+        let candidates: Vec<(String, String)> = completion
+            .candidates
+            .iter()
+            .map(|item| (item.label.clone(), item.detail.clone().unwrap_or_default()))
+            .collect();
+        render_completion_popup(
+            frame,
+            cursor,
+            " Completions ",
+            &candidates,
+            completion.selected,
+            Color::Rgb(255, 200, 120),
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    /// The newest log lines that fit, or older ones once scrolled up.
+    pub(crate) fn render_plugin_logs(&self, frame: &mut Frame) {
+        let Some(view) = self.plugin_logs.as_ref() else {
+            return;
+        };
+        let area = centered_rect(80, 60, frame.area());
+        let height = area.height.saturating_sub(2) as usize;
+        let end = view.lines.len().saturating_sub(view.back);
+        let start = end.saturating_sub(height);
+
+        let mut lines: Vec<Line> = view.lines[start..end]
+            .iter()
+            .map(|line| {
+                let color = match line.split_whitespace().next() {
+                    Some("error") => Color::Red,
+                    Some("warn") => Color::Yellow,
+                    Some("debug") => Color::DarkGray,
+                    _ => Color::Reset,
+                };
+                Line::from(Span::styled(line.clone(), Style::default().fg(color)))
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "(nothing logged yet)",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" Logs: {} ", view.plugin))
+                .title_bottom(" j/k scroll, g/G oldest/newest, r refresh, Esc close ")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
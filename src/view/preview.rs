@@ -0,0 +1,41 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_link_preview(&self, frame: &mut Frame) {
+        let Some(preview) = self.link_preview.as_ref() else {
+            return;
+        };
+        let area = centered_rect(60, 50, frame.area());
+
+        // Code fences are tracked from the top so scrolling keeps highlighting.
+        let mut code_block = None;
+        let mut lines: Vec<Line> = preview
+            .lines
+            .iter()
+            .map(|line| Line::from(self.render_markdown_line(line, &mut code_block, true)))
+            .skip(preview.scroll)
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "(empty)",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" {} ", preview.title))
+                .title_bottom(" j/k scroll, Enter open, Esc close ")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
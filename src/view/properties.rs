@@ -0,0 +1,83 @@
+use crate::app::{App, centered_rect};
+use crate::model::frontmatter::FrontmatterValue;
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_properties(&self, frame: &mut Frame) {
+        let Some(view) = self.properties_view.as_ref() else {
+            return;
+        };
+        let area = centered_rect(60, 50, frame.area());
+        let fields = self.buffer.frontmatter().unwrap_or_default().fields;
+        let width = fields
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines: Vec<Line> = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, (key, value))| {
+                let value = match value {
+                    FrontmatterValue::Scalar(scalar) => scalar.clone(),
+                    FrontmatterValue::List(items) => format!("[{}]", items.join(", ")),
+                    FrontmatterValue::Raw(_) => "… (nested)".to_string(),
+                };
+                let (key_style, value_style) = if idx == view.selected && view.input.is_none() {
+                    let style = Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD);
+                    (style, style)
+                } else {
+                    (
+                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(Color::Cyan),
+                    )
+                };
+                Line::from(vec![
+                    Span::styled(format!(" {key:<width$}  "), key_style),
+                    Span::styled(value, value_style),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                " (no frontmatter yet: a adds a field)",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        if let Some((field, text)) = view.input.as_ref() {
+            let label = match field {
+                Some(name) => format!(" {name}: "),
+                None => " new (key: value) ".to_string(),
+            };
+            lines.push(Line::from(Span::raw("")));
+            lines.push(Line::from(vec![
+                Span::styled(label, Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{text}▏")),
+            ]));
+        }
+
+        let footer = if view.input.is_some() {
+            " Enter save, Esc cancel; lists take a, b, c "
+        } else {
+            " Enter edit, a add, d delete, Esc close "
+        };
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Properties ")
+                .title_bottom(footer)
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
@@ -8,7 +8,7 @@ use ratatui::{
 
 impl App {
     pub(crate) fn render_sidebar(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
-        let lines: Vec<Line> = self
+        let mut lines: Vec<Line> = self
             .file_tree
             .nodes
             .iter()
@@ -24,18 +24,35 @@ impl App {
                 } else {
                     "  "
                 };
-                let content = format!("{indent}{prefix}{}", node.name);
+                let mut content = format!("{indent}{prefix}{}", node.name);
+                if node.problem.is_some() {
+                    content.push_str(" ⚠");
+                }
 
                 if idx == self.file_tree.selected {
                     Line::from(Span::styled(
                         content,
                         Style::default().fg(Color::Black).bg(Color::Cyan),
                     ))
+                } else if node.problem.is_some() {
+                    Line::from(Span::styled(
+                        content,
+                        Style::default().fg(Color::Rgb(200, 90, 90)),
+                    ))
                 } else {
                     Line::from(Span::styled(content, Style::default().fg(Color::Gray)))
                 }
             })
             .collect();
+        if let Some(key) = self.file_tree.grouping.key() {
+            lines.insert(
+                0,
+                Line::from(Span::styled(
+                    format!("by {key}"),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            );
+        }
 
         frame.render_widget(
             Paragraph::new(lines).style(Style::default().bg(Color::Rgb(12, 12, 18))),
@@ -0,0 +1,47 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_spell_popup(&self, frame: &mut Frame) {
+        let Some(popup) = self.spell_popup.as_ref() else {
+            return;
+        };
+        let area = centered_rect(40, 40, frame.area());
+
+        let mut lines: Vec<Line> = popup
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(idx, suggestion)| {
+                let style = if idx == popup.selected {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                Line::from(Span::styled(format!("  {}  {suggestion}", idx + 1), style))
+            })
+            .collect();
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            "  1-9/Enter replace, Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" Spelling: {} ", popup.word))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
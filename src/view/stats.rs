@@ -0,0 +1,50 @@
+use crate::app::{App, centered_rect};
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_stats(&self, frame: &mut Frame) {
+        let Some(view) = self.stats_view.as_ref() else {
+            return;
+        };
+        let area = centered_rect(40, 40, frame.area());
+        let width = view
+            .rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = view
+            .rows
+            .iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {label:<width$}  "),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        value.clone(),
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ])
+            })
+            .collect();
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" Stats: {} ", view.title))
+                .title_bottom(" Esc close ")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::app::{App, FinderMode, same_file_path};
+use crate::model::buffer::TextCounts;
 use crate::model::mode::Mode;
+use crate::update::workflow::buffer_workflow_state;
 use ratatui::{
     Frame,
     style::{Color, Modifier, Style},
@@ -15,6 +17,7 @@ impl App {
             Mode::Normal => (Color::Black, Color::Cyan),
             Mode::Insert => (Color::Black, Color::Magenta),
             Mode::Command => (Color::Black, Color::Yellow),
+            Mode::Visual | Mode::VisualLine => (Color::Black, Color::LightBlue),
             _ => (Color::White, Color::Rgb(80, 40, 120)),
         };
 
@@ -32,12 +35,19 @@ impl App {
             .as_ref()
             .and_then(|p| p.file_name())
             .map(|n| n.to_string_lossy().to_string())
+            .or_else(|| self.buffer.title.clone())
             .unwrap_or_else(|| "[scratch]".to_string());
 
         let dirty_marker = if self.buffer.dirty { "  ●" } else { "" };
+        let readonly_marker = if self.buffer_is_readonly() {
+            " [RO]"
+        } else {
+            ""
+        };
+        let safe_marker = if self.config.safe_mode { " [SAFE]" } else { "" };
 
         let file_info = Span::styled(
-            format!("  {file_name}{dirty_marker} "),
+            format!("  {file_name}{dirty_marker}{readonly_marker}{safe_marker} "),
             Style::default().fg(Color::Rgb(200, 200, 220)),
         );
 
@@ -47,14 +57,34 @@ impl App {
                 suffix.push_str(&format!(" | new: {}", self.file_tree.create_input))
             }
             Mode::FinderOpen => {
-                let label = if self.finder_mode == FinderMode::Files {
-                    "find"
-                } else {
-                    "search"
+                let label = match self.finder_mode {
+                    FinderMode::Files => "find",
+                    FinderMode::Content => "search",
+                    FinderMode::Section => "section",
+                    FinderMode::Orphans => "orphans",
+                    FinderMode::Recent => "recent",
+                    FinderMode::Stale => "stale",
+                    FinderMode::BrokenLinks => "broken links",
+                    FinderMode::LinkChoices => "which note",
                 };
                 suffix.push_str(&format!(" | {label}: {}", self.finder_query));
             }
-            Mode::Command => suffix.push_str(&format!(" | :{}", self.command_input)),
+            Mode::Command => match &self.plugin_prompt {
+                Some(prompt) => {
+                    suffix.push_str(&format!(" | {} {}", prompt.question, self.command_input))
+                }
+                None => suffix.push_str(&format!(" | :{}", self.command_input)),
+            },
+            Mode::Visual | Mode::VisualLine => {
+                let linewise = self.mode == Mode::VisualLine;
+                if let Some(text) = self.buffer.selected_text(linewise) {
+                    let counts = TextCounts::of(&text);
+                    suffix.push_str(&format!(
+                        " | sel: {} w, {} ch, {} ln",
+                        counts.words, counts.chars, counts.lines
+                    ));
+                }
+            }
             Mode::ConfirmCreate => {
                 if let Some(path) = &self.pending_create_path {
                     let name = path
@@ -66,9 +96,35 @@ impl App {
                     suffix.push_str(" | create note? (y/n)");
                 }
             }
-            _ => {}
+            Mode::Confirm => {
+                if let Some(pending) = &self.pending_confirm {
+                    suffix.push_str(&format!(" | {} (y/n)", pending.prompt));
+                }
+            }
+            _ => {
+                if let Some(message) = self.notifications.back()
+                    && self
+                        .notification_until
+                        .is_some_and(|until| std::time::Instant::now() < until)
+                {
+                    suffix.push_str(&format!(" | {message}"));
+                }
+            }
         };
 
+        if let Some(session) = self.collab.as_ref() {
+            let peer = session.peer.as_deref().unwrap_or("waiting");
+            suffix.push_str(&format!(" | collab: {peer}"));
+        }
+
+        if let Some(activity) = self.activities.indicator(std::time::Instant::now()) {
+            suffix.push_str(&format!(" | {activity}"));
+        }
+
+        if let Some(sync) = self.sync_indicator() {
+            suffix.push_str(&format!(" | {sync}"));
+        }
+
         if self.quit_confirm_armed {
             let pending = self.pending_write_count();
             suffix.push_str(&format!(" | {pending} pending, press q again to quit"));
@@ -76,15 +132,47 @@ impl App {
 
         let suffix_span = Span::styled(suffix, Style::default().fg(Color::Yellow));
 
-        let left_bar = Line::from(vec![mode_span, file_info, suffix_span]);
+        // Outer headings give way first when the path is long.
+        let mut crumb = self.breadcrumb.path.as_str();
+        let budget = usize::from(area.width / 3);
+        while crumb.chars().count() > budget {
+            match crumb.split_once(" > ") {
+                Some((_, inner)) => crumb = inner,
+                None => break,
+            }
+        }
+        let crumb_span = Span::styled(
+            if crumb.is_empty() {
+                String::new()
+            } else if crumb.len() < self.breadcrumb.path.len() {
+                format!("… > {crumb} ")
+            } else {
+                format!("{crumb} ")
+            },
+            Style::default().fg(Color::Rgb(120, 120, 150)),
+        );
+
+        let left_bar = Line::from(vec![mode_span, file_info, crumb_span, suffix_span]);
 
-        let right_spans = vec![
-            Span::styled(
-                " MD ",
-                Style::default()
-                    .bg(Color::Rgb(30, 30, 45))
-                    .fg(Color::Rgb(150, 150, 170)),
-            ),
+        let mut right_spans = vec![Span::styled(
+            " MD ",
+            Style::default()
+                .bg(Color::Rgb(30, 30, 45))
+                .fg(Color::Rgb(150, 150, 170)),
+        )];
+        let (tasks_done, tasks_total) = self.buffer.task_progress();
+        if tasks_total > 0 {
+            let color = if tasks_done == tasks_total {
+                Color::Green
+            } else {
+                Color::Yellow
+            };
+            right_spans.push(Span::styled(
+                format!("  {tasks_done}/{tasks_total} tasks "),
+                Style::default().fg(color),
+            ));
+        }
+        right_spans.extend([
             Span::styled(
                 format!("  {} w ", self.buffer.word_count()),
                 Style::default().fg(Color::Cyan),
@@ -100,13 +188,14 @@ impl App {
                     .fg(Color::Black)
                     .add_modifier(Modifier::BOLD),
             ),
-        ];
+        ]);
 
         let right_bar = Line::from(right_spans).alignment(Alignment::Right);
+        let right_width = (right_bar.width() as u16).max(30);
 
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(0), Constraint::Length(30)])
+            .constraints([Constraint::Min(0), Constraint::Length(right_width)])
             .split(area);
 
         frame.render_widget(
@@ -118,9 +207,23 @@ impl App {
             chunks[1],
         );
     }
-    pub(crate) fn render_tab_bar(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+    fn workflow_badge_style(&self, state: &str) -> Style {
+        let states = &self.config.workflow.states;
+        let position = states
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(state));
+        let color = match position {
+            Some(idx) if idx + 1 == states.len() => Color::Green,
+            Some(0) => Color::DarkGray,
+            Some(_) => Color::Yellow,
+            None => Color::Red,
+        };
+        Style::default().fg(color).add_modifier(Modifier::BOLD)
+    }
+    pub(crate) fn render_tab_bar(&mut self, frame: &mut Frame, area: ratatui::layout::Rect) {
         let active_path = self.buffer.path.as_ref();
         let mut spans = Vec::new();
+        let mut badge = None;
 
         for tab_path in &self.open_tabs {
             let name = tab_path
@@ -129,6 +232,12 @@ impl App {
                 .unwrap_or_else(|| "[note]".to_string());
 
             let is_active = active_path.is_some_and(|p| same_file_path(p, tab_path));
+            let pinned = self
+                .file_tree
+                .pinned
+                .iter()
+                .any(|pin| same_file_path(pin, tab_path));
+            let name = if pinned { format!("★ {name}") } else { name };
             let mut label = format!(" {name} ");
             if is_active && self.buffer.dirty {
                 label = format!(" {name} ● ");
@@ -144,7 +253,42 @@ impl App {
             };
 
             spans.push(Span::styled(label, style));
+
+            let tab_buffer = if is_active {
+                Some(&self.buffer)
+            } else {
+                self.inactive_buffers.get(tab_path)
+            };
+            if let Some(state) =
+                tab_buffer.and_then(|buf| buffer_workflow_state(buf, &self.config.workflow))
+            {
+                spans.push(Span::styled(
+                    format!("[{state}] "),
+                    self.workflow_badge_style(&state)
+                        .bg(style.bg.unwrap_or_default()),
+                ));
+            }
+
+            if let Some((_, count)) = self.backlink_count.as_ref().filter(|_| is_active)
+                && *count > 0
+            {
+                let x: usize = spans.iter().map(Span::width).sum();
+                let label = format!("↩ {count} ");
+                badge = Some(ratatui::layout::Rect {
+                    x: area.x.saturating_add(x as u16),
+                    y: area.y,
+                    width: label.chars().count() as u16,
+                    height: 1,
+                });
+                spans.push(Span::styled(
+                    label,
+                    Style::default()
+                        .bg(style.bg.unwrap_or_default())
+                        .fg(Color::Rgb(120, 120, 150)),
+                ));
+            }
         }
+        self.backlink_badge = badge.map(|rect| rect.intersection(area));
 
         spans.push(Span::styled(
             "  [Space] Leader ",
@@ -0,0 +1,66 @@
+use crate::app::{App, centered_rect};
+use crate::update::note_ops::rel_display;
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_tasks(&self, frame: &mut Frame) {
+        let Some(view) = self.tasks_view.as_ref() else {
+            return;
+        };
+        let area = centered_rect(70, 60, frame.area());
+        let rows = usize::from(area.height.saturating_sub(2)).max(1);
+        let first = view.selected.saturating_sub(rows - 1);
+        let root = self.config.vault_path();
+
+        let lines: Vec<Line> = view
+            .items
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows)
+            .map(|(idx, item)| {
+                let (place, mut text) = if idx == view.selected {
+                    let style = Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD);
+                    (style, style)
+                } else {
+                    (Style::default().fg(Color::Cyan), Style::default())
+                };
+                if item.done {
+                    text = text.add_modifier(Modifier::CROSSED_OUT);
+                }
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {}:{}  ", rel_display(&root, &item.path), item.line),
+                        place,
+                    ),
+                    Span::styled(item.text.trim_start().to_string(), text),
+                ])
+            })
+            .collect();
+
+        let title = match view.filter.as_str() {
+            "" => " Tasks ".to_string(),
+            filter => format!(" Tasks: {filter} "),
+        };
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(title)
+                .title_bottom(format!(
+                    " {} | Space toggle, Enter open, Esc close ",
+                    self.tasks_progress()
+                ))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::app::{App, centered_rect};
+use crate::model::yank_history;
+use ratatui::{
+    Frame,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+impl App {
+    pub(crate) fn render_yank_picker(&self, frame: &mut Frame) {
+        let area = centered_rect(60, 50, frame.area());
+        let width = usize::from(area.width.saturating_sub(8));
+        let rows = usize::from(area.height.saturating_sub(4)).max(1);
+        let first = self.yank_selected.saturating_sub(rows - 1);
+
+        let mut lines: Vec<Line> = self
+            .yank_history
+            .entries()
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows)
+            .map(|(idx, text)| {
+                let style = if idx == self.yank_selected {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                let key = if idx < 9 {
+                    (idx + 1).to_string()
+                } else {
+                    " ".to_string()
+                };
+                Line::from(Span::styled(
+                    format!("  {key}  {}", yank_history::preview(text, width)),
+                    style,
+                ))
+            })
+            .collect();
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            "  1-9/Enter paste after cursor, Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        frame.render_widget(Clear, area);
+        let widget = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Yanks ")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Rgb(15, 15, 24))),
+        );
+        frame.render_widget(widget, area);
+    }
+}
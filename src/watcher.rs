@@ -0,0 +1,124 @@
+//! File watchers: a thread that turns notify events into messages until its
+//! handle is dropped. The vault watcher sends `Msg::FileChanged` /
+//! `Msg::FileRenamed`; the plugin watcher sends `Msg::PluginFilesChanged`.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::model::fs_events::{Change, RenamePairing};
+use crate::msg::Msg;
+
+/// Keeps the watcher thread alive; it stops once this is dropped.
+pub struct FileWatcher {
+    _alive: mpsc::Sender<()>,
+}
+
+/// Watch `vault_path` recursively. Create/modify/remove events become
+/// `FileChanged`, and both halves of a rename become one `FileRenamed`.
+pub fn spawn(vault_path: PathBuf, tx: mpsc::Sender<Msg>) -> FileWatcher {
+    // A rename's old path is held back until its new path arrives; one
+    // that never does (moved out of the vault) is a removal.
+    let mut pairing = RenamePairing::default();
+    watch(vec![vault_path], tx, move |event| {
+        let changes = match event {
+            Some(event) => pairing.push(event, |path| path.exists()),
+            None => pairing.flush(),
+        };
+        changes
+            .into_iter()
+            .map(|change| match change {
+                Change::Changed(path) => Msg::FileChanged(path),
+                Change::Renamed { from, to } => Msg::FileRenamed(from, to),
+            })
+            .collect()
+    })
+}
+
+/// Watch each plugin directory. Changes are held until the files have been
+/// quiet for a moment, so a rebuild that writes many files reloads once;
+/// then each directory that changed is sent in one `PluginFilesChanged`.
+pub fn spawn_plugins(roots: Vec<PathBuf>, tx: mpsc::Sender<Msg>) -> FileWatcher {
+    // Repository plugins are cloned by `:plugins.sync`, so may not exist yet.
+    let roots: Vec<PathBuf> = roots.into_iter().filter(|root| root.is_dir()).collect();
+    let watched = roots.clone();
+    let mut dirty = BTreeSet::new();
+    watch(roots, tx, move |event| match event {
+        // Loading a plugin reads its files; only writes count.
+        Some(event) if event.kind.is_access() => Vec::new(),
+        Some(event) => {
+            let changed = event.paths.iter().filter_map(|path| {
+                watched.iter().find(|root| {
+                    path.strip_prefix(root)
+                        .is_ok_and(|rel| !rel.starts_with(".git"))
+                })
+            });
+            dirty.extend(changed.cloned());
+            Vec::new()
+        }
+        None if dirty.is_empty() => Vec::new(),
+        None => vec![Msg::PluginFilesChanged(
+            std::mem::take(&mut dirty).into_iter().collect(),
+        )],
+    })
+}
+
+/// Watch `paths` recursively on a thread, passing each event to `to_msgs`,
+/// and `None` after 200 ms without one; what it returns is sent on `tx`.
+fn watch(
+    paths: Vec<PathBuf>,
+    tx: mpsc::Sender<Msg>,
+    mut to_msgs: impl FnMut(Option<&notify::Event>) -> Vec<Msg> + Send + 'static,
+) -> FileWatcher {
+    let (alive, alive_rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        let (raw_tx, raw_rx) = mpsc::channel();
+        let mut watcher: RecommendedWatcher =
+            match notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    let _ = raw_tx.send(event);
+                }
+                Err(err) => {
+                    tracing::warn!("file watcher error: {err}");
+                }
+            }) {
+                Ok(w) => w,
+                Err(err) => {
+                    tracing::warn!("failed to initialize file watcher: {err}");
+                    return;
+                }
+            };
+
+        let mut watching = false;
+        for path in &paths {
+            match watcher.watch(path, RecursiveMode::Recursive) {
+                Ok(()) => watching = true,
+                Err(err) => tracing::warn!("failed to watch {}: {err}", path.display()),
+            }
+        }
+        if !watching {
+            return;
+        }
+
+        loop {
+            if let Err(mpsc::TryRecvError::Disconnected) = alive_rx.try_recv() {
+                return;
+            }
+            let msgs = match raw_rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(event) => to_msgs(Some(&event)),
+                Err(mpsc::RecvTimeoutError::Timeout) => to_msgs(None),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            };
+            for msg in msgs {
+                if tx.send(msg).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    FileWatcher { _alive: alive }
+}